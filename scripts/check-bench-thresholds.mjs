@@ -0,0 +1,55 @@
+import fs from "node:fs";
+import path from "node:path";
+
+// Compares criterion mean estimates against src-tauri/benches/thresholds.json (nanoseconds).
+// Run after `cargo bench --features bench`; exits non-zero when any benchmark regresses past
+// its threshold or a thresholded benchmark produced no estimate.
+
+const root = process.cwd();
+const thresholdsPath = path.join(root, "src-tauri", "benches", "thresholds.json");
+const criterionDir = path.join(root, "src-tauri", "target", "criterion");
+const allowMissing = process.argv.includes("--allow-missing");
+
+function readMeanNanos(benchId) {
+  const estimatesPath = path.join(criterionDir, ...benchId.split("/"), "new", "estimates.json");
+  try {
+    const estimates = JSON.parse(fs.readFileSync(estimatesPath, "utf8"));
+    const value = estimates?.mean?.point_estimate;
+    return typeof value === "number" ? value : null;
+  } catch {
+    return null;
+  }
+}
+
+function formatNanos(value) {
+  if (value >= 1e9) return `${(value / 1e9).toFixed(2)}s`;
+  if (value >= 1e6) return `${(value / 1e6).toFixed(2)}ms`;
+  if (value >= 1e3) return `${(value / 1e3).toFixed(2)}µs`;
+  return `${value.toFixed(0)}ns`;
+}
+
+const thresholds = JSON.parse(fs.readFileSync(thresholdsPath, "utf8"));
+let failures = 0;
+
+for (const [benchId, limit] of Object.entries(thresholds)) {
+  const mean = readMeanNanos(benchId);
+  if (mean === null) {
+    if (allowMissing) {
+      console.log(`- ${benchId}: no estimate (skipped)`);
+      continue;
+    }
+    console.log(`✗ ${benchId}: no estimate found`);
+    failures += 1;
+    continue;
+  }
+  const ok = mean <= limit;
+  console.log(
+    `${ok ? "✓" : "✗"} ${benchId}: ${formatNanos(mean)} (limit ${formatNanos(limit)})`,
+  );
+  if (!ok) failures += 1;
+}
+
+if (failures > 0) {
+  console.error(`${failures} benchmark(s) exceeded thresholds.`);
+  process.exit(1);
+}
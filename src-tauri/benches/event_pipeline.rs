@@ -0,0 +1,107 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use codex_monitor_lib::bench_support;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn bench_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("bench runtime")
+}
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "codex-monitor-bench-{prefix}-{}",
+        uuid::Uuid::new_v4()
+    ));
+    fs::create_dir_all(&dir).expect("create bench dir");
+    dir
+}
+
+/// Writes `sessions` transcripts of `turns` turns each, shaped like Codex session JSONL.
+fn write_synthetic_sessions(sessions: usize, turns: usize) -> PathBuf {
+    let root = temp_dir("sessions");
+    let day_dir = bench_support::usage_day_dir(&root);
+    fs::create_dir_all(&day_dir).expect("create day dir");
+    let timestamp = chrono::Utc::now();
+    for session in 0..sessions {
+        let path = day_dir.join(format!("rollout-{session}.jsonl"));
+        let mut file = fs::File::create(&path).expect("create transcript");
+        writeln!(
+            file,
+            r#"{{"type":"session_meta","payload":{{"cwd":"/tmp/bench-{session}"}}}}"#
+        )
+        .expect("write meta");
+        writeln!(
+            file,
+            r#"{{"type":"turn_context","payload":{{"model":"gpt-5-codex"}}}}"#
+        )
+        .expect("write context");
+        for turn in 0..turns {
+            let at = (timestamp + chrono::Duration::seconds(turn as i64)).to_rfc3339();
+            writeln!(
+                file,
+                r#"{{"timestamp":"{at}","type":"event_msg","payload":{{"type":"agent_message","message":"turn {turn} finished"}}}}"#
+            )
+            .expect("write message");
+            writeln!(
+                file,
+                r#"{{"timestamp":"{at}","type":"event_msg","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{input},"cached_input_tokens":{cached},"output_tokens":{output}}}}}}}}}"#,
+                input = (turn + 1) * 1200,
+                cached = (turn + 1) * 400,
+                output = (turn + 1) * 300,
+            )
+            .expect("write token count");
+        }
+    }
+    root
+}
+
+fn bench_event_fan_out(c: &mut Criterion) {
+    let runtime = bench_runtime();
+    let mut group = c.benchmark_group("event_fan_out");
+    let events = 2_000usize;
+    for subscribers in [1usize, 4, 16] {
+        group.throughput(Throughput::Elements((events * subscribers) as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(subscribers),
+            &subscribers,
+            |b, &subscribers| {
+                b.iter(|| {
+                    let delivered = bench_support::fan_out_app_server_events(
+                        &runtime,
+                        subscribers,
+                        events,
+                        256,
+                    );
+                    assert_eq!(delivered, events * subscribers);
+                    delivered
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_transcript_parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transcript_parsing");
+    group.sample_size(20);
+    for (sessions, turns) in [(10usize, 200usize), (50, 400)] {
+        let root = write_synthetic_sessions(sessions, turns);
+        group.throughput(Throughput::Elements((sessions * turns * 2) as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{sessions}x{turns}")),
+            |b| {
+                b.iter(|| bench_support::scan_usage_sessions(&root, 1).expect("scan sessions"));
+            },
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_event_fan_out, bench_transcript_parsing);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use codex_monitor_lib::bench_support;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use git2::{Repository, Signature};
+
+/// Creates a repo with `files` committed files of `lines` lines each, then rewrites every
+/// fourth line in the working tree so each file carries a sizeable unstaged diff.
+fn build_synthetic_repo(files: usize, lines: usize) -> PathBuf {
+    let root =
+        std::env::temp_dir().join(format!("codex-monitor-bench-repo-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(root.join("src")).expect("create repo dir");
+    let repo = Repository::init(&root).expect("init repo");
+
+    let mut index = repo.index().expect("index");
+    for file in 0..files {
+        let relative = format!("src/module_{file}.rs");
+        let content = (0..lines)
+            .map(|line| format!("pub fn item_{file}_{line}() -> usize {{ {line} }}\n"))
+            .collect::<String>();
+        fs::write(root.join(&relative), content).expect("write file");
+        index.add_path(Path::new(&relative)).expect("stage file");
+    }
+    let tree_id = index.write_tree().expect("write tree");
+    let tree = repo.find_tree(tree_id).expect("find tree");
+    let sig = Signature::now("Bench", "bench@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &sig, &sig, "baseline", &tree, &[])
+        .expect("commit");
+
+    for file in 0..files {
+        let content = (0..lines)
+            .map(|line| {
+                if line % 4 == 0 {
+                    format!("pub fn item_{file}_{line}() -> usize {{ {line} * 2 }}\n")
+                } else {
+                    format!("pub fn item_{file}_{line}() -> usize {{ {line} }}\n")
+                }
+            })
+            .collect::<String>();
+        fs::write(root.join(format!("src/module_{file}.rs")), content).expect("rewrite file");
+    }
+    root
+}
+
+fn bench_large_diffs(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("bench runtime");
+    let mut group = c.benchmark_group("large_diff");
+    group.sample_size(10);
+    for (files, lines) in [(50usize, 400usize), (200, 1_000)] {
+        let root = build_synthetic_repo(files, lines);
+        let label = format!("{files}x{lines}");
+        group.bench_function(BenchmarkId::new("structured", &label), |b| {
+            b.iter(|| bench_support::git_diffs_for_repo(&runtime, &root).expect("git diffs"));
+        });
+        group.bench_function(BenchmarkId::new("combined", &label), |b| {
+            b.iter(|| bench_support::collect_workspace_diff(&root).expect("workspace diff"));
+        });
+        let _ = fs::remove_dir_all(&root);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_large_diffs);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use codex_monitor_lib::bench_support;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn bench_state_contention(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .expect("bench runtime");
+    let storage_dir = std::env::temp_dir().join(format!(
+        "codex-monitor-bench-state-{}",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::create_dir_all(&storage_dir).expect("create storage dir");
+    let storage_path = storage_dir.join("workspaces.json");
+
+    let mut group = c.benchmark_group("state_contention");
+    group.sample_size(20);
+    for (readers, writers) in [(8usize, 1usize), (32, 4)] {
+        let label = format!("50x{readers}r{writers}w");
+        group.bench_function(BenchmarkId::from_parameter(&label), |b| {
+            b.iter(|| {
+                bench_support::workspace_state_contention(
+                    &runtime,
+                    &storage_path,
+                    50,
+                    readers,
+                    writers,
+                )
+            });
+        });
+    }
+    group.finish();
+    let _ = std::fs::remove_dir_all(&storage_dir);
+}
+
+criterion_group!(benches, bench_state_contention);
+criterion_main!(benches);
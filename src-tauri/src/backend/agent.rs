@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use super::app_server::{build_codex_command_with_bin, build_initialize_params};
+use super::cli_agent::StreamJsonCliBackend;
+use crate::types::{AgentBackendKind, WorkspaceEntry};
+
+/// How a `WorkspaceSession` should deliver an outgoing app-server request.
+pub(crate) enum AgentOutbound {
+    /// Send as a request and hand the agent's response back to the caller.
+    Request { method: String, params: Value },
+    /// Send as a request but answer the caller immediately with `result`.
+    /// The agent's eventual response is routed through `translate_incoming`.
+    Detached {
+        method: String,
+        params: Value,
+        result: Value,
+    },
+    /// Send as a notification and answer the caller with `result`.
+    Notification {
+        method: String,
+        params: Value,
+        result: Value,
+    },
+    /// Write `message` to the agent verbatim and answer the caller with
+    /// `result`. Used by agents that do not speak JSON-RPC on stdin.
+    Message { message: Value, result: Value },
+    /// Answer the caller without contacting the agent.
+    Local { result: Value },
+}
+
+/// Protocol adapter for the agent process behind a `WorkspaceSession`.
+///
+/// The session owns the child process, request ids and event routing. A
+/// backend decides how the process is launched and maps traffic between the
+/// agent's wire protocol and the Codex app-server shape the UI consumes.
+pub(crate) trait AgentBackend: Send + Sync {
+    fn kind(&self) -> AgentBackendKind;
+
+    fn build_command(&self) -> Result<Command, String>;
+
+    /// Handshake request sent right after spawn, if the protocol has one.
+    fn initialize_request(&self, client_version: &str) -> Option<(&'static str, Value)>;
+
+    fn initialized_notification(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn initialize_timeout_message(&self) -> String {
+        "Agent did not respond to initialize.".to_string()
+    }
+
+    fn translate_request(
+        &self,
+        _id: u64,
+        method: &str,
+        params: Value,
+    ) -> Result<AgentOutbound, String> {
+        Ok(AgentOutbound::Request {
+            method: method.to_string(),
+            params,
+        })
+    }
+
+    /// Maps one message read from the agent to zero or more app-server messages.
+    /// `request_method` is the app-server method a response is answering.
+    fn translate_incoming(&self, message: Value, _request_method: Option<&str>) -> Vec<Value> {
+        vec![message]
+    }
+
+    /// Maps the UI's answer to an agent-initiated request.
+    fn translate_server_response(&self, _id: &Value, result: Value) -> Value {
+        result
+    }
+}
+
+pub(crate) fn resolve_agent_backend(
+    entry: &WorkspaceEntry,
+    codex_bin: Option<String>,
+    codex_args: Option<String>,
+) -> Result<Arc<dyn AgentBackend>, String> {
+    match entry.settings.agent_backend.unwrap_or_default() {
+        AgentBackendKind::Codex => Ok(Arc::new(CodexAppServerBackend::new(codex_bin, codex_args))),
+        AgentBackendKind::Acp => {
+            let command = required_agent_command(entry, "ACP")?;
+            Ok(Arc::new(AcpBackend::new(command)))
+        }
+        AgentBackendKind::Cli => {
+            let command = required_agent_command(entry, "CLI")?;
+            Ok(Arc::new(StreamJsonCliBackend::new(command)))
+        }
+    }
+}
+
+fn required_agent_command(entry: &WorkspaceEntry, label: &str) -> Result<String, String> {
+    entry
+        .settings
+        .agent_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            format!("The {label} agent backend requires an agent command in workspace settings.")
+        })
+}
+
+/// Splits a user-supplied agent command line into a spawnable command.
+pub(super) fn build_agent_command(command_line: &str) -> Result<Command, String> {
+    let mut parts = shell_words::split(command_line)
+        .map_err(|err| format!("Invalid agent command: {err}"))?
+        .into_iter()
+        .filter(|part| !part.is_empty());
+    let program = parts
+        .next()
+        .ok_or_else(|| "Agent command is empty.".to_string())?;
+    build_codex_command_with_bin(Some(program), None, parts.collect())
+}
+
+#[cfg(test)]
+pub(crate) fn default_agent_backend() -> Arc<dyn AgentBackend> {
+    Arc::new(CodexAppServerBackend::new(None, None))
+}
+
+pub(crate) struct CodexAppServerBackend {
+    codex_bin: Option<String>,
+    codex_args: Option<String>,
+}
+
+impl CodexAppServerBackend {
+    pub(crate) fn new(codex_bin: Option<String>, codex_args: Option<String>) -> Self {
+        Self {
+            codex_bin,
+            codex_args,
+        }
+    }
+}
+
+impl AgentBackend for CodexAppServerBackend {
+    fn kind(&self) -> AgentBackendKind {
+        AgentBackendKind::Codex
+    }
+
+    fn build_command(&self) -> Result<Command, String> {
+        build_codex_command_with_bin(
+            self.codex_bin.clone(),
+            self.codex_args.as_deref(),
+            vec!["app-server".to_string()],
+        )
+    }
+
+    fn initialize_request(&self, client_version: &str) -> Option<(&'static str, Value)> {
+        Some(("initialize", build_initialize_params(client_version)))
+    }
+
+    fn initialized_notification(&self) -> Option<&'static str> {
+        Some("initialized")
+    }
+
+    fn initialize_timeout_message(&self) -> String {
+        "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal."
+            .to_string()
+    }
+}
+
+const ACP_PROTOCOL_VERSION: u64 = 1;
+
+#[derive(Default)]
+struct AcpState {
+    next_turn: u64,
+    /// ACP session id -> synthetic turn id of the prompt in flight.
+    active_turns: HashMap<String, String>,
+    /// JSON-RPC id of a `session/prompt` request -> (session id, turn id).
+    pending_prompts: HashMap<u64, (String, String)>,
+    /// JSON-RPC id of a `session/request_permission` request -> offered options.
+    permission_options: HashMap<String, Vec<Value>>,
+}
+
+/// Experimental client for agents speaking the Agent Client Protocol over
+/// stdio. ACP sessions map to threads and prompts map to turns; streamed
+/// `session/update` notifications are rewritten into app-server deltas.
+pub(crate) struct AcpBackend {
+    command: String,
+    state: Mutex<AcpState>,
+}
+
+impl AcpBackend {
+    pub(crate) fn new(command: String) -> Self {
+        Self {
+            command,
+            state: Mutex::new(AcpState::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, AcpState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn active_turn(&self, session_id: &str) -> Option<String> {
+        self.state().active_turns.get(session_id).cloned()
+    }
+
+    fn translate_session_update(&self, params: &Value) -> Option<Value> {
+        let session_id = params.get("sessionId").and_then(Value::as_str)?;
+        let update = params.get("update")?;
+        let turn_id = self.active_turn(session_id).unwrap_or_default();
+        let text = update
+            .get("content")
+            .filter(|content| content.get("type").and_then(Value::as_str) == Some("text"))
+            .and_then(|content| content.get("text"))
+            .and_then(Value::as_str);
+        let translated = match (update.get("sessionUpdate").and_then(Value::as_str), text) {
+            (Some("agent_message_chunk"), Some(text)) => json!({
+                "method": "item/agentMessage/delta",
+                "params": {
+                    "threadId": session_id,
+                    "turnId": turn_id,
+                    "itemId": format!("{turn_id}-message"),
+                    "delta": text,
+                }
+            }),
+            (Some("agent_thought_chunk"), Some(text)) => json!({
+                "method": "item/reasoning/textDelta",
+                "params": {
+                    "threadId": session_id,
+                    "turnId": turn_id,
+                    "itemId": format!("{turn_id}-reasoning"),
+                    "delta": text,
+                }
+            }),
+            _ => json!({
+                "method": "acp/sessionUpdate",
+                "params": {
+                    "threadId": session_id,
+                    "turnId": turn_id,
+                    "update": update,
+                }
+            }),
+        };
+        Some(translated)
+    }
+
+    fn translate_prompt_response(&self, id: u64, message: &Value) -> Option<Value> {
+        let (session_id, turn_id) = {
+            let mut state = self.state();
+            let (session_id, turn_id) = state.pending_prompts.remove(&id)?;
+            if state.active_turns.get(&session_id) == Some(&turn_id) {
+                state.active_turns.remove(&session_id);
+            }
+            (session_id, turn_id)
+        };
+        let mut turn = json!({ "id": turn_id });
+        if let Some(error) = message.get("error") {
+            turn["status"] = json!("failed");
+            turn["error"] = json!({
+                "message": error.get("message").and_then(Value::as_str).unwrap_or("ACP prompt failed"),
+            });
+        } else {
+            let stop_reason = message
+                .get("result")
+                .and_then(|result| result.get("stopReason"))
+                .and_then(Value::as_str);
+            let status = if stop_reason == Some("cancelled") {
+                "interrupted"
+            } else {
+                "completed"
+            };
+            turn["status"] = json!(status);
+        }
+        Some(json!({
+            "method": "turn/completed",
+            "params": { "threadId": session_id, "turn": turn }
+        }))
+    }
+
+    fn translate_permission_request(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?;
+        let params = message.get("params")?;
+        let session_id = params.get("sessionId").and_then(Value::as_str)?;
+        let tool_call = params.get("toolCall").cloned().unwrap_or(Value::Null);
+        let options = params
+            .get("options")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        self.state()
+            .permission_options
+            .insert(id.to_string(), options);
+        let title = tool_call
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("Agent tool call");
+        Some(json!({
+            "id": id,
+            "method": "item/commandExecution/requestApproval",
+            "params": {
+                "threadId": session_id,
+                "turnId": self.active_turn(session_id).unwrap_or_default(),
+                "itemId": tool_call.get("toolCallId").cloned().unwrap_or(Value::Null),
+                "command": title,
+                "reason": title,
+            }
+        }))
+    }
+}
+
+fn acp_prompt_from_turn_input(params: &Value) -> Vec<Value> {
+    let Some(items) = params.get("input").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("text") => Some(json!({
+                "type": "text",
+                "text": item.get("text").and_then(Value::as_str).unwrap_or_default(),
+            })),
+            Some("image") => {
+                let url = item.get("url").and_then(Value::as_str)?;
+                let (header, data) = url.strip_prefix("data:")?.split_once(";base64,")?;
+                Some(json!({ "type": "image", "mimeType": header, "data": data }))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn required_thread_id(params: &Value) -> Result<String, String> {
+    params
+        .get("threadId")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "missing threadId".to_string())
+}
+
+impl AgentBackend for AcpBackend {
+    fn kind(&self) -> AgentBackendKind {
+        AgentBackendKind::Acp
+    }
+
+    fn build_command(&self) -> Result<Command, String> {
+        build_agent_command(&self.command)
+    }
+
+    fn initialize_request(&self, _client_version: &str) -> Option<(&'static str, Value)> {
+        Some((
+            "initialize",
+            json!({
+                "protocolVersion": ACP_PROTOCOL_VERSION,
+                "clientCapabilities": {
+                    "fs": { "readTextFile": false, "writeTextFile": false },
+                    "terminal": false
+                }
+            }),
+        ))
+    }
+
+    fn initialize_timeout_message(&self) -> String {
+        format!(
+            "ACP agent did not respond to initialize. Check that `{}` starts an ACP agent over stdio.",
+            self.command
+        )
+    }
+
+    fn translate_request(
+        &self,
+        id: u64,
+        method: &str,
+        params: Value,
+    ) -> Result<AgentOutbound, String> {
+        match method {
+            "initialize" => Ok(AgentOutbound::Request {
+                method: method.to_string(),
+                params,
+            }),
+            "thread/start" => Ok(AgentOutbound::Request {
+                method: "session/new".to_string(),
+                params: json!({
+                    "cwd": params.get("cwd").cloned().unwrap_or(Value::Null),
+                    "mcpServers": [],
+                }),
+            }),
+            // ACP sessions live as long as the agent process, so there is no
+            // history to list and resuming a known session is a no-op.
+            "thread/list" => Ok(AgentOutbound::Local {
+                result: json!({ "data": [], "nextCursor": null }),
+            }),
+            "thread/resume" => {
+                let thread_id = required_thread_id(&params)?;
+                Ok(AgentOutbound::Local {
+                    result: json!({ "thread": { "id": thread_id, "turns": [] } }),
+                })
+            }
+            "turn/start" => {
+                let session_id = required_thread_id(&params)?;
+                let turn_id = {
+                    let mut state = self.state();
+                    state.next_turn += 1;
+                    let turn_id = format!("acp-turn-{}", state.next_turn);
+                    state
+                        .active_turns
+                        .insert(session_id.clone(), turn_id.clone());
+                    state
+                        .pending_prompts
+                        .insert(id, (session_id.clone(), turn_id.clone()));
+                    turn_id
+                };
+                Ok(AgentOutbound::Detached {
+                    method: "session/prompt".to_string(),
+                    params: json!({
+                        "sessionId": session_id,
+                        "prompt": acp_prompt_from_turn_input(&params),
+                    }),
+                    result: json!({
+                        "turn": { "id": turn_id, "status": "inProgress", "items": [] }
+                    }),
+                })
+            }
+            "turn/interrupt" => Ok(AgentOutbound::Notification {
+                method: "session/cancel".to_string(),
+                params: json!({ "sessionId": required_thread_id(&params)? }),
+                result: json!({}),
+            }),
+            _ => Err(format!(
+                "`{method}` is not supported by the ACP agent backend"
+            )),
+        }
+    }
+
+    fn translate_incoming(&self, message: Value, request_method: Option<&str>) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str);
+        let translated = match (method, request_method) {
+            (Some("session/update"), _) => message
+                .get("params")
+                .and_then(|params| self.translate_session_update(params)),
+            (Some("session/request_permission"), _) => self.translate_permission_request(&message),
+            (None, Some("thread/start")) => message
+                .get("result")
+                .and_then(|result| result.get("sessionId"))
+                .map(|session_id| {
+                    json!({
+                        "id": message.get("id").cloned().unwrap_or(Value::Null),
+                        "result": { "thread": { "id": session_id, "preview": "" } }
+                    })
+                }),
+            (None, Some("turn/start")) => message
+                .get("id")
+                .and_then(Value::as_u64)
+                .and_then(|id| self.translate_prompt_response(id, &message)),
+            _ => None,
+        };
+        vec![translated.unwrap_or(message)]
+    }
+
+    fn translate_server_response(&self, id: &Value, result: Value) -> Value {
+        let Some(options) = self.state().permission_options.remove(&id.to_string()) else {
+            return result;
+        };
+        let accepted = result.get("decision").and_then(Value::as_str) == Some("accept");
+        let preferred_kinds: &[&str] = if accepted {
+            &["allow_once", "allow_always"]
+        } else {
+            &["reject_once", "reject_always"]
+        };
+        let option_id = preferred_kinds.iter().find_map(|kind| {
+            options
+                .iter()
+                .find(|option| option.get("kind").and_then(Value::as_str) == Some(kind))
+                .and_then(|option| option.get("optionId").cloned())
+        });
+        match option_id {
+            Some(option_id) => json!({
+                "outcome": { "outcome": "selected", "optionId": option_id }
+            }),
+            None => json!({ "outcome": { "outcome": "cancelled" } }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AcpBackend, AgentBackend, AgentOutbound};
+    use serde_json::json;
+
+    #[test]
+    fn acp_turn_start_is_detached_and_completes_from_prompt_response() {
+        let backend = AcpBackend::new("agent --acp".to_string());
+        let outbound = backend
+            .translate_request(
+                7,
+                "turn/start",
+                json!({
+                    "threadId": "sess-1",
+                    "input": [{ "type": "text", "text": "hello" }]
+                }),
+            )
+            .expect("translate turn/start");
+        let AgentOutbound::Detached {
+            method,
+            params,
+            result,
+        } = outbound
+        else {
+            panic!("turn/start should be detached");
+        };
+        assert_eq!(method, "session/prompt");
+        assert_eq!(params["prompt"][0]["text"], "hello");
+        let turn_id = result["turn"]["id"].as_str().expect("turn id").to_string();
+
+        let delta = backend.translate_incoming(
+            json!({
+                "method": "session/update",
+                "params": {
+                    "sessionId": "sess-1",
+                    "update": {
+                        "sessionUpdate": "agent_message_chunk",
+                        "content": { "type": "text", "text": "hi" }
+                    }
+                }
+            }),
+            None,
+        );
+        assert_eq!(delta[0]["method"], "item/agentMessage/delta");
+        assert_eq!(delta[0]["params"]["turnId"], turn_id.as_str());
+        assert_eq!(delta[0]["params"]["delta"], "hi");
+
+        let completed = backend.translate_incoming(
+            json!({ "id": 7, "result": { "stopReason": "end_turn" } }),
+            Some("turn/start"),
+        );
+        assert_eq!(completed[0]["method"], "turn/completed");
+        assert_eq!(completed[0]["params"]["threadId"], "sess-1");
+        assert_eq!(completed[0]["params"]["turn"]["id"], turn_id.as_str());
+        assert_eq!(completed[0]["params"]["turn"]["status"], "completed");
+    }
+
+    #[test]
+    fn acp_permission_request_maps_decision_to_option() {
+        let backend = AcpBackend::new("agent --acp".to_string());
+        let request = backend.translate_incoming(
+            json!({
+                "id": 3,
+                "method": "session/request_permission",
+                "params": {
+                    "sessionId": "sess-1",
+                    "toolCall": { "toolCallId": "call-1", "title": "rm -rf build" },
+                    "options": [
+                        { "optionId": "yes", "kind": "allow_once", "name": "Allow" },
+                        { "optionId": "no", "kind": "reject_once", "name": "Reject" }
+                    ]
+                }
+            }),
+            None,
+        );
+        assert_eq!(
+            request[0]["method"],
+            "item/commandExecution/requestApproval"
+        );
+        assert_eq!(request[0]["params"]["command"], "rm -rf build");
+
+        let response =
+            backend.translate_server_response(&json!(3), json!({ "decision": "decline" }));
+        assert_eq!(response["outcome"]["optionId"], "no");
+        let passthrough =
+            backend.translate_server_response(&json!(3), json!({ "decision": "accept" }));
+        assert_eq!(passthrough, json!({ "decision": "accept" }));
+    }
+
+    #[test]
+    fn acp_rejects_unsupported_methods() {
+        let backend = AcpBackend::new("agent --acp".to_string());
+        let error = backend
+            .translate_request(1, "review/start", json!({}))
+            .err()
+            .expect("review/start should be unsupported");
+        assert!(error.contains("not supported"));
+    }
+}
@@ -4,7 +4,7 @@ use std::env;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
 use std::time::Duration;
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -12,10 +12,18 @@ use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio::time::timeout;
 
+use crate::backend::agent::{resolve_agent_backend, AgentBackend, AgentOutbound};
+use crate::backend::codex_auth::{has_codex_auth, same_home, session_codex_home, sync_auth_json};
 use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol_trace::TraceDirection;
+use crate::backend::quotas::{process_tree_cpu_ms, QuotaBreach};
+use crate::backend::request_timeouts::{RequestTimeouts, RequestWaitError};
+use crate::backend::services::{BackendServices, ReaderHook};
+use crate::backend::session_provenance::{capture_provenance, SessionProvenance};
 use crate::codex::args::parse_codex_args;
+use crate::rules::approval_request_command;
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
-use crate::types::WorkspaceEntry;
+use crate::types::{AgentBackendKind, WorkspaceEntry};
 
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
@@ -416,7 +424,31 @@ pub(crate) struct RequestContext {
     method: String,
 }
 
-fn build_initialize_params(client_version: &str) -> Value {
+/// Clears a request's bookkeeping however the caller stops waiting: a failed
+/// write, a timeout, or the caller being dropped mid-request (for example a
+/// daemon RPC canceled by its client).
+struct PendingRequestGuard<'a> {
+    session: &'a WorkspaceSession,
+    id: u64,
+    armed: bool,
+}
+
+impl PendingRequestGuard<'_> {
+    /// Keeps the bookkeeping, for a request answered later on the wire.
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for PendingRequestGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.session.forget_request(self.id);
+        }
+    }
+}
+
+pub(super) fn build_initialize_params(client_version: &str) -> Value {
     json!({
         "clientInfo": {
             "name": "codex_monitor",
@@ -429,14 +461,14 @@ fn build_initialize_params(client_version: &str) -> Value {
     })
 }
 
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
-
 pub(crate) struct WorkspaceSession {
     pub(crate) codex_args: Option<String>,
     pub(crate) child: Mutex<Child>,
     pub(crate) stdin: Mutex<ChildStdin>,
-    pub(crate) pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
-    pub(crate) request_context: Mutex<HashMap<u64, RequestContext>>,
+    /// Behind std mutexes so a dropped request can clear its entries
+    /// synchronously.
+    pub(crate) pending: StdMutex<HashMap<u64, oneshot::Sender<Value>>>,
+    pub(crate) request_context: StdMutex<HashMap<u64, RequestContext>>,
     pub(crate) thread_workspace: Mutex<HashMap<String, String>>,
     pub(crate) hidden_thread_ids: Mutex<HashSet<String>>,
     pub(crate) next_id: AtomicU64,
@@ -445,9 +477,33 @@ pub(crate) struct WorkspaceSession {
     pub(crate) owner_workspace_id: String,
     pub(crate) workspace_ids: Mutex<HashSet<String>>,
     pub(crate) workspace_roots: Mutex<HashMap<String, String>>,
+    pub(crate) backend: Arc<dyn AgentBackend>,
+    pub(crate) request_timeouts: RequestTimeouts,
+    /// Spawned with workspace-specific env, so never shared with other workspaces.
+    pub(crate) isolated_env: bool,
+    pub(crate) services: Arc<BackendServices>,
 }
 
 impl WorkspaceSession {
+    pub(crate) fn pending_requests(
+        &self,
+    ) -> StdMutexGuard<'_, HashMap<u64, oneshot::Sender<Value>>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn request_contexts(&self) -> StdMutexGuard<'_, HashMap<u64, RequestContext>> {
+        self.request_context
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn forget_request(&self, id: u64) {
+        self.pending_requests().remove(&id);
+        self.request_contexts().remove(&id);
+    }
+
     pub(crate) async fn register_workspace(&self, workspace_id: &str) {
         self.register_workspace_with_path(workspace_id, None).await;
     }
@@ -482,8 +538,9 @@ impl WorkspaceSession {
     }
 
     async fn write_message(&self, value: Value) -> Result<(), String> {
-        let mut stdin = self.stdin.lock().await;
         let mut line = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+        trace_protocol_line(self, TraceDirection::Sent, &line).await;
+        let mut stdin = self.stdin.lock().await;
         line.push('\n');
         stdin
             .write_all(line.as_bytes())
@@ -503,41 +560,72 @@ impl WorkspaceSession {
         params: Value,
     ) -> Result<Value, String> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-        let (tx, rx) = oneshot::channel();
         self.register_workspace(workspace_id).await;
-        self.pending.lock().await.insert(id, tx);
-        self.request_context.lock().await.insert(
+        if let Some(thread_id) = extract_thread_id(&json!({ "params": params.clone() })) {
+            self.thread_workspace
+                .lock()
+                .await
+                .insert(thread_id.clone(), workspace_id.to_string());
+            persist_thread_routes(self, vec![(thread_id, workspace_id.to_string(), None)]).await;
+        }
+        let (wire_method, wire_params, detached_result) =
+            match self.backend.translate_request(id, method, params)? {
+                AgentOutbound::Request { method, params } => (method, params, None),
+                AgentOutbound::Detached {
+                    method,
+                    params,
+                    result,
+                } => (method, params, Some(result)),
+                AgentOutbound::Notification {
+                    method,
+                    params,
+                    result,
+                } => {
+                    self.send_notification(&method, Some(params)).await?;
+                    return Ok(json!({ "id": id, "result": result }));
+                }
+                AgentOutbound::Message { message, result } => {
+                    self.write_message(message).await?;
+                    return Ok(json!({ "id": id, "result": result }));
+                }
+                AgentOutbound::Local { result } => {
+                    return Ok(json!({ "id": id, "result": result }));
+                }
+            };
+        let (tx, rx) = oneshot::channel();
+        if detached_result.is_none() {
+            self.pending_requests().insert(id, tx);
+        }
+        self.request_contexts().insert(
             id,
             RequestContext {
                 workspace_id: workspace_id.to_string(),
                 method: method.to_string(),
             },
         );
-        if let Some(thread_id) = extract_thread_id(&json!({ "params": params.clone() })) {
-            self.thread_workspace
-                .lock()
-                .await
-                .insert(thread_id, workspace_id.to_string());
-        }
-        if let Err(error) = self
-            .write_message(json!({ "id": id, "method": method, "params": params }))
-            .await
-        {
-            self.pending.lock().await.remove(&id);
-            self.request_context.lock().await.remove(&id);
-            return Err(error);
+        // Armed before the write so a caller dropped mid-write cleans up too.
+        let guard = PendingRequestGuard {
+            session: self,
+            id,
+            armed: true,
+        };
+        self.write_message(json!({ "id": id, "method": wire_method, "params": wire_params }))
+            .await?;
+        if let Some(result) = detached_result {
+            guard.disarm();
+            return Ok(json!({ "id": id, "result": result }));
         }
-        match timeout(REQUEST_TIMEOUT, rx).await {
-            Ok(Ok(value)) => Ok(value),
-            Ok(Err(_)) => Err("request canceled".to_string()),
-            Err(_) => {
-                self.pending.lock().await.remove(&id);
-                self.request_context.lock().await.remove(&id);
-                Err(format!(
-                    "request timed out after {} seconds",
-                    REQUEST_TIMEOUT.as_secs()
-                ))
+        let canceled = |_| RequestWaitError::Canceled.to_string();
+        let Some(limit) = self.request_timeouts.resolve(workspace_id, method) else {
+            return rx.await.map_err(canceled);
+        };
+        match timeout(limit, rx).await {
+            Ok(result) => result.map_err(canceled),
+            Err(_) => Err(RequestWaitError::TimedOut {
+                method: method.to_string(),
+                after: limit,
             }
+            .to_string()),
         }
     }
 
@@ -555,11 +643,23 @@ impl WorkspaceSession {
     }
 
     pub(crate) async fn send_response(&self, id: Value, result: Value) -> Result<(), String> {
+        let result = self.backend.translate_server_response(&id, result);
         self.write_message(json!({ "id": id, "result": result }))
             .await
     }
 }
 
+/// Mirrors a stdin/stdout line into the traces of the workspaces this
+/// session serves, when any workspace is being traced.
+async fn trace_protocol_line(session: &WorkspaceSession, direction: TraceDirection, line: &str) {
+    let traces = &session.services.protocol_traces;
+    if !traces.is_active() {
+        return;
+    }
+    let workspace_ids = session.workspace_ids_snapshot().await;
+    traces.record(&workspace_ids, direction, line);
+}
+
 pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
     let mut paths: Vec<PathBuf> = env::var_os("PATH")
         .map(|value| env::split_paths(&value).collect())
@@ -746,31 +846,498 @@ pub(crate) async fn check_codex_installation(
     })
 }
 
+/// Mirrors routing updates into the persisted store, using the workspace root
+/// as the cwd when the caller has none.
+async fn persist_thread_routes(
+    session: &WorkspaceSession,
+    routes: Vec<(String, String, Option<String>)>,
+) {
+    if routes.is_empty() {
+        return;
+    }
+    let workspace_roots = session.workspace_roots.lock().await.clone();
+    let routes = routes
+        .into_iter()
+        .map(|(thread_id, workspace_id, cwd)| {
+            let cwd = cwd.or_else(|| workspace_roots.get(&workspace_id).cloned());
+            (thread_id, workspace_id, cwd)
+        })
+        .collect();
+    session.services.thread_routes.record(routes);
+}
+
+/// Answers command approval requests covered by the CodexMonitor approval
+/// policy so they never reach the UI. Returns `true` when the request was
+/// handled.
+async fn apply_approval_policy<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    workspace_id: &str,
+    value: &Value,
+) -> bool {
+    let Some(request_id) = value.get("id") else {
+        return false;
+    };
+    let is_command_approval = value
+        .get("method")
+        .and_then(Value::as_str)
+        .is_some_and(|method| method.ends_with("commandExecution/requestApproval"));
+    if !is_command_approval {
+        return false;
+    }
+    let params = value.get("params").cloned().unwrap_or(Value::Null);
+    let Some(command) = approval_request_command(&params) else {
+        return false;
+    };
+    let cwd = match params.get("cwd").and_then(Value::as_str) {
+        Some(cwd) => Some(cwd.to_string()),
+        None => session
+            .workspace_roots
+            .lock()
+            .await
+            .get(workspace_id)
+            .cloned(),
+    };
+    let Some(rule) = session
+        .services
+        .approval_policy
+        .evaluate(&command, cwd.as_deref())
+    else {
+        return false;
+    };
+    let decision = rule.decision.response_decision();
+    if let Err(error) = session
+        .send_response(request_id.clone(), json!({ "decision": decision }))
+        .await
+    {
+        eprintln!("approval policy: failed to answer request: {error}");
+        return false;
+    }
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "approvalPolicy/applied",
+            "params": {
+                "threadId": extract_thread_id(value),
+                "requestId": request_id,
+                "command": command,
+                "decision": decision,
+                "ruleId": rule.id,
+            }
+        }),
+    });
+    true
+}
+
+const QUOTA_SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Reports a quota breach and interrupts the workspace's running turns. The
+/// interrupts are spawned because their responses arrive on the reader task
+/// that called this.
+fn enforce_quota_breach<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &E,
+    breach: QuotaBreach,
+) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: breach.workspace_id.clone(),
+        message: json!({
+            "method": "quota/exceeded",
+            "params": {
+                "workspaceId": breach.workspace_id,
+                "reason": breach.reason,
+            }
+        }),
+    });
+    for (thread_id, turn_id) in breach.interrupts {
+        let session = Arc::clone(session);
+        let workspace_id = breach.workspace_id.clone();
+        tokio::spawn(async move {
+            let params = json!({ "threadId": thread_id, "turnId": turn_id });
+            if let Err(error) = session
+                .send_request_for_workspace(&workspace_id, "turn/interrupt", params)
+                .await
+            {
+                eprintln!("quota: failed to interrupt turn: {error}");
+            }
+        });
+    }
+}
+
+/// Samples the agent process tree's CPU time and charges it to the session's
+/// workspaces with a turn running. Stops when the session is dropped.
+async fn sample_session_cpu<E: EventSink>(
+    session: Weak<WorkspaceSession>,
+    pid: u32,
+    event_sink: E,
+) {
+    let mut last_cpu_ms: Option<u64> = None;
+    let mut ticker = tokio::time::interval(QUOTA_SAMPLE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let Some(session) = session.upgrade() else {
+            break;
+        };
+        if !session.services.session_quotas.tracks_cpu() {
+            last_cpu_ms = None;
+            continue;
+        }
+        let Some(cpu_ms) = process_tree_cpu_ms(pid).await else {
+            continue;
+        };
+        let delta = last_cpu_ms.map_or(0, |last| cpu_ms.saturating_sub(last));
+        last_cpu_ms = Some(cpu_ms);
+        let workspace_ids = session.workspace_ids_snapshot().await;
+        for breach in session
+            .services
+            .session_quotas
+            .charge_cpu(&workspace_ids, delta)
+        {
+            enforce_quota_breach(&session, &event_sink, breach);
+        }
+    }
+}
+
+async fn route_incoming_message<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &E,
+    fallback_workspace_id: &str,
+    value: Value,
+) {
+    let maybe_id = value.get("id").and_then(|id| id.as_u64());
+    let has_method = value.get("method").is_some();
+    let has_result_or_error = value.get("result").is_some() || value.get("error").is_some();
+    let method_name = value.get("method").and_then(|method| method.as_str());
+
+    // Check if this event is for a background thread
+    let thread_id = extract_thread_id(&value);
+    let mut request_workspace: Option<String> = None;
+    let mut request_method: Option<String> = None;
+    if let Some(id) = maybe_id {
+        if has_result_or_error {
+            if let Some(context) = session.request_contexts().remove(&id) {
+                request_workspace = Some(context.workspace_id);
+                request_method = Some(context.method);
+            }
+        }
+    }
+
+    if let Some(ref workspace_id) = request_workspace {
+        let mut related_thread_ids = extract_related_thread_ids(&value);
+        if related_thread_ids.is_empty() {
+            related_thread_ids.extend(thread_id.clone());
+        }
+        if !related_thread_ids.is_empty() {
+            let mut thread_workspace = session.thread_workspace.lock().await;
+            for tid in &related_thread_ids {
+                thread_workspace.insert(tid.clone(), workspace_id.clone());
+            }
+            drop(thread_workspace);
+            let routes = related_thread_ids
+                .into_iter()
+                .map(|tid| (tid, workspace_id.clone(), None))
+                .collect();
+            persist_thread_routes(session, routes).await;
+        }
+    }
+    if matches!(request_method.as_deref(), Some("thread/list")) {
+        let thread_entries = extract_thread_entries_from_thread_list_result(&value);
+        if !thread_entries.is_empty() {
+            let workspace_roots = session.workspace_roots.lock().await.clone();
+            let mut hidden_thread_ids = Vec::new();
+            let mut routes = Vec::new();
+            let mut thread_workspace = session.thread_workspace.lock().await;
+            for entry in thread_entries {
+                if entry.is_memory_consolidation {
+                    thread_workspace.remove(&entry.thread_id);
+                    hidden_thread_ids.push(entry.thread_id);
+                    continue;
+                }
+                let mapped_workspace = entry
+                    .cwd
+                    .as_deref()
+                    .and_then(|cwd| resolve_workspace_for_cwd(cwd, &workspace_roots));
+                if let Some(workspace_id) = mapped_workspace {
+                    thread_workspace.insert(entry.thread_id.clone(), workspace_id.clone());
+                    routes.push((entry.thread_id, workspace_id, entry.cwd));
+                }
+            }
+            drop(thread_workspace);
+            persist_thread_routes(session, routes).await;
+            for thread_id in &hidden_thread_ids {
+                session.services.thread_routes.remove(thread_id);
+            }
+            if !hidden_thread_ids.is_empty() {
+                let mut hidden = session.hidden_thread_ids.lock().await;
+                for thread_id in hidden_thread_ids {
+                    hidden.insert(thread_id);
+                }
+            }
+        }
+    }
+
+    let mapped_thread_workspace = if let Some(ref tid) = thread_id {
+        session.thread_workspace.lock().await.get(tid).cloned()
+    } else {
+        None
+    };
+
+    let routed_workspace_id = mapped_thread_workspace
+        .or_else(|| request_workspace.clone())
+        .unwrap_or_else(|| fallback_workspace_id.to_string());
+
+    if method_name == Some("turn/completed") {
+        if let Some(ref tid) = thread_id {
+            session.services.run_manager.release_thread(tid);
+        }
+    }
+
+    if let (Some(method), false) = (method_name, has_result_or_error) {
+        let params = value.get("params").cloned().unwrap_or(Value::Null);
+        if let Some(breach) =
+            session
+                .services
+                .session_quotas
+                .on_event(&routed_workspace_id, method, &params)
+        {
+            enforce_quota_breach(session, event_sink, breach);
+        }
+        session.services.defer(ReaderHook::Notification {
+            workspace_id: routed_workspace_id.clone(),
+            method: method.to_string(),
+            params,
+        });
+    }
+
+    if let Some(ref tid) = thread_id {
+        if method_name == Some("codex/backgroundThread") {
+            let action = value
+                .get("params")
+                .and_then(|params| params.get("action"))
+                .and_then(Value::as_str)
+                .unwrap_or("hide");
+            if action.eq_ignore_ascii_case("hide") {
+                session.hidden_thread_ids.lock().await.insert(tid.clone());
+            }
+        } else if method_name == Some("thread/started")
+            && thread_started_is_memory_consolidation(&value)
+        {
+            session.hidden_thread_ids.lock().await.insert(tid.clone());
+            let payload = AppServerEvent {
+                workspace_id: routed_workspace_id.clone(),
+                message: json!({
+                    "method": "codex/backgroundThread",
+                    "params": {
+                        "threadId": tid,
+                        "action": "hide"
+                    }
+                }),
+            };
+            event_sink.emit_app_server_event(payload);
+            return;
+        }
+
+        let should_suppress_hidden_thread = {
+            let hidden = session.hidden_thread_ids.lock().await;
+            hidden.contains(tid)
+        };
+        if should_suppress_hidden_thread
+            && should_suppress_hidden_thread_event(method_name, has_result_or_error)
+        {
+            return;
+        }
+    }
+
+    if matches!(method_name, Some("item/started") | Some("item/completed")) {
+        let related_thread_ids = extract_related_thread_ids(&value);
+        if !related_thread_ids.is_empty() {
+            let mut routes = Vec::new();
+            let mut thread_workspace = session.thread_workspace.lock().await;
+            for related_id in related_thread_ids {
+                if !thread_workspace.contains_key(&related_id) {
+                    thread_workspace.insert(related_id.clone(), routed_workspace_id.clone());
+                    routes.push((related_id, routed_workspace_id.clone(), None));
+                }
+            }
+            drop(thread_workspace);
+            persist_thread_routes(session, routes).await;
+        }
+    }
+
+    if has_method && !has_result_or_error {
+        if let Some(tid) = thread_id.as_ref() {
+            session.services.defer(ReaderHook::JournalAppend {
+                thread_id: tid.clone(),
+                workspace_id: routed_workspace_id.clone(),
+                message: value.clone(),
+            });
+        }
+    }
+
+    if method_name == Some("thread/archived") {
+        if let Some(ref tid) = thread_id {
+            session.thread_workspace.lock().await.remove(tid);
+            session.hidden_thread_ids.lock().await.remove(tid);
+            session.services.thread_routes.remove(tid);
+            session.services.defer(ReaderHook::JournalRemove {
+                thread_id: tid.clone(),
+            });
+        }
+    }
+
+    if !has_result_or_error
+        && apply_approval_policy(session, event_sink, &routed_workspace_id, &value).await
+    {
+        return;
+    }
+
+    if let Some(id) = maybe_id {
+        if has_result_or_error {
+            if let Some(tx) = session.pending_requests().remove(&id) {
+                let _ = tx.send(value);
+            }
+        } else if has_method {
+            // Check for background thread callback
+            let mut sent_to_background = false;
+            if let Some(ref tid) = thread_id {
+                let callbacks = session.background_thread_callbacks.lock().await;
+                if let Some(tx) = callbacks.get(tid) {
+                    let _ = tx.send(value.clone());
+                    sent_to_background = true;
+                }
+            }
+            // Don't emit to frontend if this is a background thread event
+            if !sent_to_background {
+                if should_broadcast_global_workspace_notification(
+                    method_name,
+                    thread_id.as_ref(),
+                    request_workspace.as_deref(),
+                ) {
+                    let workspace_ids = session.workspace_ids_snapshot().await;
+                    if workspace_ids.is_empty() {
+                        let payload = AppServerEvent {
+                            workspace_id: routed_workspace_id.clone(),
+                            message: value,
+                        };
+                        event_sink.emit_app_server_event(payload);
+                    } else {
+                        for workspace_id in workspace_ids {
+                            let payload = AppServerEvent {
+                                workspace_id,
+                                message: value.clone(),
+                            };
+                            event_sink.emit_app_server_event(payload);
+                        }
+                    }
+                } else {
+                    let payload = AppServerEvent {
+                        workspace_id: routed_workspace_id.clone(),
+                        message: value,
+                    };
+                    event_sink.emit_app_server_event(payload);
+                }
+            }
+        } else if let Some(tx) = session.pending_requests().remove(&id) {
+            let _ = tx.send(value);
+        }
+    } else if has_method {
+        // Check for background thread callback
+        let mut sent_to_background = false;
+        if let Some(ref tid) = thread_id {
+            let callbacks = session.background_thread_callbacks.lock().await;
+            if let Some(tx) = callbacks.get(tid) {
+                let _ = tx.send(value.clone());
+                sent_to_background = true;
+            }
+        }
+        // Don't emit to frontend if this is a background thread event
+        if !sent_to_background {
+            if should_broadcast_global_workspace_notification(
+                method_name,
+                thread_id.as_ref(),
+                request_workspace.as_deref(),
+            ) {
+                let workspace_ids = session.workspace_ids_snapshot().await;
+                if workspace_ids.is_empty() {
+                    let payload = AppServerEvent {
+                        workspace_id: routed_workspace_id,
+                        message: value,
+                    };
+                    event_sink.emit_app_server_event(payload);
+                } else {
+                    for workspace_id in workspace_ids {
+                        let payload = AppServerEvent {
+                            workspace_id,
+                            message: value.clone(),
+                        };
+                        event_sink.emit_app_server_event(payload);
+                    }
+                }
+            } else {
+                let payload = AppServerEvent {
+                    workspace_id: routed_workspace_id,
+                    message: value,
+                };
+                event_sink.emit_app_server_event(payload);
+            }
+        }
+    }
+}
+
 pub(crate) async fn spawn_workspace_session<E: EventSink>(
     entry: WorkspaceEntry,
     default_codex_bin: Option<String>,
     codex_args: Option<String>,
     codex_home: Option<PathBuf>,
     client_version: String,
+    services: Arc<BackendServices>,
     event_sink: E,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    let codex_bin = default_codex_bin;
-    let _ = check_codex_installation(codex_bin.clone()).await?;
-
-    let mut command = build_codex_command_with_bin(
-        codex_bin,
-        codex_args.as_deref(),
-        vec!["app-server".to_string()],
-    )?;
+    let backend = resolve_agent_backend(&entry, default_codex_bin.clone(), codex_args.clone())?;
+    let codex_version = if backend.kind() == AgentBackendKind::Codex {
+        check_codex_installation(default_codex_bin).await?
+    } else {
+        None
+    };
+
+    let env = services.session_env(&entry);
+    let is_codex = backend.kind() == AgentBackendKind::Codex;
+    let session_home = session_codex_home(&env, codex_home.as_deref());
+    let propagation = services.auth_propagation();
+    let mut propagation_error = None;
+    if let (true, Some(source), Some(target)) = (is_codex, &codex_home, &session_home) {
+        if !same_home(source, target) {
+            if let Err(err) = sync_auth_json(source, target, propagation) {
+                propagation_error = Some(err);
+            }
+        }
+    }
+    let authenticated = !is_codex || has_codex_auth(&env, session_home.as_deref());
+
+    let mut command = backend.build_command()?;
     command.current_dir(&entry.path);
     if let Some(path) = codex_home.as_ref() {
         command.env("CODEX_HOME", path);
     }
+    let provenance = capture_provenance(
+        &entry.id,
+        backend.kind(),
+        &command,
+        &entry.path,
+        session_home.as_deref(),
+        codex_version,
+        &env,
+    );
+    command.envs(env);
     command.stdin(std::process::Stdio::piped());
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
 
     let mut child = command.spawn().map_err(|e| e.to_string())?;
+    let pid = child.id();
+    services
+        .session_provenance
+        .record(SessionProvenance { pid, ..provenance });
     let stdin = child.stdin.take().ok_or("missing stdin")?;
     let stdout = child.stdout.take().ok_or("missing stdout")?;
     let stderr = child.stderr.take().ok_or("missing stderr")?;
@@ -779,9 +1346,9 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         codex_args,
         child: Mutex::new(child),
         stdin: Mutex::new(stdin),
-        pending: Mutex::new(HashMap::new()),
-        request_context: Mutex::new(HashMap::new()),
-        thread_workspace: Mutex::new(HashMap::new()),
+        pending: Default::default(),
+        request_context: Default::default(),
+        thread_workspace: Mutex::new(services.thread_routes.workspace_map()),
         hidden_thread_ids: Mutex::new(HashSet::new()),
         next_id: AtomicU64::new(1),
         background_thread_callbacks: Mutex::new(HashMap::new()),
@@ -791,8 +1358,20 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
             entry.id.clone(),
             normalize_root_path(&entry.path),
         )])),
+        backend,
+        request_timeouts: RequestTimeouts::default(),
+        isolated_env: !entry.settings.env.is_empty(),
+        services,
     });
 
+    if let Some(pid) = pid {
+        tokio::spawn(sample_session_cpu(
+            Arc::downgrade(&session),
+            pid,
+            event_sink.clone(),
+        ));
+    }
+
     let session_clone = Arc::clone(&session);
     let fallback_workspace_id = entry.id.clone();
     let event_sink_clone = event_sink.clone();
@@ -802,6 +1381,7 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
             if line.trim().is_empty() {
                 continue;
             }
+            trace_protocol_line(&session_clone, TraceDirection::Received, &line).await;
             let value: Value = match serde_json::from_str(&line) {
                 Ok(value) => value,
                 Err(err) => {
@@ -817,238 +1397,46 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 }
             };
 
-            let maybe_id = value.get("id").and_then(|id| id.as_u64());
-            let has_method = value.get("method").is_some();
-            let has_result_or_error = value.get("result").is_some() || value.get("error").is_some();
-            let method_name = value.get("method").and_then(|method| method.as_str());
-
-            // Check if this event is for a background thread
-            let thread_id = extract_thread_id(&value);
-            let mut request_workspace: Option<String> = None;
-            let mut request_method: Option<String> = None;
-            if let Some(id) = maybe_id {
-                if has_result_or_error {
-                    if let Some(context) = session_clone.request_context.lock().await.remove(&id) {
-                        request_workspace = Some(context.workspace_id);
-                        request_method = Some(context.method);
-                    }
-                }
-            }
-
-            if let Some(ref workspace_id) = request_workspace {
-                let related_thread_ids = extract_related_thread_ids(&value);
-                if !related_thread_ids.is_empty() {
-                    let mut thread_workspace = session_clone.thread_workspace.lock().await;
-                    for tid in related_thread_ids {
-                        thread_workspace.insert(tid, workspace_id.clone());
-                    }
-                } else if let Some(ref tid) = thread_id {
-                    session_clone
-                        .thread_workspace
-                        .lock()
-                        .await
-                        .insert(tid.clone(), workspace_id.clone());
-                }
-            }
-            if matches!(request_method.as_deref(), Some("thread/list")) {
-                let thread_entries = extract_thread_entries_from_thread_list_result(&value);
-                if !thread_entries.is_empty() {
-                    let workspace_roots = session_clone.workspace_roots.lock().await.clone();
-                    let mut hidden_thread_ids = Vec::new();
-                    let mut thread_workspace = session_clone.thread_workspace.lock().await;
-                    for entry in thread_entries {
-                        if entry.is_memory_consolidation {
-                            thread_workspace.remove(&entry.thread_id);
-                            hidden_thread_ids.push(entry.thread_id);
-                            continue;
-                        }
-                        let mapped_workspace = entry
-                            .cwd
-                            .as_deref()
-                            .and_then(|cwd| resolve_workspace_for_cwd(cwd, &workspace_roots));
-                        if let Some(workspace_id) = mapped_workspace {
-                            thread_workspace.insert(entry.thread_id, workspace_id);
-                        }
-                    }
-                    drop(thread_workspace);
-                    if !hidden_thread_ids.is_empty() {
-                        let mut hidden = session_clone.hidden_thread_ids.lock().await;
-                        for thread_id in hidden_thread_ids {
-                            hidden.insert(thread_id);
-                        }
-                    }
-                }
-            }
-
-            let mapped_thread_workspace = if let Some(ref tid) = thread_id {
-                session_clone
-                    .thread_workspace
-                    .lock()
-                    .await
-                    .get(tid)
-                    .cloned()
-            } else {
-                None
+            let response_id = value
+                .get("id")
+                .and_then(Value::as_u64)
+                .filter(|_| value.get("method").is_none());
+            let request_method = match response_id {
+                Some(id) => session_clone
+                    .request_contexts()
+                    .get(&id)
+                    .map(|context| context.method.clone()),
+                None => None,
             };
-
-            let routed_workspace_id = mapped_thread_workspace
-                .or_else(|| request_workspace.clone())
-                .unwrap_or_else(|| fallback_workspace_id.clone());
-
-            if let Some(ref tid) = thread_id {
-                if method_name == Some("codex/backgroundThread") {
-                    let action = value
-                        .get("params")
-                        .and_then(|params| params.get("action"))
-                        .and_then(Value::as_str)
-                        .unwrap_or("hide");
-                    if action.eq_ignore_ascii_case("hide") {
-                        session_clone.hidden_thread_ids.lock().await.insert(tid.clone());
-                    }
-                } else if method_name == Some("thread/started")
-                    && thread_started_is_memory_consolidation(&value)
-                {
-                    session_clone.hidden_thread_ids.lock().await.insert(tid.clone());
-                    let payload = AppServerEvent {
-                        workspace_id: routed_workspace_id.clone(),
-                        message: json!({
-                            "method": "codex/backgroundThread",
-                            "params": {
-                                "threadId": tid,
-                                "action": "hide"
-                            }
-                        }),
-                    };
-                    event_sink_clone.emit_app_server_event(payload);
-                    continue;
-                }
-
-                let should_suppress_hidden_thread = {
-                    let hidden = session_clone.hidden_thread_ids.lock().await;
-                    hidden.contains(tid)
-                };
-                if should_suppress_hidden_thread
-                    && should_suppress_hidden_thread_event(method_name, has_result_or_error)
-                {
-                    continue;
-                }
-            }
-
-            if matches!(method_name, Some("item/started") | Some("item/completed")) {
-                let related_thread_ids = extract_related_thread_ids(&value);
-                if !related_thread_ids.is_empty() {
-                    let mut thread_workspace = session_clone.thread_workspace.lock().await;
-                    for related_id in related_thread_ids {
-                        thread_workspace
-                            .entry(related_id)
-                            .or_insert_with(|| routed_workspace_id.clone());
-                    }
+            let messages = session_clone
+                .backend
+                .translate_incoming(value, request_method.as_deref());
+            if let Some(id) = response_id {
+                let still_a_response = messages
+                    .iter()
+                    .any(|message| message.get("id").and_then(Value::as_u64) == Some(id));
+                if !still_a_response {
+                    session_clone.request_contexts().remove(&id);
                 }
             }
-
-            if method_name == Some("thread/archived") {
-                if let Some(ref tid) = thread_id {
-                    session_clone.thread_workspace.lock().await.remove(tid);
-                    session_clone.hidden_thread_ids.lock().await.remove(tid);
-                }
-            }
-
-            if let Some(id) = maybe_id {
-                if has_result_or_error {
-                    if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                        let _ = tx.send(value);
-                    }
-                } else if has_method {
-                    // Check for background thread callback
-                    let mut sent_to_background = false;
-                    if let Some(ref tid) = thread_id {
-                        let callbacks = session_clone.background_thread_callbacks.lock().await;
-                        if let Some(tx) = callbacks.get(tid) {
-                            let _ = tx.send(value.clone());
-                            sent_to_background = true;
-                        }
-                    }
-                    // Don't emit to frontend if this is a background thread event
-                    if !sent_to_background {
-                        if should_broadcast_global_workspace_notification(
-                            method_name,
-                            thread_id.as_ref(),
-                            request_workspace.as_deref(),
-                        ) {
-                            let workspace_ids = session_clone.workspace_ids_snapshot().await;
-                            if workspace_ids.is_empty() {
-                                let payload = AppServerEvent {
-                                    workspace_id: routed_workspace_id.clone(),
-                                    message: value,
-                                };
-                                event_sink_clone.emit_app_server_event(payload);
-                            } else {
-                                for workspace_id in workspace_ids {
-                                    let payload = AppServerEvent {
-                                        workspace_id,
-                                        message: value.clone(),
-                                    };
-                                    event_sink_clone.emit_app_server_event(payload);
-                                }
-                            }
-                        } else {
-                            let payload = AppServerEvent {
-                                workspace_id: routed_workspace_id.clone(),
-                                message: value,
-                            };
-                            event_sink_clone.emit_app_server_event(payload);
-                        }
-                    }
-                } else if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                    let _ = tx.send(value);
-                }
-            } else if has_method {
-                // Check for background thread callback
-                let mut sent_to_background = false;
-                if let Some(ref tid) = thread_id {
-                    let callbacks = session_clone.background_thread_callbacks.lock().await;
-                    if let Some(tx) = callbacks.get(tid) {
-                        let _ = tx.send(value.clone());
-                        sent_to_background = true;
-                    }
-                }
-                // Don't emit to frontend if this is a background thread event
-                if !sent_to_background {
-                    if should_broadcast_global_workspace_notification(
-                        method_name,
-                        thread_id.as_ref(),
-                        request_workspace.as_deref(),
-                    ) {
-                        let workspace_ids = session_clone.workspace_ids_snapshot().await;
-                        if workspace_ids.is_empty() {
-                            let payload = AppServerEvent {
-                                workspace_id: routed_workspace_id,
-                                message: value,
-                            };
-                            event_sink_clone.emit_app_server_event(payload);
-                        } else {
-                            for workspace_id in workspace_ids {
-                                let payload = AppServerEvent {
-                                    workspace_id,
-                                    message: value.clone(),
-                                };
-                                event_sink_clone.emit_app_server_event(payload);
-                            }
-                        }
-                    } else {
-                        let payload = AppServerEvent {
-                            workspace_id: routed_workspace_id,
-                            message: value,
-                        };
-                        event_sink_clone.emit_app_server_event(payload);
-                    }
-                }
+            for message in messages {
+                route_incoming_message(
+                    &session_clone,
+                    &event_sink_clone,
+                    &fallback_workspace_id,
+                    message,
+                )
+                .await;
             }
         }
 
         // Ensure pending foreground requests cannot accumulate after process output ends.
-        session_clone.pending.lock().await.clear();
-        session_clone.request_context.lock().await.clear();
+        let workspace_ids = session_clone.workspace_ids_snapshot().await;
+        let services = &session_clone.services;
+        services.run_manager.release_workspaces(&workspace_ids);
+        services.defer(ReaderHook::SessionEnded { workspace_ids });
+        session_clone.pending_requests().clear();
+        session_clone.request_contexts().clear();
     });
 
     let workspace_id = entry.id.clone();
@@ -1070,25 +1458,26 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         }
     });
 
-    let init_params = build_initialize_params(&client_version);
-    let init_result = timeout(
-        Duration::from_secs(15),
-        session.send_request("initialize", init_params),
-    )
-    .await;
-    let init_response = match init_result {
-        Ok(response) => response,
-        Err(_) => {
-            let mut child = session.child.lock().await;
-            kill_child_process_tree(&mut child).await;
-            return Err(
-                "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal."
-                    .to_string(),
-            );
+    let initialize_request = session.backend.initialize_request(&client_version);
+    if let Some((init_method, init_params)) = initialize_request {
+        let init_result = timeout(
+            Duration::from_secs(15),
+            session.send_request(init_method, init_params),
+        )
+        .await;
+        let init_response = match init_result {
+            Ok(response) => response,
+            Err(_) => {
+                let mut child = session.child.lock().await;
+                kill_child_process_tree(&mut child).await;
+                return Err(session.backend.initialize_timeout_message());
+            }
+        };
+        init_response?;
+        if let Some(method) = session.backend.initialized_notification() {
+            session.send_notification(method, None).await?;
         }
-    };
-    init_response?;
-    session.send_notification("initialized", None).await?;
+    }
 
     let payload = AppServerEvent {
         workspace_id: entry.id.clone(),
@@ -1098,6 +1487,20 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         }),
     };
     event_sink.emit_app_server_event(payload);
+    if !authenticated {
+        event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: entry.id.clone(),
+            message: json!({
+                "method": "codex/authMissing",
+                "params": {
+                    "workspaceId": entry.id.clone(),
+                    "codexHome": session_home.map(|home| home.to_string_lossy().to_string()),
+                    "propagation": propagation,
+                    "error": propagation_error,
+                }
+            }),
+        });
+    }
 
     Ok(session)
 }
@@ -1110,7 +1513,6 @@ mod tests {
         should_suppress_hidden_thread_event, source_subagent_kind,
         thread_started_is_memory_consolidation,
     };
-    use std::collections::HashMap;
     use serde_json::json;
 
     #[test]
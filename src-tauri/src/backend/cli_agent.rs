@@ -0,0 +1,321 @@
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use super::agent::{build_agent_command, AgentBackend, AgentOutbound};
+use crate::types::AgentBackendKind;
+
+#[derive(Default)]
+struct CliState {
+    thread_id: Option<String>,
+    next_turn: u64,
+    active_turn: Option<String>,
+}
+
+/// Adapter for agent CLIs that keep one conversation per process and speak
+/// newline-delimited JSON on stdio (`--input-format stream-json
+/// --output-format stream-json` style). Each process is a single thread;
+/// user messages start turns and the final `result` record completes them.
+pub(crate) struct StreamJsonCliBackend {
+    command: String,
+    state: Mutex<CliState>,
+}
+
+impl StreamJsonCliBackend {
+    pub(crate) fn new(command: String) -> Self {
+        Self {
+            command,
+            state: Mutex::new(CliState::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CliState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn thread_and_turn(&self) -> (String, String) {
+        let state = self.state();
+        (
+            state.thread_id.clone().unwrap_or_default(),
+            state.active_turn.clone().unwrap_or_default(),
+        )
+    }
+
+    /// One agent message item and one reasoning item per turn, so text split
+    /// across several blocks or records reads as a single reply.
+    fn turn_item_id(turn_id: &str, kind: &str) -> String {
+        format!("{turn_id}-{kind}")
+    }
+
+    fn translate_assistant_message(&self, message: &Value) -> Vec<Value> {
+        let (thread_id, turn_id) = self.thread_and_turn();
+        let message_item = Self::turn_item_id(&turn_id, "message");
+        let reasoning_item = Self::turn_item_id(&turn_id, "reasoning");
+        let Some(blocks) = message
+            .get("message")
+            .and_then(|inner| inner.get("content"))
+            .and_then(Value::as_array)
+        else {
+            return Vec::new();
+        };
+        blocks
+            .iter()
+            .map(|block| match block.get("type").and_then(Value::as_str) {
+                Some("text") => json!({
+                    "method": "item/agentMessage/delta",
+                    "params": {
+                        "threadId": thread_id,
+                        "turnId": turn_id,
+                        "itemId": message_item,
+                        "delta": block.get("text").and_then(Value::as_str).unwrap_or_default(),
+                    }
+                }),
+                Some("thinking") => json!({
+                    "method": "item/reasoning/textDelta",
+                    "params": {
+                        "threadId": thread_id,
+                        "turnId": turn_id,
+                        "itemId": reasoning_item,
+                        "delta": block.get("thinking").and_then(Value::as_str).unwrap_or_default(),
+                    }
+                }),
+                _ => json!({
+                    "method": "cli/contentBlock",
+                    "params": { "threadId": thread_id, "turnId": turn_id, "block": block }
+                }),
+            })
+            .collect()
+    }
+
+    fn translate_result(&self, message: &Value) -> Value {
+        let (thread_id, turn_id) = {
+            let mut state = self.state();
+            (
+                state.thread_id.clone().unwrap_or_default(),
+                state.active_turn.take().unwrap_or_default(),
+            )
+        };
+        let failed = message
+            .get("is_error")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let mut turn = json!({
+            "id": turn_id,
+            "status": if failed { "failed" } else { "completed" },
+        });
+        if failed {
+            turn["error"] = json!({
+                "message": message
+                    .get("result")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Agent CLI reported an error"),
+            });
+        }
+        json!({
+            "method": "turn/completed",
+            "params": { "threadId": thread_id, "turn": turn }
+        })
+    }
+}
+
+fn cli_content_from_turn_input(params: &Value) -> Vec<Value> {
+    let Some(items) = params.get("input").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("text") => Some(json!({
+                "type": "text",
+                "text": item.get("text").and_then(Value::as_str).unwrap_or_default(),
+            })),
+            Some("image") => {
+                let url = item.get("url").and_then(Value::as_str)?;
+                let (media_type, data) = url.strip_prefix("data:")?.split_once(";base64,")?;
+                Some(json!({
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": media_type, "data": data }
+                }))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+impl AgentBackend for StreamJsonCliBackend {
+    fn kind(&self) -> AgentBackendKind {
+        AgentBackendKind::Cli
+    }
+
+    fn build_command(&self) -> Result<Command, String> {
+        build_agent_command(&self.command)
+    }
+
+    fn initialize_request(&self, _client_version: &str) -> Option<(&'static str, Value)> {
+        None
+    }
+
+    fn translate_request(
+        &self,
+        _id: u64,
+        method: &str,
+        params: Value,
+    ) -> Result<AgentOutbound, String> {
+        match method {
+            "thread/start" => {
+                let mut state = self.state();
+                if state.thread_id.is_some() {
+                    return Err(
+                        "The CLI agent backend runs one thread per workspace. Add a worktree to start another."
+                            .to_string(),
+                    );
+                }
+                let thread_id = format!("cli-{}", uuid::Uuid::new_v4());
+                state.thread_id = Some(thread_id.clone());
+                Ok(AgentOutbound::Local {
+                    result: json!({ "thread": { "id": thread_id, "preview": "" } }),
+                })
+            }
+            "thread/list" => {
+                let data = self
+                    .state()
+                    .thread_id
+                    .clone()
+                    .map(|id| vec![json!({ "id": id, "preview": "" })])
+                    .unwrap_or_default();
+                Ok(AgentOutbound::Local {
+                    result: json!({ "data": data, "nextCursor": null }),
+                })
+            }
+            "thread/resume" | "thread/read" => {
+                let thread_id = self.state().thread_id.clone();
+                let requested = params.get("threadId").and_then(Value::as_str);
+                match thread_id {
+                    Some(thread_id) if requested == Some(thread_id.as_str()) => {
+                        Ok(AgentOutbound::Local {
+                            result: json!({ "thread": { "id": thread_id, "turns": [] } }),
+                        })
+                    }
+                    _ => Err("thread not found".to_string()),
+                }
+            }
+            "turn/start" => {
+                let turn_id = {
+                    let mut state = self.state();
+                    if state.active_turn.is_some() {
+                        return Err("A turn is already running for this agent.".to_string());
+                    }
+                    state.next_turn += 1;
+                    let turn_id = format!("cli-turn-{}", state.next_turn);
+                    state.active_turn = Some(turn_id.clone());
+                    turn_id
+                };
+                Ok(AgentOutbound::Message {
+                    message: json!({
+                        "type": "user",
+                        "message": {
+                            "role": "user",
+                            "content": cli_content_from_turn_input(&params),
+                        }
+                    }),
+                    result: json!({
+                        "turn": { "id": turn_id, "status": "inProgress", "items": [] }
+                    }),
+                })
+            }
+            _ => Err(format!(
+                "`{method}` is not supported by the CLI agent backend"
+            )),
+        }
+    }
+
+    fn translate_incoming(&self, message: Value, _request_method: Option<&str>) -> Vec<Value> {
+        match message.get("type").and_then(Value::as_str) {
+            Some("assistant") => self.translate_assistant_message(&message),
+            Some("result") => vec![self.translate_result(&message)],
+            _ => {
+                let (thread_id, turn_id) = self.thread_and_turn();
+                vec![json!({
+                    "method": "cli/event",
+                    "params": { "threadId": thread_id, "turnId": turn_id, "event": message }
+                })]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamJsonCliBackend;
+    use crate::backend::agent::{AgentBackend, AgentOutbound};
+    use serde_json::json;
+
+    #[test]
+    fn cli_turn_maps_assistant_and_result_records() {
+        let backend = StreamJsonCliBackend::new("agent --print".to_string());
+        let AgentOutbound::Local { result } = backend
+            .translate_request(1, "thread/start", json!({ "cwd": "/tmp" }))
+            .expect("start thread")
+        else {
+            panic!("thread/start should be answered locally");
+        };
+        let thread_id = result["thread"]["id"]
+            .as_str()
+            .expect("thread id")
+            .to_string();
+        assert!(backend
+            .translate_request(2, "thread/start", json!({}))
+            .is_err());
+
+        let AgentOutbound::Message { message, result } = backend
+            .translate_request(
+                3,
+                "turn/start",
+                json!({
+                    "threadId": thread_id,
+                    "input": [{ "type": "text", "text": "hello" }]
+                }),
+            )
+            .expect("start turn")
+        else {
+            panic!("turn/start should write a raw message");
+        };
+        assert_eq!(message["type"], "user");
+        assert_eq!(message["message"]["content"][0]["text"], "hello");
+        let turn_id = result["turn"]["id"].as_str().expect("turn id").to_string();
+
+        let deltas = backend.translate_incoming(
+            json!({
+                "type": "assistant",
+                "message": { "content": [
+                    { "type": "text", "text": "hi " },
+                    { "type": "text", "text": "there" }
+                ] }
+            }),
+            None,
+        );
+        assert_eq!(deltas[0]["method"], "item/agentMessage/delta");
+        assert_eq!(deltas[0]["params"]["threadId"], thread_id.as_str());
+        assert_eq!(deltas[0]["params"]["delta"], "hi ");
+        let later = backend.translate_incoming(
+            json!({
+                "type": "assistant",
+                "message": { "content": [{ "type": "text", "text": "!" }] }
+            }),
+            None,
+        );
+        assert_eq!(deltas[1]["params"]["itemId"], deltas[0]["params"]["itemId"]);
+        assert_eq!(later[0]["params"]["itemId"], deltas[0]["params"]["itemId"]);
+
+        let completed = backend.translate_incoming(
+            json!({ "type": "result", "subtype": "success", "is_error": false }),
+            None,
+        );
+        assert_eq!(completed[0]["method"], "turn/completed");
+        assert_eq!(completed[0]["params"]["turn"]["id"], turn_id.as_str());
+        assert_eq!(completed[0]["params"]["turn"]["status"], "completed");
+    }
+}
@@ -0,0 +1,222 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::codex::home::normalize_codex_home;
+use crate::types::CodexAuthPropagation;
+
+const AUTH_FILE: &str = "auth.json";
+/// Next to a copied `auth.json`: the hash of the source it was copied from.
+const COPY_MARKER_FILE: &str = "auth.json.source-sha256";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AuthSync {
+    /// Already linked, or already a copy of the current source.
+    Unchanged,
+    Linked,
+    Copied,
+    /// The isolated home has a login of its own, which is never replaced.
+    KeptLocal,
+    /// The default home has no `auth.json` to share.
+    NoSource,
+}
+
+/// The `CODEX_HOME` a session will run with: its env's value when set,
+/// otherwise `fallback`.
+pub(crate) fn session_codex_home(
+    env: &[(String, String)],
+    fallback: Option<&Path>,
+) -> Option<PathBuf> {
+    env.iter()
+        .find(|(key, _)| key == "CODEX_HOME")
+        .and_then(|(_, value)| normalize_codex_home(value))
+        .or_else(|| fallback.map(Path::to_path_buf))
+}
+
+pub(crate) fn same_home(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn io_error(action: &str, path: &Path, err: std::io::Error) -> String {
+    format!("Failed to {action} {}: {err}", path.display())
+}
+
+fn write_copy(target_home: &Path, bytes: &[u8], hash: &str) -> Result<(), String> {
+    let target = target_home.join(AUTH_FILE);
+    let tmp = target_home.join("auth.json.tmp");
+    fs::write(&tmp, bytes).map_err(|err| io_error("write", &tmp, err))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))
+            .map_err(|err| io_error("restrict", &tmp, err))?;
+    }
+    fs::rename(&tmp, &target).map_err(|err| io_error("replace", &target, err))?;
+    let marker = target_home.join(COPY_MARKER_FILE);
+    fs::write(&marker, hash).map_err(|err| io_error("write", &marker, err))
+}
+
+fn link(source: &Path, target: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(source, target)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_file(source, target)
+    }
+}
+
+/// Shares `source_home`'s `auth.json` with `target_home`. A login made in
+/// the target home is kept; a copy is refreshed only when the source
+/// changes, so tokens the session refreshed are not rolled back.
+pub(crate) fn sync_auth_json(
+    source_home: &Path,
+    target_home: &Path,
+    mode: CodexAuthPropagation,
+) -> Result<AuthSync, String> {
+    if mode == CodexAuthPropagation::Off {
+        return Ok(AuthSync::Unchanged);
+    }
+    let source = source_home.join(AUTH_FILE);
+    let bytes = match fs::read(&source) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(AuthSync::NoSource),
+        Err(err) => return Err(io_error("read", &source, err)),
+    };
+    let hash = sha256_hex(&bytes);
+    fs::create_dir_all(target_home).map_err(|err| io_error("create", target_home, err))?;
+    let target = target_home.join(AUTH_FILE);
+    let marker = target_home.join(COPY_MARKER_FILE);
+    let copied_hash = fs::read_to_string(&marker).ok();
+
+    // Anything we put there ourselves may be replaced; anything else is a
+    // login of the target home's own.
+    match fs::symlink_metadata(&target) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            if mode == CodexAuthPropagation::Symlink
+                && fs::read_link(&target).ok() == Some(source.clone())
+            {
+                return Ok(AuthSync::Unchanged);
+            }
+            fs::remove_file(&target).map_err(|err| io_error("remove", &target, err))?;
+        }
+        Ok(_) => match copied_hash.as_deref() {
+            None if fs::read(&target).ok().as_deref() == Some(bytes.as_slice()) => {
+                if mode == CodexAuthPropagation::Copy {
+                    fs::write(&marker, &hash).map_err(|err| io_error("write", &marker, err))?;
+                    return Ok(AuthSync::Unchanged);
+                }
+            }
+            None => return Ok(AuthSync::KeptLocal),
+            Some(copied) if copied == hash && mode == CodexAuthPropagation::Copy => {
+                return Ok(AuthSync::Unchanged);
+            }
+            Some(_) => {}
+        },
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(io_error("inspect", &target, err)),
+    }
+
+    if mode == CodexAuthPropagation::Symlink {
+        let _ = fs::remove_file(&target);
+        if link(&source, &target).is_ok() {
+            let _ = fs::remove_file(&marker);
+            return Ok(AuthSync::Linked);
+        }
+        // No symlink permission (e.g. Windows without developer mode).
+    }
+    write_copy(target_home, &bytes, &hash)?;
+    Ok(AuthSync::Copied)
+}
+
+/// Whether a session with this env and home can call the API without a
+/// `codex_login` first.
+pub(crate) fn has_codex_auth(env: &[(String, String)], codex_home: Option<&Path>) -> bool {
+    let api_key_set = |value: &str| !value.trim().is_empty();
+    if env
+        .iter()
+        .any(|(key, value)| key == "OPENAI_API_KEY" && api_key_set(value))
+        || std::env::var("OPENAI_API_KEY").is_ok_and(|value| api_key_set(&value))
+    {
+        return true;
+    }
+    let Some(home) = codex_home else {
+        return false;
+    };
+    let Some(auth) = fs::read(home.join(AUTH_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+    else {
+        return false;
+    };
+    let has_tokens = auth.get("tokens").is_some_and(|tokens| !tokens.is_null());
+    let has_api_key = auth
+        .get("OPENAI_API_KEY")
+        .and_then(Value::as_str)
+        .is_some_and(api_key_set);
+    has_tokens || has_api_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_follow_source_changes_but_keep_local_logins() {
+        let root =
+            std::env::temp_dir().join(format!("codex-monitor-auth-{}", uuid::Uuid::new_v4()));
+        let source = root.join("default");
+        let target = root.join("isolated");
+        fs::create_dir_all(&source).expect("create source");
+        assert_eq!(
+            sync_auth_json(&source, &target, CodexAuthPropagation::Copy),
+            Ok(AuthSync::NoSource)
+        );
+
+        fs::write(source.join(AUTH_FILE), r#"{"tokens":{"id_token":"a"}}"#).expect("write");
+        let copy = |expected| {
+            assert_eq!(
+                sync_auth_json(&source, &target, CodexAuthPropagation::Copy),
+                Ok(expected)
+            );
+        };
+        copy(AuthSync::Copied);
+        assert!(has_codex_auth(&[], Some(&target)));
+        copy(AuthSync::Unchanged);
+
+        // The session refreshed its tokens: keep them until the source moves.
+        fs::write(target.join(AUTH_FILE), r#"{"tokens":{"id_token":"b"}}"#).expect("write");
+        copy(AuthSync::Unchanged);
+        fs::write(source.join(AUTH_FILE), r#"{"tokens":{"id_token":"c"}}"#).expect("write");
+        copy(AuthSync::Copied);
+        assert_eq!(
+            fs::read_to_string(target.join(AUTH_FILE)).expect("read"),
+            r#"{"tokens":{"id_token":"c"}}"#
+        );
+
+        // A login that was never copied is not ours to replace.
+        let local = root.join("local");
+        fs::create_dir_all(&local).expect("create local");
+        fs::write(local.join(AUTH_FILE), r#"{"OPENAI_API_KEY":"sk-local"}"#).expect("write");
+        assert_eq!(
+            sync_auth_json(&source, &local, CodexAuthPropagation::Symlink),
+            Ok(AuthSync::KeptLocal)
+        );
+        assert!(!has_codex_auth(&[], Some(&root)));
+        let _ = fs::remove_dir_all(root);
+    }
+}
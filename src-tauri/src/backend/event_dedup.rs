@@ -0,0 +1,167 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::backend::events::AppServerEvent;
+
+/// How long a delivered lifecycle event suppresses identical copies.
+const DEDUP_WINDOW_MS: u64 = 10 * 60 * 1000;
+/// Oldest keys are forgotten past this many, whatever their age.
+const MAX_TRACKED_EVENTS: usize = 8192;
+
+/// Whether a sink should deliver an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// Deliver, stamped with this revision when the event has one.
+    Deliver(Option<String>),
+    /// An identical copy was delivered within the window.
+    Duplicate,
+}
+
+/// Recently delivered item and turn lifecycle events. Session respawns and
+/// `thread/resume` replay these, so repeats are dropped before they reach
+/// clients. Deltas are never de-duplicated: identical chunks are legitimate.
+#[derive(Default)]
+pub(crate) struct EventDedup {
+    seen: Mutex<SeenEvents>,
+}
+
+#[derive(Default)]
+struct SeenEvents {
+    keys: HashSet<String>,
+    /// Keys with the time they were first delivered, oldest first.
+    order: VecDeque<(u64, String)>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// `(threadId, item or turn id)` of a lifecycle notification.
+fn lifecycle_target<'a>(method: &str, params: &'a Value) -> Option<(&'a str, &'a str)> {
+    let object = match method {
+        "item/started" | "item/completed" => params.get("item")?,
+        "turn/started" | "turn/completed" => params.get("turn")?,
+        _ => return None,
+    };
+    let thread_id = params
+        .get("threadId")
+        .or_else(|| params.get("thread_id"))?
+        .as_str()?;
+    let id = object.get("id")?.as_str()?;
+    Some((thread_id, id))
+}
+
+/// A short content hash of the notification's params. Copies of the same
+/// revision are duplicates; a changed item gets a new revision.
+fn event_revision(params: &Value) -> String {
+    let encoded = serde_json::to_vec(params).unwrap_or_default();
+    Sha256::digest(&encoded)
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+impl EventDedup {
+    fn lock(&self) -> MutexGuard<'_, SeenEvents> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn admit(&self, event: &AppServerEvent) -> Admission {
+        self.admit_at(event, now_ms())
+    }
+
+    fn admit_at(&self, event: &AppServerEvent, now: u64) -> Admission {
+        let method = event
+            .message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let Some(params) = event.message.get("params") else {
+            return Admission::Deliver(None);
+        };
+        let Some((thread_id, id)) = lifecycle_target(method, params) else {
+            return Admission::Deliver(None);
+        };
+        let revision = event_revision(params);
+        let key = format!(
+            "{}\u{0}{thread_id}\u{0}{id}\u{0}{method}\u{0}{revision}",
+            event.workspace_id
+        );
+
+        let mut seen = self.lock();
+        while let Some((seen_at, _)) = seen.order.front() {
+            if now.saturating_sub(*seen_at) < DEDUP_WINDOW_MS
+                && seen.order.len() < MAX_TRACKED_EVENTS
+            {
+                break;
+            }
+            if let Some((_, expired)) = seen.order.pop_front() {
+                seen.keys.remove(&expired);
+            }
+        }
+        if !seen.keys.insert(key.clone()) {
+            return Admission::Duplicate;
+        }
+        seen.order.push_back((now, key));
+        Admission::Deliver(Some(revision))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(method: &str, params: Value) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({ "method": method, "params": params }),
+        }
+    }
+
+    #[test]
+    fn repeats_within_the_window_are_dropped() {
+        let dedup = EventDedup::default();
+        let completed = event(
+            "item/completed",
+            json!({ "threadId": "t-1", "item": { "id": "i-1", "text": "done" } }),
+        );
+        let Admission::Deliver(Some(revision)) = dedup.admit_at(&completed, 1_000) else {
+            panic!("first copy should be delivered");
+        };
+        assert_eq!(revision.len(), 16);
+        assert_eq!(dedup.admit_at(&completed, 2_000), Admission::Duplicate);
+
+        // A changed item is a new revision.
+        let edited = event(
+            "item/completed",
+            json!({ "threadId": "t-1", "item": { "id": "i-1", "text": "edited" } }),
+        );
+        assert!(matches!(
+            dedup.admit_at(&edited, 2_000),
+            Admission::Deliver(Some(_))
+        ));
+
+        let delta = event(
+            "item/agentMessage/delta",
+            json!({ "threadId": "t-1", "itemId": "i-1", "delta": "a" }),
+        );
+        assert_eq!(dedup.admit_at(&delta, 2_000), Admission::Deliver(None));
+        assert_eq!(dedup.admit_at(&delta, 2_000), Admission::Deliver(None));
+
+        assert!(matches!(
+            dedup.admit_at(&completed, 1_000 + DEDUP_WINDOW_MS),
+            Admission::Deliver(Some(_))
+        ));
+    }
+}
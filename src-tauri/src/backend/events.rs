@@ -1,3 +1,6 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serde::Serialize;
 use serde_json::Value;
 
@@ -7,6 +10,58 @@ pub(crate) struct AppServerEvent {
     pub(crate) message: Value,
 }
 
+/// An `AppServerEvent` as delivered to clients, stamped by the sink.
+///
+/// `seq` starts at 1 when the process starts and increases by one per event
+/// across all workspaces, and events are delivered in `seq` order. Within a
+/// workspace that is also the order the app-server sent them; across
+/// workspaces it is only the order they reached the sink. A daemon restart
+/// starts `seq` over, which clients see as a smaller `seq`. `emittedAt` is
+/// the wall-clock time (ms since the epoch) the sink handled the event.
+/// Item and turn lifecycle events also carry `revision`, a hash of their
+/// params; see `event_dedup`.
+#[derive(Serialize, Clone)]
+pub(crate) struct StampedAppServerEvent {
+    #[serde(flatten)]
+    pub(crate) event: AppServerEvent,
+    pub(crate) seq: u64,
+    #[serde(rename = "emittedAt")]
+    pub(crate) emitted_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) revision: Option<String>,
+}
+
+/// The last `seq` handed out by a sink.
+#[derive(Default)]
+pub(crate) struct EventSequence(Mutex<u64>);
+
+impl EventSequence {
+    /// Stamps `event` and hands it to `deliver` while holding the sequence
+    /// lock, so concurrent emitters cannot deliver out of `seq` order.
+    pub(crate) fn stamp_and_deliver(
+        &self,
+        event: AppServerEvent,
+        revision: Option<String>,
+        deliver: impl FnOnce(StampedAppServerEvent),
+    ) {
+        let mut last_seq = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *last_seq += 1;
+        let emitted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        deliver(StampedAppServerEvent {
+            event,
+            seq: *last_seq,
+            emitted_at,
+            revision,
+        });
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub(crate) struct TerminalOutput {
     #[serde(rename = "workspaceId")]
@@ -29,3 +84,34 @@ pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_terminal_output(&self, event: TerminalOutput);
     fn emit_terminal_exit(&self, event: TerminalExit);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stamped_events_keep_their_fields_and_increase_seq() {
+        let sequence = EventSequence::default();
+        let mut stamped = Vec::new();
+        for index in 0..3 {
+            sequence.stamp_and_deliver(
+                AppServerEvent {
+                    workspace_id: "ws-1".to_string(),
+                    message: json!({ "method": "item/started", "index": index }),
+                },
+                None,
+                |event| stamped.push(event),
+            );
+        }
+        assert_eq!(
+            stamped.iter().map(|event| event.seq).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        let value = serde_json::to_value(&stamped[0]).expect("serialize");
+        assert_eq!(value["workspace_id"], "ws-1");
+        assert_eq!(value["message"]["index"], 0);
+        assert_eq!(value["seq"], 1);
+        assert!(value["emittedAt"].as_u64().unwrap_or(0) > 0);
+    }
+}
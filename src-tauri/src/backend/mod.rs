@@ -1,2 +1,21 @@
+pub(crate) mod agent;
 pub(crate) mod app_server;
+pub(crate) mod cli_agent;
+pub(crate) mod codex_auth;
+pub(crate) mod event_dedup;
 pub(crate) mod events;
+pub(crate) mod operations;
+pub(crate) mod protocol_trace;
+pub(crate) mod quotas;
+pub(crate) mod rate_limits;
+pub(crate) mod request_timeouts;
+pub(crate) mod run_manager;
+pub(crate) mod services;
+pub(crate) mod session_provenance;
+pub(crate) mod terminal_history;
+pub(crate) mod thread_journal;
+pub(crate) mod thread_preferences;
+pub(crate) mod thread_routes;
+pub(crate) mod turn_tracker;
+pub(crate) mod usage_metrics;
+pub(crate) mod workspace_activity;
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::backend::events::{AppServerEvent, EventSink};
+
+type EventListener = Box<dyn Fn(AppServerEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum OperationStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StepStatus {
+    Running,
+    Done,
+    Failed,
+    RolledBack,
+    RollbackFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationStep {
+    pub(crate) name: String,
+    pub(crate) status: StepStatus,
+    pub(crate) error: Option<String>,
+}
+
+/// An operation that is still running or failed and was rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingOperation {
+    pub(crate) id: String,
+    pub(crate) kind: String,
+    pub(crate) workspace_id: String,
+    pub(crate) status: OperationStatus,
+    pub(crate) steps: Vec<OperationStep>,
+    pub(crate) error: Option<String>,
+    /// The original request, replayed by `retry_operation`.
+    pub(crate) params: Value,
+    pub(crate) started_at: u64,
+    pub(crate) updated_at: u64,
+}
+
+/// Tracks running and failed operations and reports every change as an
+/// `operation/progress` event. Completed operations are dropped.
+#[derive(Default)]
+pub(crate) struct OperationRegistry {
+    records: Mutex<HashMap<String, PendingOperation>>,
+    listener: RwLock<Option<EventListener>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn progress_event(record: &PendingOperation) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: record.workspace_id.clone(),
+        message: json!({
+            "method": "operation/progress",
+            "params": {
+                "operationId": record.id,
+                "kind": record.kind,
+                "status": record.status,
+                "step": record.steps.last().map(|step| step.name.as_str()),
+                "steps": record.steps,
+                "error": record.error,
+            }
+        }),
+    }
+}
+
+impl OperationRegistry {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, PendingOperation>> {
+        self.records
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Where `operation/progress` events go.
+    pub(crate) fn set_event_sink<E: EventSink>(&self, event_sink: E) {
+        if let Ok(mut listener) = self.listener.write() {
+            *listener = Some(Box::new(move |event| {
+                event_sink.emit_app_server_event(event)
+            }));
+        }
+    }
+
+    fn emit(&self, event: AppServerEvent) {
+        let Ok(listener) = self.listener.read() else {
+            return;
+        };
+        if let Some(listener) = listener.as_ref() {
+            listener(event);
+        }
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut PendingOperation)) {
+        let event = {
+            let mut records = self.lock();
+            let Some(record) = records.get_mut(id) else {
+                return;
+            };
+            apply(record);
+            record.updated_at = now_ms();
+            let event = progress_event(record);
+            if record.status == OperationStatus::Completed {
+                records.remove(id);
+            }
+            event
+        };
+        self.emit(event);
+    }
+
+    /// Registers a new operation. `params` should be the request that
+    /// started it so a failed run can be replayed.
+    pub(crate) fn begin<C>(
+        &self,
+        kind: &str,
+        workspace_id: &str,
+        params: Value,
+    ) -> Operation<'_, C> {
+        let now = now_ms();
+        let record = PendingOperation {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            workspace_id: workspace_id.to_string(),
+            status: OperationStatus::Running,
+            steps: Vec::new(),
+            error: None,
+            params,
+            started_at: now,
+            updated_at: now,
+        };
+        let id = record.id.clone();
+        let event = progress_event(&record);
+        self.lock().insert(id.clone(), record);
+        self.emit(event);
+        Operation {
+            registry: self,
+            id,
+            compensations: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Running and failed operations, oldest first.
+    pub(crate) fn list(&self) -> Vec<PendingOperation> {
+        let mut list: Vec<_> = self.lock().values().cloned().collect();
+        list.sort_by_key(|record| record.started_at);
+        list
+    }
+
+    /// Removes a failed operation so it can be run again.
+    pub(crate) fn take_failed(&self, id: &str) -> Result<PendingOperation, String> {
+        let mut records = self.lock();
+        match records.get(id).map(|record| record.status) {
+            None => Err(format!("Unknown operation `{id}`.")),
+            Some(OperationStatus::Failed) => Ok(records.remove(id).expect("record exists")),
+            Some(_) => Err("The operation is still running.".to_string()),
+        }
+    }
+}
+
+/// One run of a multi-step operation. Each step can register a compensation
+/// of type `C`; when the operation fails they are undone in reverse order.
+pub(crate) struct Operation<'r, C> {
+    registry: &'r OperationRegistry,
+    id: String,
+    compensations: Vec<(usize, C)>,
+    finished: bool,
+}
+
+impl<C> Operation<'_, C> {
+    /// Marks the previous step done and starts `name`.
+    pub(crate) fn step(&mut self, name: &str) {
+        self.registry.update(&self.id, |record| {
+            if let Some(last) = record.steps.last_mut() {
+                if last.status == StepStatus::Running {
+                    last.status = StepStatus::Done;
+                }
+            }
+            record.steps.push(OperationStep {
+                name: name.to_string(),
+                status: StepStatus::Running,
+                error: None,
+            });
+        });
+    }
+
+    /// Records how to undo the current step.
+    pub(crate) fn on_rollback(&mut self, compensation: C) {
+        let index = self
+            .registry
+            .lock()
+            .get(&self.id)
+            .map(|record| record.steps.len().saturating_sub(1))
+            .unwrap_or_default();
+        self.compensations.push((index, compensation));
+    }
+
+    pub(crate) fn complete(mut self) {
+        self.finished = true;
+        self.registry.update(&self.id, |record| {
+            for step in &mut record.steps {
+                if step.status == StepStatus::Running {
+                    step.status = StepStatus::Done;
+                }
+            }
+            record.status = OperationStatus::Completed;
+        });
+    }
+
+    /// Undoes the completed steps, newest first, and keeps the operation
+    /// listed as failed. Returns `error` for the caller to propagate.
+    pub(crate) async fn fail<F, Fut>(mut self, error: String, undo: F) -> String
+    where
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<(), String>>,
+    {
+        self.finished = true;
+        let id = self.id.clone();
+        self.registry.update(&id, |record| {
+            if let Some(last) = record.steps.last_mut() {
+                if last.status == StepStatus::Running {
+                    last.status = StepStatus::Failed;
+                    last.error = Some(error.clone());
+                }
+            }
+        });
+        while let Some((index, compensation)) = self.compensations.pop() {
+            let result = undo(compensation).await;
+            self.registry.update(&id, |record| {
+                if let Some(step) = record.steps.get_mut(index) {
+                    match result {
+                        Ok(()) if step.status != StepStatus::RollbackFailed => {
+                            step.status = StepStatus::RolledBack;
+                        }
+                        Ok(()) => {}
+                        Err(rollback_error) => {
+                            step.status = StepStatus::RollbackFailed;
+                            step.error = Some(rollback_error);
+                        }
+                    }
+                }
+            });
+        }
+        self.registry.update(&id, |record| {
+            record.status = OperationStatus::Failed;
+            record.error = Some(error.clone());
+        });
+        error
+    }
+}
+
+impl<C> Drop for Operation<'_, C> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // The caller went away mid-operation; nothing was rolled back.
+        self.registry.update(&self.id, |record| {
+            record.status = OperationStatus::Failed;
+            record.error = Some("Interrupted before finishing.".to_string());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn failed_operations_roll_back_in_reverse_and_stay_listed() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let registry = OperationRegistry::default();
+            let undone = Arc::new(Mutex::new(Vec::new()));
+            let mut operation = registry.begin::<&str>("add_worktree", "ws-1", json!({"a": 1}));
+            operation.step("git worktree add");
+            operation.on_rollback("remove worktree");
+            operation.step("start session");
+            operation.on_rollback("stop session");
+            operation.step("save workspace");
+
+            let error = operation
+                .fail("disk full".to_string(), |compensation| {
+                    let undone = Arc::clone(&undone);
+                    async move {
+                        undone.lock().unwrap().push(compensation);
+                        if compensation == "stop session" {
+                            Err("already gone".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    }
+                })
+                .await;
+            assert_eq!(error, "disk full");
+            assert_eq!(
+                *undone.lock().unwrap(),
+                vec!["stop session", "remove worktree"]
+            );
+
+            let listed = registry.list();
+            assert_eq!(listed.len(), 1);
+            let record = &listed[0];
+            assert_eq!(record.status, OperationStatus::Failed);
+            let statuses: Vec<_> = record.steps.iter().map(|step| step.status).collect();
+            assert_eq!(
+                statuses,
+                [
+                    StepStatus::RolledBack,
+                    StepStatus::RollbackFailed,
+                    StepStatus::Failed
+                ]
+            );
+            assert_eq!(record.params, json!({"a": 1}));
+
+            let taken = registry.take_failed(&record.id).expect("failed op");
+            assert_eq!(taken.kind, "add_worktree");
+            assert!(registry.list().is_empty());
+        });
+    }
+
+    #[test]
+    fn completed_operations_are_dropped_and_running_ones_cannot_be_retried() {
+        let registry = OperationRegistry::default();
+        let mut operation = registry.begin::<()>("add_worktree", "ws-1", Value::Null);
+        operation.step("git worktree add");
+        let id = registry.list()[0].id.clone();
+        assert!(registry.take_failed(&id).is_err());
+        operation.complete();
+        assert!(registry.list().is_empty());
+
+        let operation = registry.begin::<()>("add_worktree", "ws-1", Value::Null);
+        drop(operation);
+        assert_eq!(registry.list()[0].status, OperationStatus::Failed);
+    }
+}
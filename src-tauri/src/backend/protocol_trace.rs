@@ -0,0 +1,297 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+/// A trace file is rotated to `<name>.1` once it grows past this.
+const MAX_TRACE_BYTES: u64 = 8 * 1024 * 1024;
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TraceDirection {
+    /// Written to the app-server's stdin.
+    Sent,
+    /// Read from the app-server's stdout.
+    Received,
+}
+
+impl TraceDirection {
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Sent => ">>",
+            Self::Received => "<<",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProtocolTraceStatus {
+    pub(crate) workspace_id: String,
+    pub(crate) enabled: bool,
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProtocolTraceTail {
+    pub(crate) workspace_id: String,
+    pub(crate) enabled: bool,
+    pub(crate) path: String,
+    /// Oldest first, each `<RFC 3339 time> >>|<< <json>`.
+    pub(crate) lines: Vec<String>,
+}
+
+struct TraceFile {
+    file: File,
+    bytes: u64,
+}
+
+/// Per-workspace trace files of the raw JSON lines exchanged with the
+/// app-server. Tracing is a debugging aid and is off after a restart.
+pub(crate) struct ProtocolTraces {
+    dir: PathBuf,
+    open: Mutex<HashMap<String, TraceFile>>,
+    /// Whether any workspace is traced, so untraced sessions skip the lock.
+    active: AtomicBool,
+}
+
+fn file_name(workspace_id: &str) -> String {
+    let safe: String = workspace_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{safe}.log")
+}
+
+pub(crate) fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+fn open_append(path: &Path) -> Result<TraceFile, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Failed to open trace file: {err}"))?;
+    let bytes = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    Ok(TraceFile { file, bytes })
+}
+
+/// The last `count` lines of `path`, read from the end so large traces stay
+/// cheap to tail.
+pub(crate) fn tail_lines(path: &Path, count: usize) -> Vec<String> {
+    let Ok(mut file) = File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    // Lines are rarely longer than a few KiB; start near the end and widen.
+    let mut window = 64 * 1024_u64;
+    loop {
+        let start = len.saturating_sub(window);
+        if file.seek(SeekFrom::Start(start)).is_err() {
+            return Vec::new();
+        }
+        let mut lines: VecDeque<String> = VecDeque::new();
+        let mut reader = BufReader::new(&mut file);
+        let mut skip_partial = start > 0;
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if skip_partial {
+                skip_partial = false;
+                continue;
+            }
+            let line = String::from_utf8_lossy(&buffer);
+            lines.push_back(line.trim_end_matches(['\r', '\n']).to_string());
+            if lines.len() > count {
+                lines.pop_front();
+            }
+        }
+        if lines.len() >= count || start == 0 {
+            return lines.into();
+        }
+        window *= 4;
+    }
+}
+
+impl ProtocolTraces {
+    /// Trace files are written to `dir`.
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            open: Mutex::new(HashMap::new()),
+            active: AtomicBool::new(false),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, TraceFile>> {
+        self.open
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn path_for(&self, workspace_id: &str) -> PathBuf {
+        self.dir.join(file_name(workspace_id))
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn status(&self, workspace_id: &str) -> ProtocolTraceStatus {
+        ProtocolTraceStatus {
+            workspace_id: workspace_id.to_string(),
+            enabled: self.lock().contains_key(workspace_id),
+            path: self.path_for(workspace_id).to_string_lossy().to_string(),
+        }
+    }
+
+    /// Starts or stops tracing a workspace. Existing trace files are kept
+    /// and appended to.
+    pub(crate) fn set_enabled(
+        &self,
+        workspace_id: &str,
+        enabled: bool,
+    ) -> Result<ProtocolTraceStatus, String> {
+        let mut open = self.lock();
+        if enabled {
+            if !open.contains_key(workspace_id) {
+                std::fs::create_dir_all(&self.dir)
+                    .map_err(|err| format!("Failed to create trace directory: {err}"))?;
+                let trace = open_append(&self.path_for(workspace_id))?;
+                open.insert(workspace_id.to_string(), trace);
+            }
+        } else {
+            open.remove(workspace_id);
+        }
+        self.active.store(!open.is_empty(), Ordering::Relaxed);
+        drop(open);
+        Ok(self.status(workspace_id))
+    }
+
+    /// Appends `line` to the trace of every traced workspace among
+    /// `workspace_ids`. Write failures are ignored; tracing never breaks a
+    /// session.
+    pub(crate) fn record(&self, workspace_ids: &[String], direction: TraceDirection, line: &str) {
+        if !self.is_active() {
+            return;
+        }
+        let mut open = self.lock();
+        let line = line.trim_end_matches(['\r', '\n']);
+        let mut entry: Option<String> = None;
+        for workspace_id in workspace_ids {
+            let Some(trace) = open.get_mut(workspace_id) else {
+                continue;
+            };
+            let entry = entry.get_or_insert_with(|| {
+                format!(
+                    "{} {} {line}\n",
+                    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                    direction.marker()
+                )
+            });
+            if trace.bytes + entry.len() as u64 > MAX_TRACE_BYTES {
+                let path = self.dir.join(file_name(workspace_id));
+                let _ = std::fs::rename(&path, rotated_path(&path));
+                match open_append(&path) {
+                    Ok(fresh) => *trace = fresh,
+                    Err(_) => continue,
+                }
+            }
+            if trace.file.write_all(entry.as_bytes()).is_ok() {
+                trace.bytes += entry.len() as u64;
+            }
+        }
+    }
+
+    /// The newest `lines` trace lines, reaching into the rotated file when
+    /// the current one is short.
+    pub(crate) fn tail(&self, workspace_id: &str, lines: Option<usize>) -> ProtocolTraceTail {
+        let count = lines.unwrap_or(DEFAULT_TAIL_LINES).clamp(1, MAX_TAIL_LINES);
+        let status = self.status(workspace_id);
+        let path = self.path_for(workspace_id);
+        let mut tail = tail_lines(&path, count);
+        if tail.len() < count {
+            let mut older = tail_lines(&rotated_path(&path), count - tail.len());
+            older.append(&mut tail);
+            tail = older;
+        }
+        ProtocolTraceTail {
+            workspace_id: status.workspace_id,
+            enabled: status.enabled,
+            path: status.path,
+            lines: tail,
+        }
+    }
+}
+
+pub(crate) fn set_protocol_trace_core(
+    traces: &ProtocolTraces,
+    workspace_id: &str,
+    enabled: bool,
+) -> Result<ProtocolTraceStatus, String> {
+    traces.set_enabled(workspace_id, enabled)
+}
+
+pub(crate) fn protocol_trace_tail_core(
+    traces: &ProtocolTraces,
+    workspace_id: &str,
+    lines: Option<usize>,
+) -> Result<ProtocolTraceTail, String> {
+    Ok(traces.tail(workspace_id, lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_traced_workspaces_and_tails_across_rotation() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let traces = ProtocolTraces::new(dir.clone());
+        let ids = vec!["ws-1".to_string(), "ws-2".to_string()];
+        traces.record(&ids, TraceDirection::Sent, "{\"id\":0}");
+        assert!(!traces.is_active());
+
+        traces.set_enabled("ws-1", true).expect("enable");
+        traces.record(&ids, TraceDirection::Sent, "{\"id\":1}\n");
+        traces.record(&ids, TraceDirection::Received, "{\"id\":1,\"result\":{}}");
+        let tail = traces.tail("ws-1", None);
+        assert!(tail.enabled);
+        assert_eq!(tail.lines.len(), 2);
+        assert!(tail.lines[0].ends_with(" >> {\"id\":1}"));
+        assert!(tail.lines[1].ends_with(" << {\"id\":1,\"result\":{}}"));
+        assert!(traces.tail("ws-2", None).lines.is_empty());
+
+        // Force a rotation; the tail still reaches the older lines.
+        traces.lock().get_mut("ws-1").expect("open").bytes = MAX_TRACE_BYTES;
+        traces.record(&ids, TraceDirection::Sent, "{\"id\":2}");
+        assert!(rotated_path(&traces.path_for("ws-1")).exists());
+        let tail = traces.tail("ws-1", Some(3));
+        assert_eq!(tail.lines.len(), 3);
+        assert!(tail.lines[2].ends_with(" >> {\"id\":2}"));
+        assert_eq!(traces.tail("ws-1", Some(1)).lines.len(), 1);
+
+        traces.set_enabled("ws-1", false).expect("disable");
+        assert!(!traces.is_active());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::types::{WorkspaceEntry, WorkspaceQuota, WorkspaceQuotaStatus};
+
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// A quota that was just exceeded, with the turns to interrupt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QuotaBreach {
+    pub(crate) workspace_id: String,
+    pub(crate) reason: String,
+    /// `(thread_id, turn_id)` for each running turn of the workspace.
+    pub(crate) interrupts: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct WorkspaceUsage {
+    day: u64,
+    cpu_ms: u64,
+    running_commands: HashSet<String>,
+    active_turns: HashMap<String, String>,
+    paused_reason: Option<String>,
+}
+
+/// Per-workspace CPU and turn usage against the configured quotas. Usage
+/// lives in memory only, so a restart starts the day's CPU count from zero.
+#[derive(Default)]
+pub(crate) struct QuotaTracker {
+    quotas: Mutex<HashMap<String, WorkspaceQuota>>,
+    usage: Mutex<HashMap<String, WorkspaceUsage>>,
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64 / MS_PER_DAY)
+        .unwrap_or(0)
+}
+
+impl WorkspaceUsage {
+    /// Starts a fresh day: CPU is counted from zero and any pause is lifted.
+    fn roll_over(&mut self, day: u64) {
+        if self.day != day {
+            self.day = day;
+            self.cpu_ms = 0;
+            self.paused_reason = None;
+        }
+    }
+
+    fn pause(&mut self, workspace_id: &str, reason: String) -> Option<QuotaBreach> {
+        if self.paused_reason.is_some() {
+            return None;
+        }
+        self.paused_reason = Some(reason.clone());
+        Some(QuotaBreach {
+            workspace_id: workspace_id.to_string(),
+            reason,
+            interrupts: self
+                .active_turns
+                .iter()
+                .map(|(thread_id, turn_id)| (thread_id.clone(), turn_id.clone()))
+                .collect(),
+        })
+    }
+}
+
+fn item_is_command(params: &Value) -> Option<String> {
+    let item = params.get("item")?;
+    if item.get("type").and_then(Value::as_str) != Some("commandExecution") {
+        return None;
+    }
+    item.get("id").and_then(Value::as_str).map(str::to_string)
+}
+
+impl QuotaTracker {
+    /// Records the quota from a workspace's settings; `None` removes it.
+    pub(crate) fn set_quota(&self, workspace_id: &str, quota: Option<WorkspaceQuota>) {
+        let Ok(mut quotas) = self.quotas.lock() else {
+            return;
+        };
+        match quota.filter(|quota| *quota != WorkspaceQuota::default()) {
+            Some(quota) => {
+                quotas.insert(workspace_id.to_string(), quota);
+            }
+            None => {
+                quotas.remove(workspace_id);
+            }
+        }
+    }
+
+    fn quota(&self, workspace_id: &str) -> Option<WorkspaceQuota> {
+        self.quotas.lock().ok()?.get(workspace_id).cloned()
+    }
+
+    /// Whether any workspace has a CPU limit, so samplers can skip `ps`.
+    pub(crate) fn tracks_cpu(&self) -> bool {
+        self.quotas.lock().is_ok_and(|quotas| {
+            quotas
+                .values()
+                .any(|quota| quota.max_cpu_minutes_per_day.is_some())
+        })
+    }
+
+    /// Updates running turns and commands from an app-server notification.
+    pub(crate) fn on_event(
+        &self,
+        workspace_id: &str,
+        method: &str,
+        params: &Value,
+    ) -> Option<QuotaBreach> {
+        let thread_id = params.get("threadId").and_then(Value::as_str);
+        let mut usage = self.usage.lock().ok()?;
+        let entry = usage.entry(workspace_id.to_string()).or_default();
+        entry.roll_over(today());
+        match method {
+            "turn/started" => {
+                let turn_id = params
+                    .get("turn")
+                    .and_then(|turn| turn.get("id"))
+                    .and_then(Value::as_str);
+                if let (Some(thread_id), Some(turn_id)) = (thread_id, turn_id) {
+                    entry
+                        .active_turns
+                        .insert(thread_id.to_string(), turn_id.to_string());
+                }
+                None
+            }
+            "turn/completed" => {
+                if let Some(thread_id) = thread_id {
+                    entry.active_turns.remove(thread_id);
+                }
+                None
+            }
+            "item/completed" => {
+                if let Some(item_id) = item_is_command(params) {
+                    entry.running_commands.remove(&item_id);
+                }
+                None
+            }
+            "item/started" => {
+                let item_id = item_is_command(params)?;
+                entry.running_commands.insert(item_id);
+                let limit = self.quota(workspace_id)?.max_concurrent_commands?;
+                if entry.running_commands.len() as u64 > u64::from(limit) {
+                    let reason = format!(
+                        "{} commands running at once; the limit is {limit}",
+                        entry.running_commands.len()
+                    );
+                    return entry.pause(workspace_id, reason);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits `cpu_ms` of a shared process evenly across the given workspaces
+    /// that have a turn running, and pauses any that go over their daily limit.
+    pub(crate) fn charge_cpu(&self, workspace_ids: &[String], cpu_ms: u64) -> Vec<QuotaBreach> {
+        let Ok(mut usage) = self.usage.lock() else {
+            return Vec::new();
+        };
+        let day = today();
+        let busy: Vec<&String> = workspace_ids
+            .iter()
+            .filter(|id| {
+                usage
+                    .get(*id)
+                    .is_some_and(|entry| !entry.active_turns.is_empty())
+            })
+            .collect();
+        if busy.is_empty() || cpu_ms == 0 {
+            return Vec::new();
+        }
+        let share = cpu_ms / busy.len() as u64;
+        let mut breaches = Vec::new();
+        for workspace_id in busy {
+            let limit = self
+                .quota(workspace_id)
+                .and_then(|quota| quota.max_cpu_minutes_per_day);
+            let entry = usage.entry(workspace_id.clone()).or_default();
+            entry.roll_over(day);
+            entry.cpu_ms += share;
+            if let Some(limit) = limit {
+                let limit_ms = u64::from(limit) * 60 * 1000;
+                if entry.cpu_ms > limit_ms {
+                    let reason = format!(
+                        "used {} CPU minutes today; the limit is {limit}",
+                        entry.cpu_ms / 60_000
+                    );
+                    breaches.extend(entry.pause(workspace_id, reason));
+                }
+            }
+        }
+        breaches
+    }
+
+    /// Fails while the workspace is paused by a quota.
+    pub(crate) fn check(&self, workspace_id: &str) -> Result<(), String> {
+        let Ok(mut usage) = self.usage.lock() else {
+            return Ok(());
+        };
+        let Some(entry) = usage.get_mut(workspace_id) else {
+            return Ok(());
+        };
+        entry.roll_over(today());
+        match entry.paused_reason.as_ref() {
+            Some(reason) => Err(format!("Workspace paused by quota: {reason}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Lifts a pause and clears today's CPU count.
+    pub(crate) fn resume(&self, workspace_id: &str) {
+        if let Ok(mut usage) = self.usage.lock() {
+            if let Some(entry) = usage.get_mut(workspace_id) {
+                entry.paused_reason = None;
+                entry.cpu_ms = 0;
+            }
+        }
+    }
+
+    pub(crate) fn status(&self, workspace_id: &str) -> WorkspaceQuotaStatus {
+        let quota = self.quota(workspace_id);
+        let mut status = WorkspaceQuotaStatus {
+            workspace_id: workspace_id.to_string(),
+            quota,
+            cpu_ms_today: 0,
+            running_commands: 0,
+            paused_reason: None,
+        };
+        if let Ok(mut usage) = self.usage.lock() {
+            if let Some(entry) = usage.get_mut(workspace_id) {
+                entry.roll_over(today());
+                status.cpu_ms_today = entry.cpu_ms;
+                status.running_commands = entry.running_commands.len();
+                status.paused_reason = entry.paused_reason.clone();
+            }
+        }
+        status
+    }
+}
+
+async fn workspace_quota(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<Option<WorkspaceQuota>, String> {
+    workspaces
+        .read()
+        .await
+        .get(workspace_id)
+        .map(|entry| entry.settings.quota.clone())
+        .ok_or_else(|| "workspace not found".to_string())
+}
+
+pub(crate) async fn workspace_quota_status_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    tracker: &QuotaTracker,
+    workspace_id: String,
+) -> Result<WorkspaceQuotaStatus, String> {
+    let quota = workspace_quota(workspaces, &workspace_id).await?;
+    tracker.set_quota(&workspace_id, quota);
+    Ok(tracker.status(&workspace_id))
+}
+
+pub(crate) async fn resume_workspace_quota_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    tracker: &QuotaTracker,
+    workspace_id: String,
+) -> Result<WorkspaceQuotaStatus, String> {
+    let quota = workspace_quota(workspaces, &workspace_id).await?;
+    tracker.set_quota(&workspace_id, quota);
+    tracker.resume(&workspace_id);
+    Ok(tracker.status(&workspace_id))
+}
+
+/// Parses `ps` CPU time: `[[dd-]hh:]mm:ss[.ff]`.
+fn parse_cpu_time(value: &str) -> Option<u64> {
+    let (days, clock) = match value.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, value),
+    };
+    let mut seconds = 0.0_f64;
+    for (index, part) in clock.rsplit(':').enumerate() {
+        let unit = match index {
+            0 => 1.0,
+            1 => 60.0,
+            2 => 3600.0,
+            _ => return None,
+        };
+        seconds += part.parse::<f64>().ok()? * unit;
+    }
+    Some(days * MS_PER_DAY + (seconds * 1000.0) as u64)
+}
+
+/// Total CPU time of `root_pid` and its descendants, from `ps` output lines
+/// of `pid ppid time`.
+fn tree_cpu_ms(ps_output: &str, root_pid: u32) -> u64 {
+    let rows: Vec<(u32, u32, u64)> = ps_output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let cpu = parse_cpu_time(fields.next()?)?;
+            Some((pid, ppid, cpu))
+        })
+        .collect();
+    let mut tree = HashSet::from([root_pid]);
+    loop {
+        let before = tree.len();
+        for (pid, ppid, _) in &rows {
+            if tree.contains(ppid) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            break;
+        }
+    }
+    rows.iter()
+        .filter(|(pid, _, _)| tree.contains(pid))
+        .map(|(_, _, cpu)| cpu)
+        .sum()
+}
+
+/// CPU time used so far by a process tree. Unsupported on Windows.
+pub(crate) async fn process_tree_cpu_ms(root_pid: u32) -> Option<u64> {
+    if cfg!(windows) {
+        return None;
+    }
+    let output = crate::shared::process_core::tokio_command("ps")
+        .args(["-A", "-o", "pid=,ppid=,time="])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(tree_cpu_ms(
+        &String::from_utf8_lossy(&output.stdout),
+        root_pid,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn command_item(method: &str, id: &str) -> (String, Value) {
+        (
+            method.to_string(),
+            json!({ "threadId": "t1", "item": { "type": "commandExecution", "id": id } }),
+        )
+    }
+
+    #[test]
+    fn concurrent_commands_pause_and_interrupt_running_turns() {
+        let tracker = QuotaTracker::default();
+        tracker.set_quota(
+            "ws",
+            Some(WorkspaceQuota {
+                max_concurrent_commands: Some(1),
+                ..WorkspaceQuota::default()
+            }),
+        );
+        tracker.on_event(
+            "ws",
+            "turn/started",
+            &json!({ "threadId": "t1", "turn": { "id": "turn-1" } }),
+        );
+        let (method, params) = command_item("item/started", "c1");
+        assert_eq!(tracker.on_event("ws", &method, &params), None);
+        let (method, params) = command_item("item/started", "c2");
+        let breach = tracker.on_event("ws", &method, &params).expect("breach");
+        assert_eq!(
+            breach.interrupts,
+            vec![("t1".to_string(), "turn-1".to_string())]
+        );
+        assert!(tracker.check("ws").is_err());
+        assert_eq!(tracker.status("ws").running_commands, 2);
+
+        tracker.resume("ws");
+        assert!(tracker.check("ws").is_ok());
+    }
+
+    #[test]
+    fn cpu_is_split_across_busy_workspaces() {
+        let tracker = QuotaTracker::default();
+        tracker.set_quota(
+            "a",
+            Some(WorkspaceQuota {
+                max_cpu_minutes_per_day: Some(1),
+                ..WorkspaceQuota::default()
+            }),
+        );
+        for workspace in ["a", "b"] {
+            tracker.on_event(
+                workspace,
+                "turn/started",
+                &json!({ "threadId": format!("{workspace}-t"), "turn": { "id": "x" } }),
+            );
+        }
+        let ids = vec!["a".to_string(), "b".to_string(), "idle".to_string()];
+        assert!(tracker.charge_cpu(&ids, 100_000).is_empty());
+        assert_eq!(tracker.status("a").cpu_ms_today, 50_000);
+        let breaches = tracker.charge_cpu(&ids, 40_000);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].workspace_id, "a");
+        assert!(tracker.check("b").is_ok());
+    }
+
+    #[test]
+    fn tree_cpu_sums_descendants() {
+        let ps = " 10 1 00:01:00\n 11 10 0:30.50\n 12 11 1-00:00:00\n 13 1 05:00\n";
+        assert_eq!(tree_cpu_ms(ps, 10), 60_000 + 30_500 + MS_PER_DAY);
+        assert_eq!(parse_cpu_time("01:02:03"), Some(3_723_000));
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::run_manager::RunManager;
+
+/// Samples kept per window for the burn rate.
+const MAX_SAMPLES: usize = 32;
+/// Usage must be observed over at least this long before forecasting.
+const MIN_FORECAST_SPAN_MS: u64 = 60_000;
+const MS_PER_HOUR: f64 = 60.0 * 60.0 * 1000.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RateLimitWindowKind {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RateLimitLevel {
+    Ok,
+    Warn,
+    Queue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitWindowForecast {
+    pub(crate) window: RateLimitWindowKind,
+    pub(crate) used_percent: f64,
+    pub(crate) remaining_percent: f64,
+    pub(crate) window_duration_mins: Option<u64>,
+    /// As reported by the app-server (seconds since the epoch).
+    pub(crate) resets_at: Option<i64>,
+    /// Percent of the window used per hour over the recent updates.
+    pub(crate) burn_rate_per_hour: Option<f64>,
+    /// When the window runs out at that rate (ms since the epoch); `None`
+    /// when it would reset first or usage is not growing.
+    pub(crate) exhausts_at: Option<u64>,
+    pub(crate) level: RateLimitLevel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitForecast {
+    /// When the last rate-limit update arrived (ms since the epoch).
+    pub(crate) updated_at: Option<u64>,
+    pub(crate) warn_percent: Option<u8>,
+    pub(crate) queue_percent: Option<u8>,
+    /// The most severe level across windows.
+    pub(crate) level: RateLimitLevel,
+    pub(crate) windows: Vec<RateLimitWindowForecast>,
+    /// Turns currently held back by `queue_percent`.
+    pub(crate) waiting_turns: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct WindowState {
+    used_percent: f64,
+    window_duration_mins: Option<u64>,
+    resets_at: Option<i64>,
+    /// `(at_ms, used_percent)` since the window last reset.
+    samples: VecDeque<(u64, f64)>,
+}
+
+#[derive(Default)]
+struct Limits {
+    warn_percent: Option<u8>,
+    queue_percent: Option<u8>,
+    updated_at: Option<u64>,
+    primary: Option<WindowState>,
+    secondary: Option<WindowState>,
+}
+
+/// Keeps the latest account rate limits and rates each window against the
+/// thresholds from app settings. Rate limits belong to the account, so the
+/// run manager owns one tracker and feeds it updates from every session.
+#[derive(Default)]
+pub(crate) struct RateLimitTracker {
+    limits: Mutex<Limits>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Reset times arrive in seconds; tolerate milliseconds too.
+fn epoch_ms(value: i64) -> u64 {
+    let value = value.max(0) as u64;
+    if value < 100_000_000_000 {
+        value * 1000
+    } else {
+        value
+    }
+}
+
+fn number(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_f64))
+}
+
+impl WindowState {
+    fn update(&mut self, source: &Value, at: u64) {
+        let Some(used) = number(source, &["usedPercent", "used_percent"]).or_else(|| {
+            number(source, &["remainingPercent", "remaining_percent"]).map(|left| 100.0 - left)
+        }) else {
+            return;
+        };
+        let used = used.clamp(0.0, 100.0);
+        let resets_at = number(source, &["resetsAt", "resets_at"]).map(|value| value as i64);
+        // A drop in usage or a new reset time starts a new window.
+        if used < self.used_percent || (resets_at.is_some() && resets_at != self.resets_at) {
+            self.samples.clear();
+        }
+        self.used_percent = used;
+        self.resets_at = resets_at.or(self.resets_at);
+        if let Some(minutes) = number(source, &["windowDurationMins", "window_duration_mins"]) {
+            self.window_duration_mins = Some(minutes.max(0.0) as u64);
+        }
+        self.samples.push_back((at, used));
+        while self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Usage as of `now`: a window whose reset time has passed is empty.
+    fn current_used(&self, now: u64) -> f64 {
+        match self.resets_at {
+            Some(resets_at) if epoch_ms(resets_at) <= now => 0.0,
+            _ => self.used_percent,
+        }
+    }
+
+    fn forecast(
+        &self,
+        window: RateLimitWindowKind,
+        now: u64,
+        warn_percent: Option<u8>,
+        queue_percent: Option<u8>,
+    ) -> RateLimitWindowForecast {
+        let used = self.current_used(now);
+        let burn_rate_per_ms = match (self.samples.front(), self.samples.back()) {
+            (Some(&(first_at, first_used)), Some(&(last_at, last_used)))
+                if last_at.saturating_sub(first_at) >= MIN_FORECAST_SPAN_MS
+                    && last_used > first_used
+                    && used > 0.0 =>
+            {
+                Some((last_used - first_used) / (last_at - first_at) as f64)
+            }
+            _ => None,
+        };
+        let exhausts_at = burn_rate_per_ms.and_then(|rate| {
+            let at = now + ((100.0 - used) / rate) as u64;
+            let before_reset = self
+                .resets_at
+                .is_none_or(|resets_at| at < epoch_ms(resets_at));
+            before_reset.then_some(at)
+        });
+        let reached = |threshold: Option<u8>| threshold.is_some_and(|t| used >= f64::from(t));
+        let level = if reached(queue_percent) {
+            RateLimitLevel::Queue
+        } else if reached(warn_percent) {
+            RateLimitLevel::Warn
+        } else {
+            RateLimitLevel::Ok
+        };
+        RateLimitWindowForecast {
+            window,
+            used_percent: used,
+            remaining_percent: 100.0 - used,
+            window_duration_mins: self.window_duration_mins,
+            resets_at: self.resets_at,
+            burn_rate_per_hour: burn_rate_per_ms.map(|rate| rate * MS_PER_HOUR),
+            exhausts_at,
+            level,
+        }
+    }
+}
+
+impl Limits {
+    fn forecast(&self, now: u64, waiting_turns: usize) -> RateLimitForecast {
+        let windows: Vec<RateLimitWindowForecast> = [
+            (RateLimitWindowKind::Primary, &self.primary),
+            (RateLimitWindowKind::Secondary, &self.secondary),
+        ]
+        .into_iter()
+        .filter_map(|(kind, state)| {
+            state
+                .as_ref()
+                .map(|state| state.forecast(kind, now, self.warn_percent, self.queue_percent))
+        })
+        .collect();
+        RateLimitForecast {
+            updated_at: self.updated_at,
+            warn_percent: self.warn_percent,
+            queue_percent: self.queue_percent,
+            level: windows
+                .iter()
+                .map(|window| window.level)
+                .max()
+                .unwrap_or(RateLimitLevel::Ok),
+            windows,
+            waiting_turns,
+        }
+    }
+
+    fn record(&mut self, rate_limits: &Value, at: u64) {
+        for (key, slot) in [
+            ("primary", &mut self.primary),
+            ("secondary", &mut self.secondary),
+        ] {
+            match rate_limits.get(key) {
+                Some(Value::Null) => *slot = None,
+                Some(source) if source.is_object() => {
+                    slot.get_or_insert_with(WindowState::default)
+                        .update(source, at);
+                }
+                _ => {}
+            }
+        }
+        self.updated_at = Some(at);
+    }
+}
+
+impl RateLimitTracker {
+    fn lock(&self) -> MutexGuard<'_, Limits> {
+        self.limits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn set_thresholds(&self, warn_percent: Option<u8>, queue_percent: Option<u8>) {
+        let mut limits = self.lock();
+        limits.warn_percent = warn_percent;
+        limits.queue_percent = queue_percent;
+    }
+
+    /// Records a `rateLimits` object from an update or a read. Returns
+    /// whether it was one.
+    pub(crate) fn record(&self, rate_limits: &Value) -> bool {
+        if !rate_limits.is_object() {
+            return false;
+        }
+        self.lock().record(rate_limits, now_ms());
+        true
+    }
+
+    pub(crate) fn on_event(&self, method: &str, params: &Value) -> bool {
+        if method != "account/rateLimits/updated" {
+            return false;
+        }
+        params
+            .get("rateLimits")
+            .or_else(|| params.get("rate_limits"))
+            .is_some_and(|rate_limits| self.record(rate_limits))
+    }
+
+    /// `waiting_turns` is the number of turns the run manager holds back.
+    pub(crate) fn forecast(&self, waiting_turns: usize) -> RateLimitForecast {
+        self.lock().forecast(now_ms(), waiting_turns)
+    }
+}
+
+pub(crate) fn rate_limit_forecast_core(manager: &RunManager) -> RateLimitForecast {
+    manager.rate_limit_forecast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(used: f64, resets_at: i64) -> Value {
+        json!({
+            "primary": { "usedPercent": used, "windowDurationMins": 300, "resetsAt": resets_at },
+            "secondary": null
+        })
+    }
+
+    #[test]
+    fn forecast_projects_exhaustion_from_recent_updates() {
+        let start = 1_700_000_000_000;
+        let resets_at = (start / 1000 + 5 * 3600) as i64;
+        let mut limits = Limits {
+            warn_percent: Some(50),
+            queue_percent: Some(90),
+            ..Limits::default()
+        };
+        limits.record(&update(40.0, resets_at), start);
+        limits.record(&update(60.0, resets_at), start + 3_600_000);
+
+        let forecast = limits.forecast(start + 3_600_000, 0);
+        assert_eq!(forecast.level, RateLimitLevel::Warn);
+        assert_eq!(forecast.windows.len(), 1);
+        let window = &forecast.windows[0];
+        assert_eq!(window.remaining_percent, 40.0);
+        assert_eq!(window.burn_rate_per_hour, Some(20.0));
+        assert_eq!(window.exhausts_at, Some(start + 3 * 3_600_000));
+
+        // Past the reset time the window counts as empty.
+        let later = limits.forecast(epoch_ms(resets_at) + 1, 0);
+        assert_eq!(later.level, RateLimitLevel::Ok);
+        assert_eq!(later.windows[0].used_percent, 0.0);
+    }
+
+    #[test]
+    fn a_new_window_drops_old_samples() {
+        let start = 1_700_000_000_000;
+        let mut limits = Limits {
+            queue_percent: Some(80),
+            ..Limits::default()
+        };
+        limits.record(&update(85.0, 1_700_010_000), start);
+        assert_eq!(limits.forecast(start, 0).level, RateLimitLevel::Queue);
+        limits.record(&update(5.0, 1_700_030_000), start + 120_000);
+        let forecast = limits.forecast(start + 120_000, 0);
+        assert_eq!(forecast.level, RateLimitLevel::Ok);
+        assert_eq!(forecast.windows[0].burn_rate_per_hour, None);
+        assert_eq!(limits.primary.as_ref().map(|w| w.samples.len()), Some(1));
+    }
+}
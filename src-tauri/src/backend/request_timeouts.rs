@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::types::RequestTimeoutSettings;
+
+/// Applied when neither settings nor the built-in table name a method.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const LIST_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why a request stopped waiting for its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RequestWaitError {
+    /// The response channel was dropped, e.g. the session shut down or the
+    /// caller canceled the request.
+    Canceled,
+    TimedOut {
+        method: String,
+        after: Duration,
+    },
+}
+
+impl fmt::Display for RequestWaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Canceled => write!(f, "request canceled"),
+            Self::TimedOut { method, after } => write!(
+                f,
+                "request timed out: {method} did not respond within {} seconds",
+                after.as_secs()
+            ),
+        }
+    }
+}
+
+fn builtin_method_timeout(method: &str) -> Option<Duration> {
+    method.ends_with("/list").then_some(LIST_REQUEST_TIMEOUT)
+}
+
+fn timeout_from_secs(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[derive(Default)]
+struct RequestTimeoutConfig {
+    app: RequestTimeoutSettings,
+    workspaces: HashMap<String, RequestTimeoutSettings>,
+}
+
+/// Request timeouts for one session. A session can serve several workspaces,
+/// so workspace overrides are keyed by workspace id.
+#[derive(Default)]
+pub(crate) struct RequestTimeouts {
+    config: RwLock<RequestTimeoutConfig>,
+}
+
+impl RequestTimeouts {
+    pub(crate) fn configure(
+        &self,
+        app: RequestTimeoutSettings,
+        workspaces: HashMap<String, RequestTimeoutSettings>,
+    ) {
+        let mut config = self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *config = RequestTimeoutConfig { app, workspaces };
+    }
+
+    /// Resolves the timeout for `method`, or `None` to wait indefinitely.
+    /// Method entries win over the built-in table, which wins over the
+    /// configured defaults; workspace settings win over app settings.
+    pub(crate) fn resolve(&self, workspace_id: &str, method: &str) -> Option<Duration> {
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let workspace = config.workspaces.get(workspace_id);
+        if let Some(secs) = workspace
+            .and_then(|settings| settings.methods.get(method))
+            .or_else(|| config.app.methods.get(method))
+        {
+            return timeout_from_secs(*secs);
+        }
+        if let Some(timeout) = builtin_method_timeout(method) {
+            return Some(timeout);
+        }
+        match workspace
+            .and_then(|settings| settings.default_secs)
+            .or(config.app.default_secs)
+        {
+            Some(secs) => timeout_from_secs(secs),
+            None => Some(DEFAULT_REQUEST_TIMEOUT),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_prefers_workspace_then_app_then_builtin() {
+        let timeouts = RequestTimeouts::default();
+        assert_eq!(
+            timeouts.resolve("ws-1", "turn/start"),
+            Some(DEFAULT_REQUEST_TIMEOUT)
+        );
+        assert_eq!(
+            timeouts.resolve("ws-1", "thread/list"),
+            Some(LIST_REQUEST_TIMEOUT)
+        );
+
+        timeouts.configure(
+            RequestTimeoutSettings {
+                default_secs: Some(600),
+                methods: HashMap::from([("thread/list".to_string(), 90)]),
+            },
+            HashMap::from([(
+                "ws-1".to_string(),
+                RequestTimeoutSettings {
+                    default_secs: Some(0),
+                    methods: HashMap::from([("thread/list".to_string(), 5)]),
+                },
+            )]),
+        );
+        assert_eq!(
+            timeouts.resolve("ws-1", "thread/list"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(timeouts.resolve("ws-1", "turn/start"), None);
+        assert_eq!(
+            timeouts.resolve("ws-2", "thread/list"),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            timeouts.resolve("ws-2", "model/list"),
+            Some(LIST_REQUEST_TIMEOUT)
+        );
+        assert_eq!(
+            timeouts.resolve("ws-2", "turn/start"),
+            Some(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn wait_errors_distinguish_timeout_from_cancel() {
+        assert_eq!(RequestWaitError::Canceled.to_string(), "request canceled");
+        assert_eq!(
+            RequestWaitError::TimedOut {
+                method: "model/list".to_string(),
+                after: Duration::from_secs(30),
+            }
+            .to_string(),
+            "request timed out: model/list did not respond within 30 seconds"
+        );
+    }
+}
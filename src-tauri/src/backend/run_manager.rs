@@ -0,0 +1,728 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::rate_limits::{
+    RateLimitForecast, RateLimitLevel, RateLimitTracker, RateLimitWindowForecast,
+};
+use crate::types::TurnConcurrencyPolicy;
+
+const PREVIEW_CHARS: usize = 120;
+/// How often turns held back by the rate limit re-check even without a
+/// rate-limit update, so a passed reset time lets them through.
+const RATE_LIMIT_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+type EventListener = Box<dyn Fn(AppServerEvent) + Send + Sync>;
+
+/// Sends a queued turn's request once it is admitted. Resolves to the error
+/// when the turn could not start; its slot is released either way.
+pub(crate) type StartRun =
+    Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send>;
+
+/// Why a queued run has not started yet.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum QueueBlock {
+    /// `maxConcurrentTurns` turns are running.
+    RunLimit,
+    /// The workspace's `turnConcurrency` is `queue` and it has a running turn.
+    WorkspaceTurn,
+    /// A rate-limit window is at `rateLimitQueuePercent`.
+    RateLimit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QueuedRun {
+    pub(crate) id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    /// The start of the queued message.
+    pub(crate) preview: String,
+    /// 1-based place in the queue.
+    pub(crate) position: usize,
+    pub(crate) enqueued_at: u64,
+    pub(crate) blocked_by: QueueBlock,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunQueueSnapshot {
+    pub(crate) max_concurrent_turns: Option<u32>,
+    pub(crate) running: usize,
+    pub(crate) queued: Vec<QueuedRun>,
+}
+
+/// Returned when a workspace already has a running turn and its
+/// `turnConcurrency` is `reject`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TurnBusy {
+    pub(crate) active_thread_id: String,
+    pub(crate) queued: usize,
+}
+
+impl fmt::Display for TurnBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "agent busy: thread {} is still running a turn in this workspace ({} queued ahead of this message)",
+            self.active_thread_id, self.queued
+        )
+    }
+}
+
+/// A turn asking to start, from any entry point that starts one.
+pub(crate) struct TurnTicket<'a> {
+    pub(crate) workspace_id: &'a str,
+    pub(crate) thread_id: &'a str,
+    /// Shown as the queued run's preview.
+    pub(crate) text: &'a str,
+    pub(crate) policy: TurnConcurrencyPolicy,
+}
+
+/// What [`RunManager::admit`] decided for a turn.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RunAdmission {
+    /// The turn holds a slot and the caller starts it now. Carries the window
+    /// past `rateLimitWarnPercent`, if any.
+    Started {
+        rate_limit_warning: Option<RateLimitWindowForecast>,
+    },
+    /// The turn waits in the queue and starts from the manager later.
+    Queued(QueuedRun),
+}
+
+struct Waiter {
+    run: QueuedRun,
+    /// Waits for the workspace's running turn as well as a free slot.
+    serialized: bool,
+    start: StartRun,
+}
+
+#[derive(Default)]
+struct Runs {
+    limit: Option<u32>,
+    /// thread id -> workspace id of every turn holding a slot.
+    running: HashMap<String, String>,
+    waiters: VecDeque<Waiter>,
+    /// A rate-limit re-check is already scheduled.
+    recheck_scheduled: bool,
+}
+
+impl Runs {
+    fn has_capacity(&self) -> bool {
+        self.limit
+            .is_none_or(|limit| self.running.len() < limit.max(1) as usize)
+    }
+
+    /// The thread running a turn in `workspace_id`, if any.
+    fn workspace_turn(&self, workspace_id: &str) -> Option<&str> {
+        self.running
+            .iter()
+            .find(|(_, running_workspace)| running_workspace.as_str() == workspace_id)
+            .map(|(thread_id, _)| thread_id.as_str())
+    }
+
+    fn blocker(
+        &self,
+        workspace_id: &str,
+        serialized: bool,
+        rate_limited: bool,
+    ) -> Option<QueueBlock> {
+        if rate_limited {
+            Some(QueueBlock::RateLimit)
+        } else if serialized && self.workspace_turn(workspace_id).is_some() {
+            Some(QueueBlock::WorkspaceTurn)
+        } else if !self.has_capacity() {
+            Some(QueueBlock::RunLimit)
+        } else {
+            None
+        }
+    }
+
+    fn renumber(&mut self) {
+        for (index, waiter) in self.waiters.iter_mut().enumerate() {
+            waiter.run.position = index + 1;
+        }
+    }
+}
+
+/// Events and starts collected under the lock and handled after it.
+#[derive(Default)]
+struct Admitted {
+    events: Vec<AppServerEvent>,
+    starts: Vec<(QueuedRun, StartRun)>,
+    schedule_recheck: bool,
+}
+
+/// The single admission path for turns. Every entry point that starts a turn
+/// (messages, `/review`, `/compact`, broadcasts, playbooks, scheduled
+/// messages) asks here first. A turn starts right away unless a rate-limit
+/// window is at `rateLimitQueuePercent`, its workspace serializes turns and
+/// has one running, or `maxConcurrentTurns` turns are running; then it waits
+/// in FIFO order and starts from the manager once nothing blocks it.
+#[derive(Default)]
+pub(crate) struct RunManager {
+    runs: Mutex<Runs>,
+    rate_limits: RateLimitTracker,
+    listener: RwLock<Option<EventListener>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn preview(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    let mut preview: String = line.chars().take(PREVIEW_CHARS).collect();
+    if line.chars().count() > PREVIEW_CHARS {
+        preview.push('…');
+    }
+    preview
+}
+
+fn position_event(run: &QueuedRun) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: run.workspace_id.clone(),
+        message: json!({
+            "method": "queue/position",
+            "params": {
+                "queueId": run.id,
+                "threadId": run.thread_id,
+                "position": run.position,
+                "blockedBy": run.blocked_by,
+            }
+        }),
+    }
+}
+
+fn waiting_event(
+    workspace_id: &str,
+    thread_id: &str,
+    window: Option<&RateLimitWindowForecast>,
+) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "rateLimit/waiting",
+            "params": {
+                "threadId": thread_id,
+                "waiting": window.is_some(),
+                "window": window,
+            }
+        }),
+    }
+}
+
+/// The most severe window when it is at the queue threshold.
+fn holding_window(forecast: &RateLimitForecast) -> Option<&RateLimitWindowForecast> {
+    forecast
+        .windows
+        .iter()
+        .find(|window| window.level == RateLimitLevel::Queue)
+}
+
+impl RunManager {
+    fn lock(&self) -> MutexGuard<'_, Runs> {
+        self.runs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Where `queue/*` and `rateLimit/waiting` events go.
+    pub(crate) fn set_event_sink<E: EventSink>(&self, event_sink: E) {
+        if let Ok(mut listener) = self.listener.write() {
+            *listener = Some(Box::new(move |event| {
+                event_sink.emit_app_server_event(event)
+            }));
+        }
+    }
+
+    fn emit(&self, events: Vec<AppServerEvent>) {
+        let Ok(listener) = self.listener.read() else {
+            return;
+        };
+        if let Some(listener) = listener.as_ref() {
+            for event in events {
+                listener(event);
+            }
+        }
+    }
+
+    fn rate_limit_forecast_with(&self, runs: &Runs) -> RateLimitForecast {
+        let waiting_turns = runs
+            .waiters
+            .iter()
+            .filter(|waiter| waiter.run.blocked_by == QueueBlock::RateLimit)
+            .count();
+        self.rate_limits.forecast(waiting_turns)
+    }
+
+    /// Applies `AppSettings.max_concurrent_turns`; raising it starts waiters.
+    pub(crate) fn set_limit(self: &Arc<Self>, limit: Option<u32>) {
+        self.lock().limit = limit;
+        self.start_ready();
+    }
+
+    /// Applies `AppSettings.rate_limit_warn_percent` and
+    /// `rate_limit_queue_percent`; held-back turns re-check right away.
+    pub(crate) fn set_rate_limit_thresholds(
+        self: &Arc<Self>,
+        warn_percent: Option<u8>,
+        queue_percent: Option<u8>,
+    ) {
+        self.rate_limits.set_thresholds(warn_percent, queue_percent);
+        self.start_ready();
+    }
+
+    /// Records a `rateLimits` object from an `account/rateLimits/read`.
+    pub(crate) fn record_rate_limits(self: &Arc<Self>, rate_limits: &Value) {
+        if self.rate_limits.record(rate_limits) {
+            self.start_ready();
+        }
+    }
+
+    /// Records `account/rateLimits/updated` notifications.
+    pub(crate) fn on_rate_limit_event(self: &Arc<Self>, method: &str, params: &Value) {
+        if self.rate_limits.on_event(method, params) {
+            self.start_ready();
+        }
+    }
+
+    pub(crate) fn rate_limit_forecast(&self) -> RateLimitForecast {
+        let runs = self.lock();
+        self.rate_limit_forecast_with(&runs)
+    }
+
+    /// Starts every waiter nothing blocks any more, in queue order.
+    fn start_ready(self: &Arc<Self>) {
+        let admitted = {
+            let mut runs = self.lock();
+            self.admit_waiters(&mut runs)
+        };
+        self.finish(admitted);
+    }
+
+    fn admit_waiters(&self, runs: &mut Runs) -> Admitted {
+        let forecast = self.rate_limit_forecast_with(runs);
+        let holding = holding_window(&forecast);
+        let mut admitted = Admitted::default();
+        let mut index = 0;
+        while index < runs.waiters.len() {
+            let waiter = &runs.waiters[index];
+            let blocker = runs.blocker(
+                &waiter.run.workspace_id,
+                waiter.serialized,
+                holding.is_some(),
+            );
+            if let Some(blocker) = blocker {
+                let run = &mut runs.waiters[index].run;
+                if run.blocked_by != blocker {
+                    if blocker == QueueBlock::RateLimit {
+                        admitted.events.push(waiting_event(
+                            &run.workspace_id,
+                            &run.thread_id,
+                            holding,
+                        ));
+                    }
+                    run.blocked_by = blocker;
+                    admitted.events.push(position_event(run));
+                }
+                index += 1;
+                continue;
+            }
+            let Some(waiter) = runs.waiters.remove(index) else {
+                break;
+            };
+            let mut run = waiter.run;
+            runs.running
+                .insert(run.thread_id.clone(), run.workspace_id.clone());
+            if run.blocked_by == QueueBlock::RateLimit {
+                admitted
+                    .events
+                    .push(waiting_event(&run.workspace_id, &run.thread_id, None));
+            }
+            run.position = 0;
+            admitted.events.push(position_event(&run));
+            admitted.starts.push((run, waiter.start));
+        }
+        if !admitted.starts.is_empty() {
+            runs.renumber();
+            admitted.events.extend(
+                runs.waiters
+                    .iter()
+                    .map(|waiter| position_event(&waiter.run)),
+            );
+        }
+        if holding.is_some() && !runs.waiters.is_empty() && !runs.recheck_scheduled {
+            runs.recheck_scheduled = true;
+            admitted.schedule_recheck = true;
+        }
+        admitted
+    }
+
+    /// Emits the collected events and starts the admitted turns. A turn that
+    /// fails to start frees its slot and reports `queue/failed`, since nobody
+    /// is waiting on its result.
+    fn finish(self: &Arc<Self>, admitted: Admitted) {
+        self.emit(admitted.events);
+        for (run, start) in admitted.starts {
+            let manager = Arc::clone(self);
+            tokio::spawn(async move {
+                if let Err(error) = start().await {
+                    manager.release_thread(&run.thread_id);
+                    manager.emit(vec![AppServerEvent {
+                        workspace_id: run.workspace_id.clone(),
+                        message: json!({
+                            "method": "queue/failed",
+                            "params": {
+                                "queueId": run.id,
+                                "threadId": run.thread_id,
+                                "error": error,
+                            }
+                        }),
+                    }]);
+                }
+            });
+        }
+        if admitted.schedule_recheck {
+            let manager = Arc::clone(self);
+            tokio::spawn(async move {
+                tokio::time::sleep(RATE_LIMIT_RECHECK_INTERVAL).await;
+                manager.lock().recheck_scheduled = false;
+                manager.start_ready();
+            });
+        }
+    }
+
+    /// Takes a slot for a turn when nothing blocks it. Otherwise the turn
+    /// joins the queue and `start` runs once it is admitted, so the caller
+    /// returns right away with the queue id and position. A `reject` policy
+    /// fails instead of queueing behind the workspace's running turn.
+    pub(crate) fn admit(
+        self: &Arc<Self>,
+        ticket: TurnTicket<'_>,
+        start: StartRun,
+    ) -> Result<RunAdmission, String> {
+        let serialized = ticket.policy != TurnConcurrencyPolicy::Parallel;
+        let (admitted, run) = {
+            let mut runs = self.lock();
+            let forecast = self.rate_limit_forecast_with(&runs);
+            let holding = holding_window(&forecast);
+            if ticket.policy == TurnConcurrencyPolicy::Reject {
+                if let Some(active) = runs.workspace_turn(ticket.workspace_id) {
+                    return Err(TurnBusy {
+                        active_thread_id: active.to_string(),
+                        queued: runs
+                            .waiters
+                            .iter()
+                            .filter(|waiter| waiter.run.workspace_id == ticket.workspace_id)
+                            .count(),
+                    }
+                    .to_string());
+                }
+            }
+            let Some(blocker) = runs.blocker(ticket.workspace_id, serialized, holding.is_some())
+            else {
+                runs.running.insert(
+                    ticket.thread_id.to_string(),
+                    ticket.workspace_id.to_string(),
+                );
+                let rate_limit_warning = forecast
+                    .windows
+                    .iter()
+                    .find(|window| window.level == RateLimitLevel::Warn)
+                    .cloned();
+                return Ok(RunAdmission::Started { rate_limit_warning });
+            };
+            let run = QueuedRun {
+                id: uuid::Uuid::new_v4().to_string(),
+                workspace_id: ticket.workspace_id.to_string(),
+                thread_id: ticket.thread_id.to_string(),
+                preview: preview(ticket.text),
+                position: runs.waiters.len() + 1,
+                enqueued_at: now_ms(),
+                blocked_by: blocker,
+            };
+            let mut admitted = Admitted::default();
+            if blocker == QueueBlock::RateLimit {
+                admitted.events.push(waiting_event(
+                    ticket.workspace_id,
+                    ticket.thread_id,
+                    holding,
+                ));
+                if !runs.recheck_scheduled {
+                    runs.recheck_scheduled = true;
+                    admitted.schedule_recheck = true;
+                }
+            }
+            admitted.events.push(position_event(&run));
+            runs.waiters.push_back(Waiter {
+                run: run.clone(),
+                serialized,
+                start,
+            });
+            (admitted, run)
+        };
+        self.finish(admitted);
+        Ok(RunAdmission::Queued(run))
+    }
+
+    /// Moves the slot held by `thread_id` to `new_thread_id`, for a detached
+    /// review whose turn completes on the review thread.
+    pub(crate) fn rekey_thread(&self, thread_id: &str, new_thread_id: &str) {
+        let mut runs = self.lock();
+        if let Some(workspace_id) = runs.running.remove(thread_id) {
+            runs.running.insert(new_thread_id.to_string(), workspace_id);
+        }
+    }
+
+    /// Frees the slot held by `thread_id`, if any.
+    pub(crate) fn release_thread(self: &Arc<Self>, thread_id: &str) {
+        if self.lock().running.remove(thread_id).is_some() {
+            self.start_ready();
+        }
+    }
+
+    /// Frees every slot held by `workspace_ids`, for when their session exits.
+    pub(crate) fn release_workspaces(self: &Arc<Self>, workspace_ids: &[String]) {
+        self.lock()
+            .running
+            .retain(|_, workspace_id| !workspace_ids.contains(workspace_id));
+        self.start_ready();
+    }
+
+    pub(crate) fn snapshot(&self) -> RunQueueSnapshot {
+        let runs = self.lock();
+        RunQueueSnapshot {
+            max_concurrent_turns: runs.limit,
+            running: runs.running.len(),
+            queued: runs
+                .waiters
+                .iter()
+                .map(|waiter| waiter.run.clone())
+                .collect(),
+        }
+    }
+
+    /// Drops a queued message before it starts.
+    pub(crate) fn cancel(&self, queue_id: &str) -> Result<(), String> {
+        let events = {
+            let mut runs = self.lock();
+            let index = runs
+                .waiters
+                .iter()
+                .position(|waiter| waiter.run.id == queue_id)
+                .ok_or_else(|| format!("Queued message {queue_id} not found"))?;
+            let canceled = runs.waiters.remove(index).map(|waiter| waiter.run);
+            runs.renumber();
+            let mut events: Vec<AppServerEvent> = canceled
+                .map(|run| AppServerEvent {
+                    workspace_id: run.workspace_id.clone(),
+                    message: json!({
+                        "method": "queue/canceled",
+                        "params": { "queueId": run.id, "threadId": run.thread_id }
+                    }),
+                })
+                .into_iter()
+                .collect();
+            events.extend(
+                runs.waiters
+                    .iter()
+                    .map(|waiter| position_event(&waiter.run)),
+            );
+            events
+        };
+        self.emit(events);
+        Ok(())
+    }
+}
+
+pub(crate) fn queue_list_core(manager: &RunManager) -> RunQueueSnapshot {
+    manager.snapshot()
+}
+
+pub(crate) fn queue_cancel_core(manager: &RunManager, queue_id: &str) -> Result<(), String> {
+    manager.cancel(queue_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// A start that reports `label` on `started` and fails when asked to.
+    fn start(started: &mpsc::UnboundedSender<&'static str>, label: &'static str) -> StartRun {
+        let started = started.clone();
+        Box::new(move || {
+            Box::pin(async move {
+                let _ = started.send(label);
+                if label.starts_with("fail") {
+                    Err("turn/start failed".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+        })
+    }
+
+    fn ticket<'a>(
+        workspace_id: &'a str,
+        thread_id: &'a str,
+        policy: TurnConcurrencyPolicy,
+    ) -> TurnTicket<'a> {
+        TurnTicket {
+            workspace_id,
+            thread_id,
+            text: thread_id,
+            policy,
+        }
+    }
+
+    fn parallel<'a>(workspace_id: &'a str, thread_id: &'a str) -> TurnTicket<'a> {
+        ticket(workspace_id, thread_id, TurnConcurrencyPolicy::Parallel)
+    }
+
+    fn queued(admission: Result<RunAdmission, String>) -> QueuedRun {
+        match admission {
+            Ok(RunAdmission::Queued(run)) => run,
+            other => panic!("expected the turn to queue, got {other:?}"),
+        }
+    }
+
+    fn started(admission: Result<RunAdmission, String>) -> bool {
+        matches!(admission, Ok(RunAdmission::Started { .. }))
+    }
+
+    #[test]
+    fn turns_over_the_limit_start_later_in_order() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let manager = Arc::new(RunManager::default());
+            let (tx, mut started_rx) = mpsc::unbounded_channel();
+            manager.set_limit(Some(1));
+            assert!(started(
+                manager.admit(parallel("ws-1", "thread-a"), start(&tx, "a"))
+            ));
+            let second = queued(manager.admit(parallel("ws-2", "thread-b"), start(&tx, "b")));
+            let third = queued(manager.admit(parallel("ws-1", "thread-c"), start(&tx, "c")));
+            assert_eq!((second.position, third.position), (1, 2));
+            assert_eq!(third.blocked_by, QueueBlock::RunLimit);
+
+            let snapshot = manager.snapshot();
+            assert_eq!(snapshot.running, 1);
+            assert_eq!(snapshot.queued[1].preview, "thread-c");
+
+            manager.release_thread("thread-a");
+            assert_eq!(started_rx.recv().await, Some("b"));
+            assert_eq!(manager.snapshot().queued[0].position, 1);
+
+            manager.release_workspaces(&["ws-2".to_string()]);
+            assert_eq!(started_rx.recv().await, Some("c"));
+            assert!(manager.snapshot().queued.is_empty());
+            assert!(started_rx.try_recv().is_err());
+        });
+    }
+
+    #[test]
+    fn canceled_or_failed_runs_leave_the_queue() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let manager = Arc::new(RunManager::default());
+            let (tx, mut started_rx) = mpsc::unbounded_channel();
+            manager.set_limit(Some(1));
+            assert!(started(
+                manager.admit(parallel("ws", "thread-a"), start(&tx, "a"))
+            ));
+            let canceled = queued(manager.admit(parallel("ws", "thread-b"), start(&tx, "b")));
+            queued(manager.admit(parallel("ws", "thread-c"), start(&tx, "fail-c")));
+            queued(manager.admit(parallel("ws", "thread-d"), start(&tx, "d")));
+
+            manager.cancel(&canceled.id).expect("cancel");
+            assert!(manager.cancel(&canceled.id).is_err());
+            assert_eq!(manager.snapshot().queued[0].position, 1);
+
+            // The failed start frees its slot for the next waiter.
+            manager.release_thread("thread-a");
+            assert_eq!(started_rx.recv().await, Some("fail-c"));
+            assert_eq!(started_rx.recv().await, Some("d"));
+            assert_eq!(manager.snapshot().running, 1);
+
+            manager.set_limit(None);
+            assert!(started(
+                manager.admit(parallel("ws", "thread-e"), start(&tx, "e"))
+            ));
+        });
+    }
+
+    #[test]
+    fn serialized_workspaces_queue_or_reject_behind_their_running_turn() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let manager = Arc::new(RunManager::default());
+            let (tx, mut started_rx) = mpsc::unbounded_channel();
+            let queue = TurnConcurrencyPolicy::Queue;
+            assert!(started(
+                manager.admit(ticket("ws-1", "thread-a", queue), start(&tx, "a"))
+            ));
+            let waiting = queued(manager.admit(ticket("ws-1", "thread-b", queue), start(&tx, "b")));
+            assert_eq!(waiting.blocked_by, QueueBlock::WorkspaceTurn);
+            assert!(started(
+                manager.admit(ticket("ws-2", "thread-c", queue), start(&tx, "c"))
+            ));
+
+            let busy = manager.admit(
+                ticket("ws-1", "thread-d", TurnConcurrencyPolicy::Reject),
+                start(&tx, "d"),
+            );
+            assert_eq!(
+                busy,
+                Err(TurnBusy {
+                    active_thread_id: "thread-a".to_string(),
+                    queued: 1,
+                }
+                .to_string())
+            );
+
+            manager.release_thread("thread-a");
+            assert_eq!(started_rx.recv().await, Some("b"));
+            assert!(started_rx.try_recv().is_err());
+        });
+    }
+
+    #[test]
+    fn turns_wait_while_a_rate_limit_window_is_at_the_queue_threshold() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let manager = Arc::new(RunManager::default());
+            let (tx, mut started_rx) = mpsc::unbounded_channel();
+            let resets_at = (now_ms() / 1000 + 3600) as i64;
+            let usage =
+                |used: f64| json!({ "primary": { "usedPercent": used, "resetsAt": resets_at } });
+            manager.set_rate_limit_thresholds(Some(50), Some(90));
+            manager.record_rate_limits(&usage(60.0));
+            let admission = manager.admit(parallel("ws", "thread-a"), start(&tx, "a"));
+            assert!(matches!(
+                admission,
+                Ok(RunAdmission::Started {
+                    rate_limit_warning: Some(_)
+                })
+            ));
+
+            manager.record_rate_limits(&usage(95.0));
+            let held = queued(manager.admit(parallel("ws", "thread-b"), start(&tx, "b")));
+            assert_eq!(held.blocked_by, QueueBlock::RateLimit);
+            assert_eq!(manager.rate_limit_forecast().waiting_turns, 1);
+
+            manager.set_rate_limit_thresholds(Some(50), None);
+            assert_eq!(started_rx.recv().await, Some("b"));
+            assert_eq!(manager.rate_limit_forecast().waiting_turns, 0);
+        });
+    }
+}
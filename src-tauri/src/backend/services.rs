@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, RwLock, Weak};
+
+use serde_json::Value;
+
+use crate::backend::event_dedup::EventDedup;
+use crate::backend::events::EventSequence;
+use crate::backend::operations::OperationRegistry;
+use crate::backend::protocol_trace::ProtocolTraces;
+use crate::backend::quotas::QuotaTracker;
+use crate::backend::run_manager::RunManager;
+use crate::backend::session_provenance::SessionProvenanceStore;
+use crate::backend::terminal_history::TerminalHistory;
+use crate::backend::thread_journal::ThreadJournals;
+use crate::backend::thread_preferences::ThreadPreferenceStore;
+use crate::backend::thread_routes::ThreadRouteStore;
+use crate::backend::turn_tracker::TurnTracker;
+use crate::backend::usage_metrics::UsageMetrics;
+use crate::backend::workspace_activity::WorkspaceActivity;
+use crate::rules::ApprovalPolicyStore;
+use crate::shared::archive_undo_core::PendingArchives;
+use crate::shared::file_edit_core::FileWrites;
+use crate::shared::file_index_core::FileIndexes;
+use crate::shared::file_watch_core::FileWatchers;
+use crate::shared::git_status_core::GitStatusCache;
+use crate::shared::network_core::{self, InheritedNetworkEnv};
+use crate::shared::storage_core::{adopt_legacy_paths, category_dir};
+use crate::types::{
+    AppSettings, CodexAuthPropagation, NetworkSettings, StorageCategory, WorkspaceEntry,
+};
+
+/// Settings read when an agent process is spawned. Running sessions keep
+/// what they were spawned with.
+#[derive(Default)]
+struct SessionDefaults {
+    /// `AppSettings.codex_env`.
+    env: HashMap<String, String>,
+    auth_propagation: CodexAuthPropagation,
+}
+
+/// Work the session reader hands off instead of doing inline, so a slow
+/// disk or SQLite write never holds up event routing. Applied in order by
+/// one worker thread per `BackendServices`.
+pub(crate) enum ReaderHook {
+    /// An app-server notification routed to `workspace_id`.
+    Notification {
+        workspace_id: String,
+        method: String,
+        params: Value,
+    },
+    JournalAppend {
+        thread_id: String,
+        workspace_id: String,
+        message: Value,
+    },
+    JournalRemove {
+        thread_id: String,
+    },
+    /// The workspaces' app-server exited.
+    SessionEnded {
+        workspace_ids: Vec<String>,
+    },
+}
+
+/// The stores and trackers shared by every workspace session of one app or
+/// daemon process. `AppState` and `DaemonState` each own one and hand it to
+/// the sessions they spawn; core functions get it, or the part they need,
+/// from their caller.
+pub(crate) struct BackendServices {
+    pub(crate) run_manager: Arc<RunManager>,
+    pub(crate) thread_routes: ThreadRouteStore,
+    pub(crate) thread_preferences: ThreadPreferenceStore,
+    pub(crate) session_provenance: SessionProvenanceStore,
+    pub(crate) approval_policy: ApprovalPolicyStore,
+    pub(crate) usage_metrics: UsageMetrics,
+    pub(crate) protocol_traces: ProtocolTraces,
+    pub(crate) thread_journals: ThreadJournals,
+    pub(crate) session_quotas: QuotaTracker,
+    pub(crate) workspace_activity: WorkspaceActivity,
+    pub(crate) turn_tracker: TurnTracker,
+    pub(crate) terminal_history: TerminalHistory,
+    pub(crate) operations: OperationRegistry,
+    pub(crate) event_dedup: EventDedup,
+    pub(crate) event_sequence: EventSequence,
+    pub(crate) git_statuses: GitStatusCache,
+    pub(crate) file_watchers: FileWatchers,
+    pub(crate) file_indexes: FileIndexes,
+    pub(crate) file_writes: FileWrites,
+    pub(crate) pending_archives: PendingArchives,
+    session_defaults: RwLock<SessionDefaults>,
+    network: Mutex<NetworkSettings>,
+    inherited_network_env: InheritedNetworkEnv,
+    reader_hooks: mpsc::Sender<ReaderHook>,
+}
+
+impl BackendServices {
+    /// Opens the stores kept in `data_dir`, after moving files from older
+    /// layouts into their storage categories, and starts the reader hook
+    /// worker.
+    pub(crate) fn load(data_dir: &Path) -> Arc<Self> {
+        adopt_legacy_paths(data_dir);
+        let (reader_hooks, hooks) = mpsc::channel();
+        let services = Arc::new(Self {
+            run_manager: Arc::default(),
+            thread_routes: ThreadRouteStore::load(data_dir.join("thread-routes.json")),
+            thread_preferences: ThreadPreferenceStore::load(
+                data_dir.join("thread-preferences.json"),
+            ),
+            session_provenance: SessionProvenanceStore::load(
+                data_dir.join("session-provenance.json"),
+            ),
+            approval_policy: ApprovalPolicyStore::load(data_dir.join("approval-rules.json")),
+            usage_metrics: UsageMetrics::new(data_dir.join("usage-metrics.sqlite")),
+            protocol_traces: ProtocolTraces::new(
+                category_dir(data_dir, StorageCategory::Logs).join("protocol-traces"),
+            ),
+            thread_journals: ThreadJournals::load(category_dir(data_dir, StorageCategory::Journal)),
+            session_quotas: QuotaTracker::default(),
+            workspace_activity: WorkspaceActivity::default(),
+            turn_tracker: TurnTracker::default(),
+            terminal_history: TerminalHistory::default(),
+            operations: OperationRegistry::default(),
+            event_dedup: EventDedup::default(),
+            event_sequence: EventSequence::default(),
+            git_statuses: GitStatusCache::default(),
+            file_watchers: FileWatchers::default(),
+            file_indexes: FileIndexes::default(),
+            file_writes: FileWrites::default(),
+            pending_archives: PendingArchives::default(),
+            session_defaults: RwLock::default(),
+            network: Mutex::default(),
+            inherited_network_env: InheritedNetworkEnv::capture(),
+            reader_hooks,
+        });
+        let weak = Arc::downgrade(&services);
+        if let Err(error) = std::thread::Builder::new()
+            .name("reader-hooks".to_string())
+            .spawn(move || run_reader_hooks(weak, hooks))
+        {
+            eprintln!("Failed to start reader hooks: {error}");
+        }
+        services
+    }
+
+    /// Queues `hook` for the worker. Dropped if the worker is gone.
+    pub(crate) fn defer(&self, hook: ReaderHook) {
+        let _ = self.reader_hooks.send(hook);
+    }
+
+    fn apply_reader_hook(&self, hook: ReaderHook) {
+        match hook {
+            ReaderHook::Notification {
+                workspace_id,
+                method,
+                params,
+            } => {
+                self.workspace_activity.touch(&workspace_id);
+                self.turn_tracker.on_event(&workspace_id, &method, &params);
+                self.usage_metrics.on_event(&workspace_id, &method, &params);
+                self.run_manager.on_rate_limit_event(&method, &params);
+            }
+            ReaderHook::JournalAppend {
+                thread_id,
+                workspace_id,
+                message,
+            } => self
+                .thread_journals
+                .append(&thread_id, &workspace_id, &message),
+            ReaderHook::JournalRemove { thread_id } => self.thread_journals.remove(&thread_id),
+            ReaderHook::SessionEnded { workspace_ids } => {
+                self.turn_tracker.clear_workspaces(&workspace_ids)
+            }
+        }
+    }
+
+    /// Applies the settings these services read. Called when settings are
+    /// loaded and whenever they are saved.
+    pub(crate) fn apply_settings(&self, settings: &AppSettings) {
+        let network = network_core::normalize(settings.network.as_ref());
+        self.inherited_network_env.apply(&network);
+        *self.network_lock() = network;
+        if let Ok(mut defaults) = self.session_defaults.write() {
+            *defaults = SessionDefaults {
+                env: settings.codex_env.clone(),
+                auth_propagation: settings.codex_auth_propagation,
+            };
+        }
+        self.run_manager.set_limit(settings.max_concurrent_turns);
+        self.run_manager.set_rate_limit_thresholds(
+            settings.rate_limit_warn_percent,
+            settings.rate_limit_queue_percent,
+        );
+        self.terminal_history
+            .set_capacity(settings.terminal_history_bytes);
+    }
+
+    fn network_lock(&self) -> MutexGuard<'_, NetworkSettings> {
+        self.network
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The proxy and CA settings every outbound HTTP client should use.
+    pub(crate) fn network_settings(&self) -> NetworkSettings {
+        self.network_lock().clone()
+    }
+
+    /// A `reqwest` builder with the configured proxy and CA certificates.
+    pub(crate) fn http_client_builder(&self) -> Result<reqwest::ClientBuilder, String> {
+        network_core::http_client_builder(&self.network_settings())
+    }
+
+    /// Global env first, then the workspace's own, so workspace values win.
+    pub(crate) fn session_env(&self, entry: &WorkspaceEntry) -> Vec<(String, String)> {
+        let defaults = self
+            .session_defaults
+            .read()
+            .map(|defaults| defaults.env.clone())
+            .unwrap_or_default();
+        merge_session_env(defaults, entry)
+    }
+
+    pub(crate) fn auth_propagation(&self) -> CodexAuthPropagation {
+        self.session_defaults
+            .read()
+            .map(|defaults| defaults.auth_propagation)
+            .unwrap_or_default()
+    }
+}
+
+/// Runs until the services are dropped, which closes the channel.
+fn run_reader_hooks(services: Weak<BackendServices>, hooks: mpsc::Receiver<ReaderHook>) {
+    for hook in hooks {
+        let Some(services) = services.upgrade() else {
+            break;
+        };
+        services.apply_reader_hook(hook);
+    }
+}
+
+#[cfg(test)]
+impl BackendServices {
+    /// Services over a fresh temp dir, for tests that build sessions.
+    pub(crate) fn for_tests() -> Arc<Self> {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        Self::load(&dir)
+    }
+}
+
+fn merge_session_env(
+    mut merged: HashMap<String, String>,
+    entry: &WorkspaceEntry,
+) -> Vec<(String, String)> {
+    merged.extend(entry.settings.env.clone());
+    let mut env: Vec<_> = merged
+        .into_iter()
+        .filter(|(key, _)| !key.trim().is_empty())
+        .collect();
+    env.sort();
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+
+    #[test]
+    fn session_env_layers_workspace_over_global() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let services = BackendServices::load(&dir);
+        services.apply_settings(&AppSettings {
+            codex_env: HashMap::from([
+                (
+                    "OPENAI_BASE_URL".to_string(),
+                    "https://global.example".to_string(),
+                ),
+                ("SHARED".to_string(), "global".to_string()),
+            ]),
+            ..AppSettings::default()
+        });
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "Workspace".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                env: HashMap::from([
+                    ("SHARED".to_string(), "workspace".to_string()),
+                    (" ".to_string(), "ignored".to_string()),
+                ]),
+                ..WorkspaceSettings::default()
+            },
+        };
+
+        assert_eq!(
+            services.session_env(&entry),
+            vec![
+                (
+                    "OPENAI_BASE_URL".to_string(),
+                    "https://global.example".to_string()
+                ),
+                ("SHARED".to_string(), "workspace".to_string()),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn reader_hooks_are_applied_in_order_off_the_caller() {
+        let services = BackendServices::for_tests();
+        services.defer(ReaderHook::Notification {
+            workspace_id: "ws".to_string(),
+            method: "turn/started".to_string(),
+            params: serde_json::json!({ "threadId": "t1", "turn": { "id": "turn-1" } }),
+        });
+        services.defer(ReaderHook::JournalAppend {
+            thread_id: "t1".to_string(),
+            workspace_id: "ws".to_string(),
+            message: serde_json::json!({ "method": "turn/started" }),
+        });
+        services.defer(ReaderHook::JournalAppend {
+            thread_id: "t2".to_string(),
+            workspace_id: "ws".to_string(),
+            message: serde_json::json!({ "method": "turn/started" }),
+        });
+        services.defer(ReaderHook::JournalRemove {
+            thread_id: "t1".to_string(),
+        });
+        services.defer(ReaderHook::SessionEnded {
+            workspace_ids: vec!["ws".to_string()],
+        });
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while services.thread_journals.tail("t2", None).events.is_empty() {
+            assert!(std::time::Instant::now() < deadline, "hooks never ran");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        // The removal and the session end only undo the earlier hooks when
+        // they run after them.
+        while !services.turn_tracker.list(Some("ws")).is_empty()
+            || !services.thread_journals.tail("t1", None).events.is_empty()
+        {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "hooks ran out of order"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(services.workspace_activity.last_event_at("ws").is_some());
+    }
+}
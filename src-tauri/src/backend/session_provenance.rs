@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::types::AgentBackendKind;
+
+/// Older spawns of a workspace are dropped past this.
+const MAX_SPAWNS_PER_WORKSPACE: usize = 20;
+const REDACTED: &str = "<redacted>";
+/// Env keys containing any of these have their value replaced by `REDACTED`.
+const SECRET_KEY_PARTS: [&str; 6] = ["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTH", "CREDENTIAL"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHeadSnapshot {
+    pub(crate) commit: Option<String>,
+    /// `None` on a detached HEAD.
+    pub(crate) branch: Option<String>,
+}
+
+/// How one agent process was started.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionProvenance {
+    pub(crate) workspace_id: String,
+    pub(crate) spawned_at: u64,
+    #[serde(default)]
+    pub(crate) pid: Option<u32>,
+    pub(crate) backend: AgentBackendKind,
+    /// The program as resolved at spawn time, e.g. through a login shell.
+    pub(crate) program: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) cwd: String,
+    #[serde(default)]
+    pub(crate) codex_home: Option<String>,
+    /// `codex --version` output, for Codex sessions.
+    #[serde(default)]
+    pub(crate) codex_version: Option<String>,
+    /// Global and workspace env overrides; secret-looking values are redacted.
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) git_head: Option<GitHeadSnapshot>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn redact_env(env: &[(String, String)]) -> BTreeMap<String, String> {
+    env.iter()
+        .map(|(key, value)| {
+            let upper = key.to_ascii_uppercase();
+            let value = if SECRET_KEY_PARTS.iter().any(|part| upper.contains(part)) {
+                REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+fn git_head(path: &Path) -> Option<GitHeadSnapshot> {
+    let repo = Repository::discover(path).ok()?;
+    let head = repo.head().ok();
+    Some(GitHeadSnapshot {
+        commit: head
+            .as_ref()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string()),
+        branch: head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string)),
+    })
+}
+
+/// Captures what `command` is about to run for `workspace_id`.
+pub(crate) fn capture_provenance(
+    workspace_id: &str,
+    backend: AgentBackendKind,
+    command: &Command,
+    cwd: &str,
+    codex_home: Option<&Path>,
+    codex_version: Option<String>,
+    env: &[(String, String)],
+) -> SessionProvenance {
+    let command = command.as_std();
+    SessionProvenance {
+        workspace_id: workspace_id.to_string(),
+        spawned_at: now_ms(),
+        pid: None,
+        backend,
+        program: command.get_program().to_string_lossy().to_string(),
+        args: command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+        cwd: cwd.to_string(),
+        codex_home: codex_home.map(|home| home.to_string_lossy().to_string()),
+        codex_version,
+        env: redact_env(env),
+        git_head: git_head(Path::new(cwd)),
+    }
+}
+
+/// Spawn records per workspace, newest first.
+pub(crate) struct SessionProvenanceStore {
+    path: PathBuf,
+    spawns: Mutex<HashMap<String, Vec<SessionProvenance>>>,
+}
+
+impl SessionProvenanceStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let spawns = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            spawns: Mutex::new(spawns),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Vec<SessionProvenance>>> {
+        self.spawns
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn persist(&self, spawns: &HashMap<String, Vec<SessionProvenance>>) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(spawns).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn record(&self, provenance: SessionProvenance) {
+        let mut spawns = self.lock();
+        let history = spawns.entry(provenance.workspace_id.clone()).or_default();
+        history.insert(0, provenance);
+        history.truncate(MAX_SPAWNS_PER_WORKSPACE);
+        if let Err(error) = self.persist(&spawns) {
+            eprintln!("failed to persist session provenance: {error}");
+        }
+    }
+
+    pub(crate) fn list(&self, workspace_id: &str) -> Vec<SessionProvenance> {
+        self.lock().get(workspace_id).cloned().unwrap_or_default()
+    }
+}
+
+/// The workspace's recorded spawns, newest first.
+pub(crate) fn session_provenance_core(
+    store: &SessionProvenanceStore,
+    workspace_id: &str,
+) -> Result<Vec<SessionProvenance>, String> {
+    Ok(store.list(workspace_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_redact_secrets_and_survive_reload() {
+        let dir =
+            std::env::temp_dir().join(format!("codex-monitor-provenance-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("session-provenance.json");
+        let mut command = Command::new("codex");
+        command.args(["app-server"]);
+        let env = vec![
+            ("OPENAI_API_KEY".to_string(), "sk-secret".to_string()),
+            ("RUST_LOG".to_string(), "debug".to_string()),
+        ];
+        let store = SessionProvenanceStore::load(path.clone());
+        for version in ["codex-cli 0.1.0", "codex-cli 0.2.0"] {
+            store.record(capture_provenance(
+                "ws-1",
+                AgentBackendKind::Codex,
+                &command,
+                &dir.to_string_lossy(),
+                None,
+                Some(version.to_string()),
+                &env,
+            ));
+        }
+
+        let history = SessionProvenanceStore::load(path).list("ws-1");
+        assert_eq!(history.len(), 2);
+        let latest = &history[0];
+        assert_eq!(latest.codex_version.as_deref(), Some("codex-cli 0.2.0"));
+        assert_eq!(latest.program, "codex");
+        assert_eq!(latest.args, ["app-server"]);
+        assert_eq!(latest.env["OPENAI_API_KEY"], REDACTED);
+        assert_eq!(latest.env["RUST_LOG"], "debug");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::events::TerminalOutput;
+
+/// Bytes kept per terminal when `terminalHistoryBytes` is not set.
+const DEFAULT_HISTORY_BYTES: usize = 1024 * 1024;
+const MIN_HISTORY_BYTES: usize = 16 * 1024;
+/// The least recently written terminals are forgotten past this many.
+const MAX_TERMINALS: usize = 32;
+const DEFAULT_PAGE_BYTES: usize = 64 * 1024;
+const MAX_PAGE_BYTES: usize = 1024 * 1024;
+
+/// One page of terminal output. Offsets count bytes since the terminal
+/// opened, so they stay valid as old output is dropped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalOutputPage {
+    pub(crate) data: String,
+    /// Offset of the first byte in `data`; pass it as the next `cursor` to
+    /// page further back.
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    /// Offset of the oldest byte still kept.
+    pub(crate) oldest: u64,
+    /// Offset just past the newest byte.
+    pub(crate) latest: u64,
+    pub(crate) has_more: bool,
+}
+
+#[derive(Default)]
+struct TerminalBuffer {
+    data: String,
+    /// Bytes dropped from the front.
+    dropped: u64,
+    last_write: u64,
+}
+
+/// Ring buffers of recent output per terminal, whether closed or not.
+pub(crate) struct TerminalHistory {
+    buffers: Mutex<HashMap<String, TerminalBuffer>>,
+    capacity: AtomicUsize,
+    writes: AtomicUsize,
+}
+
+impl Default for TerminalHistory {
+    fn default() -> Self {
+        Self {
+            buffers: Mutex::new(HashMap::new()),
+            capacity: AtomicUsize::new(DEFAULT_HISTORY_BYTES),
+            writes: AtomicUsize::new(0),
+        }
+    }
+}
+
+fn buffer_key(workspace_id: &str, terminal_id: &str) -> String {
+    format!("{workspace_id}:{terminal_id}")
+}
+
+fn ceil_char_boundary(data: &str, mut index: usize) -> usize {
+    while index < data.len() && !data.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+fn floor_char_boundary(data: &str, mut index: usize) -> usize {
+    while index > 0 && !data.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl TerminalHistory {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, TerminalBuffer>> {
+        self.buffers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Applies `terminalHistoryBytes`. Existing buffers shrink on their next
+    /// write.
+    pub(crate) fn set_capacity(&self, bytes: Option<u64>) {
+        let bytes = bytes
+            .map(|bytes| bytes as usize)
+            .unwrap_or(DEFAULT_HISTORY_BYTES)
+            .max(MIN_HISTORY_BYTES);
+        self.capacity.store(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record(&self, output: &TerminalOutput) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        let write = self.writes.fetch_add(1, Ordering::Relaxed) as u64;
+        let mut buffers = self.lock();
+        let key = buffer_key(&output.workspace_id, &output.terminal_id);
+        if !buffers.contains_key(&key) && buffers.len() >= MAX_TERMINALS {
+            if let Some(stale) = buffers
+                .iter()
+                .min_by_key(|(_, buffer)| buffer.last_write)
+                .map(|(key, _)| key.clone())
+            {
+                buffers.remove(&stale);
+            }
+        }
+        let buffer = buffers.entry(key).or_default();
+        buffer.data.push_str(&output.data);
+        buffer.last_write = write;
+        if buffer.data.len() > capacity {
+            let cut = ceil_char_boundary(&buffer.data, buffer.data.len() - capacity);
+            buffer.data.drain(..cut);
+            buffer.dropped += cut as u64;
+        }
+    }
+
+    /// Returns up to `max_bytes` of output ending at `cursor`, or the newest
+    /// output when `cursor` is `None`.
+    pub(crate) fn page(
+        &self,
+        workspace_id: &str,
+        terminal_id: &str,
+        cursor: Option<u64>,
+        max_bytes: Option<usize>,
+    ) -> Result<TerminalOutputPage, String> {
+        let buffers = self.lock();
+        let buffer = buffers
+            .get(&buffer_key(workspace_id, terminal_id))
+            .ok_or_else(|| "No output recorded for this terminal".to_string())?;
+        let max_bytes = max_bytes
+            .unwrap_or(DEFAULT_PAGE_BYTES)
+            .clamp(1, MAX_PAGE_BYTES);
+        let oldest = buffer.dropped;
+        let latest = oldest + buffer.data.len() as u64;
+        let end = cursor.unwrap_or(latest).clamp(oldest, latest);
+        let end_index = floor_char_boundary(&buffer.data, (end - oldest) as usize);
+        let start_index = ceil_char_boundary(&buffer.data, end_index.saturating_sub(max_bytes));
+        let start = oldest + start_index as u64;
+        Ok(TerminalOutputPage {
+            data: buffer.data[start_index..end_index].to_string(),
+            start,
+            end: oldest + end_index as u64,
+            oldest,
+            latest,
+            has_more: start > oldest,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(data: &str) -> TerminalOutput {
+        TerminalOutput {
+            workspace_id: "ws-1".to_string(),
+            terminal_id: "term-1".to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn pages_walk_back_through_retained_output() {
+        let history = TerminalHistory::default();
+        history.set_capacity(Some(0));
+        let line = "x".repeat(1023) + "\n";
+        for _ in 0..20 {
+            history.record(&output(&line));
+        }
+
+        let newest = history.page("ws-1", "term-1", None, Some(4096)).unwrap();
+        assert_eq!(newest.latest, 20 * 1024);
+        assert_eq!(newest.oldest, 20 * 1024 - MIN_HISTORY_BYTES as u64);
+        assert_eq!(newest.data.len(), 4096);
+        assert!(newest.has_more);
+
+        let mut cursor = newest.start;
+        let mut pages = 1;
+        loop {
+            let page = history
+                .page("ws-1", "term-1", Some(cursor), Some(4096))
+                .unwrap();
+            assert_eq!(page.end, cursor);
+            pages += 1;
+            cursor = page.start;
+            if !page.has_more {
+                break;
+            }
+        }
+        assert_eq!(pages, MIN_HISTORY_BYTES / 4096);
+        assert_eq!(cursor, newest.oldest);
+        assert!(history.page("ws-1", "other", None, None).is_err());
+    }
+
+    #[test]
+    fn pages_never_split_a_character() {
+        let history = TerminalHistory::default();
+        history.record(&output("héllo wörld"));
+        let page = history.page("ws-1", "term-1", Some(2), Some(2)).unwrap();
+        assert_eq!(page.data, "h");
+        assert_eq!((page.start, page.end), (0, 1));
+    }
+}
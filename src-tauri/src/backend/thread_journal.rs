@@ -0,0 +1,340 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The current segment is compressed into `1.jsonl.gz` once it grows past
+/// this.
+const SEGMENT_BYTES: u64 = 512 * 1024;
+/// Compressed segments kept per thread; older ones are deleted.
+const MAX_SEGMENTS: usize = 4;
+/// Journals of the least recently written threads are deleted past this.
+const MAX_JOURNALED_THREADS: usize = 500;
+/// Open file handles are dropped past this and reopened on the next write.
+const MAX_OPEN_JOURNALS: usize = 64;
+const CURRENT_SEGMENT: &str = "current.jsonl";
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadJournalEntry {
+    /// When the event was routed (ms since the epoch).
+    pub(crate) at: u64,
+    pub(crate) workspace_id: String,
+    /// The app-server message as it was emitted.
+    pub(crate) message: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadTail {
+    pub(crate) thread_id: String,
+    /// Oldest first.
+    pub(crate) events: Vec<ThreadJournalEntry>,
+    /// Whether older events are still on disk.
+    pub(crate) has_more: bool,
+}
+
+struct JournalFile {
+    file: File,
+    bytes: u64,
+}
+
+/// Per-thread on-disk journals of the app-server events routed to clients,
+/// so history can be shown before `thread/resume` answers. Each thread has a
+/// folder with a plain current segment and a few gzip-compressed older ones.
+pub(crate) struct ThreadJournals {
+    dir: PathBuf,
+    open: Mutex<HashMap<String, JournalFile>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn folder_name(thread_id: &str) -> String {
+    thread_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn segment_path(folder: &Path, index: usize) -> PathBuf {
+    folder.join(format!("{index}.jsonl.gz"))
+}
+
+fn open_append(path: &Path) -> Result<JournalFile, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Failed to open thread journal: {err}"))?;
+    let bytes = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    Ok(JournalFile { file, bytes })
+}
+
+/// Compresses the current segment into `1.jsonl.gz`, shifting older
+/// segments up and dropping the oldest.
+fn rotate(folder: &Path) -> Result<(), String> {
+    let _ = fs::remove_file(segment_path(folder, MAX_SEGMENTS));
+    for index in (1..MAX_SEGMENTS).rev() {
+        let from = segment_path(folder, index);
+        if from.exists() {
+            let _ = fs::rename(&from, segment_path(folder, index + 1));
+        }
+    }
+    let current = folder.join(CURRENT_SEGMENT);
+    let data = fs::read(&current).map_err(|err| format!("Failed to read thread journal: {err}"))?;
+    let temp = folder.join("1.jsonl.gz.tmp");
+    let mut encoder = GzEncoder::new(
+        File::create(&temp).map_err(|err| format!("Failed to write thread journal: {err}"))?,
+        Compression::default(),
+    );
+    encoder
+        .write_all(&data)
+        .and_then(|_| encoder.finish().map(|_| ()))
+        .map_err(|err| format!("Failed to write thread journal: {err}"))?;
+    fs::rename(&temp, segment_path(folder, 1))
+        .map_err(|err| format!("Failed to write thread journal: {err}"))?;
+    fs::remove_file(&current).map_err(|err| format!("Failed to rotate thread journal: {err}"))
+}
+
+/// Parsed entries of one segment, oldest first. Lines cut short by a crash
+/// are skipped.
+fn read_segment(reader: impl BufRead) -> Vec<ThreadJournalEntry> {
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn last_written(folder: &Path) -> SystemTime {
+    fs::metadata(folder.join(CURRENT_SEGMENT))
+        .or_else(|_| fs::metadata(segment_path(folder, 1)))
+        .or_else(|_| fs::metadata(folder))
+        .and_then(|meta| meta.modified())
+        .unwrap_or(UNIX_EPOCH)
+}
+
+impl ThreadJournals {
+    /// Journals are written under `dir`; old ones are pruned on load.
+    pub(crate) fn load(dir: PathBuf) -> Self {
+        let journals = Self {
+            dir,
+            open: Mutex::new(HashMap::new()),
+        };
+        journals.prune(MAX_JOURNALED_THREADS);
+        journals
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, JournalFile>> {
+        self.open
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn folder_for(&self, thread_id: &str) -> PathBuf {
+        self.dir.join(folder_name(thread_id))
+    }
+
+    /// Deletes the journals of all but the `keep` most recently written
+    /// threads.
+    fn prune(&self, keep: usize) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut folders: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .map(|path| (last_written(&path), path))
+            .collect();
+        if folders.len() <= keep {
+            return;
+        }
+        folders.sort_by(|a, b| b.0.cmp(&a.0));
+        for (_, folder) in folders.into_iter().skip(keep) {
+            let _ = fs::remove_dir_all(folder);
+        }
+    }
+
+    /// Appends an event to the thread's journal. Write failures are ignored;
+    /// journaling never breaks event routing.
+    pub(crate) fn append(&self, thread_id: &str, workspace_id: &str, message: &Value) {
+        let entry = ThreadJournalEntry {
+            at: now_ms(),
+            workspace_id: workspace_id.to_string(),
+            message: message.clone(),
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+        let folder = self.folder_for(thread_id);
+        let mut open = self.lock();
+        if !open.contains_key(thread_id) {
+            if open.len() >= MAX_OPEN_JOURNALS {
+                open.clear();
+            }
+            if fs::create_dir_all(&folder).is_err() {
+                return;
+            }
+            match open_append(&folder.join(CURRENT_SEGMENT)) {
+                Ok(journal) => {
+                    open.insert(thread_id.to_string(), journal);
+                }
+                Err(_) => return,
+            }
+        }
+        let Some(journal) = open.get_mut(thread_id) else {
+            return;
+        };
+        if journal.bytes > 0 && journal.bytes + line.len() as u64 > SEGMENT_BYTES {
+            if rotate(&folder).is_err() {
+                return;
+            }
+            match open_append(&folder.join(CURRENT_SEGMENT)) {
+                Ok(fresh) => *journal = fresh,
+                Err(_) => {
+                    open.remove(thread_id);
+                    return;
+                }
+            }
+        }
+        if journal.file.write_all(line.as_bytes()).is_ok() {
+            journal.bytes += line.len() as u64;
+        }
+    }
+
+    /// The newest `lines` events, reaching into compressed segments when the
+    /// current one is short.
+    pub(crate) fn tail(&self, thread_id: &str, lines: Option<usize>) -> ThreadTail {
+        let count = lines.unwrap_or(DEFAULT_TAIL_LINES).clamp(1, MAX_TAIL_LINES);
+        let folder = self.folder_for(thread_id);
+        // Hold the lock so a rotation cannot move segments mid-read.
+        let _open = self.lock();
+        let mut events: VecDeque<ThreadJournalEntry> = File::open(folder.join(CURRENT_SEGMENT))
+            .map(|file| read_segment(BufReader::new(file)).into())
+            .unwrap_or_default();
+        let mut next_segment = 1;
+        while events.len() < count && next_segment <= MAX_SEGMENTS {
+            let Ok(file) = File::open(segment_path(&folder, next_segment)) else {
+                break;
+            };
+            next_segment += 1;
+            for entry in read_segment(BufReader::new(GzDecoder::new(file)))
+                .into_iter()
+                .rev()
+            {
+                events.push_front(entry);
+            }
+        }
+        let mut has_more =
+            next_segment <= MAX_SEGMENTS && segment_path(&folder, next_segment).exists();
+        while events.len() > count {
+            events.pop_front();
+            has_more = true;
+        }
+        ThreadTail {
+            thread_id: thread_id.to_string(),
+            events: events.into(),
+            has_more,
+        }
+    }
+
+    /// Deletes the thread's journal, e.g. once it is archived.
+    pub(crate) fn remove(&self, thread_id: &str) {
+        self.lock().remove(thread_id);
+        let _ = fs::remove_dir_all(self.folder_for(thread_id));
+    }
+}
+
+pub(crate) fn get_thread_tail_core(
+    journals: &ThreadJournals,
+    thread_id: &str,
+    lines: Option<usize>,
+) -> Result<ThreadTail, String> {
+    if thread_id.trim().is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    Ok(journals.tail(thread_id, lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn delta(index: usize) -> Value {
+        json!({
+            "method": "item/agentMessage/delta",
+            "params": { "threadId": "thread/1", "delta": format!("chunk {index} {}", "x".repeat(200)) }
+        })
+    }
+
+    #[test]
+    fn tails_across_compressed_segments_and_caps_them() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let journals = ThreadJournals::load(dir.clone());
+        assert!(journals.tail("thread/1", None).events.is_empty());
+
+        journals.append("thread/1", "ws-1", &delta(0));
+        let tail = journals.tail("thread/1", None);
+        assert_eq!(tail.events.len(), 1);
+        assert_eq!(tail.events[0].workspace_id, "ws-1");
+        assert!(!tail.has_more);
+
+        // Enough events to rotate more often than segments are kept.
+        let total = 16_000;
+        for index in 1..total {
+            journals.append("thread/1", "ws-1", &delta(index));
+        }
+        let folder = journals.folder_for("thread/1");
+        assert!(segment_path(&folder, MAX_SEGMENTS).exists());
+        assert!(!segment_path(&folder, MAX_SEGMENTS + 1).exists());
+
+        let tail = journals.tail("thread/1", Some(MAX_TAIL_LINES));
+        assert_eq!(tail.events.len(), MAX_TAIL_LINES);
+        assert!(tail.has_more);
+        let last = tail.events.last().expect("newest event");
+        assert_eq!(last.message, delta(total - 1));
+        let first = &tail.events[0];
+        assert_eq!(first.message, delta(total - MAX_TAIL_LINES));
+
+        journals.remove("thread/1");
+        assert!(!folder.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn prune_keeps_the_most_recent_threads() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let journals = ThreadJournals::load(dir.clone());
+        journals.append("old", "ws-1", &delta(0));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        journals.append("new", "ws-1", &delta(1));
+        journals.prune(1);
+        assert!(!journals.folder_for("old").exists());
+        assert!(journals.folder_for("new").exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Least recently used threads are dropped past this.
+const MAX_THREAD_PREFERENCES: usize = 5000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModelPreference {
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) effort: Option<String>,
+    /// Set through `set_thread_preferences`; turns sent with another model
+    /// no longer replace it.
+    #[serde(default)]
+    pub(crate) pinned: bool,
+    #[serde(default)]
+    pub(crate) updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadPreferences {
+    pub(crate) thread: Option<ModelPreference>,
+    /// The last model used in the thread's workspace, for threads without
+    /// their own.
+    pub(crate) workspace: Option<ModelPreference>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredPreferences {
+    #[serde(default)]
+    threads: HashMap<String, ModelPreference>,
+    #[serde(default)]
+    workspaces: HashMap<String, ModelPreference>,
+}
+
+/// Last-used (or pinned) model and effort per thread and per workspace, so
+/// a resumed thread keeps its model when the frontend sends none.
+pub(crate) struct ThreadPreferenceStore {
+    path: PathBuf,
+    stored: Mutex<StoredPreferences>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl ThreadPreferenceStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let stored = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            stored: Mutex::new(stored),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StoredPreferences> {
+        self.stored
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn persist(&self, stored: &StoredPreferences) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(stored).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn get(&self, thread_id: &str, workspace_id: Option<&str>) -> ThreadPreferences {
+        let stored = self.lock();
+        ThreadPreferences {
+            thread: stored.threads.get(thread_id).cloned(),
+            workspace: workspace_id.and_then(|id| stored.workspaces.get(id).cloned()),
+        }
+    }
+
+    /// Records the model a turn was started with. Pinned threads keep their
+    /// pin; the workspace always follows the latest turn.
+    pub(crate) fn record_turn(
+        &self,
+        workspace_id: &str,
+        thread_id: &str,
+        model: Option<String>,
+        effort: Option<String>,
+    ) {
+        let model = non_empty(model);
+        if model.is_none() {
+            return;
+        }
+        let used = ModelPreference {
+            model,
+            effort: non_empty(effort),
+            pinned: false,
+            updated_at: now_ms(),
+        };
+        let mut stored = self.lock();
+        let same = |known: Option<&ModelPreference>| {
+            known.is_some_and(|known| known.model == used.model && known.effort == used.effort)
+        };
+        let thread_pinned = stored
+            .threads
+            .get(thread_id)
+            .is_some_and(|known| known.pinned);
+        if (thread_pinned || same(stored.threads.get(thread_id)))
+            && same(stored.workspaces.get(workspace_id))
+        {
+            return;
+        }
+        if !thread_pinned {
+            stored.threads.insert(thread_id.to_string(), used.clone());
+        }
+        stored.workspaces.insert(workspace_id.to_string(), used);
+        self.evict(&mut stored);
+        if let Err(error) = self.persist(&stored) {
+            eprintln!("failed to persist thread preferences: {error}");
+        }
+    }
+
+    /// Pins `model`/`effort` to the thread. With neither set the thread's
+    /// preference is cleared and it follows its turns again.
+    pub(crate) fn set(
+        &self,
+        thread_id: &str,
+        workspace_id: Option<&str>,
+        model: Option<String>,
+        effort: Option<String>,
+    ) -> Result<ThreadPreferences, String> {
+        let model = non_empty(model);
+        let effort = non_empty(effort);
+        let mut stored = self.lock();
+        if model.is_none() && effort.is_none() {
+            stored.threads.remove(thread_id);
+        } else {
+            stored.threads.insert(
+                thread_id.to_string(),
+                ModelPreference {
+                    model,
+                    effort,
+                    pinned: true,
+                    updated_at: now_ms(),
+                },
+            );
+            self.evict(&mut stored);
+        }
+        self.persist(&stored)?;
+        Ok(ThreadPreferences {
+            thread: stored.threads.get(thread_id).cloned(),
+            workspace: workspace_id.and_then(|id| stored.workspaces.get(id).cloned()),
+        })
+    }
+
+    pub(crate) fn remove_thread(&self, thread_id: &str) {
+        let mut stored = self.lock();
+        if stored.threads.remove(thread_id).is_some() {
+            let _ = self.persist(&stored);
+        }
+    }
+
+    fn evict(&self, stored: &mut StoredPreferences) {
+        if stored.threads.len() <= MAX_THREAD_PREFERENCES {
+            return;
+        }
+        let mut by_age = stored
+            .threads
+            .iter()
+            .map(|(thread_id, preference)| (preference.updated_at, thread_id.clone()))
+            .collect::<Vec<_>>();
+        by_age.sort();
+        let excess = stored.threads.len() - MAX_THREAD_PREFERENCES;
+        for (_, thread_id) in by_age.into_iter().take(excess) {
+            stored.threads.remove(&thread_id);
+        }
+    }
+}
+
+/// Model and effort for a turn that did not name them: the thread's
+/// preference, then `fallback` (the workspace's configured defaults), then
+/// the last model used in the workspace.
+pub(crate) fn resolve_turn_model(
+    store: &ThreadPreferenceStore,
+    workspace_id: &str,
+    thread_id: &str,
+    fallback: (Option<String>, Option<String>),
+) -> (Option<String>, Option<String>) {
+    let preferences = store.get(thread_id, Some(workspace_id));
+    if let Some(thread) = preferences.thread {
+        if thread.model.is_some() || thread.effort.is_some() {
+            return (thread.model.or(fallback.0), thread.effort.or(fallback.1));
+        }
+    }
+    if fallback.0.is_some() {
+        return fallback;
+    }
+    match preferences.workspace {
+        Some(workspace) => (workspace.model, fallback.1.or(workspace.effort)),
+        None => fallback,
+    }
+}
+
+pub(crate) fn get_thread_preferences_core(
+    store: &ThreadPreferenceStore,
+    thread_id: &str,
+    workspace_id: Option<&str>,
+) -> Result<ThreadPreferences, String> {
+    Ok(store.get(thread_id, workspace_id))
+}
+
+pub(crate) fn set_thread_preferences_core(
+    store: &ThreadPreferenceStore,
+    thread_id: &str,
+    workspace_id: Option<&str>,
+    model: Option<String>,
+    effort: Option<String>,
+) -> Result<ThreadPreferences, String> {
+    store.set(thread_id, workspace_id, model, effort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_update_unpinned_threads_and_pins_survive_reload() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "codex-monitor-thread-prefs-{}",
+                uuid::Uuid::new_v4()
+            ))
+            .join("thread-preferences.json");
+        let store = ThreadPreferenceStore::load(path.clone());
+        store.record_turn("ws-1", "t-1", Some("gpt-5".into()), Some("high".into()));
+        store.record_turn("ws-1", "t-2", Some("gpt-5-mini".into()), None);
+        assert_eq!(
+            store.get("t-1", None).thread.and_then(|pref| pref.model),
+            Some("gpt-5".to_string())
+        );
+
+        store
+            .set("t-1", None, Some("o3".into()), None)
+            .expect("pin");
+        store.record_turn("ws-1", "t-1", Some("gpt-5".into()), None);
+
+        let reloaded = ThreadPreferenceStore::load(path.clone());
+        let preferences = reloaded.get("t-1", Some("ws-1"));
+        let thread = preferences.thread.expect("thread preference");
+        assert!(thread.pinned);
+        assert_eq!(thread.model.as_deref(), Some("o3"));
+        assert_eq!(
+            preferences.workspace.and_then(|pref| pref.model).as_deref(),
+            Some("gpt-5")
+        );
+
+        reloaded.set("t-1", None, None, None).expect("clear");
+        assert!(reloaded.get("t-1", None).thread.is_none());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Oldest routes are dropped past this so the file stays small.
+const MAX_THREAD_ROUTES: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadRoute {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) cwd: Option<String>,
+    #[serde(default)]
+    pub(crate) updated_at: u64,
+}
+
+/// Thread → workspace routing persisted across restarts, so events for
+/// resumed threads route correctly before the next `thread/list`.
+pub(crate) struct ThreadRouteStore {
+    path: PathBuf,
+    routes: Mutex<HashMap<String, ThreadRoute>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl ThreadRouteStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let routes = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            routes: Mutex::new(routes),
+        }
+    }
+
+    /// thread id → workspace id, used to seed a new session's routing table.
+    pub(crate) fn workspace_map(&self) -> HashMap<String, String> {
+        let Ok(routes) = self.routes.lock() else {
+            return HashMap::new();
+        };
+        routes
+            .iter()
+            .map(|(thread_id, route)| (thread_id.clone(), route.workspace_id.clone()))
+            .collect()
+    }
+
+    /// Records `(thread_id, workspace_id, cwd)` routes, writing the file only
+    /// when something changed.
+    pub(crate) fn record(&self, entries: Vec<(String, String, Option<String>)>) {
+        let Ok(mut routes) = self.routes.lock() else {
+            return;
+        };
+        let updated_at = now_ms();
+        let mut changed = false;
+        for (thread_id, workspace_id, cwd) in entries {
+            let unchanged = routes.get(&thread_id).is_some_and(|route| {
+                route.workspace_id == workspace_id && (cwd.is_none() || route.cwd == cwd)
+            });
+            if unchanged {
+                continue;
+            }
+            let cwd = cwd.or_else(|| routes.get(&thread_id).and_then(|route| route.cwd.clone()));
+            routes.insert(
+                thread_id,
+                ThreadRoute {
+                    workspace_id,
+                    cwd,
+                    updated_at,
+                },
+            );
+            changed = true;
+        }
+        if !changed {
+            return;
+        }
+        if routes.len() > MAX_THREAD_ROUTES {
+            let mut by_age = routes
+                .iter()
+                .map(|(thread_id, route)| (route.updated_at, thread_id.clone()))
+                .collect::<Vec<_>>();
+            by_age.sort();
+            for (_, thread_id) in by_age.into_iter().take(routes.len() - MAX_THREAD_ROUTES) {
+                routes.remove(&thread_id);
+            }
+        }
+        self.persist(&routes);
+    }
+
+    pub(crate) fn remove(&self, thread_id: &str) {
+        let Ok(mut routes) = self.routes.lock() else {
+            return;
+        };
+        if routes.remove(thread_id).is_some() {
+            self.persist(&routes);
+        }
+    }
+
+    fn persist(&self, routes: &HashMap<String, ThreadRoute>) {
+        let result = (|| -> Result<(), String> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let data = serde_json::to_string(routes).map_err(|e| e.to_string())?;
+            let tmp = self.path.with_extension("json.tmp");
+            std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+            std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+        })();
+        if let Err(error) = result {
+            eprintln!("failed to persist thread routes: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!(
+                "codex-monitor-thread-routes-{}",
+                uuid::Uuid::new_v4()
+            ))
+            .join("thread-routes.json")
+    }
+
+    #[test]
+    fn routes_survive_reload() {
+        let path = temp_path();
+        let store = ThreadRouteStore::load(path.clone());
+        store.record(vec![
+            ("thread-1".into(), "ws-1".into(), Some("/repo/a".into())),
+            ("thread-2".into(), "ws-2".into(), None),
+        ]);
+        store.remove("thread-2");
+
+        let reloaded = ThreadRouteStore::load(path.clone());
+        assert_eq!(
+            reloaded.workspace_map(),
+            HashMap::from([("thread-1".to_string(), "ws-1".to_string())])
+        );
+        let routes = reloaded.routes.lock().unwrap();
+        assert_eq!(routes["thread-1"].cwd.as_deref(), Some("/repo/a"));
+        drop(routes);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn record_keeps_known_cwd_and_caps_size() {
+        let path = temp_path();
+        let store = ThreadRouteStore::load(path.clone());
+        store.record(vec![(
+            "thread-0".into(),
+            "ws-1".into(),
+            Some("/repo".into()),
+        )]);
+        store.record(vec![("thread-0".into(), "ws-2".into(), None)]);
+        assert_eq!(
+            store.routes.lock().unwrap()["thread-0"].cwd.as_deref(),
+            Some("/repo")
+        );
+
+        store.record(
+            (1..=MAX_THREAD_ROUTES)
+                .map(|index| (format!("thread-{index}"), "ws-1".to_string(), None))
+                .collect(),
+        );
+        assert_eq!(store.workspace_map().len(), MAX_THREAD_ROUTES);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActiveTurn {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+    /// When `turn/started` arrived (ms since the epoch).
+    pub(crate) started_at: u64,
+}
+
+/// Turns that have started and not yet completed or failed, keyed by
+/// workspace and thread. A thread runs at most one turn at a time, so a new
+/// `turn/started` replaces whatever the thread had.
+#[derive(Default)]
+pub(crate) struct TurnTracker {
+    turns: Mutex<HashMap<(String, String), ActiveTurn>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+impl TurnTracker {
+    fn lock(&self) -> MutexGuard<'_, HashMap<(String, String), ActiveTurn>> {
+        self.turns
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn on_event(&self, workspace_id: &str, method: &str, params: &Value) {
+        let turn = params.get("turn").unwrap_or(&Value::Null);
+        let Some(thread_id) = str_field(params, "threadId").or_else(|| str_field(turn, "threadId"))
+        else {
+            return;
+        };
+        let key = (workspace_id.to_string(), thread_id);
+        match method {
+            "turn/started" => {
+                let turn = ActiveTurn {
+                    workspace_id: key.0.clone(),
+                    thread_id: key.1.clone(),
+                    turn_id: str_field(turn, "id").or_else(|| str_field(params, "turnId")),
+                    started_at: now_ms(),
+                };
+                self.lock().insert(key, turn);
+            }
+            "turn/completed" | "turn/error" => {
+                self.lock().remove(&key);
+            }
+            // A retried error leaves the turn running.
+            "error" if params.get("willRetry").and_then(Value::as_bool) != Some(true) => {
+                self.lock().remove(&key);
+            }
+            _ => {}
+        }
+    }
+
+    /// Drops the turns of workspaces whose app-server went away; no
+    /// completion will arrive for them.
+    pub(crate) fn clear_workspaces(&self, workspace_ids: &[String]) {
+        self.lock()
+            .retain(|(workspace_id, _), _| !workspace_ids.contains(workspace_id));
+    }
+
+    /// Running turns, oldest first, optionally for one workspace.
+    pub(crate) fn list(&self, workspace_id: Option<&str>) -> Vec<ActiveTurn> {
+        let mut turns: Vec<ActiveTurn> = self
+            .lock()
+            .values()
+            .filter(|turn| workspace_id.is_none_or(|id| turn.workspace_id == id))
+            .cloned()
+            .collect();
+        turns.sort_by(|a, b| {
+            a.started_at
+                .cmp(&b.started_at)
+                .then_with(|| a.thread_id.cmp(&b.thread_id))
+        });
+        turns
+    }
+}
+
+pub(crate) fn list_active_turns_core(
+    tracker: &TurnTracker,
+    workspace_id: Option<&str>,
+) -> Vec<ActiveTurn> {
+    tracker.list(workspace_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn turns_are_tracked_until_they_complete_or_fail() {
+        let tracker = TurnTracker::default();
+        tracker.on_event(
+            "ws-1",
+            "turn/started",
+            &json!({ "threadId": "t-1", "turn": { "id": "turn-1" } }),
+        );
+        tracker.on_event(
+            "ws-1",
+            "turn/started",
+            &json!({ "threadId": "t-2", "turn": { "id": "turn-2" } }),
+        );
+        tracker.on_event(
+            "ws-2",
+            "turn/started",
+            &json!({ "threadId": "t-3", "turn": { "id": "turn-3" } }),
+        );
+        assert_eq!(tracker.list(None).len(), 3);
+        let ws1 = tracker.list(Some("ws-1"));
+        assert_eq!(ws1.len(), 2);
+        assert!(ws1.iter().all(|turn| turn.workspace_id == "ws-1"));
+
+        tracker.on_event(
+            "ws-1",
+            "error",
+            &json!({ "threadId": "t-1", "willRetry": true }),
+        );
+        tracker.on_event(
+            "ws-1",
+            "turn/completed",
+            &json!({ "threadId": "t-2", "turn": { "id": "turn-2" } }),
+        );
+        let remaining = tracker.list(Some("ws-1"));
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].turn_id.as_deref(), Some("turn-1"));
+        tracker.on_event("ws-1", "error", &json!({ "threadId": "t-1" }));
+        assert!(tracker.list(Some("ws-1")).is_empty());
+
+        tracker.clear_workspaces(&["ws-2".to_string()]);
+        assert!(tracker.list(None).is_empty());
+    }
+}
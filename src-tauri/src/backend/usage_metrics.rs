@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS turns (
+    id INTEGER PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    turn_id TEXT NOT NULL,
+    model TEXT,
+    status TEXT NOT NULL,
+    error TEXT,
+    started_at INTEGER NOT NULL,
+    completed_at INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    cached_input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    reasoning_output_tokens INTEGER NOT NULL,
+    total_tokens INTEGER NOT NULL,
+    UNIQUE (thread_id, turn_id)
+);
+CREATE INDEX IF NOT EXISTS turns_completed_at ON turns (completed_at);
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TokenCounts {
+    input: u64,
+    cached_input: u64,
+    output: u64,
+    reasoning_output: u64,
+    total: u64,
+}
+
+impl TokenCounts {
+    fn parse(value: &Value) -> Option<Self> {
+        let field = |camel: &str, snake: &str| {
+            value
+                .get(camel)
+                .or_else(|| value.get(snake))
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+        };
+        if !value.is_object() {
+            return None;
+        }
+        Some(Self {
+            input: field("inputTokens", "input_tokens"),
+            cached_input: field("cachedInputTokens", "cached_input_tokens"),
+            output: field("outputTokens", "output_tokens"),
+            reasoning_output: field("reasoningOutputTokens", "reasoning_output_tokens"),
+            total: field("totalTokens", "total_tokens"),
+        })
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            input: self.input.saturating_sub(other.input),
+            cached_input: self.cached_input.saturating_sub(other.cached_input),
+            output: self.output.saturating_sub(other.output),
+            reasoning_output: self.reasoning_output.saturating_sub(other.reasoning_output),
+            total: self.total.saturating_sub(other.total),
+        }
+    }
+}
+
+struct ActiveTurn {
+    workspace_id: String,
+    turn_id: String,
+    model: Option<String>,
+    started_at: u64,
+    /// The thread's cumulative usage when the turn started.
+    baseline: Option<TokenCounts>,
+    latest: Option<TokenCounts>,
+}
+
+/// A finished turn, as stored in `usage-metrics.sqlite`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TurnRecord {
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    model: Option<String>,
+    status: String,
+    error: Option<String>,
+    started_at: u64,
+    completed_at: u64,
+    tokens: TokenCounts,
+}
+
+/// In-memory turn state between `turn/started` and `turn/completed`.
+#[derive(Default)]
+struct TurnTracker {
+    /// Model requested by the latest `turn/start` per thread.
+    thread_models: HashMap<String, String>,
+    /// Latest cumulative token usage per thread.
+    thread_totals: HashMap<String, TokenCounts>,
+    active: HashMap<String, ActiveTurn>,
+}
+
+impl TurnTracker {
+    fn on_event(
+        &mut self,
+        workspace_id: &str,
+        method: &str,
+        params: &Value,
+        now: u64,
+    ) -> Option<TurnRecord> {
+        let thread_id = params
+            .get("threadId")
+            .or_else(|| params.get("thread_id"))
+            .and_then(Value::as_str)?;
+        match method {
+            "turn/started" => {
+                let turn_id = params.get("turn")?.get("id")?.as_str()?;
+                self.active.insert(
+                    thread_id.to_string(),
+                    ActiveTurn {
+                        workspace_id: workspace_id.to_string(),
+                        turn_id: turn_id.to_string(),
+                        model: self.thread_models.get(thread_id).cloned(),
+                        started_at: now,
+                        baseline: self.thread_totals.get(thread_id).copied(),
+                        latest: None,
+                    },
+                );
+                None
+            }
+            "thread/tokenUsage/updated" => {
+                let usage = params
+                    .get("tokenUsage")
+                    .or_else(|| params.get("token_usage"))?;
+                let total = TokenCounts::parse(usage.get("total")?)?;
+                self.thread_totals.insert(thread_id.to_string(), total);
+                if let Some(turn) = self.active.get_mut(thread_id) {
+                    if turn.baseline.is_none() {
+                        // First update since startup: the thread's earlier
+                        // usage is the total minus this request.
+                        let last = usage
+                            .get("last")
+                            .and_then(TokenCounts::parse)
+                            .unwrap_or_default();
+                        turn.baseline = Some(total.saturating_sub(last));
+                    }
+                    turn.latest = Some(total);
+                }
+                None
+            }
+            "turn/completed" => {
+                let turn = self.active.remove(thread_id)?;
+                let completed = params.get("turn");
+                let status = completed
+                    .and_then(|turn| turn.get("status"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("completed")
+                    .to_string();
+                let error = completed
+                    .and_then(|turn| turn.get("error"))
+                    .and_then(|error| error.get("message"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                let tokens = match (turn.latest, turn.baseline) {
+                    (Some(latest), Some(baseline)) => latest.saturating_sub(baseline),
+                    (Some(latest), None) => latest,
+                    _ => TokenCounts::default(),
+                };
+                Some(TurnRecord {
+                    workspace_id: turn.workspace_id,
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn.turn_id,
+                    model: turn.model,
+                    status,
+                    error,
+                    started_at: turn.started_at,
+                    completed_at: now,
+                    tokens,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum UsageGroupBy {
+    Workspace,
+    Model,
+    Day,
+}
+
+impl UsageGroupBy {
+    pub(crate) fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::trim).unwrap_or("workspace") {
+            "" | "workspace" => Ok(Self::Workspace),
+            "model" => Ok(Self::Model),
+            "day" => Ok(Self::Day),
+            other => Err(format!(
+                "Unknown groupBy `{other}`; use workspace, model or day"
+            )),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::Workspace => "workspace_id",
+            Self::Model => "COALESCE(model, 'default')",
+            Self::Day => "date(completed_at / 1000, 'unixepoch')",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageSummaryRow {
+    /// Workspace id, model (`default` when none was requested) or UTC day.
+    pub(crate) key: String,
+    pub(crate) turns: u64,
+    pub(crate) failed_turns: u64,
+    pub(crate) interrupted_turns: u64,
+    pub(crate) duration_ms: u64,
+    pub(crate) input_tokens: u64,
+    pub(crate) cached_input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) reasoning_output_tokens: u64,
+    pub(crate) total_tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageSummary {
+    pub(crate) since: Option<u64>,
+    pub(crate) group_by: UsageGroupBy,
+    pub(crate) rows: Vec<UsageSummaryRow>,
+}
+
+/// Per-turn metrics (workspace, model, duration, tokens, status) recorded
+/// from app-server events into `usage-metrics.sqlite`.
+pub(crate) struct UsageMetrics {
+    path: PathBuf,
+    connection: Mutex<Option<Connection>>,
+    tracker: Mutex<TurnTracker>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl UsageMetrics {
+    /// The database at `path` is opened on first use.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            connection: Mutex::new(None),
+            tracker: Mutex::new(TurnTracker::default()),
+        }
+    }
+
+    fn with_connection<T>(
+        &self,
+        run: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut guard = self
+            .connection
+            .lock()
+            .map_err(|_| "usage metrics lock poisoned".to_string())?;
+        if guard.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let connection = Connection::open(&self.path).map_err(|e| e.to_string())?;
+            connection
+                .execute_batch(SCHEMA)
+                .map_err(|e| e.to_string())?;
+            *guard = Some(connection);
+        }
+        let connection = guard.as_ref().expect("usage metrics connection");
+        run(connection).map_err(|e| e.to_string())
+    }
+
+    /// Remembers the model a `turn/start` asked for, for the turn it starts.
+    pub(crate) fn note_model(&self, thread_id: &str, model: Option<&str>) {
+        let Ok(mut tracker) = self.tracker.lock() else {
+            return;
+        };
+        match model.map(str::trim).filter(|model| !model.is_empty()) {
+            Some(model) => {
+                tracker
+                    .thread_models
+                    .insert(thread_id.to_string(), model.to_string());
+            }
+            None => {
+                tracker.thread_models.remove(thread_id);
+            }
+        }
+    }
+
+    /// Feeds an app-server notification; a `turn/completed` writes a row.
+    pub(crate) fn on_event(&self, workspace_id: &str, method: &str, params: &Value) {
+        if !matches!(
+            method,
+            "turn/started" | "turn/completed" | "thread/tokenUsage/updated"
+        ) {
+            return;
+        }
+        let record = match self.tracker.lock() {
+            Ok(mut tracker) => tracker.on_event(workspace_id, method, params, now_ms()),
+            Err(_) => return,
+        };
+        if let Some(record) = record {
+            if let Err(error) = self.insert(&record) {
+                eprintln!("usage metrics: failed to record turn: {error}");
+            }
+        }
+    }
+
+    fn insert(&self, record: &TurnRecord) -> Result<(), String> {
+        self.with_connection(|connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO turns (
+                    workspace_id, thread_id, turn_id, model, status, error,
+                    started_at, completed_at, duration_ms, input_tokens, cached_input_tokens,
+                    output_tokens, reasoning_output_tokens, total_tokens
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    record.workspace_id,
+                    record.thread_id,
+                    record.turn_id,
+                    record.model,
+                    record.status,
+                    record.error,
+                    record.started_at as i64,
+                    record.completed_at as i64,
+                    record.completed_at.saturating_sub(record.started_at) as i64,
+                    record.tokens.input as i64,
+                    record.tokens.cached_input as i64,
+                    record.tokens.output as i64,
+                    record.tokens.reasoning_output as i64,
+                    record.tokens.total as i64,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Turns recorded for the workspace since metrics began.
+    pub(crate) fn completed_turns(&self, workspace_id: &str) -> Result<u64, String> {
+        self.with_connection(|connection| {
+            connection.query_row(
+                "SELECT COUNT(*) FROM turns WHERE workspace_id = ?1",
+                params![workspace_id],
+                |row| row.get::<_, i64>(0).map(|count| count.max(0) as u64),
+            )
+        })
+    }
+
+    /// Totals per workspace, model or UTC day for turns completed at or
+    /// after `since` (ms since the epoch), largest token count first.
+    pub(crate) fn summary(
+        &self,
+        since: Option<u64>,
+        group_by: UsageGroupBy,
+    ) -> Result<UsageSummary, String> {
+        let sql = format!(
+            "SELECT {key}, COUNT(*),
+                    SUM(status = 'failed'), SUM(status = 'interrupted'),
+                    SUM(duration_ms), SUM(input_tokens), SUM(cached_input_tokens),
+                    SUM(output_tokens), SUM(reasoning_output_tokens), SUM(total_tokens)
+             FROM turns
+             WHERE completed_at >= ?1
+             GROUP BY 1
+             ORDER BY SUM(total_tokens) DESC, 1",
+            key = group_by.column()
+        );
+        let rows = self.with_connection(|connection| {
+            let mut statement = connection.prepare(&sql)?;
+            let rows = statement.query_map(params![since.unwrap_or(0) as i64], |row| {
+                let count =
+                    |index: usize| row.get::<_, i64>(index).map(|value| value.max(0) as u64);
+                Ok(UsageSummaryRow {
+                    key: row.get(0)?,
+                    turns: count(1)?,
+                    failed_turns: count(2)?,
+                    interrupted_turns: count(3)?,
+                    duration_ms: count(4)?,
+                    input_tokens: count(5)?,
+                    cached_input_tokens: count(6)?,
+                    output_tokens: count(7)?,
+                    reasoning_output_tokens: count(8)?,
+                    total_tokens: count(9)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+        Ok(UsageSummary {
+            since,
+            group_by,
+            rows,
+        })
+    }
+}
+
+pub(crate) fn usage_summary_core(
+    metrics: &UsageMetrics,
+    since: Option<u64>,
+    group_by: Option<String>,
+) -> Result<UsageSummary, String> {
+    let group_by = UsageGroupBy::parse(group_by.as_deref())?;
+    metrics.summary(since, group_by)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usage(total: u64, last: u64) -> Value {
+        json!({
+            "threadId": "thread-1",
+            "tokenUsage": {
+                "total": { "inputTokens": total, "outputTokens": total / 10, "totalTokens": total + total / 10 },
+                "last": { "inputTokens": last, "outputTokens": last / 10, "totalTokens": last + last / 10 }
+            }
+        })
+    }
+
+    #[test]
+    fn turns_record_their_share_of_thread_usage() {
+        let mut tracker = TurnTracker::default();
+        let started = json!({ "threadId": "thread-1", "turn": { "id": "turn-1" } });
+        let completed = |status: &str| json!({ "threadId": "thread-1", "turn": { "id": "turn-1", "status": status } });
+        // A resumed thread: usage before this turn is only known from `last`.
+        tracker
+            .thread_models
+            .insert("thread-1".to_string(), "gpt-x".to_string());
+        assert!(tracker
+            .on_event("ws-1", "turn/started", &started, 1_000)
+            .is_none());
+        tracker.on_event(
+            "ws-1",
+            "thread/tokenUsage/updated",
+            &usage(5_000, 1_000),
+            1_500,
+        );
+        tracker.on_event(
+            "ws-1",
+            "thread/tokenUsage/updated",
+            &usage(5_500, 500),
+            2_000,
+        );
+        let record = tracker
+            .on_event("ws-1", "turn/completed", &completed("completed"), 4_000)
+            .unwrap();
+        assert_eq!(record.model.as_deref(), Some("gpt-x"));
+        assert_eq!(record.completed_at - record.started_at, 3_000);
+        assert_eq!(record.tokens.input, 1_500);
+        assert_eq!(record.tokens.total, 1_650);
+
+        // The next turn starts from the last known total.
+        tracker.on_event("ws-1", "turn/started", &started, 5_000);
+        tracker.on_event(
+            "ws-1",
+            "thread/tokenUsage/updated",
+            &usage(6_000, 500),
+            5_500,
+        );
+        let record = tracker
+            .on_event("ws-1", "turn/completed", &completed("failed"), 6_000)
+            .unwrap();
+        assert_eq!(record.status, "failed");
+        assert_eq!(record.tokens.input, 500);
+    }
+
+    #[test]
+    fn summary_groups_stored_turns() {
+        let nonce = uuid::Uuid::new_v4();
+        let dir = std::env::temp_dir().join(format!("codex-monitor-usage-{nonce}"));
+        let metrics = UsageMetrics::new(dir.join("usage-metrics.sqlite"));
+        let record =
+            |workspace_id: &str, turn_id: &str, model: Option<&str>, status: &str| TurnRecord {
+                workspace_id: workspace_id.to_string(),
+                thread_id: "thread-1".to_string(),
+                turn_id: turn_id.to_string(),
+                model: model.map(str::to_string),
+                status: status.to_string(),
+                error: None,
+                started_at: 86_400_000,
+                completed_at: 86_400_000 + 2_000,
+                tokens: TokenCounts {
+                    input: 100,
+                    total: 120,
+                    ..TokenCounts::default()
+                },
+            };
+        metrics
+            .insert(&record("ws-1", "t1", Some("gpt-x"), "completed"))
+            .unwrap();
+        metrics
+            .insert(&record("ws-1", "t2", None, "failed"))
+            .unwrap();
+        metrics
+            .insert(&record("ws-2", "t3", Some("gpt-x"), "interrupted"))
+            .unwrap();
+
+        let by_workspace = metrics.summary(None, UsageGroupBy::Workspace).unwrap();
+        assert_eq!(by_workspace.rows.len(), 2);
+        assert_eq!(by_workspace.rows[0].key, "ws-1");
+        assert_eq!(by_workspace.rows[0].turns, 2);
+        assert_eq!(by_workspace.rows[0].failed_turns, 1);
+        assert_eq!(by_workspace.rows[0].duration_ms, 4_000);
+        assert_eq!(by_workspace.rows[0].total_tokens, 240);
+
+        let by_model = metrics.summary(None, UsageGroupBy::Model).unwrap();
+        let keys: Vec<&str> = by_model.rows.iter().map(|row| row.key.as_str()).collect();
+        assert_eq!(keys, ["gpt-x", "default"]);
+
+        let by_day = metrics.summary(None, UsageGroupBy::Day).unwrap();
+        assert_eq!(by_day.rows[0].key, "1970-01-02");
+        assert!(metrics
+            .summary(Some(86_500_000), UsageGroupBy::Day)
+            .unwrap()
+            .rows
+            .is_empty());
+        assert!(UsageGroupBy::parse(Some("team")).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
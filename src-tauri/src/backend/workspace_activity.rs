@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// When each workspace last received an app-server notification. Kept in
+/// memory only, so a workspace has no last activity until its session sends
+/// something after a restart.
+#[derive(Default)]
+pub(crate) struct WorkspaceActivity {
+    last_event_at: Mutex<HashMap<String, u64>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl WorkspaceActivity {
+    pub(crate) fn touch(&self, workspace_id: &str) {
+        if let Ok(mut last_event_at) = self.last_event_at.lock() {
+            match last_event_at.get_mut(workspace_id) {
+                Some(at) => *at = now_ms(),
+                None => {
+                    last_event_at.insert(workspace_id.to_string(), now_ms());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn last_event_at(&self, workspace_id: &str) -> Option<u64> {
+        self.last_event_at
+            .lock()
+            .ok()
+            .and_then(|last_event_at| last_event_at.get(workspace_id).copied())
+    }
+}
@@ -0,0 +1,200 @@
+//! Thin public entry points into crate-private cores, compiled only with the `bench` feature so
+//! `benches/*` can measure the real code paths without widening the crate's visibility.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, StampedAppServerEvent};
+use crate::backend::services::BackendServices;
+use crate::shared::event_forward_core::{self, DaemonEvent};
+use crate::shared::{git_ui_core, local_usage_core, workspaces_core};
+use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+/// Day directory under a sessions root where today's transcripts are scanned from.
+pub fn usage_day_dir(sessions_root: &Path) -> PathBuf {
+    let day_keys = local_usage_core::make_day_keys(1);
+    let today = day_keys.last().cloned().unwrap_or_default();
+    local_usage_core::day_dir_for_key(sessions_root, &today)
+}
+
+/// Scan every transcript under `sessions_root` and return the total token count.
+pub fn scan_usage_sessions(sessions_root: &Path, days: u32) -> Result<i64, String> {
+    let snapshot = local_usage_core::scan_local_usage(days, None, &[sessions_root.to_path_buf()])?;
+    Ok(snapshot.totals.last30_days_tokens)
+}
+
+pub fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
+    git_ui_core::collect_workspace_diff_core(repo_root)
+}
+
+/// Build structured per-file diffs for the repo at `repo_root`, returning the file count.
+pub fn git_diffs_for_repo(
+    runtime: &tokio::runtime::Runtime,
+    repo_root: &Path,
+) -> Result<usize, String> {
+    let entry = WorkspaceEntry {
+        id: "bench".to_string(),
+        name: "bench".to_string(),
+        path: repo_root.to_string_lossy().to_string(),
+        kind: WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        settings: WorkspaceSettings::default(),
+    };
+    let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry)]));
+    let app_settings = Mutex::new(AppSettings::default());
+    runtime
+        .block_on(git_ui_core::get_git_diffs_core(
+            &workspaces,
+            &app_settings,
+            "bench".to_string(),
+        ))
+        .map(|diffs| diffs.len())
+}
+
+/// Push `events` app-server events through a broadcast channel to `subscribers` copies of the
+/// daemon's per-client `forward_events`. The channel holds every event, so no forwarder lags and
+/// each one delivers all of them. Returns the number of notifications delivered.
+pub fn fan_out_app_server_events(
+    runtime: &tokio::runtime::Runtime,
+    subscribers: usize,
+    events: usize,
+    payload_bytes: usize,
+) -> usize {
+    runtime.block_on(async move {
+        let (tx, _rx) = broadcast::channel::<DaemonEvent>(events.max(1));
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+        let forwarders: Vec<_> = (0..subscribers)
+            .map(|_| {
+                tokio::spawn(event_forward_core::forward_events(
+                    tx.subscribe(),
+                    out_tx.clone(),
+                ))
+            })
+            .collect();
+        drop(out_tx);
+
+        let delta = "x".repeat(payload_bytes);
+        for index in 0..events {
+            let _ = tx.send(DaemonEvent::AppServer(StampedAppServerEvent {
+                event: AppServerEvent {
+                    workspace_id: "bench".to_string(),
+                    message: json!({
+                        "method": "item/agentMessage/delta",
+                        "params": { "threadId": "thread-bench", "itemId": index, "delta": delta },
+                    }),
+                },
+                seq: index as u64 + 1,
+                emitted_at: 0,
+                revision: None,
+            }));
+        }
+        drop(tx);
+
+        let mut delivered = 0usize;
+        while out_rx.recv().await.is_some() {
+            delivered += 1;
+        }
+        for forwarder in forwarders {
+            let _ = forwarder.await;
+        }
+        delivered
+    })
+}
+
+/// Seed `workspace_count` workspaces, then run `readers` concurrent `list_workspaces` loops
+/// alongside `writers` concurrent settings updates persisted to `storage_path`, mirroring a
+/// daemon serving many clients while one of them edits settings. Returns the number of
+/// workspace listings served.
+pub fn workspace_state_contention(
+    runtime: &tokio::runtime::Runtime,
+    storage_path: &Path,
+    workspace_count: usize,
+    readers: usize,
+    writers: usize,
+) -> usize {
+    let entries = (0..workspace_count).map(|index| {
+        let id = format!("ws-{index}");
+        let entry = WorkspaceEntry {
+            id: id.clone(),
+            name: id.clone(),
+            path: format!("/tmp/{id}"),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        (id, entry)
+    });
+    let workspaces = Arc::new(RwLock::new(entries.collect::<HashMap<_, _>>()));
+    let sessions = Arc::new(RwLock::new(HashMap::<String, Arc<WorkspaceSession>>::new()));
+    let app_settings = Arc::new(Mutex::new(AppSettings::default()));
+    let services = BackendServices::load(storage_path.parent().unwrap_or(storage_path));
+    let storage_path = storage_path.to_path_buf();
+
+    runtime.block_on(async move {
+        let mut tasks = Vec::with_capacity(readers + writers);
+        for _ in 0..readers {
+            let workspaces = Arc::clone(&workspaces);
+            let sessions = Arc::clone(&sessions);
+            tasks.push(tokio::spawn(async move {
+                let mut served = 0usize;
+                for _ in 0..workspace_count {
+                    if !workspaces_core::list_workspaces_core(&workspaces, &sessions)
+                        .await
+                        .is_empty()
+                    {
+                        served += 1;
+                    }
+                }
+                served
+            }));
+        }
+        for writer in 0..writers {
+            let workspaces = Arc::clone(&workspaces);
+            let sessions = Arc::clone(&sessions);
+            let services = Arc::clone(&services);
+            let app_settings = Arc::clone(&app_settings);
+            let storage_path = storage_path.clone();
+            tasks.push(tokio::spawn(async move {
+                for index in (writer..workspace_count).step_by(writers.max(1)) {
+                    let settings = WorkspaceSettings {
+                        sort_order: Some(index as u32),
+                        ..WorkspaceSettings::default()
+                    };
+                    let _ = workspaces_core::update_workspace_settings_core(
+                        format!("ws-{index}"),
+                        settings,
+                        &workspaces,
+                        &sessions,
+                        &services,
+                        &app_settings,
+                        &storage_path,
+                        |workspaces, id, settings| {
+                            let entry = workspaces
+                                .get_mut(id)
+                                .ok_or_else(|| "workspace not found".to_string())?;
+                            entry.settings = settings;
+                            Ok(entry.clone())
+                        },
+                        |_, _, _, _| async {
+                            Err::<Arc<WorkspaceSession>, String>("no spawn".to_string())
+                        },
+                    )
+                    .await;
+                }
+                0
+            }));
+        }
+        let mut served = 0usize;
+        for task in tasks {
+            served += task.await.unwrap_or(0);
+        }
+        served
+    })
+}
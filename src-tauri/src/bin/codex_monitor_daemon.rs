@@ -1,3 +1,9 @@
+#[path = "codex_monitor_daemon/approval_chat.rs"]
+mod approval_chat;
+#[path = "codex_monitor_daemon/approvals.rs"]
+mod approvals;
+#[path = "codex_monitor_daemon/automation.rs"]
+mod automation;
 #[allow(dead_code)]
 #[path = "../backend/mod.rs"]
 mod backend;
@@ -7,6 +13,8 @@ mod codex_args;
 mod codex_config;
 #[path = "../codex/home.rs"]
 mod codex_home;
+#[path = "codex_monitor_daemon/dashboard.rs"]
+mod dashboard;
 #[path = "../files/io.rs"]
 mod file_io;
 #[path = "../files/ops.rs"]
@@ -15,10 +23,18 @@ mod file_ops;
 mod file_policy;
 #[path = "../git_utils.rs"]
 mod git_utils;
+#[path = "codex_monitor_daemon/merge_queue.rs"]
+mod merge_queue;
+#[path = "codex_monitor_daemon/plugins.rs"]
+mod plugins;
+#[path = "codex_monitor_daemon/push_relay.rs"]
+mod push_relay;
 #[path = "codex_monitor_daemon/rpc.rs"]
 mod rpc;
 #[path = "../rules.rs"]
 mod rules;
+#[path = "../secrets.rs"]
+mod secrets;
 #[path = "../shared/mod.rs"]
 mod shared;
 #[path = "../storage.rs"]
@@ -62,10 +78,8 @@ mod files {
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::fs::File;
-use std::io::Read;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -73,27 +87,84 @@ use std::sync::Arc;
 use ignore::WalkBuilder;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc, Mutex, Semaphore};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock, Semaphore};
 
 use backend::app_server::{spawn_workspace_session, WorkspaceSession};
-use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::event_dedup::Admission;
+use backend::events::{
+    AppServerEvent, EventSink, StampedAppServerEvent, TerminalExit, TerminalOutput,
+};
+use backend::operations::PendingOperation;
+use backend::protocol_trace::{self, ProtocolTraceStatus, ProtocolTraceTail};
+use backend::quotas;
+use backend::rate_limits::{self, RateLimitForecast};
+use backend::run_manager::{self, RunQueueSnapshot};
+use backend::services::BackendServices;
+use backend::session_provenance::{self, SessionProvenance};
+use backend::terminal_history::TerminalOutputPage;
+use backend::thread_journal::{self, ThreadTail};
+use backend::thread_preferences::{self, ThreadPreferences};
+use backend::turn_tracker::{self, ActiveTurn};
+use backend::usage_metrics::{self, UsageSummary};
+use file_io::WorkspaceFileResponse;
+use secrets::{claim_backend, SecretBackend};
+use shared::archive_undo_core::{self, PendingArchive};
+use shared::broadcast_core::{self, BroadcastRun, BroadcastRunStore, WinnerSelection};
+use shared::capabilities_core::{self, HostCapabilities};
 use shared::codex_core::CodexLoginCancelState;
+use shared::context_attach_core::{self, ContextAttachment};
+use shared::daemon_version_core::{
+    self, DaemonVersion, DAEMON_PROTOCOL_VERSION, MIN_DAEMON_PROTOCOL_VERSION,
+};
+use shared::dashboard_core::{self, DashboardSnapshot};
+use shared::dir_tree_core::{self, WorkspaceDirListing};
+use shared::discovery_core;
+use shared::event_forward_core::DaemonEvent;
+use shared::file_edit_core::{self, WorkspaceFileWriteResult};
+use shared::file_index_core::{self, WorkspaceFilePage};
+use shared::file_search_core::{self, FileSearchResult};
+use shared::file_transfer_core::{self, DownloadedFile, UploadedFile};
+use shared::file_watch_core;
+use shared::git_status_core::{self, WorkspaceGitStatus};
+use shared::host_stats_core::{self, HostStats};
+use shared::network_core;
+use shared::notifications_core::{self, NotificationCenter, NotificationEntry};
+use shared::playbooks_core::{self, Playbook, PlaybookDraft, PlaybookRun, PlaybookStore};
 use shared::process_core::kill_child_process_tree;
+use shared::prompt_library_core::{self, PromptLibrary, SavedPrompt};
 use shared::prompts_core::{self, CustomPromptEntry};
+use shared::scheduled_messages_core::{self, ScheduledMessage, ScheduledMessageStore};
+use shared::search_core::{SearchHit, SearchIndex};
+use shared::slash_commands_core::{self, SlashCommandInfo};
+use shared::storage_core;
+use shared::task_output_core::{self, AttachedOutput};
+use shared::thread_export_core::{self, ThreadExport};
+use shared::thread_meta_core::{self, ThreadMetaStore};
+use shared::thread_order_core::{self, ThreadOrder, ThreadOrderStore};
+use shared::thread_tags_core::{
+    self, ThreadAnnotations, ThreadLink, ThreadLinkKind, ThreadTagStore,
+};
+use shared::workspace_recency_core::{self, WorkspaceFocus, WorkspaceRecencyStore};
+use shared::workspace_summary_core::{self, WorkspaceSummary, WorkspaceSummaryCache};
+use shared::worktree_compare_core::{self, ChangeAttributions, WorktreeComparison};
+use shared::worktree_lock_core::{self, WorktreeLock};
 use shared::{
     agents_config_core, codex_aux_core, codex_core, files_core, git_core, git_ui_core,
-    local_usage_core, settings_core, workspaces_core, worktree_core,
+    local_usage_core, release_core, settings_core, workspace_rpc, workspaces_core, worktree_core,
 };
-use storage::{read_settings, read_workspaces};
+use storage::{read_settings, read_workspaces, StoreWriteOptions};
 use types::{
-    AppSettings, GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse, LocalUsageSnapshot,
-    WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
+    AppSettings, BranchNameSuggestion, GitAuthDoctorReport, GitCommitDiff, GitConflictsResponse,
+    GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse, GitLogResponse, LocalUsageSnapshot, NetworkConnectivityReport,
+    ReleaseManifest, StorageCategory, StorageCleanupResult, StorageUsageReport, WorkspaceEntry,
+    WorkspaceInfo, WorkspaceQuotaStatus, WorkspaceSettings, WorktreeResult, WorktreeSetupStatus,
 };
 use workspace_settings::apply_workspace_settings_update;
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
 const MAX_IN_FLIGHT_RPC_PER_CONNECTION: usize = 32;
+const MAX_WORKER_THREADS: usize = 256;
 const DAEMON_NAME: &str = "codex-monitor-daemon";
 
 fn spawn_with_client(
@@ -110,6 +181,7 @@ fn spawn_with_client(
         codex_args,
         codex_home,
         client_version,
+        Arc::clone(&event_sink.services),
         event_sink,
     )
 }
@@ -117,23 +189,28 @@ fn spawn_with_client(
 #[derive(Clone)]
 struct DaemonEventSink {
     tx: broadcast::Sender<DaemonEvent>,
-}
-
-#[derive(Clone)]
-enum DaemonEvent {
-    AppServer(AppServerEvent),
-    #[allow(dead_code)]
-    TerminalOutput(TerminalOutput),
-    #[allow(dead_code)]
-    TerminalExit(TerminalExit),
+    pending_approvals: Arc<approvals::PendingApprovals>,
+    services: Arc<BackendServices>,
 }
 
 impl EventSink for DaemonEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.tx.send(DaemonEvent::AppServer(event));
+        let revision = match self.services.event_dedup.admit(&event) {
+            Admission::Deliver(revision) => revision,
+            Admission::Duplicate => return,
+        };
+        // Recorded here rather than from a subscriber so a lagging receiver
+        // cannot drop an approval.
+        self.pending_approvals.record(&event);
+        self.services
+            .event_sequence
+            .stamp_and_deliver(event, revision, |stamped| {
+                let _ = self.tx.send(DaemonEvent::AppServer(stamped));
+            });
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
+        self.services.terminal_history.record(&event);
         let _ = self.tx.send(DaemonEvent::TerminalOutput(event));
     }
 
@@ -142,56 +219,245 @@ impl EventSink for DaemonEventSink {
     }
 }
 
+/// Feeds completed `fileChange` items into the attributions used by
+/// `compare_worktrees`.
+async fn track_change_attributions(
+    state: Arc<DaemonState>,
+    mut rx: broadcast::Receiver<DaemonEvent>,
+) {
+    loop {
+        match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => state.change_attributions.record(&stamped.event),
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Indexes completed thread items for `search_threads`.
+async fn track_search_index(state: Arc<DaemonState>, mut rx: broadcast::Receiver<DaemonEvent>) {
+    loop {
+        match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => state.search_index.record(&stamped.event),
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Invalidates cached `workspace_summary` details when turns complete.
+async fn track_workspace_summaries(
+    state: Arc<DaemonState>,
+    mut rx: broadcast::Receiver<DaemonEvent>,
+) {
+    loop {
+        match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => state.workspace_summaries.record(&stamped.event),
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Records notification-worthy events in the notification center and tells
+/// connected clients about each new entry.
+async fn track_notifications(state: Arc<DaemonState>, mut rx: broadcast::Receiver<DaemonEvent>) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => stamped.event,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if !notifications_core::may_notify(&event) {
+            continue;
+        }
+        let Some(notification) =
+            notifications_core::describe_event(&state.workspaces, &event).await
+        else {
+            continue;
+        };
+        let entry = state.notification_center.record(&notification);
+        state.event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: entry.workspace_id.clone(),
+            message: json!({ "method": "notifications/added", "params": entry }),
+        });
+    }
+}
+
+fn non_empty_url(url: Option<&String>) -> Option<String> {
+    url.map(|url| url.trim())
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+}
+
+/// Forwards turn completions, failures and approval requests to the
+/// configured webhooks: every event to the `--webhook-url` sink (or
+/// `daemonWebhookUrl`), and a short notification for opted-in workspaces to
+/// `notificationWebhookUrl`.
+async fn deliver_webhooks(state: Arc<DaemonState>, mut rx: broadcast::Receiver<DaemonEvent>) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => stamped.event,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if !notifications_core::may_notify(&event) {
+            continue;
+        }
+        let (sink_url, notification_url) = {
+            let settings = state.app_settings.lock().await;
+            (
+                non_empty_url(state.webhook_url.as_ref())
+                    .or_else(|| non_empty_url(settings.daemon_webhook_url.as_ref())),
+                non_empty_url(settings.notification_webhook_url.as_ref()),
+            )
+        };
+        if let Some(url) = sink_url {
+            if let Some(payload) =
+                notifications_core::webhook_event_for(&state.workspaces, &event).await
+            {
+                let network = state.services.network_settings();
+                tokio::spawn(async move {
+                    if let Err(error) =
+                        notifications_core::post_webhook(&network, &url, &payload).await
+                    {
+                        eprintln!("{error}");
+                    }
+                });
+            }
+        }
+        if let Some(url) = notification_url {
+            if let Some(notification) =
+                notifications_core::notification_for_event(&state.workspaces, &event).await
+            {
+                let network = state.services.network_settings();
+                tokio::spawn(async move {
+                    if let Err(error) =
+                        notifications_core::post_webhook(&network, &url, &notification).await
+                    {
+                        eprintln!("{error}");
+                    }
+                });
+            }
+        }
+    }
+}
+
 struct DaemonConfig {
     listen: SocketAddr,
     token: Option<String>,
     data_dir: PathBuf,
+    dashboard_listen: Option<SocketAddr>,
+    workers: Option<usize>,
+    webhook_url: Option<String>,
+    /// Advertise the daemon over mDNS (`_codexmonitor._tcp`).
+    advertise: bool,
 }
 
 struct DaemonState {
     data_dir: PathBuf,
-    workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     storage_path: PathBuf,
     settings_path: PathBuf,
     app_settings: Mutex<AppSettings>,
     event_sink: DaemonEventSink,
     codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
     daemon_binary_path: Option<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct WorkspaceFileResponse {
-    content: String,
-    truncated: bool,
+    plugins: plugins::PluginRegistry,
+    change_attributions: ChangeAttributions,
+    thread_tags: ThreadTagStore,
+    broadcast_runs: BroadcastRunStore,
+    thread_meta: ThreadMetaStore,
+    thread_order: ThreadOrderStore,
+    webhook_url: Option<String>,
+    notification_center: NotificationCenter,
+    push_devices: push_relay::PushDevices,
+    prompt_library: PromptLibrary,
+    playbooks: PlaybookStore,
+    scheduled_messages: ScheduledMessageStore,
+    search_index: SearchIndex,
+    workspace_summaries: WorkspaceSummaryCache,
+    workspace_recency: WorkspaceRecencyStore,
+    services: Arc<BackendServices>,
 }
 
 impl DaemonState {
     fn load(config: &DaemonConfig, event_sink: DaemonEventSink) -> Self {
         let storage_path = config.data_dir.join("workspaces.json");
         let settings_path = config.data_dir.join("settings.json");
+        let services = Arc::clone(&event_sink.services);
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
+        // Usually headless: no keychain to unlock, so a data dir the app has
+        // not claimed keeps secrets in the encrypted file.
+        if let Err(error) = claim_backend(&config.data_dir, SecretBackend::File) {
+            eprintln!("Failed to record the secrets store: {error}");
+        }
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        services.apply_settings(&app_settings);
+        services.run_manager.set_event_sink(event_sink.clone());
+        services.operations.set_event_sink(event_sink.clone());
         let daemon_binary_path = std::env::current_exe()
             .ok()
             .and_then(|path| path.to_str().map(str::to_string));
         Self {
             data_dir: config.data_dir.clone(),
-            workspaces: Mutex::new(workspaces),
-            sessions: Mutex::new(HashMap::new()),
+            workspaces: RwLock::new(workspaces),
+            sessions: RwLock::new(HashMap::new()),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
             event_sink,
             codex_login_cancels: Mutex::new(HashMap::new()),
             daemon_binary_path,
+            plugins: plugins::PluginRegistry::load(&config.data_dir),
+            change_attributions: ChangeAttributions::default(),
+            thread_tags: ThreadTagStore::load(config.data_dir.join("thread-tags.json")),
+            broadcast_runs: BroadcastRunStore::load(config.data_dir.join("broadcast-runs.json")),
+            thread_meta: ThreadMetaStore::load(config.data_dir.join("thread-meta.json")),
+            thread_order: ThreadOrderStore::load(config.data_dir.join("thread-order.json")),
+            webhook_url: config.webhook_url.clone(),
+            notification_center: NotificationCenter::load(
+                config.data_dir.join("notifications.json"),
+            ),
+            push_devices: push_relay::PushDevices::load(config.data_dir.join("push-devices.json")),
+            prompt_library: PromptLibrary::load(config.data_dir.join("prompt-library.json")),
+            playbooks: PlaybookStore::load(config.data_dir.join("playbooks.json")),
+            scheduled_messages: ScheduledMessageStore::load(
+                config.data_dir.join("scheduled-messages.json"),
+            ),
+            search_index: SearchIndex::load(config.data_dir.join("search-index.sqlite")),
+            workspace_summaries: WorkspaceSummaryCache::default(),
+            workspace_recency: WorkspaceRecencyStore::load(
+                config.data_dir.join("workspace-recency.json"),
+            ),
+            services,
         }
     }
 
+    async fn get_capabilities(&self) -> HostCapabilities {
+        capabilities_core::host_capabilities_core(&self.app_settings).await
+    }
+
+    /// Stops every app-server so a shutdown leaves none behind.
+    async fn stop_all_sessions(&self) {
+        workspaces_core::disconnect_all_sessions_core(&self.sessions).await;
+    }
+
+    async fn host_stats(&self) -> HostStats {
+        host_stats_core::host_stats_core(&self.data_dir).await
+    }
+
+    fn daemon_version(&self) -> DaemonVersion {
+        daemon_version_core::daemon_version_core()
+    }
+
     fn daemon_info(&self) -> Value {
         json!({
             "name": DAEMON_NAME,
             "version": env!("CARGO_PKG_VERSION"),
+            "protocolVersion": DAEMON_PROTOCOL_VERSION,
+            "minProtocolVersion": MIN_DAEMON_PROTOCOL_VERSION,
             "pid": std::process::id(),
             "mode": "tcp",
             "binaryPath": self.daemon_binary_path,
@@ -211,12 +477,12 @@ impl DaemonState {
         };
         let workspace_ids: HashSet<String> = stored.keys().cloned().collect();
         {
-            let mut workspaces = self.workspaces.lock().await;
+            let mut workspaces = self.workspaces.write().await;
             *workspaces = stored;
         }
 
         let stale_sessions: Vec<(String, Arc<WorkspaceSession>)> = {
-            let mut sessions = self.sessions.lock().await;
+            let mut sessions = self.sessions.write().await;
             sessions
                 .keys()
                 .filter(|id| !workspace_ids.contains(*id))
@@ -310,7 +576,7 @@ impl DaemonState {
         name: Option<String>,
         copy_agents_md: bool,
         client_version: String,
-    ) -> Result<WorkspaceInfo, String> {
+    ) -> Result<WorktreeResult, String> {
         let client_version = client_version.clone();
         workspaces_core::add_worktree_core(
             parent_id,
@@ -320,6 +586,7 @@ impl DaemonState {
             &self.data_dir,
             &self.workspaces,
             &self.sessions,
+            &self.services,
             &self.app_settings,
             &self.storage_path,
             |value| worktree_core::sanitize_worktree_name(value),
@@ -351,6 +618,57 @@ impl DaemonState {
         .await
     }
 
+    async fn watch_workspace_files(&self, id: String) -> Result<(), String> {
+        file_watch_core::watch_workspace_files_core(
+            &self.services,
+            &id,
+            &self.workspaces,
+            self.event_sink.clone(),
+        )
+        .await
+    }
+
+    fn unwatch_workspace_files(&self, id: String) {
+        file_watch_core::unwatch_workspace_files_core(&self.services, &id);
+    }
+
+    fn list_pending_operations(&self) -> Vec<PendingOperation> {
+        self.services.operations.list()
+    }
+
+    async fn retry_operation(
+        &self,
+        operation_id: String,
+        client_version: String,
+    ) -> Result<Value, String> {
+        let operation = self.services.operations.take_failed(&operation_id)?;
+        match operation.kind.as_str() {
+            "add_worktree" => {
+                let request: workspace_rpc::AddWorktreeRequest =
+                    serde_json::from_value(operation.params).map_err(|err| err.to_string())?;
+                let result = self
+                    .add_worktree(
+                        request.parent_id,
+                        request.branch,
+                        request.name,
+                        request.copy_agents_md,
+                        client_version,
+                    )
+                    .await?;
+                serde_json::to_value(result).map_err(|err| err.to_string())
+            }
+            kind => Err(format!("`{kind}` operations cannot be retried.")),
+        }
+    }
+
+    async fn suggest_branch_name(
+        &self,
+        workspace_id: String,
+        base: String,
+    ) -> Result<BranchNameSuggestion, String> {
+        workspaces_core::suggest_branch_name_core(&self.workspaces, workspace_id, base).await
+    }
+
     async fn worktree_setup_status(
         &self,
         workspace_id: String,
@@ -373,7 +691,9 @@ impl DaemonState {
             id,
             &self.workspaces,
             &self.sessions,
+            &self.services,
             &self.storage_path,
+            StoreWriteOptions::from_settings(&*self.app_settings.lock().await),
             |root, args| {
                 workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
             },
@@ -393,7 +713,9 @@ impl DaemonState {
             id,
             &self.workspaces,
             &self.sessions,
+            &self.services,
             &self.storage_path,
+            StoreWriteOptions::from_settings(&*self.app_settings.lock().await),
             |root, args| {
                 workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
             },
@@ -406,12 +728,16 @@ impl DaemonState {
         .await
     }
 
+    async fn unlock_worktree(&self, id: String) -> Result<Option<WorktreeLock>, String> {
+        worktree_lock_core::unlock_worktree_core(&self.workspaces, &id).await
+    }
+
     async fn rename_worktree(
         &self,
         id: String,
         branch: String,
         client_version: String,
-    ) -> Result<WorkspaceInfo, String> {
+    ) -> Result<WorktreeResult, String> {
         let client_version = client_version.clone();
         workspaces_core::rename_worktree_core(
             id,
@@ -506,6 +832,7 @@ impl DaemonState {
             settings,
             &self.workspaces,
             &self.sessions,
+            &self.services,
             &self.app_settings,
             &self.storage_path,
             |workspaces, workspace_id, next_settings| {
@@ -525,9 +852,37 @@ impl DaemonState {
         .await
     }
 
+    async fn update_workspaces_bulk(
+        &self,
+        ids: Vec<String>,
+        partial_settings: Value,
+        client_version: String,
+    ) -> Result<Vec<WorkspaceInfo>, String> {
+        workspaces_core::update_workspaces_bulk_core(
+            ids,
+            partial_settings,
+            &self.workspaces,
+            &self.sessions,
+            &self.services,
+            &self.app_settings,
+            &self.storage_path,
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
         {
-            let sessions = self.sessions.lock().await;
+            let sessions = self.sessions.read().await;
             if sessions.contains_key(&id) {
                 return Ok(());
             }
@@ -538,6 +893,7 @@ impl DaemonState {
             id,
             &self.workspaces,
             &self.sessions,
+            &self.services,
             &self.app_settings,
             move |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
@@ -553,6 +909,63 @@ impl DaemonState {
         .await
     }
 
+    async fn broadcast_run(
+        &self,
+        workspace_ids: Vec<String>,
+        text: String,
+        model: Option<String>,
+        worktree_branch: Option<String>,
+        client_version: String,
+    ) -> Result<BroadcastRun, String> {
+        let run = broadcast_core::broadcast_run_core(
+            &self.sessions,
+            &self.workspaces,
+            &self.event_sink,
+            workspace_ids,
+            text,
+            model,
+            worktree_branch,
+            |workspace_id| self.connect_workspace(workspace_id, client_version.clone()),
+            |parent_id, branch| {
+                self.add_worktree(parent_id, branch, None, true, client_version.clone())
+            },
+        )
+        .await?;
+        if let Err(error) = self.broadcast_runs.record(&run) {
+            eprintln!("Failed to record broadcast run: {error}");
+        }
+        Ok(run)
+    }
+
+    async fn select_winner(
+        &self,
+        run_id: String,
+        worktree_id: String,
+        client_version: String,
+    ) -> Result<WinnerSelection, String> {
+        let retention = self.app_settings.lock().await.losing_worktree_retention;
+        broadcast_core::select_winner_core(
+            &self.broadcast_runs,
+            &self.workspaces,
+            &self.thread_tags,
+            retention,
+            run_id,
+            worktree_id,
+            |workspace_id, thread_id| {
+                let client_version = client_version.clone();
+                async move {
+                    self.connect_workspace(workspace_id.clone(), client_version)
+                        .await?;
+                    self.archive_thread(workspace_id, thread_id)
+                        .await
+                        .map(|_| ())
+                }
+            },
+            |worktree_id| self.remove_worktree(worktree_id),
+        )
+        .await
+    }
+
     async fn set_workspace_runtime_codex_args(
         &self,
         workspace_id: String,
@@ -584,8 +997,50 @@ impl DaemonState {
     }
 
     async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
-        settings_core::update_app_settings_core(settings, &self.app_settings, &self.settings_path)
-            .await
+        let updated = settings_core::update_app_settings_core(
+            settings,
+            &self.app_settings,
+            &self.settings_path,
+            &self.services,
+        )
+        .await?;
+        workspaces_core::sync_request_timeouts_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn storage_usage_report(&self) -> Result<StorageUsageReport, String> {
+        let settings = self.app_settings.lock().await.clone();
+        let data_dir = self.data_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            storage_core::storage_usage_report_core(&data_dir, &settings)
+        })
+        .await
+        .map_err(|err| err.to_string())
+    }
+
+    async fn storage_cleanup(
+        &self,
+        category: StorageCategory,
+        older_than_days: Option<u32>,
+    ) -> Result<StorageCleanupResult, String> {
+        let data_dir = self.data_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            storage_core::cleanup_storage_core(&data_dir, category, older_than_days)
+        })
+        .await
+        .map_err(|err| err.to_string())
+    }
+
+    async fn test_network_connectivity(
+        &self,
+        urls: Option<Vec<String>>,
+    ) -> Result<NetworkConnectivityReport, String> {
+        network_core::test_network_connectivity_core(self.services.network_settings(), urls).await
     }
 
     async fn set_codex_feature_flag(
@@ -596,6 +1051,30 @@ impl DaemonState {
         codex_config::write_feature_enabled(feature_key.as_str(), enabled)
     }
 
+    async fn get_codex_config(&self) -> Result<codex_config::CodexConfigSettings, String> {
+        codex_config::read_codex_config()
+    }
+
+    async fn set_codex_config_value(
+        &self,
+        key: String,
+        value: Option<String>,
+    ) -> Result<(), String> {
+        codex_config::write_config_string(&key, value.as_deref())
+    }
+
+    async fn set_codex_mcp_server(
+        &self,
+        name: String,
+        server: Option<codex_config::McpServerConfig>,
+    ) -> Result<(), String> {
+        codex_config::write_mcp_server(&name, server.as_ref())
+    }
+
+    async fn validate_codex_config(&self) -> Result<codex_config::CodexConfigValidation, String> {
+        codex_config::validate_codex_config()
+    }
+
     async fn get_agents_settings(&self) -> Result<agents_config_core::AgentsSettingsDto, String> {
         agents_config_core::get_agents_settings_core()
     }
@@ -640,10 +1119,106 @@ impl DaemonState {
         agents_config_core::write_agent_config_toml_core(agent_name.as_str(), content.as_str())
     }
 
-    async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
-        workspaces_core::list_workspace_files_core(&self.workspaces, &workspace_id, |root| {
-            list_workspace_files_inner(root, 20000)
-        })
+    async fn list_workspace_files(
+        &self,
+        workspace_id: String,
+        refresh: bool,
+    ) -> Result<Vec<String>, String> {
+        let (files, _) = file_index_core::cached_workspace_files_core(
+            &self.services,
+            &self.workspaces,
+            &workspace_id,
+            refresh,
+            |root| list_workspace_files_inner(root, 20000),
+        )
+        .await?;
+        Ok(files.to_vec())
+    }
+
+    async fn list_workspace_dir(
+        &self,
+        workspace_id: String,
+        path: String,
+    ) -> Result<WorkspaceDirListing, String> {
+        dir_tree_core::list_workspace_dir_core(&self.workspaces, &workspace_id, &path).await
+    }
+
+    async fn attach_context(
+        &self,
+        workspace_id: String,
+        paths: Vec<String>,
+    ) -> Result<ContextAttachment, String> {
+        context_attach_core::attach_context_core(&self.workspaces, &workspace_id, paths).await
+    }
+
+    async fn record_workspace_focus(
+        &self,
+        workspace_id: String,
+        device: Option<String>,
+    ) -> Result<WorkspaceFocus, String> {
+        workspace_recency_core::record_workspace_focus_core(
+            &self.workspace_recency,
+            &self.workspaces,
+            &workspace_id,
+            device,
+        )
+        .await
+    }
+
+    async fn recent_workspaces(&self, limit: Option<usize>) -> Vec<WorkspaceFocus> {
+        workspace_recency_core::recent_workspaces_core(
+            &self.workspace_recency,
+            &self.workspaces,
+            limit,
+        )
+        .await
+    }
+
+    async fn list_workspace_files_page(
+        &self,
+        workspace_id: String,
+        refresh: bool,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<WorkspaceFilePage, String> {
+        file_index_core::list_workspace_files_page_core(
+            &self.services,
+            &self.workspaces,
+            &workspace_id,
+            refresh,
+            offset,
+            limit,
+            |root| list_workspace_files_inner(root, 20000),
+        )
+        .await
+    }
+
+    fn terminal_output_page(
+        &self,
+        workspace_id: &str,
+        terminal_id: &str,
+        cursor: Option<u64>,
+        max_bytes: Option<usize>,
+    ) -> Result<TerminalOutputPage, String> {
+        self.services
+            .terminal_history
+            .page(workspace_id, terminal_id, cursor, max_bytes)
+    }
+
+    async fn search_workspace_files(
+        &self,
+        workspace_id: String,
+        query: String,
+        regex: bool,
+        max_results: Option<usize>,
+    ) -> Result<FileSearchResult, String> {
+        file_search_core::search_workspace_files_core(
+            &self.workspaces,
+            &workspace_id,
+            &query,
+            regex,
+            max_results,
+        )
         .await
     }
 
@@ -651,12 +1226,143 @@ impl DaemonState {
         &self,
         workspace_id: String,
         path: String,
+        offset: Option<u64>,
+        length: Option<u64>,
     ) -> Result<WorkspaceFileResponse, String> {
         workspaces_core::read_workspace_file_core(
             &self.workspaces,
             &workspace_id,
             &path,
-            |root, rel_path| read_workspace_file_inner(root, rel_path),
+            |root, rel_path| {
+                file_io::read_file_range_within(root, rel_path, offset.unwrap_or(0), length)
+            },
+        )
+        .await
+    }
+
+    async fn write_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        content: String,
+        expected_hash: Option<String>,
+    ) -> Result<WorkspaceFileWriteResult, String> {
+        file_edit_core::write_workspace_file_core(
+            &self.workspaces,
+            &self.services.file_writes,
+            &workspace_id,
+            &path,
+            &content,
+            expected_hash.as_deref(),
+        )
+        .await
+    }
+
+    async fn apply_workspace_patch(
+        &self,
+        workspace_id: String,
+        path: String,
+        patch: String,
+        expected_hash: Option<String>,
+    ) -> Result<WorkspaceFileWriteResult, String> {
+        file_edit_core::apply_workspace_patch_core(
+            &self.workspaces,
+            &self.services.file_writes,
+            &workspace_id,
+            &path,
+            &patch,
+            expected_hash.as_deref(),
+        )
+        .await
+    }
+
+    async fn upload_file(
+        &self,
+        workspace_id: String,
+        relative_path: Option<String>,
+        file_name: Option<String>,
+        bytes_base64: String,
+    ) -> Result<UploadedFile, String> {
+        file_transfer_core::upload_file_core(
+            &self.workspaces,
+            &self.data_dir,
+            &workspace_id,
+            relative_path.as_deref(),
+            file_name.as_deref(),
+            &bytes_base64,
+        )
+        .await
+    }
+
+    async fn download_file(
+        &self,
+        workspace_id: String,
+        relative_path: String,
+    ) -> Result<DownloadedFile, String> {
+        file_transfer_core::download_file_core(&self.workspaces, &workspace_id, &relative_path)
+            .await
+    }
+
+    async fn workspace_quota_status(
+        &self,
+        workspace_id: String,
+    ) -> Result<WorkspaceQuotaStatus, String> {
+        quotas::workspace_quota_status_core(
+            &self.workspaces,
+            &self.services.session_quotas,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn resume_workspace_quota(
+        &self,
+        workspace_id: String,
+    ) -> Result<WorkspaceQuotaStatus, String> {
+        quotas::resume_workspace_quota_core(
+            &self.workspaces,
+            &self.services.session_quotas,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn package_release(
+        &self,
+        workspace_id: String,
+        task: String,
+    ) -> Result<ReleaseManifest, String> {
+        release_core::package_release_core(&self.workspaces, workspace_id, task).await
+    }
+
+    async fn run_and_attach(
+        &self,
+        workspace_id: String,
+        command: String,
+    ) -> Result<AttachedOutput, String> {
+        task_output_core::run_and_attach_core(&self.workspaces, workspace_id, command).await
+    }
+
+    async fn dashboard_snapshot(&self) -> DashboardSnapshot {
+        let mut pending_approvals = HashMap::new();
+        for approval in self.event_sink.pending_approvals.list(None) {
+            *pending_approvals.entry(approval.workspace_id).or_insert(0) += 1;
+        }
+        dashboard_core::dashboard_snapshot_core(
+            &self.services,
+            &self.workspaces,
+            &self.sessions,
+            &pending_approvals,
+        )
+        .await
+    }
+
+    async fn workspace_summary(&self, workspace_id: String) -> Result<WorkspaceSummary, String> {
+        workspace_summary_core::workspace_summary_core(
+            &self.services,
+            &self.workspaces,
+            &self.workspace_summaries,
+            workspace_id,
         )
         .await
     }
@@ -700,6 +1406,33 @@ impl DaemonState {
         codex_core::read_thread_core(&self.sessions, workspace_id, thread_id).await
     }
 
+    async fn export_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        format: String,
+        path: Option<String>,
+    ) -> Result<ThreadExport, String> {
+        thread_export_core::export_thread_core(
+            &self.sessions,
+            workspace_id,
+            thread_id,
+            format,
+            path,
+        )
+        .await
+    }
+
+    fn search_threads(
+        &self,
+        workspace_id: Option<String>,
+        query: String,
+        limit: Option<usize>,
+    ) -> Result<Vec<SearchHit>, String> {
+        self.search_index
+            .search(workspace_id.as_deref(), &query, limit)
+    }
+
     async fn thread_live_subscribe(
         &self,
         workspace_id: String,
@@ -754,36 +1487,221 @@ impl DaemonState {
         Ok(json!({ "ok": true }))
     }
 
-    async fn fork_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
-        codex_core::fork_thread_core(&self.sessions, workspace_id, thread_id).await
+    async fn fork_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
+        codex_core::fork_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn list_threads(
+        &self,
+        workspace_id: String,
+        cursor: Option<String>,
+        limit: Option<u32>,
+        sort_key: Option<String>,
+        tag: Option<String>,
+    ) -> Result<Value, String> {
+        let response = codex_core::list_threads_core(
+            &self.sessions,
+            workspace_id.clone(),
+            cursor,
+            limit,
+            sort_key,
+        )
+        .await?;
+        let response = match tag {
+            Some(tag) => {
+                thread_tags_core::filter_thread_list_by_tag(&self.thread_tags, response, &tag)
+            }
+            None => response,
+        };
+        Ok(thread_order_core::apply_thread_order(
+            &self.thread_order,
+            &workspace_id,
+            response,
+        ))
+    }
+
+    fn tag_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        tags: Vec<String>,
+    ) -> Result<ThreadAnnotations, String> {
+        thread_tags_core::tag_thread_core(&self.thread_tags, workspace_id, thread_id, tags)
+    }
+
+    fn link_threads(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        target_workspace_id: Option<String>,
+        target_thread_id: String,
+        kind: String,
+    ) -> Result<ThreadAnnotations, String> {
+        let target = ThreadLink {
+            thread_id: target_thread_id,
+            workspace_id: target_workspace_id.unwrap_or_else(|| workspace_id.clone()),
+            kind: ThreadLinkKind::parse(&kind)?,
+        };
+        thread_tags_core::link_threads_core(&self.thread_tags, workspace_id, thread_id, target)
+    }
+
+    fn unlink_threads(
+        &self,
+        thread_id: String,
+        target_thread_id: String,
+        kind: Option<String>,
+    ) -> Result<(), String> {
+        let kind = kind.as_deref().map(ThreadLinkKind::parse).transpose()?;
+        thread_tags_core::unlink_threads_core(&self.thread_tags, thread_id, target_thread_id, kind)
+    }
+
+    fn pin_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        pinned: bool,
+    ) -> Result<ThreadOrder, String> {
+        thread_order_core::pin_thread_core(&self.thread_order, workspace_id, thread_id, pinned)
+    }
+
+    fn set_thread_order(
+        &self,
+        workspace_id: String,
+        thread_ids: Vec<String>,
+    ) -> Result<ThreadOrder, String> {
+        thread_order_core::set_thread_order_core(&self.thread_order, workspace_id, thread_ids)
+    }
+
+    fn set_thread_meta(
+        &self,
+        thread_id: String,
+        key: String,
+        value: Value,
+    ) -> Result<BTreeMap<String, Value>, String> {
+        thread_meta_core::set_thread_meta_core(&self.thread_meta, thread_id, key, value)
+    }
+
+    fn get_thread_meta(&self, thread_id: &str) -> BTreeMap<String, Value> {
+        thread_meta_core::get_thread_meta_core(&self.thread_meta, thread_id)
+    }
+
+    fn get_thread_preferences(
+        &self,
+        thread_id: &str,
+        workspace_id: Option<&str>,
+    ) -> Result<ThreadPreferences, String> {
+        thread_preferences::get_thread_preferences_core(
+            &self.services.thread_preferences,
+            thread_id,
+            workspace_id,
+        )
+    }
+
+    fn set_thread_preferences(
+        &self,
+        thread_id: &str,
+        workspace_id: Option<&str>,
+        model: Option<String>,
+        effort: Option<String>,
+    ) -> Result<ThreadPreferences, String> {
+        thread_preferences::set_thread_preferences_core(
+            &self.services.thread_preferences,
+            thread_id,
+            workspace_id,
+            model,
+            effort,
+        )
+    }
+
+    fn session_provenance(&self, workspace_id: &str) -> Result<Vec<SessionProvenance>, String> {
+        session_provenance::session_provenance_core(&self.services.session_provenance, workspace_id)
+    }
+
+    fn list_active_turns(&self, workspace_id: Option<&str>) -> Vec<ActiveTurn> {
+        turn_tracker::list_active_turns_core(&self.services.turn_tracker, workspace_id)
+    }
+
+    fn list_thread_annotations(&self, workspace_id: &str) -> HashMap<String, ThreadAnnotations> {
+        thread_tags_core::list_thread_annotations_core(&self.thread_tags, workspace_id)
+    }
+
+    async fn list_mcp_server_status(
+        &self,
+        workspace_id: String,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Value, String> {
+        codex_core::list_mcp_server_status_core(&self.sessions, workspace_id, cursor, limit).await
+    }
+
+    async fn archive_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        let response = codex_core::archive_thread_core(
+            &self.sessions,
+            workspace_id.clone(),
+            thread_id.clone(),
+        )
+        .await?;
+        let _ = thread_meta_core::clear_thread_meta_core(&self.thread_meta, &thread_id);
+        let _ = thread_order_core::clear_thread_order_core(
+            &self.thread_order,
+            &workspace_id,
+            &thread_id,
+        );
+        Ok(response)
+    }
+
+    fn archive_thread_with_undo(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        delay_seconds: Option<u64>,
+    ) -> PendingArchive {
+        archive_undo_core::archive_thread_with_undo_core(
+            &self.services.pending_archives,
+            &workspace_id,
+            &thread_id,
+            delay_seconds,
+        )
     }
 
-    async fn list_threads(
+    fn undo_archive(&self, token: String) -> Result<PendingArchive, String> {
+        archive_undo_core::undo_archive_core(&self.services.pending_archives, &token)
+    }
+
+    fn set_protocol_trace(
         &self,
         workspace_id: String,
-        cursor: Option<String>,
-        limit: Option<u32>,
-        sort_key: Option<String>,
-    ) -> Result<Value, String> {
-        codex_core::list_threads_core(&self.sessions, workspace_id, cursor, limit, sort_key)
-            .await
+        enabled: bool,
+    ) -> Result<ProtocolTraceStatus, String> {
+        protocol_trace::set_protocol_trace_core(
+            &self.services.protocol_traces,
+            &workspace_id,
+            enabled,
+        )
     }
 
-    async fn list_mcp_server_status(
+    fn protocol_trace_tail(
         &self,
         workspace_id: String,
-        cursor: Option<String>,
-        limit: Option<u32>,
-    ) -> Result<Value, String> {
-        codex_core::list_mcp_server_status_core(&self.sessions, workspace_id, cursor, limit).await
+        lines: Option<usize>,
+    ) -> Result<ProtocolTraceTail, String> {
+        protocol_trace::protocol_trace_tail_core(
+            &self.services.protocol_traces,
+            &workspace_id,
+            lines,
+        )
     }
 
-    async fn archive_thread(
+    fn get_thread_tail(
         &self,
-        workspace_id: String,
         thread_id: String,
-    ) -> Result<Value, String> {
-        codex_core::archive_thread_core(&self.sessions, workspace_id, thread_id).await
+        lines: Option<usize>,
+    ) -> Result<ThreadTail, String> {
+        thread_journal::get_thread_tail_core(&self.services.thread_journals, &thread_id, lines)
     }
 
     async fn compact_thread(
@@ -791,7 +1709,8 @@ impl DaemonState {
         workspace_id: String,
         thread_id: String,
     ) -> Result<Value, String> {
-        codex_core::compact_thread_core(&self.sessions, workspace_id, thread_id).await
+        codex_core::compact_thread_core(&self.sessions, &self.workspaces, workspace_id, thread_id)
+            .await
     }
 
     async fn set_thread_name(
@@ -863,6 +1782,52 @@ impl DaemonState {
         codex_core::turn_interrupt_core(&self.sessions, workspace_id, thread_id, turn_id).await
     }
 
+    fn list_slash_commands(&self) -> Vec<SlashCommandInfo> {
+        slash_commands_core::list_slash_commands_core()
+    }
+
+    fn queue_list(&self) -> RunQueueSnapshot {
+        run_manager::queue_list_core(&self.services.run_manager)
+    }
+
+    fn rate_limit_forecast(&self) -> RateLimitForecast {
+        rate_limits::rate_limit_forecast_core(&self.services.run_manager)
+    }
+
+    fn queue_cancel(&self, queue_id: &str) -> Result<(), String> {
+        run_manager::queue_cancel_core(&self.services.run_manager, queue_id)
+    }
+
+    fn schedule_message(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        text: String,
+        send_at: u64,
+    ) -> Result<ScheduledMessage, String> {
+        scheduled_messages_core::schedule_message_core(
+            &self.scheduled_messages,
+            workspace_id,
+            thread_id,
+            text,
+            send_at,
+        )
+    }
+
+    fn list_scheduled_messages(&self, workspace_id: Option<&str>) -> Vec<ScheduledMessage> {
+        scheduled_messages_core::list_scheduled_messages_core(
+            &self.scheduled_messages,
+            workspace_id,
+        )
+    }
+
+    fn cancel_scheduled_message(&self, schedule_id: &str) -> Result<(), String> {
+        scheduled_messages_core::cancel_scheduled_message_core(
+            &self.scheduled_messages,
+            schedule_id,
+        )
+    }
+
     async fn start_review(
         &self,
         workspace_id: String,
@@ -870,8 +1835,15 @@ impl DaemonState {
         target: Value,
         delivery: Option<String>,
     ) -> Result<Value, String> {
-        codex_core::start_review_core(&self.sessions, workspace_id, thread_id, target, delivery)
-            .await
+        codex_core::start_review_core(
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            thread_id,
+            target,
+            delivery,
+        )
+        .await
     }
 
     async fn model_list(&self, workspace_id: String) -> Result<Value, String> {
@@ -904,6 +1876,48 @@ impl DaemonState {
         codex_core::codex_login_core(&self.sessions, &self.codex_login_cancels, workspace_id).await
     }
 
+    async fn codex_login_api_key(
+        &self,
+        workspace_id: String,
+        api_key: String,
+    ) -> Result<Value, String> {
+        codex_core::codex_login_api_key_core(
+            &self.services,
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            api_key,
+        )
+        .await
+    }
+
+    async fn codex_logout(&self, workspace_id: String) -> Result<Value, String> {
+        codex_core::codex_logout_core(
+            &self.services,
+            &self.sessions,
+            &self.workspaces,
+            &self.codex_login_cancels,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn account_switch(
+        &self,
+        workspace_id: String,
+        api_key: Option<String>,
+    ) -> Result<Value, String> {
+        codex_core::account_switch_core(
+            &self.services,
+            &self.sessions,
+            &self.workspaces,
+            &self.codex_login_cancels,
+            workspace_id,
+            api_key,
+        )
+        .await
+    }
+
     async fn codex_login_cancel(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::codex_login_cancel_core(&self.sessions, &self.codex_login_cancels, workspace_id)
             .await
@@ -931,14 +1945,33 @@ impl DaemonState {
     ) -> Result<Value, String> {
         codex_core::respond_to_server_request_core(
             &self.sessions,
-            workspace_id,
-            request_id,
+            workspace_id.clone(),
+            request_id.clone(),
             result,
         )
         .await?;
+        self.event_sink
+            .pending_approvals
+            .resolve(&workspace_id, &request_id);
         Ok(json!({ "ok": true }))
     }
 
+    fn list_notifications(&self, unread_only: bool, limit: Option<u32>) -> Vec<NotificationEntry> {
+        self.notification_center
+            .list(unread_only, limit.map(|limit| limit as usize))
+    }
+
+    fn mark_notifications_read(&self, ids: Option<Vec<String>>) -> usize {
+        self.notification_center.mark_read(ids.as_deref())
+    }
+
+    fn list_pending_approvals(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> Vec<approvals::PendingApproval> {
+        self.event_sink.pending_approvals.list(workspace_id)
+    }
+
     async fn remember_approval_rule(
         &self,
         workspace_id: String,
@@ -947,6 +1980,45 @@ impl DaemonState {
         codex_core::remember_approval_rule_core(&self.workspaces, workspace_id, command).await
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn add_approval_rule(
+        &self,
+        workspace_id: String,
+        kind: String,
+        pattern: String,
+        decision: String,
+        scoped: bool,
+        expires_at: Option<u64>,
+    ) -> Result<rules::ApprovalRule, String> {
+        codex_core::add_approval_rule_core(
+            &self.services.approval_policy,
+            &self.workspaces,
+            workspace_id,
+            kind,
+            pattern,
+            decision,
+            scoped,
+            expires_at,
+        )
+        .await
+    }
+
+    async fn list_approval_rules(
+        &self,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<rules::ApprovalRule>, String> {
+        codex_core::list_approval_rules_core(
+            &self.services.approval_policy,
+            &self.workspaces,
+            workspace_id,
+        )
+        .await
+    }
+
+    fn delete_approval_rule(&self, rule_id: &str) -> Result<(), String> {
+        codex_core::delete_approval_rule_core(&self.services.approval_policy, rule_id)
+    }
+
     async fn get_config_model(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::get_config_model_core(&self.workspaces, workspace_id).await
     }
@@ -984,6 +2056,16 @@ impl DaemonState {
         workspaces_core::apply_worktree_changes_core(&self.workspaces, workspace_id).await
     }
 
+    async fn compare_worktrees(&self, a: String, b: String) -> Result<WorktreeComparison, String> {
+        worktree_compare_core::compare_worktrees_core(
+            &self.workspaces,
+            &self.change_attributions,
+            a,
+            b,
+        )
+        .await
+    }
+
     async fn open_workspace_in(
         &self,
         path: String,
@@ -1015,6 +2097,16 @@ impl DaemonState {
         git_ui_core::get_git_status_core(&self.workspaces, workspace_id).await
     }
 
+    async fn git_status_all(&self, refresh: bool) -> Vec<WorkspaceGitStatus> {
+        git_status_core::git_status_all_core(
+            &self.services,
+            &self.workspaces,
+            refresh,
+            self.event_sink.clone(),
+        )
+        .await
+    }
+
     async fn init_git_repo(
         &self,
         workspace_id: String,
@@ -1079,6 +2171,10 @@ impl DaemonState {
         git_ui_core::get_git_remote_core(&self.workspaces, workspace_id).await
     }
 
+    async fn git_auth_doctor(&self, workspace_id: String) -> Result<GitAuthDoctorReport, String> {
+        git_ui_core::git_auth_doctor_core(&self.workspaces, workspace_id).await
+    }
+
     async fn stage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
         git_ui_core::stage_git_file_core(&self.workspaces, workspace_id, path).await
     }
@@ -1176,6 +2272,51 @@ impl DaemonState {
         git_ui_core::create_git_branch_core(&self.workspaces, workspace_id, name).await
     }
 
+    async fn delete_git_branch(
+        &self,
+        workspace_id: String,
+        name: String,
+        force: bool,
+    ) -> Result<(), String> {
+        git_ui_core::delete_git_branch_core(&self.workspaces, workspace_id, name, force).await
+    }
+
+    async fn save_git_stash(
+        &self,
+        workspace_id: String,
+        message: Option<String>,
+    ) -> Result<(), String> {
+        git_ui_core::save_git_stash_core(&self.workspaces, workspace_id, message).await
+    }
+
+    async fn list_git_stashes(&self, workspace_id: String) -> Result<Value, String> {
+        git_ui_core::list_git_stashes_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn pop_git_stash(
+        &self,
+        workspace_id: String,
+        index: Option<usize>,
+    ) -> Result<(), String> {
+        git_ui_core::pop_git_stash_core(&self.workspaces, workspace_id, index).await
+    }
+
+    async fn get_git_conflicts(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitConflictsResponse, String> {
+        git_ui_core::get_git_conflicts_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn resolve_git_conflict(
+        &self,
+        workspace_id: String,
+        path: String,
+        content: String,
+    ) -> Result<(), String> {
+        git_ui_core::resolve_git_conflict_core(&self.workspaces, workspace_id, path, content).await
+    }
+
     async fn prompts_list(&self, workspace_id: String) -> Result<Vec<CustomPromptEntry>, String> {
         prompts_core::prompts_list_core(&self.workspaces, &self.settings_path, workspace_id).await
     }
@@ -1258,6 +2399,74 @@ impl DaemonState {
         .await
     }
 
+    fn prompt_list(&self) -> Vec<SavedPrompt> {
+        prompt_library_core::prompt_list_core(&self.prompt_library)
+    }
+
+    fn prompt_save(
+        &self,
+        id: Option<String>,
+        name: String,
+        description: Option<String>,
+        template: String,
+    ) -> Result<SavedPrompt, String> {
+        prompt_library_core::prompt_save_core(&self.prompt_library, id, name, description, template)
+    }
+
+    fn prompt_delete(&self, id: &str) -> Result<(), String> {
+        prompt_library_core::prompt_delete_core(&self.prompt_library, id)
+    }
+
+    async fn prompt_render(
+        &self,
+        id: String,
+        workspace_id: String,
+        values: HashMap<String, String>,
+    ) -> Result<String, String> {
+        prompt_library_core::prompt_render_core(
+            &self.prompt_library,
+            &self.workspaces,
+            id,
+            workspace_id,
+            values,
+        )
+        .await
+    }
+
+    fn playbook_list(&self) -> Vec<Playbook> {
+        playbooks_core::playbook_list_core(&self.playbooks)
+    }
+
+    fn playbook_save(&self, draft: PlaybookDraft) -> Result<Playbook, String> {
+        playbooks_core::playbook_save_core(&self.playbooks, draft)
+    }
+
+    fn playbook_delete(&self, id: &str) -> Result<(), String> {
+        playbooks_core::playbook_delete_core(&self.playbooks, id)
+    }
+
+    async fn run_playbook(
+        &self,
+        workspace_id: String,
+        playbook_id: String,
+        variables: HashMap<String, String>,
+        client_version: String,
+    ) -> Result<PlaybookRun, String> {
+        playbooks_core::run_playbook_core(
+            &self.playbooks,
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            playbook_id,
+            variables,
+            |workspace_id| self.connect_workspace(workspace_id, client_version.clone()),
+            |parent_id, branch| {
+                self.add_worktree(parent_id, branch, None, true, client_version.clone())
+            },
+        )
+        .await
+    }
+
     async fn codex_doctor(
         &self,
         codex_bin: Option<String>,
@@ -1276,7 +2485,11 @@ impl DaemonState {
             workspace_id.clone(),
         )
         .await?;
-        let diff = git_ui_core::collect_workspace_diff_core(&repo_root)?;
+        let diff = tokio::task::spawn_blocking(move || {
+            git_ui_core::collect_workspace_diff_core(&repo_root)
+        })
+        .await
+        .map_err(|err| err.to_string())??;
         let commit_message_prompt = {
             let settings = self.app_settings.lock().await;
             settings.commit_message_prompt.clone()
@@ -1337,6 +2550,14 @@ impl DaemonState {
         local_usage_core::local_usage_snapshot_core(&self.workspaces, days, workspace_path).await
     }
 
+    fn usage_summary(
+        &self,
+        since: Option<u64>,
+        group_by: Option<String>,
+    ) -> Result<UsageSummary, String> {
+        usage_metrics::usage_summary_core(&self.services.usage_metrics, since, group_by)
+    }
+
     async fn menu_set_accelerators(&self, _updates: Vec<Value>) -> Result<(), String> {
         // Daemon has no native menu runtime; treat as no-op for remote parity.
         Ok(())
@@ -1445,43 +2666,6 @@ fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
     results
 }
 
-const MAX_WORKSPACE_FILE_BYTES: u64 = 400_000;
-
-fn read_workspace_file_inner(
-    root: &PathBuf,
-    relative_path: &str,
-) -> Result<WorkspaceFileResponse, String> {
-    let canonical_root = root
-        .canonicalize()
-        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-    let candidate = canonical_root.join(relative_path);
-    let canonical_path = candidate
-        .canonicalize()
-        .map_err(|err| format!("Failed to open file: {err}"))?;
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Invalid file path".to_string());
-    }
-    let metadata = std::fs::metadata(&canonical_path)
-        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
-    if !metadata.is_file() {
-        return Err("Path is not a file".to_string());
-    }
-
-    let file = File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
-    let mut buffer = Vec::new();
-    file.take(MAX_WORKSPACE_FILE_BYTES + 1)
-        .read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read file: {err}"))?;
-
-    let truncated = buffer.len() > MAX_WORKSPACE_FILE_BYTES as usize;
-    if truncated {
-        buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
-    }
-
-    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
-    Ok(WorkspaceFileResponse { content, truncated })
-}
-
 fn default_data_dir() -> PathBuf {
     if let Ok(xdg) = env::var("XDG_DATA_HOME") {
         let trimmed = xdg.trim();
@@ -1499,8 +2683,8 @@ fn default_data_dir() -> PathBuf {
 fn usage() -> String {
     format!(
         "\
-USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n\n\
-OPTIONS:\n  --listen <addr>          Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>        Data dir holding workspaces.json/settings.json\n  --token <token>          Shared token required by TCP clients\n  --insecure-no-auth       Disable TCP auth (dev only)\n  -h, --help               Show this help\n"
+USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --token-file <path> | --insecure-no-auth] [--dashboard-listen <addr>] [--workers <n>] [--webhook-url <url>] [--no-mdns]\n\n\
+OPTIONS:\n  --listen <addr>          Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>        Data dir holding workspaces.json/settings.json\n  --token <token>          Shared token required by TCP clients\n  --token-file <path>      Read the token from a file instead\n  --insecure-no-auth       Disable TCP auth (dev only)\n  --dashboard-listen <addr> Serve the read-only web dashboard on this address\n  --workers <n>            Runtime worker threads (default: one per CPU core)\n  --webhook-url <url>      POST turn completions, errors and approval requests here as JSON\n  --no-mdns                Do not advertise the daemon on the local network\n  -h, --help               Show this help\n"
     )
 }
 
@@ -1514,6 +2698,10 @@ fn parse_args() -> Result<DaemonConfig, String> {
         .filter(|value| !value.is_empty());
     let mut insecure_no_auth = false;
     let mut data_dir: Option<PathBuf> = None;
+    let mut dashboard_listen: Option<SocketAddr> = None;
+    let mut workers: Option<usize> = None;
+    let mut webhook_url: Option<String> = None;
+    let mut advertise = true;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -1534,6 +2722,16 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 }
                 token = Some(trimmed.to_string());
             }
+            "--token-file" => {
+                let value = args.next().ok_or("--token-file requires a value")?;
+                let contents = std::fs::read_to_string(value.trim())
+                    .map_err(|err| format!("Failed to read --token-file {value}: {err}"))?;
+                let trimmed = contents.trim();
+                if trimmed.is_empty() {
+                    return Err(format!("--token-file {value} is empty"));
+                }
+                token = Some(trimmed.to_string());
+            }
             "--data-dir" => {
                 let value = args.next().ok_or("--data-dir requires a value")?;
                 let trimmed = value.trim();
@@ -1542,6 +2740,24 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 }
                 data_dir = Some(PathBuf::from(trimmed));
             }
+            "--dashboard-listen" => {
+                let value = args.next().ok_or("--dashboard-listen requires a value")?;
+                dashboard_listen =
+                    Some(value.parse::<SocketAddr>().map_err(|err| err.to_string())?);
+            }
+            "--workers" => {
+                let value = args.next().ok_or("--workers requires a value")?;
+                workers = Some(parse_worker_count(&value)?);
+            }
+            "--webhook-url" => {
+                let value = args.next().ok_or("--webhook-url requires a value")?;
+                let trimmed = value.trim();
+                if !(trimmed.starts_with("http://") || trimmed.starts_with("https://")) {
+                    return Err("--webhook-url must be an http(s) URL".to_string());
+                }
+                webhook_url = Some(trimmed.to_string());
+            }
+            "--no-mdns" => advertise = false,
             "--insecure-no-auth" => {
                 insecure_no_auth = true;
                 token = None;
@@ -1552,7 +2768,7 @@ fn parse_args() -> Result<DaemonConfig, String> {
 
     if token.is_none() && !insecure_no_auth {
         return Err(
-            "Missing --token (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
+            "Missing --token or --token-file (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
                 .to_string(),
         );
     }
@@ -1561,14 +2777,28 @@ fn parse_args() -> Result<DaemonConfig, String> {
         listen,
         token,
         data_dir: data_dir.unwrap_or_else(default_data_dir),
+        dashboard_listen,
+        workers,
+        webhook_url,
+        advertise,
     })
 }
 
+fn parse_worker_count(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(count) if (1..=MAX_WORKER_THREADS).contains(&count) => Ok(count),
+        _ => Err(format!(
+            "--workers must be a number between 1 and {MAX_WORKER_THREADS}"
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::agent::default_agent_backend;
     use crate::shared::process_core::kill_child_process_tree;
-    use crate::storage::write_workspaces;
+    use crate::storage::{write_workspaces, StoreWriteOptions};
     use crate::types::WorkspaceKind;
     use serde_json::json;
     use std::future::Future;
@@ -1579,7 +2809,7 @@ mod tests {
     use std::time::{Duration, SystemTime, UNIX_EPOCH};
     use tokio::process::Command;
 
-    fn run_async_test<F>(future: F)
+    pub(super) fn run_async_test<F>(future: F)
     where
         F: Future<Output = ()>,
     {
@@ -1590,7 +2820,7 @@ mod tests {
             .block_on(future);
     }
 
-    fn make_temp_dir(prefix: &str) -> PathBuf {
+    pub(super) fn make_temp_dir(prefix: &str) -> PathBuf {
         let unique = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("time")
@@ -1603,18 +2833,41 @@ mod tests {
         dir
     }
 
-    fn test_state(data_dir: &std::path::Path) -> DaemonState {
+    pub(super) fn test_state(data_dir: &std::path::Path) -> DaemonState {
         let (tx, _rx) = broadcast::channel::<DaemonEvent>(32);
+        let services = BackendServices::load(data_dir);
         DaemonState {
             data_dir: data_dir.to_path_buf(),
-            workspaces: Mutex::new(HashMap::new()),
-            sessions: Mutex::new(HashMap::new()),
+            workspaces: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
             storage_path: data_dir.join("workspaces.json"),
             settings_path: data_dir.join("settings.json"),
             app_settings: Mutex::new(AppSettings::default()),
-            event_sink: DaemonEventSink { tx },
+            event_sink: DaemonEventSink {
+                tx,
+                pending_approvals: Arc::default(),
+                services: Arc::clone(&services),
+            },
             codex_login_cancels: Mutex::new(HashMap::new()),
             daemon_binary_path: Some("/tmp/codex-monitor-daemon".to_string()),
+            plugins: plugins::PluginRegistry::default(),
+            change_attributions: ChangeAttributions::default(),
+            thread_tags: ThreadTagStore::load(data_dir.join("thread-tags.json")),
+            broadcast_runs: BroadcastRunStore::load(data_dir.join("broadcast-runs.json")),
+            thread_meta: ThreadMetaStore::load(data_dir.join("thread-meta.json")),
+            thread_order: ThreadOrderStore::load(data_dir.join("thread-order.json")),
+            webhook_url: None,
+            notification_center: NotificationCenter::load(data_dir.join("notifications.json")),
+            push_devices: push_relay::PushDevices::load(data_dir.join("push-devices.json")),
+            prompt_library: PromptLibrary::load(data_dir.join("prompt-library.json")),
+            playbooks: PlaybookStore::load(data_dir.join("playbooks.json")),
+            scheduled_messages: ScheduledMessageStore::load(
+                data_dir.join("scheduled-messages.json"),
+            ),
+            search_index: SearchIndex::load(data_dir.join("search-index.sqlite")),
+            workspace_summaries: WorkspaceSummaryCache::default(),
+            workspace_recency: WorkspaceRecencyStore::load(data_dir.join("workspace-recency.json")),
+            services,
         }
     }
 
@@ -1632,7 +2885,7 @@ mod tests {
         };
         state
             .workspaces
-            .lock()
+            .write()
             .await
             .insert(workspace_id.to_string(), entry);
     }
@@ -1672,8 +2925,8 @@ mod tests {
             codex_args: None,
             child: Mutex::new(child),
             stdin: Mutex::new(stdin),
-            pending: Mutex::new(HashMap::new()),
-            request_context: Mutex::new(HashMap::new()),
+            pending: Default::default(),
+            request_context: Default::default(),
             thread_workspace: Mutex::new(HashMap::new()),
             hidden_thread_ids: Mutex::new(HashSet::new()),
             next_id: AtomicU64::new(0),
@@ -1681,6 +2934,10 @@ mod tests {
             workspace_ids: Mutex::new(HashSet::from([owner_workspace_id.clone()])),
             workspace_roots: Mutex::new(HashMap::new()),
             owner_workspace_id,
+            backend: default_agent_backend(),
+            request_timeouts: Default::default(),
+            isolated_env: false,
+            services: BackendServices::for_tests(),
         })
     }
 
@@ -1791,9 +3048,36 @@ mod tests {
                 result.get("version").and_then(Value::as_str),
                 Some(env!("CARGO_PKG_VERSION"))
             );
+            assert_eq!(
+                result.get("protocolVersion").and_then(Value::as_u64),
+                Some(u64::from(DAEMON_PROTOCOL_VERSION))
+            );
+
+            let version = rpc::handle_rpc_request(
+                &state,
+                "daemon_version",
+                json!({}),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("daemon_version should succeed");
+            assert_eq!(
+                serde_json::from_value::<DaemonVersion>(version).expect("daemon version"),
+                daemon_version_core::daemon_version_core()
+            );
             let _ = std::fs::remove_dir_all(&tmp);
         });
     }
+
+    #[test]
+    fn parse_worker_count_accepts_bounded_values() {
+        assert_eq!(parse_worker_count("4"), Ok(4));
+        assert_eq!(parse_worker_count(" 16 "), Ok(16));
+        assert!(parse_worker_count("0").is_err());
+        assert!(parse_worker_count("many").is_err());
+        assert!(parse_worker_count(&(MAX_WORKER_THREADS + 1).to_string()).is_err());
+    }
+
     #[test]
     fn list_workspaces_syncs_from_storage_file() {
         run_async_test(async {
@@ -1809,7 +3093,13 @@ mod tests {
                 worktree: None,
                 settings: WorkspaceSettings::default(),
             }];
-            write_workspaces(&state.storage_path, &persisted).expect("write workspaces");
+            write_workspaces(
+                &state.storage_path,
+                &persisted,
+                StoreWriteOptions::default(),
+            )
+            .await
+            .expect("write workspaces");
 
             let listed = state.list_workspaces().await;
             assert!(
@@ -1833,7 +3123,13 @@ mod tests {
                 "ws-keep",
                 &keep_path.to_string_lossy(),
             )];
-            write_workspaces(&state.storage_path, &persisted).expect("write workspaces");
+            write_workspaces(
+                &state.storage_path,
+                &persisted,
+                StoreWriteOptions::default(),
+            )
+            .await
+            .expect("write workspaces");
 
             let keep_session = make_session(make_workspace_entry(
                 "ws-keep",
@@ -1844,7 +3140,7 @@ mod tests {
                 &stale_path.to_string_lossy(),
             ));
             {
-                let mut sessions = state.sessions.lock().await;
+                let mut sessions = state.sessions.write().await;
                 sessions.insert("ws-keep".to_string(), keep_session);
                 sessions.insert("ws-stale".to_string(), stale_session.clone());
             }
@@ -1856,7 +3152,7 @@ mod tests {
             );
 
             {
-                let sessions = state.sessions.lock().await;
+                let sessions = state.sessions.read().await;
                 assert!(
                     sessions.contains_key("ws-keep"),
                     "expected connected persisted workspace session to remain"
@@ -1888,7 +3184,7 @@ mod tests {
                 "expected stale session child process to terminate"
             );
 
-            if let Some(keep_session) = state.sessions.lock().await.remove("ws-keep") {
+            if let Some(keep_session) = state.sessions.write().await.remove("ws-keep") {
                 let mut child = keep_session.child.lock().await;
                 kill_child_process_tree(&mut child).await;
             }
@@ -1919,7 +3215,13 @@ fn main() {
         }
     };
 
-    let runtime = tokio::runtime::Builder::new_current_thread()
+    // Each session's stdout reader, git work, and RPC handler run as separate
+    // tasks, so spread them across workers instead of a single thread.
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    if let Some(workers) = config.workers {
+        builder.worker_threads(workers);
+    }
+    let runtime = builder
         .enable_all()
         .build()
         .expect("failed to build tokio runtime");
@@ -1928,9 +3230,83 @@ fn main() {
         let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
         let event_sink = DaemonEventSink {
             tx: events_tx.clone(),
+            pending_approvals: Arc::default(),
+            services: BackendServices::load(&config.data_dir),
         };
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
+        tokio::spawn(merge_queue::run_merge_queue(Arc::clone(&state)));
+        tokio::spawn(automation::run_automation(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(track_change_attributions(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(track_search_index(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(track_workspace_summaries(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(deliver_webhooks(Arc::clone(&state), events_tx.subscribe()));
+        tokio::spawn(approval_chat::run_approval_chat(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(push_relay::run_push_relay(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        tokio::spawn(track_notifications(
+            Arc::clone(&state),
+            events_tx.subscribe(),
+        ));
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                storage_core::run_storage_budget(state.data_dir.clone(), &state.app_settings).await;
+            });
+        }
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                scheduled_messages_core::run_message_scheduler(
+                    &state.scheduled_messages,
+                    &state.sessions,
+                    &state.workspaces,
+                    state.event_sink.clone(),
+                    |workspace_id| {
+                        let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
+                        state.connect_workspace(workspace_id, client_version)
+                    },
+                )
+                .await;
+            });
+        }
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                archive_undo_core::run_archive_scheduler(
+                    &state.services.pending_archives,
+                    state.event_sink.clone(),
+                    |workspace_id, thread_id| state.archive_thread(workspace_id, thread_id),
+                )
+                .await;
+            });
+        }
+        if let Some(dashboard_listen) = config.dashboard_listen {
+            dashboard::start_dashboard(
+                dashboard_listen,
+                Arc::clone(&config),
+                Arc::clone(&state),
+                &events_tx,
+            )
+            .await;
+        }
 
         let listener = match TcpListener::bind(config.listen).await {
             Ok(listener) => listener,
@@ -1948,6 +3324,22 @@ fn main() {
                 .unwrap_or(&state.storage_path)
                 .display()
         );
+        // Dropping the handle would withdraw the advertisement.
+        let _mdns = if config.advertise {
+            match discovery_core::advertise_daemon(
+                config.listen,
+                env!("CARGO_PKG_VERSION"),
+                config.token.is_some(),
+            ) {
+                Ok(handle) => handle,
+                Err(err) => {
+                    eprintln!("daemon: mDNS advertisement failed: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         loop {
             match listener.accept().await {
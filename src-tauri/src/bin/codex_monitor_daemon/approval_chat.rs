@@ -0,0 +1,903 @@
+use super::*;
+use futures_util::{SinkExt, StreamExt};
+use shared::notifications_core::{self, NotificationKind};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use types::{ApprovalChatProvider, ApprovalChatSettings};
+
+const CHAT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const CHAT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CHAT_RECONNECT_DELAY: Duration = Duration::from_secs(15);
+const SLACK_API: &str = "https://slack.com/api";
+const DISCORD_API: &str = "https://discord.com/api/v10";
+const DISCORD_GATEWAY: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+// Slack `action_id` and Discord `custom_id` of the two buttons.
+const APPROVE_ACTION: &str = "codex_monitor_approve";
+const DECLINE_ACTION: &str = "codex_monitor_decline";
+const SLACK_APPROVE: &str = "white_check_mark";
+const SLACK_DECLINE: &str = "x";
+const DISCORD_APPROVE: &str = "✅";
+const DISCORD_DECLINE: &str = "❌";
+// Percent-encoded forms of the emoji above, for Discord reaction routes.
+const DISCORD_APPROVE_PATH: &str = "%E2%9C%85";
+const DISCORD_DECLINE_PATH: &str = "%E2%9D%8C";
+
+/// An approval request mirrored into a chat channel.
+struct PostedApproval {
+    settings: ApprovalChatSettings,
+    message_id: String,
+    workspace_id: String,
+    request_id: Value,
+    summary: String,
+    /// The Slack bot's own user id, whose reactions never count.
+    bot_user_id: Option<String>,
+}
+
+/// A button press read from Slack Socket Mode or the Discord gateway.
+#[derive(Debug, PartialEq, Eq)]
+struct ButtonPress {
+    message_id: String,
+    user_id: String,
+    is_bot: bool,
+    decision: &'static str,
+}
+
+fn configured(settings: Option<&ApprovalChatSettings>) -> Option<ApprovalChatSettings> {
+    settings
+        .filter(|settings| {
+            !settings.bot_token.trim().is_empty() && !settings.channel_id.trim().is_empty()
+        })
+        .cloned()
+}
+
+/// Only people listed in `approverIds` can answer, and never a bot.
+fn may_answer(settings: &ApprovalChatSettings, user_id: &str, is_bot: bool) -> bool {
+    !is_bot
+        && !user_id.is_empty()
+        && settings
+            .approver_ids
+            .iter()
+            .any(|approver| approver.trim() == user_id)
+}
+
+/// Posts approval requests to the `approvalChat` Slack or Discord channel
+/// with Approve and Decline buttons. The first press by one of
+/// `approverIds` answers the request; an approver can also react with ✅ or
+/// ❌, which is read every few seconds. Messages for requests answered
+/// anywhere else are edited to say so and no longer polled.
+pub(super) async fn run_approval_chat(
+    state: Arc<DaemonState>,
+    mut rx: broadcast::Receiver<DaemonEvent>,
+) {
+    let client = match state.services.http_client_builder().and_then(|builder| {
+        builder
+            .timeout(CHAT_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|err| err.to_string())
+    }) {
+        Ok(client) => client,
+        Err(error) => {
+            eprintln!("approval chat: failed to build HTTP client: {error}");
+            return;
+        }
+    };
+    let mut posted: Vec<PostedApproval> = Vec::new();
+    let (press_tx, mut presses) = mpsc::unbounded_channel::<ButtonPress>();
+    let mut listener: Option<(ApprovalChatSettings, JoinHandle<()>)> = None;
+    let mut ticker = tokio::time::interval(CHAT_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            received = rx.recv() => {
+                let event = match received {
+                    Ok(DaemonEvent::AppServer(stamped)) => stamped.event,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let Some(approval) = post_approval(&state, &client, &event).await {
+                    posted.push(approval);
+                }
+            }
+            Some(press) = presses.recv() => {
+                answer_press(&state, &client, &mut posted, press).await;
+            }
+            _ = ticker.tick() => {
+                let settings = configured(state.app_settings.lock().await.approval_chat.as_ref());
+                refresh_listener(&client, settings, &press_tx, &mut listener);
+                if !posted.is_empty() {
+                    poll_posted(&state, &client, &mut posted).await;
+                }
+            }
+        }
+    }
+    if let Some((_, task)) = listener {
+        task.abort();
+    }
+}
+
+/// Keeps one button listener running for the current settings, restarting
+/// it when they change.
+fn refresh_listener(
+    client: &reqwest::Client,
+    settings: Option<ApprovalChatSettings>,
+    presses: &mpsc::UnboundedSender<ButtonPress>,
+    listener: &mut Option<(ApprovalChatSettings, JoinHandle<()>)>,
+) {
+    if let Some((running, _)) = listener.as_ref() {
+        if settings.as_ref() == Some(running) {
+            return;
+        }
+    }
+    if let Some((_, task)) = listener.take() {
+        task.abort();
+    }
+    let Some(settings) = settings else {
+        return;
+    };
+    let task = tokio::spawn(listen_for_buttons(
+        client.clone(),
+        settings.clone(),
+        presses.clone(),
+    ));
+    *listener = Some((settings, task));
+}
+
+async fn post_approval(
+    state: &DaemonState,
+    client: &reqwest::Client,
+    event: &AppServerEvent,
+) -> Option<PostedApproval> {
+    let request_id = event.message.get("id")?.clone();
+    let settings = configured(state.app_settings.lock().await.approval_chat.as_ref())?;
+    let notification = notifications_core::describe_event(&state.workspaces, event).await?;
+    if notification.kind != NotificationKind::ApprovalRequested {
+        return None;
+    }
+    let summary = format!("{}\n{}", notification.title, notification.body);
+    let bot_user_id = match settings.provider {
+        ApprovalChatProvider::Slack => slack_bot_user_id(client, &settings).await,
+        ApprovalChatProvider::Discord => None,
+    };
+    match post_message(client, &settings, &summary).await {
+        Ok(message_id) => Some(PostedApproval {
+            settings,
+            message_id,
+            workspace_id: event.workspace_id.clone(),
+            request_id,
+            summary,
+            bot_user_id,
+        }),
+        Err(error) => {
+            eprintln!("approval chat: {error}");
+            None
+        }
+    }
+}
+
+async fn answer_press(
+    state: &DaemonState,
+    client: &reqwest::Client,
+    posted: &mut Vec<PostedApproval>,
+    press: ButtonPress,
+) {
+    let Some(index) = posted
+        .iter()
+        .position(|approval| approval.message_id == press.message_id)
+    else {
+        return;
+    };
+    if !may_answer(&posted[index].settings, &press.user_id, press.is_bot) {
+        eprintln!(
+            "approval chat: ignoring a press by {}, who is not in approverIds",
+            press.user_id
+        );
+        return;
+    }
+    let approval = posted.remove(index);
+    answer(state, client, &approval, press.decision).await;
+}
+
+async fn poll_posted(
+    state: &DaemonState,
+    client: &reqwest::Client,
+    posted: &mut Vec<PostedApproval>,
+) {
+    let pending = state.event_sink.pending_approvals.list(None);
+    let mut remaining = Vec::with_capacity(posted.len());
+    for approval in posted.drain(..) {
+        let still_pending = pending.iter().any(|entry| {
+            entry.workspace_id == approval.workspace_id && entry.request_id == approval.request_id
+        });
+        if !still_pending {
+            finish(client, &approval, "Answered in CodexMonitor.").await;
+            continue;
+        }
+        match read_decision(client, &approval).await {
+            Ok(Some(decision)) => answer(state, client, &approval, decision).await,
+            Ok(None) => remaining.push(approval),
+            Err(error) => {
+                eprintln!("approval chat: {error}");
+                remaining.push(approval);
+            }
+        }
+    }
+    *posted = remaining;
+}
+
+async fn answer(
+    state: &DaemonState,
+    client: &reqwest::Client,
+    approval: &PostedApproval,
+    decision: &'static str,
+) {
+    let result = state
+        .respond_to_server_request(
+            approval.workspace_id.clone(),
+            approval.request_id.clone(),
+            json!({ "decision": decision }),
+        )
+        .await;
+    let outcome = match (result, decision) {
+        (Ok(_), "accept") => "Approved from chat.".to_string(),
+        (Ok(_), _) => "Declined from chat.".to_string(),
+        (Err(error), _) => format!("Could not answer the request: {error}"),
+    };
+    finish(client, approval, &outcome).await;
+}
+
+async fn finish(client: &reqwest::Client, approval: &PostedApproval, outcome: &str) {
+    let text = format!("{}\n{outcome}", approval.summary);
+    if let Err(error) = update_message(client, approval, &text).await {
+        eprintln!("approval chat: {error}");
+    }
+}
+
+fn prompt_text(settings: &ApprovalChatSettings, summary: &str) -> String {
+    if settings.approver_ids.is_empty() {
+        return format!(
+            "{summary}\nNo approvers are set in approverIds, so answer this in CodexMonitor."
+        );
+    }
+    match settings.provider {
+        ApprovalChatProvider::Slack => format!(
+            "{summary}\nPress Approve or Decline, or react with :{SLACK_APPROVE}: or :{SLACK_DECLINE}:."
+        ),
+        ApprovalChatProvider::Discord => format!(
+            "{summary}\nPress Approve or Decline, or react with {DISCORD_APPROVE} or {DISCORD_DECLINE}."
+        ),
+    }
+}
+
+fn button_decision(action: &str) -> Option<&'static str> {
+    match action {
+        APPROVE_ACTION => Some("accept"),
+        DECLINE_ACTION => Some("decline"),
+        _ => None,
+    }
+}
+
+async fn send_json(request: reqwest::RequestBuilder, body: Option<Value>) -> Result<Value, String> {
+    let request = match body {
+        Some(body) => request
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/json; charset=utf-8",
+            )
+            .body(serde_json::to_vec(&body).map_err(|err| err.to_string())?),
+        None => request,
+    };
+    let response = request.send().await.map_err(|err| err.to_string())?;
+    let status = response.status();
+    let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+    if !status.is_success() {
+        return Err(format!("chat API returned {status}"));
+    }
+    if bytes.is_empty() {
+        return Ok(Value::Null);
+    }
+    let value: Value = serde_json::from_slice(&bytes).map_err(|err| err.to_string())?;
+    // Slack reports failures with HTTP 200 and `ok: false`.
+    if value.get("ok") == Some(&Value::Bool(false)) {
+        let error = value
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(format!("Slack API error: {error}"));
+    }
+    Ok(value)
+}
+
+fn authorized(
+    request: reqwest::RequestBuilder,
+    settings: &ApprovalChatSettings,
+) -> reqwest::RequestBuilder {
+    let token = settings.bot_token.trim();
+    let value = match settings.provider {
+        ApprovalChatProvider::Slack => format!("Bearer {token}"),
+        ApprovalChatProvider::Discord => format!("Bot {token}"),
+    };
+    request.header(reqwest::header::AUTHORIZATION, value)
+}
+
+async fn slack_bot_user_id(
+    client: &reqwest::Client,
+    settings: &ApprovalChatSettings,
+) -> Option<String> {
+    let request = authorized(client.post(format!("{SLACK_API}/auth.test")), settings);
+    match send_json(request, None).await {
+        Ok(response) => response
+            .get("user_id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        Err(error) => {
+            eprintln!("approval chat: {error}");
+            None
+        }
+    }
+}
+
+/// Posts the prompt with its buttons, returning the message id.
+async fn post_message(
+    client: &reqwest::Client,
+    settings: &ApprovalChatSettings,
+    summary: &str,
+) -> Result<String, String> {
+    let channel = settings.channel_id.trim();
+    let text = prompt_text(settings, summary);
+    let with_buttons = !settings.approver_ids.is_empty();
+    match settings.provider {
+        ApprovalChatProvider::Slack => {
+            let mut blocks = vec![json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": text },
+            })];
+            if with_buttons {
+                blocks.push(json!({
+                    "type": "actions",
+                    "elements": [
+                        {
+                            "type": "button",
+                            "text": { "type": "plain_text", "text": "Approve" },
+                            "style": "primary",
+                            "action_id": APPROVE_ACTION,
+                            "value": "accept",
+                        },
+                        {
+                            "type": "button",
+                            "text": { "type": "plain_text", "text": "Decline" },
+                            "style": "danger",
+                            "action_id": DECLINE_ACTION,
+                            "value": "decline",
+                        },
+                    ],
+                }));
+            }
+            let request = authorized(
+                client.post(format!("{SLACK_API}/chat.postMessage")),
+                settings,
+            );
+            let response = send_json(
+                request,
+                Some(json!({ "channel": channel, "text": text, "blocks": blocks })),
+            )
+            .await?;
+            response
+                .get("ts")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| "Slack did not return a message timestamp".to_string())
+        }
+        ApprovalChatProvider::Discord => {
+            let components = if with_buttons {
+                json!([{
+                    "type": 1,
+                    "components": [
+                        { "type": 2, "style": 3, "label": "Approve", "custom_id": APPROVE_ACTION },
+                        { "type": 2, "style": 4, "label": "Decline", "custom_id": DECLINE_ACTION },
+                    ],
+                }])
+            } else {
+                json!([])
+            };
+            let url = format!("{DISCORD_API}/channels/{channel}/messages");
+            let request = authorized(client.post(url), settings);
+            let response = send_json(
+                request,
+                Some(json!({ "content": text, "components": components })),
+            )
+            .await?;
+            response
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| "Discord did not return a message id".to_string())
+        }
+    }
+}
+
+async fn read_decision(
+    client: &reqwest::Client,
+    approval: &PostedApproval,
+) -> Result<Option<&'static str>, String> {
+    let settings = &approval.settings;
+    let channel = settings.channel_id.trim();
+    match settings.provider {
+        ApprovalChatProvider::Slack => {
+            let url = format!(
+                "{SLACK_API}/reactions.get?channel={channel}&timestamp={}&full=true",
+                approval.message_id
+            );
+            let response = send_json(authorized(client.get(url), settings), None).await?;
+            Ok(slack_decision(
+                &response,
+                settings,
+                approval.bot_user_id.as_deref(),
+            ))
+        }
+        ApprovalChatProvider::Discord => {
+            let mut reactors = Vec::with_capacity(2);
+            for emoji in [DISCORD_APPROVE_PATH, DISCORD_DECLINE_PATH] {
+                let url = format!(
+                    "{DISCORD_API}/channels/{channel}/messages/{}/reactions/{emoji}?limit=100",
+                    approval.message_id
+                );
+                reactors.push(send_json(authorized(client.get(url), settings), None).await?);
+            }
+            Ok(discord_decision(&reactors[0], &reactors[1], settings))
+        }
+    }
+}
+
+async fn update_message(
+    client: &reqwest::Client,
+    approval: &PostedApproval,
+    text: &str,
+) -> Result<(), String> {
+    let settings = &approval.settings;
+    let channel = settings.channel_id.trim();
+    match settings.provider {
+        ApprovalChatProvider::Slack => {
+            // Replacing the blocks drops the buttons.
+            let blocks = json!([{ "type": "section", "text": { "type": "mrkdwn", "text": text } }]);
+            let request = authorized(client.post(format!("{SLACK_API}/chat.update")), settings);
+            send_json(
+                request,
+                Some(json!({
+                    "channel": channel,
+                    "ts": approval.message_id,
+                    "text": text,
+                    "blocks": blocks,
+                })),
+            )
+            .await?;
+        }
+        ApprovalChatProvider::Discord => {
+            let url = format!(
+                "{DISCORD_API}/channels/{channel}/messages/{}",
+                approval.message_id
+            );
+            send_json(
+                authorized(client.patch(url), settings),
+                Some(json!({ "content": text, "components": [] })),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Receives button presses until aborted, reconnecting after a dropped
+/// connection. Slack delivers presses over Socket Mode, which needs the
+/// app-level `appToken`; without one only reactions answer Slack requests.
+async fn listen_for_buttons(
+    client: reqwest::Client,
+    settings: ApprovalChatSettings,
+    presses: mpsc::UnboundedSender<ButtonPress>,
+) {
+    loop {
+        let result = match settings.provider {
+            ApprovalChatProvider::Slack => {
+                let Some(app_token) = settings
+                    .app_token
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                else {
+                    return;
+                };
+                listen_slack(&client, app_token, &presses).await
+            }
+            ApprovalChatProvider::Discord => listen_discord(&client, &settings, &presses).await,
+        };
+        if let Err(error) = result {
+            eprintln!("approval chat: {error}");
+        }
+        if presses.is_closed() {
+            return;
+        }
+        tokio::time::sleep(CHAT_RECONNECT_DELAY).await;
+    }
+}
+
+async fn listen_slack(
+    client: &reqwest::Client,
+    app_token: &str,
+    presses: &mpsc::UnboundedSender<ButtonPress>,
+) -> Result<(), String> {
+    let request = client
+        .post(format!("{SLACK_API}/apps.connections.open"))
+        .header(
+            reqwest::header::AUTHORIZATION,
+            format!("Bearer {app_token}"),
+        );
+    let response = send_json(request, None).await?;
+    let url = response
+        .get("url")
+        .and_then(Value::as_str)
+        .ok_or("Slack did not return a Socket Mode URL")?;
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|err| format!("Slack Socket Mode: {err}"))?;
+    while let Some(message) = socket.next().await {
+        let text = match message.map_err(|err| format!("Slack Socket Mode: {err}"))? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let Ok(envelope) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+        // Slack redelivers envelopes that are not acknowledged.
+        if let Some(envelope_id) = envelope.get("envelope_id").and_then(Value::as_str) {
+            socket
+                .send(Message::Text(
+                    json!({ "envelope_id": envelope_id }).to_string(),
+                ))
+                .await
+                .map_err(|err| format!("Slack Socket Mode: {err}"))?;
+        }
+        if envelope.get("type").and_then(Value::as_str) == Some("disconnect") {
+            break;
+        }
+        if let Some(press) = slack_button_press(&envelope) {
+            if presses.send(press).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Identifies with no intents, which is enough to receive interactions, and
+/// acknowledges each press so Discord does not report it as failed.
+async fn listen_discord(
+    client: &reqwest::Client,
+    settings: &ApprovalChatSettings,
+    presses: &mpsc::UnboundedSender<ButtonPress>,
+) -> Result<(), String> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(DISCORD_GATEWAY)
+        .await
+        .map_err(|err| format!("Discord gateway: {err}"))?;
+    // Replaced with the real interval once the gateway says hello.
+    let idle = Duration::from_secs(3600);
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + idle, idle);
+    let mut sequence = Value::Null;
+    loop {
+        let frame = tokio::select! {
+            message = socket.next() => {
+                let Some(message) = message else {
+                    return Ok(());
+                };
+                match message.map_err(|err| format!("Discord gateway: {err}"))? {
+                    Message::Text(text) => text,
+                    Message::Close(_) => return Ok(()),
+                    _ => continue,
+                }
+            }
+            _ = heartbeat.tick() => {
+                socket
+                    .send(Message::Text(json!({ "op": 1, "d": sequence }).to_string()))
+                    .await
+                    .map_err(|err| format!("Discord gateway: {err}"))?;
+                continue;
+            }
+        };
+        let Ok(frame) = serde_json::from_str::<Value>(&frame) else {
+            continue;
+        };
+        if let Some(seq) = frame.get("s").filter(|seq| !seq.is_null()) {
+            sequence = seq.clone();
+        }
+        match frame.get("op").and_then(Value::as_u64) {
+            // Hello
+            Some(10) => {
+                let interval = frame
+                    .pointer("/d/heartbeat_interval")
+                    .and_then(Value::as_u64)
+                    .map(Duration::from_millis)
+                    .unwrap_or(Duration::from_secs(41));
+                heartbeat =
+                    tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+                let identify = json!({
+                    "op": 2,
+                    "d": {
+                        "token": settings.bot_token.trim(),
+                        "intents": 0,
+                        "properties": {
+                            "os": std::env::consts::OS,
+                            "browser": "codex-monitor",
+                            "device": "codex-monitor",
+                        },
+                    },
+                });
+                socket
+                    .send(Message::Text(identify.to_string()))
+                    .await
+                    .map_err(|err| format!("Discord gateway: {err}"))?;
+            }
+            // Heartbeat request
+            Some(1) => heartbeat.reset_immediately(),
+            // Reconnect or invalid session
+            Some(7) | Some(9) => return Ok(()),
+            Some(0) if frame.get("t").and_then(Value::as_str) == Some("INTERACTION_CREATE") => {
+                let Some(interaction) = frame.get("d") else {
+                    continue;
+                };
+                let Some(press) = discord_button_press(interaction) else {
+                    continue;
+                };
+                acknowledge_interaction(client, interaction).await;
+                if presses.send(press).is_err() {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Defers the update; the message is edited once the request is answered.
+async fn acknowledge_interaction(client: &reqwest::Client, interaction: &Value) {
+    let (Some(id), Some(token)) = (
+        interaction.get("id").and_then(Value::as_str),
+        interaction.get("token").and_then(Value::as_str),
+    ) else {
+        return;
+    };
+    let url = format!("{DISCORD_API}/interactions/{id}/{token}/callback");
+    if let Err(error) = send_json(client.post(url), Some(json!({ "type": 6 }))).await {
+        eprintln!("approval chat: {error}");
+    }
+}
+
+fn slack_button_press(envelope: &Value) -> Option<ButtonPress> {
+    if envelope.get("type").and_then(Value::as_str) != Some("interactive") {
+        return None;
+    }
+    let payload = envelope.get("payload")?;
+    if payload.get("type").and_then(Value::as_str) != Some("block_actions") {
+        return None;
+    }
+    let action = payload.pointer("/actions/0/action_id")?.as_str()?;
+    let message_id = payload
+        .pointer("/container/message_ts")
+        .or_else(|| payload.pointer("/message/ts"))?
+        .as_str()?;
+    Some(ButtonPress {
+        message_id: message_id.to_string(),
+        user_id: payload.pointer("/user/id")?.as_str()?.to_string(),
+        is_bot: false,
+        decision: button_decision(action)?,
+    })
+}
+
+fn discord_button_press(interaction: &Value) -> Option<ButtonPress> {
+    // 3 is a message component interaction.
+    if interaction.get("type").and_then(Value::as_u64) != Some(3) {
+        return None;
+    }
+    let action = interaction.pointer("/data/custom_id")?.as_str()?;
+    // Guild interactions carry the user under `member`, DMs at the top.
+    let user = interaction
+        .pointer("/member/user")
+        .or_else(|| interaction.get("user"))?;
+    Some(ButtonPress {
+        message_id: interaction.pointer("/message/id")?.as_str()?.to_string(),
+        user_id: user.get("id")?.as_str()?.to_string(),
+        is_bot: user.get("bot").and_then(Value::as_bool).unwrap_or(false),
+        decision: button_decision(action)?,
+    })
+}
+
+/// Decline wins when both reactions were added, so a mistaken approve can be
+/// overridden before the next poll.
+fn pick_decision(approved: bool, declined: bool) -> Option<&'static str> {
+    if declined {
+        Some("decline")
+    } else if approved {
+        Some("accept")
+    } else {
+        None
+    }
+}
+
+/// A reaction counts once an approver other than the bot has added it.
+fn slack_decision(
+    response: &Value,
+    settings: &ApprovalChatSettings,
+    bot_user_id: Option<&str>,
+) -> Option<&'static str> {
+    let reactions = response
+        .get("message")
+        .and_then(|message| message.get("reactions"))
+        .and_then(Value::as_array)?;
+    let reacted = |name: &str| {
+        reactions.iter().any(|reaction| {
+            reaction.get("name").and_then(Value::as_str) == Some(name)
+                && reaction
+                    .get("users")
+                    .and_then(Value::as_array)
+                    .is_some_and(|users| {
+                        users
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .any(|user| may_answer(settings, user, Some(user) == bot_user_id))
+                    })
+        })
+    };
+    pick_decision(reacted(SLACK_APPROVE), reacted(SLACK_DECLINE))
+}
+
+/// Takes the users Discord lists for the approve and decline reactions.
+fn discord_decision(
+    approvers: &Value,
+    decliners: &Value,
+    settings: &ApprovalChatSettings,
+) -> Option<&'static str> {
+    let reacted = |users: &Value| {
+        users.as_array().is_some_and(|users| {
+            users.iter().any(|user| {
+                let id = user.get("id").and_then(Value::as_str).unwrap_or_default();
+                let is_bot = user.get("bot").and_then(Value::as_bool).unwrap_or(false);
+                may_answer(settings, id, is_bot)
+            })
+        })
+    };
+    pick_decision(reacted(approvers), reacted(decliners))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(provider: ApprovalChatProvider) -> ApprovalChatSettings {
+        ApprovalChatSettings {
+            provider,
+            bot_token: "token-1".to_string(),
+            channel_id: "C1".to_string(),
+            approver_ids: vec!["U1".to_string()],
+            app_token: None,
+        }
+    }
+
+    #[test]
+    fn slack_reactions_count_only_approvers() {
+        let settings = settings(ApprovalChatProvider::Slack);
+        let others = json!({
+            "ok": true,
+            "message": { "reactions": [
+                { "name": "white_check_mark", "count": 2, "users": ["UBOT", "U2"] },
+                { "name": "x", "count": 1, "users": ["U3"] }
+            ] }
+        });
+        assert_eq!(slack_decision(&others, &settings, Some("UBOT")), None);
+        let approved = json!({
+            "message": { "reactions": [
+                { "name": "white_check_mark", "count": 1, "users": ["U1"] }
+            ] }
+        });
+        assert_eq!(
+            slack_decision(&approved, &settings, Some("UBOT")),
+            Some("accept")
+        );
+        let both = json!({
+            "message": { "reactions": [
+                { "name": "white_check_mark", "count": 1, "users": ["U1"] },
+                { "name": "x", "count": 1, "users": ["U1"] }
+            ] }
+        });
+        assert_eq!(slack_decision(&both, &settings, None), Some("decline"));
+
+        let mut bot_listed = settings.clone();
+        bot_listed.approver_ids.push("UBOT".to_string());
+        let bot_only = json!({
+            "message": { "reactions": [
+                { "name": "white_check_mark", "count": 1, "users": ["UBOT"] }
+            ] }
+        });
+        assert_eq!(slack_decision(&bot_only, &bot_listed, Some("UBOT")), None);
+    }
+
+    #[test]
+    fn discord_reactions_ignore_bots_and_other_users() {
+        let settings = settings(ApprovalChatProvider::Discord);
+        let nobody = json!([]);
+        let bot = json!([{ "id": "U1", "bot": true }]);
+        let stranger = json!([{ "id": "U2" }]);
+        let approver = json!([{ "id": "U1" }]);
+        assert_eq!(discord_decision(&bot, &nobody, &settings), None);
+        assert_eq!(discord_decision(&stranger, &nobody, &settings), None);
+        assert_eq!(
+            discord_decision(&approver, &nobody, &settings),
+            Some("accept")
+        );
+        assert_eq!(
+            discord_decision(&approver, &approver, &settings),
+            Some("decline")
+        );
+    }
+
+    #[test]
+    fn button_presses_are_read_from_slack_and_discord() {
+        let slack = json!({
+            "envelope_id": "e1",
+            "type": "interactive",
+            "payload": {
+                "type": "block_actions",
+                "user": { "id": "U1" },
+                "container": { "message_ts": "1700000000.000100" },
+                "actions": [{ "action_id": "codex_monitor_decline", "value": "decline" }]
+            }
+        });
+        assert_eq!(
+            slack_button_press(&slack),
+            Some(ButtonPress {
+                message_id: "1700000000.000100".to_string(),
+                user_id: "U1".to_string(),
+                is_bot: false,
+                decision: "decline",
+            })
+        );
+        assert_eq!(slack_button_press(&json!({ "type": "hello" })), None);
+
+        let discord = json!({
+            "id": "i1",
+            "token": "t1",
+            "type": 3,
+            "data": { "custom_id": "codex_monitor_approve", "component_type": 2 },
+            "member": { "user": { "id": "U1" } },
+            "message": { "id": "M1" }
+        });
+        assert_eq!(
+            discord_button_press(&discord),
+            Some(ButtonPress {
+                message_id: "M1".to_string(),
+                user_id: "U1".to_string(),
+                is_bot: false,
+                decision: "accept",
+            })
+        );
+        let mut other_button = discord.clone();
+        other_button["data"]["custom_id"] = json!("something_else");
+        assert_eq!(discord_button_press(&other_button), None);
+    }
+
+    #[test]
+    fn only_listed_people_may_answer() {
+        let mut settings = settings(ApprovalChatProvider::Slack);
+        assert!(may_answer(&settings, "U1", false));
+        assert!(!may_answer(&settings, "U1", true));
+        assert!(!may_answer(&settings, "U2", false));
+        settings.approver_ids.clear();
+        assert!(!may_answer(&settings, "U1", false));
+        assert!(prompt_text(&settings, "Run `ls`?").contains("approverIds"));
+    }
+
+    #[test]
+    fn incomplete_settings_are_ignored() {
+        let mut settings = settings(ApprovalChatProvider::Slack);
+        settings.channel_id = " ".to_string();
+        assert!(configured(Some(&settings)).is_none());
+        assert!(configured(None).is_none());
+    }
+}
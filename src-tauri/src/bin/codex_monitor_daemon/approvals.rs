@@ -0,0 +1,175 @@
+use super::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Server requests waiting for a client decision. Kept in memory on the
+/// daemon rather than on disk: request ids belong to the running app-server
+/// process, so they cannot be answered after a daemon restart anyway.
+///
+/// Fields stay snake_case to match the frontend's `ApprovalRequest`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub(super) struct PendingApproval {
+    pub(super) workspace_id: String,
+    pub(super) request_id: Value,
+    pub(super) method: String,
+    pub(super) thread_id: Option<String>,
+    pub(super) params: Value,
+    pub(super) received_at: u64,
+}
+
+#[derive(Default)]
+pub(super) struct PendingApprovals {
+    entries: std::sync::Mutex<Vec<PendingApproval>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn thread_id_of(params: &Value) -> Option<String> {
+    params
+        .get("threadId")
+        .or_else(|| params.get("thread_id"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+impl PendingApprovals {
+    /// Tracks approval requests as they are emitted and drops them once the
+    /// server resolves them, the turn finishes, or the session restarts.
+    pub(super) fn record(&self, event: &AppServerEvent) {
+        let Some(method) = event.message.get("method").and_then(Value::as_str) else {
+            return;
+        };
+        let params = event.message.get("params").cloned().unwrap_or(Value::Null);
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        match method {
+            "codex/connected" => {
+                entries.retain(|entry| entry.workspace_id != event.workspace_id);
+            }
+            "serverRequest/resolved" => {
+                if let Some(request_id) = params.get("requestId") {
+                    entries.retain(|entry| {
+                        entry.workspace_id != event.workspace_id || &entry.request_id != request_id
+                    });
+                }
+            }
+            "turn/completed" => {
+                let thread_id =
+                    thread_id_of(&params).or_else(|| params.get("turn").and_then(thread_id_of));
+                if let Some(thread_id) = thread_id {
+                    entries.retain(|entry| {
+                        entry.workspace_id != event.workspace_id
+                            || entry.thread_id.as_deref() != Some(thread_id.as_str())
+                    });
+                }
+            }
+            _ if method.ends_with("requestApproval") => {
+                let Some(request_id) = event.message.get("id").cloned() else {
+                    return;
+                };
+                let exists = entries.iter().any(|entry| {
+                    entry.workspace_id == event.workspace_id && entry.request_id == request_id
+                });
+                if !exists {
+                    entries.push(PendingApproval {
+                        workspace_id: event.workspace_id.clone(),
+                        request_id,
+                        method: method.to_string(),
+                        thread_id: thread_id_of(&params),
+                        params,
+                        received_at: now_ms(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn list(&self, workspace_id: Option<&str>) -> Vec<PendingApproval> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter(|entry| workspace_id.is_none_or(|id| entry.workspace_id == id))
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn resolve(&self, workspace_id: &str, request_id: &Value) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|entry| {
+                entry.workspace_id != workspace_id || &entry.request_id != request_id
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(workspace_id: &str, message: Value) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message,
+        }
+    }
+
+    #[test]
+    fn queues_approvals_until_resolved() {
+        let queue = PendingApprovals::default();
+        let request = event(
+            "ws-1",
+            json!({
+                "id": 4,
+                "method": "item/commandExecution/requestApproval",
+                "params": { "threadId": "t-1", "command": "ls" }
+            }),
+        );
+        queue.record(&request);
+        queue.record(&request);
+        queue.record(&event(
+            "ws-2",
+            json!({
+                "id": "a",
+                "method": "item/fileChange/requestApproval",
+                "params": { "threadId": "t-2" }
+            }),
+        ));
+        assert_eq!(queue.list(None).len(), 2);
+        assert_eq!(
+            queue.list(Some("ws-1"))[0].thread_id.as_deref(),
+            Some("t-1")
+        );
+
+        queue.resolve("ws-1", &json!(4));
+        assert!(queue.list(Some("ws-1")).is_empty());
+
+        queue.record(&event(
+            "ws-2",
+            json!({ "method": "turn/completed", "params": { "threadId": "t-2" } }),
+        ));
+        assert!(queue.list(None).is_empty());
+    }
+
+    #[test]
+    fn reconnect_drops_stale_requests() {
+        let queue = PendingApprovals::default();
+        queue.record(&event(
+            "ws-1",
+            json!({
+                "id": 1,
+                "method": "item/commandExecution/requestApproval",
+                "params": {}
+            }),
+        ));
+        queue.record(&event("ws-1", json!({ "method": "codex/connected" })));
+        assert!(queue.list(None).is_empty());
+    }
+}
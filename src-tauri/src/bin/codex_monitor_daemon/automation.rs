@@ -0,0 +1,240 @@
+use super::*;
+use shared::automation_core::{self, AutomationAction, AutomationHook};
+use shared::process_core::shell_command;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const AUTOMATED_MESSAGE_WINDOW: Duration = Duration::from_secs(600);
+const MAX_AUTOMATED_MESSAGES_PER_WINDOW: usize = 3;
+
+/// Runs enabled scripts from `<data dir>/automation` against app-server events.
+/// Scripts are evaluated one at a time; the actions they request run in the
+/// background. Failures are recorded on the script and emitted as
+/// `automation/error` events.
+pub(super) async fn run_automation(
+    state: Arc<DaemonState>,
+    mut rx: broadcast::Receiver<DaemonEvent>,
+) {
+    // Guards against scripts that reply to every completed turn with another
+    // message, which would otherwise loop forever.
+    let mut automated_messages: HashMap<String, VecDeque<Instant>> = HashMap::new();
+    loop {
+        let event = match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => stamped.event,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let Some(hook) = AutomationHook::from_message(&event.message) else {
+            continue;
+        };
+        run_hook(&state, hook, &event, &mut automated_messages).await;
+    }
+}
+
+async fn run_hook(
+    state: &Arc<DaemonState>,
+    hook: AutomationHook,
+    event: &AppServerEvent,
+    automated_messages: &mut HashMap<String, VecDeque<Instant>>,
+) {
+    let scripts = match automation_core::list_automation_scripts(&state.data_dir) {
+        Ok(scripts) => scripts,
+        Err(error) => {
+            eprintln!("automation: failed to list scripts: {error}");
+            return;
+        }
+    };
+    let payload = automation_core::hook_event(hook, &event.workspace_id, &event.message);
+    for script in scripts.into_iter().filter(|script| script.enabled) {
+        let data_dir = state.data_dir.clone();
+        let script_id = script.id.clone();
+        let hook_payload = payload.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let source = automation_core::read_automation_script(&data_dir, &script_id)?;
+            automation_core::run_automation_hook(&source, hook, &hook_payload)
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|result| result)
+        .and_then(|actions| {
+            for action in actions {
+                perform_action(
+                    state,
+                    &script.id,
+                    &event.workspace_id,
+                    action,
+                    automated_messages,
+                )?;
+            }
+            Ok(())
+        });
+        record_run(state, &script.id, &event.workspace_id, result.err()).await;
+    }
+}
+
+fn perform_action(
+    state: &Arc<DaemonState>,
+    script_id: &str,
+    event_workspace_id: &str,
+    action: AutomationAction,
+    automated_messages: &mut HashMap<String, VecDeque<Instant>>,
+) -> Result<(), String> {
+    match action {
+        AutomationAction::SendMessage {
+            workspace_id,
+            thread_id,
+            text,
+        } => {
+            let now = Instant::now();
+            let sent = automated_messages.entry(thread_id.clone()).or_default();
+            while sent
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > AUTOMATED_MESSAGE_WINDOW)
+            {
+                sent.pop_front();
+            }
+            if sent.len() >= MAX_AUTOMATED_MESSAGES_PER_WINDOW {
+                return Err(format!(
+                    "send_message limit reached for thread {thread_id} ({MAX_AUTOMATED_MESSAGES_PER_WINDOW} per {} minutes)",
+                    AUTOMATED_MESSAGE_WINDOW.as_secs() / 60
+                ));
+            }
+            sent.push_back(now);
+            spawn_action(
+                state,
+                script_id,
+                event_workspace_id,
+                move |state| async move {
+                    state
+                        .send_user_message(
+                            workspace_id,
+                            thread_id,
+                            text,
+                            None,
+                            None,
+                            None,
+                            None,
+                            None,
+                            None,
+                            None,
+                        )
+                        .await
+                        .map(|_| ())
+                },
+            );
+        }
+        AutomationAction::RunTask { workspace_id, task } => {
+            spawn_action(
+                state,
+                script_id,
+                event_workspace_id,
+                move |state| async move { state.run_automation_task(&workspace_id, &task).await },
+            );
+        }
+        AutomationAction::Notify { title, body } => {
+            emit_automation_event(
+                state,
+                event_workspace_id,
+                "automation/notify",
+                json!({ "scriptId": script_id, "title": title, "body": body }),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn spawn_action<F, Fut>(state: &Arc<DaemonState>, script_id: &str, workspace_id: &str, action: F)
+where
+    F: FnOnce(Arc<DaemonState>) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<(), String>> + Send,
+{
+    let state = Arc::clone(state);
+    let script_id = script_id.to_string();
+    let workspace_id = workspace_id.to_string();
+    tokio::spawn(async move {
+        if let Err(error) = action(Arc::clone(&state)).await {
+            record_run(&state, &script_id, &workspace_id, Some(error)).await;
+        }
+    });
+}
+
+/// Records the run on the blocking pool; `record_automation_run` serializes
+/// concurrent recorders itself.
+async fn record_run(
+    state: &DaemonState,
+    script_id: &str,
+    workspace_id: &str,
+    error: Option<String>,
+) {
+    let ran_at = chrono::Utc::now().timestamp_millis();
+    let data_dir = state.data_dir.clone();
+    let id = script_id.to_string();
+    let run_error = error.clone();
+    let recorded = tokio::task::spawn_blocking(move || {
+        automation_core::record_automation_run(&data_dir, &id, run_error, ran_at)
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    if let Err(record_error) = recorded {
+        eprintln!("automation: failed to record run of {script_id}: {record_error}");
+    }
+    if let Some(error) = error {
+        emit_automation_event(
+            state,
+            workspace_id,
+            "automation/error",
+            json!({ "scriptId": script_id, "error": error }),
+        );
+    }
+}
+
+fn emit_automation_event(state: &DaemonState, workspace_id: &str, method: &str, params: Value) {
+    state.event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({ "method": method, "params": params }),
+    });
+}
+
+impl DaemonState {
+    async fn run_automation_task(&self, workspace_id: &str, task: &str) -> Result<(), String> {
+        let entry = self
+            .workspaces
+            .read()
+            .await
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let script = release_core::resolve_launch_script(&entry, task)?;
+        let output = shell_command(&script)
+            .current_dir(&entry.path)
+            .output()
+            .await
+            .map_err(|err| format!("Failed to run task `{task}`: {err}"))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.trim().lines().last().unwrap_or_default();
+        Err(format!("Task `{task}` failed: {detail}").trim().to_string())
+    }
+
+    pub(super) fn list_automation_scripts(
+        &self,
+    ) -> Result<Vec<automation_core::AutomationScriptInfo>, String> {
+        automation_core::list_automation_scripts(&self.data_dir)
+    }
+
+    pub(super) async fn set_automation_script_enabled(
+        &self,
+        script_id: String,
+        enabled: bool,
+    ) -> Result<automation_core::AutomationScriptInfo, String> {
+        let data_dir = self.data_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            automation_core::set_automation_script_enabled(&data_dir, &script_id, enabled)
+        })
+        .await
+        .map_err(|err| err.to_string())?
+    }
+}
@@ -0,0 +1,366 @@
+use super::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+const RECENT_ACTIVITY_LIMIT: usize = 50;
+const RECORDED_METHODS: &[&str] = &[
+    "turn/started",
+    "turn/completed",
+    "item/completed",
+    "error",
+    "mergeQueue/progress",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityEntry {
+    at: i64,
+    workspace_id: String,
+    thread_id: Option<String>,
+    method: String,
+    detail: Option<String>,
+}
+
+/// Rolling view of app-server events for the dashboard. Only a handful of
+/// turn-level notifications are kept; deltas are ignored. Running turns come
+/// from the shared `TurnTracker` instead.
+#[derive(Default)]
+pub(super) struct DashboardActivity {
+    recent: VecDeque<ActivityEntry>,
+}
+
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .filter(|value| !value.is_empty())
+}
+
+impl DashboardActivity {
+    fn record(&mut self, event: &AppServerEvent, now: i64) {
+        let Some(method) = event.message.get("method").and_then(Value::as_str) else {
+            return;
+        };
+        if !RECORDED_METHODS.contains(&method) {
+            return;
+        }
+        let params = event.message.get("params").unwrap_or(&Value::Null);
+        let turn = params.get("turn").unwrap_or(&Value::Null);
+        let thread_id = str_field(params, &["threadId", "thread_id"])
+            .or_else(|| str_field(turn, &["threadId", "thread_id"]));
+        let detail = match method {
+            "turn/started" => None,
+            "turn/completed" => str_field(turn, &["status"]),
+            "item/completed" => params
+                .get("item")
+                .and_then(|item| str_field(item, &["type"])),
+            "error" => params
+                .get("error")
+                .and_then(|error| str_field(error, &["message"])),
+            _ => {
+                let stage = str_field(params, &["stage"]).unwrap_or_default();
+                let branch = str_field(params, &["branch"]).unwrap_or_default();
+                Some(format!("{branch}: {stage}"))
+            }
+        };
+        self.recent.push_front(ActivityEntry {
+            at: now,
+            workspace_id: event.workspace_id.clone(),
+            thread_id,
+            method: method.to_string(),
+            detail,
+        });
+        self.recent.truncate(RECENT_ACTIVITY_LIMIT);
+    }
+
+    fn snapshot(&self) -> Vec<ActivityEntry> {
+        self.recent.iter().cloned().collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    bearer_token: Option<String>,
+}
+
+fn parse_request_head(head: &str) -> Option<HttpRequest> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or(target).to_string();
+    let bearer_token = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    Some(HttpRequest {
+        method,
+        path,
+        bearer_token,
+    })
+}
+
+/// Compares every byte of `expected` whatever `provided` holds, so the time
+/// taken does not reveal how much of the token was right.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    let mut difference = provided.len() ^ expected.len();
+    for (index, byte) in expected.iter().enumerate() {
+        let other = provided.get(index).copied().unwrap_or(0);
+        difference |= usize::from(byte ^ other);
+    }
+    difference == 0
+}
+
+fn is_authorized(request: &HttpRequest, expected: Option<&str>) -> bool {
+    match expected {
+        None => true,
+        Some(expected) => request
+            .bearer_token
+            .as_deref()
+            .is_some_and(|provided| tokens_match(provided, expected)),
+    }
+}
+
+async fn read_request_head(socket: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        if buffer.len() > MAX_REQUEST_HEAD_BYTES {
+            return None;
+        }
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    String::from_utf8(buffer).ok()
+}
+
+async fn write_response(socket: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nX-Content-Type-Options: nosniff\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    if socket.write_all(head.as_bytes()).await.is_ok() {
+        let _ = socket.write_all(body).await;
+    }
+    let _ = socket.shutdown().await;
+}
+
+impl DaemonState {
+    async fn dashboard_status(&self, activity: &Mutex<DashboardActivity>) -> Value {
+        // Settings can hold scripts and env overrides, so only identity and
+        // connection state are exposed here.
+        let workspaces = workspaces_core::list_workspaces_core(&self.workspaces, &self.sessions)
+            .await
+            .into_iter()
+            .map(|workspace| {
+                json!({
+                    "id": workspace.id,
+                    "name": workspace.name,
+                    "kind": workspace.kind,
+                    "parentId": workspace.parent_id,
+                    "branch": workspace.worktree.map(|worktree| worktree.branch),
+                    "connected": workspace.connected,
+                })
+            })
+            .collect::<Vec<_>>();
+        let recent_activity = activity.lock().await.snapshot();
+        json!({
+            "daemon": self.daemon_info(),
+            "generatedAt": chrono::Utc::now().timestamp_millis(),
+            "workspaces": workspaces,
+            "activeTurns": self.services.turn_tracker.list(None),
+            "recentActivity": recent_activity,
+        })
+    }
+}
+
+async fn handle_dashboard_client(
+    mut socket: TcpStream,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+    activity: Arc<Mutex<DashboardActivity>>,
+) {
+    let head =
+        match tokio::time::timeout(REQUEST_HEAD_TIMEOUT, read_request_head(&mut socket)).await {
+            Ok(Some(head)) => head,
+            _ => return,
+        };
+    let Some(request) = parse_request_head(&head) else {
+        write_response(&mut socket, "400 Bad Request", "text/plain", b"bad request").await;
+        return;
+    };
+    if request.method != "GET" {
+        write_response(
+            &mut socket,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"method not allowed",
+        )
+        .await;
+        return;
+    }
+    match request.path.as_str() {
+        // The page itself carries no data; it asks for the token client-side.
+        "/" | "/index.html" => {
+            write_response(
+                &mut socket,
+                "200 OK",
+                "text/html; charset=utf-8",
+                DASHBOARD_HTML.as_bytes(),
+            )
+            .await;
+        }
+        "/api/status" => {
+            if !is_authorized(&request, config.token.as_deref()) {
+                write_response(
+                    &mut socket,
+                    "401 Unauthorized",
+                    "text/plain",
+                    b"unauthorized",
+                )
+                .await;
+                return;
+            }
+            let body = state.dashboard_status(&activity).await.to_string();
+            write_response(&mut socket, "200 OK", "application/json", body.as_bytes()).await;
+        }
+        _ => {
+            write_response(&mut socket, "404 Not Found", "text/plain", b"not found").await;
+        }
+    }
+}
+
+async fn track_activity(
+    mut rx: broadcast::Receiver<DaemonEvent>,
+    activity: Arc<Mutex<DashboardActivity>>,
+) {
+    loop {
+        match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => {
+                let emitted_at = stamped.emitted_at as i64;
+                activity.lock().await.record(&stamped.event, emitted_at);
+            }
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Serves the read-only status page on its own listener. The JSON endpoint
+/// reuses the daemon token as a bearer token.
+pub(super) async fn start_dashboard(
+    listen: SocketAddr,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+    events: &broadcast::Sender<DaemonEvent>,
+) {
+    let listener = match TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("failed to bind dashboard {listen}: {err}");
+            std::process::exit(2);
+        }
+    };
+    eprintln!("codex-monitor-daemon dashboard on http://{listen}/");
+
+    let activity = Arc::new(Mutex::new(DashboardActivity::default()));
+    tokio::spawn(track_activity(events.subscribe(), Arc::clone(&activity)));
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _addr)) => {
+                    tokio::spawn(handle_dashboard_client(
+                        socket,
+                        Arc::clone(&config),
+                        Arc::clone(&state),
+                        Arc::clone(&activity),
+                    ));
+                }
+                Err(_) => continue,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(workspace_id: &str, message: Value) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message,
+        }
+    }
+
+    #[test]
+    fn parse_request_head_reads_path_and_bearer_token() {
+        let request = parse_request_head(
+            "GET /api/status?x=1 HTTP/1.1\r\nHost: example\r\nauthorization: Bearer secret \r\n\r\n",
+        )
+        .expect("request");
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/status");
+        assert_eq!(request.bearer_token.as_deref(), Some("secret"));
+        assert!(is_authorized(&request, Some("secret")));
+        assert!(!is_authorized(&request, Some("other")));
+        assert!(!is_authorized(&request, Some("secret-longer")));
+        assert!(!is_authorized(&request, Some("secre")));
+
+        let anonymous = parse_request_head("GET / HTTP/1.1\r\n\r\n").expect("request");
+        assert!(!is_authorized(&anonymous, Some("secret")));
+        assert!(is_authorized(&anonymous, None));
+        assert!(parse_request_head("").is_none());
+    }
+
+    #[test]
+    fn activity_keeps_recent_turn_events() {
+        let mut activity = DashboardActivity::default();
+        activity.record(
+            &event(
+                "ws-1",
+                json!({
+                    "method": "turn/started",
+                    "params": { "threadId": "thread-1", "turn": { "id": "turn-1" } }
+                }),
+            ),
+            10,
+        );
+        activity.record(
+            &event(
+                "ws-1",
+                json!({ "method": "item/agentMessage/delta", "params": { "threadId": "thread-1" } }),
+            ),
+            11,
+        );
+        let recent = activity.snapshot();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].thread_id.as_deref(), Some("thread-1"));
+
+        activity.record(
+            &event(
+                "ws-1",
+                json!({
+                    "method": "turn/completed",
+                    "params": { "threadId": "thread-1", "turn": { "id": "turn-1", "status": "completed" } }
+                }),
+            ),
+            12,
+        );
+        let recent = activity.snapshot();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].method, "turn/completed");
+        assert_eq!(recent[0].detail.as_deref(), Some("completed"));
+    }
+}
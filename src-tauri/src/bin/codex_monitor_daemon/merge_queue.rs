@@ -0,0 +1,115 @@
+use super::*;
+use shared::merge_queue_core::{self, CiStatus, MergeCandidate, MergeOutcome, MergeQueueStage};
+use std::time::Duration;
+
+const MERGE_QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Polls worktrees labeled `mergeReady` while `mergeQueueEnabled` is set and
+/// lands them one at a time. A conflict or failure clears the label and stops
+/// the current pass so the remaining branches are not rebased onto a
+/// half-processed parent.
+pub(super) async fn run_merge_queue(state: Arc<DaemonState>) {
+    let mut ticker = tokio::time::interval(MERGE_QUEUE_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if !state.app_settings.lock().await.merge_queue_enabled {
+            continue;
+        }
+        state.process_merge_queue().await;
+    }
+}
+
+impl DaemonState {
+    async fn process_merge_queue(&self) {
+        let candidates = {
+            let workspaces = self.workspaces.read().await;
+            merge_queue_core::ready_merge_candidates(&workspaces)
+        };
+        for candidate in candidates {
+            let worktree_path = PathBuf::from(&candidate.worktree.path);
+            match merge_queue_core::fetch_ci_status(&worktree_path, &candidate.branch).await {
+                Ok(CiStatus::Passing) => {}
+                Ok(CiStatus::Pending | CiStatus::Failing | CiStatus::NoPullRequest) => continue,
+                Err(error) => {
+                    self.halt_merge_candidate(&candidate, MergeQueueStage::Failed, error)
+                        .await;
+                    return;
+                }
+            }
+
+            let outcome = merge_queue_core::rebase_test_and_merge(&candidate, |stage| {
+                self.emit_merge_queue_progress(&candidate, stage, None)
+            })
+            .await;
+            match outcome {
+                Ok(MergeOutcome::Merged { base_branch }) => {
+                    self.emit_merge_queue_progress(&candidate, MergeQueueStage::Removing, None);
+                    if let Err(error) = self.remove_worktree(candidate.worktree.id.clone()).await {
+                        self.halt_merge_candidate(&candidate, MergeQueueStage::Failed, error)
+                            .await;
+                        return;
+                    }
+                    self.emit_merge_queue_progress(
+                        &candidate,
+                        MergeQueueStage::Merged,
+                        Some(base_branch),
+                    );
+                }
+                Ok(MergeOutcome::Conflict { detail }) => {
+                    self.halt_merge_candidate(&candidate, MergeQueueStage::Conflict, detail)
+                        .await;
+                    return;
+                }
+                Err(error) => {
+                    self.halt_merge_candidate(&candidate, MergeQueueStage::Failed, error)
+                        .await;
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn halt_merge_candidate(
+        &self,
+        candidate: &MergeCandidate,
+        stage: MergeQueueStage,
+        detail: String,
+    ) {
+        let list = {
+            let mut workspaces = self.workspaces.write().await;
+            if let Some(entry) = workspaces.get_mut(&candidate.worktree.id) {
+                entry.settings.merge_ready = false;
+            }
+            workspaces.values().cloned().collect::<Vec<_>>()
+        };
+        let store_options = StoreWriteOptions::from_settings(&*self.app_settings.lock().await);
+        if let Err(error) =
+            storage::write_workspaces(&self.storage_path, &list, store_options).await
+        {
+            eprintln!("merge queue: failed to persist workspaces: {error}");
+        }
+        self.emit_merge_queue_progress(candidate, stage, Some(detail));
+    }
+
+    fn emit_merge_queue_progress(
+        &self,
+        candidate: &MergeCandidate,
+        stage: MergeQueueStage,
+        detail: Option<String>,
+    ) {
+        // Routed to the parent: the worktree workspace is gone once merged.
+        self.event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: candidate.parent.id.clone(),
+            message: json!({
+                "method": "mergeQueue/progress",
+                "params": {
+                    "workspaceId": candidate.parent.id,
+                    "worktreeId": candidate.worktree.id,
+                    "branch": candidate.branch,
+                    "stage": stage,
+                    "detail": detail,
+                }
+            }),
+        });
+    }
+}
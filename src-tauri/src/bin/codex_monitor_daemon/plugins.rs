@@ -0,0 +1,591 @@
+use super::rpc::{read_bounded_line, BoundedLine, MAX_RPC_LINE_BYTES};
+use super::*;
+use shared::process_core::tokio_command;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::oneshot;
+
+const PLUGINS_DIR: &str = "plugins";
+const PLUGIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+/// A plugin that stops reading stdin fills the pipe; the write gives up
+/// after this long instead of holding the process lock forever.
+const PLUGIN_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type PendingPluginRequests =
+    Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// `<data dir>/plugins/<name>.json`. Each declared method is served as
+/// `<namespace>/<method>` on the daemon RPC surface.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginManifest {
+    namespace: String,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    methods: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PluginInfo {
+    file: String,
+    namespace: Option<String>,
+    description: Option<String>,
+    methods: Vec<String>,
+    running: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum PluginMessage {
+    Response {
+        id: u64,
+        result: Result<Value, String>,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+}
+
+struct PluginProcess {
+    child: Child,
+    stdin: ChildStdin,
+    pending: PendingPluginRequests,
+}
+
+struct Plugin {
+    file: String,
+    manifest: PluginManifest,
+    working_dir: PathBuf,
+    next_id: AtomicU64,
+    process: Mutex<Option<PluginProcess>>,
+    request_timeout: Duration,
+    write_timeout: Duration,
+}
+
+#[derive(Default)]
+struct PluginSet {
+    plugins: HashMap<String, Arc<Plugin>>,
+    invalid: Vec<PluginInfo>,
+}
+
+/// Plugins loaded from `<data dir>/plugins`. Processes start on the first
+/// call and are restarted if they exit; reloading stops all of them.
+#[derive(Default)]
+pub(super) struct PluginRegistry {
+    set: std::sync::RwLock<PluginSet>,
+}
+
+fn is_valid_plugin_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
+fn validate_manifest(manifest: &PluginManifest) -> Result<(), String> {
+    if !is_valid_plugin_name(&manifest.namespace) {
+        return Err(format!("invalid namespace `{}`", manifest.namespace));
+    }
+    if manifest.command.trim().is_empty() {
+        return Err("`command` is required".to_string());
+    }
+    if manifest.methods.is_empty() {
+        return Err("`methods` must list at least one method".to_string());
+    }
+    if let Some(method) = manifest
+        .methods
+        .iter()
+        .find(|method| !is_valid_plugin_name(method))
+    {
+        return Err(format!("invalid method name `{method}`"));
+    }
+    Ok(())
+}
+
+fn read_manifest(path: &Path) -> Result<PluginManifest, String> {
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let manifest: PluginManifest = serde_json::from_str(&data).map_err(|err| err.to_string())?;
+    validate_manifest(&manifest)?;
+    Ok(manifest)
+}
+
+fn load_plugin_set(data_dir: &Path) -> PluginSet {
+    let plugins_dir = data_dir.join(PLUGINS_DIR);
+    let mut paths = match std::fs::read_dir(&plugins_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>(),
+        Err(_) => return PluginSet::default(),
+    };
+    paths.sort();
+
+    let mut set = PluginSet::default();
+    for path in paths {
+        let file = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let manifest =
+            read_manifest(&path).and_then(|manifest| match set.plugins.get(&manifest.namespace) {
+                Some(existing) => Err(format!(
+                    "namespace `{}` is already provided by {}",
+                    manifest.namespace, existing.file
+                )),
+                None => Ok(manifest),
+            });
+        match manifest {
+            Ok(manifest) => {
+                set.plugins.insert(
+                    manifest.namespace.clone(),
+                    Arc::new(Plugin {
+                        file,
+                        manifest,
+                        working_dir: plugins_dir.clone(),
+                        next_id: AtomicU64::new(1),
+                        process: Mutex::new(None),
+                        request_timeout: PLUGIN_REQUEST_TIMEOUT,
+                        write_timeout: PLUGIN_WRITE_TIMEOUT,
+                    }),
+                );
+            }
+            Err(error) => set.invalid.push(PluginInfo {
+                file,
+                namespace: None,
+                description: None,
+                methods: Vec::new(),
+                running: false,
+                error: Some(error),
+            }),
+        }
+    }
+    set
+}
+
+/// Parses one line of plugin output: a response carries the request `id`,
+/// anything else with a `method` is a notification.
+fn parse_plugin_message(line: &str) -> Option<PluginMessage> {
+    let Value::Object(mut map) = serde_json::from_str::<Value>(line).ok()? else {
+        return None;
+    };
+    if let Some(id) = map.get("id").and_then(Value::as_u64) {
+        let result = match map.remove("error") {
+            Some(error) if !error.is_null() => Err(error
+                .get("message")
+                .and_then(Value::as_str)
+                .or_else(|| error.as_str())
+                .unwrap_or("plugin request failed")
+                .to_string()),
+            _ => Ok(map.remove("result").unwrap_or(Value::Null)),
+        };
+        return Some(PluginMessage::Response { id, result });
+    }
+    let method = map.get("method").and_then(Value::as_str)?.to_string();
+    let params = map.remove("params").unwrap_or(Value::Null);
+    Some(PluginMessage::Notification { method, params })
+}
+
+async fn read_plugin_output(
+    namespace: String,
+    stdout: ChildStdout,
+    pending: PendingPluginRequests,
+    event_sink: DaemonEventSink,
+) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let line = match read_bounded_line(&mut reader, MAX_RPC_LINE_BYTES).await {
+            Ok(BoundedLine::Line(line)) => line,
+            Ok(BoundedLine::TooLarge(len)) => {
+                eprintln!("plugin `{namespace}`: dropped oversized line ({len} bytes)");
+                continue;
+            }
+            Ok(BoundedLine::Eof) | Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_plugin_message(&line) {
+            Some(PluginMessage::Response { id, result }) => {
+                let sender = pending
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .remove(&id);
+                if let Some(sender) = sender {
+                    let _ = sender.send(result);
+                }
+            }
+            Some(PluginMessage::Notification { method, params }) => {
+                let workspace_id = params
+                    .get("workspaceId")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id,
+                    message: json!({ "method": format!("{namespace}/{method}"), "params": params }),
+                });
+            }
+            None => eprintln!("plugin `{namespace}`: ignoring malformed output line"),
+        }
+    }
+    let pending = std::mem::take(
+        &mut *pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    );
+    for (_, sender) in pending {
+        let _ = sender.send(Err(format!("plugin `{namespace}` exited")));
+    }
+}
+
+impl Plugin {
+    fn command_path(&self) -> PathBuf {
+        let command = Path::new(&self.manifest.command);
+        if command.is_relative() && command.components().count() > 1 {
+            self.working_dir.join(command)
+        } else {
+            command.to_path_buf()
+        }
+    }
+
+    fn spawn(&self, event_sink: &DaemonEventSink) -> Result<PluginProcess, String> {
+        let namespace = &self.manifest.namespace;
+        let mut child = tokio_command(self.command_path())
+            .args(&self.manifest.args)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| format!("failed to start plugin `{namespace}`: {err}"))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| format!("plugin `{namespace}` has no stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| format!("plugin `{namespace}` has no stdout"))?;
+        let pending = PendingPluginRequests::default();
+        tokio::spawn(read_plugin_output(
+            namespace.clone(),
+            stdout,
+            Arc::clone(&pending),
+            event_sink.clone(),
+        ));
+        Ok(PluginProcess {
+            child,
+            stdin,
+            pending,
+        })
+    }
+
+    async fn is_running(&self) -> bool {
+        match self.process.lock().await.as_mut() {
+            Some(process) => matches!(process.child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    /// Kills the process `pid` if it is still the running one, so a stuck
+    /// plugin is started afresh on the next call. Its pending requests fail
+    /// once its output closes.
+    async fn stop(&self, pid: Option<u32>) {
+        let mut process = self.process.lock().await;
+        if process.as_ref().map(|running| running.child.id()) == Some(pid) {
+            if let Some(mut stuck) = process.take() {
+                let _ = stuck.child.start_kill();
+            }
+        }
+    }
+
+    /// Sends one request and waits for its response. The write and the wait
+    /// are both bounded; a plugin that misses either deadline is stopped.
+    async fn call(
+        &self,
+        method: &str,
+        params: &Value,
+        event_sink: &DaemonEventSink,
+    ) -> Result<Value, String> {
+        let namespace = &self.manifest.namespace;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        let (pending, pid) = {
+            let mut process = self.process.lock().await;
+            let exited = match process.as_mut() {
+                Some(running) => !matches!(running.child.try_wait(), Ok(None)),
+                None => true,
+            };
+            if exited {
+                *process = Some(self.spawn(event_sink)?);
+            }
+            let Some(running) = process.as_mut() else {
+                return Err(format!("plugin `{namespace}` is not running"));
+            };
+            running
+                .pending
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(id, tx);
+            let mut line =
+                serde_json::to_string(&json!({ "id": id, "method": method, "params": params }))
+                    .map_err(|err| err.to_string())?;
+            line.push('\n');
+            let written =
+                tokio::time::timeout(self.write_timeout, running.stdin.write_all(line.as_bytes()))
+                    .await;
+            if !matches!(written, Ok(Ok(()))) {
+                running
+                    .pending
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .remove(&id);
+                return Err(match written {
+                    Ok(Err(err)) => format!("failed to write to plugin `{namespace}`: {err}"),
+                    _ => {
+                        if let Some(mut stuck) = process.take() {
+                            let _ = stuck.child.start_kill();
+                        }
+                        format!(
+                            "plugin `{namespace}` stopped reading requests and was stopped; the next call restarts it"
+                        )
+                    }
+                });
+            }
+            (Arc::clone(&running.pending), running.child.id())
+        };
+        match tokio::time::timeout(self.request_timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("plugin `{namespace}` exited")),
+            Err(_) => {
+                pending
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .remove(&id);
+                self.stop(pid).await;
+                Err(format!(
+                    "plugin `{namespace}` did not respond to `{method}` within {} seconds and was stopped; the next call restarts it",
+                    self.request_timeout.as_secs()
+                ))
+            }
+        }
+    }
+}
+
+impl PluginRegistry {
+    pub(super) fn load(data_dir: &Path) -> Self {
+        Self {
+            set: std::sync::RwLock::new(load_plugin_set(data_dir)),
+        }
+    }
+
+    /// Rescans the plugins directory. Running plugin processes are dropped,
+    /// which stops them; the next call starts the new command.
+    fn reload(&self, data_dir: &Path) {
+        let set = load_plugin_set(data_dir);
+        *self
+            .set
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = set;
+    }
+
+    fn snapshot(&self) -> (Vec<Arc<Plugin>>, Vec<PluginInfo>) {
+        let set = self
+            .set
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut plugins = set.plugins.values().cloned().collect::<Vec<_>>();
+        plugins.sort_by(|a, b| a.file.cmp(&b.file));
+        (plugins, set.invalid.clone())
+    }
+
+    async fn list(&self) -> Vec<PluginInfo> {
+        let (plugins, invalid) = self.snapshot();
+        let mut infos = Vec::with_capacity(plugins.len() + invalid.len());
+        for plugin in plugins {
+            let namespace = &plugin.manifest.namespace;
+            infos.push(PluginInfo {
+                file: plugin.file.clone(),
+                namespace: Some(namespace.clone()),
+                description: plugin.manifest.description.clone(),
+                methods: plugin
+                    .manifest
+                    .methods
+                    .iter()
+                    .map(|method| format!("{namespace}/{method}"))
+                    .collect(),
+                running: plugin.is_running().await,
+                error: None,
+            });
+        }
+        infos.extend(invalid);
+        infos.sort_by(|a, b| a.file.cmp(&b.file));
+        infos
+    }
+
+    /// Finds the plugin serving `method`, or `None` when no plugin claims its
+    /// namespace so the caller can report an unknown method.
+    fn resolve(&self, method: &str) -> Option<Result<(Arc<Plugin>, String), String>> {
+        let (namespace, name) = method.split_once('/')?;
+        let plugin = self
+            .set
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .plugins
+            .get(namespace)
+            .cloned()?;
+        if !plugin.manifest.methods.iter().any(|method| method == name) {
+            return Some(Err(format!(
+                "plugin `{namespace}` does not provide `{name}`"
+            )));
+        }
+        Some(Ok((plugin, name.to_string())))
+    }
+}
+
+impl DaemonState {
+    pub(super) async fn list_plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.list().await
+    }
+
+    pub(super) async fn reload_plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.reload(&self.data_dir);
+        self.plugins.list().await
+    }
+
+    pub(super) async fn call_plugin_method(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Option<Result<Value, String>> {
+        let (plugin, name) = match self.plugins.resolve(method)? {
+            Ok(resolved) => resolved,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(plugin.call(&name, params, &self.event_sink).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{make_temp_dir, run_async_test, test_state};
+
+    fn write_manifest(data_dir: &Path, file: &str, manifest: Value) {
+        let dir = data_dir.join(PLUGINS_DIR);
+        std::fs::create_dir_all(&dir).expect("create plugins dir");
+        std::fs::write(dir.join(file), manifest.to_string()).expect("write manifest");
+    }
+
+    #[test]
+    fn load_plugin_set_reports_invalid_and_duplicate_manifests() {
+        let tmp = make_temp_dir("plugins-load");
+        write_manifest(
+            &tmp,
+            "a-deploy.json",
+            json!({ "namespace": "deploy", "command": "deployctl", "methods": ["run"] }),
+        );
+        write_manifest(
+            &tmp,
+            "b-deploy.json",
+            json!({ "namespace": "deploy", "command": "other", "methods": ["run"] }),
+        );
+        write_manifest(
+            &tmp,
+            "c-bad.json",
+            json!({ "namespace": "bad/name", "command": "x", "methods": ["run"] }),
+        );
+
+        let set = load_plugin_set(&tmp);
+        assert_eq!(set.plugins.len(), 1);
+        assert_eq!(set.plugins["deploy"].file, "a-deploy.json");
+        let errors = set
+            .invalid
+            .iter()
+            .map(|info| info.error.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "namespace `deploy` is already provided by a-deploy.json".to_string(),
+                "invalid namespace `bad/name`".to_string(),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unresponsive_plugins_time_out_and_are_restarted() {
+        run_async_test(async {
+            let tmp = make_temp_dir("plugins-timeout");
+            let state = test_state(&tmp);
+            let plugin = Plugin {
+                file: "slow.json".to_string(),
+                manifest: PluginManifest {
+                    namespace: "slow".to_string(),
+                    command: "sh".to_string(),
+                    args: vec!["-c".to_string(), "read line; exec sleep 30".to_string()],
+                    methods: vec!["run".to_string()],
+                    description: None,
+                },
+                working_dir: tmp.clone(),
+                next_id: AtomicU64::new(1),
+                process: Mutex::new(None),
+                request_timeout: Duration::from_millis(200),
+                write_timeout: Duration::from_millis(200),
+            };
+
+            let error = plugin
+                .call("run", &json!({}), &state.event_sink)
+                .await
+                .expect_err("timeout");
+            assert!(error.contains("did not respond to `run`"), "{error}");
+            assert!(!plugin.is_running().await);
+
+            // The next call starts a fresh process.
+            let error = plugin
+                .call("run", &json!({}), &state.event_sink)
+                .await
+                .expect_err("timeout");
+            assert!(error.contains("did not respond"), "{error}");
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn parse_plugin_message_distinguishes_responses_and_notifications() {
+        assert_eq!(
+            parse_plugin_message(r#"{"id":3,"result":{"ok":true}}"#),
+            Some(PluginMessage::Response {
+                id: 3,
+                result: Ok(json!({ "ok": true })),
+            })
+        );
+        assert_eq!(
+            parse_plugin_message(r#"{"id":4,"error":{"message":"denied"}}"#),
+            Some(PluginMessage::Response {
+                id: 4,
+                result: Err("denied".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_plugin_message(r#"{"method":"progress","params":{"step":1}}"#),
+            Some(PluginMessage::Notification {
+                method: "progress".to_string(),
+                params: json!({ "step": 1 }),
+            })
+        );
+        assert_eq!(parse_plugin_message("not json"), None);
+        assert_eq!(parse_plugin_message(r#"{"params":{}}"#), None);
+    }
+}
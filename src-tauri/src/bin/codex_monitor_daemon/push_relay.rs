@@ -0,0 +1,349 @@
+use super::*;
+use shared::notifications_core::{self, NotificationKind, WorkspaceNotification};
+use std::time::Duration;
+use types::{PushDevice, PushPlatform, PushRelaySettings};
+
+const PUSH_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Phones registered for pushes, persisted in `push-devices.json`, plus the
+/// tokens currently held by a live connection. A device is only pushed to
+/// while no connection holds its token, since a connected app already gets
+/// the event itself.
+pub(super) struct PushDevices {
+    path: PathBuf,
+    devices: std::sync::Mutex<Vec<PushDevice>>,
+    attached: std::sync::Mutex<HashMap<String, usize>>,
+}
+
+impl PushDevices {
+    pub(super) fn load(path: PathBuf) -> Self {
+        let devices = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            devices: std::sync::Mutex::new(devices),
+            attached: std::sync::Mutex::default(),
+        }
+    }
+
+    fn persist(&self, devices: &[PushDevice]) {
+        let result = (|| -> Result<(), String> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let data = serde_json::to_string_pretty(devices).map_err(|e| e.to_string())?;
+            let tmp = self.path.with_extension("json.tmp");
+            std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+            std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+        })();
+        if let Err(error) = result {
+            eprintln!("failed to persist push devices: {error}");
+        }
+    }
+
+    /// Adds `device`, replacing an earlier registration of the same token.
+    pub(super) fn register(&self, device: PushDevice) -> PushDevice {
+        if let Ok(mut devices) = self.devices.lock() {
+            devices.retain(|entry| entry.device_token != device.device_token);
+            devices.push(device.clone());
+            self.persist(&devices);
+        }
+        device
+    }
+
+    pub(super) fn unregister(&self, device_token: &str) -> bool {
+        let Ok(mut devices) = self.devices.lock() else {
+            return false;
+        };
+        let before = devices.len();
+        devices.retain(|entry| entry.device_token != device_token);
+        let removed = devices.len() != before;
+        if removed {
+            self.persist(&devices);
+        }
+        removed
+    }
+
+    pub(super) fn list(&self) -> Vec<PushDevice> {
+        self.devices
+            .lock()
+            .map(|devices| devices.clone())
+            .unwrap_or_default()
+    }
+
+    /// Marks `device_token` as reachable over a connection until the
+    /// matching [`PushDevices::detach`].
+    pub(super) fn attach(&self, device_token: &str) {
+        if let Ok(mut attached) = self.attached.lock() {
+            *attached.entry(device_token.to_string()).or_default() += 1;
+        }
+    }
+
+    pub(super) fn detach(&self, device_token: &str) {
+        let Ok(mut attached) = self.attached.lock() else {
+            return;
+        };
+        if let Some(count) = attached.get_mut(device_token) {
+            *count -= 1;
+            if *count == 0 {
+                attached.remove(device_token);
+            }
+        }
+    }
+
+    /// Registered devices that no live connection holds.
+    fn unreachable(&self) -> Vec<PushDevice> {
+        let attached = match self.attached.lock() {
+            Ok(attached) => attached.keys().cloned().collect::<HashSet<_>>(),
+            Err(_) => HashSet::new(),
+        };
+        self.list()
+            .into_iter()
+            .filter(|device| !attached.contains(&device.device_token))
+            .collect()
+    }
+}
+
+/// Reads the `push_register_device` params.
+pub(super) fn parse_push_device(params: &Value) -> Result<PushDevice, String> {
+    let device_token = params
+        .get("deviceToken")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or("missing `deviceToken`")?
+        .to_string();
+    let platform: PushPlatform = params
+        .get("platform")
+        .cloned()
+        .ok_or("missing `platform`")
+        .and_then(|value| {
+            serde_json::from_value(value).map_err(|_| "`platform` must be `apns` or `fcm`")
+        })?;
+    let optional = |key: &str| {
+        params
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    Ok(PushDevice {
+        device_token,
+        platform,
+        app_id: optional("appId"),
+        name: optional("name"),
+        registered_at_ms: rpc::server_time_ms(),
+    })
+}
+
+fn has_api_key(settings: &PushRelaySettings) -> bool {
+    settings
+        .api_key
+        .as_deref()
+        .is_some_and(|key| !key.trim().is_empty())
+}
+
+/// The relay settings when usable. A relay with an `apiKey` must be
+/// `https://` so the key never travels in cleartext.
+fn configured(settings: Option<&PushRelaySettings>) -> Option<PushRelaySettings> {
+    let settings = settings?;
+    let url = settings.url.trim();
+    if url.starts_with("https://") {
+        return Some(settings.clone());
+    }
+    if !url.starts_with("http://") {
+        return None;
+    }
+    if has_api_key(settings) {
+        eprintln!("push relay: refusing to send the API key to {url}; use an https:// URL");
+        return None;
+    }
+    Some(settings.clone())
+}
+
+/// The relay request for one device. The relay picks APNs or FCM from
+/// `platform` and shows `title` and `body`; `data` lets the app open the
+/// thread when the push is tapped.
+fn relay_payload(device: &PushDevice, notification: &WorkspaceNotification) -> Value {
+    json!({
+        "deviceToken": device.device_token,
+        "platform": device.platform,
+        "appId": device.app_id,
+        "title": notification.title,
+        "body": notification.body,
+        "data": {
+            "kind": notification.kind,
+            "workspaceId": notification.workspace_id,
+            "threadId": notification.thread_id,
+        },
+    })
+}
+
+enum PushOutcome {
+    Sent,
+    /// The relay reports the token as no longer valid.
+    Unregistered,
+}
+
+async fn send_push(
+    client: &reqwest::Client,
+    settings: &PushRelaySettings,
+    payload: &Value,
+) -> Result<PushOutcome, String> {
+    let url = settings.url.trim();
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(payload).map_err(|err| err.to_string())?);
+    if let Some(api_key) = settings
+        .api_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+    {
+        request = request.bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| format!("POST to {url} failed: {err}"))?;
+    match response.status() {
+        status if status.is_success() => Ok(PushOutcome::Sent),
+        reqwest::StatusCode::GONE => Ok(PushOutcome::Unregistered),
+        status => Err(format!("{url} returned {status}")),
+    }
+}
+
+/// Pushes turn completions, failures and approval requests through the
+/// `pushRelay` to every registered device whose app is not connected, so a
+/// backgrounded phone still hears about them. Devices the relay answers
+/// with `410 Gone` are unregistered.
+pub(super) async fn run_push_relay(
+    state: Arc<DaemonState>,
+    mut rx: broadcast::Receiver<DaemonEvent>,
+) {
+    let client = match state.services.http_client_builder().and_then(|builder| {
+        builder
+            .timeout(PUSH_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|err| err.to_string())
+    }) {
+        Ok(client) => client,
+        Err(error) => {
+            eprintln!("push relay: failed to build HTTP client: {error}");
+            return;
+        }
+    };
+    loop {
+        let event = match rx.recv().await {
+            Ok(DaemonEvent::AppServer(stamped)) => stamped.event,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if !notifications_core::may_notify(&event) {
+            continue;
+        }
+        let Some(settings) = configured(state.app_settings.lock().await.push_relay.as_ref()) else {
+            continue;
+        };
+        let Some(notification) =
+            notifications_core::describe_event(&state.workspaces, &event).await
+        else {
+            continue;
+        };
+        if notification.kind == NotificationKind::CiResult {
+            continue;
+        }
+        for device in state.push_devices.unreachable() {
+            let payload = relay_payload(&device, &notification);
+            let client = client.clone();
+            let settings = settings.clone();
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                match send_push(&client, &settings, &payload).await {
+                    Ok(PushOutcome::Sent) => {}
+                    Ok(PushOutcome::Unregistered) => {
+                        state.push_devices.unregister(&device.device_token);
+                    }
+                    Err(error) => eprintln!("push relay: {error}"),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(token: &str) -> PushDevice {
+        parse_push_device(&json!({ "deviceToken": token, "platform": "apns" }))
+            .expect("valid device")
+    }
+
+    #[test]
+    fn only_devices_without_a_connection_are_pushed() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-push-devices-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let path = dir.join("push-devices.json");
+        let devices = PushDevices::load(path.clone());
+        devices.register(device("phone"));
+        devices.register(device("tablet"));
+        devices.register(device("phone"));
+        assert_eq!(devices.list().len(), 2);
+
+        devices.attach("phone");
+        devices.attach("phone");
+        devices.detach("phone");
+        let tokens = |list: Vec<PushDevice>| {
+            list.into_iter()
+                .map(|device| device.device_token)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokens(devices.unreachable()), vec!["tablet"]);
+        devices.detach("phone");
+        assert_eq!(devices.unreachable().len(), 2);
+
+        assert!(devices.unregister("tablet"));
+        assert_eq!(tokens(PushDevices::load(path).list()), vec!["phone"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn api_keys_are_only_sent_over_https() {
+        let relay = |url: &str, api_key: Option<&str>| PushRelaySettings {
+            url: url.to_string(),
+            api_key: api_key.map(str::to_string),
+        };
+        assert!(configured(Some(&relay("https://relay.example", Some("secret")))).is_some());
+        assert!(configured(Some(&relay("http://relay.example", Some("secret")))).is_none());
+        assert!(configured(Some(&relay("http://localhost:8080", None))).is_some());
+        assert!(configured(Some(&relay("http://localhost:8080", Some("  ")))).is_some());
+        assert!(configured(Some(&relay("relay.example", None))).is_none());
+    }
+
+    #[test]
+    fn payload_carries_the_platform_and_thread() {
+        assert!(parse_push_device(&json!({ "deviceToken": "x", "platform": "sms" })).is_err());
+        assert!(parse_push_device(&json!({ "platform": "fcm" })).is_err());
+
+        let notification = WorkspaceNotification {
+            kind: NotificationKind::ApprovalRequested,
+            workspace_id: "ws-1".to_string(),
+            workspace_name: "Repo".to_string(),
+            thread_id: Some("t-1".to_string()),
+            title: "Approval needed — Repo".to_string(),
+            body: "rm -rf build".to_string(),
+        };
+        let payload = relay_payload(&device("phone"), &notification);
+        assert_eq!(payload["platform"], "apns");
+        assert_eq!(payload["title"], "Approval needed — Repo");
+        assert_eq!(payload["data"]["kind"], "approvalRequested");
+        assert_eq!(payload["data"]["threadId"], "t-1");
+    }
+}
@@ -1,4 +1,7 @@
 use super::*;
+use futures_util::FutureExt;
+use std::panic::AssertUnwindSafe;
+use tokio::sync::oneshot;
 
 #[path = "rpc/codex.rs"]
 mod codex;
@@ -13,6 +16,151 @@ mod prompts;
 #[path = "rpc/workspace.rs"]
 mod workspace;
 
+#[cfg(test)]
+#[path = "rpc/tests.rs"]
+mod tests;
+
+pub(super) use shared::daemon_version_core::MAX_RPC_LINE_BYTES;
+const RPC_PARSE_ERROR_CODE: i64 = -32700;
+const RPC_INVALID_REQUEST_CODE: i64 = -32600;
+
+pub(super) const METHOD_CANCEL: &str = "cancel";
+const CANCELED_MESSAGE: &str = "request canceled";
+
+/// Cancellation handles for a connection's in-flight requests, keyed by id.
+pub(super) type InFlightRequests = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<()>>>>;
+
+pub(super) struct RpcRequest {
+    pub(super) id: Option<u64>,
+    pub(super) method: String,
+    pub(super) params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum RpcParseError {
+    LineTooLarge { len: usize, limit: usize },
+    InvalidJson(String),
+    NotAnObject,
+    InvalidId,
+    MissingMethod,
+}
+
+impl RpcParseError {
+    fn code(&self) -> i64 {
+        match self {
+            RpcParseError::LineTooLarge { .. } | RpcParseError::InvalidJson(_) => {
+                RPC_PARSE_ERROR_CODE
+            }
+            RpcParseError::NotAnObject
+            | RpcParseError::InvalidId
+            | RpcParseError::MissingMethod => RPC_INVALID_REQUEST_CODE,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            RpcParseError::LineTooLarge { len, limit } => {
+                format!("request line too large ({len} bytes, limit {limit})")
+            }
+            RpcParseError::InvalidJson(detail) => format!("invalid JSON: {detail}"),
+            RpcParseError::NotAnObject => "request must be a JSON object".to_string(),
+            RpcParseError::InvalidId => "`id` must be a non-negative integer".to_string(),
+            RpcParseError::MissingMethod => "missing or invalid `method`".to_string(),
+        }
+    }
+}
+
+/// Parse one request line. On failure the request id is returned when it could be recovered
+/// so the client can fail the matching pending call instead of waiting for a timeout.
+pub(super) fn parse_rpc_request(line: &str) -> Result<RpcRequest, (Option<u64>, RpcParseError)> {
+    if line.len() > MAX_RPC_LINE_BYTES {
+        return Err((
+            None,
+            RpcParseError::LineTooLarge {
+                len: line.len(),
+                limit: MAX_RPC_LINE_BYTES,
+            },
+        ));
+    }
+    let message: Value = serde_json::from_str(line)
+        .map_err(|err| (None, RpcParseError::InvalidJson(err.to_string())))?;
+    let Value::Object(mut map) = message else {
+        return Err((None, RpcParseError::NotAnObject));
+    };
+    let id = match map.get("id") {
+        None | Some(Value::Null) => None,
+        Some(value) => match value.as_u64() {
+            Some(id) => Some(id),
+            None => return Err((None, RpcParseError::InvalidId)),
+        },
+    };
+    let method = match map.get("method").and_then(|value| value.as_str()) {
+        Some(method) if !method.trim().is_empty() => method.to_string(),
+        _ => return Err((id, RpcParseError::MissingMethod)),
+    };
+    let params = map.remove("params").unwrap_or(Value::Null);
+    Ok(RpcRequest { id, method, params })
+}
+
+pub(super) fn build_parse_error_response(id: Option<u64>, error: &RpcParseError) -> String {
+    serde_json::to_string(&json!({
+        "id": id,
+        "error": { "message": error.message(), "code": error.code() }
+    }))
+    .unwrap_or_else(|_| {
+        "{\"id\":null,\"error\":{\"message\":\"serialization failed\"}}".to_string()
+    })
+}
+
+pub(super) enum BoundedLine {
+    Line(String),
+    TooLarge(usize),
+    Eof,
+}
+
+/// Read one newline-terminated line without buffering more than `limit` bytes. Oversized lines
+/// are drained from the reader so the stream stays aligned on the next request.
+pub(super) async fn read_bounded_line<R>(
+    reader: &mut R,
+    limit: usize,
+) -> std::io::Result<BoundedLine>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let mut buffer = Vec::new();
+    let mut total = 0usize;
+    let mut saw_bytes = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            if !saw_bytes {
+                return Ok(BoundedLine::Eof);
+            }
+            break;
+        }
+        saw_bytes = true;
+        let newline = available.iter().position(|byte| *byte == b'\n');
+        let consumed = newline.map(|index| index + 1).unwrap_or(available.len());
+        let content_len = newline.unwrap_or(available.len());
+        if total + content_len <= limit {
+            buffer.extend_from_slice(&available[..content_len]);
+        } else {
+            buffer.clear();
+        }
+        total = total.saturating_add(content_len);
+        reader.consume(consumed);
+        if newline.is_some() {
+            break;
+        }
+    }
+    if total > limit {
+        return Ok(BoundedLine::TooLarge(total));
+    }
+    Ok(BoundedLine::Line(
+        String::from_utf8_lossy(&buffer).into_owned(),
+    ))
+}
+
 pub(super) fn build_error_response(id: Option<u64>, message: &str) -> Option<String> {
     let id = id?;
     Some(
@@ -26,31 +174,19 @@ pub(super) fn build_error_response(id: Option<u64>, message: &str) -> Option<Str
     )
 }
 
-pub(super) fn build_result_response(id: Option<u64>, result: Value) -> Option<String> {
-    let id = id?;
-    Some(
-        serde_json::to_string(&json!({ "id": id, "result": result })).unwrap_or_else(|_| {
-            "{\"id\":0,\"error\":{\"message\":\"serialization failed\"}}".to_string()
-        }),
-    )
+/// The daemon's clock (ms since the epoch). Every result carries it as
+/// `serverTime` so clients with a skewed clock can still render relative
+/// times correctly.
+pub(super) fn server_time_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
 }
 
-fn build_event_notification(event: DaemonEvent) -> Option<String> {
-    let payload = match event {
-        DaemonEvent::AppServer(payload) => json!({
-            "method": "app-server-event",
-            "params": payload,
-        }),
-        DaemonEvent::TerminalOutput(payload) => json!({
-            "method": "terminal-output",
-            "params": payload,
-        }),
-        DaemonEvent::TerminalExit(payload) => json!({
-            "method": "terminal-exit",
-            "params": payload,
-        }),
-    };
-    serde_json::to_string(&payload).ok()
+pub(super) fn build_result_response(id: Option<u64>, result: Value) -> Option<String> {
+    let id = id?;
+    let response = json!({ "id": id, "result": result, "serverTime": server_time_ms() });
+    Some(serde_json::to_string(&response).unwrap_or_else(|_| {
+        "{\"id\":0,\"error\":{\"message\":\"serialization failed\"}}".to_string()
+    }))
 }
 
 pub(super) fn parse_auth_token(params: &Value) -> Option<String> {
@@ -113,6 +249,13 @@ pub(super) fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     }
 }
 
+pub(super) fn parse_optional_u64(value: &Value, key: &str) -> Option<u64> {
+    match value {
+        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()),
+        _ => None,
+    }
+}
+
 pub(super) fn parse_optional_bool(value: &Value, key: &str) -> Option<bool> {
     match value {
         Value::Object(map) => map.get(key).and_then(|value| value.as_bool()),
@@ -155,25 +298,22 @@ pub(super) async fn handle_rpc_request(
     dispatcher::dispatch_rpc_request(state, method, &params, &client_version).await
 }
 
-pub(super) async fn forward_events(
-    mut rx: broadcast::Receiver<DaemonEvent>,
-    out_tx_events: mpsc::UnboundedSender<String>,
-) {
-    loop {
-        let event = match rx.recv().await {
-            Ok(event) => event,
-            Err(broadcast::error::RecvError::Lagged(_)) => continue,
-            Err(broadcast::error::RecvError::Closed) => break,
-        };
-
-        let Some(payload) = build_event_notification(event) else {
-            continue;
-        };
-
-        if out_tx_events.send(payload).is_err() {
-            break;
-        }
-    }
+/// Handles `cancel {requestId}`: drops the matching in-flight request so its
+/// pending app-server wait is released and the client gets a canceled error.
+pub(super) fn cancel_in_flight_request(
+    in_flight: &InFlightRequests,
+    params: &Value,
+) -> Result<Value, String> {
+    let request_id = params
+        .get("requestId")
+        .and_then(Value::as_u64)
+        .ok_or("missing or invalid `requestId`")?;
+    let sender = in_flight
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(&request_id);
+    let canceled = sender.is_some_and(|sender| sender.send(()).is_ok());
+    Ok(json!({ "canceled": canceled }))
 }
 
 pub(super) fn spawn_rpc_response_task(
@@ -184,12 +324,44 @@ pub(super) fn spawn_rpc_response_task(
     params: Value,
     client_version: String,
     request_limiter: Arc<Semaphore>,
+    in_flight: InFlightRequests,
 ) {
+    let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+    if let Some(id) = id {
+        in_flight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id, cancel_tx);
+    }
     tokio::spawn(async move {
-        let Ok(_permit) = request_limiter.acquire_owned().await else {
+        let handle = async {
+            let Ok(_permit) = request_limiter.acquire_owned().await else {
+                return None;
+            };
+            Some(
+                AssertUnwindSafe(handle_rpc_request(&state, &method, params, client_version))
+                    .catch_unwind()
+                    .await
+                    .unwrap_or_else(|_| Err(format!("internal error while handling `{method}`"))),
+            )
+        };
+        // Dropping `handle` on cancel also drops any pending app-server wait.
+        let result = tokio::select! {
+            result = handle => result,
+            Ok(()) = cancel_rx => Some(Err(CANCELED_MESSAGE.to_string())),
+        };
+        if let Some(id) = id {
+            let mut in_flight = in_flight
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            // A later request may have reused the id; only drop our own handle.
+            if in_flight.get(&id).is_some_and(|sender| sender.is_closed()) {
+                in_flight.remove(&id);
+            }
+        }
+        let Some(result) = result else {
             return;
         };
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
         let response = match result {
             Ok(result) => build_result_response(id, result),
             Err(message) => build_error_response(id, &message),
@@ -59,6 +59,40 @@ pub(super) async fn try_handle(
             };
             Some(state.read_thread(workspace_id, thread_id).await)
         }
+        "export_thread" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let format = match parse_string(params, "format") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let path = parse_optional_string(params, "path");
+            Some(
+                state
+                    .export_thread(workspace_id, thread_id, format, path)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "search_threads" => {
+            let query = match parse_string(params, "query") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let limit = parse_optional_u32(params, "limit").map(|limit| limit as usize);
+            Some(
+                state
+                    .search_threads(workspace_id, query, limit)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
         "thread_live_subscribe" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -100,12 +134,192 @@ pub(super) async fn try_handle(
             let cursor = parse_optional_string(params, "cursor");
             let limit = parse_optional_u32(params, "limit");
             let sort_key = parse_optional_string(params, "sortKey");
+            let tag = parse_optional_string(params, "tag");
             Some(
                 state
-                    .list_threads(workspace_id, cursor, limit, sort_key)
+                    .list_threads(workspace_id, cursor, limit, sort_key, tag)
                     .await,
             )
         }
+        "tag_thread" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let tags = match parse_string_array(params, "tags") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state
+                    .tag_thread(workspace_id, thread_id, tags)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "link_threads" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let target_thread_id = match parse_string(params, "targetThreadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let kind = match parse_string(params, "kind") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let target_workspace_id = parse_optional_string(params, "targetWorkspaceId");
+            Some(
+                state
+                    .link_threads(
+                        workspace_id,
+                        thread_id,
+                        target_workspace_id,
+                        target_thread_id,
+                        kind,
+                    )
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "unlink_threads" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let target_thread_id = match parse_string(params, "targetThreadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let kind = parse_optional_string(params, "kind");
+            Some(
+                state
+                    .unlink_threads(thread_id, target_thread_id, kind)
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "pin_thread" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let pinned = match parse_optional_bool(params, "pinned") {
+                Some(value) => value,
+                None => return Some(Err("missing or invalid `pinned`".to_string())),
+            };
+            Some(
+                state
+                    .pin_thread(workspace_id, thread_id, pinned)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "set_thread_order" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_ids = match parse_string_array(params, "threadIds") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state
+                    .set_thread_order(workspace_id, thread_ids)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "set_thread_meta" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let key = match parse_string(params, "key") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let value = parse_optional_value(params, "value").unwrap_or(Value::Null);
+            Some(
+                state
+                    .set_thread_meta(thread_id, key, value)
+                    .and_then(|meta| serde_json::to_value(meta).map_err(|err| err.to_string())),
+            )
+        }
+        "get_thread_meta" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serde_json::to_value(state.get_thread_meta(&thread_id))
+                    .map_err(|err| err.to_string()),
+            )
+        }
+        "get_thread_preferences" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .get_thread_preferences(&thread_id, workspace_id.as_deref())
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "set_thread_preferences" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let model = parse_optional_string(params, "model");
+            let effort = parse_optional_string(params, "effort");
+            Some(
+                state
+                    .set_thread_preferences(&thread_id, workspace_id.as_deref(), model, effort)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "session_provenance" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state
+                    .session_provenance(&workspace_id)
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "list_active_turns" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                serde_json::to_value(state.list_active_turns(workspace_id.as_deref()))
+                    .map_err(|err| err.to_string()),
+            )
+        }
+        "list_thread_annotations" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serde_json::to_value(state.list_thread_annotations(&workspace_id))
+                    .map_err(|err| err.to_string()),
+            )
+        }
         "list_mcp_server_status" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -130,6 +344,75 @@ pub(super) async fn try_handle(
             };
             Some(state.archive_thread(workspace_id, thread_id).await)
         }
+        "archive_thread_with_undo" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let delay_seconds = parse_optional_u64(params, "delaySeconds");
+            Some(
+                serde_json::to_value(state.archive_thread_with_undo(
+                    workspace_id,
+                    thread_id,
+                    delay_seconds,
+                ))
+                .map_err(|err| err.to_string()),
+            )
+        }
+        "undo_archive" => {
+            let token = match parse_string(params, "token") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state.undo_archive(token).and_then(|archive| {
+                    serde_json::to_value(archive).map_err(|err| err.to_string())
+                }),
+            )
+        }
+        "set_protocol_trace" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let enabled = match parse_optional_bool(params, "enabled") {
+                Some(value) => value,
+                None => return Some(Err("missing or invalid `enabled`".to_string())),
+            };
+            Some(
+                state
+                    .set_protocol_trace(workspace_id, enabled)
+                    .and_then(|status| serde_json::to_value(status).map_err(|err| err.to_string())),
+            )
+        }
+        "protocol_trace_tail" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let lines = parse_optional_u64(params, "lines").map(|lines| lines as usize);
+            Some(
+                state
+                    .protocol_trace_tail(workspace_id, lines)
+                    .and_then(|tail| serde_json::to_value(tail).map_err(|err| err.to_string())),
+            )
+        }
+        "get_thread_tail" => {
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let lines = parse_optional_u64(params, "lines").map(|lines| lines as usize);
+            Some(
+                state
+                    .get_thread_tail(thread_id, lines)
+                    .and_then(|tail| serde_json::to_value(tail).map_err(|err| err.to_string())),
+            )
+        }
         "compact_thread" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -209,6 +492,64 @@ pub(super) async fn try_handle(
             };
             Some(state.turn_interrupt(workspace_id, thread_id, turn_id).await)
         }
+        "list_slash_commands" => {
+            Some(serde_json::to_value(state.list_slash_commands()).map_err(|err| err.to_string()))
+        }
+        "queue_list" => {
+            Some(serde_json::to_value(state.queue_list()).map_err(|err| err.to_string()))
+        }
+        "rate_limit_forecast" => {
+            Some(serde_json::to_value(state.rate_limit_forecast()).map_err(|err| err.to_string()))
+        }
+        "queue_cancel" => {
+            let queue_id = match parse_string(params, "queueId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(state.queue_cancel(&queue_id).map(|_| json!({ "ok": true })))
+        }
+        "schedule_message" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let thread_id = match parse_string(params, "threadId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let text = match parse_string(params, "text") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let Some(send_at) = parse_optional_u64(params, "sendAt") else {
+                return Some(Err("missing `sendAt`".to_string()));
+            };
+            Some(
+                state
+                    .schedule_message(workspace_id, thread_id, text, send_at)
+                    .and_then(|message| {
+                        serde_json::to_value(message).map_err(|err| err.to_string())
+                    }),
+            )
+        }
+        "list_scheduled_messages" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                serde_json::to_value(state.list_scheduled_messages(workspace_id.as_deref()))
+                    .map_err(|err| err.to_string()),
+            )
+        }
+        "cancel_scheduled_message" => {
+            let schedule_id = match parse_string(params, "scheduleId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state
+                    .cancel_scheduled_message(&schedule_id)
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
         "turn_steer" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -303,6 +644,51 @@ pub(super) async fn try_handle(
                     .map(|_| json!({ "ok": true })),
             )
         }
+        "get_codex_config" => Some(
+            state
+                .get_codex_config()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "set_codex_config_value" => {
+            let key = match parse_string(params, "key") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let value = parse_optional_string(params, "value");
+            Some(
+                state
+                    .set_codex_config_value(key, value)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "set_codex_mcp_server" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let server = match parse_optional_value(params, "server")
+                .filter(|value| !value.is_null())
+                .map(serde_json::from_value::<codex_config::McpServerConfig>)
+                .transpose()
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err.to_string())),
+            };
+            Some(
+                state
+                    .set_codex_mcp_server(name, server)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "validate_codex_config" => Some(
+            state
+                .validate_codex_config()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
         "get_agents_settings" => Some(
             state
                 .get_agents_settings()
@@ -406,6 +792,17 @@ pub(super) async fn try_handle(
             };
             Some(state.codex_login(workspace_id).await)
         }
+        "codex_login_api_key" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let api_key = match parse_string(params, "apiKey") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(state.codex_login_api_key(workspace_id, api_key).await)
+        }
         "codex_login_cancel" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -413,6 +810,21 @@ pub(super) async fn try_handle(
             };
             Some(state.codex_login_cancel(workspace_id).await)
         }
+        "codex_logout" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(state.codex_logout(workspace_id).await)
+        }
+        "account_switch" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let api_key = parse_optional_string(params, "apiKey");
+            Some(state.account_switch(workspace_id, api_key).await)
+        }
         "skills_list" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -462,6 +874,42 @@ pub(super) async fn try_handle(
                     .await,
             )
         }
+        "list_notifications" => {
+            let unread_only = parse_optional_bool(params, "unreadOnly").unwrap_or(false);
+            let limit = parse_optional_u32(params, "limit");
+            Some(
+                serde_json::to_value(state.list_notifications(unread_only, limit))
+                    .map_err(|err| err.to_string()),
+            )
+        }
+        "mark_notifications_read" => {
+            let ids = parse_optional_string_array(params, "ids");
+            Some(Ok(json!(state.mark_notifications_read(ids))))
+        }
+        "push_register_device" => Some(
+            push_relay::parse_push_device(params)
+                .map(|device| state.push_devices.register(device))
+                .and_then(|device| serde_json::to_value(device).map_err(|err| err.to_string())),
+        ),
+        "push_unregister_device" => {
+            let device_token = match parse_string(params, "deviceToken") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(Ok(
+                json!({ "removed": state.push_devices.unregister(&device_token) }),
+            ))
+        }
+        "push_list_devices" => {
+            Some(serde_json::to_value(state.push_devices.list()).map_err(|err| err.to_string()))
+        }
+        "list_pending_approvals" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                serde_json::to_value(state.list_pending_approvals(workspace_id.as_deref()))
+                    .map_err(|err| err.to_string()),
+            )
+        }
         "remember_approval_rule" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -473,6 +921,52 @@ pub(super) async fn try_handle(
             };
             Some(state.remember_approval_rule(workspace_id, command).await)
         }
+        "add_approval_rule" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let kind = match parse_string(params, "kind") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let pattern = match parse_string(params, "pattern") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let decision = match parse_string(params, "decision") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let scoped = parse_optional_bool(params, "scoped").unwrap_or(false);
+            let expires_at = parse_optional_u64(params, "expiresAt");
+            Some(
+                state
+                    .add_approval_rule(workspace_id, kind, pattern, decision, scoped, expires_at)
+                    .await
+                    .and_then(|rule| serde_json::to_value(rule).map_err(|err| err.to_string())),
+            )
+        }
+        "list_approval_rules" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .list_approval_rules(workspace_id)
+                    .await
+                    .and_then(|rules| serde_json::to_value(rules).map_err(|err| err.to_string())),
+            )
+        }
+        "delete_approval_rule" => {
+            let rule_id = match parse_string(params, "ruleId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state
+                    .delete_approval_rule(&rule_id)
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
         "codex_doctor" => {
             let codex_bin = parse_optional_string(params, "codexBin");
             let codex_args = parse_optional_string(params, "codexArgs");
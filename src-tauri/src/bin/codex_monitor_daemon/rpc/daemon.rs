@@ -6,9 +6,23 @@ pub(super) async fn try_handle(
     params: &Value,
 ) -> Option<Result<Value, String>> {
     match method {
-        "ping" => Some(Ok(json!({ "ok": true }))),
+        "ping" => Some(Ok(json!({
+            "ok": true,
+            "protocolVersion": DAEMON_PROTOCOL_VERSION,
+        }))),
+        "server_time" => Some(Ok(json!({ "serverTime": server_time_ms() }))),
         "daemon_info" => Some(Ok(state.daemon_info())),
+        "daemon_version" => {
+            Some(serde_json::to_value(state.daemon_version()).map_err(|err| err.to_string()))
+        }
+        "get_capabilities" => Some(
+            serde_json::to_value(state.get_capabilities().await).map_err(|err| err.to_string()),
+        ),
+        "host_stats" => {
+            Some(serde_json::to_value(state.host_stats().await).map_err(|err| err.to_string()))
+        }
         "daemon_shutdown" => {
+            state.stop_all_sessions().await;
             tokio::spawn(async {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                 std::process::exit(0);
@@ -55,6 +69,52 @@ pub(super) async fn try_handle(
                     .map(|_| json!({ "ok": true })),
             )
         }
+        "terminal_output_page" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let terminal_id = match parse_string(params, "terminalId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let cursor = parse_optional_u64(params, "cursor");
+            let max_bytes = parse_optional_u64(params, "maxBytes").map(|bytes| bytes as usize);
+            Some(
+                state
+                    .terminal_output_page(&workspace_id, &terminal_id, cursor, max_bytes)
+                    .and_then(|page| serde_json::to_value(page).map_err(|err| err.to_string())),
+            )
+        }
+        "list_automation_scripts" => Some(
+            state
+                .list_automation_scripts()
+                .and_then(|scripts| serde_json::to_value(scripts).map_err(|e| e.to_string())),
+        ),
+        "set_automation_script_enabled" => {
+            let script_id = match parse_string(params, "scriptId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let enabled = match parse_optional_bool(params, "enabled") {
+                Some(value) => value,
+                None => return Some(Err("missing or invalid `enabled`".to_string())),
+            };
+            Some(
+                state
+                    .set_automation_script_enabled(script_id, enabled)
+                    .await
+                    .and_then(|script| serde_json::to_value(script).map_err(|e| e.to_string())),
+            )
+        }
+        "list_plugins" => {
+            let plugins = state.list_plugins().await;
+            Some(serde_json::to_value(plugins).map_err(|e| e.to_string()))
+        }
+        "reload_plugins" => {
+            let plugins = state.reload_plugins().await;
+            Some(serde_json::to_value(plugins).map_err(|e| e.to_string()))
+        }
         _ => None,
     }
 }
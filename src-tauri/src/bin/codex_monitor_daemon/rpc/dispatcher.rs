@@ -26,5 +26,9 @@ pub(super) async fn dispatch_rpc_request(
         return result;
     }
 
+    if let Some(result) = state.call_plugin_method(method, params).await {
+        return result;
+    }
+
     Err(format!("unknown method: {method}"))
 }
@@ -46,6 +46,10 @@ pub(super) async fn try_handle(
             let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
             Some(state.get_git_status(request.workspace_id).await)
         }
+        git_rpc::METHOD_GIT_STATUS_ALL => {
+            let refresh = parse_optional_bool(params, "refresh").unwrap_or(false);
+            Some(serialize_value(state.git_status_all(refresh).await))
+        }
         git_rpc::METHOD_INIT_GIT_REPO => {
             let request = parse_request_or_err!(params, git_rpc::InitGitRepoRequiredRequest);
             let force = parse_optional_bool(params, "force").unwrap_or(false);
@@ -94,6 +98,10 @@ pub(super) async fn try_handle(
             let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
             Some(serialize_result(state.get_git_remote(request.workspace_id)).await)
         }
+        git_rpc::METHOD_GIT_AUTH_DOCTOR => {
+            let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.git_auth_doctor(request.workspace_id)).await)
+        }
         git_rpc::METHOD_STAGE_GIT_FILE => {
             let request = parse_request_or_err!(params, git_rpc::WorkspacePathRequest);
             Some(serialize_ok(state.stage_git_file(request.workspace_id, request.path)).await)
@@ -181,6 +189,45 @@ pub(super) async fn try_handle(
             let request = parse_request_or_err!(params, git_rpc::WorkspaceNameRequest);
             Some(serialize_ok(state.create_git_branch(request.workspace_id, request.name)).await)
         }
+        git_rpc::METHOD_DELETE_GIT_BRANCH => {
+            let request = parse_request_or_err!(params, git_rpc::DeleteGitBranchRequest);
+            Some(
+                serialize_ok(state.delete_git_branch(
+                    request.workspace_id,
+                    request.name,
+                    request.force.unwrap_or(false),
+                ))
+                .await,
+            )
+        }
+        git_rpc::METHOD_SAVE_GIT_STASH => {
+            let request = parse_request_or_err!(params, git_rpc::SaveGitStashRequest);
+            Some(serialize_ok(state.save_git_stash(request.workspace_id, request.message)).await)
+        }
+        git_rpc::METHOD_LIST_GIT_STASHES => {
+            let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
+            Some(state.list_git_stashes(request.workspace_id).await)
+        }
+        git_rpc::METHOD_POP_GIT_STASH => {
+            let request = parse_request_or_err!(params, git_rpc::PopGitStashRequest);
+            let index = request.index.map(|value| value as usize);
+            Some(serialize_ok(state.pop_git_stash(request.workspace_id, index)).await)
+        }
+        git_rpc::METHOD_GET_GIT_CONFLICTS => {
+            let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.get_git_conflicts(request.workspace_id)).await)
+        }
+        git_rpc::METHOD_RESOLVE_GIT_CONFLICT => {
+            let request = parse_request_or_err!(params, git_rpc::ResolveGitConflictRequest);
+            Some(
+                serialize_ok(state.resolve_git_conflict(
+                    request.workspace_id,
+                    request.path,
+                    request.content,
+                ))
+                .await,
+            )
+        }
         git_rpc::METHOD_GENERATE_COMMIT_MESSAGE => {
             let request = parse_request_or_err!(params, git_rpc::WorkspaceIdRequest);
             let commit_message_model_id = parse_optional_string(params, "commitMessageModelId");
@@ -144,6 +144,81 @@ pub(super) async fn try_handle(
             };
             Some(serde_json::to_value(prompt).map_err(|err| err.to_string()))
         }
+        "prompt_list" => {
+            Some(serde_json::to_value(state.prompt_list()).map_err(|err| err.to_string()))
+        }
+        "prompt_save" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let template = match parse_string(params, "template") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let id = parse_optional_string(params, "id");
+            let description = parse_optional_string(params, "description");
+            let prompt = match state.prompt_save(id, name, description, template) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serde_json::to_value(prompt).map_err(|err| err.to_string()))
+        }
+        "prompt_delete" => {
+            let id = match parse_string(params, "id") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(state.prompt_delete(&id).map(|_| json!({ "ok": true })))
+        }
+        "prompt_render" => {
+            let id = match parse_string(params, "id") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let values: HashMap<String, String> = match parse_optional_value(params, "values")
+                .filter(|value| !value.is_null())
+                .map(serde_json::from_value)
+                .transpose()
+            {
+                Ok(value) => value.unwrap_or_default(),
+                Err(err) => return Some(Err(err.to_string())),
+            };
+            Some(
+                state
+                    .prompt_render(id, workspace_id, values)
+                    .await
+                    .map(Value::String),
+            )
+        }
+        "playbook_list" => {
+            Some(serde_json::to_value(state.playbook_list()).map_err(|err| err.to_string()))
+        }
+        "playbook_save" => {
+            let draft: PlaybookDraft = match parse_optional_value(params, "playbook")
+                .ok_or_else(|| "missing `playbook`".to_string())
+                .and_then(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                state.playbook_save(draft).and_then(|playbook| {
+                    serde_json::to_value(playbook).map_err(|err| err.to_string())
+                }),
+            )
+        }
+        "playbook_delete" => {
+            let id = match parse_string(params, "id") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(state.playbook_delete(&id).map(|_| json!({ "ok": true })))
+        }
         _ => None,
     }
 }
@@ -0,0 +1,424 @@
+use super::*;
+use crate::tests::{make_temp_dir, run_async_test, test_state};
+use proptest::prelude::*;
+
+fn arbitrary_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|value| json!(value)),
+        any::<u64>().prop_map(|value| json!(value)),
+        any::<f64>()
+            .prop_filter("finite", |value| value.is_finite())
+            .prop_map(|value| json!(value)),
+        ".{0,24}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::hash_map(
+                prop_oneof![
+                    Just("workspaceId".to_string()),
+                    Just("threadId".to_string()),
+                    Just("path".to_string()),
+                    Just("name".to_string()),
+                    Just("limit".to_string()),
+                    Just("input".to_string()),
+                    "[a-zA-Z]{1,12}",
+                ],
+                inner,
+                0..8,
+            )
+            .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+// Methods that only read state or resolve an unknown workspace, so fuzzing
+// them has no side effects.
+const FUZZED_METHODS: &[&str] = &[
+    "get_git_status",
+    "get_git_diffs",
+    "get_git_log",
+    "list_git_branches",
+    "delete_git_branch",
+    "list_git_stashes",
+    "pop_git_stash",
+    "get_git_conflicts",
+    "resolve_git_conflict",
+    "list_threads",
+    "read_thread",
+    "resume_thread",
+    "send_user_message",
+    "turn_interrupt",
+    "turn_steer",
+    "fork_thread",
+    "compact_thread",
+    "set_thread_name",
+    "list_mcp_server_status",
+    "apps_list",
+    "experimental_feature_list",
+    "respond_to_server_request",
+    "read_workspace_file",
+    "list_workspace_files",
+    "worktree_setup_status",
+    "workspace_quota_status",
+    "broadcast_run",
+    "select_winner",
+    "run_playbook",
+    "run_and_attach",
+    "workspace_summary",
+    "prompts_list",
+    "list_automation_scripts",
+    "set_automation_script_enabled",
+    "not_a_real_method",
+];
+
+// Inputs that once broke a fuzzed method. They are keyed by method name
+// rather than kept as proptest seeds, which only record an index into
+// `FUZZED_METHODS` and replay the wrong method once the list changes.
+fn fuzz_regressions() -> Vec<(&'static str, Value)> {
+    vec![
+        ("broadcast_run", Value::Null),
+        ("worktree_setup_status", json!({ "workspaceId": "" })),
+    ]
+}
+
+/// Runs `method` through the handler and the response builders, and checks
+/// that the client gets one line with its id and either a result or an
+/// error message.
+fn check_fuzzed_response(
+    state: &DaemonState,
+    method: &str,
+    params: Value,
+) -> Result<(), TestCaseError> {
+    let mut outcome = None;
+    run_async_test(async {
+        outcome = Some(handle_rpc_request(state, method, params, "daemon-test".to_string()).await);
+    });
+    let line = match outcome.expect("handler outcome") {
+        Ok(result) => build_result_response(Some(7), result),
+        Err(message) => {
+            prop_assert!(
+                !message.trim().is_empty(),
+                "`{}` failed without a message",
+                method
+            );
+            build_error_response(Some(7), &message)
+        }
+    };
+    let line = line.expect("response line");
+    prop_assert!(!line.contains('\n'), "`{}` response spans lines", method);
+    let response: Value = serde_json::from_str(&line)
+        .map_err(|err| TestCaseError::fail(format!("`{method}` response: {err}")))?;
+    prop_assert_eq!(&response["id"], &json!(7));
+    prop_assert!(
+        response.get("result").is_some() != response["error"]["message"].is_string(),
+        "`{}` returned {}",
+        method,
+        response
+    );
+    Ok(())
+}
+
+#[test]
+fn fuzz_regressions_return_well_formed_responses() {
+    let tmp = make_temp_dir("rpc-fuzz-regressions");
+    let state = test_state(&tmp);
+    for (method, params) in fuzz_regressions() {
+        check_fuzzed_response(&state, method, params).expect("well-formed response");
+    }
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+// Codex methods the desktop app calls through `shared::codex_core`; remote
+// clients must reach the same handlers instead of `unknown method`.
+const CODEX_PARITY_METHODS: &[&str] = &[
+    "fork_thread",
+    "turn_steer",
+    "compact_thread",
+    "set_thread_name",
+    "list_mcp_server_status",
+    "apps_list",
+    "experimental_feature_list",
+    "generate_run_metadata",
+    "generate_commit_message",
+];
+
+#[test]
+fn handle_rpc_request_routes_codex_parity_methods() {
+    let tmp = make_temp_dir("rpc-parity");
+    let state = test_state(&tmp);
+    let params = json!({
+        "workspaceId": "missing",
+        "threadId": "thread-1",
+        "turnId": "turn-1",
+        "text": "hello",
+        "name": "renamed",
+        "prompt": "summarize",
+    });
+    run_async_test(async {
+        for method in CODEX_PARITY_METHODS {
+            let error =
+                handle_rpc_request(&state, method, params.clone(), "daemon-test".to_string())
+                    .await
+                    .expect_err("unknown workspace should fail");
+            assert!(
+                !error.starts_with("unknown method"),
+                "`{method}` is not routed by the daemon: {error}"
+            );
+        }
+    });
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn automation_script_methods_list_and_toggle_scripts() {
+    let tmp = make_temp_dir("rpc-automation");
+    let state = test_state(&tmp);
+    std::fs::create_dir_all(tmp.join("automation")).expect("create automation dir");
+    std::fs::write(
+        tmp.join("automation").join("retry.rhai"),
+        "fn on_turn_completed(event) {}",
+    )
+    .expect("write script");
+    run_async_test(async {
+        let enabled = handle_rpc_request(
+            &state,
+            "set_automation_script_enabled",
+            json!({ "scriptId": "retry", "enabled": true }),
+            "daemon-test".to_string(),
+        )
+        .await
+        .expect("enable script");
+        assert_eq!(enabled["enabled"], json!(true));
+
+        let scripts = handle_rpc_request(
+            &state,
+            "list_automation_scripts",
+            json!({}),
+            "daemon-test".to_string(),
+        )
+        .await
+        .expect("list scripts");
+        assert_eq!(scripts[0]["id"], json!("retry"));
+        assert_eq!(scripts[0]["enabled"], json!(true));
+    });
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn plugin_methods_route_by_namespace_after_reload() {
+    let tmp = make_temp_dir("rpc-plugins");
+    let state = test_state(&tmp);
+    std::fs::create_dir_all(tmp.join("plugins")).expect("create plugins dir");
+    std::fs::write(
+        tmp.join("plugins").join("deploy.json"),
+        json!({
+            "namespace": "deploy",
+            "command": "./missing-deployctl",
+            "methods": ["run"],
+        })
+        .to_string(),
+    )
+    .expect("write manifest");
+    run_async_test(async {
+        let error = handle_rpc_request(&state, "deploy/run", json!({}), "daemon-test".to_string())
+            .await
+            .expect_err("plugins load on reload");
+        assert_eq!(error, "unknown method: deploy/run");
+
+        let plugins = handle_rpc_request(
+            &state,
+            "reload_plugins",
+            json!({}),
+            "daemon-test".to_string(),
+        )
+        .await
+        .expect("reload plugins");
+        assert_eq!(plugins[0]["namespace"], json!("deploy"));
+        assert_eq!(plugins[0]["methods"], json!(["deploy/run"]));
+        assert_eq!(plugins[0]["running"], json!(false));
+
+        let error = handle_rpc_request(
+            &state,
+            "deploy/rollback",
+            json!({}),
+            "daemon-test".to_string(),
+        )
+        .await
+        .expect_err("undeclared method");
+        assert_eq!(error, "plugin `deploy` does not provide `rollback`");
+
+        let error = handle_rpc_request(&state, "deploy/run", json!({}), "daemon-test".to_string())
+            .await
+            .expect_err("missing command");
+        assert!(
+            error.starts_with("failed to start plugin `deploy`"),
+            "{error}"
+        );
+    });
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cancel_in_flight_request_signals_matching_request() {
+    let in_flight: InFlightRequests = Arc::default();
+    let (tx, mut rx) = oneshot::channel();
+    in_flight.lock().unwrap().insert(4, tx);
+
+    let result = cancel_in_flight_request(&in_flight, &json!({ "requestId": 4 })).expect("cancel");
+    assert_eq!(result, json!({ "canceled": true }));
+    assert!(rx.try_recv().is_ok());
+    assert!(in_flight.lock().unwrap().is_empty());
+
+    let result = cancel_in_flight_request(&in_flight, &json!({ "requestId": 4 })).expect("cancel");
+    assert_eq!(result, json!({ "canceled": false }));
+    assert!(cancel_in_flight_request(&in_flight, &json!({})).is_err());
+}
+
+#[test]
+fn result_responses_carry_server_time() {
+    let before = server_time_ms();
+    let response = build_result_response(Some(3), json!({ "data": [] })).expect("response");
+    let response: Value = serde_json::from_str(&response).expect("response json");
+    assert_eq!(response["id"], json!(3));
+    assert_eq!(response["result"], json!({ "data": [] }));
+    let server_time = response["serverTime"].as_i64().expect("serverTime");
+    assert!(server_time >= before && server_time <= server_time_ms());
+    assert!(build_result_response(None, json!({})).is_none());
+}
+
+#[test]
+fn parse_rpc_request_rejects_oversized_lines() {
+    let line = format!(
+        "{{\"id\":1,\"method\":\"ping\",\"params\":\"{}\"}}",
+        "x".repeat(MAX_RPC_LINE_BYTES)
+    );
+    let Err((id, error)) = parse_rpc_request(&line) else {
+        panic!("expected oversized line to be rejected");
+    };
+    assert_eq!(id, None);
+    assert!(matches!(error, RpcParseError::LineTooLarge { .. }));
+}
+
+#[test]
+fn parse_rpc_request_keeps_id_when_method_is_missing() {
+    let Err((id, error)) = parse_rpc_request(r#"{"id":7,"params":{}}"#) else {
+        panic!("expected missing method to be rejected");
+    };
+    assert_eq!(id, Some(7));
+    assert_eq!(error, RpcParseError::MissingMethod);
+
+    let response: Value =
+        serde_json::from_str(&build_parse_error_response(id, &error)).expect("response json");
+    assert_eq!(response["id"], json!(7));
+    assert_eq!(response["error"]["code"], json!(RPC_INVALID_REQUEST_CODE));
+}
+
+#[test]
+fn parse_rpc_request_rejects_non_integer_ids() {
+    for line in [
+        r#"{"id":"1","method":"ping"}"#,
+        r#"{"id":-1,"method":"ping"}"#,
+        r#"{"id":1.5,"method":"ping"}"#,
+    ] {
+        let Err((id, error)) = parse_rpc_request(line) else {
+            panic!("expected invalid id to be rejected: {line}");
+        };
+        assert_eq!(id, None);
+        assert_eq!(error, RpcParseError::InvalidId);
+    }
+}
+
+#[test]
+fn parse_rpc_request_reports_invalid_json() {
+    let Err((_, error)) = parse_rpc_request("{not json") else {
+        panic!("expected invalid json to be rejected");
+    };
+    assert!(matches!(error, RpcParseError::InvalidJson(_)));
+    let Err((_, error)) = parse_rpc_request("[1,2,3]") else {
+        panic!("expected array to be rejected");
+    };
+    assert_eq!(error, RpcParseError::NotAnObject);
+}
+
+#[test]
+fn read_bounded_line_drains_oversized_line_and_keeps_stream_aligned() {
+    run_async_test(async {
+        let input = format!("{}\n{{\"id\":1}}\n", "y".repeat(64));
+        let mut reader = BufReader::with_capacity(8, input.as_bytes());
+
+        let first = read_bounded_line(&mut reader, 16).await.expect("read");
+        assert!(matches!(first, BoundedLine::TooLarge(64)));
+
+        let BoundedLine::Line(second) = read_bounded_line(&mut reader, 16).await.expect("read")
+        else {
+            panic!("expected second line");
+        };
+        assert_eq!(second, "{\"id\":1}");
+
+        assert!(matches!(
+            read_bounded_line(&mut reader, 16).await.expect("read"),
+            BoundedLine::Eof
+        ));
+    });
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn parse_rpc_request_never_panics(line in ".{0,256}") {
+        let _ = parse_rpc_request(&line);
+    }
+
+    #[test]
+    fn parse_rpc_request_accepts_any_params_shape(id in any::<u64>(), params in arbitrary_json()) {
+        let line = serde_json::to_string(&json!({
+            "id": id,
+            "method": "ping",
+            "params": params.clone(),
+        }))
+        .expect("serialize request");
+        // Floats may not survive a text round trip bit-for-bit; compare with
+        // what serde_json itself reads back.
+        let expected: Value =
+            serde_json::from_str(&params.to_string()).expect("reparse params");
+        let request = parse_rpc_request(&line).expect("valid request");
+        prop_assert_eq!(request.id, Some(id));
+        prop_assert_eq!(request.method, "ping");
+        prop_assert_eq!(request.params, expected);
+    }
+
+    #[test]
+    fn param_helpers_never_panic(params in arbitrary_json(), key in "[a-zA-Z]{1,12}") {
+        let _ = parse_string(&params, &key);
+        let _ = parse_optional_string(&params, &key);
+        let _ = parse_optional_nullable_string(&params, &key);
+        let _ = parse_optional_u32(&params, &key);
+        let _ = parse_optional_bool(&params, &key);
+        let _ = parse_optional_string_array(&params, &key);
+        let _ = parse_string_array(&params, &key);
+        let _ = parse_optional_value(&params, &key);
+        let _ = parse_auth_token(&params);
+    }
+}
+
+proptest! {
+    // Each case runs every fuzzed method, so keep the case count modest.
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn handle_rpc_request_answers_hostile_params_with_well_formed_responses(
+        params in arbitrary_json(),
+    ) {
+        let tmp = make_temp_dir("rpc-fuzz");
+        let state = test_state(&tmp);
+        let checked = FUZZED_METHODS
+            .iter()
+            .try_for_each(|method| check_fuzzed_response(&state, method, params.clone()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        checked?;
+    }
+}
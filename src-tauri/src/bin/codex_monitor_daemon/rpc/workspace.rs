@@ -21,6 +21,14 @@ struct FileWriteRequest {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageCleanupRequest {
+    category: StorageCategory,
+    #[serde(default)]
+    older_than_days: Option<u32>,
+}
+
 fn parse_file_read_request(params: &Value) -> Result<FileReadRequest, String> {
     serde_json::from_value(params.clone()).map_err(|err| err.to_string())
 }
@@ -108,6 +116,31 @@ pub(super) async fn try_handle(
                 .await,
             )
         }
+        "watch_workspace_files" => {
+            let request = parse_request_or_err!(params, workspace_rpc::IdRequest);
+            Some(serialize_ok(state.watch_workspace_files(request.id)).await)
+        }
+        "unwatch_workspace_files" => {
+            let request = parse_request_or_err!(params, workspace_rpc::IdRequest);
+            state.unwatch_workspace_files(request.id);
+            Some(Ok(json!({ "ok": true })))
+        }
+        "list_pending_operations" => Some(serialize_value(state.list_pending_operations())),
+        "retry_operation" => {
+            let request = parse_request_or_err!(params, workspace_rpc::OperationIdRequest);
+            Some(
+                state
+                    .retry_operation(request.operation_id, client_version.to_string())
+                    .await,
+            )
+        }
+        "suggest_branch_name" => {
+            let request = parse_request_or_err!(params, workspace_rpc::SuggestBranchNameRequest);
+            Some(
+                serialize_result(state.suggest_branch_name(request.workspace_id, request.base))
+                    .await,
+            )
+        }
         "worktree_setup_status" => {
             let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
             Some(serialize_result(state.worktree_setup_status(request.workspace_id)).await)
@@ -116,6 +149,42 @@ pub(super) async fn try_handle(
             let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
             Some(serialize_ok(state.worktree_setup_mark_ran(request.workspace_id)).await)
         }
+        "broadcast_run" => {
+            let request = parse_request_or_err!(params, workspace_rpc::BroadcastRunRequest);
+            Some(
+                serialize_result(state.broadcast_run(
+                    request.workspace_ids,
+                    request.text,
+                    request.model,
+                    request.worktree_branch,
+                    client_version.to_string(),
+                ))
+                .await,
+            )
+        }
+        "select_winner" => {
+            let request = parse_request_or_err!(params, workspace_rpc::SelectWinnerRequest);
+            Some(
+                serialize_result(state.select_winner(
+                    request.run_id,
+                    request.worktree_id,
+                    client_version.to_string(),
+                ))
+                .await,
+            )
+        }
+        "run_playbook" => {
+            let request = parse_request_or_err!(params, workspace_rpc::RunPlaybookRequest);
+            Some(
+                serialize_result(state.run_playbook(
+                    request.workspace_id,
+                    request.playbook_id,
+                    request.variables.unwrap_or_default(),
+                    client_version.to_string(),
+                ))
+                .await,
+            )
+        }
         "connect_workspace" => {
             let request = parse_request_or_err!(params, workspace_rpc::IdRequest);
             Some(
@@ -142,6 +211,10 @@ pub(super) async fn try_handle(
             let request = parse_request_or_err!(params, workspace_rpc::IdRequest);
             Some(serialize_ok(state.remove_worktree(request.id)).await)
         }
+        "unlock_worktree" => {
+            let request = parse_request_or_err!(params, workspace_rpc::IdRequest);
+            Some(serialize_result(state.unlock_worktree(request.id)).await)
+        }
         "rename_worktree" => {
             let request = parse_request_or_err!(params, workspace_rpc::RenameWorktreeRequest);
             Some(
@@ -177,17 +250,156 @@ pub(super) async fn try_handle(
                 .await,
             )
         }
+        "update_workspaces_bulk" => {
+            let request = parse_request_or_err!(params, workspace_rpc::UpdateWorkspacesBulkRequest);
+            Some(
+                serialize_result(state.update_workspaces_bulk(
+                    request.ids,
+                    request.partial_settings,
+                    client_version.to_string(),
+                ))
+                .await,
+            )
+        }
         "list_workspace_files" => {
-            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
-            Some(serialize_result(state.list_workspace_files(request.workspace_id)).await)
+            let request = parse_request_or_err!(params, workspace_rpc::ListWorkspaceFilesRequest);
+            Some(
+                serialize_result(state.list_workspace_files(request.workspace_id, request.refresh))
+                    .await,
+            )
+        }
+        "list_workspace_dir" => {
+            let request = parse_request_or_err!(params, workspace_rpc::ListWorkspaceDirRequest);
+            Some(
+                serialize_result(state.list_workspace_dir(request.workspace_id, request.path))
+                    .await,
+            )
+        }
+        "attach_context" => {
+            let request = parse_request_or_err!(params, workspace_rpc::AttachContextRequest);
+            Some(serialize_result(state.attach_context(request.workspace_id, request.paths)).await)
+        }
+        "record_workspace_focus" => {
+            let request = parse_request_or_err!(params, workspace_rpc::RecordWorkspaceFocusRequest);
+            Some(
+                serialize_result(
+                    state.record_workspace_focus(request.workspace_id, request.device),
+                )
+                .await,
+            )
+        }
+        "recent_workspaces" => {
+            let request = parse_request_or_err!(params, workspace_rpc::RecentWorkspacesRequest);
+            Some(
+                serde_json::to_value(state.recent_workspaces(request.limit).await)
+                    .map_err(|err| err.to_string()),
+            )
+        }
+        "list_workspace_files_page" => {
+            let request =
+                parse_request_or_err!(params, workspace_rpc::ListWorkspaceFilesPageRequest);
+            Some(
+                serialize_result(state.list_workspace_files_page(
+                    request.workspace_id,
+                    request.refresh,
+                    request.offset,
+                    request.limit,
+                ))
+                .await,
+            )
+        }
+        "write_workspace_file" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WriteWorkspaceFileRequest);
+            Some(
+                serialize_result(state.write_workspace_file(
+                    request.workspace_id,
+                    request.path,
+                    request.content,
+                    request.expected_hash,
+                ))
+                .await,
+            )
+        }
+        "apply_workspace_patch" => {
+            let request = parse_request_or_err!(params, workspace_rpc::ApplyWorkspacePatchRequest);
+            Some(
+                serialize_result(state.apply_workspace_patch(
+                    request.workspace_id,
+                    request.path,
+                    request.patch,
+                    request.expected_hash,
+                ))
+                .await,
+            )
+        }
+        "upload_file" => {
+            let request = parse_request_or_err!(params, workspace_rpc::UploadFileRequest);
+            Some(
+                serialize_result(state.upload_file(
+                    request.workspace_id,
+                    request.relative_path,
+                    request.file_name,
+                    request.bytes_base64,
+                ))
+                .await,
+            )
+        }
+        "download_file" => {
+            let request = parse_request_or_err!(params, workspace_rpc::DownloadFileRequest);
+            Some(
+                serialize_result(state.download_file(request.workspace_id, request.relative_path))
+                    .await,
+            )
+        }
+        "search_workspace_files" => {
+            let request = parse_request_or_err!(params, workspace_rpc::SearchWorkspaceFilesRequest);
+            Some(
+                serialize_result(state.search_workspace_files(
+                    request.workspace_id,
+                    request.query,
+                    request.regex,
+                    request.max_results,
+                ))
+                .await,
+            )
         }
         "read_workspace_file" => {
             let request = parse_request_or_err!(params, workspace_rpc::ReadWorkspaceFileRequest);
             Some(
-                serialize_result(state.read_workspace_file(request.workspace_id, request.path))
-                    .await,
+                serialize_result(state.read_workspace_file(
+                    request.workspace_id,
+                    request.path,
+                    request.offset,
+                    request.length,
+                ))
+                .await,
+            )
+        }
+        "workspace_quota_status" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.workspace_quota_status(request.workspace_id)).await)
+        }
+        "resume_workspace_quota" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.resume_workspace_quota(request.workspace_id)).await)
+        }
+        "package_release" => {
+            let request = parse_request_or_err!(params, workspace_rpc::PackageReleaseRequest);
+            Some(serialize_result(state.package_release(request.workspace_id, request.task)).await)
+        }
+        "run_and_attach" => {
+            let request = parse_request_or_err!(params, workspace_rpc::RunAndAttachRequest);
+            Some(
+                serialize_result(state.run_and_attach(request.workspace_id, request.command)).await,
             )
         }
+        "dashboard_snapshot" => Some(
+            serde_json::to_value(state.dashboard_snapshot().await).map_err(|err| err.to_string()),
+        ),
+        "workspace_summary" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.workspace_summary(request.workspace_id)).await)
+        }
         "add_clone" => {
             let request = parse_request_or_err!(params, workspace_rpc::AddCloneRequest);
             Some(
@@ -241,10 +453,26 @@ pub(super) async fn try_handle(
             };
             Some(serialize_result(state.update_app_settings(settings)).await)
         }
+        "storage_usage_report" => Some(serialize_result(state.storage_usage_report()).await),
+        "storage_cleanup" => {
+            let request = parse_request_or_err!(params, StorageCleanupRequest);
+            Some(
+                serialize_result(state.storage_cleanup(request.category, request.older_than_days))
+                    .await,
+            )
+        }
+        "test_network_connectivity" => {
+            let urls = parse_optional_string_array(params, "urls");
+            Some(serialize_result(state.test_network_connectivity(urls)).await)
+        }
         "apply_worktree_changes" => {
             let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
             Some(serialize_ok(state.apply_worktree_changes(request.workspace_id)).await)
         }
+        "compare_worktrees" => {
+            let request = parse_request_or_err!(params, workspace_rpc::CompareWorktreesRequest);
+            Some(serialize_result(state.compare_worktrees(request.a, request.b)).await)
+        }
         "open_workspace_in" => {
             let request = parse_request_or_err!(params, workspace_rpc::OpenWorkspaceInRequest);
             Some(
@@ -268,6 +496,15 @@ pub(super) async fn try_handle(
             let workspace_path = parse_optional_string(params, "workspacePath");
             Some(serialize_result(state.local_usage_snapshot(days, workspace_path)).await)
         }
+        "usage_summary" => {
+            let since = parse_optional_u64(params, "since");
+            let group_by = parse_optional_string(params, "groupBy");
+            Some(
+                state
+                    .usage_summary(since, group_by)
+                    .and_then(serialize_value),
+            )
+        }
         _ => None,
     }
 }
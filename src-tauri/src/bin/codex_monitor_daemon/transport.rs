@@ -1,8 +1,11 @@
 use super::rpc::{
-    build_error_response, build_result_response, forward_events, parse_auth_token,
-    spawn_rpc_response_task,
+    build_error_response, build_parse_error_response, build_result_response,
+    cancel_in_flight_request, parse_auth_token, parse_rpc_request, read_bounded_line,
+    spawn_rpc_response_task, BoundedLine, InFlightRequests, RpcParseError, RpcRequest,
+    MAX_RPC_LINE_BYTES, METHOD_CANCEL,
 };
 use super::*;
+use shared::event_forward_core::forward_events;
 
 pub(super) async fn handle_client(
     socket: TcpStream,
@@ -11,7 +14,7 @@ pub(super) async fn handle_client(
     events: broadcast::Sender<DaemonEvent>,
 ) {
     let (reader, mut writer) = socket.into_split();
-    let mut lines = BufReader::new(reader).lines();
+    let mut reader = BufReader::new(reader);
 
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
     let write_task = tokio::spawn(async move {
@@ -28,7 +31,11 @@ pub(super) async fn handle_client(
     let mut authenticated = config.token.is_none();
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
     let request_limiter = Arc::new(Semaphore::new(MAX_IN_FLIGHT_RPC_PER_CONNECTION));
+    let in_flight: InFlightRequests = Arc::default();
     let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
+    // Set once the client registers for pushes; the device is not pushed to
+    // while this connection lives.
+    let mut push_device: Option<String> = None;
 
     if authenticated {
         let rx = events.subscribe();
@@ -36,25 +43,32 @@ pub(super) async fn handle_client(
         events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
     }
 
-    while let Ok(Some(line)) = lines.next_line().await {
+    loop {
+        let line = match read_bounded_line(&mut reader, MAX_RPC_LINE_BYTES).await {
+            Ok(BoundedLine::Line(line)) => line,
+            Ok(BoundedLine::TooLarge(len)) => {
+                let error = RpcParseError::LineTooLarge {
+                    len,
+                    limit: MAX_RPC_LINE_BYTES,
+                };
+                let _ = out_tx.send(build_parse_error_response(None, &error));
+                continue;
+            }
+            Ok(BoundedLine::Eof) | Err(_) => break,
+        };
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
 
-        let message: Value = match serde_json::from_str(line) {
-            Ok(value) => value,
-            Err(_) => continue,
+        let RpcRequest { id, method, params } = match parse_rpc_request(line) {
+            Ok(request) => request,
+            Err((id, error)) => {
+                let _ = out_tx.send(build_parse_error_response(id, &error));
+                continue;
+            }
         };
 
-        let id = message.get("id").and_then(|value| value.as_u64());
-        let method = message
-            .get("method")
-            .and_then(|value| value.as_str())
-            .unwrap_or("")
-            .to_string();
-        let params = message.get("params").cloned().unwrap_or(Value::Null);
-
         if !authenticated {
             if method != "auth" {
                 if let Some(response) = build_error_response(id, "unauthorized") {
@@ -72,8 +86,29 @@ pub(super) async fn handle_client(
                 continue;
             }
 
+            // Clients that predate versioning send no protocol and are
+            // treated as protocol 0.
+            if let Err(message) = daemon_version_core::check_peer_protocol(
+                "daemon",
+                "app",
+                params.get("protocolVersion").and_then(Value::as_u64),
+                params.get("minProtocolVersion").and_then(Value::as_u64),
+            ) {
+                if let Some(response) = build_error_response(id, &message) {
+                    let _ = out_tx.send(response);
+                }
+                continue;
+            }
+
             authenticated = true;
-            if let Some(response) = build_result_response(id, json!({ "ok": true })) {
+            let version = daemon_version_core::daemon_version_core();
+            let result = json!({
+                "ok": true,
+                "version": version.version,
+                "protocolVersion": version.protocol_version,
+                "minProtocolVersion": version.min_protocol_version,
+            });
+            if let Some(response) = build_result_response(id, result) {
                 let _ = out_tx.send(response);
             }
 
@@ -84,6 +119,34 @@ pub(super) async fn handle_client(
             continue;
         }
 
+        if method == METHOD_CANCEL {
+            let response = match cancel_in_flight_request(&in_flight, &params) {
+                Ok(result) => build_result_response(id, result),
+                Err(message) => build_error_response(id, &message),
+            };
+            if let Some(response) = response {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
+        if method == "push_register_device" {
+            let device_token = params
+                .get("deviceToken")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(str::to_string);
+            if let Some(device_token) = device_token {
+                if push_device.as_deref() != Some(device_token.as_str()) {
+                    state.push_devices.attach(&device_token);
+                    if let Some(previous) = push_device.replace(device_token) {
+                        state.push_devices.detach(&previous);
+                    }
+                }
+            }
+        }
+
         spawn_rpc_response_task(
             Arc::clone(&state),
             out_tx.clone(),
@@ -92,9 +155,13 @@ pub(super) async fn handle_client(
             params,
             client_version.clone(),
             Arc::clone(&request_limiter),
+            Arc::clone(&in_flight),
         );
     }
 
+    if let Some(device_token) = push_device {
+        state.push_devices.detach(&device_token);
+    }
     drop(out_tx);
     if let Some(task) = events_task {
         task.abort();
@@ -1,6 +1,9 @@
 #[path = "../daemon_binary.rs"]
 mod daemon_binary;
 #[allow(dead_code)]
+#[path = "../secrets.rs"]
+mod secrets;
+#[allow(dead_code)]
 #[path = "../storage.rs"]
 mod storage;
 #[allow(dead_code)]
@@ -417,6 +420,7 @@ fn daemon_command_preview(
         daemon_path: daemon_path_str,
         args,
         token_configured,
+        serve_command: None,
     }
 }
 
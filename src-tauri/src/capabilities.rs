@@ -0,0 +1,93 @@
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::capabilities_core::{self, HostCapabilities, PlatformInfo};
+use crate::shared::host_stats_core::{self, HostStats};
+use crate::state::AppState;
+use crate::tailscale;
+use crate::types::BackendMode;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TailscaleCapabilities {
+    pub(crate) installed: bool,
+    pub(crate) running: bool,
+}
+
+/// Everything the UI gates features on, gathered in one call.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Capabilities {
+    pub(crate) backend_mode: BackendMode,
+    /// Remote mode only: whether the daemon answered.
+    pub(crate) daemon_reachable: Option<bool>,
+    pub(crate) daemon_error: Option<String>,
+    /// The machine running this app.
+    pub(crate) platform: PlatformInfo,
+    /// The machine running the Codex sessions; `None` while the daemon is
+    /// unreachable.
+    pub(crate) host: Option<HostCapabilities>,
+    pub(crate) tailscale: TailscaleCapabilities,
+}
+
+#[tauri::command]
+pub(crate) async fn get_capabilities(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Capabilities, String> {
+    let backend_mode = state.app_settings.lock().await.backend_mode.clone();
+    let host = async {
+        match backend_mode {
+            BackendMode::Remote => {
+                remote_backend::call_remote(&*state, app, "get_capabilities", json!({}))
+                    .await
+                    .and_then(|response| {
+                        serde_json::from_value::<HostCapabilities>(response)
+                            .map_err(|err| err.to_string())
+                    })
+            }
+            BackendMode::Local => {
+                Ok(capabilities_core::host_capabilities_core(&state.app_settings).await)
+            }
+        }
+    };
+    let (host, tailscale) = tokio::join!(host, tailscale::tailscale_status());
+    let tailscale = tailscale
+        .map(|status| TailscaleCapabilities {
+            installed: status.installed,
+            running: status.running,
+        })
+        .unwrap_or(TailscaleCapabilities {
+            installed: false,
+            running: false,
+        });
+    let (daemon_reachable, daemon_error) = match (&backend_mode, &host) {
+        (BackendMode::Local, _) => (None, None),
+        (BackendMode::Remote, Ok(_)) => (Some(true), None),
+        (BackendMode::Remote, Err(error)) => (Some(false), Some(error.clone())),
+    };
+    Ok(Capabilities {
+        backend_mode,
+        daemon_reachable,
+        daemon_error,
+        platform: capabilities_core::platform_info(),
+        host: host.ok(),
+        tailscale,
+    })
+}
+
+/// Load, memory, disk and Codex process counts for the machine running the
+/// Codex sessions.
+#[tauri::command]
+pub(crate) async fn host_stats(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<HostStats, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "host_stats", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(host_stats_core::host_stats_core(&state.data_dir()).await)
+}
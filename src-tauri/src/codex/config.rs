@@ -1,7 +1,138 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
+use toml_edit::{value, Array, Document, InlineTable, Item, Table};
+
+use crate::files::ops::read_with_policy;
+use crate::files::policy::{policy_for, FileKind, FileScope};
 use crate::shared::config_toml_core;
 
+/// Top-level string settings the editor may write, with their allowed
+/// values (empty: any non-empty string).
+const STRING_SETTINGS: &[(&str, &[&str])] = &[
+    ("model", &[]),
+    ("review_model", &[]),
+    ("model_provider", &[]),
+    (
+        "model_reasoning_effort",
+        &["minimal", "low", "medium", "high", "xhigh"],
+    ),
+    (
+        "model_reasoning_summary",
+        &["auto", "concise", "detailed", "none"],
+    ),
+    ("model_verbosity", &["low", "medium", "high"]),
+    (
+        "approval_policy",
+        &["untrusted", "on-failure", "on-request", "never"],
+    ),
+    (
+        "sandbox_mode",
+        &["read-only", "workspace-write", "danger-full-access"],
+    ),
+];
+
+/// Top-level keys Codex reads besides `STRING_SETTINGS`. Keys starting with
+/// `experimental_` are accepted as well.
+const OTHER_KNOWN_KEYS: &[&str] = &[
+    "chatgpt_base_url",
+    "check_for_update_on_startup",
+    "cli_auth_credentials_store",
+    "compact_prompt",
+    "developer_instructions",
+    "disable_paste_burst",
+    "features",
+    "file_opener",
+    "forced_chatgpt_workspace_id",
+    "forced_login_method",
+    "hide_agent_reasoning",
+    "history",
+    "instructions",
+    "mcp_oauth_credentials_store",
+    "mcp_servers",
+    "model_auto_compact_token_limit",
+    "model_context_window",
+    "model_max_output_tokens",
+    "model_providers",
+    "model_supports_reasoning_summaries",
+    "notice",
+    "notify",
+    "otel",
+    "personality",
+    "preferred_auth_method",
+    "profile",
+    "profiles",
+    "project_doc_fallback_filenames",
+    "project_doc_max_bytes",
+    "projects",
+    "sandbox_workspace_write",
+    "shell_environment_policy",
+    "show_raw_agent_reasoning",
+    "tools",
+    "tui",
+    "windows_wsl_setup_acknowledged",
+];
+
+/// One `[mcp_servers.<name>]` entry. Keys the editor does not manage (e.g.
+/// `startup_timeout_sec`) are left as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerConfig {
+    #[serde(default)]
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    /// Streamable HTTP servers use `url` instead of `command`.
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default)]
+    pub(crate) enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexConfigSettings {
+    pub(crate) model: Option<String>,
+    pub(crate) model_reasoning_effort: Option<String>,
+    pub(crate) approval_policy: Option<String>,
+    pub(crate) sandbox_mode: Option<String>,
+    /// `[features]`, including flags this app has no toggle for.
+    pub(crate) features: BTreeMap<String, bool>,
+    pub(crate) mcp_servers: BTreeMap<String, McpServerConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigParseError {
+    pub(crate) message: String,
+    /// 1-based.
+    pub(crate) line: Option<usize>,
+    pub(crate) column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigIssue {
+    /// Dotted path, e.g. `mcp_servers.docs.command`.
+    pub(crate) key: String,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexConfigValidation {
+    pub(crate) path: Option<String>,
+    pub(crate) exists: bool,
+    /// No parse error and no issues. Unknown keys are only reported.
+    pub(crate) valid: bool,
+    pub(crate) parse_error: Option<ConfigParseError>,
+    pub(crate) unknown_keys: Vec<String>,
+    pub(crate) issues: Vec<ConfigIssue>,
+}
+
 pub(crate) fn read_steer_enabled() -> Result<Option<bool>, String> {
     read_feature_flag("steer")
 }
@@ -93,6 +224,317 @@ pub(crate) fn read_config_model(codex_home: Option<PathBuf>) -> Result<Option<St
     Ok(config_toml_core::read_top_level_string(&document, "model"))
 }
 
+fn require_default_codex_home() -> Result<PathBuf, String> {
+    resolve_default_codex_home().ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
+}
+
+pub(crate) fn read_codex_config() -> Result<CodexConfigSettings, String> {
+    let root = require_default_codex_home()?;
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(read_settings_from_document(&document))
+}
+
+/// Sets or, with `None`, removes one of the top-level string settings.
+pub(crate) fn write_config_string(key: &str, raw: Option<&str>) -> Result<(), String> {
+    let root = require_default_codex_home()?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    set_string_setting(&mut document, key, raw)?;
+    config_toml_core::persist_global_config_document(&root, &document)
+}
+
+/// Adds, updates or, with `None`, removes `[mcp_servers.<name>]`.
+pub(crate) fn write_mcp_server(name: &str, server: Option<&McpServerConfig>) -> Result<(), String> {
+    let root = require_default_codex_home()?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    set_mcp_server(&mut document, name, server)?;
+    config_toml_core::persist_global_config_document(&root, &document)
+}
+
+pub(crate) fn validate_codex_config() -> Result<CodexConfigValidation, String> {
+    let root = require_default_codex_home()?;
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let response = read_with_policy(&root, policy)?;
+    let path = Some(root.join("config.toml").to_string_lossy().to_string());
+    Ok(validate_config_contents(
+        path,
+        response.exists,
+        &response.content,
+    ))
+}
+
+fn read_settings_from_document(document: &Document) -> CodexConfigSettings {
+    let features = document
+        .get("features")
+        .and_then(Item::as_table_like)
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(key, item)| item.as_bool().map(|enabled| (key.to_string(), enabled)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mcp_servers = document
+        .get("mcp_servers")
+        .and_then(Item::as_table_like)
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(name, item)| {
+                    item.as_table_like()
+                        .map(|server| (name.to_string(), read_mcp_server(server)))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    CodexConfigSettings {
+        model: config_toml_core::read_top_level_string(document, "model"),
+        model_reasoning_effort: config_toml_core::read_top_level_string(
+            document,
+            "model_reasoning_effort",
+        ),
+        approval_policy: config_toml_core::read_top_level_string(document, "approval_policy"),
+        sandbox_mode: config_toml_core::read_top_level_string(document, "sandbox_mode"),
+        features,
+        mcp_servers,
+    }
+}
+
+fn read_mcp_server(server: &dyn toml_edit::TableLike) -> McpServerConfig {
+    let string = |key: &str| server.get(key).and_then(Item::as_str).map(str::to_string);
+    McpServerConfig {
+        command: string("command"),
+        args: server
+            .get("args")
+            .and_then(Item::as_array)
+            .map(|args| {
+                args.iter()
+                    .filter_map(|arg| arg.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        env: server
+            .get("env")
+            .and_then(Item::as_table_like)
+            .map(|env| {
+                env.iter()
+                    .filter_map(|(key, item)| {
+                        item.as_str()
+                            .map(|value| (key.to_string(), value.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        url: string("url"),
+        enabled: server.get("enabled").and_then(Item::as_bool),
+    }
+}
+
+fn set_string_setting(document: &mut Document, key: &str, raw: Option<&str>) -> Result<(), String> {
+    let Some((key, allowed)) = STRING_SETTINGS
+        .iter()
+        .find(|(known, _)| *known == key.trim())
+    else {
+        return Err(format!("`{key}` is not an editable config.toml setting"));
+    };
+    let raw = raw.map(str::trim).filter(|raw| !raw.is_empty());
+    if let Some(raw) = raw {
+        if !allowed.is_empty() && !allowed.contains(&raw) {
+            return Err(format!("`{key}` must be one of: {}", allowed.join(", ")));
+        }
+    }
+    config_toml_core::set_top_level_string(document, key, raw);
+    Ok(())
+}
+
+fn valid_mcp_server_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
+fn set_mcp_server(
+    document: &mut Document,
+    name: &str,
+    server: Option<&McpServerConfig>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if !valid_mcp_server_name(name) {
+        return Err("MCP server names may only use letters, digits, `_` and `-`".to_string());
+    }
+    let Some(server) = server else {
+        if let Some(servers) = document
+            .get_mut("mcp_servers")
+            .and_then(Item::as_table_like_mut)
+        {
+            servers.remove(name);
+            if servers.is_empty() {
+                document.remove("mcp_servers");
+            }
+        }
+        return Ok(());
+    };
+    let command = server
+        .command
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    let url = server
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    if command.is_some() == url.is_some() {
+        return Err("An MCP server needs either `command` or `url`".to_string());
+    }
+
+    let servers = config_toml_core::ensure_table(document, "mcp_servers")?;
+    if servers.get(name).and_then(Item::as_table_like).is_none() {
+        servers.insert(name, Item::Table(Table::new()));
+    }
+    let entry = servers
+        .get_mut(name)
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| format!("`mcp_servers.{name}` must be a table"))?;
+    let mut set = |key: &str, item: Option<Item>| match item {
+        Some(item) => {
+            entry.insert(key, item);
+        }
+        None => {
+            entry.remove(key);
+        }
+    };
+    set("command", command.map(value));
+    set(
+        "args",
+        (!server.args.is_empty()).then(|| value(server.args.iter().collect::<Array>())),
+    );
+    set(
+        "env",
+        (!server.env.is_empty()).then(|| {
+            let mut env = InlineTable::new();
+            for (key, val) in &server.env {
+                env.insert(key, val.as_str().into());
+            }
+            value(env)
+        }),
+    );
+    set("url", url.map(value));
+    set("enabled", server.enabled.map(value));
+    Ok(())
+}
+
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+fn validate_config_contents(
+    path: Option<String>,
+    exists: bool,
+    contents: &str,
+) -> CodexConfigValidation {
+    let mut validation = CodexConfigValidation {
+        path,
+        exists,
+        valid: true,
+        parse_error: None,
+        unknown_keys: Vec::new(),
+        issues: Vec::new(),
+    };
+    let document = if contents.trim().is_empty() {
+        Document::new()
+    } else {
+        match contents.parse::<Document>() {
+            Ok(document) => document,
+            Err(err) => {
+                let (line, column) = err
+                    .span()
+                    .map(|span| line_and_column(contents, span.start))
+                    .unzip();
+                validation.valid = false;
+                validation.parse_error = Some(ConfigParseError {
+                    message: err.message().to_string(),
+                    line,
+                    column,
+                });
+                return validation;
+            }
+        }
+    };
+
+    let mut issue = |key: String, message: String| {
+        validation.issues.push(ConfigIssue { key, message });
+    };
+    for (key, item) in document.iter() {
+        if let Some((_, allowed)) = STRING_SETTINGS.iter().find(|(known, _)| *known == key) {
+            match item.as_str() {
+                None => issue(key.to_string(), "must be a string".to_string()),
+                Some(raw) if !allowed.is_empty() && !allowed.contains(&raw) => issue(
+                    key.to_string(),
+                    format!("must be one of: {}", allowed.join(", ")),
+                ),
+                Some(_) => {}
+            }
+        } else if !OTHER_KNOWN_KEYS.contains(&key) && !key.starts_with("experimental_") {
+            validation.unknown_keys.push(key.to_string());
+        }
+    }
+    if let Some(features) = document.get("features") {
+        match features.as_table_like() {
+            None => issue("features".to_string(), "must be a table".to_string()),
+            Some(features) => {
+                for (key, item) in features.iter() {
+                    if item.as_bool().is_none() {
+                        issue(
+                            format!("features.{key}"),
+                            "must be true or false".to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    if let Some(servers) = document.get("mcp_servers") {
+        match servers.as_table_like() {
+            None => issue("mcp_servers".to_string(), "must be a table".to_string()),
+            Some(servers) => {
+                for (name, item) in servers.iter() {
+                    let Some(server) = item.as_table_like() else {
+                        issue(format!("mcp_servers.{name}"), "must be a table".to_string());
+                        continue;
+                    };
+                    let has = |key: &str| server.get(key).and_then(Item::as_str).is_some();
+                    if !has("command") && !has("url") {
+                        issue(
+                            format!("mcp_servers.{name}"),
+                            "needs a `command` or `url` string".to_string(),
+                        );
+                    }
+                    if server.get("args").is_some_and(|args| {
+                        !args
+                            .as_array()
+                            .is_some_and(|args| args.iter().all(|arg| arg.as_str().is_some()))
+                    }) {
+                        issue(
+                            format!("mcp_servers.{name}.args"),
+                            "must be an array of strings".to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    validation.valid = validation.issues.is_empty();
+    validation
+}
+
 fn resolve_default_codex_home() -> Option<PathBuf> {
     crate::codex::home::resolve_default_codex_home()
 }
@@ -114,8 +556,12 @@ fn normalize_personality_value(value: &str) -> Option<&'static str> {
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_personality_value, read_personality_from_document};
+    use super::{
+        normalize_personality_value, read_personality_from_document, read_settings_from_document,
+        set_mcp_server, set_string_setting, validate_config_contents, McpServerConfig,
+    };
     use crate::shared::config_toml_core;
+    use std::collections::BTreeMap;
 
     #[test]
     fn parse_personality_reads_supported_values() {
@@ -143,4 +589,59 @@ mod tests {
         assert_eq!(normalize_personality_value("PRAGMATIC"), Some("pragmatic"));
         assert_eq!(normalize_personality_value("unknown"), None);
     }
+
+    #[test]
+    fn edits_keep_comments_and_unmanaged_keys() {
+        let mut document = config_toml_core::parse_document(
+            "# my model\nmodel = \"gpt-5\"\n\n[mcp_servers.docs]\ncommand = \"docs-mcp\"\nstartup_timeout_sec = 20\n",
+        )
+        .expect("parse");
+        set_string_setting(&mut document, "approval_policy", Some("on-request")).expect("set");
+        assert!(set_string_setting(&mut document, "sandbox_mode", Some("yolo")).is_err());
+        assert!(set_string_setting(&mut document, "unknown", Some("x")).is_err());
+        let server = McpServerConfig {
+            command: Some("docs-mcp".to_string()),
+            args: vec!["--stdio".to_string()],
+            env: BTreeMap::from([("TOKEN".to_string(), "abc".to_string())]),
+            ..McpServerConfig::default()
+        };
+        set_mcp_server(&mut document, "docs", Some(&server)).expect("set server");
+
+        let rendered = document.to_string();
+        assert!(rendered.contains("# my model"));
+        assert!(rendered.contains("startup_timeout_sec = 20"));
+        let settings = read_settings_from_document(&document);
+        assert_eq!(settings.approval_policy.as_deref(), Some("on-request"));
+        assert_eq!(settings.mcp_servers.get("docs"), Some(&server));
+
+        set_mcp_server(&mut document, "docs", None).expect("remove server");
+        assert!(read_settings_from_document(&document)
+            .mcp_servers
+            .is_empty());
+    }
+
+    #[test]
+    fn validation_reports_parse_errors_unknown_keys_and_bad_values() {
+        let broken = validate_config_contents(None, true, "model = \"gpt-5\"\nsandbox_mode = \n");
+        let parse_error = broken.parse_error.expect("parse error");
+        assert_eq!(parse_error.line, Some(2));
+        assert!(!broken.valid);
+
+        let checked = validate_config_contents(
+            None,
+            true,
+            "approval_policy = \"sometimes\"\nmodle = \"typo\"\nexperimental_x = 1\n[features]\nsteer = \"yes\"\n[mcp_servers.empty]\n",
+        );
+        assert!(!checked.valid);
+        assert_eq!(checked.unknown_keys, ["modle"]);
+        let keys: Vec<_> = checked
+            .issues
+            .iter()
+            .map(|issue| issue.key.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            ["approval_policy", "features.steer", "mcp_servers.empty"]
+        );
+    }
 }
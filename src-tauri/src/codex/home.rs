@@ -19,7 +19,7 @@ pub(crate) fn resolve_default_codex_home() -> Option<PathBuf> {
     resolve_home_dir().map(|home| home.join(".codex"))
 }
 
-fn normalize_codex_home(value: &str) -> Option<PathBuf> {
+pub(crate) fn normalize_codex_home(value: &str) -> Option<PathBuf> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return None;
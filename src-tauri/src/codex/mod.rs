@@ -1,8 +1,9 @@
 use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Manager, State};
 
 pub(crate) mod args;
 pub(crate) mod config;
@@ -10,25 +11,51 @@ pub(crate) mod home;
 
 use crate::backend::app_server::spawn_workspace_session as spawn_workspace_session_inner;
 pub(crate) use crate::backend::app_server::WorkspaceSession;
-use crate::backend::events::AppServerEvent;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol_trace::{self, ProtocolTraceStatus, ProtocolTraceTail};
+use crate::backend::rate_limits::{self, RateLimitForecast};
+use crate::backend::run_manager::{self, RunQueueSnapshot};
+use crate::backend::session_provenance::{self, SessionProvenance};
+use crate::backend::thread_journal::{self, ThreadTail};
+use crate::backend::thread_preferences::{self, ThreadPreferences};
+use crate::backend::turn_tracker::{self, ActiveTurn};
 use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
+use crate::rules::ApprovalRule;
 use crate::shared::agents_config_core;
+use crate::shared::archive_undo_core::{self, PendingArchive};
 use crate::shared::codex_core::{self, insert_optional_nullable_string};
+use crate::shared::scheduled_messages_core::{self, ScheduledMessage};
+use crate::shared::slash_commands_core::{self, SlashCommandInfo};
+use crate::shared::thread_export_core::{self, ThreadExport};
+use crate::shared::thread_meta_core;
+use crate::shared::thread_order_core::{self, ThreadOrder};
+use crate::shared::thread_tags_core::{self, ThreadAnnotations, ThreadLink, ThreadLinkKind};
+use crate::shared::workspaces_core;
 use crate::state::AppState;
 use crate::types::WorkspaceEntry;
 
 fn emit_thread_live_event(app: &AppHandle, workspace_id: &str, method: &str, params: Value) {
-    let _ = app.emit(
-        "app-server-event",
-        AppServerEvent {
-            workspace_id: workspace_id.to_string(),
-            message: json!({
-                "method": method,
-                "params": params,
-            }),
-        },
-    );
+    TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": method,
+            "params": params,
+        }),
+    });
+}
+
+fn emit_background_thread_hide(app: &AppHandle, workspace_id: &str, thread_id: &str) {
+    TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "codex/backgroundThread",
+            "params": {
+                "threadId": thread_id,
+                "action": "hide"
+            }
+        }),
+    });
 }
 
 pub(crate) async fn spawn_workspace_session(
@@ -39,6 +66,7 @@ pub(crate) async fn spawn_workspace_session(
     codex_home: Option<PathBuf>,
 ) -> Result<Arc<WorkspaceSession>, String> {
     let client_version = app_handle.package_info().version.to_string();
+    let services = Arc::clone(&app_handle.state::<AppState>().services);
     let event_sink = TauriEventSink::new(app_handle);
     spawn_workspace_session_inner(
         entry,
@@ -46,6 +74,7 @@ pub(crate) async fn spawn_workspace_session(
         codex_args,
         codex_home,
         client_version,
+        services,
         event_sink,
     )
     .await
@@ -130,6 +159,37 @@ pub(crate) async fn read_thread(
     codex_core::read_thread_core(&state.sessions, workspace_id, thread_id).await
 }
 
+/// Exports a thread's transcript as `markdown` or `json`, written to `path`
+/// when given and returned inline otherwise.
+#[tauri::command]
+pub(crate) async fn export_thread(
+    workspace_id: String,
+    thread_id: String,
+    format: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadExport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "export_thread",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "format": format,
+                "path": path,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_export_core::export_thread_core(&state.sessions, workspace_id, thread_id, format, path)
+        .await
+}
+
 #[tauri::command]
 pub(crate) async fn thread_live_subscribe(
     workspace_id: String,
@@ -232,6 +292,7 @@ pub(crate) async fn list_threads(
     cursor: Option<String>,
     limit: Option<u32>,
     sort_key: Option<String>,
+    tag: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -244,13 +305,353 @@ pub(crate) async fn list_threads(
                 "workspaceId": workspace_id,
                 "cursor": cursor,
                 "limit": limit,
-                "sortKey": sort_key
+                "sortKey": sort_key,
+                "tag": tag
             }),
         )
         .await;
     }
 
-    codex_core::list_threads_core(&state.sessions, workspace_id, cursor, limit, sort_key).await
+    let response = codex_core::list_threads_core(
+        &state.sessions,
+        workspace_id.clone(),
+        cursor,
+        limit,
+        sort_key,
+    )
+    .await?;
+    let response = match tag {
+        Some(tag) => {
+            thread_tags_core::filter_thread_list_by_tag(&state.thread_tags, response, &tag)
+        }
+        None => response,
+    };
+    Ok(thread_order_core::apply_thread_order(
+        &state.thread_order,
+        &workspace_id,
+        response,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn tag_thread(
+    workspace_id: String,
+    thread_id: String,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadAnnotations, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tag_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "tags": tags }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_tags_core::tag_thread_core(&state.thread_tags, workspace_id, thread_id, tags)
+}
+
+#[tauri::command]
+pub(crate) async fn link_threads(
+    workspace_id: String,
+    thread_id: String,
+    target_workspace_id: Option<String>,
+    target_thread_id: String,
+    kind: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadAnnotations, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "link_threads",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "targetWorkspaceId": target_workspace_id,
+                "targetThreadId": target_thread_id,
+                "kind": kind
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let target = ThreadLink {
+        thread_id: target_thread_id,
+        workspace_id: target_workspace_id.unwrap_or_else(|| workspace_id.clone()),
+        kind: ThreadLinkKind::parse(&kind)?,
+    };
+    thread_tags_core::link_threads_core(&state.thread_tags, workspace_id, thread_id, target)
+}
+
+#[tauri::command]
+pub(crate) async fn unlink_threads(
+    thread_id: String,
+    target_thread_id: String,
+    kind: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "unlink_threads",
+            json!({ "threadId": thread_id, "targetThreadId": target_thread_id, "kind": kind }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let kind = kind.as_deref().map(ThreadLinkKind::parse).transpose()?;
+    thread_tags_core::unlink_threads_core(&state.thread_tags, thread_id, target_thread_id, kind)
+}
+
+/// Pins or unpins a thread at the top of its workspace's `list_threads`.
+#[tauri::command]
+pub(crate) async fn pin_thread(
+    workspace_id: String,
+    thread_id: String,
+    pinned: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadOrder, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "pin_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "pinned": pinned }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_order_core::pin_thread_core(&state.thread_order, workspace_id, thread_id, pinned)
+}
+
+#[tauri::command]
+pub(crate) async fn set_thread_order(
+    workspace_id: String,
+    thread_ids: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadOrder, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_thread_order",
+            json!({ "workspaceId": workspace_id, "threadIds": thread_ids }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_order_core::set_thread_order_core(&state.thread_order, workspace_id, thread_ids)
+}
+
+#[tauri::command]
+pub(crate) async fn set_thread_meta(
+    thread_id: String,
+    key: String,
+    value: Value,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BTreeMap<String, Value>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_thread_meta",
+            json!({ "threadId": thread_id, "key": key, "value": value }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_meta_core::set_thread_meta_core(&state.thread_meta, thread_id, key, value)
+}
+
+#[tauri::command]
+pub(crate) async fn get_thread_meta(
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BTreeMap<String, Value>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_meta",
+            json!({ "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(thread_meta_core::get_thread_meta_core(
+        &state.thread_meta,
+        &thread_id,
+    ))
+}
+
+/// The thread's pinned or last-used model and effort, and the last ones
+/// used in `workspace_id`.
+#[tauri::command]
+pub(crate) async fn get_thread_preferences(
+    thread_id: String,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadPreferences, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_preferences",
+            json!({ "threadId": thread_id, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_preferences::get_thread_preferences_core(
+        &state.services.thread_preferences,
+        &thread_id,
+        workspace_id.as_deref(),
+    )
+}
+
+/// Pins a model and effort to the thread; with neither, unpins it.
+#[tauri::command]
+pub(crate) async fn set_thread_preferences(
+    thread_id: String,
+    workspace_id: Option<String>,
+    model: Option<String>,
+    effort: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadPreferences, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_thread_preferences",
+            json!({
+                "threadId": thread_id,
+                "workspaceId": workspace_id,
+                "model": model,
+                "effort": effort,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_preferences::set_thread_preferences_core(
+        &state.services.thread_preferences,
+        &thread_id,
+        workspace_id.as_deref(),
+        model,
+        effort,
+    )
+}
+
+/// How the workspace's agent sessions were spawned, newest first.
+#[tauri::command]
+pub(crate) async fn session_provenance(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SessionProvenance>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "session_provenance",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    session_provenance::session_provenance_core(&state.services.session_provenance, &workspace_id)
+}
+
+/// Turns that are running now, oldest first; all workspaces when
+/// `workspace_id` is omitted.
+#[tauri::command]
+pub(crate) async fn list_active_turns(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ActiveTurn>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_active_turns",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(turn_tracker::list_active_turns_core(
+        &state.services.turn_tracker,
+        workspace_id.as_deref(),
+    ))
+}
+
+/// Approvals queued by a remote daemon while no client was connected. Local
+/// mode always has the app attached, so there is nothing to restore.
+#[tauri::command]
+pub(crate) async fn list_pending_approvals(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_approvals",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    Ok(json!([]))
+}
+
+#[tauri::command]
+pub(crate) async fn list_thread_annotations(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<HashMap<String, ThreadAnnotations>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_thread_annotations",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(thread_tags_core::list_thread_annotations_core(
+        &state.thread_tags,
+        &workspace_id,
+    ))
 }
 
 #[tauri::command]
@@ -291,7 +692,129 @@ pub(crate) async fn archive_thread(
         .await;
     }
 
-    codex_core::archive_thread_core(&state.sessions, workspace_id, thread_id).await
+    let response =
+        codex_core::archive_thread_core(&state.sessions, workspace_id.clone(), thread_id.clone())
+            .await?;
+    let _ = thread_meta_core::clear_thread_meta_core(&state.thread_meta, &thread_id);
+    let _ =
+        thread_order_core::clear_thread_order_core(&state.thread_order, &workspace_id, &thread_id);
+    Ok(response)
+}
+
+/// Archives the thread after `delay_seconds` unless `undo_archive` is called
+/// with the returned token first.
+#[tauri::command]
+pub(crate) async fn archive_thread_with_undo(
+    workspace_id: String,
+    thread_id: String,
+    delay_seconds: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PendingArchive, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "archive_thread_with_undo",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "delaySeconds": delay_seconds,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(archive_undo_core::archive_thread_with_undo_core(
+        &state.services.pending_archives,
+        &workspace_id,
+        &thread_id,
+        delay_seconds,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn undo_archive(
+    token: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PendingArchive, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "undo_archive", json!({ "token": token }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    archive_undo_core::undo_archive_core(&state.services.pending_archives, &token)
+}
+
+/// Mirrors the workspace's app-server stdin/stdout into a trace file until
+/// turned off again.
+#[tauri::command]
+pub(crate) async fn set_protocol_trace(
+    workspace_id: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ProtocolTraceStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_protocol_trace",
+            json!({ "workspaceId": workspace_id, "enabled": enabled }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    protocol_trace::set_protocol_trace_core(&state.services.protocol_traces, &workspace_id, enabled)
+}
+
+#[tauri::command]
+pub(crate) async fn protocol_trace_tail(
+    workspace_id: String,
+    lines: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ProtocolTraceTail, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "protocol_trace_tail",
+            json!({ "workspaceId": workspace_id, "lines": lines }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    protocol_trace::protocol_trace_tail_core(&state.services.protocol_traces, &workspace_id, lines)
+}
+
+/// Recent events of a thread from its on-disk journal, available before
+/// `thread/resume` answers.
+#[tauri::command]
+pub(crate) async fn get_thread_tail(
+    thread_id: String,
+    lines: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadTail, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_tail",
+            json!({ "threadId": thread_id, "lines": lines }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    thread_journal::get_thread_tail_core(&state.services.thread_journals, &thread_id, lines)
 }
 
 #[tauri::command]
@@ -311,7 +834,8 @@ pub(crate) async fn compact_thread(
         .await;
     }
 
-    codex_core::compact_thread_core(&state.sessions, workspace_id, thread_id).await
+    codex_core::compact_thread_core(&state.sessions, &state.workspaces, workspace_id, thread_id)
+        .await
 }
 
 #[tauri::command]
@@ -351,12 +875,9 @@ pub(crate) async fn send_user_message(
     app: AppHandle,
 ) -> Result<Value, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let images = images.map(|paths| {
-            paths
-                .into_iter()
-                .map(remote_backend::normalize_path_for_remote)
-                .collect::<Vec<_>>()
-        });
+        let images =
+            remote_backend::upload_images_for_remote(&*state, app.clone(), &workspace_id, images)
+                .await?;
         let mut payload = Map::new();
         payload.insert("workspaceId".to_string(), json!(workspace_id));
         payload.insert("threadId".to_string(), json!(thread_id));
@@ -399,89 +920,275 @@ pub(crate) async fn send_user_message(
 }
 
 #[tauri::command]
-pub(crate) async fn turn_steer(
+pub(crate) async fn turn_steer(
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    text: String,
+    images: Option<Vec<String>>,
+    app_mentions: Option<Vec<Value>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let images =
+            remote_backend::upload_images_for_remote(&*state, app.clone(), &workspace_id, images)
+                .await?;
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "turn_steer",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "text": text,
+                "images": images,
+                "appMentions": app_mentions,
+            }),
+        )
+        .await;
+    }
+
+    codex_core::turn_steer_core(
+        &state.sessions,
+        workspace_id,
+        thread_id,
+        turn_id,
+        text,
+        images,
+        app_mentions,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn collaboration_mode_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "collaboration_mode_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    codex_core::collaboration_mode_list_core(&state.sessions, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn turn_interrupt(
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "turn_interrupt",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "turnId": turn_id }),
+        )
+        .await;
+    }
+
+    codex_core::turn_interrupt_core(&state.sessions, workspace_id, thread_id, turn_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn list_slash_commands(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SlashCommandInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "list_slash_commands", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(slash_commands_core::list_slash_commands_core())
+}
+
+#[tauri::command]
+pub(crate) async fn queue_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RunQueueSnapshot, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "queue_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(run_manager::queue_list_core(&state.services.run_manager))
+}
+
+#[tauri::command]
+pub(crate) async fn queue_cancel(
+    queue_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(&*state, app, "queue_cancel", json!({ "queueId": queue_id }))
+            .await?;
+        return Ok(());
+    }
+
+    run_manager::queue_cancel_core(&state.services.run_manager, &queue_id)
+}
+
+#[tauri::command]
+pub(crate) async fn schedule_message(
     workspace_id: String,
     thread_id: String,
-    turn_id: String,
     text: String,
-    images: Option<Vec<String>>,
-    app_mentions: Option<Vec<Value>>,
+    send_at: u64,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> Result<ScheduledMessage, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let images = images.map(|paths| {
-            paths
-                .into_iter()
-                .map(remote_backend::normalize_path_for_remote)
-                .collect::<Vec<_>>()
-        });
-        return remote_backend::call_remote(
+        let response = remote_backend::call_remote(
             &*state,
             app,
-            "turn_steer",
+            "schedule_message",
             json!({
                 "workspaceId": workspace_id,
                 "threadId": thread_id,
-                "turnId": turn_id,
                 "text": text,
-                "images": images,
-                "appMentions": app_mentions,
+                "sendAt": send_at,
             }),
         )
-        .await;
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    codex_core::turn_steer_core(
-        &state.sessions,
+    scheduled_messages_core::schedule_message_core(
+        &state.scheduled_messages,
         workspace_id,
         thread_id,
-        turn_id,
         text,
-        images,
-        app_mentions,
+        send_at,
     )
-    .await
 }
 
 #[tauri::command]
-pub(crate) async fn collaboration_mode_list(
-    workspace_id: String,
+pub(crate) async fn list_scheduled_messages(
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> Result<Vec<ScheduledMessage>, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        return remote_backend::call_remote(
+        let response = remote_backend::call_remote(
             &*state,
             app,
-            "collaboration_mode_list",
+            "list_scheduled_messages",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    codex_core::collaboration_mode_list_core(&state.sessions, workspace_id).await
+    Ok(scheduled_messages_core::list_scheduled_messages_core(
+        &state.scheduled_messages,
+        workspace_id.as_deref(),
+    ))
 }
 
 #[tauri::command]
-pub(crate) async fn turn_interrupt(
-    workspace_id: String,
-    thread_id: String,
-    turn_id: String,
+pub(crate) async fn cancel_scheduled_message(
+    schedule_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> Result<(), String> {
     if remote_backend::is_remote_mode(&*state).await {
-        return remote_backend::call_remote(
+        remote_backend::call_remote(
             &*state,
             app,
-            "turn_interrupt",
-            json!({ "workspaceId": workspace_id, "threadId": thread_id, "turnId": turn_id }),
+            "cancel_scheduled_message",
+            json!({ "scheduleId": schedule_id }),
         )
-        .await;
+        .await?;
+        return Ok(());
     }
 
-    codex_core::turn_interrupt_core(&state.sessions, workspace_id, thread_id, turn_id).await
+    scheduled_messages_core::cancel_scheduled_message_core(&state.scheduled_messages, &schedule_id)
+}
+
+/// Sends archives queued by `archive_thread_with_undo` in local mode.
+pub(crate) async fn run_archive_scheduler(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let event_sink = TauriEventSink::new(app.clone());
+    archive_undo_core::run_archive_scheduler(
+        &state.services.pending_archives,
+        event_sink,
+        |workspace_id, thread_id| {
+            let app = app.clone();
+            async move {
+                let state = app.state::<AppState>();
+                let response = codex_core::archive_thread_core(
+                    &state.sessions,
+                    workspace_id.clone(),
+                    thread_id.clone(),
+                )
+                .await?;
+                let _ = thread_meta_core::clear_thread_meta_core(&state.thread_meta, &thread_id);
+                let _ = thread_order_core::clear_thread_order_core(
+                    &state.thread_order,
+                    &workspace_id,
+                    &thread_id,
+                );
+                Ok(response)
+            }
+        },
+    )
+    .await;
+}
+
+/// Delivers messages scheduled in local mode. In remote mode the daemon owns
+/// the schedule, so local workspaces are not connected and due messages wait.
+pub(crate) async fn run_message_scheduler(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let event_sink = TauriEventSink::new(app.clone());
+    scheduled_messages_core::run_message_scheduler(
+        &state.scheduled_messages,
+        &state.sessions,
+        &state.workspaces,
+        event_sink,
+        |workspace_id| {
+            let app = app.clone();
+            async move {
+                let state = app.state::<AppState>();
+                if remote_backend::is_remote_mode(&*state).await {
+                    return Err("Waiting for local mode to send".to_string());
+                }
+                workspaces_core::connect_workspace_core(
+                    workspace_id,
+                    &state.workspaces,
+                    &state.sessions,
+                    &state.services,
+                    &state.app_settings,
+                    |entry, default_bin, codex_args, codex_home| {
+                        spawn_workspace_session(
+                            entry,
+                            default_bin,
+                            codex_args,
+                            app.clone(),
+                            codex_home,
+                        )
+                    },
+                )
+                .await
+            }
+        },
+    )
+    .await;
 }
 
 #[tauri::command]
@@ -510,6 +1217,7 @@ pub(crate) async fn start_review(
 
     codex_core::start_review_core(
         &state.sessions,
+        &state.workspaces,
         workspace_id,
         thread_id,
         target,
@@ -586,6 +1294,81 @@ pub(crate) async fn set_codex_feature_flag(
     config::write_feature_enabled(feature_key.as_str(), enabled)
 }
 
+/// The settings the config editor shows, read from the host's
+/// `config.toml`.
+#[tauri::command]
+pub(crate) async fn get_codex_config(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::CodexConfigSettings, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "get_codex_config", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::read_codex_config()
+}
+
+/// Sets a top-level string setting such as `model` or `approval_policy`;
+/// `null` removes it. Comments and other keys are kept.
+#[tauri::command]
+pub(crate) async fn set_codex_config_value(
+    key: String,
+    value: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "set_codex_config_value",
+            json!({ "key": key, "value": value }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    config::write_config_string(&key, value.as_deref())
+}
+
+/// Adds or updates `[mcp_servers.<name>]`; `null` removes it.
+#[tauri::command]
+pub(crate) async fn set_codex_mcp_server(
+    name: String,
+    server: Option<config::McpServerConfig>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "set_codex_mcp_server",
+            json!({ "name": name, "server": server }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    config::write_mcp_server(&name, server.as_ref())
+}
+
+#[tauri::command]
+pub(crate) async fn validate_codex_config(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::CodexConfigValidation, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "validate_codex_config", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::validate_codex_config()
+}
+
 #[tauri::command]
 pub(crate) async fn get_agents_settings(
     state: State<'_, AppState>,
@@ -731,6 +1514,23 @@ pub(crate) async fn account_rate_limits(
     codex_core::account_rate_limits_core(&state.sessions, workspace_id).await
 }
 
+/// Remaining capacity per rate-limit window, with a burn-rate projection.
+#[tauri::command]
+pub(crate) async fn rate_limit_forecast(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RateLimitForecast, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "rate_limit_forecast", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(rate_limits::rate_limit_forecast_core(
+        &state.services.run_manager,
+    ))
+}
+
 #[tauri::command]
 pub(crate) async fn account_read(
     workspace_id: String,
@@ -769,6 +1569,87 @@ pub(crate) async fn codex_login(
     codex_core::codex_login_core(&state.sessions, &state.codex_login_cancels, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn codex_login_api_key(
+    workspace_id: String,
+    api_key: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_login_api_key",
+            json!({ "workspaceId": workspace_id, "apiKey": api_key }),
+        )
+        .await;
+    }
+
+    codex_core::codex_login_api_key_core(
+        &state.services,
+        &state.sessions,
+        &state.workspaces,
+        workspace_id,
+        api_key,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn codex_logout(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_logout",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    codex_core::codex_logout_core(
+        &state.services,
+        &state.sessions,
+        &state.workspaces,
+        &state.codex_login_cancels,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn account_switch(
+    workspace_id: String,
+    api_key: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "account_switch",
+            json!({ "workspaceId": workspace_id, "apiKey": api_key }),
+        )
+        .await;
+    }
+
+    codex_core::account_switch_core(
+        &state.services,
+        &state.sessions,
+        &state.workspaces,
+        &state.codex_login_cancels,
+        workspace_id,
+        api_key,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn codex_login_cancel(
     workspace_id: String,
@@ -867,6 +1748,95 @@ pub(crate) async fn remember_approval_rule(
     codex_core::remember_approval_rule_core(&state.workspaces, workspace_id, command).await
 }
 
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn add_approval_rule(
+    workspace_id: String,
+    kind: String,
+    pattern: String,
+    decision: String,
+    scoped: Option<bool>,
+    expires_at: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ApprovalRule, String> {
+    let scoped = scoped.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "add_approval_rule",
+            json!({
+                "workspaceId": workspace_id,
+                "kind": kind,
+                "pattern": pattern,
+                "decision": decision,
+                "scoped": scoped,
+                "expiresAt": expires_at,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::add_approval_rule_core(
+        &state.services.approval_policy,
+        &state.workspaces,
+        workspace_id,
+        kind,
+        pattern,
+        decision,
+        scoped,
+        expires_at,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn list_approval_rules(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ApprovalRule>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_approval_rules",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::list_approval_rules_core(
+        &state.services.approval_policy,
+        &state.workspaces,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn delete_approval_rule(
+    rule_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_approval_rule",
+            json!({ "ruleId": rule_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    codex_core::delete_approval_rule_core(&state.services.approval_policy, &rule_id)
+}
+
 #[tauri::command]
 pub(crate) async fn get_config_model(
     workspace_id: String,
@@ -921,21 +1891,7 @@ pub(crate) async fn generate_commit_message(
         &diff,
         &commit_message_prompt,
         commit_message_model_id.as_deref(),
-        |workspace_id, thread_id| {
-            let _ = app.emit(
-                "app-server-event",
-                AppServerEvent {
-                    workspace_id: workspace_id.to_string(),
-                    message: json!({
-                        "method": "codex/backgroundThread",
-                        "params": {
-                            "threadId": thread_id,
-                            "action": "hide"
-                        }
-                    }),
-                },
-            );
-        },
+        |workspace_id, thread_id| emit_background_thread_hide(&app, workspace_id, thread_id),
     )
     .await
 }
@@ -962,21 +1918,7 @@ pub(crate) async fn generate_run_metadata(
         &state.workspaces,
         workspace_id,
         &prompt,
-        |workspace_id, thread_id| {
-            let _ = app.emit(
-                "app-server-event",
-                AppServerEvent {
-                    workspace_id: workspace_id.to_string(),
-                    message: json!({
-                        "method": "codex/backgroundThread",
-                        "params": {
-                            "threadId": thread_id,
-                            "action": "hide"
-                        }
-                    }),
-                },
-            );
-        },
+        |workspace_id, thread_id| emit_background_thread_hide(&app, workspace_id, thread_id),
     )
     .await
 }
@@ -1004,21 +1946,7 @@ pub(crate) async fn generate_agent_description(
         &state.workspaces,
         workspace_id,
         &description,
-        |workspace_id, thread_id| {
-            let _ = app.emit(
-                "app-server-event",
-                AppServerEvent {
-                    workspace_id: workspace_id.to_string(),
-                    message: json!({
-                        "method": "codex/backgroundThread",
-                        "params": {
-                            "threadId": thread_id,
-                            "action": "hide"
-                        }
-                    }),
-                },
-            );
-        },
+        |workspace_id, thread_id| emit_background_thread_hide(&app, workspace_id, thread_id),
     )
     .await
 }
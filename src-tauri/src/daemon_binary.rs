@@ -8,7 +8,7 @@ pub(crate) fn daemon_binary_candidates() -> &'static [&'static str] {
     }
 }
 
-fn daemon_search_dirs(executable_dir: &std::path::Path) -> Vec<PathBuf> {
+pub(crate) fn daemon_search_dirs(executable_dir: &std::path::Path) -> Vec<PathBuf> {
     let mut dirs = Vec::new();
 
     let mut push_unique = |path: PathBuf| {
@@ -482,11 +482,13 @@ pub(crate) async fn dictation_download_model(
                 return;
             }
         };
-        let client = match reqwest::Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30 * 60))
-            .build()
-        {
+        let client = match state.services.http_client_builder().and_then(|builder| {
+            builder
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(Duration::from_secs(30 * 60))
+                .build()
+                .map_err(|err| err.to_string())
+        }) {
             Ok(client) => client,
             Err(error) => {
                 let status = DictationModelStatus {
@@ -0,0 +1,12 @@
+use crate::shared::discovery_core::{self, DiscoveredDaemon};
+
+/// Browses the local network for daemons advertising `_codexmonitor._tcp`.
+/// Always runs on this machine, even in remote mode.
+#[tauri::command]
+pub(crate) async fn discover_daemons(
+    timeout_ms: Option<u64>,
+) -> Result<Vec<DiscoveredDaemon>, String> {
+    tokio::task::spawn_blocking(move || discovery_core::discover_daemons_core(timeout_ms))
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -1,6 +1,8 @@
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
+use crate::backend::event_dedup::Admission;
 use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::state::AppState;
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -15,10 +17,31 @@ impl TauriEventSink {
 
 impl EventSink for TauriEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.app.emit("app-server-event", event);
+        // Sessions are spawned through `AppState`, so there is nothing to
+        // deliver before it is managed.
+        let Some(state) = self.app.try_state::<AppState>() else {
+            return;
+        };
+        let revision = match state.services.event_dedup.admit(&event) {
+            Admission::Deliver(revision) => revision,
+            Admission::Duplicate => return,
+        };
+        state.change_attributions.record(&event);
+        state.search_index.record(&event);
+        state.workspace_summaries.record(&event);
+        crate::notifications::notify_app_server_event(&self.app, &event);
+        state
+            .services
+            .event_sequence
+            .stamp_and_deliver(event, revision, |stamped| {
+                let _ = self.app.emit("app-server-event", stamped);
+            });
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
+        if let Some(state) = self.app.try_state::<AppState>() {
+            state.services.terminal_history.record(&event);
+        }
         let _ = self.app.emit("terminal-output", event);
     }
 
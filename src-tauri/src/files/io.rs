@@ -1,7 +1,8 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -136,6 +137,122 @@ pub(crate) fn write_text_file_within(
         .map_err(|err| format!("Failed to write {file_context}: {err}"))
 }
 
+/// Bytes returned by `read_workspace_file` when no `length` is given.
+const DEFAULT_RANGE_BYTES: u64 = 400_000;
+const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+/// A NUL byte in this many leading bytes marks a file as binary.
+const BINARY_SNIFF_BYTES: u64 = 8 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FileEncoding {
+    #[default]
+    Utf8,
+    Base64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileResponse {
+    pub(crate) content: String,
+    /// More of the file follows this range.
+    pub(crate) truncated: bool,
+    /// Hash of the whole file, for `write_workspace_file`'s `expectedHash`.
+    /// Only reads from offset 0 carry it.
+    #[serde(default)]
+    pub(crate) hash: Option<String>,
+    #[serde(default)]
+    pub(crate) encoding: FileEncoding,
+    #[serde(default)]
+    pub(crate) offset: u64,
+    #[serde(default)]
+    pub(crate) total_bytes: u64,
+    /// Where the next range starts; `None` once the end is reached.
+    #[serde(default)]
+    pub(crate) next_offset: Option<u64>,
+}
+
+/// Reads `length` bytes of a file inside `root` starting at `offset`. Text
+/// ranges end on a character boundary; files that look binary, or ranges
+/// that are not valid UTF-8, come back base64-encoded.
+pub(crate) fn read_file_range_within(
+    root: &Path,
+    relative_path: &str,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<WorkspaceFileResponse, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let candidate = canonical_root.join(relative_path);
+    let canonical_path = candidate
+        .canonicalize()
+        .map_err(|err| format!("Failed to open file: {err}"))?;
+    if !canonical_path.starts_with(&canonical_root) {
+        return Err("Invalid file path".to_string());
+    }
+    let metadata = std::fs::metadata(&canonical_path)
+        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
+    if !metadata.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    let total_bytes = metadata.len();
+    let offset = offset.min(total_bytes);
+    let length = length
+        .unwrap_or(DEFAULT_RANGE_BYTES)
+        .clamp(1, MAX_RANGE_BYTES);
+
+    let mut file =
+        File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let mut head = Vec::new();
+    (&mut file)
+        .take(BINARY_SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    let binary = head.contains(&0);
+
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    let mut buffer = Vec::new();
+    file.take(length)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+
+    let mut end = offset + buffer.len() as u64;
+    let (content, encoding) = if binary {
+        (BASE64.encode(&buffer), FileEncoding::Base64)
+    } else {
+        match std::str::from_utf8(&buffer) {
+            Ok(text) => (text.to_string(), FileEncoding::Utf8),
+            // A character split by the end of the range; the next range
+            // starts at it.
+            Err(err) if err.error_len().is_none() && err.valid_up_to() > 0 => {
+                buffer.truncate(err.valid_up_to());
+                end = offset + buffer.len() as u64;
+                (
+                    String::from_utf8(buffer).unwrap_or_default(),
+                    FileEncoding::Utf8,
+                )
+            }
+            Err(_) => (BASE64.encode(&buffer), FileEncoding::Base64),
+        }
+    };
+    let hash = if offset == 0 {
+        crate::shared::file_edit_core::file_hash(&canonical_path).ok()
+    } else {
+        None
+    };
+    Ok(WorkspaceFileResponse {
+        content,
+        truncated: end < total_bytes,
+        hash,
+        encoding,
+        offset,
+        total_bytes,
+        next_offset: (end < total_bytes).then_some(end),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,4 +431,32 @@ mod tests {
         .expect_err("should reject symlink escape");
         assert!(error.contains("Invalid config.toml path"));
     }
+
+    #[test]
+    fn ranges_walk_a_file_on_character_boundaries() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("notes.txt"), "abcé").expect("write text");
+
+        let first = read_file_range_within(&root, "notes.txt", 0, Some(4)).expect("read");
+        assert_eq!(first.content, "abc");
+        assert_eq!(first.encoding, FileEncoding::Utf8);
+        assert_eq!(first.total_bytes, 5);
+        assert_eq!(first.next_offset, Some(3));
+        assert!(first.truncated);
+        assert!(first.hash.is_some());
+
+        let rest = read_file_range_within(&root, "notes.txt", 3, None).expect("read");
+        assert_eq!(rest.content, "é");
+        assert_eq!(rest.next_offset, None);
+        assert!(rest.hash.is_none());
+
+        std::fs::write(root.join("image.bin"), [0u8, 159, 146, 150]).expect("write binary");
+        let binary = read_file_range_within(&root, "image.bin", 0, None).expect("read");
+        assert_eq!(binary.encoding, FileEncoding::Base64);
+        assert_eq!(binary.content, BASE64.encode([0u8, 159, 146, 150]));
+
+        assert!(read_file_range_within(&root, "../escape", 0, None).is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
@@ -7,6 +7,7 @@ use self::policy::{FileKind, FileScope};
 use crate::remote_backend;
 use crate::shared::codex_core;
 use crate::shared::files_core::{file_read_core, file_write_core};
+use crate::shared::image_prepare_core::{self, PreparedImage};
 use crate::state::AppState;
 
 pub(crate) mod io;
@@ -110,6 +111,25 @@ pub(crate) async fn read_image_as_data_url(
     codex_core::read_image_as_data_url_core(&normalized)
 }
 
+/// Turns a pasted `data:` URL or an image path into the payload to send,
+/// transcoding HEIC/AVIF and downscaling huge screenshots, with a thumbnail
+/// for the composer. Runs locally in remote mode too, since the image lives
+/// on this machine.
+#[tauri::command]
+pub(crate) async fn prepare_image_for_send(source: String) -> Result<PreparedImage, String> {
+    let source = if source.trim_start().starts_with("data:") {
+        source
+    } else {
+        codex_core::normalize_file_path(&source)
+    };
+    if source.trim().is_empty() {
+        return Err("Image path is required".to_string());
+    }
+    tokio::task::spawn_blocking(move || image_prepare_core::prepare_image_for_send_core(&source))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
 #[tauri::command]
 pub(crate) fn write_text_file(path: String, content: String) -> Result<(), String> {
     let target = PathBuf::from(path.trim());
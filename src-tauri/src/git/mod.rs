@@ -3,12 +3,14 @@ use serde::Serialize;
 use serde_json::Value;
 use tauri::{AppHandle, State};
 
+use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
+use crate::shared::git_status_core::{self, WorkspaceGitStatus};
 use crate::shared::{git_rpc, git_ui_core};
 use crate::state::AppState;
 use crate::types::{
-    GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse,
+    GitAuthDoctorReport, GitCommitDiff, GitConflictsResponse, GitFileDiff, GitHubIssuesResponse,
+    GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse,
 };
 
 fn git_remote_params<T: Serialize>(request: &T) -> Result<Value, String> {
@@ -96,6 +98,29 @@ pub(crate) async fn get_git_status(
     git_ui_core::get_git_status_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn git_status_all(
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceGitStatus>, String> {
+    let request = git_rpc::GitStatusAllRequest { refresh };
+    try_remote_typed!(
+        state,
+        app,
+        git_rpc::METHOD_GIT_STATUS_ALL,
+        git_remote_params(&request)?,
+        Vec<WorkspaceGitStatus>
+    );
+    Ok(git_status_core::git_status_all_core(
+        &state.services,
+        &state.workspaces,
+        refresh.unwrap_or(false),
+        TauriEventSink::new(app),
+    )
+    .await)
+}
+
 #[tauri::command]
 pub(crate) async fn init_git_repo(
     workspace_id: String,
@@ -452,6 +477,25 @@ pub(crate) async fn get_git_remote(
     git_ui_core::get_git_remote_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn git_auth_doctor(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitAuthDoctorReport, String> {
+    let request = git_rpc::WorkspaceIdRequest {
+        workspace_id: workspace_id.clone(),
+    };
+    try_remote_typed!(
+        state,
+        app,
+        git_rpc::METHOD_GIT_AUTH_DOCTOR,
+        git_remote_params(&request)?,
+        GitAuthDoctorReport
+    );
+    git_ui_core::git_auth_doctor_core(&state.workspaces, workspace_id).await
+}
+
 #[tauri::command]
 pub(crate) async fn get_github_issues(
     workspace_id: String,
@@ -610,3 +654,130 @@ pub(crate) async fn create_git_branch(
     );
     git_ui_core::create_git_branch_core(&state.workspaces, workspace_id, name).await
 }
+
+#[tauri::command]
+pub(crate) async fn delete_git_branch(
+    workspace_id: String,
+    name: String,
+    force: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let request = git_rpc::DeleteGitBranchRequest {
+        workspace_id: workspace_id.clone(),
+        name: name.clone(),
+        force,
+    };
+    try_remote_unit!(
+        state,
+        app,
+        git_rpc::METHOD_DELETE_GIT_BRANCH,
+        git_remote_params(&request)?
+    );
+    git_ui_core::delete_git_branch_core(
+        &state.workspaces,
+        workspace_id,
+        name,
+        force.unwrap_or(false),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn save_git_stash(
+    workspace_id: String,
+    message: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let request = git_rpc::SaveGitStashRequest {
+        workspace_id: workspace_id.clone(),
+        message: message.clone(),
+    };
+    try_remote_unit!(
+        state,
+        app,
+        git_rpc::METHOD_SAVE_GIT_STASH,
+        git_remote_params(&request)?
+    );
+    git_ui_core::save_git_stash_core(&state.workspaces, workspace_id, message).await
+}
+
+#[tauri::command]
+pub(crate) async fn list_git_stashes(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let request = git_rpc::WorkspaceIdRequest {
+        workspace_id: workspace_id.clone(),
+    };
+    try_remote_value!(
+        state,
+        app,
+        git_rpc::METHOD_LIST_GIT_STASHES,
+        git_remote_params(&request)?
+    );
+    git_ui_core::list_git_stashes_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn pop_git_stash(
+    workspace_id: String,
+    index: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let request = git_rpc::PopGitStashRequest {
+        workspace_id: workspace_id.clone(),
+        index: optional_usize_to_u32(index),
+    };
+    try_remote_unit!(
+        state,
+        app,
+        git_rpc::METHOD_POP_GIT_STASH,
+        git_remote_params(&request)?
+    );
+    git_ui_core::pop_git_stash_core(&state.workspaces, workspace_id, index).await
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_conflicts(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitConflictsResponse, String> {
+    let request = git_rpc::WorkspaceIdRequest {
+        workspace_id: workspace_id.clone(),
+    };
+    try_remote_typed!(
+        state,
+        app,
+        git_rpc::METHOD_GET_GIT_CONFLICTS,
+        git_remote_params(&request)?,
+        GitConflictsResponse
+    );
+    git_ui_core::get_git_conflicts_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn resolve_git_conflict(
+    workspace_id: String,
+    path: String,
+    content: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let request = git_rpc::ResolveGitConflictRequest {
+        workspace_id: workspace_id.clone(),
+        path: path.clone(),
+        content: content.clone(),
+    };
+    try_remote_unit!(
+        state,
+        app,
+        git_rpc::METHOD_RESOLVE_GIT_CONFLICT,
+        git_remote_params(&request)?
+    );
+    git_ui_core::resolve_git_conflict_core(&state.workspaces, workspace_id, path, content).await
+}
@@ -7,9 +7,14 @@ use tauri::RunEvent;
 use tauri::WindowEvent;
 
 mod backend;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench_support;
+mod capabilities;
 mod codex;
 mod daemon_binary;
 mod dictation;
+mod discovery;
 mod event_sink;
 mod files;
 mod git;
@@ -21,12 +26,16 @@ mod menu;
 #[path = "menu_mobile.rs"]
 mod menu;
 mod notifications;
+mod playbooks;
 mod prompts;
 mod remote_backend;
 mod rules;
+mod search;
+mod secrets;
 mod settings;
 mod shared;
 mod state;
+mod state_snapshot;
 mod storage;
 mod tailscale;
 #[cfg(desktop)]
@@ -116,6 +125,21 @@ pub fn run() {
         .setup(|app| {
             let state = state::AppState::load(&app.handle());
             app.manage(state);
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<state::AppState>();
+                    shared::storage_core::run_storage_budget(state.data_dir(), &state.app_settings)
+                        .await;
+                });
+            }
+            tauri::async_runtime::spawn(codex::run_message_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(codex::run_archive_scheduler(app.handle().clone()));
+            tauri::async_runtime::spawn(remote_backend::supervisor::run_supervisor(
+                app.handle().clone(),
+            ));
+            #[cfg(desktop)]
+            tauri::async_runtime::spawn(tailscale::watch::run_status_watch(app.handle().clone()));
             #[cfg(target_os = "macos")]
             {
                 let tray_state = app.state::<tray::TrayState>();
@@ -182,10 +206,24 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            settings::switch_backend_mode,
             settings::get_codex_config_path,
+            settings::server_time,
+            settings::test_network_connectivity,
+            capabilities::get_capabilities,
+            capabilities::host_stats,
+            discovery::discover_daemons,
+            remote_backend::profiles::remote_profile_list,
+            remote_backend::profiles::remote_profile_add,
+            remote_backend::profiles::remote_profile_remove,
+            remote_backend::profiles::remote_profile_activate,
+            remote_backend::supervisor::remote_backend_status,
+            settings::storage_usage_report,
+            settings::storage_cleanup,
             files::file_read,
             files::file_write,
             files::read_image_as_data_url,
+            files::prepare_image_for_send,
             files::write_text_file,
             codex::get_config_model,
             menu::menu_set_accelerators,
@@ -199,38 +237,78 @@ pub fn run() {
             workspaces::add_workspace_from_git_url,
             workspaces::add_clone,
             workspaces::add_worktree,
+            workspaces::list_pending_operations,
+            workspaces::retry_operation,
+            workspaces::watch_workspace_files,
+            workspaces::unwatch_workspace_files,
+            workspaces::suggest_branch_name,
             workspaces::worktree_setup_status,
             workspaces::worktree_setup_mark_ran,
             workspaces::remove_workspace,
             workspaces::remove_worktree,
+            workspaces::unlock_worktree,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
             workspaces::apply_worktree_changes,
+            workspaces::compare_worktrees,
             workspaces::update_workspace_settings,
+            workspaces::update_workspaces_bulk,
             workspaces::set_workspace_runtime_codex_args,
             codex::start_thread,
             codex::send_user_message,
             codex::turn_steer,
             codex::turn_interrupt,
+            codex::list_slash_commands,
+            codex::queue_list,
+            codex::queue_cancel,
+            codex::schedule_message,
+            codex::list_scheduled_messages,
+            codex::cancel_scheduled_message,
             codex::start_review,
             codex::respond_to_server_request,
             codex::remember_approval_rule,
+            codex::add_approval_rule,
+            codex::list_approval_rules,
+            codex::delete_approval_rule,
             codex::generate_commit_message,
             codex::generate_run_metadata,
             codex::generate_agent_description,
             codex::resume_thread,
             codex::read_thread,
+            codex::export_thread,
+            search::search_threads,
             codex::thread_live_subscribe,
             codex::thread_live_unsubscribe,
             codex::fork_thread,
             codex::list_threads,
+            codex::tag_thread,
+            codex::link_threads,
+            codex::unlink_threads,
+            codex::pin_thread,
+            codex::set_thread_order,
+            codex::list_thread_annotations,
+            codex::list_pending_approvals,
+            codex::set_thread_meta,
+            codex::get_thread_meta,
+            codex::get_thread_preferences,
+            codex::set_thread_preferences,
+            codex::session_provenance,
+            codex::list_active_turns,
             codex::list_mcp_server_status,
             codex::archive_thread,
+            codex::archive_thread_with_undo,
+            codex::undo_archive,
+            codex::set_protocol_trace,
+            codex::protocol_trace_tail,
+            codex::get_thread_tail,
             codex::compact_thread,
             codex::set_thread_name,
             codex::collaboration_mode_list,
             workspaces::connect_workspace,
+            workspaces::broadcast_run,
+            workspaces::select_winner,
             git::get_git_status,
+            git::git_status_all,
             git::init_git_repo,
             git::create_github_repo,
             git::list_git_roots,
@@ -238,6 +316,7 @@ pub fn run() {
             git::get_git_log,
             git::get_git_commit_diff,
             git::get_git_remote,
+            git::git_auth_doctor,
             git::stage_git_file,
             git::stage_git_all,
             git::unstage_git_file,
@@ -254,15 +333,41 @@ pub fn run() {
             git::get_github_pull_request_comments,
             git::checkout_github_pull_request,
             workspaces::list_workspace_files,
+            workspaces::list_workspace_files_page,
+            workspaces::list_workspace_dir,
+            workspaces::attach_context,
+            workspaces::record_workspace_focus,
+            workspaces::recent_workspaces,
+            workspaces::search_workspace_files,
             workspaces::read_workspace_file,
+            workspaces::write_workspace_file,
+            workspaces::apply_workspace_patch,
+            workspaces::upload_file,
+            workspaces::download_file,
+            workspaces::package_release,
+            workspaces::run_and_attach,
+            workspaces::workspace_summary,
+            workspaces::dashboard_snapshot,
             workspaces::open_workspace_in,
             workspaces::get_open_app_icon,
+            workspaces::workspace_quota_status,
+            workspaces::resume_workspace_quota,
             git::list_git_branches,
             git::checkout_git_branch,
             git::create_git_branch,
+            git::delete_git_branch,
+            git::save_git_stash,
+            git::list_git_stashes,
+            git::pop_git_stash,
+            git::get_git_conflicts,
+            git::resolve_git_conflict,
             codex::model_list,
             codex::experimental_feature_list,
             codex::set_codex_feature_flag,
+            codex::get_codex_config,
+            codex::set_codex_config_value,
+            codex::set_codex_mcp_server,
+            codex::validate_codex_config,
             codex::get_agents_settings,
             codex::set_agents_core_settings,
             codex::create_agent,
@@ -271,9 +376,13 @@ pub fn run() {
             codex::read_agent_config_toml,
             codex::write_agent_config_toml,
             codex::account_rate_limits,
+            codex::rate_limit_forecast,
             codex::account_read,
             codex::codex_login,
+            codex::codex_login_api_key,
             codex::codex_login_cancel,
+            codex::codex_logout,
+            codex::account_switch,
             codex::skills_list,
             codex::apps_list,
             prompts::prompts_list,
@@ -283,10 +392,21 @@ pub fn run() {
             prompts::prompts_move,
             prompts::prompts_workspace_dir,
             prompts::prompts_global_dir,
+            state_snapshot::update_state_snapshot,
+            state_snapshot::restore_state_snapshot,
+            prompts::prompt_list,
+            prompts::prompt_save,
+            prompts::prompt_delete,
+            prompts::prompt_render,
+            playbooks::playbook_list,
+            playbooks::playbook_save,
+            playbooks::playbook_delete,
+            playbooks::run_playbook,
             terminal::terminal_open,
             terminal::terminal_write,
             terminal::terminal_resize,
             terminal::terminal_close,
+            terminal::terminal_output_page,
             dictation::dictation_model_status,
             dictation::dictation_download_model,
             dictation::dictation_cancel_download,
@@ -296,14 +416,29 @@ pub fn run() {
             dictation::dictation_stop,
             dictation::dictation_cancel,
             local_usage::local_usage_snapshot,
+            local_usage::usage_summary,
             notifications::is_macos_debug_build,
             notifications::app_build_type,
             notifications::send_notification_fallback,
+            notifications::list_notifications,
+            notifications::mark_notifications_read,
+            notifications::push_register_device,
+            notifications::push_unregister_device,
+            notifications::push_list_devices,
             tailscale::tailscale_status,
+            tailscale::tailscale_list_peers,
             tailscale::tailscale_daemon_command_preview,
             tailscale::tailscale_daemon_start,
             tailscale::tailscale_daemon_stop,
             tailscale::tailscale_daemon_status,
+            tailscale::tailscale_daemon_logs,
+            tailscale::tailscale_daemon_version,
+            tailscale::tailscale_daemon_upgrade,
+            tailscale::tailscale_serve_enable,
+            tailscale::tailscale_serve_disable,
+            tailscale::daemon_install_autostart,
+            tailscale::daemon_uninstall_autostart,
+            tailscale::daemon_autostart_status,
             is_mobile_runtime
         ])
         .build(tauri::generate_context!())
@@ -312,9 +447,11 @@ pub fn run() {
     app.run(|app_handle, event| {
         #[cfg(desktop)]
         if let RunEvent::ExitRequested { api, .. } = event {
-            if !EXIT_CLEANUP_IN_PROGRESS.load(Ordering::SeqCst)
-                && !keep_daemon_running_after_close(app_handle)
-            {
+            if EXIT_CLEANUP_IN_PROGRESS.load(Ordering::SeqCst) {
+                return;
+            }
+            state_snapshot::hibernate_for_exit(app_handle);
+            if !keep_daemon_running_after_close(app_handle) {
                 api.prevent_exit();
                 EXIT_CLEANUP_IN_PROGRESS.store(true, Ordering::SeqCst);
                 let app_handle = app_handle.clone();
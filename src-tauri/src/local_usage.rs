@@ -1,6 +1,7 @@
 use serde_json::json;
 use tauri::{AppHandle, State};
 
+use crate::backend::usage_metrics::{self, UsageSummary};
 use crate::remote_backend;
 use crate::shared::local_usage_core;
 use crate::state::AppState;
@@ -26,3 +27,26 @@ pub(crate) async fn local_usage_snapshot(
 
     local_usage_core::local_usage_snapshot_core(&state.workspaces, days, workspace_path).await
 }
+
+/// Turn counts, time and tokens since `since` (ms since the epoch), grouped
+/// by `workspace` (default), `model` or `day`.
+#[tauri::command]
+pub(crate) async fn usage_summary(
+    since: Option<u64>,
+    group_by: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<UsageSummary, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "usage_summary",
+            json!({ "since": since, "groupBy": group_by }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    usage_metrics::usage_summary_core(&state.services.usage_metrics, since, group_by)
+}
@@ -1,6 +1,19 @@
 #[cfg(all(target_os = "macos", debug_assertions))]
 use std::process::Command;
 
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::shared::notifications_core::{self, NotificationEntry};
+use crate::state::AppState;
+use crate::types::{PushDevice, PushPlatform};
+
+const PUSH_REMOTE_ONLY: &str = "Push notifications are sent by a remote backend daemon.";
+
 #[tauri::command]
 pub(crate) async fn is_macos_debug_build() -> bool {
     cfg!(all(target_os = "macos", debug_assertions))
@@ -54,3 +67,165 @@ pub(crate) async fn send_notification_fallback(title: String, body: String) -> R
         Err("Notification fallback is only available on macOS debug builds.".to_string())
     }
 }
+
+/// Records turn completions, failures, approval requests and merge-queue
+/// results in the notification center, and shows a system notification for
+/// opted-in workspaces while the main window is unfocused.
+pub(crate) fn notify_app_server_event(app: &AppHandle, event: &AppServerEvent) {
+    if !notifications_core::may_notify(event) {
+        return;
+    }
+    let app = app.clone();
+    let event = event.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let Some(notification) =
+            notifications_core::describe_event(&state.workspaces, &event).await
+        else {
+            return;
+        };
+        let entry = state.notification_center.record(&notification);
+        TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+            workspace_id: entry.workspace_id.clone(),
+            message: json!({ "method": "notifications/added", "params": entry }),
+        });
+        let focused = app
+            .get_webview_window("main")
+            .and_then(|window| window.is_focused().ok())
+            .unwrap_or(false);
+        if focused
+            || !notifications_core::notifications_enabled(&state.workspaces, &event.workspace_id)
+                .await
+        {
+            return;
+        }
+        if let Err(error) = app
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+        {
+            eprintln!("failed to show notification: {error}");
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn list_notifications(
+    unread_only: Option<bool>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<NotificationEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_notifications",
+            json!({ "unreadOnly": unread_only, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(state.notification_center.list(
+        unread_only.unwrap_or(false),
+        limit.map(|limit| limit as usize),
+    ))
+}
+
+/// Marks `ids` as read, or every notification when `ids` is omitted.
+#[tauri::command]
+pub(crate) async fn mark_notifications_read(
+    ids: Option<Vec<String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "mark_notifications_read",
+            json!({ "ids": ids }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(state.notification_center.mark_read(ids.as_deref()))
+}
+
+/// Registers this device's APNs or FCM token with the daemon, which pushes
+/// turn completions and approval requests through its `pushRelay` while the
+/// app has no connection. Re-sent on every reconnect.
+#[tauri::command]
+pub(crate) async fn push_register_device(
+    device_token: String,
+    platform: PushPlatform,
+    app_id: Option<String>,
+    name: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PushDevice, String> {
+    if !remote_backend::is_remote_mode(&*state).await {
+        return Err(PUSH_REMOTE_ONLY.to_string());
+    }
+    let params = json!({
+        "deviceToken": device_token,
+        "platform": platform,
+        "appId": app_id,
+        "name": name,
+    });
+    let response =
+        remote_backend::call_remote(&*state, app, "push_register_device", params.clone()).await?;
+    *state.push_registration.lock().await = Some(params);
+    serde_json::from_value(response).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn push_unregister_device(
+    device_token: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if !remote_backend::is_remote_mode(&*state).await {
+        return Err(PUSH_REMOTE_ONLY.to_string());
+    }
+    {
+        let mut registration = state.push_registration.lock().await;
+        let registered = registration
+            .as_ref()
+            .and_then(|params| params.get("deviceToken"))
+            .and_then(|token| token.as_str());
+        if registered == Some(device_token.as_str()) {
+            *registration = None;
+        }
+    }
+    let response = remote_backend::call_remote(
+        &*state,
+        app,
+        "push_unregister_device",
+        json!({ "deviceToken": device_token }),
+    )
+    .await?;
+    Ok(response
+        .get("removed")
+        .and_then(|removed| removed.as_bool())
+        .unwrap_or(false))
+}
+
+#[tauri::command]
+pub(crate) async fn push_list_devices(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PushDevice>, String> {
+    if !remote_backend::is_remote_mode(&*state).await {
+        return Err(PUSH_REMOTE_ONLY.to_string());
+    }
+    let response =
+        remote_backend::call_remote(&*state, app, "push_list_devices", json!({})).await?;
+    serde_json::from_value(response).map_err(|err| err.to_string())
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+
+use crate::remote_backend;
+use crate::shared::playbooks_core::{self, Playbook, PlaybookDraft, PlaybookRun};
+use crate::shared::workspace_rpc;
+use crate::state::AppState;
+use crate::workspaces;
+
+#[tauri::command]
+pub(crate) async fn playbook_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Playbook>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "playbook_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(playbooks_core::playbook_list_core(&state.playbooks))
+}
+
+/// Creates a playbook, or replaces the one with `playbook.id`.
+#[tauri::command]
+pub(crate) async fn playbook_save(
+    playbook: PlaybookDraft,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Playbook, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "playbook_save",
+            json!({ "playbook": playbook }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    playbooks_core::playbook_save_core(&state.playbooks, playbook)
+}
+
+#[tauri::command]
+pub(crate) async fn playbook_delete(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(&*state, app, "playbook_delete", json!({ "id": id })).await?;
+        return Ok(());
+    }
+    playbooks_core::playbook_delete_core(&state.playbooks, &id)
+}
+
+/// Runs a playbook against `workspace_id`, returning where its thread started.
+#[tauri::command]
+pub(crate) async fn run_playbook(
+    workspace_id: String,
+    playbook_id: String,
+    variables: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PlaybookRun, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::RunPlaybookRequest {
+            workspace_id,
+            playbook_id,
+            variables,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "run_playbook",
+            workspace_rpc::to_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    playbooks_core::run_playbook_core(
+        &state.playbooks,
+        &state.sessions,
+        &state.workspaces,
+        workspace_id,
+        playbook_id,
+        variables.unwrap_or_default(),
+        |workspace_id| {
+            workspaces::connect_workspace(workspace_id, app.state::<AppState>(), app.clone())
+        },
+        |parent_id, branch| {
+            workspaces::add_worktree(
+                parent_id,
+                branch,
+                None,
+                None,
+                app.state::<AppState>(),
+                app.clone(),
+            )
+        },
+    )
+    .await
+}
@@ -1,5 +1,10 @@
-use tauri::State;
+use std::collections::HashMap;
 
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::prompt_library_core::{self, SavedPrompt};
 use crate::shared::prompts_core::{self, CustomPromptEntry};
 use crate::state::AppState;
 
@@ -100,3 +105,85 @@ pub(crate) async fn prompts_move(
     )
     .await
 }
+
+#[tauri::command]
+pub(crate) async fn prompt_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SavedPrompt>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "prompt_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(prompt_library_core::prompt_list_core(&state.prompt_library))
+}
+
+/// Creates a library prompt, or replaces the one with `id`.
+#[tauri::command]
+pub(crate) async fn prompt_save(
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    template: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SavedPrompt, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_save",
+            json!({
+                "id": id,
+                "name": name,
+                "description": description,
+                "template": template,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    prompt_library_core::prompt_save_core(&state.prompt_library, id, name, description, template)
+}
+
+#[tauri::command]
+pub(crate) async fn prompt_delete(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(&*state, app, "prompt_delete", json!({ "id": id })).await?;
+        return Ok(());
+    }
+    prompt_library_core::prompt_delete_core(&state.prompt_library, &id)
+}
+
+/// Fills a library prompt's placeholders for `workspace_id`.
+#[tauri::command]
+pub(crate) async fn prompt_render(
+    id: String,
+    workspace_id: String,
+    values: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_render",
+            json!({ "id": id, "workspaceId": workspace_id, "values": values }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    prompt_library_core::prompt_render_core(
+        &state.prompt_library,
+        &state.workspaces,
+        id,
+        workspace_id,
+        values.unwrap_or_default(),
+    )
+    .await
+}
@@ -1,8 +1,13 @@
+pub(crate) mod profiles;
 mod protocol;
+pub(crate) mod supervisor;
 mod tcp_transport;
 mod transport;
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde_json::{json, Value};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,10 +16,15 @@ use tauri::AppHandle;
 use tokio::sync::Mutex;
 use tokio::time::timeout;
 
+use crate::shared::codex_core;
+use crate::shared::daemon_version_core;
+use crate::shared::file_transfer_core::{UploadedFile, MAX_TRANSFER_BYTES};
 use crate::state::AppState;
 use crate::types::BackendMode;
 
-use self::protocol::{build_request_line, DEFAULT_REMOTE_HOST, DISCONNECTED_MESSAGE};
+use self::protocol::{
+    build_cancel_line, build_request_line, DEFAULT_REMOTE_HOST, DISCONNECTED_MESSAGE,
+};
 use self::tcp_transport::TcpTransport;
 use self::transport::{PendingMap, RemoteTransport, RemoteTransportConfig, RemoteTransportKind};
 
@@ -98,6 +108,9 @@ impl RemoteBackend {
             Ok(Err(_)) => Err(DISCONNECTED_MESSAGE.to_string()),
             Err(_) => {
                 self.inner.pending.lock().await.remove(&id);
+                if let Ok(line) = build_cancel_line(id) {
+                    let _ = self.inner.out_tx.try_send(line);
+                }
                 Err(format!(
                     "remote backend request timed out after {} seconds",
                     REMOTE_REQUEST_TIMEOUT.as_secs()
@@ -118,11 +131,13 @@ pub(crate) async fn call_remote(
     method: &str,
     params: Value,
 ) -> Result<Value, String> {
+    state.remote_supervisor.wait_while_reconnecting().await;
     let client = ensure_remote_backend(state, app.clone()).await?;
     match client.call(method, params.clone()).await {
         Ok(value) => Ok(value),
         Err(err) if err == DISCONNECTED_MESSAGE => {
             *state.remote_backend.lock().await = None;
+            state.remote_supervisor.report_disconnect();
             if !can_retry_after_disconnect(method) {
                 return Err(err);
             }
@@ -142,6 +157,89 @@ pub(crate) async fn call_remote(
     }
 }
 
+/// Connects if needed and pings the daemon once, dropping the connection
+/// when the ping fails.
+async fn ping(state: &AppState, app: AppHandle) -> Result<(), String> {
+    let client = ensure_remote_backend(state, app).await?;
+    match client.call("ping", json!({})).await {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            *state.remote_backend.lock().await = None;
+            Err(err)
+        }
+    }
+}
+
+/// Whether the daemon runs on this machine and can read local paths itself.
+fn shares_local_files(host: &str) -> bool {
+    let host = if host.trim().is_empty() {
+        DEFAULT_REMOTE_HOST
+    } else {
+        host
+    };
+    let name = profiles::host_name(host);
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<IpAddr>()
+            .is_ok_and(|address| address.is_loopback())
+}
+
+/// Prepares image paths for a remote send. The daemon host usually cannot
+/// see paths on this machine, so images that exist here are uploaded with
+/// `upload_file` and replaced by the daemon-side path. URLs, paths missing
+/// here and sends to a daemon on loopback pass through unchanged.
+pub(crate) async fn upload_images_for_remote(
+    state: &AppState,
+    app: AppHandle,
+    workspace_id: &str,
+    images: Option<Vec<String>>,
+) -> Result<Option<Vec<String>>, String> {
+    let Some(images) = images else {
+        return Ok(None);
+    };
+    let host = state.app_settings.lock().await.remote_backend_host.clone();
+    let shared = shares_local_files(&host);
+    let mut prepared = Vec::with_capacity(images.len());
+    for image in images {
+        let lower = image.trim().to_ascii_lowercase();
+        let is_url = ["data:", "http://", "https://"]
+            .iter()
+            .any(|prefix| lower.starts_with(prefix));
+        let local = PathBuf::from(codex_core::normalize_file_path(&image));
+        if shared || is_url || !local.is_file() {
+            prepared.push(normalize_path_for_remote(image));
+            continue;
+        }
+        let bytes = tokio::fs::read(&local)
+            .await
+            .map_err(|err| format!("Failed to read image {}: {err}", local.display()))?;
+        if bytes.len() > MAX_TRANSFER_BYTES {
+            return Err(format!(
+                "Image {} exceeds the {MAX_TRANSFER_BYTES} byte upload limit",
+                local.display()
+            ));
+        }
+        let file_name = local
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        let uploaded = call_remote(
+            state,
+            app.clone(),
+            "upload_file",
+            json!({
+                "workspaceId": workspace_id,
+                "fileName": file_name,
+                "bytesBase64": STANDARD.encode(bytes),
+            }),
+        )
+        .await?;
+        let uploaded: UploadedFile =
+            serde_json::from_value(uploaded).map_err(|err| err.to_string())?;
+        prepared.push(uploaded.path);
+    }
+    Ok(Some(prepared))
+}
+
 fn can_retry_after_disconnect(method: &str) -> bool {
     matches!(
         method,
@@ -151,35 +249,72 @@ fn can_retry_after_disconnect(method: &str) -> bool {
             | "collaboration_mode_list"
             | "connect_workspace"
             | "experimental_feature_list"
+            | "server_time"
+            | "daemon_version"
             | "set_workspace_runtime_codex_args"
             | "file_read"
             | "get_agents_settings"
             | "get_config_model"
+            | "get_thread_preferences"
+            | "session_provenance"
+            | "list_active_turns"
+            | "get_codex_config"
+            | "validate_codex_config"
             | "get_git_commit_diff"
             | "get_git_diffs"
             | "get_git_log"
             | "get_git_remote"
             | "get_git_status"
+            | "git_status_all"
             | "get_github_issues"
             | "get_github_pull_request_comments"
             | "get_github_pull_request_diff"
             | "get_github_pull_requests"
             | "is_workspace_path_dir"
+            | "list_approval_rules"
             | "list_git_branches"
             | "list_git_roots"
             | "list_mcp_server_status"
+            | "list_pending_operations"
+            | "list_pending_approvals"
+            | "list_slash_commands"
             | "list_threads"
             | "local_usage_snapshot"
+            | "usage_summary"
             | "list_workspace_files"
+            | "list_workspace_files_page"
+            | "list_workspace_dir"
+            | "attach_context"
+            | "recent_workspaces"
+            | "get_capabilities"
+            | "host_stats"
             | "list_workspaces"
+            | "watch_workspace_files"
             | "model_list"
+            | "prompt_list"
+            | "push_list_devices"
+            | "queue_list"
+            | "rate_limit_forecast"
+            | "list_scheduled_messages"
+            | "playbook_list"
             | "read_thread"
             | "read_agent_config_toml"
             | "read_workspace_file"
+            | "download_file"
             | "resume_thread"
+            | "search_threads"
+            | "search_workspace_files"
             | "thread_live_subscribe"
             | "thread_live_unsubscribe"
             | "skills_list"
+            | "terminal_output_page"
+            | "protocol_trace_tail"
+            | "get_thread_tail"
+            | "storage_usage_report"
+            | "workspace_quota_status"
+            | "workspace_summary"
+            | "dashboard_snapshot"
+            | "suggest_branch_name"
             | "worktree_setup_status"
     )
 }
@@ -215,13 +350,32 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
 
     if matches!(transport_kind, RemoteTransportKind::Tcp) {
         if let Some(token) = auth_token {
-            client
-                .call("auth", json!({ "token": token }))
-                .await
-                .map(|_| ())?;
+            let ours = daemon_version_core::daemon_version_core();
+            let result = client
+                .call(
+                    "auth",
+                    json!({
+                        "token": token,
+                        "protocolVersion": ours.protocol_version,
+                        "minProtocolVersion": ours.min_protocol_version,
+                    }),
+                )
+                .await?;
+            daemon_version_core::check_peer_protocol(
+                "app",
+                "daemon",
+                result.get("protocolVersion").and_then(Value::as_u64),
+                result.get("minProtocolVersion").and_then(Value::as_u64),
+            )?;
         }
     }
 
+    // Daemons without push support reject this; the connection is fine.
+    let push_registration = state.push_registration.lock().await.clone();
+    if let Some(params) = push_registration {
+        let _ = client.call("push_register_device", params).await;
+    }
+
     {
         let mut guard = state.remote_backend.lock().await;
         *guard = Some(client.clone());
@@ -238,15 +392,35 @@ fn resolve_transport_config(
     } else {
         settings.remote_backend_host.clone()
     };
+    // The legacy host and token fields mirror the active profile; TLS only
+    // lives on the profile.
+    let tls_server_name = profiles::active_profile(settings)
+        .filter(|profile| profile.tls)
+        .map(|profile| {
+            profile
+                .tls_server_name
+                .clone()
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| profiles::host_name(&host).to_string())
+        });
     Ok(RemoteTransportConfig::Tcp {
         host,
         auth_token: settings.remote_backend_token.clone(),
+        tls_server_name,
     })
 }
 
+/// Whether a settings change points the remote connection somewhere else.
+pub(crate) fn transport_changed(
+    previous: &crate::types::AppSettings,
+    updated: &crate::types::AppSettings,
+) -> bool {
+    resolve_transport_config(previous).ok() != resolve_transport_config(updated).ok()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{can_retry_after_disconnect, resolve_transport_config};
+    use super::{can_retry_after_disconnect, resolve_transport_config, shares_local_files};
     use crate::remote_backend::transport::RemoteTransportConfig;
     use crate::types::AppSettings;
 
@@ -271,4 +445,13 @@ mod tests {
         assert!(!can_retry_after_disconnect("start_thread"));
         assert!(!can_retry_after_disconnect("remove_workspace"));
     }
+
+    #[test]
+    fn only_loopback_daemons_share_local_files() {
+        assert!(shares_local_files(""));
+        assert!(shares_local_files("localhost:4732"));
+        assert!(shares_local_files("[::1]:4732"));
+        assert!(!shares_local_files("studio.local:4732"));
+        assert!(!shares_local_files("100.64.0.2:4732"));
+    }
 }
@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::shared::settings_core::update_app_settings_core;
+use crate::state::AppState;
+use crate::tailscale;
+use crate::types::{AppSettings, BackendMode, RemoteBackendProvider, RemoteBackendTarget};
+
+/// A connection profile as listed to the UI; the token is never sent back.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteProfile {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    /// `host:port`.
+    pub(crate) host: String,
+    pub(crate) has_token: bool,
+    pub(crate) tls: bool,
+    pub(crate) tls_server_name: Option<String>,
+    pub(crate) last_connected_at_ms: Option<i64>,
+    pub(crate) active: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteProfileDraft {
+    /// Updates the profile with this id instead of adding one.
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    pub(crate) name: String,
+    pub(crate) host: String,
+    /// Replaces any port given in `host`.
+    #[serde(default)]
+    pub(crate) port: Option<u16>,
+    /// `None` keeps the stored token when updating; an empty string clears it.
+    #[serde(default)]
+    pub(crate) token: Option<String>,
+    #[serde(default)]
+    pub(crate) tls: bool,
+    #[serde(default)]
+    pub(crate) tls_server_name: Option<String>,
+}
+
+/// The host of a `host:port` address, without IPv6 brackets.
+pub(crate) fn host_name(address: &str) -> &str {
+    let address = address.trim();
+    if let Some(rest) = address.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => host,
+        _ => address,
+    }
+}
+
+fn with_port(host: &str, port: Option<u16>) -> String {
+    let host = host.trim();
+    let Some(port) = port else {
+        return host.to_string();
+    };
+    let name = host_name(host);
+    if name.contains(':') {
+        format!("[{name}]:{port}")
+    } else {
+        format!("{name}:{port}")
+    }
+}
+
+/// The profile the remote connection uses, if `activeRemoteBackendId` names
+/// one.
+pub(crate) fn active_profile(settings: &AppSettings) -> Option<&RemoteBackendTarget> {
+    let id = settings.active_remote_backend_id.as_deref()?;
+    settings
+        .remote_backends
+        .iter()
+        .find(|profile| profile.id == id)
+}
+
+pub(crate) fn list_profiles(settings: &AppSettings) -> Vec<RemoteProfile> {
+    let active_id = settings.active_remote_backend_id.as_deref();
+    settings
+        .remote_backends
+        .iter()
+        .map(|profile| RemoteProfile {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            host: profile.host.clone(),
+            has_token: profile
+                .token
+                .as_deref()
+                .is_some_and(|token| !token.is_empty()),
+            tls: profile.tls,
+            tls_server_name: profile.tls_server_name.clone(),
+            last_connected_at_ms: profile.last_connected_at_ms,
+            active: Some(profile.id.as_str()) == active_id,
+        })
+        .collect()
+}
+
+/// Copies the active profile into the single-host fields the rest of the
+/// app reads.
+fn sync_active_profile(settings: &mut AppSettings) {
+    let Some(profile) = active_profile(settings).cloned() else {
+        return;
+    };
+    settings.remote_backend_provider = profile.provider;
+    settings.remote_backend_host = profile.host;
+    settings.remote_backend_token = profile.token;
+}
+
+/// Adds a profile, or updates the one `draft.id` names. Returns its id.
+pub(crate) fn upsert_profile(
+    settings: &mut AppSettings,
+    draft: RemoteProfileDraft,
+) -> Result<String, String> {
+    let name = draft.name.trim();
+    if name.is_empty() {
+        return Err("Profile name is required.".to_string());
+    }
+    if draft.host.trim().is_empty() {
+        return Err("Profile host is required.".to_string());
+    }
+    let host = with_port(&draft.host, draft.port);
+    let tls_server_name = draft
+        .tls_server_name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let existing = draft.id.as_deref().and_then(|id| {
+        settings
+            .remote_backends
+            .iter_mut()
+            .find(|profile| profile.id == id)
+    });
+    let id = match existing {
+        Some(profile) => {
+            profile.name = name.to_string();
+            profile.host = host;
+            if let Some(token) = draft.token {
+                profile.token = Some(token).filter(|token| !token.is_empty());
+            }
+            profile.tls = draft.tls;
+            profile.tls_server_name = tls_server_name;
+            profile.id.clone()
+        }
+        None => {
+            if let Some(id) = draft.id.as_deref() {
+                return Err(format!("Remote profile `{id}` not found."));
+            }
+            let id = format!("remote-{}", uuid::Uuid::new_v4());
+            settings.remote_backends.push(RemoteBackendTarget {
+                id: id.clone(),
+                name: name.to_string(),
+                provider: RemoteBackendProvider::Tcp,
+                host,
+                token: draft.token.filter(|token| !token.is_empty()),
+                tls: draft.tls,
+                tls_server_name,
+                last_connected_at_ms: None,
+            });
+            id
+        }
+    };
+    if settings.active_remote_backend_id.as_deref() == Some(id.as_str()) {
+        sync_active_profile(settings);
+    }
+    Ok(id)
+}
+
+/// Removes a profile. Removing the active one activates the first profile
+/// left, if any.
+pub(crate) fn remove_profile(settings: &mut AppSettings, id: &str) -> Result<(), String> {
+    let before = settings.remote_backends.len();
+    settings.remote_backends.retain(|profile| profile.id != id);
+    if settings.remote_backends.len() == before {
+        return Err(format!("Remote profile `{id}` not found."));
+    }
+    if settings.active_remote_backend_id.as_deref() == Some(id) {
+        settings.active_remote_backend_id = settings
+            .remote_backends
+            .first()
+            .map(|profile| profile.id.clone());
+        sync_active_profile(settings);
+    }
+    Ok(())
+}
+
+/// Makes a profile the remote connection and switches to remote mode.
+pub(crate) fn activate_profile(settings: &mut AppSettings, id: &str) -> Result<(), String> {
+    if !settings
+        .remote_backends
+        .iter()
+        .any(|profile| profile.id == id)
+    {
+        return Err(format!("Remote profile `{id}` not found."));
+    }
+    settings.active_remote_backend_id = Some(id.to_string());
+    settings.backend_mode = BackendMode::Remote;
+    sync_active_profile(settings);
+    Ok(())
+}
+
+/// Applies `change` to the settings, saves them, and drops the remote
+/// connection if it now points elsewhere.
+async fn update_profiles<T>(
+    state: &AppState,
+    change: impl FnOnce(&mut AppSettings) -> Result<T, String>,
+) -> Result<T, String> {
+    let previous = state.app_settings.lock().await.clone();
+    let mut settings = previous.clone();
+    let result = change(&mut settings)?;
+    let updated = update_app_settings_core(
+        settings,
+        &state.app_settings,
+        &state.settings_path,
+        &state.services,
+    )
+    .await?;
+    tailscale::sync_daemon_autostart(state, &previous, &updated).await;
+    if super::transport_changed(&previous, &updated)
+        || !matches!(
+            (&previous.backend_mode, &updated.backend_mode),
+            (BackendMode::Local, BackendMode::Local) | (BackendMode::Remote, BackendMode::Remote)
+        )
+    {
+        *state.remote_backend.lock().await = None;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub(crate) async fn remote_profile_list(
+    state: State<'_, AppState>,
+) -> Result<Vec<RemoteProfile>, String> {
+    Ok(list_profiles(&*state.app_settings.lock().await))
+}
+
+#[tauri::command]
+pub(crate) async fn remote_profile_add(
+    profile: RemoteProfileDraft,
+    state: State<'_, AppState>,
+) -> Result<Vec<RemoteProfile>, String> {
+    update_profiles(&state, |settings| {
+        upsert_profile(settings, profile)?;
+        Ok(list_profiles(settings))
+    })
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn remote_profile_remove(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<RemoteProfile>, String> {
+    update_profiles(&state, |settings| {
+        remove_profile(settings, &id)?;
+        Ok(list_profiles(settings))
+    })
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn remote_profile_activate(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<RemoteProfile>, String> {
+    update_profiles(&state, |settings| {
+        activate_profile(settings, &id)?;
+        Ok(list_profiles(settings))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(name: &str, host: &str) -> RemoteProfileDraft {
+        RemoteProfileDraft {
+            id: None,
+            name: name.to_string(),
+            host: host.to_string(),
+            port: None,
+            token: Some("token-1".to_string()),
+            tls: false,
+            tls_server_name: None,
+        }
+    }
+
+    #[test]
+    fn hosts_and_ports_combine() {
+        assert_eq!(host_name("studio.local:4732"), "studio.local");
+        assert_eq!(host_name("[fe80::1]:4732"), "fe80::1");
+        assert_eq!(host_name("fe80::1"), "fe80::1");
+        assert_eq!(
+            with_port("studio.local:4732", Some(5000)),
+            "studio.local:5000"
+        );
+        assert_eq!(with_port("fe80::1", Some(5000)), "[fe80::1]:5000");
+        assert_eq!(with_port(" studio.local:4732 ", None), "studio.local:4732");
+    }
+
+    #[test]
+    fn activating_a_profile_switches_the_connection() {
+        let mut settings = AppSettings::default();
+        let home = upsert_profile(&mut settings, draft("Home", "home.example:4732")).expect("add");
+        let mut work = draft("Work", "work.example");
+        work.port = Some(9000);
+        work.tls = true;
+        let work = upsert_profile(&mut settings, work).expect("add");
+
+        activate_profile(&mut settings, &work).expect("activate");
+        assert!(matches!(settings.backend_mode, BackendMode::Remote));
+        assert_eq!(settings.remote_backend_host, "work.example:9000");
+        assert_eq!(settings.remote_backend_token.as_deref(), Some("token-1"));
+        let listed = list_profiles(&settings);
+        assert!(listed
+            .iter()
+            .any(|profile| profile.id == work && profile.active));
+        assert!(listed.iter().all(|profile| profile.has_token));
+
+        remove_profile(&mut settings, &work).expect("remove");
+        assert_eq!(
+            settings.active_remote_backend_id.as_deref(),
+            Some(home.as_str())
+        );
+        assert_eq!(settings.remote_backend_host, "home.example:4732");
+        assert!(remove_profile(&mut settings, &work).is_err());
+    }
+
+    #[test]
+    fn updates_keep_the_token_unless_one_is_given() {
+        let mut settings = AppSettings::default();
+        let id = upsert_profile(&mut settings, draft("Home", "home.example:4732")).expect("add");
+        let mut update = draft("Home office", "home.example:4732");
+        update.id = Some(id.clone());
+        update.token = None;
+        upsert_profile(&mut settings, update.clone()).expect("update");
+        assert_eq!(settings.remote_backends[0].name, "Home office");
+        assert_eq!(
+            settings.remote_backends[0].token.as_deref(),
+            Some("token-1")
+        );
+
+        update.token = Some(String::new());
+        upsert_profile(&mut settings, update).expect("update");
+        assert_eq!(settings.remote_backends[0].token, None);
+
+        let mut missing = draft("Other", "other.example");
+        missing.id = Some("remote-missing".to_string());
+        assert!(upsert_profile(&mut settings, missing).is_err());
+    }
+}
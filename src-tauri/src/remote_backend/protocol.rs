@@ -23,6 +23,15 @@ pub(crate) fn build_request_line(id: u64, method: &str, params: Value) -> Result
     serde_json::to_string(&request).map_err(|err| err.to_string())
 }
 
+/// Asks the daemon to abandon an in-flight request it has not answered yet.
+pub(crate) fn build_cancel_line(request_id: u64) -> Result<String, String> {
+    let notification = json!({
+        "method": "cancel",
+        "params": { "requestId": request_id },
+    });
+    serde_json::to_string(&notification).map_err(|err| err.to_string())
+}
+
 pub(crate) fn parse_incoming_line(line: &str) -> Option<IncomingMessage> {
     let message: Value = serde_json::from_str(line).ok()?;
 
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{watch, Notify};
+use tokio::time::timeout;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::event_sink::TauriEventSink;
+use crate::state::AppState;
+
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_BACKOFF_START: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// How long a call made while reconnecting waits for the connection.
+pub(super) const RECONNECT_GRACE: Duration = Duration::from_secs(5);
+/// Failed reconnects after which calls stop waiting and fail right away.
+const MAX_BUFFERED_ATTEMPTS: u32 = 5;
+/// Pings averaged into `averageLatencyMs`.
+const LATENCY_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteBackendStatus {
+    pub(crate) remote_mode: bool,
+    pub(crate) host: Option<String>,
+    pub(crate) connected: bool,
+    /// The connection dropped and the supervisor is trying again.
+    pub(crate) reconnecting: bool,
+    /// Round trip of the last ping.
+    pub(crate) latency_ms: Option<u64>,
+    pub(crate) average_latency_ms: Option<u64>,
+    pub(crate) last_ping_at_ms: Option<i64>,
+    pub(crate) connected_since_ms: Option<i64>,
+    /// Failed attempts since the connection was last up.
+    pub(crate) reconnect_attempts: u32,
+    pub(crate) last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct SupervisorState {
+    status: RemoteBackendStatus,
+    samples: VecDeque<u64>,
+}
+
+/// Connection state of the remote backend, kept on `AppState` and updated
+/// by `run_supervisor`.
+pub(crate) struct RemoteSupervisor {
+    state: Mutex<SupervisorState>,
+    connected: watch::Sender<bool>,
+    wake: Notify,
+}
+
+impl Default for RemoteSupervisor {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(SupervisorState::default()),
+            connected: watch::channel(false).0,
+            wake: Notify::new(),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+impl SupervisorState {
+    /// Returns whether the connection just came up.
+    fn record_ping(&mut self, host: &str, latency_ms: u64, now: i64) -> bool {
+        if self.status.host.as_deref() != Some(host) {
+            self.samples.clear();
+        }
+        self.samples.push_back(latency_ms);
+        while self.samples.len() > LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        let came_up = !self.status.connected;
+        let status = &mut self.status;
+        status.remote_mode = true;
+        status.host = Some(host.to_string());
+        status.connected = true;
+        status.reconnecting = false;
+        status.latency_ms = Some(latency_ms);
+        status.average_latency_ms =
+            Some(self.samples.iter().sum::<u64>() / self.samples.len() as u64);
+        status.last_ping_at_ms = Some(now);
+        if came_up {
+            status.connected_since_ms = Some(now);
+        }
+        status.reconnect_attempts = 0;
+        status.last_error = None;
+        came_up
+    }
+
+    /// Returns whether the connection just went down.
+    fn record_failure(&mut self, host: &str, error: String) -> bool {
+        let went_down = self.status.connected;
+        let status = &mut self.status;
+        status.remote_mode = true;
+        status.host = Some(host.to_string());
+        status.connected = false;
+        status.latency_ms = None;
+        status.connected_since_ms = None;
+        status.reconnect_attempts = status.reconnect_attempts.saturating_add(1);
+        status.reconnecting =
+            (went_down || status.reconnecting) && status.reconnect_attempts < MAX_BUFFERED_ATTEMPTS;
+        status.last_error = Some(error);
+        went_down
+    }
+
+    fn record_local_mode(&mut self) {
+        self.samples.clear();
+        self.status = RemoteBackendStatus::default();
+    }
+}
+
+impl RemoteSupervisor {
+    fn lock(&self) -> MutexGuard<'_, SupervisorState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn publish(&self) {
+        let connected = self.lock().status.connected;
+        self.connected.send_if_modified(|current| {
+            let changed = *current != connected;
+            *current = connected;
+            changed
+        });
+    }
+
+    pub(crate) fn status_snapshot(&self) -> RemoteBackendStatus {
+        self.lock().status.clone()
+    }
+
+    /// Asks the supervisor to check the connection now rather than at the
+    /// next ping.
+    pub(crate) fn report_disconnect(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Holds a call made while the supervisor is reconnecting until the
+    /// connection is back or the grace period ends.
+    pub(super) async fn wait_while_reconnecting(&self) {
+        if !self.lock().status.reconnecting {
+            return;
+        }
+        let mut connected = self.connected.subscribe();
+        self.report_disconnect();
+        let _ = timeout(RECONNECT_GRACE, connected.wait_for(|connected| *connected)).await;
+    }
+
+    /// Called after `switch_backend_mode`. Leaving remote mode reports the
+    /// connection as gone; entering it starts over so the next ping emits
+    /// `remote/connected`.
+    pub(crate) fn backend_mode_changed(&self, app: &AppHandle, remote: bool) {
+        let previous = {
+            let mut state = self.lock();
+            let previous = state.status.clone();
+            state.record_local_mode();
+            previous
+        };
+        if !remote && previous.connected {
+            let status = RemoteBackendStatus {
+                connected: false,
+                latency_ms: None,
+                last_error: Some("Switched to local mode".to_string()),
+                ..previous
+            };
+            emit_connection_event(app, "remote/disconnected", &status);
+        }
+        self.publish();
+        self.report_disconnect();
+    }
+}
+
+fn emit_connection_event(app: &AppHandle, method: &str, status: &RemoteBackendStatus) {
+    TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+        workspace_id: String::new(),
+        message: json!({
+            "method": method,
+            "params": {
+                "host": status.host,
+                "latencyMs": status.latency_ms,
+                "error": status.last_error,
+                "reconnectAttempts": status.reconnect_attempts,
+            }
+        }),
+    });
+}
+
+/// Pings the daemon every few seconds in remote mode, reconnecting with
+/// backoff when it stops answering, and emits `remote/connected` and
+/// `remote/disconnected` as the connection comes and goes.
+pub(crate) async fn run_supervisor(app: AppHandle) {
+    let mut backoff = RECONNECT_BACKOFF_START;
+    loop {
+        let state = app.state::<AppState>();
+        let supervisor = &state.remote_supervisor;
+        let delay = if !super::is_remote_mode(&state).await {
+            supervisor.lock().record_local_mode();
+            backoff = RECONNECT_BACKOFF_START;
+            PING_INTERVAL
+        } else {
+            let host = state.app_settings.lock().await.remote_backend_host.clone();
+            let started = Instant::now();
+            let result = if state.tailscale_watch.tailnet_offline_for(&host) {
+                // The tailscale watch wakes the supervisor when it reconnects.
+                *state.remote_backend.lock().await = None;
+                Err("Tailscale is not connected".to_string())
+            } else {
+                match timeout(PING_TIMEOUT, super::ping(&state, app.clone())).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        *state.remote_backend.lock().await = None;
+                        Err(format!(
+                            "ping timed out after {} seconds",
+                            PING_TIMEOUT.as_secs()
+                        ))
+                    }
+                }
+            };
+            match result {
+                Ok(()) => {
+                    let latency_ms = started.elapsed().as_millis() as u64;
+                    let came_up = supervisor.lock().record_ping(&host, latency_ms, now_ms());
+                    if came_up {
+                        emit_connection_event(
+                            &app,
+                            "remote/connected",
+                            &supervisor.status_snapshot(),
+                        );
+                    }
+                    backoff = RECONNECT_BACKOFF_START;
+                    PING_INTERVAL
+                }
+                Err(error) => {
+                    let went_down = supervisor.lock().record_failure(&host, error);
+                    if went_down {
+                        emit_connection_event(
+                            &app,
+                            "remote/disconnected",
+                            &supervisor.status_snapshot(),
+                        );
+                    }
+                    let delay = backoff;
+                    backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                    delay
+                }
+            }
+        };
+        supervisor.publish();
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = supervisor.wake.notified() => {}
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn remote_backend_status(
+    state: State<'_, AppState>,
+) -> Result<RemoteBackendStatus, String> {
+    let settings = state.app_settings.lock().await.clone();
+    let mut status = state.remote_supervisor.status_snapshot();
+    status.remote_mode = matches!(settings.backend_mode, crate::types::BackendMode::Remote);
+    if status.remote_mode && status.host.is_none() {
+        status.host = Some(settings.remote_backend_host);
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pings_average_and_reset_after_failures() {
+        let mut state = SupervisorState::default();
+        assert!(state.record_ping("studio:4732", 10, 1_000));
+        assert!(!state.record_ping("studio:4732", 30, 2_000));
+        assert_eq!(state.status.latency_ms, Some(30));
+        assert_eq!(state.status.average_latency_ms, Some(20));
+        assert_eq!(state.status.connected_since_ms, Some(1_000));
+
+        assert!(state.record_failure("studio:4732", "refused".to_string()));
+        assert!(!state.record_failure("studio:4732", "refused".to_string()));
+        assert!(state.status.reconnecting);
+        assert_eq!(state.status.reconnect_attempts, 2);
+
+        for _ in 0..MAX_BUFFERED_ATTEMPTS {
+            state.record_failure("studio:4732", "refused".to_string());
+        }
+        assert!(!state.status.reconnecting);
+
+        assert!(state.record_ping("studio:4732", 50, 3_000));
+        assert_eq!(state.status.reconnect_attempts, 0);
+        assert_eq!(state.status.average_latency_ms, Some(30));
+        assert_eq!(state.status.connected_since_ms, Some(3_000));
+    }
+
+    #[test]
+    fn a_new_host_starts_a_new_latency_average() {
+        let mut state = SupervisorState::default();
+        state.record_ping("home:4732", 100, 1_000);
+        state.record_ping("work:4732", 10, 2_000);
+        assert_eq!(state.status.average_latency_ms, Some(10));
+
+        state.record_local_mode();
+        assert_eq!(state.status, RemoteBackendStatus::default());
+    }
+}
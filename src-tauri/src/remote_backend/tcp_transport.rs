@@ -1,5 +1,10 @@
+use std::sync::Arc;
+
 use tauri::AppHandle;
 use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{crypto, ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
 
 use super::transport::{
     spawn_transport_io, RemoteTransport, RemoteTransportConfig, TransportFuture,
@@ -7,15 +12,40 @@ use super::transport::{
 
 pub(crate) struct TcpTransport;
 
+fn tls_connector() -> Result<TlsConnector, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
 impl RemoteTransport for TcpTransport {
     fn connect(&self, app: AppHandle, config: RemoteTransportConfig) -> TransportFuture {
         Box::pin(async move {
-            let RemoteTransportConfig::Tcp { host, .. } = config;
+            let RemoteTransportConfig::Tcp {
+                host,
+                tls_server_name,
+                ..
+            } = config;
 
             let stream = TcpStream::connect(host.clone())
                 .await
                 .map_err(|err| format!("Failed to connect to remote backend at {host}: {err}"))?;
-            let (reader, writer) = stream.into_split();
+            let Some(server_name) = tls_server_name else {
+                let (reader, writer) = stream.into_split();
+                return Ok(spawn_transport_io(app, reader, writer));
+            };
+            let server_name = ServerName::try_from(server_name.clone())
+                .map_err(|_| format!("Invalid TLS server name `{server_name}`"))?;
+            let stream = tls_connector()?
+                .connect(server_name, stream)
+                .await
+                .map_err(|err| format!("TLS handshake with {host} failed: {err}"))?;
+            let (reader, writer) = tokio::io::split(stream);
             Ok(spawn_transport_io(app, reader, writer))
         })
     }
@@ -14,11 +14,13 @@ use super::protocol::{parse_incoming_line, IncomingMessage, DISCONNECTED_MESSAGE
 pub(crate) type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
 const OUTBOUND_QUEUE_CAPACITY: usize = 512;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum RemoteTransportConfig {
     Tcp {
         host: String,
         auth_token: Option<String>,
+        /// Certificate name to verify when the connection is wrapped in TLS.
+        tls_server_name: Option<String>,
     },
 }
 
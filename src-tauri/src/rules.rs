@@ -1,8 +1,13 @@
 use std::fs;
 use std::fs::OpenOptions;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::thread;
-use std::time::{Duration, Instant, SystemTime};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 const RULES_DIR: &str = "rules";
 const DEFAULT_RULES_FILE: &str = "default.rules";
@@ -160,3 +165,333 @@ fn escape_string(value: &str) -> String {
         .replace('\r', "\\r")
         .replace('\t', "\\t")
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ApprovalRuleKind {
+    /// Whitespace-separated tokens that must lead the command.
+    Prefix,
+    /// Regex matched anywhere in the command line.
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ApprovalRuleDecision {
+    Allow,
+    Deny,
+}
+
+impl ApprovalRuleDecision {
+    /// The `decision` the app-server expects in an approval response.
+    pub(crate) fn response_decision(self) -> &'static str {
+        match self {
+            Self::Allow => "accept",
+            Self::Deny => "decline",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApprovalRule {
+    pub(crate) id: String,
+    pub(crate) kind: ApprovalRuleKind,
+    pub(crate) pattern: String,
+    pub(crate) decision: ApprovalRuleDecision,
+    /// When set, the rule only applies to commands running inside this path.
+    #[serde(default)]
+    pub(crate) scope_path: Option<String>,
+    #[serde(default)]
+    pub(crate) expires_at: Option<u64>,
+    #[serde(default)]
+    pub(crate) created_at: u64,
+}
+
+impl ApprovalRule {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    fn applies_to_cwd(&self, cwd: Option<&str>) -> bool {
+        let Some(scope) = self.scope_path.as_deref() else {
+            return true;
+        };
+        cwd.is_some_and(|cwd| Path::new(cwd).starts_with(scope))
+    }
+
+    fn matches_command(&self, command: &str) -> bool {
+        match self.kind {
+            ApprovalRuleKind::Prefix => {
+                let pattern = self.pattern.split_whitespace();
+                let mut tokens = command.split_whitespace();
+                pattern.into_iter().all(|part| tokens.next() == Some(part))
+            }
+            ApprovalRuleKind::Regex => Regex::new(&self.pattern)
+                .map(|regex| regex.is_match(command))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// CodexMonitor-side approval policy evaluated before approval requests reach
+/// the UI. Unlike `default.rules`, which Codex reads itself, these rules can
+/// deny, expire, match by regex, and be limited to one workspace.
+pub(crate) struct ApprovalPolicyStore {
+    path: PathBuf,
+    rules: Mutex<Vec<ApprovalRule>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl ApprovalPolicyStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let rules = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            rules: Mutex::new(rules),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut Vec<ApprovalRule>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut rules = self
+            .rules
+            .lock()
+            .map_err(|_| "approval policy is poisoned".to_string())?;
+        let result = apply(&mut rules)?;
+        let now = now_ms();
+        rules.retain(|rule| !rule.is_expired(now));
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(&*rules).map_err(|err| err.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, data).map_err(|err| err.to_string())?;
+        fs::rename(&tmp, &self.path).map_err(|err| err.to_string())?;
+        Ok(result)
+    }
+
+    pub(crate) fn add(
+        &self,
+        kind: ApprovalRuleKind,
+        pattern: String,
+        decision: ApprovalRuleDecision,
+        scope_path: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Result<ApprovalRule, String> {
+        let pattern = pattern.trim().to_string();
+        if pattern.is_empty() {
+            return Err("empty rule pattern".to_string());
+        }
+        if kind == ApprovalRuleKind::Regex {
+            Regex::new(&pattern).map_err(|err| format!("Invalid rule regex: {err}"))?;
+        }
+        let now = now_ms();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err("Rule expiry must be in the future".to_string());
+        }
+        let rule = ApprovalRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            pattern,
+            decision,
+            scope_path,
+            expires_at,
+            created_at: now,
+        };
+        self.update(|rules| {
+            rules.push(rule.clone());
+            Ok(rule)
+        })
+    }
+
+    /// Unexpired rules. With `cwd`, only those that apply inside it.
+    pub(crate) fn list(&self, cwd: Option<&str>) -> Vec<ApprovalRule> {
+        let now = now_ms();
+        let Ok(rules) = self.rules.lock() else {
+            return Vec::new();
+        };
+        rules
+            .iter()
+            .filter(|rule| !rule.is_expired(now))
+            .filter(|rule| cwd.is_none() || rule.applies_to_cwd(cwd))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn delete(&self, id: &str) -> Result<(), String> {
+        self.update(|rules| {
+            let before = rules.len();
+            rules.retain(|rule| rule.id != id);
+            if rules.len() == before {
+                return Err(format!("Unknown approval rule `{id}`"));
+            }
+            Ok(())
+        })
+    }
+
+    /// The rule that decides `command`, if any. Deny rules win over allow
+    /// rules so a broad allow can be carved out with a narrow deny.
+    pub(crate) fn evaluate(&self, command: &str, cwd: Option<&str>) -> Option<ApprovalRule> {
+        let now = now_ms();
+        let rules = self.rules.lock().ok()?;
+        let mut matching = rules.iter().filter(|rule| {
+            !rule.is_expired(now) && rule.applies_to_cwd(cwd) && rule.matches_command(command)
+        });
+        let first = matching.next()?;
+        if first.decision == ApprovalRuleDecision::Deny {
+            return Some(first.clone());
+        }
+        Some(
+            matching
+                .find(|rule| rule.decision == ApprovalRuleDecision::Deny)
+                .unwrap_or(first)
+                .clone(),
+        )
+    }
+}
+
+/// The command line of an approval request, joined when sent as tokens.
+pub(crate) fn approval_request_command(params: &Value) -> Option<String> {
+    match params.get("command")? {
+        Value::String(command) => Some(command.trim().to_string()),
+        Value::Array(tokens) => {
+            let tokens = tokens
+                .iter()
+                .map(Value::as_str)
+                .collect::<Option<Vec<_>>>()?;
+            Some(tokens.join(" "))
+        }
+        _ => None,
+    }
+    .filter(|command| !command.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store() -> (ApprovalPolicyStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-approval-policy-{}",
+            uuid::Uuid::new_v4()
+        ));
+        (
+            ApprovalPolicyStore::load(dir.join("approval-rules.json")),
+            dir,
+        )
+    }
+
+    #[test]
+    fn deny_rules_win_and_scopes_apply() {
+        let (store, dir) = temp_store();
+        store
+            .add(
+                ApprovalRuleKind::Prefix,
+                "git".into(),
+                ApprovalRuleDecision::Allow,
+                None,
+                None,
+            )
+            .expect("allow git");
+        let deny = store
+            .add(
+                ApprovalRuleKind::Regex,
+                r"^git push\b.*--force".into(),
+                ApprovalRuleDecision::Deny,
+                None,
+                None,
+            )
+            .expect("deny force push");
+        store
+            .add(
+                ApprovalRuleKind::Prefix,
+                "npm test".into(),
+                ApprovalRuleDecision::Allow,
+                Some("/repo/app".into()),
+                None,
+            )
+            .expect("scoped npm");
+
+        let reloaded = ApprovalPolicyStore::load(dir.join("approval-rules.json"));
+        assert_eq!(
+            reloaded
+                .evaluate("git status", None)
+                .map(|rule| rule.decision),
+            Some(ApprovalRuleDecision::Allow)
+        );
+        assert_eq!(
+            reloaded
+                .evaluate("git push origin main --force", None)
+                .map(|rule| rule.id),
+            Some(deny.id.clone())
+        );
+        assert!(reloaded.evaluate("gitk", None).is_none());
+        assert!(reloaded
+            .evaluate("npm test", Some("/repo/app/src"))
+            .is_some());
+        assert!(reloaded.evaluate("npm test", Some("/repo/other")).is_none());
+        assert_eq!(reloaded.list(Some("/repo/other")).len(), 2);
+
+        reloaded.delete(&deny.id).expect("delete");
+        assert!(reloaded.delete(&deny.id).is_err());
+        assert_eq!(reloaded.list(None).len(), 2);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_bad_rules_and_ignores_expired_ones() {
+        let (store, dir) = temp_store();
+        assert!(store
+            .add(
+                ApprovalRuleKind::Regex,
+                "(".into(),
+                ApprovalRuleDecision::Allow,
+                None,
+                None,
+            )
+            .is_err());
+        assert!(store
+            .add(
+                ApprovalRuleKind::Prefix,
+                "ls".into(),
+                ApprovalRuleDecision::Allow,
+                None,
+                Some(1),
+            )
+            .is_err());
+        {
+            let mut rules = store.rules.lock().expect("lock");
+            rules.push(ApprovalRule {
+                id: "old".into(),
+                kind: ApprovalRuleKind::Prefix,
+                pattern: "ls".into(),
+                decision: ApprovalRuleDecision::Allow,
+                scope_path: None,
+                expires_at: Some(1),
+                created_at: 0,
+            });
+        }
+        assert!(store.evaluate("ls -la", None).is_none());
+        assert!(store.list(None).is_empty());
+
+        assert_eq!(
+            approval_request_command(&json!({ "command": ["ls", "-la"] })),
+            Some("ls -la".to_string())
+        );
+        assert_eq!(approval_request_command(&json!({ "command": " " })), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
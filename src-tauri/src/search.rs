@@ -0,0 +1,33 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::search_core::SearchHit;
+use crate::state::AppState;
+
+/// Full-text search over indexed thread items, best match first with at most
+/// one hit per turn. `workspace_id` narrows the search to one workspace.
+#[tauri::command]
+pub(crate) async fn search_threads(
+    workspace_id: Option<String>,
+    query: String,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SearchHit>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "search_threads",
+            json!({ "workspaceId": workspace_id, "query": query, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    state.search_index.search(
+        workspace_id.as_deref(),
+        &query,
+        limit.map(|limit| limit as usize),
+    )
+}
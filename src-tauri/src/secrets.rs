@@ -0,0 +1,397 @@
+//! Tokens and keys kept out of `settings.json`.
+//!
+//! Settings store a reference such as `secret://keychain/remoteBackendToken`
+//! in place of the value. Values live either in the OS keychain (Keychain,
+//! Windows Credential Manager/DPAPI or the Secret Service) or in
+//! `secrets.json` next to `settings.json`, encrypted with ChaCha20-Poly1305
+//! under a key from `CODEX_MONITOR_SECRETS_KEY` (base64, 32 bytes) or a
+//! generated `secrets.key`. The store is chosen once per data dir and
+//! recorded in `secrets-backend`, so the app and a daemon sharing the dir
+//! never split secrets between them. Keychain entries are scoped to the data
+//! dir, so two dirs on one machine keep separate secrets.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use sha2::{Digest, Sha256};
+
+const REFERENCE_PREFIX: &str = "secret://";
+const SECRETS_FILE: &str = "secrets.json";
+const SECRETS_KEY_FILE: &str = "secrets.key";
+const SECRETS_KEY_ENV: &str = "CODEX_MONITOR_SECRETS_KEY";
+const BACKEND_FILE: &str = "secrets-backend";
+const NONCE_LEN: usize = 12;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const KEYCHAIN_SERVICE: &str = "com.dimillian.codexmonitor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SecretBackend {
+    Keychain,
+    File,
+}
+
+impl SecretBackend {
+    fn as_str(self) -> &'static str {
+        match self {
+            SecretBackend::Keychain => "keychain",
+            SecretBackend::File => "file",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "keychain" => Some(SecretBackend::Keychain),
+            "file" => Some(SecretBackend::File),
+            _ => None,
+        }
+    }
+}
+
+/// Records `backend` as the store for `dir` unless one is recorded already,
+/// and returns the recorded one: the first process to claim the dir decides.
+pub(crate) fn claim_backend(dir: &Path, backend: SecretBackend) -> Result<SecretBackend, String> {
+    let path = dir.join(BACKEND_FILE);
+    let _lock = lock_file(&path)?;
+    if let Some(recorded) = backend_for(dir) {
+        return Ok(recorded);
+    }
+    write_private_locked(&path, backend.as_str().as_bytes())?;
+    Ok(backend)
+}
+
+/// The store recorded for `dir`. Until one is claimed, settings keep secrets
+/// inline, which is what tests and read-only tools want; existing references
+/// still resolve.
+pub(crate) fn backend_for(dir: &Path) -> Option<SecretBackend> {
+    let recorded = fs::read_to_string(dir.join(BACKEND_FILE)).ok()?;
+    SecretBackend::parse(recorded.trim())
+}
+
+pub(crate) fn is_reference(value: &str) -> bool {
+    value.starts_with(REFERENCE_PREFIX)
+}
+
+fn reference(backend: SecretBackend, name: &str) -> String {
+    format!("{REFERENCE_PREFIX}{}/{name}", backend.as_str())
+}
+
+fn parse_reference(value: &str) -> Option<(SecretBackend, &str)> {
+    let rest = value.strip_prefix(REFERENCE_PREFIX)?;
+    let (backend, name) = rest.split_once('/')?;
+    Some((SecretBackend::parse(backend)?, name))
+}
+
+/// Stores `value` as `name` and returns the reference to keep in settings.
+/// A keychain that cannot be reached falls back to the encrypted file.
+pub(crate) fn store(
+    dir: &Path,
+    backend: SecretBackend,
+    name: &str,
+    value: &str,
+) -> Result<String, String> {
+    if backend == SecretBackend::Keychain {
+        match keychain_set(dir, name, value) {
+            Ok(()) => return Ok(reference(SecretBackend::Keychain, name)),
+            Err(err) => {
+                eprintln!("secrets: keychain unavailable ({err}); using {SECRETS_FILE}");
+            }
+        }
+    }
+    let key = load_file_key(dir)?;
+    let path = dir.join(SECRETS_FILE);
+    let _lock = lock_file(&path)?;
+    let mut entries = read_file_entries(dir)?;
+    entries.insert(name.to_string(), encrypt(&key, name, value)?);
+    let data =
+        serde_json::to_string_pretty(&SecretsFile { entries }).map_err(|err| err.to_string())?;
+    write_private_locked(&path, data.as_bytes())?;
+    Ok(reference(SecretBackend::File, name))
+}
+
+/// Returns the value behind a reference; anything else is returned as is.
+pub(crate) fn resolve(dir: &Path, value: &str) -> Result<String, String> {
+    let Some((backend, name)) = parse_reference(value) else {
+        if is_reference(value) {
+            return Err(format!("Unknown secret reference `{value}`"));
+        }
+        return Ok(value.to_string());
+    };
+    match backend {
+        SecretBackend::Keychain => keychain_get(dir, name),
+        SecretBackend::File => {
+            let entries = read_file_entries(dir)?;
+            let sealed = entries
+                .get(name)
+                .ok_or_else(|| format!("Secret `{name}` is missing from {SECRETS_FILE}"))?;
+            decrypt(&load_file_key(dir)?, name, sealed)
+        }
+    }
+}
+
+/// The keychain account for `name` in `dir`: the name plus a hash of the
+/// canonical data dir, so dirs sharing the service never read each other's
+/// secrets.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn keychain_account(dir: &Path, name: &str) -> String {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let scope: String = Sha256::digest(dir.to_string_lossy().as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{name}@{scope}")
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn keychain_entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|err| err.to_string())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn keychain_set(dir: &Path, name: &str, value: &str) -> Result<(), String> {
+    keychain_entry(&keychain_account(dir, name))?
+        .set_password(value)
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn keychain_get(dir: &Path, name: &str) -> Result<String, String> {
+    let scoped = keychain_entry(&keychain_account(dir, name))?.get_password();
+    // Entries stored before accounts were scoped to the data dir.
+    let scoped = match scoped {
+        Err(keyring::Error::NoEntry) => keychain_entry(name)?.get_password(),
+        other => other,
+    };
+    scoped.map_err(|err| format!("Failed to read `{name}` from the keychain: {err}"))
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn keychain_set(_dir: &Path, _name: &str, _value: &str) -> Result<(), String> {
+    Err("the OS keychain is not supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn keychain_get(_dir: &Path, name: &str) -> Result<String, String> {
+    Err(format!(
+        "`{name}` is in a keychain this platform cannot read"
+    ))
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SecretsFile {
+    #[serde(default)]
+    entries: BTreeMap<String, String>,
+}
+
+fn read_file_entries(dir: &Path) -> Result<BTreeMap<String, String>, String> {
+    let path = dir.join(SECRETS_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let file: SecretsFile = serde_json::from_str(&data)
+        .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?;
+    Ok(file.entries)
+}
+
+fn lock_file(path: &Path) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(sibling(path, ".lock"))
+        .map_err(|err| err.to_string())?;
+    lock.lock()
+        .map_err(|err| format!("Failed to lock {}: {err}", path.display()))?;
+    Ok(lock)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Replaces `path` through a temp file created readable only by the current
+/// user where that is expressible, so the data is never briefly world
+/// readable. Callers hold the lock from `lock_file`.
+fn write_private_locked(path: &Path, data: &[u8]) -> Result<(), String> {
+    let tmp = sibling(path, ".tmp");
+    let mut options = File::options();
+    options.create(true).truncate(true).write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options.open(&tmp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {err}", path.display()));
+    }
+    Ok(())
+}
+
+fn decode_key(encoded: &str) -> Result<Key, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|err| format!("Invalid secrets key: {err}"))?;
+    if bytes.len() != 32 {
+        return Err("Invalid secrets key: expected 32 bytes".to_string());
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+fn load_file_key(dir: &Path) -> Result<Key, String> {
+    if let Ok(encoded) = std::env::var(SECRETS_KEY_ENV) {
+        if !encoded.trim().is_empty() {
+            return decode_key(&encoded);
+        }
+    }
+    let path = dir.join(SECRETS_KEY_FILE);
+    let _lock = lock_file(&path)?;
+    if path.exists() {
+        let encoded = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        return decode_key(&encoded);
+    }
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    write_private_locked(
+        &path,
+        base64::engine::general_purpose::STANDARD
+            .encode(key.as_slice())
+            .as_bytes(),
+    )?;
+    Ok(key)
+}
+
+/// Seals `value` with the entry name as associated data, so entries cannot
+/// be swapped between names.
+fn encrypt(key: &Key, name: &str, value: &str) -> Result<String, String> {
+    let cipher = ChaCha20Poly1305::new(key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: value.as_bytes(),
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("Failed to encrypt secret `{name}`"))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+}
+
+fn decrypt(key: &Key, name: &str, sealed: &str) -> Result<String, String> {
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(sealed)
+        .map_err(|_| format!("Secret `{name}` is corrupted"))?;
+    if sealed.len() < NONCE_LEN {
+        return Err(format!("Secret `{name}` is corrupted"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("Secret `{name}` cannot be decrypted with the current key"))?;
+    String::from_utf8(plaintext).map_err(|_| format!("Secret `{name}` is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn file_backend_round_trips_secrets() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-secrets-{}", Uuid::new_v4()));
+
+        let stored = store(&dir, SecretBackend::File, "remoteBackendToken", "token-1")
+            .expect("store secret");
+        assert_eq!(stored, "secret://file/remoteBackendToken");
+        assert!(is_reference(&stored));
+        let on_disk = fs::read_to_string(dir.join(SECRETS_FILE)).expect("secrets file");
+        assert!(!on_disk.contains("token-1"));
+        assert_eq!(resolve(&dir, &stored).as_deref(), Ok("token-1"));
+        assert_eq!(resolve(&dir, "plain").as_deref(), Ok("plain"));
+        assert!(resolve(&dir, "secret://vault/x").is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for file in [SECRETS_FILE, SECRETS_KEY_FILE] {
+                let mode = fs::metadata(dir.join(file))
+                    .expect("metadata")
+                    .permissions();
+                assert_eq!(mode.mode() & 0o777, 0o600);
+            }
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn first_claim_decides_the_backend_for_a_data_dir() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-secrets-{}", Uuid::new_v4()));
+
+        assert_eq!(backend_for(&dir), None);
+        assert_eq!(
+            claim_backend(&dir, SecretBackend::File),
+            Ok(SecretBackend::File)
+        );
+        assert_eq!(
+            claim_backend(&dir, SecretBackend::Keychain),
+            Ok(SecretBackend::File)
+        );
+        assert_eq!(backend_for(&dir), Some(SecretBackend::File));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    #[test]
+    fn keychain_accounts_do_not_collide_across_data_dirs() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-secrets-{}", Uuid::new_v4()));
+        let first = root.join("first");
+        let second = root.join("second");
+        fs::create_dir_all(&first).expect("first dir");
+        fs::create_dir_all(&second).expect("second dir");
+
+        let account = keychain_account(&first, "remoteBackendToken");
+        assert!(account.starts_with("remoteBackendToken@"));
+        assert_ne!(account, keychain_account(&second, "remoteBackendToken"));
+        assert_ne!(account, keychain_account(&first, "pushRelay/apiKey"));
+        assert_eq!(
+            account,
+            keychain_account(&second.join("..").join("first"), "remoteBackendToken")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn sealed_entries_are_bound_to_their_name() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = encrypt(&key, "approvalChat/botToken", "xoxb-1").expect("encrypt");
+        assert_eq!(
+            decrypt(&key, "approvalChat/botToken", &sealed).as_deref(),
+            Ok("xoxb-1")
+        );
+        assert!(decrypt(&key, "remoteBackendToken", &sealed).is_err());
+    }
+}
@@ -1,10 +1,25 @@
-use tauri::{State, Window};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State, Window};
 
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::turn_tracker::list_active_turns_core;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::shared::network_core::test_network_connectivity_core;
 use crate::shared::settings_core::{
     get_app_settings_core, get_codex_config_path_core, update_app_settings_core,
 };
+use crate::shared::storage_core::{cleanup_storage_core, storage_usage_report_core};
+use crate::shared::workspaces_core::{
+    disconnect_all_sessions_core, list_workspaces_core, sync_request_timeouts_core,
+};
 use crate::state::AppState;
-use crate::types::{AppSettings, BackendMode};
+use crate::tailscale;
+use crate::types::{
+    AppSettings, BackendMode, NetworkConnectivityReport, ServerTime, StorageCategory,
+    StorageCleanupResult, StorageUsageReport, WorkspaceInfo,
+};
 use crate::window;
 
 #[tauri::command]
@@ -24,8 +39,15 @@ pub(crate) async fn update_app_settings(
     window: Window,
 ) -> Result<AppSettings, String> {
     let previous = state.app_settings.lock().await.clone();
-    let updated =
-        update_app_settings_core(settings, &state.app_settings, &state.settings_path).await?;
+    let updated = update_app_settings_core(
+        settings,
+        &state.app_settings,
+        &state.settings_path,
+        &state.services,
+    )
+    .await?;
+    sync_request_timeouts_core(&state.workspaces, &state.sessions, &state.app_settings).await;
+    tailscale::sync_daemon_autostart(&state, &previous, &updated).await;
     if should_reset_remote_backend(&previous, &updated) {
         *state.remote_backend.lock().await = None;
     }
@@ -39,6 +61,187 @@ pub(crate) async fn get_codex_config_path() -> Result<String, String> {
     get_codex_config_path_core()
 }
 
+/// Reads the backend's clock and this client's offset from it, so relative
+/// times ("5m ago", "resets in 2h") stay right when the daemon's clock and
+/// this device's disagree.
+#[tauri::command]
+pub(crate) async fn server_time(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ServerTime, String> {
+    let local_now = || chrono::Utc::now().timestamp_millis();
+    if remote_backend::is_remote_mode(&*state).await {
+        let sent_at = local_now();
+        let response = remote_backend::call_remote(&*state, app, "server_time", json!({})).await?;
+        let received_at = local_now();
+        let server_time = response
+            .get("serverTime")
+            .and_then(Value::as_i64)
+            .ok_or_else(|| "missing serverTime in server_time response".to_string())?;
+        // Assume the daemon read its clock halfway through the round trip.
+        return Ok(ServerTime {
+            server_time,
+            offset_ms: server_time - (sent_at + received_at) / 2,
+            round_trip_ms: received_at - sent_at,
+        });
+    }
+    Ok(ServerTime {
+        server_time: local_now(),
+        offset_ms: 0,
+        round_trip_ms: 0,
+    })
+}
+
+/// Probes `urls` (or a default set) through the configured proxy and CA
+/// certificates. In remote mode the daemon host is tested instead.
+#[tauri::command]
+pub(crate) async fn test_network_connectivity(
+    urls: Option<Vec<String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<NetworkConnectivityReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "test_network_connectivity",
+            json!({ "urls": urls }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    test_network_connectivity_core(state.services.network_settings(), urls).await
+}
+
+#[tauri::command]
+pub(crate) async fn storage_usage_report(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<StorageUsageReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "storage_usage_report", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let settings = state.app_settings.lock().await.clone();
+    let data_dir = state.data_dir();
+    tokio::task::spawn_blocking(move || storage_usage_report_core(&data_dir, &settings))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Deletes every file in `category`, or only those older than `older_than_days`.
+#[tauri::command]
+pub(crate) async fn storage_cleanup(
+    category: StorageCategory,
+    older_than_days: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<StorageCleanupResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "storage_cleanup",
+            json!({ "category": category, "olderThanDays": older_than_days }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let data_dir = state.data_dir();
+    tokio::task::spawn_blocking(move || cleanup_storage_core(&data_dir, category, older_than_days))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackendModeSwitch {
+    pub(crate) mode: BackendMode,
+    /// Local app-servers stopped on the way to remote mode.
+    pub(crate) stopped_sessions: usize,
+    /// The workspace list as the new backend sees it; empty when it could
+    /// not be read.
+    pub(crate) workspaces: Vec<WorkspaceInfo>,
+    pub(crate) workspaces_error: Option<String>,
+}
+
+/// Switches between local sessions and a daemon without leaving duplicate
+/// app-servers behind. Going remote stops the local sessions, refusing
+/// while local turns run unless `force` is set; going local drops the
+/// connection and leaves the daemon's sessions running. Emits
+/// `backend/modeChanged` with the workspace list from the new backend.
+#[tauri::command]
+pub(crate) async fn switch_backend_mode(
+    mode: BackendMode,
+    force: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BackendModeSwitch, String> {
+    let remote = matches!(mode, BackendMode::Remote);
+    let was_remote = remote_backend::is_remote_mode(&*state).await;
+    let mut stopped_sessions = 0;
+    if remote && !was_remote {
+        let running = list_active_turns_core(&state.services.turn_tracker, None).len();
+        if running > 0 && !force.unwrap_or(false) {
+            return Err(format!(
+                "{running} turn(s) are still running locally. Wait for them to finish or switch with force to stop them."
+            ));
+        }
+        stopped_sessions = disconnect_all_sessions_core(&state.sessions).await;
+    }
+
+    let mut settings = state.app_settings.lock().await.clone();
+    settings.backend_mode = mode.clone();
+    let updated = update_app_settings_core(
+        settings,
+        &state.app_settings,
+        &state.settings_path,
+        &state.services,
+    )
+    .await?;
+    *state.remote_backend.lock().await = None;
+    state.remote_supervisor.backend_mode_changed(&app, remote);
+    if remote {
+        ensure_remote_runtime_for_settings(&updated, state.clone()).await;
+    }
+
+    // The mode has switched by now; a daemon that is not up yet should not
+    // undo that, so a failed read is reported alongside the result.
+    let (workspaces, workspaces_error) = if remote {
+        match remote_backend::call_remote(&*state, app.clone(), "list_workspaces", json!({}))
+            .await
+            .and_then(|response| serde_json::from_value(response).map_err(|err| err.to_string()))
+        {
+            Ok(workspaces) => (workspaces, None),
+            Err(error) => (Vec::new(), Some(error)),
+        }
+    } else {
+        (
+            list_workspaces_core(&state.workspaces, &state.sessions).await,
+            None,
+        )
+    };
+    TauriEventSink::new(app).emit_app_server_event(AppServerEvent {
+        workspace_id: String::new(),
+        message: json!({
+            "method": "backend/modeChanged",
+            "params": {
+                "mode": mode,
+                "stoppedSessions": stopped_sessions,
+                "workspaces": workspaces,
+                "workspacesError": workspaces_error,
+            }
+        }),
+    });
+    Ok(BackendModeSwitch {
+        mode,
+        stopped_sessions,
+        workspaces,
+        workspaces_error,
+    })
+}
+
 fn should_reset_remote_backend(previous: &AppSettings, updated: &AppSettings) -> bool {
     let backend_mode_changed = !matches!(
         (&previous.backend_mode, &updated.backend_mode),
@@ -54,6 +257,7 @@ fn should_reset_remote_backend(previous: &AppSettings, updated: &AppSettings) ->
         || previous.remote_backend_provider != updated.remote_backend_provider
         || previous.remote_backend_host != updated.remote_backend_host
         || previous.remote_backend_token != updated.remote_backend_token
+        || remote_backend::transport_changed(previous, updated)
 }
 
 async fn ensure_remote_runtime_for_settings(settings: &AppSettings, state: State<'_, AppState>) {
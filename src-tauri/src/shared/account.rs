@@ -1,7 +1,7 @@
 use base64::Engine;
 use serde_json::{Map, Value};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug)]
 pub(crate) struct AuthAccount {
@@ -99,6 +99,69 @@ pub(crate) fn read_auth_account(codex_home: Option<PathBuf>) -> Option<AuthAccou
     })
 }
 
+/// Trims a pasted API key and rejects values that cannot be a key.
+pub(crate) fn normalize_api_key(api_key: &str) -> Result<String, String> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err("API key is empty.".to_string());
+    }
+    if !api_key.chars().all(|ch| ch.is_ascii_graphic()) {
+        return Err("API key contains spaces or non-ASCII characters.".to_string());
+    }
+    Ok(api_key.to_string())
+}
+
+/// Replaces `auth.json` with an API-key login, the same shape Codex writes
+/// for `codex login --with-api-key`. Any ChatGPT tokens are dropped.
+pub(crate) fn write_api_key_auth(codex_home: &Path, api_key: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(codex_home)
+        .map_err(|err| format!("Failed to create {}: {err}", codex_home.display()))?;
+    let auth_path = codex_home.join("auth.json");
+    let contents = serde_json::to_vec_pretty(&serde_json::json!({
+        "OPENAI_API_KEY": api_key,
+        "tokens": null,
+        "last_refresh": null,
+    }))
+    .map_err(|err| err.to_string())?;
+    fs::write(&auth_path, contents)
+        .map_err(|err| format!("Failed to write {}: {err}", auth_path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&auth_path, fs::Permissions::from_mode(0o600))
+            .map_err(|err| format!("Failed to restrict {}: {err}", auth_path.display()))?;
+    }
+    Ok(auth_path)
+}
+
+/// Copies a real `auth.json` to `auth.json.bak` before a logout, so a
+/// mistaken logout can be undone by hand. A symlinked (shared) login is not
+/// backed up: its target stays where it is.
+pub(crate) fn backup_auth_file(codex_home: &Path) -> Result<Option<PathBuf>, String> {
+    let auth_path = codex_home.join("auth.json");
+    match fs::symlink_metadata(&auth_path) {
+        Ok(meta) if meta.file_type().is_file() => {}
+        Ok(_) => return Ok(None),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Failed to inspect {}: {err}", auth_path.display())),
+    }
+    let backup_path = codex_home.join("auth.json.bak");
+    fs::copy(&auth_path, &backup_path)
+        .map_err(|err| format!("Failed to back up {}: {err}", auth_path.display()))?;
+    Ok(Some(backup_path))
+}
+
+/// Deletes `auth.json`, unlinking rather than following a symlink. Returns
+/// whether there was anything to delete.
+pub(crate) fn remove_auth_file(codex_home: &Path) -> Result<bool, String> {
+    let auth_path = codex_home.join("auth.json");
+    match fs::remove_file(&auth_path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(format!("Failed to remove {}: {err}", auth_path.display())),
+    }
+}
+
 fn extract_account_map(value: &Value) -> Option<Map<String, Value>> {
     let account = value
         .get("account")
@@ -132,10 +195,13 @@ fn extract_requires_openai_auth(value: &Value) -> Option<bool> {
 }
 
 fn decode_jwt_payload(token: &str) -> Option<Value> {
-    let payload = token.split('.').nth(1)?;
+    let payload = token.trim().split('.').nth(1)?;
+    // JWTs use unpadded base64url, but some issuers pad or use the standard
+    // alphabet; strip padding so one decoder handles both alphabets.
+    let payload = payload.trim_end_matches('=');
     let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
         .decode(payload.as_bytes())
-        .or_else(|_| base64::engine::general_purpose::URL_SAFE.decode(payload.as_bytes()))
+        .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(payload.as_bytes()))
         .ok()?;
     serde_json::from_slice(&decoded).ok()
 }
@@ -167,6 +233,47 @@ mod tests {
             .unwrap_or_default()
     }
 
+    fn encode_token(payload: &Value) -> String {
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(payload).expect("serialize payload"));
+        format!("header.{encoded}.signature")
+    }
+
+    #[test]
+    fn decode_jwt_payload_accepts_padded_segments() {
+        let payload = json!({ "email": "a@example.com" });
+        let encoded = base64::engine::general_purpose::URL_SAFE
+            .encode(serde_json::to_vec(&payload).expect("serialize payload"));
+        let token = format!("header.{encoded}.signature");
+
+        assert_eq!(decode_jwt_payload(&token), Some(payload));
+        assert_eq!(decode_jwt_payload("not-a-jwt"), None);
+    }
+
+    #[test]
+    fn read_auth_account_reads_nested_claims() {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let codex_home = std::env::temp_dir().join(format!("codex-monitor-account-{nonce}"));
+        fs::create_dir_all(&codex_home).expect("create codex home");
+        let token = encode_token(&json!({
+            "https://api.openai.com/auth": { "chatgpt_plan_type": "pro" },
+            "https://api.openai.com/profile": { "email": " user@example.com " }
+        }));
+        fs::write(
+            codex_home.join("auth.json"),
+            json!({ "tokens": { "id_token": token } }).to_string(),
+        )
+        .expect("write auth.json");
+
+        let account = read_auth_account(Some(codex_home.clone())).expect("account");
+        assert_eq!(account.email.as_deref(), Some("user@example.com"));
+        assert_eq!(account.plan_type.as_deref(), Some("pro"));
+        let _ = fs::remove_dir_all(&codex_home);
+    }
+
     #[test]
     fn build_account_response_does_not_fallback_for_apikey() {
         let response = Some(json!({
@@ -218,4 +325,63 @@ mod tests {
             Some("plus")
         );
     }
+
+    #[test]
+    fn api_key_auth_replaces_chatgpt_tokens() {
+        assert_eq!(
+            normalize_api_key("  sk-test-123\n").as_deref(),
+            Ok("sk-test-123")
+        );
+        assert!(normalize_api_key("   ").is_err());
+        assert!(normalize_api_key("sk-test 123").is_err());
+
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let codex_home = std::env::temp_dir().join(format!("codex-monitor-api-key-{nonce}"));
+        fs::create_dir_all(&codex_home).expect("create codex home");
+        fs::write(
+            codex_home.join("auth.json"),
+            json!({ "tokens": { "id_token": "header.payload.signature" } }).to_string(),
+        )
+        .expect("write auth.json");
+
+        let auth_path = write_api_key_auth(&codex_home, "sk-test-123").expect("write api key");
+        let auth: Value =
+            serde_json::from_slice(&fs::read(&auth_path).expect("read auth.json")).expect("json");
+        assert_eq!(auth["OPENAI_API_KEY"], "sk-test-123");
+        assert!(auth["tokens"].is_null());
+        assert!(read_auth_account(Some(codex_home.clone())).is_none());
+        let _ = fs::remove_dir_all(&codex_home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn logout_unlinks_shared_logins_and_backs_up_own_ones() {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("codex-monitor-logout-{nonce}"));
+        let shared = root.join("default");
+        let isolated = root.join("isolated");
+        fs::create_dir_all(&shared).expect("create shared");
+        fs::create_dir_all(&isolated).expect("create isolated");
+        fs::write(shared.join("auth.json"), "{}").expect("write auth.json");
+        std::os::unix::fs::symlink(shared.join("auth.json"), isolated.join("auth.json"))
+            .expect("link auth.json");
+
+        assert_eq!(backup_auth_file(&isolated), Ok(None));
+        assert_eq!(remove_auth_file(&isolated), Ok(true));
+        assert!(shared.join("auth.json").exists());
+        assert_eq!(remove_auth_file(&isolated), Ok(false));
+
+        let backup = backup_auth_file(&shared)
+            .expect("backup")
+            .expect("backup path");
+        assert_eq!(remove_auth_file(&shared), Ok(true));
+        assert_eq!(fs::read_to_string(backup).expect("read backup"), "{}");
+        let _ = fs::remove_dir_all(&root);
+    }
 }
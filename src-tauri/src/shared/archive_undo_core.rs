@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Notify;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+
+/// How long an archive can be undone when the caller gives no delay.
+pub(crate) const DEFAULT_UNDO_SECONDS: u64 = 5;
+const MAX_UNDO_SECONDS: u64 = 60;
+
+/// A `thread/archive` call that has not been sent yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingArchive {
+    pub(crate) token: String,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) archive_at: u64,
+}
+
+/// Archives waiting out their undo window, by token.
+#[derive(Default)]
+pub(crate) struct PendingArchives {
+    pending: Mutex<HashMap<String, PendingArchive>>,
+    changed: Notify,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl PendingArchives {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, PendingArchive>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Queues the archive. Archiving a thread that is already queued keeps
+    /// the original token and deadline.
+    pub(crate) fn schedule(
+        &self,
+        workspace_id: &str,
+        thread_id: &str,
+        delay_seconds: u64,
+        now: u64,
+    ) -> PendingArchive {
+        let mut pending = self.lock();
+        if let Some(existing) = pending
+            .values()
+            .find(|entry| entry.workspace_id == workspace_id && entry.thread_id == thread_id)
+        {
+            return existing.clone();
+        }
+        let entry = PendingArchive {
+            token: uuid::Uuid::new_v4().to_string(),
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            archive_at: now + delay_seconds.min(MAX_UNDO_SECONDS) * 1000,
+        };
+        pending.insert(entry.token.clone(), entry.clone());
+        drop(pending);
+        self.changed.notify_one();
+        entry
+    }
+
+    /// Cancels a queued archive. Fails once the archive has been sent.
+    pub(crate) fn undo(&self, token: &str) -> Result<PendingArchive, String> {
+        self.lock().remove(token).ok_or_else(|| {
+            "Nothing to undo: the thread was already archived or the token is unknown.".to_string()
+        })
+    }
+
+    /// Removes and returns archives that are due, oldest first. Once taken
+    /// an archive can no longer be undone.
+    fn take_due(&self, now: u64) -> Vec<PendingArchive> {
+        let mut pending = self.lock();
+        let due: Vec<String> = pending
+            .values()
+            .filter(|entry| entry.archive_at <= now)
+            .map(|entry| entry.token.clone())
+            .collect();
+        let mut taken: Vec<_> = due
+            .iter()
+            .filter_map(|token| pending.remove(token))
+            .collect();
+        taken.sort_by_key(|entry| entry.archive_at);
+        taken
+    }
+
+    fn next_due(&self) -> Option<u64> {
+        self.lock().values().map(|entry| entry.archive_at).min()
+    }
+}
+
+pub(crate) fn archive_thread_with_undo_core(
+    registry: &PendingArchives,
+    workspace_id: &str,
+    thread_id: &str,
+    delay_seconds: Option<u64>,
+) -> PendingArchive {
+    registry.schedule(
+        workspace_id,
+        thread_id,
+        delay_seconds.unwrap_or(DEFAULT_UNDO_SECONDS),
+        now_ms(),
+    )
+}
+
+pub(crate) fn undo_archive_core(
+    registry: &PendingArchives,
+    token: &str,
+) -> Result<PendingArchive, String> {
+    registry.undo(token)
+}
+
+/// Sends queued archives once their undo window closes. A failed archive is
+/// reported as `thread/archiveFailed` so clients can bring the thread back.
+pub(crate) async fn run_archive_scheduler<E, A, Fut>(
+    registry: &PendingArchives,
+    event_sink: E,
+    archive: A,
+) where
+    E: EventSink,
+    A: Fn(String, String) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    loop {
+        let changed = registry.changed.notified();
+        for entry in registry.take_due(now_ms()) {
+            if let Err(error) = archive(entry.workspace_id.clone(), entry.thread_id.clone()).await {
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: entry.workspace_id.clone(),
+                    message: json!({
+                        "method": "thread/archiveFailed",
+                        "params": {
+                            "token": entry.token,
+                            "threadId": entry.thread_id,
+                            "error": error,
+                        }
+                    }),
+                });
+            }
+        }
+        match registry.next_due() {
+            Some(archive_at) => {
+                let wait = Duration::from_millis(archive_at.saturating_sub(now_ms()));
+                let _ = tokio::time::timeout(wait, changed).await;
+            }
+            None => changed.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_cancels_until_the_archive_is_taken() {
+        let registry = PendingArchives::default();
+        let first = registry.schedule("ws-1", "t-1", 5, 1_000);
+        assert_eq!(first.archive_at, 6_000);
+        assert_eq!(registry.schedule("ws-1", "t-1", 30, 2_000), first);
+        let second = registry.schedule("ws-1", "t-2", 600, 1_000);
+        assert_eq!(second.archive_at, 1_000 + MAX_UNDO_SECONDS * 1000);
+
+        assert_eq!(registry.undo(&second.token).expect("undo"), second);
+        assert!(registry.undo(&second.token).is_err());
+
+        assert!(registry.take_due(5_999).is_empty());
+        assert_eq!(registry.take_due(6_000), vec![first.clone()]);
+        assert!(registry.undo(&first.token).is_err());
+        assert_eq!(registry.next_due(), None);
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub(crate) const AUTOMATION_DIR: &str = "automation";
+const AUTOMATION_STATE_FILE: &str = "automation.json";
+const SCRIPT_EXTENSION: &str = "rhai";
+const MAX_OPERATIONS: u64 = 200_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 64 * 1024;
+const MAX_ACTIONS_PER_RUN: usize = 16;
+
+/// Events scripts can react to. Each maps to an optional script function that
+/// receives the event as a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AutomationHook {
+    TurnCompleted,
+    ApprovalRequest,
+}
+
+impl AutomationHook {
+    pub(crate) fn function_name(self) -> &'static str {
+        match self {
+            Self::TurnCompleted => "on_turn_completed",
+            Self::ApprovalRequest => "on_approval_request",
+        }
+    }
+
+    pub(crate) fn from_message(message: &Value) -> Option<Self> {
+        let method = message.get("method").and_then(Value::as_str)?;
+        if method == "turn/completed" {
+            return Some(Self::TurnCompleted);
+        }
+        // Approval requests are server requests, so they carry an id.
+        if method.ends_with("requestApproval") && message.get("id").is_some() {
+            return Some(Self::ApprovalRequest);
+        }
+        None
+    }
+}
+
+/// Side effects requested by a script. Scripts never act directly; the host
+/// performs these after the script returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum AutomationAction {
+    SendMessage {
+        workspace_id: String,
+        thread_id: String,
+        text: String,
+    },
+    RunTask {
+        workspace_id: String,
+        task: String,
+    },
+    Notify {
+        title: String,
+        body: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AutomationScriptState {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    last_error: Option<String>,
+    #[serde(default)]
+    last_run_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AutomationScriptInfo {
+    pub(crate) id: String,
+    pub(crate) path: String,
+    pub(crate) enabled: bool,
+    pub(crate) last_error: Option<String>,
+    pub(crate) last_run_at: Option<i64>,
+}
+
+fn scripts_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(AUTOMATION_DIR)
+}
+
+fn state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(AUTOMATION_STATE_FILE)
+}
+
+fn read_state(data_dir: &Path) -> Result<HashMap<String, AutomationScriptState>, String> {
+    let path = state_path(data_dir);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn state_sibling(data_dir: &Path, suffix: &str) -> PathBuf {
+    data_dir.join(format!("{AUTOMATION_STATE_FILE}{suffix}"))
+}
+
+/// Applies `update` to the script state and writes it back through a temp
+/// file and a rename. A lock file orders concurrent updates, so runs
+/// recorded at the same time never drop each other's results.
+fn update_state<R>(
+    data_dir: &Path,
+    update: impl FnOnce(&mut HashMap<String, AutomationScriptState>) -> R,
+) -> Result<R, String> {
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(state_sibling(data_dir, ".lock"))
+        .map_err(|e| e.to_string())?;
+    lock.lock()
+        .map_err(|e| format!("Failed to lock {AUTOMATION_STATE_FILE}: {e}"))?;
+
+    let mut state = read_state(data_dir)?;
+    let result = update(&mut state);
+    let data = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    let tmp = state_sibling(data_dir, ".tmp");
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(data.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(error) = written.and_then(|()| fs::rename(&tmp, state_path(data_dir))) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {AUTOMATION_STATE_FILE}: {error}"));
+    }
+    Ok(result)
+}
+
+fn script_path(data_dir: &Path, script_id: &str) -> Result<PathBuf, String> {
+    let valid = !script_id.is_empty()
+        && script_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+        && !script_id.starts_with('.');
+    if !valid {
+        return Err(format!("Invalid automation script id `{script_id}`."));
+    }
+    let path = scripts_dir(data_dir).join(format!("{script_id}.{SCRIPT_EXTENSION}"));
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(format!("Automation script `{script_id}` not found."))
+    }
+}
+
+fn script_info(
+    id: String,
+    path: &Path,
+    state: Option<&AutomationScriptState>,
+) -> AutomationScriptInfo {
+    let state = state.cloned().unwrap_or_default();
+    AutomationScriptInfo {
+        id,
+        path: path.to_string_lossy().to_string(),
+        enabled: state.enabled,
+        last_error: state.last_error,
+        last_run_at: state.last_run_at,
+    }
+}
+
+/// Lists `<data dir>/automation/*.rhai`. New scripts start disabled.
+pub(crate) fn list_automation_scripts(
+    data_dir: &Path,
+) -> Result<Vec<AutomationScriptInfo>, String> {
+    let dir = scripts_dir(data_dir);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let state = read_state(data_dir)?;
+    let mut scripts = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|ext| ext.to_str()) == Some(SCRIPT_EXTENSION)
+        })
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            Some(script_info(id.clone(), &path, state.get(&id)))
+        })
+        .collect::<Vec<_>>();
+    scripts.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(scripts)
+}
+
+pub(crate) fn set_automation_script_enabled(
+    data_dir: &Path,
+    script_id: &str,
+    enabled: bool,
+) -> Result<AutomationScriptInfo, String> {
+    let path = script_path(data_dir, script_id)?;
+    update_state(data_dir, |state| {
+        let entry = state.entry(script_id.to_string()).or_default();
+        entry.enabled = enabled;
+        if enabled {
+            entry.last_error = None;
+        }
+        script_info(script_id.to_string(), &path, Some(entry))
+    })
+}
+
+pub(crate) fn record_automation_run(
+    data_dir: &Path,
+    script_id: &str,
+    error: Option<String>,
+    ran_at: i64,
+) -> Result<(), String> {
+    update_state(data_dir, |state| {
+        let entry = state.entry(script_id.to_string()).or_default();
+        entry.last_run_at = Some(ran_at);
+        entry.last_error = error;
+    })
+}
+
+pub(crate) fn read_automation_script(data_dir: &Path, script_id: &str) -> Result<String, String> {
+    let path = script_path(data_dir, script_id)?;
+    fs::read_to_string(&path).map_err(|e| e.to_string())
+}
+
+fn build_engine(actions: &Arc<Mutex<Vec<AutomationAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_STRING_SIZE)
+        .set_max_array_size(1024)
+        .set_max_map_size(1024);
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+
+    let push = {
+        let actions = Arc::clone(actions);
+        move |action: AutomationAction| {
+            let mut actions = actions
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if actions.len() < MAX_ACTIONS_PER_RUN {
+                actions.push(action);
+            }
+        }
+    };
+    let send = push.clone();
+    engine.register_fn(
+        "send_message",
+        move |workspace_id: &str, thread_id: &str, text: &str| {
+            send(AutomationAction::SendMessage {
+                workspace_id: workspace_id.to_string(),
+                thread_id: thread_id.to_string(),
+                text: text.to_string(),
+            })
+        },
+    );
+    let run = push.clone();
+    engine.register_fn("run_task", move |workspace_id: &str, task: &str| {
+        run(AutomationAction::RunTask {
+            workspace_id: workspace_id.to_string(),
+            task: task.to_string(),
+        })
+    });
+    engine.register_fn("notify", move |title: &str, body: &str| {
+        push(AutomationAction::Notify {
+            title: title.to_string(),
+            body: body.to_string(),
+        })
+    });
+    engine
+}
+
+fn defines_hook(ast: &AST, hook: AutomationHook) -> bool {
+    ast.iter_functions()
+        .any(|function| function.name == hook.function_name() && function.params.len() == 1)
+}
+
+/// Builds the map handed to a hook function.
+pub(crate) fn hook_event(hook: AutomationHook, workspace_id: &str, message: &Value) -> Value {
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let thread_id = params
+        .get("threadId")
+        .or_else(|| params.get("thread_id"))
+        .or_else(|| params.get("turn").and_then(|turn| turn.get("threadId")))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    json!({
+        "hook": hook.function_name(),
+        "workspaceId": workspace_id,
+        "threadId": thread_id,
+        "method": message.get("method").cloned().unwrap_or(Value::Null),
+        "params": params,
+    })
+}
+
+/// Runs `hook` in `source` against `event` and returns the requested actions.
+/// Scripts that do not define the hook function return no actions.
+pub(crate) fn run_automation_hook(
+    source: &str,
+    hook: AutomationHook,
+    event: &Value,
+) -> Result<Vec<AutomationAction>, String> {
+    let actions = Arc::new(Mutex::new(Vec::new()));
+    let engine = build_engine(&actions);
+    let ast = engine.compile(source).map_err(|err| err.to_string())?;
+    if !defines_hook(&ast, hook) {
+        return Ok(Vec::new());
+    }
+    let event = rhai::serde::to_dynamic(event).map_err(|err| err.to_string())?;
+    let _: Dynamic = engine
+        .call_fn(&mut Scope::new(), &ast, hook.function_name(), (event,))
+        .map_err(|err| err.to_string())?;
+    let actions = actions
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .drain(..)
+        .collect();
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_data_dir() -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("codex-monitor-automation-{nonce}"));
+        fs::create_dir_all(dir.join(AUTOMATION_DIR)).expect("create automation dir");
+        dir
+    }
+
+    #[test]
+    fn hook_collects_actions_from_script() {
+        let source = r#"
+            fn on_turn_completed(event) {
+                if event.params.turn.status == "failed" {
+                    notify("Turn failed", event.threadId);
+                    send_message(event.workspaceId, event.threadId, "Please retry.");
+                }
+            }
+        "#;
+        let message = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "thread-1", "turn": { "id": "turn-1", "status": "failed" } }
+        });
+        let hook = AutomationHook::from_message(&message).expect("hook");
+        let event = hook_event(hook, "ws-1", &message);
+
+        let actions = run_automation_hook(source, hook, &event).expect("run hook");
+        assert_eq!(
+            actions,
+            vec![
+                AutomationAction::Notify {
+                    title: "Turn failed".to_string(),
+                    body: "thread-1".to_string(),
+                },
+                AutomationAction::SendMessage {
+                    workspace_id: "ws-1".to_string(),
+                    thread_id: "thread-1".to_string(),
+                    text: "Please retry.".to_string(),
+                },
+            ]
+        );
+        assert!(
+            run_automation_hook(source, AutomationHook::ApprovalRequest, &event)
+                .expect("missing hook")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn hook_reports_runaway_scripts() {
+        let source = "fn on_turn_completed(event) { loop {} }";
+        let error = run_automation_hook(source, AutomationHook::TurnCompleted, &json!({}))
+            .expect_err("operation limit");
+        assert!(error.contains("Too many operations"), "{error}");
+    }
+
+    #[test]
+    fn scripts_start_disabled_and_persist_state() {
+        let data_dir = temp_data_dir();
+        fs::write(data_dir.join(AUTOMATION_DIR).join("notify.rhai"), "").expect("write");
+        fs::write(data_dir.join(AUTOMATION_DIR).join("notes.txt"), "").expect("write");
+
+        let scripts = list_automation_scripts(&data_dir).expect("list");
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts[0].id, "notify");
+        assert!(!scripts[0].enabled);
+
+        set_automation_script_enabled(&data_dir, "notify", true).expect("enable");
+        record_automation_run(&data_dir, "notify", Some("boom".to_string()), 42).expect("record");
+        let scripts = list_automation_scripts(&data_dir).expect("list");
+        assert!(scripts[0].enabled);
+        assert_eq!(scripts[0].last_error.as_deref(), Some("boom"));
+        assert_eq!(scripts[0].last_run_at, Some(42));
+
+        assert!(set_automation_script_enabled(&data_dir, "../notify", true).is_err());
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn concurrent_runs_are_all_recorded() {
+        let data_dir = temp_data_dir();
+        let recorders: Vec<_> = (0..8)
+            .map(|index| {
+                let data_dir = data_dir.clone();
+                std::thread::spawn(move || {
+                    for round in 0..5 {
+                        record_automation_run(&data_dir, &format!("script-{index}"), None, round)
+                            .expect("record");
+                    }
+                })
+            })
+            .collect();
+        for recorder in recorders {
+            recorder.join().expect("recorder thread");
+        }
+
+        let state = read_state(&data_dir).expect("read state");
+        assert_eq!(state.len(), 8);
+        assert!(state.values().all(|entry| entry.last_run_at == Some(4)));
+        assert!(!state_sibling(&data_dir, ".tmp").exists());
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+}
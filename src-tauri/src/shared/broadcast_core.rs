@@ -0,0 +1,538 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::shared::codex_core;
+use crate::shared::merge_queue_core::{self, MergeCandidate, MergeOutcome};
+use crate::shared::thread_tags_core::{self, ThreadTagStore};
+use crate::types::{LosingWorktreeRetention, WorkspaceEntry, WorktreeResult};
+
+const MAX_BROADCAST_WORKSPACES: usize = 50;
+/// Oldest runs are dropped past this so the file stays small.
+const MAX_RECORDED_RUNS: usize = 200;
+/// Added to the threads of a broadcast's losing runs.
+pub(crate) const LOSING_THREAD_TAG: &str = "broadcast-lost";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BroadcastStage {
+    CreatingWorktree,
+    Starting,
+    Started,
+    Failed,
+}
+
+/// The outcome for one requested workspace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BroadcastRunResult {
+    pub(crate) workspace_id: String,
+    /// The worktree created for this run, when the broadcast asked for one.
+    pub(crate) worktree_workspace_id: Option<String>,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) turn_id: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BroadcastRun {
+    pub(crate) broadcast_id: String,
+    pub(crate) results: Vec<BroadcastRunResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct RecordedRun {
+    #[serde(flatten)]
+    run: BroadcastRun,
+    #[serde(default)]
+    winner_workspace_id: Option<String>,
+}
+
+/// Broadcast runs persisted across restarts, oldest first, so a winner can
+/// be selected long after the run started.
+pub(crate) struct BroadcastRunStore {
+    path: PathBuf,
+    runs: Mutex<Vec<RecordedRun>>,
+}
+
+impl BroadcastRunStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let runs = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            runs: Mutex::new(runs),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut Vec<RecordedRun>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut runs = self
+            .runs
+            .lock()
+            .map_err(|_| "broadcast run store is poisoned".to_string())?;
+        let result = apply(&mut runs)?;
+        let excess = runs.len().saturating_sub(MAX_RECORDED_RUNS);
+        runs.drain(..excess);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(&*runs).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    pub(crate) fn record(&self, run: &BroadcastRun) -> Result<(), String> {
+        self.update(|runs| {
+            runs.push(RecordedRun {
+                run: run.clone(),
+                winner_workspace_id: None,
+            });
+            Ok(())
+        })
+    }
+
+    fn get(&self, broadcast_id: &str) -> Option<RecordedRun> {
+        let runs = self.runs.lock().ok()?;
+        runs.iter()
+            .find(|recorded| recorded.run.broadcast_id == broadcast_id)
+            .cloned()
+    }
+
+    fn set_winner(&self, broadcast_id: &str, worktree_id: &str) -> Result<(), String> {
+        self.update(|runs| {
+            let recorded = runs
+                .iter_mut()
+                .find(|recorded| recorded.run.broadcast_id == broadcast_id)
+                .ok_or_else(|| format!("Unknown broadcast run: {broadcast_id}"))?;
+            recorded.winner_workspace_id = Some(worktree_id.to_string());
+            Ok(())
+        })
+    }
+}
+
+/// What `select_winner` merged and cleaned up. Cleanup of one losing run
+/// failing does not stop the others; the failures are listed in `errors`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WinnerSelection {
+    pub(crate) broadcast_id: String,
+    pub(crate) winner_workspace_id: String,
+    /// The parent's branch the winner was merged into.
+    pub(crate) base_branch: String,
+    pub(crate) archived_thread_ids: Vec<String>,
+    pub(crate) removed_worktree_ids: Vec<String>,
+    pub(crate) errors: Vec<String>,
+}
+
+/// Ordered, de-duplicated workspace ids; fails on unknown ids so a typo does
+/// not silently shrink the broadcast.
+pub(crate) fn broadcast_targets(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    workspace_ids: &[String],
+) -> Result<Vec<String>, String> {
+    let mut seen = HashSet::new();
+    let targets: Vec<String> = workspace_ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect();
+    if targets.is_empty() {
+        return Err("workspaceIds must not be empty".to_string());
+    }
+    if targets.len() > MAX_BROADCAST_WORKSPACES {
+        return Err(format!(
+            "A broadcast reaches at most {MAX_BROADCAST_WORKSPACES} workspaces"
+        ));
+    }
+    let unknown: Vec<&str> = targets
+        .iter()
+        .filter(|id| !workspaces.contains_key(*id))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("Unknown workspaces: {}", unknown.join(", ")));
+    }
+    Ok(targets)
+}
+
+struct Progress<'a, E> {
+    event_sink: &'a E,
+    broadcast_id: &'a str,
+    total: usize,
+    started: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl<E: EventSink> Progress<'_, E> {
+    fn emit(&self, result: &BroadcastRunResult, stage: BroadcastStage) {
+        match stage {
+            BroadcastStage::Started => {
+                self.started.fetch_add(1, Ordering::SeqCst);
+            }
+            BroadcastStage::Failed => {
+                self.failed.fetch_add(1, Ordering::SeqCst);
+            }
+            BroadcastStage::CreatingWorktree | BroadcastStage::Starting => {}
+        }
+        self.event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: result.workspace_id.clone(),
+            message: json!({
+                "method": "broadcast/progress",
+                "params": {
+                    "broadcastId": self.broadcast_id,
+                    "workspaceId": result.workspace_id,
+                    "worktreeWorkspaceId": result.worktree_workspace_id,
+                    "threadId": result.thread_id,
+                    "stage": stage,
+                    "error": result.error,
+                    "started": self.started.load(Ordering::SeqCst),
+                    "failed": self.failed.load(Ordering::SeqCst),
+                    "total": self.total,
+                }
+            }),
+        });
+    }
+}
+
+/// Starts a thread with the same first message in every workspace, or in a
+/// new worktree of each when `worktree_branch` is set. Workspaces run
+/// concurrently (turns still respect the run queue) and a failure in one does
+/// not stop the others; each stage is reported as `broadcast/progress`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn broadcast_run_core<E, C, FutC, W, FutW>(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event_sink: &E,
+    workspace_ids: Vec<String>,
+    text: String,
+    model: Option<String>,
+    worktree_branch: Option<String>,
+    connect: C,
+    add_worktree: W,
+) -> Result<BroadcastRun, String>
+where
+    E: EventSink,
+    C: Fn(String) -> FutC,
+    FutC: Future<Output = Result<(), String>>,
+    W: Fn(String, String) -> FutW,
+    FutW: Future<Output = Result<WorktreeResult, String>>,
+{
+    if text.trim().is_empty() {
+        return Err("Broadcast message must not be empty".to_string());
+    }
+    let worktree_branch = worktree_branch
+        .map(|branch| branch.trim().to_string())
+        .filter(|branch| !branch.is_empty());
+    let targets = broadcast_targets(&*workspaces.read().await, &workspace_ids)?;
+    let broadcast_id = uuid::Uuid::new_v4().to_string();
+    let progress = Progress {
+        event_sink,
+        broadcast_id: &broadcast_id,
+        total: targets.len(),
+        started: AtomicUsize::new(0),
+        failed: AtomicUsize::new(0),
+    };
+
+    let runs = targets.into_iter().map(|workspace_id| {
+        let progress = &progress;
+        let connect = &connect;
+        let add_worktree = &add_worktree;
+        let text = text.clone();
+        let model = model.clone();
+        let worktree_branch = worktree_branch.clone();
+        async move {
+            let mut result = BroadcastRunResult {
+                workspace_id: workspace_id.clone(),
+                ..BroadcastRunResult::default()
+            };
+            let outcome: Result<(), String> = async {
+                let mut target_id = workspace_id.clone();
+                if let Some(branch) = worktree_branch {
+                    progress.emit(&result, BroadcastStage::CreatingWorktree);
+                    let worktree = add_worktree(workspace_id.clone(), branch).await?;
+                    result.worktree_workspace_id = Some(worktree.workspace.id.clone());
+                    target_id = worktree.workspace.id;
+                }
+                progress.emit(&result, BroadcastStage::Starting);
+                connect(target_id.clone()).await?;
+                let thread =
+                    codex_core::start_thread_core(sessions, workspaces, target_id.clone()).await?;
+                let thread_id = codex_core::response_object_id(&thread, "thread")
+                    .ok_or_else(|| "thread/start did not return a thread id".to_string())?;
+                result.thread_id = Some(thread_id.clone());
+                let turn = codex_core::send_user_message_core(
+                    sessions, workspaces, target_id, thread_id, text, model, None, None, None,
+                    None, None, None,
+                )
+                .await?;
+                result.turn_id = codex_core::response_object_id(&turn, "turn");
+                Ok(())
+            }
+            .await;
+            match outcome {
+                Ok(()) => progress.emit(&result, BroadcastStage::Started),
+                Err(error) => {
+                    result.error = Some(error);
+                    progress.emit(&result, BroadcastStage::Failed);
+                }
+            }
+            result
+        }
+    });
+    let results = join_all(runs).await;
+    Ok(BroadcastRun {
+        broadcast_id,
+        results,
+    })
+}
+
+/// The runs that lost to `worktree_id`; fails when the worktree is not one
+/// this broadcast created.
+fn losing_results(
+    run: &BroadcastRun,
+    worktree_id: &str,
+) -> Result<Vec<BroadcastRunResult>, String> {
+    let is_winner =
+        |result: &BroadcastRunResult| result.worktree_workspace_id.as_deref() == Some(worktree_id);
+    if !run.results.iter().any(is_winner) {
+        return Err(format!(
+            "Worktree {worktree_id} is not part of broadcast run {}",
+            run.broadcast_id
+        ));
+    }
+    Ok(run
+        .results
+        .iter()
+        .filter(|result| !is_winner(result))
+        .cloned()
+        .collect())
+}
+
+/// Adds [`LOSING_THREAD_TAG`] to the thread, keeping its other tags.
+fn label_losing_thread(
+    thread_tags: &ThreadTagStore,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<(), String> {
+    let mut tags = thread_tags_core::list_thread_annotations_core(thread_tags, workspace_id)
+        .remove(thread_id)
+        .map(|entry| entry.tags)
+        .unwrap_or_default();
+    tags.push(LOSING_THREAD_TAG.to_string());
+    thread_tags_core::tag_thread_core(
+        thread_tags,
+        workspace_id.to_string(),
+        thread_id.to_string(),
+        tags,
+    )
+    .map(|_| ())
+}
+
+/// Merges the winning worktree of a broadcast into its parent the way the
+/// merge queue does (rebase, test command, fast-forward), then labels and
+/// archives every losing thread and, unless `retention` keeps them, removes
+/// the losing worktrees. Nothing is cleaned up when the merge fails.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn select_winner_core<A, FutA, R, FutR>(
+    runs: &BroadcastRunStore,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    thread_tags: &ThreadTagStore,
+    retention: LosingWorktreeRetention,
+    run_id: String,
+    worktree_id: String,
+    archive_thread: A,
+    remove_worktree: R,
+) -> Result<WinnerSelection, String>
+where
+    A: Fn(String, String) -> FutA,
+    FutA: Future<Output = Result<(), String>>,
+    R: Fn(String) -> FutR,
+    FutR: Future<Output = Result<(), String>>,
+{
+    let recorded = runs
+        .get(&run_id)
+        .ok_or_else(|| format!("Unknown broadcast run: {run_id}"))?;
+    if let Some(winner) = recorded.winner_workspace_id {
+        return Err(format!(
+            "Broadcast run {run_id} already has a winner: {winner}"
+        ));
+    }
+    let losers = losing_results(&recorded.run, &worktree_id)?;
+
+    let candidate = {
+        let workspaces = workspaces.read().await;
+        let worktree = workspaces
+            .get(&worktree_id)
+            .cloned()
+            .ok_or_else(|| format!("Worktree {worktree_id} no longer exists"))?;
+        let branch = worktree
+            .worktree
+            .as_ref()
+            .map(|info| info.branch.clone())
+            .ok_or_else(|| format!("Workspace {worktree_id} is not a worktree"))?;
+        let parent = worktree
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned()
+            .ok_or_else(|| format!("Parent of worktree {worktree_id} no longer exists"))?;
+        MergeCandidate {
+            worktree,
+            parent,
+            branch,
+        }
+    };
+    let base_branch = match merge_queue_core::rebase_test_and_merge(&candidate, |_| {}).await? {
+        MergeOutcome::Merged { base_branch } => base_branch,
+        MergeOutcome::Conflict { detail } => {
+            return Err(format!(
+                "{} conflicts with the parent branch: {detail}",
+                candidate.branch
+            ))
+        }
+    };
+    runs.set_winner(&run_id, &worktree_id)?;
+
+    let mut selection = WinnerSelection {
+        broadcast_id: run_id,
+        winner_workspace_id: worktree_id,
+        base_branch,
+        ..WinnerSelection::default()
+    };
+    // Threads are archived before their worktree goes, while the session
+    // that owns them is still there.
+    for loser in &losers {
+        let Some(thread_id) = loser.thread_id.clone() else {
+            continue;
+        };
+        let workspace_id = loser
+            .worktree_workspace_id
+            .clone()
+            .unwrap_or_else(|| loser.workspace_id.clone());
+        if let Err(error) = label_losing_thread(thread_tags, &workspace_id, &thread_id) {
+            selection
+                .errors
+                .push(format!("Failed to label thread {thread_id}: {error}"));
+        }
+        match archive_thread(workspace_id, thread_id.clone()).await {
+            Ok(()) => selection.archived_thread_ids.push(thread_id),
+            Err(error) => selection
+                .errors
+                .push(format!("Failed to archive thread {thread_id}: {error}")),
+        }
+    }
+    if retention == LosingWorktreeRetention::Remove {
+        for worktree_id in losers
+            .into_iter()
+            .filter_map(|loser| loser.worktree_workspace_id)
+        {
+            match remove_worktree(worktree_id.clone()).await {
+                Ok(()) => selection.removed_worktree_ids.push(worktree_id),
+                Err(error) => selection
+                    .errors
+                    .push(format!("Failed to remove worktree {worktree_id}: {error}")),
+            }
+        }
+    }
+    Ok(selection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+
+    fn entry(id: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/repos/{id}"),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn targets_are_deduplicated_and_validated() {
+        let workspaces =
+            HashMap::from([("a".to_string(), entry("a")), ("b".to_string(), entry("b"))]);
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            broadcast_targets(&workspaces, &ids(&["b", " a", "b", ""])),
+            Ok(ids(&["b", "a"]))
+        );
+        assert_eq!(
+            broadcast_targets(&workspaces, &ids(&["a", "missing"])),
+            Err("Unknown workspaces: missing".to_string())
+        );
+        assert!(broadcast_targets(&workspaces, &[]).is_err());
+    }
+
+    fn result(workspace_id: &str, worktree_id: Option<&str>) -> BroadcastRunResult {
+        BroadcastRunResult {
+            workspace_id: workspace_id.to_string(),
+            worktree_workspace_id: worktree_id.map(str::to_string),
+            thread_id: Some(format!("thread-{workspace_id}")),
+            ..BroadcastRunResult::default()
+        }
+    }
+
+    #[test]
+    fn losers_are_every_run_but_the_chosen_worktree() {
+        let run = BroadcastRun {
+            broadcast_id: "run".to_string(),
+            results: vec![
+                result("a", Some("wt-a")),
+                result("b", Some("wt-b")),
+                result("c", None),
+            ],
+        };
+        let losers = losing_results(&run, "wt-b").expect("winner is in the run");
+        assert_eq!(losers, vec![result("a", Some("wt-a")), result("c", None)]);
+        assert_eq!(
+            losing_results(&run, "a"),
+            Err("Worktree a is not part of broadcast run run".to_string())
+        );
+    }
+
+    #[test]
+    fn recorded_runs_and_winners_survive_reload() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("broadcast-runs.json");
+        let run = BroadcastRun {
+            broadcast_id: "run".to_string(),
+            results: vec![result("a", Some("wt-a"))],
+        };
+        let store = BroadcastRunStore::load(path.clone());
+        store.record(&run).expect("record run");
+        store.set_winner("run", "wt-a").expect("set winner");
+        assert!(store.set_winner("missing", "wt-a").is_err());
+
+        let recorded = BroadcastRunStore::load(path)
+            .get("run")
+            .expect("run is persisted");
+        assert_eq!(recorded.run, run);
+        assert_eq!(recorded.winner_workspace_id.as_deref(), Some("wt-a"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::backend::app_server::check_codex_installation;
+use crate::types::AppSettings;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlatformInfo {
+    /// `std::env::consts::OS`, e.g. `macos`, `linux`, `windows`, `ios`.
+    pub(crate) os: String,
+    pub(crate) arch: String,
+    pub(crate) mobile: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexCapabilities {
+    pub(crate) installed: bool,
+    pub(crate) version: Option<String>,
+    /// Why the CLI could not be run, when it could not.
+    pub(crate) error: Option<String>,
+    pub(crate) steer: bool,
+    pub(crate) collaboration_modes: bool,
+    pub(crate) unified_exec: bool,
+    pub(crate) apps: bool,
+}
+
+/// What the machine running the Codex sessions can do: this process in
+/// local mode, the daemon in remote mode.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HostCapabilities {
+    pub(crate) version: String,
+    pub(crate) platform: PlatformInfo,
+    pub(crate) codex: CodexCapabilities,
+}
+
+pub(crate) fn platform_info() -> PlatformInfo {
+    PlatformInfo {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        mobile: cfg!(any(target_os = "ios", target_os = "android")),
+    }
+}
+
+pub(crate) async fn host_capabilities_core(app_settings: &Mutex<AppSettings>) -> HostCapabilities {
+    let settings = app_settings.lock().await.clone();
+    let (installed, version, error) = match check_codex_installation(settings.codex_bin).await {
+        Ok(version) => (true, version, None),
+        Err(error) => (false, None, Some(error)),
+    };
+    HostCapabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: platform_info(),
+        codex: CodexCapabilities {
+            installed,
+            version,
+            error,
+            // Features need a working CLI as well as the setting.
+            steer: installed && settings.steer_enabled,
+            collaboration_modes: installed && settings.collaboration_modes_enabled,
+            unified_exec: installed && settings.unified_exec_enabled,
+            apps: installed && settings.experimental_apps_enabled,
+        },
+    }
+}
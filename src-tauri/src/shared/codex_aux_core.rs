@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::time::timeout;
 
 use crate::backend::app_server::{
@@ -393,8 +393,8 @@ pub(crate) async fn codex_doctor_core(
 }
 
 pub(crate) async fn run_background_prompt_core<F>(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     prompt: String,
     model: Option<&str>,
@@ -406,12 +406,12 @@ where
     F: Fn(&str, &str),
 {
     let workspace_path = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
         entry.path.clone()
     };
     let session = {
-        let sessions = sessions.lock().await;
+        let sessions = sessions.read().await;
         sessions
             .get(&workspace_id)
             .ok_or("workspace not connected")?
@@ -562,8 +562,8 @@ where
 }
 
 pub(crate) async fn generate_commit_message_core<F>(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     diff: &str,
     template: &str,
@@ -588,8 +588,8 @@ where
 }
 
 pub(crate) async fn generate_run_metadata_core<F>(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     prompt: &str,
     on_hide_thread: F,
@@ -619,8 +619,8 @@ where
 }
 
 pub(crate) async fn generate_agent_description_core<F>(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     description: &str,
     on_hide_thread: F,
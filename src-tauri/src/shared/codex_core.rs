@@ -1,26 +1,35 @@
-use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde_json::{json, Map, Value};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 use tokio::sync::oneshot::error::TryRecvError;
-use tokio::sync::{oneshot, Mutex};
+use tokio::sync::{oneshot, Mutex, RwLock};
 use tokio::time::timeout;
 use tokio::time::Instant;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::codex_auth::{same_home, session_codex_home};
+use crate::backend::run_manager::{RunAdmission, StartRun, TurnTicket};
+use crate::backend::services::BackendServices;
+use crate::backend::thread_preferences::resolve_turn_model;
 use crate::codex::config as codex_config;
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::rules;
-use crate::shared::account::{build_account_response, read_auth_account};
-use crate::types::WorkspaceEntry;
+use crate::shared::account::{
+    backup_auth_file, build_account_response, normalize_api_key, read_auth_account,
+    remove_auth_file, write_api_key_auth,
+};
+use crate::shared::image_prepare_core;
+use crate::shared::slash_commands_core::{preprocess_turn, SlashOutcome, TurnRequest};
+use crate::types::{TurnConcurrencyPolicy, WorkspaceEntry};
 
 const LOGIN_START_TIMEOUT: Duration = Duration::from_secs(30);
-#[allow(dead_code)]
-const MAX_INLINE_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
+/// Any authenticated endpoint works; listing models is cheap and read-only.
+const API_KEY_VALIDATION_URL: &str = "https://api.openai.com/v1/models";
+const API_KEY_VALIDATION_TIMEOUT: Duration = Duration::from_secs(15);
 const THREAD_LIST_SOURCE_KINDS: &[&str] = &[
     "cli",
     "vscode",
@@ -39,76 +48,14 @@ fn image_extension_for_path(path: &str) -> Option<String> {
         .map(|value| value.to_ascii_lowercase())
 }
 
-#[allow(dead_code)]
-fn image_mime_type_for_path(path: &str) -> Option<&'static str> {
-    let extension = image_extension_for_path(path)?;
-    match extension.as_str() {
-        "png" => Some("image/png"),
-        "jpg" | "jpeg" => Some("image/jpeg"),
-        "gif" => Some("image/gif"),
-        "webp" => Some("image/webp"),
-        "bmp" => Some("image/bmp"),
-        "tiff" | "tif" => Some("image/tiff"),
-        _ => None,
-    }
-}
-
 #[allow(dead_code)]
 fn should_inline_image_path_for_codex(path: &str) -> bool {
     matches!(
         image_extension_for_path(path).as_deref(),
-        Some("heic") | Some("heif")
+        Some("heic") | Some("heif") | Some("avif")
     )
 }
 
-#[cfg(target_os = "macos")]
-fn temp_converted_image_path(path: &str, extension: &str) -> PathBuf {
-    let stem = Path::new(path)
-        .file_stem()
-        .and_then(|value| value.to_str())
-        .unwrap_or("image");
-    let safe_stem = stem
-        .chars()
-        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
-        .collect::<String>();
-    let ts = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|value| value.as_millis())
-        .unwrap_or_default();
-    std::env::temp_dir().join(format!("codex-monitor-image-{safe_stem}-{ts}.{extension}"))
-}
-
-#[cfg(target_os = "macos")]
-fn convert_heif_image_to_jpeg_bytes(path: &str) -> Result<Vec<u8>, String> {
-    let output_path = temp_converted_image_path(path, "jpg");
-    let status = std::process::Command::new("/usr/bin/sips")
-        .args(["-s", "format", "jpeg"])
-        .arg(path)
-        .arg("--out")
-        .arg(&output_path)
-        .status()
-        .map_err(|err| format!("Failed to launch HEIC/HEIF conversion for {path}: {err}"))?;
-    if !status.success() {
-        let _ = std::fs::remove_file(&output_path);
-        return Err(format!(
-            "Failed to convert HEIC/HEIF image into a Codex-compatible JPEG: {path}"
-        ));
-    }
-    let bytes = std::fs::read(&output_path).map_err(|err| {
-        format!(
-            "Failed to read converted JPEG for {path} at {}: {err}",
-            output_path.display()
-        )
-    })?;
-    let _ = std::fs::remove_file(&output_path);
-    if bytes.is_empty() {
-        return Err(format!(
-            "Converted JPEG is empty after HEIC/HEIF conversion: {path}"
-        ));
-    }
-    Ok(bytes)
-}
-
 #[allow(dead_code)]
 pub(crate) fn normalize_file_path(raw: &str) -> String {
     let path = raw.trim();
@@ -156,42 +103,7 @@ pub(crate) fn read_image_as_data_url_core(path: &str) -> Result<String, String>
     if trimmed_path.is_empty() {
         return Err("Image path is required".to_string());
     }
-    if should_inline_image_path_for_codex(&trimmed_path) {
-        #[cfg(target_os = "macos")]
-        {
-            let encoded = STANDARD.encode(convert_heif_image_to_jpeg_bytes(&trimmed_path)?);
-            return Ok(format!("data:image/jpeg;base64,{encoded}"));
-        }
-        #[cfg(not(target_os = "macos"))]
-        {
-            return Err(format!(
-                "HEIC/HEIF images are not supported on this platform; convert to JPEG or PNG first: {trimmed_path}"
-            ));
-        }
-    }
-    let mime_type = image_mime_type_for_path(&trimmed_path).ok_or_else(|| {
-        format!("Unsupported or missing image extension for path: {trimmed_path}")
-    })?;
-    let metadata = std::fs::symlink_metadata(&trimmed_path)
-        .map_err(|err| format!("Failed to stat image file at {trimmed_path}: {err}"))?;
-    if metadata.file_type().is_symlink() {
-        return Err(format!("Image path must not be a symlink: {trimmed_path}"));
-    }
-    if !metadata.is_file() {
-        return Err(format!("Image path is not a file: {trimmed_path}"));
-    }
-    if metadata.len() > MAX_INLINE_IMAGE_BYTES {
-        return Err(format!(
-            "Image file exceeds maximum size of {MAX_INLINE_IMAGE_BYTES} bytes: {trimmed_path}"
-        ));
-    }
-    let bytes = std::fs::read(&trimmed_path)
-        .map_err(|err| format!("Failed to read image file at {trimmed_path}: {err}"))?;
-    if bytes.is_empty() {
-        return Err(format!("Image file is empty: {trimmed_path}"));
-    }
-    let encoded = STANDARD.encode(bytes);
-    Ok(format!("data:{mime_type};base64,{encoded}"))
+    image_prepare_core::image_data_url_core(&trimmed_path)
 }
 
 pub(crate) enum CodexLoginCancelState {
@@ -200,10 +112,10 @@ pub(crate) enum CodexLoginCancelState {
 }
 
 async fn get_session_clone(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: &str,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    let sessions = sessions.lock().await;
+    let sessions = sessions.read().await;
     sessions
         .get(workspace_id)
         .cloned()
@@ -211,10 +123,10 @@ async fn get_session_clone(
 }
 
 async fn resolve_workspace_and_parent(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<(WorkspaceEntry, Option<WorkspaceEntry>), String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .cloned()
@@ -228,7 +140,7 @@ async fn resolve_workspace_and_parent(
 }
 
 async fn resolve_codex_home_for_workspace_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<PathBuf, String> {
     let (entry, parent_entry) = resolve_workspace_and_parent(workspaces, workspace_id).await?;
@@ -238,34 +150,85 @@ async fn resolve_codex_home_for_workspace_core(
 }
 
 async fn resolve_workspace_path_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<String, String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .ok_or_else(|| "workspace not found".to_string())?;
     Ok(entry.path.clone())
 }
 
+async fn resolve_turn_concurrency_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> TurnConcurrencyPolicy {
+    workspaces
+        .read()
+        .await
+        .get(workspace_id)
+        .and_then(|entry| entry.settings.turn_concurrency)
+        .unwrap_or_default()
+}
+
+/// Read-only workspaces get a read-only sandbox whatever access mode the
+/// client asks for.
+async fn workspace_read_only_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> bool {
+    workspaces
+        .read()
+        .await
+        .get(workspace_id)
+        .is_some_and(|entry| entry.settings.read_only)
+}
+
+/// Model, effort and access mode a workspace falls back to when a message
+/// does not specify them.
+async fn resolve_turn_defaults_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> (Option<String>, Option<String>, Option<String>) {
+    let workspaces = workspaces.read().await;
+    let Some(settings) = workspaces.get(workspace_id).map(|entry| &entry.settings) else {
+        return (None, None, None);
+    };
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_ref()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    (
+        non_empty(&settings.default_model),
+        non_empty(&settings.default_effort),
+        non_empty(&settings.default_access_mode),
+    )
+}
+
 pub(crate) async fn start_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
     let workspace_path = resolve_workspace_path_core(workspaces, &workspace_id).await?;
-    let params = json!({
+    let mut params = json!({
         "cwd": workspace_path,
         "approvalPolicy": "on-request"
     });
+    if workspace_read_only_core(workspaces, &workspace_id).await {
+        params["sandbox"] = json!("read-only");
+    }
     session
         .send_request_for_workspace(&workspace_id, "thread/start", params)
         .await
 }
 
 pub(crate) async fn resume_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<Value, String> {
@@ -277,7 +240,7 @@ pub(crate) async fn resume_thread_core(
 }
 
 pub(crate) async fn read_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<Value, String> {
@@ -288,8 +251,21 @@ pub(crate) async fn read_thread_core(
         .await
 }
 
+/// `thread/read` with every turn and item, for transcripts.
+pub(crate) async fn read_thread_turns_core(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let params = json!({ "threadId": thread_id, "includeTurns": true });
+    session
+        .send_request_for_workspace(&workspace_id, "thread/read", params)
+        .await
+}
+
 pub(crate) async fn thread_live_subscribe_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<(), String> {
@@ -301,7 +277,7 @@ pub(crate) async fn thread_live_subscribe_core(
 }
 
 pub(crate) async fn thread_live_unsubscribe_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<(), String> {
@@ -313,7 +289,7 @@ pub(crate) async fn thread_live_unsubscribe_core(
 }
 
 pub(crate) async fn fork_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<Value, String> {
@@ -325,7 +301,7 @@ pub(crate) async fn fork_thread_core(
 }
 
 pub(crate) async fn list_threads_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
@@ -348,7 +324,7 @@ pub(crate) async fn list_threads_core(
 }
 
 pub(crate) async fn list_mcp_server_status_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
@@ -361,31 +337,44 @@ pub(crate) async fn list_mcp_server_status_core(
 }
 
 pub(crate) async fn archive_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
     let params = json!({ "threadId": thread_id });
-    session
+    let response = session
         .send_request_for_workspace(&workspace_id, "thread/archive", params)
-        .await
+        .await?;
+    session
+        .services
+        .thread_preferences
+        .remove_thread(&thread_id);
+    Ok(response)
 }
 
 pub(crate) async fn compact_thread_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
     let params = json!({ "threadId": thread_id });
-    session
-        .send_request_for_workspace(&workspace_id, "thread/compact/start", params)
-        .await
+    let turn = PendingTurn {
+        session,
+        workspace_id,
+        thread_id,
+        method: "thread/compact/start",
+        params,
+        model: None,
+        effort: None,
+    };
+    admit_turn_core(workspaces, turn, "/compact").await
 }
 
 pub(crate) async fn set_thread_name_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
     name: String,
@@ -446,7 +435,13 @@ fn build_turn_input_items(
                 .map(str::trim)
                 .filter(|value| !value.is_empty())
                 .ok_or_else(|| "invalid app mention path".to_string())?;
-            if !path.starts_with("app://") || path.len() <= "app://".len() {
+            // `app://` names an app; an absolute path is a file from
+            // `attach_context`.
+            let valid_path = match path.strip_prefix("app://") {
+                Some(app) => !app.is_empty(),
+                None => Path::new(path).is_absolute(),
+            };
+            if !valid_path {
                 return Err("invalid app mention path".to_string());
             }
             if !seen_paths.insert(path.to_string()) {
@@ -461,6 +456,18 @@ fn build_turn_input_items(
     Ok(input)
 }
 
+/// `<key>.id` from an app-server response such as `thread/start` or
+/// `turn/start`, with or without the `result` wrapper.
+pub(crate) fn response_object_id(response: &Value, key: &str) -> Option<String> {
+    response
+        .get("result")
+        .unwrap_or(response)
+        .get(key)
+        .and_then(|value| value.get("id"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
 pub(crate) fn insert_optional_nullable_string(
     params: &mut Map<String, Value>,
     key: &str,
@@ -472,8 +479,8 @@ pub(crate) fn insert_optional_nullable_string(
 }
 
 pub(crate) async fn send_user_message_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     thread_id: String,
     text: String,
@@ -486,8 +493,46 @@ pub(crate) async fn send_user_message_core(
     collaboration_mode: Option<Value>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
+    session.services.session_quotas.check(&workspace_id)?;
+    let request = TurnRequest {
+        text,
+        model,
+        effort,
+        access_mode,
+    };
+    let TurnRequest {
+        text,
+        model,
+        effort,
+        access_mode,
+    } = match preprocess_turn(request)? {
+        SlashOutcome::Turn(request) => request,
+        SlashOutcome::Review { target } => {
+            return start_review_core(sessions, workspaces, workspace_id, thread_id, target, None)
+                .await;
+        }
+        SlashOutcome::Compact => {
+            return compact_thread_core(sessions, workspaces, workspace_id, thread_id).await;
+        }
+    };
     let workspace_path = resolve_workspace_path_core(workspaces, &workspace_id).await?;
-    let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
+    let (default_model, default_effort, default_access_mode) =
+        resolve_turn_defaults_core(workspaces, &workspace_id).await;
+    let (preferred_model, preferred_effort) = resolve_turn_model(
+        &session.services.thread_preferences,
+        &workspace_id,
+        &thread_id,
+        (default_model, default_effort),
+    );
+    let model = model.or(preferred_model);
+    let effort = effort.or(preferred_effort);
+    let access_mode = if workspace_read_only_core(workspaces, &workspace_id).await {
+        "read-only".to_string()
+    } else {
+        access_mode
+            .or(default_access_mode)
+            .unwrap_or_else(|| "current".to_string())
+    };
     let sandbox_policy = match access_mode.as_str() {
         "full-access" => json!({ "type": "dangerFullAccess" }),
         "read-only" => json!({ "type": "readOnly" }),
@@ -504,6 +549,7 @@ pub(crate) async fn send_user_message_core(
         "on-request"
     };
 
+    let preview = text.clone();
     let input = build_turn_input_items(text, images, app_mentions)?;
 
     let mut params = Map::new();
@@ -520,13 +566,135 @@ pub(crate) async fn send_user_message_core(
             params.insert("collaborationMode".to_string(), mode);
         }
     }
-    session
-        .send_request_for_workspace(&workspace_id, "turn/start", Value::Object(params))
-        .await
+    let turn = PendingTurn {
+        session,
+        workspace_id,
+        thread_id,
+        method: "turn/start",
+        params: Value::Object(params),
+        model,
+        effort,
+    };
+    admit_turn_core(workspaces, turn, &preview).await
+}
+
+/// A request that starts a turn (`turn/start`, `review/start` or
+/// `thread/compact/start`), sent once the run manager admits it.
+#[derive(Clone)]
+struct PendingTurn {
+    session: Arc<WorkspaceSession>,
+    workspace_id: String,
+    thread_id: String,
+    method: &'static str,
+    params: Value,
+    model: Option<String>,
+    effort: Option<String>,
+}
+
+impl PendingTurn {
+    /// Sends the request, freeing the run slot again when it fails.
+    async fn start(self) -> Result<Value, String> {
+        let is_user_turn = self.method == "turn/start";
+        let services = Arc::clone(&self.session.services);
+        if is_user_turn {
+            services
+                .usage_metrics
+                .note_model(&self.thread_id, self.model.as_deref());
+        }
+        let response = self
+            .session
+            .send_request_for_workspace(&self.workspace_id, self.method, self.params)
+            .await;
+        match response.as_ref() {
+            Ok(value) if value.get("error").is_none() => {
+                // A detached review runs on its own thread, so its
+                // `turn/completed` names that thread.
+                if let Some(review_thread_id) = value
+                    .get("result")
+                    .and_then(|result| result.get("reviewThreadId"))
+                    .and_then(Value::as_str)
+                {
+                    services
+                        .run_manager
+                        .rekey_thread(&self.thread_id, review_thread_id);
+                }
+                if is_user_turn {
+                    services.thread_preferences.record_turn(
+                        &self.workspace_id,
+                        &self.thread_id,
+                        self.model,
+                        self.effort,
+                    );
+                }
+            }
+            _ => services.run_manager.release_thread(&self.thread_id),
+        }
+        response
+    }
+}
+
+/// The one admission path for every request that starts a turn. The run
+/// manager applies the rate-limit hold, the workspace's `turnConcurrency` and
+/// `maxConcurrentTurns` together; a turn it queues returns right away with
+/// `queueId` and `runQueuePosition` and is sent once admitted.
+async fn admit_turn_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    turn: PendingTurn,
+    preview: &str,
+) -> Result<Value, String> {
+    let policy = resolve_turn_concurrency_core(workspaces, &turn.workspace_id).await;
+    let start: StartRun = {
+        let turn = turn.clone();
+        Box::new(move || {
+            Box::pin(async move {
+                let response = turn.start().await?;
+                match response.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("request failed")
+                        .to_string()),
+                    None => Ok(()),
+                }
+            })
+        })
+    };
+    let ticket = TurnTicket {
+        workspace_id: &turn.workspace_id,
+        thread_id: &turn.thread_id,
+        text: preview,
+        policy,
+    };
+    let run_manager = &turn.session.services.run_manager;
+    let rate_limit_warning = match run_manager.admit(ticket, start)? {
+        RunAdmission::Started { rate_limit_warning } => rate_limit_warning,
+        RunAdmission::Queued(run) => {
+            // `queue/position` events follow the run until it starts.
+            return Ok(json!({
+                "result": {
+                    "queueId": run.id,
+                    "runQueuePosition": run.position,
+                    "blockedBy": run.blocked_by,
+                }
+            }));
+        }
+    };
+    let mut response = turn.start().await;
+    if let (Some(window), Some(result)) = (
+        rate_limit_warning,
+        response
+            .as_mut()
+            .ok()
+            .and_then(|value| value.get_mut("result"))
+            .and_then(Value::as_object_mut),
+    ) {
+        result.insert("rateLimitWarning".to_string(), json!(window));
+    }
+    response
 }
 
 pub(crate) async fn turn_steer_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
     turn_id: String,
@@ -550,7 +718,7 @@ pub(crate) async fn turn_steer_core(
 }
 
 pub(crate) async fn collaboration_mode_list_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
@@ -560,7 +728,7 @@ pub(crate) async fn collaboration_mode_list_core(
 }
 
 pub(crate) async fn turn_interrupt_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
     turn_id: String,
@@ -573,7 +741,8 @@ pub(crate) async fn turn_interrupt_core(
 }
 
 pub(crate) async fn start_review_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     thread_id: String,
     target: Value,
@@ -586,13 +755,20 @@ pub(crate) async fn start_review_core(
     if let Some(delivery) = delivery {
         params.insert("delivery".to_string(), json!(delivery));
     }
-    session
-        .send_request_for_workspace(&workspace_id, "review/start", Value::Object(params))
-        .await
+    let turn = PendingTurn {
+        session,
+        workspace_id,
+        thread_id,
+        method: "review/start",
+        params: Value::Object(params),
+        model: None,
+        effort: None,
+    };
+    admit_turn_core(workspaces, turn, "/review").await
 }
 
 pub(crate) async fn model_list_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
@@ -602,7 +778,7 @@ pub(crate) async fn model_list_core(
 }
 
 pub(crate) async fn experimental_feature_list_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
@@ -615,22 +791,32 @@ pub(crate) async fn experimental_feature_list_core(
 }
 
 pub(crate) async fn account_rate_limits_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    session
+    let response = session
         .send_request_for_workspace(&workspace_id, "account/rateLimits/read", Value::Null)
-        .await
+        .await?;
+    if let Some(rate_limits_value) = response
+        .get("result")
+        .and_then(|result| result.get("rateLimits"))
+    {
+        session
+            .services
+            .run_manager
+            .record_rate_limits(rate_limits_value);
+    }
+    Ok(response)
 }
 
 pub(crate) async fn account_read_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = {
-        let sessions = sessions.lock().await;
+        let sessions = sessions.read().await;
         sessions.get(&workspace_id).cloned()
     };
     let response = if let Some(session) = session {
@@ -651,7 +837,7 @@ pub(crate) async fn account_read_core(
 }
 
 pub(crate) async fn codex_login_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
     workspace_id: String,
 ) -> Result<Value, String> {
@@ -743,8 +929,189 @@ pub(crate) async fn codex_login_core(
     }))
 }
 
+async fn validate_api_key(services: &BackendServices, api_key: &str) -> Result<(), String> {
+    let client = services
+        .http_client_builder()?
+        .timeout(API_KEY_VALIDATION_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response = client
+        .get(API_KEY_VALIDATION_URL)
+        .bearer_auth(api_key)
+        .send()
+        .await
+        .map_err(|err| format!("Could not validate the API key: {err}"))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(format!("The API key was rejected ({status})."));
+    }
+    if !status.is_success() {
+        return Err(format!(
+            "Could not validate the API key: OpenAI returned {status}"
+        ));
+    }
+    Ok(())
+}
+
+/// Logs the workspace's Codex in with an OpenAI API key, for hosts without a
+/// browser. The key is checked against the API first. A running app-server
+/// stores it through `account/login/start`; otherwise `auth.json` in the
+/// workspace's CODEX_HOME is written directly and used on the next start.
+pub(crate) async fn codex_login_api_key_core(
+    services: &BackendServices,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    api_key: String,
+) -> Result<Value, String> {
+    let api_key = normalize_api_key(&api_key)?;
+    validate_api_key(services, &api_key).await?;
+    store_api_key_login(sessions, workspaces, workspace_id, api_key).await
+}
+
+async fn store_api_key_login(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    api_key: String,
+) -> Result<Value, String> {
+    let session = {
+        let sessions = sessions.read().await;
+        sessions.get(&workspace_id).cloned()
+    };
+    if let Some(session) = session {
+        session
+            .send_request_for_workspace(
+                &workspace_id,
+                "account/login/start",
+                json!({ "type": "apiKey", "apiKey": api_key }),
+            )
+            .await?;
+        return Ok(json!({ "type": "apiKey", "storedBy": "appServer" }));
+    }
+    let codex_home = resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
+    let auth_path = write_api_key_auth(&codex_home, &api_key)?;
+    Ok(json!({
+        "type": "apiKey",
+        "storedBy": "authFile",
+        "authPath": auth_path.to_string_lossy(),
+    }))
+}
+
+/// The CODEX_HOME the workspace's agent runs with, including a `CODEX_HOME`
+/// set through its env.
+fn session_home_for_entry(
+    services: &BackendServices,
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> Option<PathBuf> {
+    let default_home =
+        resolve_workspace_codex_home(entry, parent_entry).or_else(resolve_default_codex_home);
+    session_codex_home(&services.session_env(entry), default_home.as_deref())
+}
+
+/// Logs out of the account stored in the workspace's CODEX_HOME. Every
+/// running app-server using that home is sent `account/logout`, so they
+/// drop the login they hold in memory; then `auth.json` is removed. A real
+/// file is kept as `auth.json.bak`; a symlinked shared login is only
+/// unlinked, leaving the default home logged in.
+pub(crate) async fn codex_logout_core(
+    services: &BackendServices,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let _ = codex_login_cancel_core(sessions, codex_login_cancels, workspace_id.clone()).await;
+    let (entry, parent_entry) = resolve_workspace_and_parent(workspaces, &workspace_id).await?;
+    let codex_home = session_home_for_entry(services, &entry, parent_entry.as_ref())
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    let backup_path = backup_auth_file(&codex_home)?;
+
+    // One request per app-server process, through any workspace it serves.
+    let targets: Vec<(String, Arc<WorkspaceSession>)> = {
+        let sessions = sessions.read().await;
+        let workspaces = workspaces.read().await;
+        let mut seen: Vec<Arc<WorkspaceSession>> = Vec::new();
+        let mut targets = Vec::new();
+        for (id, session) in sessions.iter() {
+            if seen.iter().any(|known| Arc::ptr_eq(known, session)) {
+                continue;
+            }
+            let Some(entry) = workspaces.get(id) else {
+                continue;
+            };
+            let parent = entry
+                .parent_id
+                .as_ref()
+                .and_then(|parent_id| workspaces.get(parent_id));
+            if session_home_for_entry(services, entry, parent)
+                .is_some_and(|home| same_home(&home, &codex_home))
+            {
+                seen.push(Arc::clone(session));
+                targets.push((id.clone(), Arc::clone(session)));
+            }
+        }
+        targets
+    };
+    let mut sessions_notified = 0;
+    for (id, session) in targets {
+        if session
+            .send_request_for_workspace(&id, "account/logout", Value::Null)
+            .await
+            .is_ok()
+        {
+            sessions_notified += 1;
+        }
+    }
+    let removed = remove_auth_file(&codex_home)?;
+
+    Ok(json!({
+        "loggedOut": true,
+        "codexHome": codex_home.to_string_lossy(),
+        "removed": removed,
+        "backupPath": backup_path.map(|path| path.to_string_lossy().to_string()),
+        "sessionsNotified": sessions_notified,
+    }))
+}
+
+/// Logs out, then starts a new login: with an API key when one is given,
+/// otherwise the ChatGPT browser flow (`authUrl` in `login`).
+pub(crate) async fn account_switch_core(
+    services: &BackendServices,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
+    workspace_id: String,
+    api_key: Option<String>,
+) -> Result<Value, String> {
+    // Check the key before logging out, so a typo does not leave the
+    // workspace with no account at all.
+    let api_key = match api_key {
+        Some(api_key) => {
+            let api_key = normalize_api_key(&api_key)?;
+            validate_api_key(services, &api_key).await?;
+            Some(api_key)
+        }
+        None => None,
+    };
+    let logout = codex_logout_core(
+        services,
+        sessions,
+        workspaces,
+        codex_login_cancels,
+        workspace_id.clone(),
+    )
+    .await?;
+    let login = match api_key {
+        Some(api_key) => store_api_key_login(sessions, workspaces, workspace_id, api_key).await?,
+        None => codex_login_core(sessions, codex_login_cancels, workspace_id).await?,
+    };
+    Ok(json!({ "logout": logout, "login": login }))
+}
+
 pub(crate) async fn codex_login_cancel_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
     workspace_id: String,
 ) -> Result<Value, String> {
@@ -794,8 +1161,8 @@ pub(crate) async fn codex_login_cancel_core(
 }
 
 pub(crate) async fn skills_list_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
@@ -832,7 +1199,7 @@ pub(crate) async fn skills_list_core(
 }
 
 pub(crate) async fn apps_list_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
@@ -846,7 +1213,7 @@ pub(crate) async fn apps_list_core(
 }
 
 pub(crate) async fn respond_to_server_request_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     request_id: Value,
     result: Value,
@@ -856,7 +1223,7 @@ pub(crate) async fn respond_to_server_request_core(
 }
 
 pub(crate) async fn remember_approval_rule_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     command: Vec<String>,
 ) -> Result<Value, String> {
@@ -879,8 +1246,49 @@ pub(crate) async fn remember_approval_rule_core(
     }))
 }
 
+/// Adds a CodexMonitor approval rule. `scoped` limits it to commands running
+/// inside the workspace.
+pub(crate) async fn add_approval_rule_core(
+    policy: &rules::ApprovalPolicyStore,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    kind: String,
+    pattern: String,
+    decision: String,
+    scoped: bool,
+    expires_at: Option<u64>,
+) -> Result<rules::ApprovalRule, String> {
+    let kind = serde_json::from_value(Value::String(kind.clone()))
+        .map_err(|_| format!("Unknown approval rule kind `{kind}`"))?;
+    let decision = serde_json::from_value(Value::String(decision.clone()))
+        .map_err(|_| format!("Unknown approval rule decision `{decision}`"))?;
+    let workspace_path = resolve_workspace_path_core(workspaces, &workspace_id).await?;
+    let scope_path = scoped.then_some(workspace_path);
+    policy.add(kind, pattern, decision, scope_path, expires_at)
+}
+
+/// Unexpired approval rules; with a workspace, only those that apply there.
+pub(crate) async fn list_approval_rules_core(
+    policy: &rules::ApprovalPolicyStore,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: Option<String>,
+) -> Result<Vec<rules::ApprovalRule>, String> {
+    let workspace_path = match workspace_id {
+        Some(workspace_id) => Some(resolve_workspace_path_core(workspaces, &workspace_id).await?),
+        None => None,
+    };
+    Ok(policy.list(workspace_path.as_deref()))
+}
+
+pub(crate) fn delete_approval_rule_core(
+    policy: &rules::ApprovalPolicyStore,
+    rule_id: &str,
+) -> Result<(), String> {
+    policy.delete(rule_id)
+}
+
 pub(crate) async fn get_config_model_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let codex_home = resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
@@ -893,6 +1301,19 @@ mod tests {
     use super::*;
     use serde_json::Value;
 
+    #[test]
+    fn response_object_id_reads_result_or_top_level() {
+        assert_eq!(
+            response_object_id(&json!({ "result": { "thread": { "id": "t1" } } }), "thread"),
+            Some("t1".to_string())
+        );
+        assert_eq!(
+            response_object_id(&json!({ "turn": { "id": "turn-1" } }), "turn"),
+            Some("turn-1".to_string())
+        );
+        assert_eq!(response_object_id(&json!({ "result": {} }), "turn"), None);
+    }
+
     #[test]
     fn normalize_strips_file_uri_prefix() {
         assert_eq!(
@@ -1006,9 +1427,31 @@ mod tests {
     fn heif_paths_are_inlined_for_codex() {
         assert!(should_inline_image_path_for_codex("/tmp/photo.heic"));
         assert!(should_inline_image_path_for_codex("/tmp/photo.HEIF"));
+        assert!(should_inline_image_path_for_codex("/tmp/photo.avif"));
         assert!(!should_inline_image_path_for_codex("/tmp/photo.png"));
     }
 
+    #[test]
+    fn mentions_accept_apps_and_attached_files() {
+        let input = build_turn_input_items(
+            "look".to_string(),
+            None,
+            Some(vec![
+                json!({ "name": "Linear", "path": "app://linear" }),
+                json!({ "name": "src/lib.rs", "path": "/repo/src/lib.rs" }),
+                json!({ "name": "src/lib.rs", "path": "/repo/src/lib.rs" }),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(input.len(), 3);
+        assert_eq!(input[2]["path"], "/repo/src/lib.rs");
+
+        for path in ["app://", "src/lib.rs"] {
+            let mention = json!({ "name": "x", "path": path });
+            assert!(build_turn_input_items("look".to_string(), None, Some(vec![mention])).is_err());
+        }
+    }
+
     #[test]
     fn insert_optional_nullable_string_omits_missing_and_preserves_null() {
         let mut params = Map::new();
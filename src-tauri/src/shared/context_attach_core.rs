@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::shared::file_edit_core::resolve_root;
+use crate::types::WorkspaceEntry;
+use crate::utils::normalize_git_path;
+
+/// Files past this many are left out and the attachment flagged `truncated`.
+const MAX_CONTEXT_FILES: usize = 200;
+/// Larger files are skipped; they are rarely useful as context.
+const MAX_CONTEXT_FILE_BYTES: u64 = 512 * 1024;
+/// Total size of the attached files.
+const MAX_CONTEXT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// A file to reference in the turn, shaped like the `appMentions` entries
+/// `send_user_message` takes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContextMention {
+    /// Relative to the workspace root, with `/` separators.
+    pub(crate) name: String,
+    /// Absolute path on the backend host.
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SkippedContextPath {
+    pub(crate) path: String,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContextAttachment {
+    /// Sorted by name.
+    pub(crate) mentions: Vec<ContextMention>,
+    pub(crate) bytes: u64,
+    pub(crate) skipped: Vec<SkippedContextPath>,
+    /// A file or size cap was hit.
+    pub(crate) truncated: bool,
+}
+
+struct Collector<'a> {
+    root: &'a Path,
+    seen: HashSet<PathBuf>,
+    attachment: ContextAttachment,
+}
+
+impl Collector<'_> {
+    fn skip(&mut self, path: &str, reason: &str) {
+        self.attachment.skipped.push(SkippedContextPath {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Returns `false` once a cap is reached and collecting should stop.
+    fn add_file(&mut self, path: &Path, size: u64) -> bool {
+        let Ok(relative) = path.strip_prefix(self.root) else {
+            return true;
+        };
+        let name = normalize_git_path(&relative.to_string_lossy());
+        if size > MAX_CONTEXT_FILE_BYTES {
+            self.skip(&name, "larger than 512 KB");
+            return true;
+        }
+        if !self.seen.insert(path.to_path_buf()) {
+            return true;
+        }
+        if self.attachment.mentions.len() == MAX_CONTEXT_FILES
+            || self.attachment.bytes + size > MAX_CONTEXT_BYTES
+        {
+            self.attachment.truncated = true;
+            return false;
+        }
+        self.attachment.bytes += size;
+        self.attachment.mentions.push(ContextMention {
+            name,
+            path: path.to_string_lossy().to_string(),
+        });
+        true
+    }
+
+    /// Adds the files under `dir` that `.gitignore` and the usual build
+    /// folders do not exclude. Symlinks are not followed.
+    fn add_dir(&mut self, dir: &Path) -> bool {
+        let walker = WalkBuilder::new(dir)
+            .hidden(false)
+            .follow_links(false)
+            .require_git(false)
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !matches!(
+                        entry.file_name().to_str(),
+                        Some(".git" | "node_modules" | "dist" | "target")
+                    )
+            })
+            .sort_by_file_path(|a, b| a.cmp(b))
+            .build();
+        for entry in walker.flatten() {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            if !self.add_file(entry.path(), size) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Resolves each dropped path, absolute or relative to the workspace, and
+/// collects the files it names. Paths outside the workspace are skipped.
+pub(crate) fn attach_context_within(
+    root: &Path,
+    paths: &[String],
+) -> Result<ContextAttachment, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let mut collector = Collector {
+        root: &canonical_root,
+        seen: HashSet::new(),
+        attachment: ContextAttachment::default(),
+    };
+    for raw in paths {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Ok(path) = canonical_root.join(trimmed).canonicalize() else {
+            collector.skip(trimmed, "not found");
+            continue;
+        };
+        if !path.starts_with(&canonical_root) {
+            collector.skip(trimmed, "outside the workspace");
+            continue;
+        }
+        let keep_going = if path.is_dir() {
+            collector.add_dir(&path)
+        } else {
+            let size = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+            collector.add_file(&path, size)
+        };
+        if !keep_going {
+            break;
+        }
+    }
+    let mut attachment = collector.attachment;
+    attachment.mentions.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(attachment)
+}
+
+pub(crate) async fn attach_context_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    paths: Vec<String>,
+) -> Result<ContextAttachment, String> {
+    let root = resolve_root(workspaces, workspace_id).await?;
+    tokio::task::spawn_blocking(move || attach_context_within(&root, &paths))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_expand_to_files_that_are_not_ignored() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("src/node_modules/pkg")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        std::fs::write(root.join("src/nested/mod.rs"), "mod b;\n").unwrap();
+        std::fs::write(root.join("src/debug.log"), "noise").unwrap();
+        std::fs::write(root.join("src/node_modules/pkg/index.js"), "x").unwrap();
+        std::fs::write(
+            root.join("src/big.bin"),
+            vec![0u8; MAX_CONTEXT_FILE_BYTES as usize + 1],
+        )
+        .unwrap();
+
+        let absolute = root.join("src/lib.rs").to_string_lossy().to_string();
+        let attachment = attach_context_within(
+            &root,
+            &[
+                "src".to_string(),
+                absolute,
+                "..".to_string(),
+                "missing.txt".to_string(),
+            ],
+        )
+        .unwrap();
+        let names: Vec<_> = attachment
+            .mentions
+            .iter()
+            .map(|mention| mention.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["src/lib.rs", "src/nested/mod.rs"]);
+        assert!(Path::new(&attachment.mentions[0].path).is_absolute());
+        assert_eq!(attachment.bytes, 14 + 7);
+        let skipped: Vec<_> = attachment
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.path.as_str(), skipped.reason.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                ("src/big.bin", "larger than 512 KB"),
+                ("..", "outside the workspace"),
+                ("missing.txt", "not found"),
+            ]
+        );
+        assert!(!attachment.truncated);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Bumped when the app and daemon stop understanding each other's requests
+/// or events. Daemons that predate versioning count as 0.
+pub(crate) const DAEMON_PROTOCOL_VERSION: u32 = 1;
+/// The oldest peer protocol still accepted, on either side.
+pub(crate) const MIN_DAEMON_PROTOCOL_VERSION: u32 = 0;
+/// The daemon refuses JSON-RPC lines longer than this.
+pub(crate) const MAX_RPC_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DaemonVersion {
+    pub(crate) version: String,
+    pub(crate) protocol_version: u32,
+    pub(crate) min_protocol_version: u32,
+}
+
+pub(crate) fn daemon_version_core() -> DaemonVersion {
+    DaemonVersion {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        protocol_version: DAEMON_PROTOCOL_VERSION,
+        min_protocol_version: MIN_DAEMON_PROTOCOL_VERSION,
+    }
+}
+
+/// Checks what a peer reported, if anything, against this build: the
+/// peer's protocol must be one this build still accepts, and this build's
+/// one the peer still accepts. Returns the protocol to assume for the peer.
+pub(crate) fn check_peer_protocol(
+    local: &str,
+    peer: &str,
+    reported: Option<u64>,
+    peer_min: Option<u64>,
+) -> Result<u32, String> {
+    let ours = daemon_version_core();
+    let version = reported
+        .map(|value| u32::try_from(value).unwrap_or(u32::MAX))
+        .unwrap_or(0);
+    if version < ours.min_protocol_version {
+        return Err(format!(
+            "The {peer} speaks protocol {version}, but the {local} needs {} or newer; upgrade the {peer}.",
+            ours.min_protocol_version
+        ));
+    }
+    if let Some(peer_min) = peer_min.filter(|min| *min > u64::from(ours.protocol_version)) {
+        return Err(format!(
+            "The {peer} needs protocol {peer_min} or newer, but the {local} speaks {}; upgrade the {local}.",
+            ours.protocol_version
+        ));
+    }
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_without_a_version_are_legacy_and_newer_minimums_are_refused() {
+        assert_eq!(check_peer_protocol("app", "daemon", None, None), Ok(0));
+        assert_eq!(
+            check_peer_protocol(
+                "app",
+                "daemon",
+                Some(u64::from(DAEMON_PROTOCOL_VERSION)),
+                Some(0)
+            ),
+            Ok(DAEMON_PROTOCOL_VERSION)
+        );
+        let error = check_peer_protocol(
+            "app",
+            "daemon",
+            Some(u64::from(DAEMON_PROTOCOL_VERSION) + 1),
+            Some(u64::from(DAEMON_PROTOCOL_VERSION) + 1),
+        )
+        .unwrap_err();
+        assert!(error.ends_with("upgrade the app."));
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::future::join_all;
+use git2::{BranchType, Repository};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::services::BackendServices;
+use crate::backend::turn_tracker::ActiveTurn;
+use crate::shared::worktree_lock_core::{read_worktree_lock, WorktreeLock};
+use crate::types::WorkspaceEntry;
+
+/// Ahead/behind against the upstream as of the last fetch; nothing is
+/// fetched to build the snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DashboardGit {
+    pub(crate) branch: Option<String>,
+    pub(crate) upstream: Option<String>,
+    pub(crate) ahead: usize,
+    pub(crate) behind: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DashboardWorkspace {
+    pub(crate) workspace_id: String,
+    pub(crate) name: String,
+    pub(crate) connected: bool,
+    pub(crate) active_turns: Vec<ActiveTurn>,
+    /// Messages waiting for a `maxConcurrentTurns` slot.
+    pub(crate) queued_turns: usize,
+    /// Last app-server notification for the workspace (ms since the epoch)
+    /// since this process started.
+    pub(crate) last_activity_at: Option<u64>,
+    pub(crate) pending_approvals: usize,
+    /// `None` when the workspace is not a git repository.
+    pub(crate) git: Option<DashboardGit>,
+    /// Set when a worktree workspace is locked; see `unlock_worktree`.
+    pub(crate) worktree_lock: Option<WorktreeLock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DashboardSnapshot {
+    pub(crate) generated_at: u64,
+    pub(crate) workspaces: Vec<DashboardWorkspace>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn git_ahead_behind(path: &Path) -> Option<DashboardGit> {
+    let repo = Repository::open(path).ok()?;
+    let head = match repo.head() {
+        Ok(head) => head,
+        // An unborn branch has nothing to compare yet.
+        Err(_) => {
+            return Some(DashboardGit {
+                branch: None,
+                upstream: None,
+                ahead: 0,
+                behind: 0,
+            })
+        }
+    };
+    let branch = head
+        .is_branch()
+        .then(|| head.shorthand().map(str::to_string))
+        .flatten();
+    let upstream = branch
+        .as_deref()
+        .and_then(|name| repo.find_branch(name, BranchType::Local).ok())
+        .and_then(|local| local.upstream().ok());
+    let (upstream_name, ahead, behind) = match upstream {
+        Some(upstream) => {
+            let reference = upstream.get();
+            let counts = head
+                .target()
+                .zip(reference.target())
+                .and_then(|(local, remote)| repo.graph_ahead_behind(local, remote).ok())
+                .unwrap_or((0, 0));
+            (
+                reference.shorthand().map(str::to_string),
+                counts.0,
+                counts.1,
+            )
+        }
+        None => (None, 0, 0),
+    };
+    Some(DashboardGit {
+        branch,
+        upstream: upstream_name,
+        ahead,
+        behind,
+    })
+}
+
+/// One row per workspace with what the overview screen shows, so clients do
+/// not poll several commands for each workspace. `pending_approvals` counts
+/// approvals per workspace id; local mode has none queued.
+pub(crate) async fn dashboard_snapshot_core(
+    services: &BackendServices,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    pending_approvals: &HashMap<String, usize>,
+) -> DashboardSnapshot {
+    let mut entries: Vec<WorkspaceEntry> = workspaces.read().await.values().cloned().collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    let connected: Vec<bool> = {
+        let sessions = sessions.read().await;
+        entries
+            .iter()
+            .map(|entry| sessions.contains_key(&entry.id))
+            .collect()
+    };
+    let git = join_all(entries.iter().map(|entry| {
+        let path = entry.path.clone();
+        async move {
+            tokio::task::spawn_blocking(move || git_ahead_behind(Path::new(&path)))
+                .await
+                .ok()
+                .flatten()
+        }
+    }))
+    .await;
+    let queue = services.run_manager.snapshot();
+
+    let workspaces = entries
+        .into_iter()
+        .zip(connected)
+        .zip(git)
+        .map(|((entry, connected), git)| DashboardWorkspace {
+            active_turns: services.turn_tracker.list(Some(&entry.id)),
+            queued_turns: queue
+                .queued
+                .iter()
+                .filter(|run| run.workspace_id == entry.id)
+                .count(),
+            last_activity_at: services.workspace_activity.last_event_at(&entry.id),
+            pending_approvals: pending_approvals.get(&entry.id).copied().unwrap_or(0),
+            connected,
+            git,
+            worktree_lock: entry
+                .kind
+                .is_worktree()
+                .then(|| read_worktree_lock(Path::new(&entry.path)))
+                .flatten(),
+            workspace_id: entry.id,
+            name: entry.name,
+        })
+        .collect();
+    DashboardSnapshot {
+        generated_at: now_ms(),
+        workspaces,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ahead_behind_is_counted_against_the_upstream() {
+        let dir =
+            std::env::temp_dir().join(format!("codex-monitor-dashboard-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&dir).expect("init");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let base = repo
+            .commit(Some("HEAD"), &sig, &sig, "base", &tree, &[])
+            .expect("base commit");
+        let base_commit = repo.find_commit(base).unwrap();
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+        repo.remote("origin", "https://example.com/repo.git")
+            .expect("remote");
+        repo.reference("refs/remotes/origin/main", base, true, "upstream")
+            .expect("remote ref");
+        let mut config = repo.config().unwrap();
+        config
+            .set_str(&format!("branch.{branch}.remote"), "origin")
+            .unwrap();
+        config
+            .set_str(&format!("branch.{branch}.merge"), "refs/heads/main")
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "ahead", &tree, &[&base_commit])
+            .expect("ahead commit");
+
+        let git = git_ahead_behind(&dir).expect("git status");
+        assert_eq!(git.branch.as_deref(), Some(branch.as_str()));
+        assert_eq!(git.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((git.ahead, git.behind), (1, 0));
+        assert!(git_ahead_behind(&dir.join("missing")).is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::types::WorkspaceEntry;
+use crate::utils::normalize_git_path;
+
+/// Children past this are cut and the listing flagged `truncated`.
+const MAX_DIR_ENTRIES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum DirEntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DirEntry {
+    pub(crate) name: String,
+    /// Relative to the workspace root, with `/` separators.
+    pub(crate) path: String,
+    #[serde(rename = "type")]
+    pub(crate) kind: DirEntryKind,
+    /// Files only.
+    pub(crate) size: Option<u64>,
+    /// Milliseconds since the epoch.
+    pub(crate) mtime: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceDirListing {
+    pub(crate) path: String,
+    /// Folders first, then by name.
+    pub(crate) entries: Vec<DirEntry>,
+    pub(crate) truncated: bool,
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(
+        name,
+        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
+    )
+}
+
+/// Resolves `relative` to a directory inside `root`. An empty path is the
+/// root itself.
+fn resolve_dir(root: &Path, relative: &str) -> Result<(PathBuf, PathBuf), String> {
+    let relative = Path::new(relative.trim_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err("Invalid directory path".to_string());
+    }
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let dir = canonical_root
+        .join(relative)
+        .canonicalize()
+        .map_err(|err| format!("Failed to open directory: {err}"))?;
+    if !dir.starts_with(&canonical_root) {
+        return Err("Invalid directory path".to_string());
+    }
+    if !dir.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    Ok((canonical_root, dir))
+}
+
+/// Lists the immediate children of `relative`, hiding what
+/// `list_workspace_files` hides. Symlinks are reported, not followed.
+pub(crate) fn list_dir_within(root: &Path, relative: &str) -> Result<WorkspaceDirListing, String> {
+    let (canonical_root, dir) = resolve_dir(root, relative)?;
+    let walker = WalkBuilder::new(&dir)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .max_depth(Some(1))
+        .build();
+
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    for entry in walker.flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let kind = if file_type.is_symlink() {
+            DirEntryKind::Symlink
+        } else if file_type.is_dir() {
+            if should_skip_dir(&name) {
+                continue;
+            }
+            DirEntryKind::Dir
+        } else {
+            DirEntryKind::File
+        };
+        if entries.len() == MAX_DIR_ENTRIES {
+            truncated = true;
+            break;
+        }
+        let metadata = entry.metadata().ok();
+        let Ok(relative_path) = entry.path().strip_prefix(&canonical_root) else {
+            continue;
+        };
+        entries.push(DirEntry {
+            name,
+            path: normalize_git_path(&relative_path.to_string_lossy()),
+            kind,
+            size: metadata
+                .as_ref()
+                .filter(|_| kind == DirEntryKind::File)
+                .map(|meta| meta.len()),
+            mtime: metadata
+                .and_then(|meta| meta.modified().ok())
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as u64),
+        });
+    }
+    entries.sort_by(|a, b| {
+        (a.kind != DirEntryKind::Dir)
+            .cmp(&(b.kind != DirEntryKind::Dir))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let path = dir
+        .strip_prefix(&canonical_root)
+        .map(|path| normalize_git_path(&path.to_string_lossy()))
+        .unwrap_or_default();
+    Ok(WorkspaceDirListing {
+        path,
+        entries,
+        truncated,
+    })
+}
+
+pub(crate) async fn list_workspace_dir_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    path: &str,
+) -> Result<WorkspaceDirListing, String> {
+    let root = {
+        let workspaces = workspaces.read().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || list_dir_within(&root, &path))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_children_folders_first_and_hides_ignored() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/nested")).expect("create src");
+        std::fs::create_dir_all(root.join("node_modules/pkg")).expect("create node_modules");
+        std::fs::create_dir_all(root.join("build")).expect("create build");
+        std::fs::write(root.join(".gitignore"), "build/\n*.log\n").expect("write gitignore");
+        std::fs::write(root.join("README.md"), "hello").expect("write readme");
+        std::fs::write(root.join("debug.log"), "").expect("write log");
+        std::fs::write(root.join("src/main.rs"), "").expect("write main");
+
+        let listing = list_dir_within(&root, "").expect("list root");
+        let names: Vec<_> = listing
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["src", ".gitignore", "README.md"]);
+        assert_eq!(listing.entries[2].size, Some(5));
+        assert_eq!(listing.entries[0].size, None);
+
+        let nested = list_dir_within(&root, "src/").expect("list src");
+        assert_eq!(nested.path, "src");
+        let paths: Vec<_> = nested
+            .entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.kind))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                ("src/nested", DirEntryKind::Dir),
+                ("src/main.rs", DirEntryKind::File)
+            ]
+        );
+
+        assert!(list_dir_within(&root, "../").is_err());
+        assert!(list_dir_within(&root, "README.md").is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
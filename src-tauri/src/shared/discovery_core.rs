@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+
+/// The mDNS service type the daemon advertises.
+pub(crate) const SERVICE_TYPE: &str = "_codexmonitor._tcp.local.";
+const DEFAULT_BROWSE_MS: u64 = 3_000;
+const MAX_BROWSE_MS: u64 = 15_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiscoveredDaemon {
+    /// The advertised instance name, usually the machine name.
+    pub(crate) name: String,
+    /// The mDNS host name, e.g. `studio.local.`.
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    /// IPv4 addresses first.
+    pub(crate) addresses: Vec<String>,
+    pub(crate) version: Option<String>,
+    /// Whether the daemon wants a token; `None` if it did not say.
+    pub(crate) auth_required: Option<bool>,
+    /// `address:port` for the first address, ready for the remote host
+    /// setting.
+    pub(crate) remote_host: Option<String>,
+}
+
+fn remote_host(addresses: &[String], port: u16) -> Option<String> {
+    let address = addresses.first()?;
+    Some(if address.contains(':') {
+        format!("[{address}]:{port}")
+    } else {
+        format!("{address}:{port}")
+    })
+}
+
+fn machine_name() -> String {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        let result =
+            unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+        if result == 0 {
+            let end = buffer
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(buffer.len());
+            let name = String::from_utf8_lossy(&buffer[..end]).to_string();
+            if !name.trim().is_empty() {
+                return name;
+            }
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "codex-monitor".to_string())
+}
+
+/// The first label of a host name, reduced to what mDNS host names allow.
+fn mdns_label(name: &str) -> String {
+    let label: String = name
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "codex-monitor".to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+/// Advertises the daemon as `_codexmonitor._tcp` with its version and
+/// whether it needs a token. Returns `None` for loopback listeners, which
+/// other machines cannot reach. Keep the returned daemon alive for as long
+/// as the advertisement should stay up.
+pub(crate) fn advertise_daemon(
+    listen: SocketAddr,
+    version: &str,
+    auth_required: bool,
+) -> Result<Option<ServiceDaemon>, String> {
+    if listen.ip().is_loopback() {
+        return Ok(None);
+    }
+    let machine = machine_name();
+    let label = mdns_label(&machine);
+    let host_name = format!("{label}.local.");
+    let properties = [
+        ("version", version),
+        ("auth", if auth_required { "token" } else { "none" }),
+    ];
+    let address = if listen.ip().is_unspecified() {
+        String::new()
+    } else {
+        listen.ip().to_string()
+    };
+    let mut info = ServiceInfo::new(
+        SERVICE_TYPE,
+        machine.split('.').next().unwrap_or_default(),
+        &host_name,
+        address.as_str(),
+        listen.port(),
+        &properties[..],
+    )
+    .map_err(|err| err.to_string())?;
+    if listen.ip().is_unspecified() {
+        info = info.enable_addr_auto();
+    }
+    let mdns = ServiceDaemon::new().map_err(|err| err.to_string())?;
+    mdns.register(info).map_err(|err| err.to_string())?;
+    Ok(Some(mdns))
+}
+
+fn discovered_from_info(info: &ServiceInfo) -> DiscoveredDaemon {
+    let mut addresses: Vec<_> = info.get_addresses().iter().copied().collect();
+    addresses.sort_by_key(|address| (address.is_ipv6(), *address));
+    let name = info
+        .get_fullname()
+        .strip_suffix(SERVICE_TYPE)
+        .unwrap_or(info.get_fullname())
+        .trim_end_matches('.')
+        .to_string();
+    let addresses: Vec<String> = addresses
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect();
+    DiscoveredDaemon {
+        name,
+        host: info.get_hostname().to_string(),
+        port: info.get_port(),
+        remote_host: remote_host(&addresses, info.get_port()),
+        addresses,
+        version: info.get_property_val_str("version").map(str::to_string),
+        auth_required: info.get_property_val_str("auth").map(|auth| auth != "none"),
+    }
+}
+
+/// Blocking. Browses for advertised daemons for `timeout_ms` (3s by default)
+/// and returns each one found, sorted by name.
+pub(crate) fn discover_daemons_core(
+    timeout_ms: Option<u64>,
+) -> Result<Vec<DiscoveredDaemon>, String> {
+    let window = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_BROWSE_MS).min(MAX_BROWSE_MS));
+    let mdns = ServiceDaemon::new().map_err(|err| err.to_string())?;
+    let receiver = mdns.browse(SERVICE_TYPE).map_err(|err| err.to_string())?;
+    let deadline = Instant::now() + window;
+    let mut found: HashMap<String, DiscoveredDaemon> = HashMap::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                found.insert(info.get_fullname().to_string(), discovered_from_info(&info));
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+                found.remove(&fullname);
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+    let _ = mdns.stop_browse(SERVICE_TYPE);
+    let _ = mdns.shutdown();
+    let mut daemons: Vec<_> = found.into_values().collect();
+    daemons.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.host.cmp(&b.host)));
+    Ok(daemons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mdns_labels_keep_only_the_first_host_label() {
+        assert_eq!(mdns_label("studio.local"), "studio");
+        assert_eq!(mdns_label("Jamie's MacBook Pro"), "Jamie-s-MacBook-Pro");
+        assert_eq!(mdns_label("..."), "codex-monitor");
+    }
+
+    #[test]
+    fn remote_host_brackets_ipv6_addresses() {
+        let v4 = vec!["192.168.1.20".to_string()];
+        assert_eq!(remote_host(&v4, 4732).as_deref(), Some("192.168.1.20:4732"));
+        let v6 = vec!["fe80::1".to_string()];
+        assert_eq!(remote_host(&v6, 4732).as_deref(), Some("[fe80::1]:4732"));
+        assert_eq!(remote_host(&[], 4732), None);
+    }
+}
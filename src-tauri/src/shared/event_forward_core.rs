@@ -0,0 +1,56 @@
+//! Per-client forwarding of daemon events as JSON-RPC notification lines.
+
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::backend::events::{StampedAppServerEvent, TerminalExit, TerminalOutput};
+
+#[derive(Clone)]
+pub(crate) enum DaemonEvent {
+    AppServer(StampedAppServerEvent),
+    #[allow(dead_code)]
+    TerminalOutput(TerminalOutput),
+    #[allow(dead_code)]
+    TerminalExit(TerminalExit),
+}
+
+fn build_event_notification(event: DaemonEvent) -> Option<String> {
+    let payload = match event {
+        DaemonEvent::AppServer(payload) => json!({
+            "method": "app-server-event",
+            "params": payload,
+        }),
+        DaemonEvent::TerminalOutput(payload) => json!({
+            "method": "terminal-output",
+            "params": payload,
+        }),
+        DaemonEvent::TerminalExit(payload) => json!({
+            "method": "terminal-exit",
+            "params": payload,
+        }),
+    };
+    serde_json::to_string(&payload).ok()
+}
+
+/// Serializes each event for one client until the channel closes or the
+/// client goes away. A client that falls behind skips what it missed.
+pub(crate) async fn forward_events(
+    mut rx: broadcast::Receiver<DaemonEvent>,
+    out_tx_events: mpsc::UnboundedSender<String>,
+) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let Some(payload) = build_event_notification(event) else {
+            continue;
+        };
+
+        if out_tx_events.send(payload).is_err() {
+            break;
+        }
+    }
+}
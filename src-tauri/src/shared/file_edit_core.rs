@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
+use crate::types::WorkspaceEntry;
+
+/// Larger contents are refused; the editor is meant for source files.
+const MAX_WRITE_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileWriteResult {
+    pub(crate) path: String,
+    /// Hash of the content now on disk; pass it as `expectedHash` next time.
+    pub(crate) hash: String,
+    pub(crate) bytes: usize,
+    pub(crate) created: bool,
+}
+
+/// Hex SHA-256 of file content, as returned by `read_workspace_file`.
+pub(crate) fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Hashes a whole file without loading it at once.
+pub(crate) fn file_hash(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|err| format!("Failed to read file: {err}"))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Orders the file edits of one process, so each edit checks `expectedHash`
+/// against what the edit before it left on disk.
+#[derive(Default)]
+pub(crate) struct FileWrites(Mutex<()>);
+
+const GIT_DIR_REFUSED: &str = "Files under .git cannot be written";
+
+/// Whether `canonical_path` lies in a `.git` entry of the workspace.
+fn is_in_git_dir(canonical_root: &Path, canonical_path: &Path) -> bool {
+    canonical_path
+        .strip_prefix(canonical_root)
+        .map(|relative| {
+            relative
+                .components()
+                .any(|component| component.as_os_str() == ".git")
+        })
+        .unwrap_or(false)
+}
+
+/// Resolves `relative_path` for writing. The path must stay inside the
+/// workspace once symlinks are resolved and may not touch `.git`, whether
+/// named directly or reached through a symlink.
+pub(crate) fn resolve_writable_path(root: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative_path);
+    if relative_path.trim().is_empty() {
+        return Err("File path is required".to_string());
+    }
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) if name == ".git" => {
+                return Err(GIT_DIR_REFUSED.to_string());
+            }
+            Component::Normal(_) | Component::CurDir => {}
+            _ => return Err("Invalid file path".to_string()),
+        }
+    }
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let candidate = canonical_root.join(relative);
+    if candidate.exists() {
+        let canonical_path = candidate
+            .canonicalize()
+            .map_err(|err| format!("Failed to resolve file: {err}"))?;
+        if !canonical_path.starts_with(&canonical_root) {
+            return Err("Invalid file path".to_string());
+        }
+        if is_in_git_dir(&canonical_root, &canonical_path) {
+            return Err(GIT_DIR_REFUSED.to_string());
+        }
+        if !canonical_path.is_file() {
+            return Err("Path is not a file".to_string());
+        }
+        return Ok(canonical_path);
+    }
+    // New file: the deepest existing ancestor decides containment.
+    let mut ancestor = candidate.parent();
+    while let Some(dir) = ancestor {
+        if dir.exists() {
+            let canonical_dir = dir
+                .canonicalize()
+                .map_err(|err| format!("Failed to resolve directory: {err}"))?;
+            if !canonical_dir.starts_with(&canonical_root) {
+                return Err("Invalid file path".to_string());
+            }
+            if is_in_git_dir(&canonical_root, &canonical_dir) {
+                return Err(GIT_DIR_REFUSED.to_string());
+            }
+            break;
+        }
+        ancestor = dir.parent();
+    }
+    Ok(candidate)
+}
+
+fn check_expected_hash(current: Option<&[u8]>, expected_hash: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected_hash else {
+        return Ok(());
+    };
+    match current {
+        None => Err("File no longer exists on disk".to_string()),
+        Some(current) if content_hash(current) != expected => {
+            Err("File changed on disk since it was read".to_string())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Replaces the file through a temporary sibling so readers never see a
+/// partial write. Existing permissions are kept.
+pub(crate) fn replace_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+    std::fs::create_dir_all(parent).map_err(|err| format!("Failed to create directory: {err}"))?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = parent.join(format!(".{file_name}.{}.tmp", uuid::Uuid::new_v4()));
+    let write = || -> Result<(), String> {
+        let mut file =
+            File::create(&temp_path).map_err(|err| format!("Failed to write file: {err}"))?;
+        file.write_all(content)
+            .and_then(|_| file.sync_all())
+            .map_err(|err| format!("Failed to write file: {err}"))?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let _ = std::fs::set_permissions(&temp_path, metadata.permissions());
+        }
+        std::fs::rename(&temp_path, path).map_err(|err| format!("Failed to write file: {err}"))
+    };
+    let result = write();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn read_current(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read(path)
+        .map(Some)
+        .map_err(|err| format!("Failed to read file: {err}"))
+}
+
+pub(crate) fn write_workspace_file_inner(
+    root: &Path,
+    relative_path: &str,
+    content: &str,
+    expected_hash: Option<&str>,
+) -> Result<WorkspaceFileWriteResult, String> {
+    if content.len() > MAX_WRITE_BYTES {
+        return Err(format!(
+            "File content exceeds the {MAX_WRITE_BYTES} byte limit"
+        ));
+    }
+    let path = resolve_writable_path(root, relative_path)?;
+    let current = read_current(&path)?;
+    check_expected_hash(current.as_deref(), expected_hash)?;
+    replace_file(&path, content.as_bytes())?;
+    Ok(WorkspaceFileWriteResult {
+        path: relative_path.to_string(),
+        hash: content_hash(content.as_bytes()),
+        bytes: content.len(),
+        created: current.is_none(),
+    })
+}
+
+pub(crate) fn apply_workspace_patch_inner(
+    root: &Path,
+    relative_path: &str,
+    patch: &str,
+    expected_hash: Option<&str>,
+) -> Result<WorkspaceFileWriteResult, String> {
+    let path = resolve_writable_path(root, relative_path)?;
+    let current = read_current(&path)?;
+    check_expected_hash(current.as_deref(), expected_hash)?;
+    let original = match &current {
+        Some(bytes) => {
+            String::from_utf8(bytes.clone()).map_err(|_| "File is not valid UTF-8".to_string())?
+        }
+        None => String::new(),
+    };
+    let patched = apply_unified_diff(&original, patch)?;
+    if patched.len() > MAX_WRITE_BYTES {
+        return Err(format!(
+            "File content exceeds the {MAX_WRITE_BYTES} byte limit"
+        ));
+    }
+    replace_file(&path, patched.as_bytes())?;
+    Ok(WorkspaceFileWriteResult {
+        path: relative_path.to_string(),
+        hash: content_hash(patched.as_bytes()),
+        bytes: patched.len(),
+        created: current.is_none(),
+    })
+}
+
+#[derive(Debug, Default)]
+struct Hunk {
+    /// 1-based line in the original file; 0 for an empty original.
+    old_start: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    /// `\ No newline at end of file` followed the last new line.
+    new_missing_newline: bool,
+}
+
+fn parse_hunk_start(header: &str) -> Result<usize, String> {
+    let invalid = || format!("Invalid hunk header: {header}");
+    let old_range = header
+        .strip_prefix("@@ -")
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or_else(invalid)?;
+    old_range
+        .split(',')
+        .next()
+        .and_then(|start| start.parse().ok())
+        .ok_or_else(invalid)
+}
+
+fn parse_hunks(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut last_kind = ' ';
+    for line in patch.lines() {
+        if line.starts_with("@@") {
+            hunks.push(Hunk {
+                old_start: parse_hunk_start(line)?,
+                ..Hunk::default()
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // `diff --git`, `---`, `+++` and other headers.
+            continue;
+        };
+        if line.starts_with('\\') {
+            if last_kind != '-' {
+                hunk.new_missing_newline = true;
+            }
+            continue;
+        }
+        let (kind, text) = match line.chars().next() {
+            Some(kind @ (' ' | '-' | '+')) => (kind, &line[1..]),
+            // Some tools drop the space of empty context lines.
+            None => (' ', ""),
+            Some(_) => {
+                if line.starts_with("diff ") {
+                    return Err("The patch must change a single file".to_string());
+                }
+                return Err(format!("Invalid patch line: {line}"));
+            }
+        };
+        if kind != '+' {
+            hunk.old_lines.push(text.to_string());
+        }
+        if kind != '-' {
+            hunk.new_lines.push(text.to_string());
+        }
+        last_kind = kind;
+    }
+    if hunks.is_empty() {
+        return Err("The patch has no hunks".to_string());
+    }
+    Ok(hunks)
+}
+
+fn line_text(line: &str) -> &str {
+    line.trim_end_matches('\n').trim_end_matches('\r')
+}
+
+/// Applies a unified diff for one file. Hunks may have drifted from their
+/// stated line numbers; the nearest exact match of their context wins.
+pub(crate) fn apply_unified_diff(original: &str, patch: &str) -> Result<String, String> {
+    let hunks = parse_hunks(patch)?;
+    let eol = if original.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(original.len());
+    let mut next = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let expected = hunk.old_start.saturating_sub(1).max(next);
+        let matches_at = |start: usize| {
+            start + hunk.old_lines.len() <= lines.len()
+                && hunk
+                    .old_lines
+                    .iter()
+                    .zip(&lines[start..])
+                    .all(|(old, line)| old == line_text(line))
+        };
+        let position = (0..=lines.len())
+            .flat_map(|distance| [expected.checked_sub(distance), Some(expected + distance)])
+            .flatten()
+            .filter(|start| *start >= next && *start <= lines.len())
+            .find(|start| matches_at(*start))
+            .ok_or_else(|| format!("Hunk {} does not apply", index + 1))?;
+        for line in &lines[next..position] {
+            output.push_str(line);
+        }
+        let replaced_end = position + hunk.old_lines.len();
+        for (line_index, line) in hunk.new_lines.iter().enumerate() {
+            output.push_str(line);
+            let is_last = line_index + 1 == hunk.new_lines.len();
+            if !(is_last && hunk.new_missing_newline) {
+                output.push_str(eol);
+            }
+        }
+        next = replaced_end;
+    }
+    for line in &lines[next..] {
+        output.push_str(line);
+    }
+    Ok(output)
+}
+
+pub(crate) async fn resolve_root(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<PathBuf, String> {
+    let workspaces = workspaces.read().await;
+    let entry = workspaces
+        .get(workspace_id)
+        .ok_or_else(|| "workspace not found".to_string())?;
+    Ok(PathBuf::from(&entry.path))
+}
+
+/// Runs `edit` on the blocking pool while holding `file_writes`, so the
+/// hash check and the rename that follows it are not interleaved with
+/// another edit.
+async fn run_edit<F>(file_writes: &FileWrites, edit: F) -> Result<WorkspaceFileWriteResult, String>
+where
+    F: FnOnce() -> Result<WorkspaceFileWriteResult, String> + Send + 'static,
+{
+    let _guard = file_writes.0.lock().await;
+    tokio::task::spawn_blocking(edit)
+        .await
+        .map_err(|err| format!("File write failed: {err}"))?
+}
+
+pub(crate) async fn write_workspace_file_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    file_writes: &FileWrites,
+    workspace_id: &str,
+    path: &str,
+    content: &str,
+    expected_hash: Option<&str>,
+) -> Result<WorkspaceFileWriteResult, String> {
+    ensure_workspace_writable_core(workspaces, workspace_id).await?;
+    let root = resolve_root(workspaces, workspace_id).await?;
+    let path = path.to_string();
+    let content = content.to_string();
+    let expected_hash = expected_hash.map(str::to_string);
+    run_edit(file_writes, move || {
+        write_workspace_file_inner(&root, &path, &content, expected_hash.as_deref())
+    })
+    .await
+}
+
+pub(crate) async fn apply_workspace_patch_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    file_writes: &FileWrites,
+    workspace_id: &str,
+    path: &str,
+    patch: &str,
+    expected_hash: Option<&str>,
+) -> Result<WorkspaceFileWriteResult, String> {
+    ensure_workspace_writable_core(workspaces, workspace_id).await?;
+    let root = resolve_root(workspaces, workspace_id).await?;
+    let path = path.to_string();
+    let patch = patch.to_string();
+    let expected_hash = expected_hash.map(str::to_string);
+    run_edit(file_writes, move || {
+        apply_workspace_patch_inner(&root, &path, &patch, expected_hash.as_deref())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        root
+    }
+
+    #[test]
+    fn writes_check_the_expected_hash_and_stay_inside_the_workspace() {
+        let root = temp_root();
+        let created = write_workspace_file_inner(&root, "src/lib.rs", "one\n", None).unwrap();
+        assert!(created.created);
+        assert_eq!(created.hash, content_hash(b"one\n"));
+
+        let stale = content_hash(b"something else");
+        assert!(write_workspace_file_inner(&root, "src/lib.rs", "two\n", Some(&stale)).is_err());
+        let updated =
+            write_workspace_file_inner(&root, "src/lib.rs", "two\n", Some(&created.hash)).unwrap();
+        assert!(!updated.created);
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "two\n"
+        );
+        assert_eq!(file_hash(&root.join("src/lib.rs")).unwrap(), updated.hash);
+
+        assert!(write_workspace_file_inner(&root, "../escape.txt", "x", None).is_err());
+        assert!(write_workspace_file_inner(&root, "/tmp/escape.txt", "x", None).is_err());
+        assert!(write_workspace_file_inner(&root, ".git/config", "x", None).is_err());
+        let too_big = "x".repeat(MAX_WRITE_BYTES + 1);
+        assert!(write_workspace_file_inner(&root, "big.txt", &too_big, None).is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_into_git_are_refused() {
+        let root = temp_root();
+        std::fs::create_dir_all(root.join(".git/hooks")).unwrap();
+        std::fs::write(root.join(".git/config"), "[core]\n").unwrap();
+        std::os::unix::fs::symlink(root.join(".git/config"), root.join("config-link")).unwrap();
+        std::os::unix::fs::symlink(root.join(".git/hooks"), root.join("hooks-link")).unwrap();
+
+        for path in ["config-link", "hooks-link/pre-commit"] {
+            let error = write_workspace_file_inner(&root, path, "x", None).unwrap_err();
+            assert_eq!(error, GIT_DIR_REFUSED);
+        }
+        assert_eq!(
+            std::fs::read_to_string(root.join(".git/config")).unwrap(),
+            "[core]\n"
+        );
+        assert!(!root.join(".git/hooks/pre-commit").exists());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn unified_diffs_apply_with_drifted_line_numbers() {
+        let original = "a\nb\nc\nd\ne\nf\n";
+        let patch = "--- a/file.txt\n+++ b/file.txt\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n@@ -9,2 +9,3 @@\n e\n+e2\n f\n";
+        assert_eq!(
+            apply_unified_diff(original, patch).unwrap(),
+            "a\nB\nc\nd\ne\ne2\nf\n"
+        );
+        assert!(apply_unified_diff(original, "@@ -1 +1 @@\n-z\n+y\n").is_err());
+        assert_eq!(
+            apply_unified_diff("", "@@ -0,0 +1 @@\n+new\n\\ No newline at end of file\n").unwrap(),
+            "new"
+        );
+        assert_eq!(
+            apply_unified_diff("x\r\ny\r\n", "@@ -2 +2 @@\n-y\n+z\n").unwrap(),
+            "x\r\nz\r\n"
+        );
+        // A removed line that itself starts with `-- ` is not a file header.
+        assert_eq!(
+            apply_unified_diff(
+                "select 1;\n-- note\n",
+                "@@ -1,2 +1 @@\n select 1;\n--- note\n"
+            )
+            .unwrap(),
+            "select 1;\n"
+        );
+        assert!(apply_unified_diff("a\n", "@@ -1 +1 @@\n-a\n+b\ndiff --git a/x b/x\n").is_err());
+    }
+}
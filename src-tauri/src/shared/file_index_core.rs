@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::backend::services::BackendServices;
+use crate::shared::file_watch_core::{FileChange, FileChangeKind};
+use crate::types::WorkspaceEntry;
+
+/// How long a listing is reused for a workspace without a file watcher.
+const UNWATCHED_TTL: Duration = Duration::from_secs(15);
+const DEFAULT_PAGE_SIZE: usize = 1000;
+const MAX_PAGE_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFilePage {
+    pub(crate) files: Vec<String>,
+    pub(crate) offset: usize,
+    /// Files in the whole listing.
+    pub(crate) total: usize,
+    /// Where the next page starts; `None` on the last page.
+    pub(crate) next_offset: Option<usize>,
+    /// Milliseconds since the listing was walked.
+    pub(crate) age_ms: u64,
+}
+
+struct CachedListing {
+    root: PathBuf,
+    files: Arc<Vec<String>>,
+    built_at: Instant,
+    /// Built while a watcher ran, so its batches keep the listing current.
+    watched: bool,
+}
+
+/// Sorted file listings by workspace id.
+#[derive(Default)]
+pub(crate) struct FileIndexes {
+    listings: Mutex<HashMap<String, CachedListing>>,
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(
+        name,
+        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
+    )
+}
+
+impl FileIndexes {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, CachedListing>> {
+        self.listings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// `watching` is whether the workspace's file watcher is running now.
+    fn fresh(
+        &self,
+        workspace_id: &str,
+        root: &PathBuf,
+        watching: bool,
+    ) -> Option<(Arc<Vec<String>>, Instant)> {
+        let listings = self.lock();
+        let listing = listings.get(workspace_id)?;
+        if &listing.root != root {
+            return None;
+        }
+        let current = if listing.watched && watching {
+            true
+        } else {
+            listing.built_at.elapsed() < UNWATCHED_TTL
+        };
+        current.then(|| (Arc::clone(&listing.files), listing.built_at))
+    }
+
+    fn store(
+        &self,
+        workspace_id: &str,
+        root: PathBuf,
+        files: Vec<String>,
+        watched: bool,
+    ) -> (Arc<Vec<String>>, Instant) {
+        let files = Arc::new(files);
+        let built_at = Instant::now();
+        self.lock().insert(
+            workspace_id.to_string(),
+            CachedListing {
+                root,
+                files: Arc::clone(&files),
+                built_at,
+                watched,
+            },
+        );
+        (files, built_at)
+    }
+
+    pub(crate) fn invalidate(&self, workspace_id: &str) {
+        self.lock().remove(workspace_id);
+    }
+
+    /// Patches a listing with a `workspace/filesChanged` batch. New
+    /// directories and truncated batches drop the listing so the next call
+    /// walks again.
+    pub(crate) fn apply_changes(
+        &self,
+        workspace_id: &str,
+        changes: &[FileChange],
+        truncated: bool,
+    ) {
+        let mut listings = self.lock();
+        if truncated {
+            listings.remove(workspace_id);
+            return;
+        }
+        let Some(listing) = listings.get_mut(workspace_id) else {
+            return;
+        };
+        let root = listing.root.clone();
+        let files = Arc::make_mut(&mut listing.files);
+        let mut stale = false;
+        for change in changes {
+            if change.path.is_empty() || change.path.split('/').any(should_skip_dir) {
+                continue;
+            }
+            match change.kind {
+                FileChangeKind::Created => {
+                    let path = root.join(&change.path);
+                    if path.is_dir() {
+                        stale = true;
+                        break;
+                    }
+                    if let Err(index) = files.binary_search(&change.path) {
+                        if path.is_file() {
+                            files.insert(index, change.path.clone());
+                        }
+                    }
+                }
+                FileChangeKind::Deleted => {
+                    // The path may have been a directory.
+                    let prefix = format!("{}/", change.path);
+                    files.retain(|file| file != &change.path && !file.starts_with(&prefix));
+                }
+                FileChangeKind::Modified => {}
+            }
+        }
+        if stale {
+            listings.remove(workspace_id);
+        }
+    }
+}
+
+/// The workspace's files, walking the tree only when there is no current
+/// cached listing or `refresh` is set.
+pub(crate) async fn cached_workspace_files_core<F>(
+    services: &BackendServices,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    refresh: bool,
+    list_files: F,
+) -> Result<(Arc<Vec<String>>, Instant), String>
+where
+    F: FnOnce(&PathBuf) -> Vec<String> + Send + 'static,
+{
+    let root = {
+        let workspaces = workspaces.read().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let indexes = &services.file_indexes;
+    // Batches that arrive mid-walk are lost, so only trust watcher upkeep
+    // when the watcher was already running before the walk started.
+    let watched = services.file_watchers.is_watching(workspace_id);
+    if !refresh {
+        if let Some(cached) = indexes.fresh(workspace_id, &root, watched) {
+            return Ok(cached);
+        }
+    }
+    let walk_root = root.clone();
+    // Walking a large tree takes a while; keep it off the async workers.
+    let files = tokio::task::spawn_blocking(move || list_files(&walk_root))
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(indexes.store(workspace_id, root, files, watched))
+}
+
+pub(crate) async fn list_workspace_files_page_core<F>(
+    services: &BackendServices,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    refresh: bool,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    list_files: F,
+) -> Result<WorkspaceFilePage, String>
+where
+    F: FnOnce(&PathBuf) -> Vec<String> + Send + 'static,
+{
+    let (files, built_at) =
+        cached_workspace_files_core(services, workspaces, workspace_id, refresh, list_files)
+            .await?;
+    Ok(page(&files, built_at, offset, limit))
+}
+
+fn page(
+    files: &[String],
+    built_at: Instant,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> WorkspaceFilePage {
+    let total = files.len();
+    let offset = offset.unwrap_or(0).min(total);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let end = (offset + limit).min(total);
+    WorkspaceFilePage {
+        files: files[offset..end].to_vec(),
+        offset,
+        total,
+        next_offset: (end < total).then_some(end),
+        age_ms: built_at.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, kind: FileChangeKind) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn watcher_batches_patch_the_listing() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).expect("create root");
+        std::fs::write(root.join("src/new.rs"), "").expect("write file");
+        let indexes = FileIndexes::default();
+        let files = vec!["README.md", "src/lib.rs", "src/old/a.rs", "src/old/b.rs"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        indexes.store("ws-1", root.clone(), files, true);
+
+        indexes.apply_changes(
+            "ws-1",
+            &[
+                change("src/new.rs", FileChangeKind::Created),
+                change("src/old", FileChangeKind::Deleted),
+                change("node_modules/x.js", FileChangeKind::Created),
+                change("src/lib.rs", FileChangeKind::Modified),
+            ],
+            false,
+        );
+        let listing = Arc::clone(&indexes.lock()["ws-1"].files);
+        assert_eq!(*listing, ["README.md", "src/lib.rs", "src/new.rs"]);
+
+        let first = page(&listing, Instant::now(), None, Some(2));
+        assert_eq!(first.files, ["README.md", "src/lib.rs"]);
+        assert_eq!((first.total, first.next_offset), (3, Some(2)));
+        let last = page(&listing, Instant::now(), first.next_offset, Some(2));
+        assert_eq!((last.files.len(), last.next_offset), (1, None));
+
+        indexes.apply_changes("ws-1", &[change("src", FileChangeKind::Created)], false);
+        assert!(!indexes.lock().contains_key("ws-1"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
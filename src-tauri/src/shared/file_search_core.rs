@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::types::WorkspaceEntry;
+use crate::utils::normalize_git_path;
+
+const DEFAULT_MAX_RESULTS: usize = 200;
+const MAX_RESULTS: usize = 2000;
+/// Larger files are skipped; they are rarely source and slow the scan.
+const MAX_SEARCHED_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// A NUL byte in this many leading bytes marks a file as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const MAX_SNIPPET_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileSearchMatch {
+    pub(crate) path: String,
+    /// 1-based.
+    pub(crate) line: usize,
+    /// 1-based, in characters.
+    pub(crate) column: usize,
+    pub(crate) snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileSearchResult {
+    pub(crate) matches: Vec<FileSearchMatch>,
+    /// More matches exist than `maxResults`.
+    pub(crate) truncated: bool,
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(
+        name,
+        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
+    )
+}
+
+/// Builds the matcher. Plain queries are literal; both kinds ignore case
+/// unless the query has an uppercase letter.
+fn build_matcher(query: &str, regex: bool) -> Result<Regex, String> {
+    if query.is_empty() {
+        return Err("Search query is empty.".to_string());
+    }
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!query.chars().any(char::is_uppercase))
+        .build()
+        .map_err(|err| format!("Invalid search pattern: {err}"))
+}
+
+fn is_binary(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return true;
+    };
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    if file
+        .take(BINARY_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .is_err()
+    {
+        return true;
+    }
+    head.contains(&0)
+}
+
+fn snippet(line: &str, match_start: usize) -> String {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.chars().count() <= MAX_SNIPPET_CHARS {
+        return line.to_string();
+    }
+    // Keep some context before the match in long lines.
+    let start = line[..match_start]
+        .char_indices()
+        .rev()
+        .nth(MAX_SNIPPET_CHARS / 4)
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    line[start..].chars().take(MAX_SNIPPET_CHARS).collect()
+}
+
+/// Greps every file the workspace file list would show, in path order.
+pub(crate) fn search_files(root: &Path, matcher: &Regex, max_results: usize) -> FileSearchResult {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+
+    let mut matches = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        if entry
+            .metadata()
+            .map_or(true, |meta| meta.len() > MAX_SEARCHED_FILE_BYTES)
+            || is_binary(path)
+        {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = normalize_git_path(&relative.to_string_lossy());
+        let Ok(file) = File::open(path) else {
+            continue;
+        };
+        let mut reader = BufReader::new(file);
+        let mut buffer = Vec::new();
+        let mut line_number = 0;
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            line_number += 1;
+            let line = String::from_utf8_lossy(&buffer);
+            let Some(found) = matcher.find(&line) else {
+                continue;
+            };
+            if matches.len() == max_results {
+                return FileSearchResult {
+                    matches,
+                    truncated: true,
+                };
+            }
+            matches.push(FileSearchMatch {
+                path: relative.clone(),
+                line: line_number,
+                column: line[..found.start()].chars().count() + 1,
+                snippet: snippet(&line, found.start()),
+            });
+        }
+    }
+    FileSearchResult {
+        matches,
+        truncated: false,
+    }
+}
+
+pub(crate) async fn search_workspace_files_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    query: &str,
+    regex: bool,
+    max_results: Option<usize>,
+) -> Result<FileSearchResult, String> {
+    let root = {
+        let workspaces = workspaces.read().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let matcher = build_matcher(query, regex)?;
+    let max_results = max_results
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS);
+    // Reading every file takes a while; keep it off the async workers.
+    tokio::task::spawn_blocking(move || search_files(&root, &matcher, max_results))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).expect("create root");
+        root
+    }
+
+    #[test]
+    fn finds_lines_and_respects_gitignore() {
+        let root = temp_root();
+        std::fs::write(root.join(".gitignore"), "generated/\n").expect("write gitignore");
+        std::fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    let Total = 1;\n    println!(\"{total}\");\n}\n",
+        )
+        .expect("write main");
+        std::fs::create_dir_all(root.join("generated")).expect("create generated");
+        std::fs::write(root.join("generated/out.rs"), "let total = 2;\n").expect("write out");
+        std::fs::write(root.join("src/data.bin"), b"total\0\x01").expect("write binary");
+
+        let result = search_files(&root, &build_matcher("total", false).unwrap(), 10);
+        assert!(!result.truncated);
+        let found: Vec<_> = result
+            .matches
+            .iter()
+            .map(|hit| (hit.path.as_str(), hit.line, hit.column))
+            .collect();
+        assert_eq!(found, [("src/main.rs", 2, 9), ("src/main.rs", 3, 16)]);
+        assert_eq!(result.matches[0].snippet, "    let Total = 1;");
+
+        let result = search_files(&root, &build_matcher("Total", false).unwrap(), 10);
+        assert_eq!(result.matches.len(), 1);
+        let result = search_files(&root, &build_matcher(r"let \w+ =", true).unwrap(), 10);
+        assert_eq!(result.matches.len(), 1);
+        let result = search_files(&root, &build_matcher("total", false).unwrap(), 1);
+        assert_eq!(result.matches.len(), 1);
+        assert!(result.truncated);
+        assert!(build_matcher("(", true).is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
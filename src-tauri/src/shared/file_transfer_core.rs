@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::shared::daemon_version_core::MAX_RPC_LINE_BYTES;
+use crate::shared::file_edit_core::{replace_file, resolve_root, resolve_writable_path};
+use crate::shared::storage_core::category_dir;
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
+use crate::types::{StorageCategory, WorkspaceEntry};
+
+/// Room left in a JSON-RPC line for the envelope and the other params.
+const RPC_ENVELOPE_BYTES: usize = 64 * 1024;
+/// Uploads and downloads larger than this are refused. Content travels
+/// base64-encoded in a single JSON-RPC line, so this is what still fits in
+/// the daemon's line limit once encoded.
+pub(crate) const MAX_TRANSFER_BYTES: usize = (MAX_RPC_LINE_BYTES - RPC_ENVELOPE_BYTES) / 4 * 3;
+/// Temporary uploads older than this are removed on the next upload.
+const UPLOAD_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UploadedFile {
+    /// Absolute path on the backend host.
+    pub(crate) path: String,
+    pub(crate) bytes: usize,
+    /// Stored in the temporary upload area rather than the workspace.
+    pub(crate) temporary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DownloadedFile {
+    pub(crate) relative_path: String,
+    pub(crate) bytes: usize,
+    pub(crate) bytes_base64: String,
+}
+
+/// Decodes upload content, accepting a `data:` URL as well as bare base64.
+fn decode_upload(bytes_base64: &str) -> Result<Vec<u8>, String> {
+    let encoded = bytes_base64.trim();
+    let encoded = match encoded.strip_prefix("data:") {
+        Some(rest) => rest
+            .split_once(";base64,")
+            .map(|(_, data)| data)
+            .ok_or_else(|| "Unsupported data URL".to_string())?,
+        None => encoded,
+    };
+    if encoded.len() / 4 * 3 > MAX_TRANSFER_BYTES + 2 {
+        return Err(format!(
+            "File exceeds the {MAX_TRANSFER_BYTES} byte transfer limit"
+        ));
+    }
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|err| format!("Invalid base64 content: {err}"))?;
+    if bytes.len() > MAX_TRANSFER_BYTES {
+        return Err(format!(
+            "File exceeds the {MAX_TRANSFER_BYTES} byte transfer limit"
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Keeps the last path component and replaces anything outside
+/// `[A-Za-z0-9._-]`, so the name is safe on every platform.
+fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    let sanitized: String = base
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+    if sanitized.is_empty() {
+        "upload".to_string()
+    } else {
+        sanitized.chars().take(100).collect()
+    }
+}
+
+/// Removes uploads older than `retention` under `uploads_dir`, then any
+/// per-workspace directories left empty.
+fn prune_uploads(uploads_dir: &Path, retention: Duration, now: SystemTime) {
+    let Ok(workspace_dirs) = std::fs::read_dir(uploads_dir) else {
+        return;
+    };
+    for workspace_dir in workspace_dirs.flatten() {
+        let dir = workspace_dir.path();
+        let Ok(files) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let expired = file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > retention);
+            if expired {
+                let _ = std::fs::remove_file(file.path());
+            }
+        }
+        let _ = std::fs::remove_dir(&dir);
+    }
+}
+
+/// Writes `bytes` to `relative_path` inside the workspace, or to a fresh file
+/// in the attachments directory under `<workspace>/` when no path is given.
+pub(crate) fn upload_file_inner(
+    root: &Path,
+    data_dir: &Path,
+    workspace_id: &str,
+    relative_path: Option<&str>,
+    file_name: Option<&str>,
+    bytes: &[u8],
+) -> Result<UploadedFile, String> {
+    let (path, temporary) = match relative_path.filter(|path| !path.trim().is_empty()) {
+        Some(relative_path) => (resolve_writable_path(root, relative_path)?, false),
+        None => {
+            let uploads_dir = category_dir(data_dir, StorageCategory::Attachments);
+            prune_uploads(&uploads_dir, UPLOAD_RETENTION, SystemTime::now());
+            let name = sanitize_file_name(file_name.unwrap_or_default());
+            let path = uploads_dir
+                .join(sanitize_file_name(workspace_id))
+                .join(format!("{}-{name}", uuid::Uuid::new_v4()));
+            (path, true)
+        }
+    };
+    replace_file(&path, bytes)?;
+    Ok(UploadedFile {
+        path: path.to_string_lossy().to_string(),
+        bytes: bytes.len(),
+        temporary,
+    })
+}
+
+pub(crate) fn download_file_inner(
+    root: &Path,
+    relative_path: &str,
+) -> Result<DownloadedFile, String> {
+    if relative_path.trim().is_empty() {
+        return Err("File path is required".to_string());
+    }
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let path = canonical_root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve file: {err}"))?;
+    if !path.starts_with(&canonical_root) {
+        return Err("Invalid file path".to_string());
+    }
+    let metadata = std::fs::metadata(&path).map_err(|err| format!("Failed to stat file: {err}"))?;
+    if !metadata.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    if metadata.len() > MAX_TRANSFER_BYTES as u64 {
+        return Err(format!(
+            "File exceeds the {MAX_TRANSFER_BYTES} byte transfer limit"
+        ));
+    }
+    let bytes = std::fs::read(&path).map_err(|err| format!("Failed to read file: {err}"))?;
+    Ok(DownloadedFile {
+        relative_path: relative_path.to_string(),
+        bytes: bytes.len(),
+        bytes_base64: STANDARD.encode(bytes),
+    })
+}
+
+/// Writing into the workspace needs it to be writable; temporary uploads
+/// only need it to exist.
+pub(crate) async fn upload_file_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    data_dir: &Path,
+    workspace_id: &str,
+    relative_path: Option<&str>,
+    file_name: Option<&str>,
+    bytes_base64: &str,
+) -> Result<UploadedFile, String> {
+    let into_workspace = relative_path.is_some_and(|path| !path.trim().is_empty());
+    if into_workspace {
+        ensure_workspace_writable_core(workspaces, workspace_id).await?;
+    }
+    let root = resolve_root(workspaces, workspace_id).await?;
+    let bytes = decode_upload(bytes_base64)?;
+    upload_file_inner(
+        &root,
+        data_dir,
+        workspace_id,
+        relative_path,
+        file_name,
+        &bytes,
+    )
+}
+
+pub(crate) async fn download_file_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    relative_path: &str,
+) -> Result<DownloadedFile, String> {
+    let root = resolve_root(workspaces, workspace_id).await?;
+    download_file_inner(&root, relative_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        dir
+    }
+
+    #[test]
+    fn uploads_land_in_the_workspace_or_the_upload_area() {
+        let root = temp_dir();
+        let data_dir = temp_dir();
+        let placed =
+            upload_file_inner(&root, &data_dir, "ws-1", Some("assets/a.png"), None, b"png")
+                .unwrap();
+        assert!(!placed.temporary);
+        assert_eq!(std::fs::read(root.join("assets/a.png")).unwrap(), b"png");
+
+        let temporary = upload_file_inner(
+            &root,
+            &data_dir,
+            "ws-1",
+            None,
+            Some("C:\\Users\\me\\Screen Shot.png"),
+            b"shot",
+        )
+        .unwrap();
+        assert!(temporary.temporary);
+        assert!(temporary.path.starts_with(
+            &*category_dir(&data_dir, StorageCategory::Attachments)
+                .join("ws-1")
+                .to_string_lossy()
+        ));
+        assert!(temporary.path.ends_with("-Screen_Shot.png"));
+        assert_eq!(std::fs::read(&temporary.path).unwrap(), b"shot");
+
+        assert!(
+            upload_file_inner(&root, &data_dir, "ws-1", Some("../escape"), None, b"x").is_err()
+        );
+
+        let downloaded = download_file_inner(&root, "assets/a.png").unwrap();
+        assert_eq!(downloaded.bytes, 3);
+        assert_eq!(STANDARD.decode(downloaded.bytes_base64).unwrap(), b"png");
+        assert!(download_file_inner(&root, "../../etc/passwd").is_err());
+        assert!(download_file_inner(&root, "assets").is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn uploads_decode_data_urls_and_enforce_the_limit() {
+        assert_eq!(decode_upload("aGk=").unwrap(), b"hi");
+        assert_eq!(decode_upload("data:image/png;base64,aGk=").unwrap(), b"hi");
+        assert!(decode_upload("data:text/plain,hi").is_err());
+        let too_big = "A".repeat((MAX_TRANSFER_BYTES / 3 + 2) * 4);
+        assert!(decode_upload(&too_big).is_err());
+        assert_eq!(sanitize_file_name("../../.bashrc"), "bashrc");
+        assert_eq!(sanitize_file_name(""), "upload");
+    }
+
+    #[test]
+    fn files_at_the_cap_fit_in_one_rpc_line_both_ways() {
+        let root = temp_dir();
+        let data_dir = temp_dir();
+        let content = vec![0xA5_u8; MAX_TRANSFER_BYTES];
+        let request = serde_json::json!({
+            "id": u64::MAX,
+            "method": "upload_file",
+            "params": {
+                "workspaceId": uuid::Uuid::new_v4().to_string(),
+                "relativePath": "assets/at-the-cap.bin",
+                "bytesBase64": STANDARD.encode(&content),
+            },
+        })
+        .to_string();
+        assert!(request.len() <= MAX_RPC_LINE_BYTES);
+
+        let bytes = decode_upload(&STANDARD.encode(&content)).unwrap();
+        upload_file_inner(
+            &root,
+            &data_dir,
+            "ws-1",
+            Some("assets/at-the-cap.bin"),
+            None,
+            &bytes,
+        )
+        .unwrap();
+        let downloaded = download_file_inner(&root, "assets/at-the-cap.bin").unwrap();
+        let response = serde_json::json!({ "id": u64::MAX, "result": downloaded }).to_string();
+        assert!(response.len() <= MAX_RPC_LINE_BYTES);
+        assert_eq!(STANDARD.decode(downloaded.bytes_base64).unwrap(), content);
+
+        let over = vec![0_u8; MAX_TRANSFER_BYTES + 1];
+        assert!(decode_upload(&STANDARD.encode(&over)).is_err());
+        std::fs::write(root.join("over.bin"), &over).unwrap();
+        assert!(download_file_inner(&root, "over.bin").is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn pruning_removes_expired_uploads_only() {
+        let data_dir = temp_dir();
+        let uploads = category_dir(&data_dir, StorageCategory::Attachments);
+        let dir = uploads.join("ws-1");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("old.png"), b"x").unwrap();
+
+        prune_uploads(&uploads, UPLOAD_RETENTION, SystemTime::now());
+        assert!(dir.join("old.png").exists());
+        let later = SystemTime::now() + UPLOAD_RETENTION + Duration::from_secs(60);
+        prune_uploads(&uploads, UPLOAD_RETENTION, later);
+        assert!(!dir.exists());
+
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::services::BackendServices;
+use crate::shared::git_status_core;
+use crate::types::WorkspaceEntry;
+
+/// Quiet time after the last change before a batch is emitted.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// A batch is emitted at least this often while changes keep coming.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+/// Larger batches are cut and flagged `truncated`; clients should rescan.
+const MAX_CHANGES_PER_EVENT: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct FileChange {
+    /// Relative to the workspace root, with `/` separators.
+    pub(crate) path: String,
+    pub(crate) kind: FileChangeKind,
+}
+
+/// Live file watchers by workspace id. Dropping a watcher stops its thread.
+#[derive(Default)]
+pub(crate) struct FileWatchers {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+impl FileWatchers {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, RecommendedWatcher>> {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn is_watching(&self, workspace_id: &str) -> bool {
+        self.lock().contains_key(workspace_id)
+    }
+
+    /// Returns whether a watcher was running.
+    pub(crate) fn unwatch(&self, workspace_id: &str) -> bool {
+        self.lock().remove(workspace_id).is_some()
+    }
+}
+
+/// Starts emitting `workspace/filesChanged` for the workspace, and
+/// `workspace/gitStatusChanged` once its git status has been read. Calling it
+/// again while the watcher runs is a no-op.
+pub(crate) async fn watch_workspace_files_core<E: EventSink>(
+    services: &Arc<BackendServices>,
+    workspace_id: &str,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event_sink: E,
+) -> Result<(), String> {
+    let entry = {
+        let workspaces = workspaces.read().await;
+        workspaces
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?
+    };
+    let root = PathBuf::from(&entry.path);
+    if services.file_watchers.is_watching(workspace_id) {
+        return Ok(());
+    }
+    let root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {}: {err}", root.display()))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |result| {
+        let _ = sender.send(result);
+    })
+    .map_err(|err| format!("Failed to start file watcher: {err}"))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", root.display()))?;
+
+    let ignored = load_gitignore(&root);
+    let thread_workspace_id = workspace_id.to_string();
+    let thread_services = Arc::clone(services);
+    std::thread::Builder::new()
+        .name(format!("file-watch-{workspace_id}"))
+        .spawn(move || {
+            run_debouncer(receiver, &root, &ignored, |changes, truncated| {
+                thread_services.file_indexes.apply_changes(
+                    &thread_workspace_id,
+                    &changes,
+                    truncated,
+                );
+                git_status_core::on_files_changed(
+                    &thread_services.git_statuses,
+                    &entry,
+                    &event_sink,
+                );
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: thread_workspace_id.clone(),
+                    message: json!({
+                        "method": "workspace/filesChanged",
+                        "params": {
+                            "workspaceId": thread_workspace_id,
+                            "changes": changes,
+                            "truncated": truncated,
+                        }
+                    }),
+                });
+            })
+        })
+        .map_err(|err| format!("Failed to start file watcher: {err}"))?;
+
+    services
+        .file_watchers
+        .lock()
+        .insert(workspace_id.to_string(), watcher);
+    Ok(())
+}
+
+pub(crate) fn unwatch_workspace_files_core(services: &BackendServices, workspace_id: &str) {
+    if services.file_watchers.unwatch(workspace_id) {
+        // Nothing keeps the cached listing current any more.
+        services.file_indexes.invalidate(workspace_id);
+    }
+}
+
+fn load_gitignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    let _ = builder.add(root.join(".gitignore"));
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Collects raw events into batches until the watcher is dropped.
+fn run_debouncer(
+    receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+    root: &Path,
+    ignored: &Gitignore,
+    mut emit: impl FnMut(Vec<FileChange>, bool),
+) {
+    let mut pending: Vec<(PathBuf, FileChangeKind)> = Vec::new();
+    let mut batch_started: Option<Instant> = None;
+    loop {
+        let received = match batch_started {
+            None => receiver
+                .recv()
+                .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            Some(started) => {
+                let deadline =
+                    (started + MAX_BATCH_DELAY).saturating_duration_since(Instant::now());
+                if deadline.is_zero() {
+                    Err(mpsc::RecvTimeoutError::Timeout)
+                } else {
+                    receiver.recv_timeout(DEBOUNCE.min(deadline))
+                }
+            }
+        };
+        match received {
+            Ok(Ok(event)) => {
+                for (path, kind) in classify_event(&event) {
+                    if is_ignored(root, ignored, &path) {
+                        continue;
+                    }
+                    pending.push((path, kind));
+                    batch_started.get_or_insert_with(Instant::now);
+                }
+            }
+            Ok(Err(_)) => {}
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let (changes, truncated) = coalesce_changes(root, std::mem::take(&mut pending));
+                batch_started = None;
+                if !changes.is_empty() {
+                    emit(changes, truncated);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+fn classify_event(event: &notify::Event) -> Vec<(PathBuf, FileChangeKind)> {
+    let all = |kind| {
+        event
+            .paths
+            .iter()
+            .map(|path| (path.clone(), kind))
+            .collect()
+    };
+    match event.kind {
+        EventKind::Create(_) => all(FileChangeKind::Created),
+        EventKind::Remove(_) => all(FileChangeKind::Deleted),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => all(FileChangeKind::Deleted),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => all(FileChangeKind::Created),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => vec![
+            (event.paths[0].clone(), FileChangeKind::Deleted),
+            (event.paths[1].clone(), FileChangeKind::Created),
+        ],
+        // Backends that cannot tell the two sides of a rename apart.
+        EventKind::Modify(ModifyKind::Name(_)) => event
+            .paths
+            .iter()
+            .map(|path| {
+                let kind = if path.exists() {
+                    FileChangeKind::Created
+                } else {
+                    FileChangeKind::Deleted
+                };
+                (path.clone(), kind)
+            })
+            .collect(),
+        EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => Vec::new(),
+        EventKind::Modify(_) | EventKind::Any | EventKind::Other => all(FileChangeKind::Modified),
+    }
+}
+
+fn is_ignored(root: &Path, ignored: &Gitignore, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return true;
+    };
+    if relative
+        .components()
+        .next()
+        .is_some_and(|first| first.as_os_str() == ".git")
+    {
+        return true;
+    }
+    ignored
+        .matched_path_or_any_parents(relative, path.is_dir())
+        .is_ignore()
+}
+
+/// Folds repeated events for a path into the net change since the batch
+/// started, keeping first-seen order.
+fn coalesce_changes(
+    root: &Path,
+    events: Vec<(PathBuf, FileChangeKind)>,
+) -> (Vec<FileChange>, bool) {
+    let mut order: Vec<PathBuf> = Vec::new();
+    // Path -> (first kind seen in the batch, latest kind).
+    let mut net: HashMap<PathBuf, (FileChangeKind, FileChangeKind)> = HashMap::new();
+    for (path, kind) in events {
+        match net.get_mut(&path) {
+            Some((_, latest)) => *latest = kind,
+            None => {
+                order.push(path.clone());
+                net.insert(path, (kind, kind));
+            }
+        }
+    }
+    let mut changes: Vec<FileChange> = order
+        .into_iter()
+        .filter_map(|path| {
+            let (first, latest) = net.remove(&path)?;
+            let kind = match (first, latest) {
+                (FileChangeKind::Created, FileChangeKind::Deleted) => return None,
+                (FileChangeKind::Created, _) => FileChangeKind::Created,
+                (_, FileChangeKind::Deleted) => FileChangeKind::Deleted,
+                (FileChangeKind::Deleted, _) => FileChangeKind::Modified,
+                (FileChangeKind::Modified, latest) => latest,
+            };
+            let relative = path.strip_prefix(root).ok()?;
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some(FileChange {
+                path: relative,
+                kind,
+            })
+        })
+        .collect();
+    let truncated = changes.len() > MAX_CHANGES_PER_EVENT;
+    changes.truncate(MAX_CHANGES_PER_EVENT);
+    (changes, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_events_fold_into_the_net_change() {
+        let root = PathBuf::from("/repo");
+        let events = vec![
+            (root.join("src/new.rs"), FileChangeKind::Created),
+            (root.join("src/new.rs"), FileChangeKind::Modified),
+            (root.join("src/tmp.rs"), FileChangeKind::Created),
+            (root.join("src/tmp.rs"), FileChangeKind::Deleted),
+            (root.join("README.md"), FileChangeKind::Modified),
+            (root.join("README.md"), FileChangeKind::Deleted),
+            (root.join("Cargo.toml"), FileChangeKind::Deleted),
+            (root.join("Cargo.toml"), FileChangeKind::Created),
+        ];
+        let (changes, truncated) = coalesce_changes(&root, events);
+        assert!(!truncated);
+        assert_eq!(
+            changes,
+            vec![
+                FileChange {
+                    path: "src/new.rs".to_string(),
+                    kind: FileChangeKind::Created,
+                },
+                FileChange {
+                    path: "README.md".to_string(),
+                    kind: FileChangeKind::Deleted,
+                },
+                FileChange {
+                    path: "Cargo.toml".to_string(),
+                    kind: FileChangeKind::Modified,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn git_dir_and_gitignored_paths_are_skipped() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").expect("write gitignore");
+        let ignored = load_gitignore(&root);
+
+        assert!(is_ignored(&root, &ignored, &root.join(".git/index")));
+        assert!(is_ignored(&root, &ignored, &root.join("target/debug/app")));
+        assert!(is_ignored(&root, &ignored, &root.join("server.log")));
+        assert!(!is_ignored(&root, &ignored, &root.join("src/main.rs")));
+        assert!(is_ignored(&root, &ignored, Path::new("/elsewhere/file")));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::codex::home as codex_home;
 use crate::files::io::TextFileResponse;
 use crate::files::ops::{read_with_policy, write_with_policy};
 use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
 use crate::types::WorkspaceEntry;
 
 fn resolve_default_codex_home() -> Result<PathBuf, String> {
@@ -15,10 +16,10 @@ fn resolve_default_codex_home() -> Result<PathBuf, String> {
 }
 
 async fn resolve_workspace_root(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<PathBuf, String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .ok_or_else(|| "workspace not found".to_string())?;
@@ -26,7 +27,7 @@ async fn resolve_workspace_root(
 }
 
 pub(crate) async fn resolve_root_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     scope: FileScope,
     workspace_id: Option<&str>,
 ) -> Result<PathBuf, String> {
@@ -40,7 +41,7 @@ pub(crate) async fn resolve_root_core(
 }
 
 pub(crate) async fn file_read_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
@@ -51,13 +52,16 @@ pub(crate) async fn file_read_core(
 }
 
 pub(crate) async fn file_write_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
     content: String,
 ) -> Result<(), String> {
     let policy = policy_for(scope, kind)?;
+    if let (FileScope::Workspace, Some(workspace_id)) = (scope, workspace_id.as_deref()) {
+        ensure_workspace_writable_core(workspaces, workspace_id).await?;
+    }
     let root = resolve_root_core(workspaces, scope, workspace_id.as_deref()).await?;
     write_with_policy(&root, policy, &content)
 }
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use crate::shared::process_core::tokio_command;
@@ -93,6 +94,19 @@ pub(crate) async fn git_branch_exists(repo_path: &PathBuf, branch: &str) -> Resu
     Ok(status.success())
 }
 
+/// Branches checked out in any worktree of the repository, the main one
+/// included. Git refuses to check these out a second time.
+pub(crate) async fn git_checked_out_branches(
+    repo_path: &PathBuf,
+) -> Result<HashSet<String>, String> {
+    let output = run_git_command(repo_path, &["worktree", "list", "--porcelain"]).await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.strip_prefix("branch refs/heads/"))
+        .map(str::to_string)
+        .collect())
+}
+
 pub(crate) async fn git_remote_exists(repo_path: &PathBuf, remote: &str) -> Result<bool, String> {
     let git_bin = resolve_git_binary().map_err(|err| format!("Failed to run git: {err}"))?;
     let status = tokio_command(git_bin)
@@ -129,8 +143,6 @@ pub(crate) async fn git_remote_branch_exists_live(
     Err(format_git_error(&output.stdout, &output.stderr))
 }
 
-// Used by daemon-only worktree orchestration paths.
-#[allow(dead_code)]
 pub(crate) async fn git_remote_branch_exists_local(
     repo_path: &PathBuf,
     remote: &str,
@@ -183,8 +195,6 @@ pub(crate) async fn git_find_remote_for_branch_live(
     Ok(None)
 }
 
-// Used by daemon-only worktree orchestration paths.
-#[allow(dead_code)]
 pub(crate) async fn git_find_remote_tracking_branch_local(
     repo_path: &PathBuf,
     branch: &str,
@@ -241,3 +251,86 @@ pub(crate) async fn git_get_origin_url(repo_path: &PathBuf) -> Option<String> {
         .await
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio::runtime::Runtime;
+
+    fn temp_repo() -> (PathBuf, Repository) {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("codex-monitor-git-core-{nonce}"));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let repo = Repository::init(&dir).expect("init repo");
+        let oid = {
+            let signature = git2::Signature::now("Test", "test@example.com").expect("signature");
+            let tree_id = repo
+                .index()
+                .expect("index")
+                .write_tree()
+                .expect("write tree");
+            let tree = repo.find_tree(tree_id).expect("tree");
+            repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+                .expect("commit")
+        };
+        repo.reference("refs/heads/feature", oid, true, "test")
+            .expect("local branch");
+        repo.reference("refs/remotes/upstream/shared", oid, true, "test")
+            .expect("remote tracking ref");
+        repo.remote("upstream", "https://example.com/repo.git")
+            .expect("add remote");
+        (dir, repo)
+    }
+
+    #[test]
+    fn format_git_error_prefers_stderr_then_stdout() {
+        assert_eq!(format_git_error(b"out", b" fatal: nope \n"), "fatal: nope");
+        assert_eq!(format_git_error(b" out \n", b"  "), "out");
+        assert_eq!(format_git_error(b"", b""), "Git command failed.");
+    }
+
+    #[test]
+    fn is_missing_worktree_error_matches_git_message() {
+        assert!(is_missing_worktree_error(
+            "fatal: '/tmp/wt' is not a working tree"
+        ));
+        assert!(!is_missing_worktree_error("fatal: not a git repository"));
+    }
+
+    #[test]
+    fn branch_lookups_use_local_refs() {
+        let (dir, repo) = temp_repo();
+        let runtime = Runtime::new().expect("create tokio runtime");
+        runtime.block_on(async {
+            assert!(git_branch_exists(&dir, "feature").await.expect("lookup"));
+            assert!(!git_branch_exists(&dir, "missing").await.expect("lookup"));
+            assert_eq!(
+                git_find_remote_tracking_branch_local(&dir, "shared")
+                    .await
+                    .expect("lookup"),
+                Some("upstream/shared".to_string())
+            );
+            assert_eq!(
+                git_find_remote_tracking_branch_local(&dir, "feature")
+                    .await
+                    .expect("lookup"),
+                None
+            );
+            let head = repo.head().expect("head");
+            let checked_out = git_checked_out_branches(&dir).await.expect("worktrees");
+            assert!(checked_out.contains(head.shorthand().expect("head branch")));
+            assert!(!checked_out.contains("feature"));
+            let (name, changed) = unique_branch_name_live(&dir, "feature", None)
+                .await
+                .expect("unique name");
+            assert_eq!(name, "feature-2");
+            assert!(changed);
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
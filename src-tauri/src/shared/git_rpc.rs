@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 pub(crate) const METHOD_GET_GIT_STATUS: &str = "get_git_status";
+pub(crate) const METHOD_GIT_STATUS_ALL: &str = "git_status_all";
 pub(crate) const METHOD_INIT_GIT_REPO: &str = "init_git_repo";
 pub(crate) const METHOD_CREATE_GITHUB_REPO: &str = "create_github_repo";
 pub(crate) const METHOD_STAGE_GIT_FILE: &str = "stage_git_file";
@@ -20,6 +21,7 @@ pub(crate) const METHOD_GET_GIT_DIFFS: &str = "get_git_diffs";
 pub(crate) const METHOD_GET_GIT_LOG: &str = "get_git_log";
 pub(crate) const METHOD_GET_GIT_COMMIT_DIFF: &str = "get_git_commit_diff";
 pub(crate) const METHOD_GET_GIT_REMOTE: &str = "get_git_remote";
+pub(crate) const METHOD_GIT_AUTH_DOCTOR: &str = "git_auth_doctor";
 pub(crate) const METHOD_GET_GITHUB_ISSUES: &str = "get_github_issues";
 pub(crate) const METHOD_GET_GITHUB_PULL_REQUESTS: &str = "get_github_pull_requests";
 pub(crate) const METHOD_GET_GITHUB_PULL_REQUEST_DIFF: &str = "get_github_pull_request_diff";
@@ -28,6 +30,12 @@ pub(crate) const METHOD_CHECKOUT_GITHUB_PULL_REQUEST: &str = "checkout_github_pu
 pub(crate) const METHOD_LIST_GIT_BRANCHES: &str = "list_git_branches";
 pub(crate) const METHOD_CHECKOUT_GIT_BRANCH: &str = "checkout_git_branch";
 pub(crate) const METHOD_CREATE_GIT_BRANCH: &str = "create_git_branch";
+pub(crate) const METHOD_DELETE_GIT_BRANCH: &str = "delete_git_branch";
+pub(crate) const METHOD_SAVE_GIT_STASH: &str = "save_git_stash";
+pub(crate) const METHOD_LIST_GIT_STASHES: &str = "list_git_stashes";
+pub(crate) const METHOD_POP_GIT_STASH: &str = "pop_git_stash";
+pub(crate) const METHOD_GET_GIT_CONFLICTS: &str = "get_git_conflicts";
+pub(crate) const METHOD_RESOLVE_GIT_CONFLICT: &str = "resolve_git_conflict";
 #[allow(dead_code)]
 pub(crate) const METHOD_GENERATE_COMMIT_MESSAGE: &str = "generate_commit_message";
 
@@ -47,6 +55,12 @@ pub(crate) struct WorkspaceIdRequest {
     pub(crate) workspace_id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStatusAllRequest {
+    pub(crate) refresh: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct InitGitRepoRequest {
@@ -128,6 +142,36 @@ pub(crate) struct WorkspaceNameRequest {
     pub(crate) name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeleteGitBranchRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) name: String,
+    pub(crate) force: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SaveGitStashRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PopGitStashRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) index: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResolveGitConflictRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) path: String,
+    pub(crate) content: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct GenerateCommitMessageRequest {
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{RwLock, Semaphore};
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::services::BackendServices;
+use crate::shared::git_ui_core;
+use crate::types::WorkspaceEntry;
+
+/// Statuses are reused this long; watcher batches drop them sooner. Changes
+/// under `.git` (staging, commits from a terminal) are not watched, so this
+/// stays short.
+const STATUS_TTL: Duration = Duration::from_secs(5);
+/// Repositories read at once by `git_status_all`.
+const MAX_PARALLEL_STATUS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceGitStatus {
+    pub(crate) workspace_id: String,
+    /// The `get_git_status` payload, or `None` when it failed.
+    pub(crate) status: Option<Value>,
+    pub(crate) error: Option<String>,
+    /// Age of the cached result in ms; 0 when it was just read.
+    pub(crate) age_ms: u64,
+}
+
+struct CachedStatus {
+    path: String,
+    result: Result<Value, String>,
+    fetched_at: Instant,
+    /// Set by a watcher batch; the result is kept to tell whether the next
+    /// read changed anything.
+    stale: bool,
+}
+
+/// The last git status read per workspace.
+#[derive(Default)]
+pub(crate) struct GitStatusCache {
+    entries: Mutex<HashMap<String, CachedStatus>>,
+}
+
+impl GitStatusCache {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, CachedStatus>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn fresh(&self, workspace_id: &str, path: &str) -> Option<(Result<Value, String>, Instant)> {
+        let entries = self.lock();
+        let cached = entries.get(workspace_id)?;
+        let current =
+            !cached.stale && cached.path == path && cached.fetched_at.elapsed() < STATUS_TTL;
+        current.then(|| (cached.result.clone(), cached.fetched_at))
+    }
+
+    /// Whether a client has read this workspace's status, so file changes
+    /// should refresh it.
+    pub(crate) fn is_tracked(&self, workspace_id: &str) -> bool {
+        self.lock().contains_key(workspace_id)
+    }
+
+    pub(crate) fn invalidate(&self, workspace_id: &str) {
+        if let Some(cached) = self.lock().get_mut(workspace_id) {
+            cached.stale = true;
+        }
+    }
+
+    /// Returns whether the result differs from the one it replaces. A first
+    /// result is not a change.
+    fn store(&self, workspace_id: &str, path: &str, result: Result<Value, String>) -> bool {
+        let mut entries = self.lock();
+        let changed = entries
+            .get(workspace_id)
+            .is_some_and(|previous| previous.path == path && previous.result != result);
+        entries.insert(
+            workspace_id.to_string(),
+            CachedStatus {
+                path: path.to_string(),
+                result,
+                fetched_at: Instant::now(),
+                stale: false,
+            },
+        );
+        changed
+    }
+}
+
+fn status_row(
+    workspace_id: String,
+    result: Result<Value, String>,
+    fetched_at: Instant,
+) -> WorkspaceGitStatus {
+    let (status, error) = match result {
+        Ok(status) => (Some(status), None),
+        Err(error) => (None, Some(error)),
+    };
+    WorkspaceGitStatus {
+        workspace_id,
+        status,
+        error,
+        age_ms: fetched_at.elapsed().as_millis() as u64,
+    }
+}
+
+/// Blocking. Reads the status, caches it, and emits
+/// `workspace/gitStatusChanged` when it differs from the cached one.
+pub(crate) fn refresh_git_status<E: EventSink>(
+    cache: &GitStatusCache,
+    entry: &WorkspaceEntry,
+    event_sink: &E,
+) -> Result<Value, String> {
+    let result = git_ui_core::git_status_for_entry_core(entry);
+    if cache.store(&entry.id, &entry.path, result.clone()) {
+        let (status, error) = match &result {
+            Ok(status) => (status.clone(), Value::Null),
+            Err(error) => (Value::Null, Value::String(error.clone())),
+        };
+        event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: entry.id.clone(),
+            message: json!({
+                "method": "workspace/gitStatusChanged",
+                "params": {
+                    "workspaceId": entry.id,
+                    "status": status,
+                    "error": error,
+                }
+            }),
+        });
+    }
+    result
+}
+
+/// Called with each watcher batch: drops the cached status and, if a client
+/// has read it, reads it again so a change is pushed.
+pub(crate) fn on_files_changed<E: EventSink>(
+    cache: &GitStatusCache,
+    entry: &WorkspaceEntry,
+    event_sink: &E,
+) {
+    if !cache.is_tracked(&entry.id) {
+        return;
+    }
+    cache.invalidate(&entry.id);
+    let _ = refresh_git_status(cache, entry, event_sink);
+}
+
+/// Git status for every workspace, sorted by name. Cached results younger
+/// than the TTL are reused unless `refresh` is set; the rest are read in
+/// parallel, a few repositories at a time.
+pub(crate) async fn git_status_all_core<E: EventSink>(
+    services: &Arc<BackendServices>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    refresh: bool,
+    event_sink: E,
+) -> Vec<WorkspaceGitStatus> {
+    let mut entries: Vec<WorkspaceEntry> = workspaces.read().await.values().cloned().collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    let limiter = Arc::new(Semaphore::new(MAX_PARALLEL_STATUS));
+    join_all(entries.into_iter().map(|entry| {
+        let limiter = Arc::clone(&limiter);
+        let event_sink = event_sink.clone();
+        let services = Arc::clone(services);
+        async move {
+            if !refresh {
+                if let Some((result, fetched_at)) =
+                    services.git_statuses.fresh(&entry.id, &entry.path)
+                {
+                    return status_row(entry.id, result, fetched_at);
+                }
+            }
+            let _permit = limiter.acquire_owned().await;
+            let workspace_id = entry.id.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                refresh_git_status(&services.git_statuses, &entry, &event_sink)
+            })
+            .await
+            .unwrap_or_else(|err| Err(err.to_string()));
+            status_row(workspace_id, result, Instant::now())
+        }
+    }))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_different_result_counts_as_a_change() {
+        let cache = GitStatusCache::default();
+        assert!(!cache.store("w1", "/repo", Ok(json!({ "files": [] }))));
+        assert!(!cache.store("w1", "/repo", Ok(json!({ "files": [] }))));
+        assert!(cache.store("w1", "/repo", Ok(json!({ "files": ["a.rs"] }))));
+        assert!(cache.store("w1", "/repo", Err("not a repository".to_string())));
+        // A moved workspace starts over.
+        assert!(!cache.store("w1", "/moved", Ok(json!({ "files": [] }))));
+    }
+
+    #[test]
+    fn invalidated_statuses_are_not_reused() {
+        let cache = GitStatusCache::default();
+        assert!(cache.fresh("w1", "/repo").is_none());
+        cache.store("w1", "/repo", Ok(json!({ "branchName": "main" })));
+        assert!(cache.fresh("w1", "/repo").is_some());
+        assert!(cache.fresh("w1", "/elsewhere").is_none());
+
+        cache.invalidate("w1");
+        assert!(cache.fresh("w1", "/repo").is_none());
+        assert!(cache.is_tracked("w1"));
+        assert!(!cache.is_tracked("w2"));
+    }
+}
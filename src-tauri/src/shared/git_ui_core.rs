@@ -2,15 +2,21 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use serde_json::Value;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
 use crate::types::{
-    AppSettings, GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse, WorkspaceEntry,
+    AppSettings, GitAuthDoctorReport, GitCommitDiff, GitConflictsResponse, GitFileDiff,
+    GitHubIssuesResponse, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse, GitLogResponse, WorkspaceEntry,
 };
 
+#[path = "git_ui_core/auth_doctor.rs"]
+mod auth_doctor;
 #[path = "git_ui_core/commands.rs"]
 mod commands;
+#[path = "git_ui_core/conflicts.rs"]
+mod conflicts;
 #[path = "git_ui_core/context.rs"]
 mod context;
 #[path = "git_ui_core/diff.rs"]
@@ -25,7 +31,7 @@ mod log;
 mod tests;
 
 pub(crate) async fn resolve_repo_root_for_workspace_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<PathBuf, String> {
     context::resolve_repo_root_for_workspace(workspaces, workspace_id).await
@@ -36,33 +42,41 @@ pub(crate) fn collect_workspace_diff_core(repo_root: &Path) -> Result<String, St
 }
 
 pub(crate) async fn get_git_status_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     diff::get_git_status_inner(workspaces, workspace_id).await
 }
 
+/// Blocking; the same payload as `get_git_status` for an entry already in
+/// hand.
+pub(crate) fn git_status_for_entry_core(entry: &WorkspaceEntry) -> Result<Value, String> {
+    diff::git_status_for_entry(entry)
+}
+
 pub(crate) async fn init_git_repo_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     branch: String,
     force: bool,
 ) -> Result<Value, String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::init_git_repo_inner(workspaces, workspace_id, branch, force).await
 }
 
 pub(crate) async fn create_github_repo_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     repo: String,
     visibility: String,
     branch: Option<String>,
 ) -> Result<Value, String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::create_github_repo_inner(workspaces, workspace_id, repo, visibility, branch).await
 }
 
 pub(crate) async fn list_git_roots_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     depth: Option<usize>,
 ) -> Result<Vec<String>, String> {
@@ -70,7 +84,7 @@ pub(crate) async fn list_git_roots_core(
 }
 
 pub(crate) async fn get_git_diffs_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<Vec<GitFileDiff>, String> {
@@ -78,7 +92,7 @@ pub(crate) async fn get_git_diffs_core(
 }
 
 pub(crate) async fn get_git_log_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     limit: Option<usize>,
 ) -> Result<GitLogResponse, String> {
@@ -86,7 +100,7 @@ pub(crate) async fn get_git_log_core(
 }
 
 pub(crate) async fn get_git_commit_diff_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     sha: String,
@@ -95,102 +109,119 @@ pub(crate) async fn get_git_commit_diff_core(
 }
 
 pub(crate) async fn get_git_remote_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Option<String>, String> {
     log::get_git_remote_inner(workspaces, workspace_id).await
 }
 
+/// Diagnoses push/fetch authentication for the workspace's preferred remote.
+pub(crate) async fn git_auth_doctor_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitAuthDoctorReport, String> {
+    auth_doctor::git_auth_doctor_inner(workspaces, workspace_id).await
+}
+
 pub(crate) async fn stage_git_file_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::stage_git_file_inner(workspaces, workspace_id, path).await
 }
 
 pub(crate) async fn stage_git_all_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::stage_git_all_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn unstage_git_file_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::unstage_git_file_inner(workspaces, workspace_id, path).await
 }
 
 pub(crate) async fn revert_git_file_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::revert_git_file_inner(workspaces, workspace_id, path).await
 }
 
 pub(crate) async fn revert_git_all_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::revert_git_all_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn commit_git_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     message: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::commit_git_inner(workspaces, workspace_id, message).await
 }
 
 pub(crate) async fn push_git_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::push_git_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn pull_git_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::pull_git_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn fetch_git_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     commands::fetch_git_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn sync_git_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::sync_git_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn get_github_issues_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<GitHubIssuesResponse, String> {
     github::get_github_issues_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn get_github_pull_requests_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<GitHubPullRequestsResponse, String> {
     github::get_github_pull_requests_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn get_github_pull_request_diff_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
@@ -198,7 +229,7 @@ pub(crate) async fn get_github_pull_request_diff_core(
 }
 
 pub(crate) async fn get_github_pull_request_comments_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestComment>, String> {
@@ -206,32 +237,87 @@ pub(crate) async fn get_github_pull_request_comments_core(
 }
 
 pub(crate) async fn checkout_github_pull_request_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     github::checkout_github_pull_request_inner(workspaces, workspace_id, pr_number).await
 }
 
 pub(crate) async fn list_git_branches_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     commands::list_git_branches_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn checkout_git_branch_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     name: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::checkout_git_branch_inner(workspaces, workspace_id, name).await
 }
 
 pub(crate) async fn create_git_branch_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     name: String,
 ) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
     commands::create_git_branch_inner(workspaces, workspace_id, name).await
 }
+
+pub(crate) async fn delete_git_branch_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+    force: bool,
+) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
+    commands::delete_git_branch_inner(workspaces, workspace_id, name, force).await
+}
+
+pub(crate) async fn save_git_stash_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    message: Option<String>,
+) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
+    commands::save_git_stash_inner(workspaces, workspace_id, message).await
+}
+
+pub(crate) async fn list_git_stashes_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    commands::list_git_stashes_inner(workspaces, workspace_id).await
+}
+
+pub(crate) async fn pop_git_stash_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    index: Option<usize>,
+) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
+    commands::pop_git_stash_inner(workspaces, workspace_id, index).await
+}
+
+pub(crate) async fn get_git_conflicts_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitConflictsResponse, String> {
+    conflicts::get_git_conflicts_inner(workspaces, workspace_id).await
+}
+
+pub(crate) async fn resolve_git_conflict_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
+    conflicts::resolve_git_conflict_inner(workspaces, workspace_id, path, content).await
+}
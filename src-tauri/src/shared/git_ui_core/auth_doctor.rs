@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use git2::Repository;
+use tokio::sync::RwLock;
+use tokio::time::timeout;
+
+use crate::git_utils::resolve_git_root;
+use crate::shared::process_core::tokio_command;
+use crate::types::{GitAuthCheck, GitAuthCheckStatus, GitAuthDoctorReport, WorkspaceEntry};
+use crate::utils::{git_env_path, resolve_git_binary};
+
+use super::context::workspace_entry_for_id;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RemoteTarget {
+    pub(super) scheme: String,
+    pub(super) user: Option<String>,
+    pub(super) host: Option<String>,
+    pub(super) port: Option<u16>,
+}
+
+impl RemoteTarget {
+    fn ssh_destination(&self) -> Option<String> {
+        let host = self.host.as_deref()?;
+        Some(match self.user.as_deref() {
+            Some(user) => format!("{user}@{host}"),
+            None => host.to_string(),
+        })
+    }
+}
+
+/// Splits a remote URL into scheme and endpoint. Handles `scheme://` URLs,
+/// scp-style `user@host:path` SSH remotes and plain local paths.
+pub(super) fn parse_remote_url(url: &str) -> RemoteTarget {
+    let url = url.trim();
+    if let Some((scheme, rest)) = url.split_once("://") {
+        let scheme = match scheme.to_ascii_lowercase().as_str() {
+            "ssh" | "git+ssh" | "ssh+git" => "ssh".to_string(),
+            other => other.to_string(),
+        };
+        if scheme == "file" {
+            return RemoteTarget {
+                scheme,
+                user: None,
+                host: None,
+                port: None,
+            };
+        }
+        let authority = rest.split('/').next().unwrap_or_default();
+        let (user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host_port)) => (Some(user.to_string()), host_port),
+            None => (None, authority),
+        };
+        // Credentials embedded in https URLs are not reported back.
+        let user = user.map(|user| user.split(':').next().unwrap_or_default().to_string());
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|ch| ch.is_ascii_digit()) => {
+                (host.to_string(), port.parse().ok())
+            }
+            _ => (host_port.to_string(), None),
+        };
+        return RemoteTarget {
+            scheme,
+            user: user.filter(|user| !user.is_empty()),
+            host: Some(host).filter(|host| !host.is_empty()),
+            port,
+        };
+    }
+    let scp_like = url.split_once(':').filter(|(host, _)| {
+        // `C:\repo` is a Windows path, not a host.
+        host.len() > 1 && !host.contains('/') && !host.contains('\\')
+    });
+    if let Some((authority, _)) = scp_like {
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host.to_string()),
+            None => (None, authority.to_string()),
+        };
+        return RemoteTarget {
+            scheme: "ssh".to_string(),
+            user,
+            host: Some(host),
+            port: None,
+        };
+    }
+    RemoteTarget {
+        scheme: "file".to_string(),
+        user: None,
+        host: None,
+        port: None,
+    }
+}
+
+/// Drops any password or token embedded in an HTTP(S) remote URL.
+pub(super) fn redact_remote_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    match authority.rsplit_once('@') {
+        Some((user_info, host)) if user_info.contains(':') => {
+            let user = user_info.split(':').next().unwrap_or_default();
+            format!("{scheme}://{user}@{host}{path}")
+        }
+        _ => url.to_string(),
+    }
+}
+
+fn check(
+    id: &str,
+    status: GitAuthCheckStatus,
+    detail: impl Into<String>,
+    hint: Option<String>,
+) -> GitAuthCheck {
+    GitAuthCheck {
+        id: id.to_string(),
+        status,
+        detail: detail.into(),
+        hint,
+    }
+}
+
+fn first_line(output: &str) -> String {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Interprets `ssh -T` output. Most forges exit non-zero even after a
+/// successful login because they refuse a shell, so the text decides.
+pub(super) fn classify_ssh_probe(success: bool, output: &str, destination: &str) -> GitAuthCheck {
+    let lower = output.to_ascii_lowercase();
+    let detail = first_line(output);
+    if success
+        || lower.contains("successfully authenticated")
+        || lower.contains("welcome to")
+        || lower.contains("logged in as")
+    {
+        return check("sshConnectivity", GitAuthCheckStatus::Ok, detail, None);
+    }
+    let hint = if lower.contains("permission denied") {
+        "The server rejected every offered key. Add your public key to your account on the host and load it with `ssh-add`, or set an IdentityFile for it in ~/.ssh/config.".to_string()
+    } else if lower.contains("host key verification failed") {
+        format!("Run `ssh -T {destination}` once in a terminal to review and accept the host key.")
+    } else if lower.contains("could not resolve hostname")
+        || lower.contains("timed out")
+        || lower.contains("connection refused")
+        || lower.contains("network is unreachable")
+    {
+        "The host is unreachable. Check the remote URL, your network or VPN, and whether port 22 is blocked (GitHub also serves SSH on ssh.github.com:443).".to_string()
+    } else {
+        return check("sshConnectivity", GitAuthCheckStatus::Warning, detail, None);
+    };
+    check(
+        "sshConnectivity",
+        GitAuthCheckStatus::Error,
+        detail,
+        Some(hint),
+    )
+}
+
+/// Interprets a failed `git ls-remote` against an HTTP(S) remote.
+pub(super) fn classify_ls_remote_failure(output: &str) -> GitAuthCheck {
+    let lower = output.to_ascii_lowercase();
+    let detail = first_line(output);
+    let hint = if lower.contains("authentication failed")
+        || lower.contains("could not read username")
+        || lower.contains("could not read password")
+        || lower.contains("terminal prompts disabled")
+    {
+        "Git needs credentials it cannot prompt for here. Configure a credential helper (or run `gh auth setup-git`) and sign in once from a terminal; use a personal access token instead of a password."
+    } else if lower.contains("repository not found") || lower.contains("not found") {
+        "The repository was not found with the current credentials. Check the URL and that your account or token can access it."
+    } else if lower.contains("could not resolve host")
+        || lower.contains("timed out")
+        || lower.contains("failed to connect")
+    {
+        "The host is unreachable. Check the remote URL, your network or VPN, and any proxy settings (`http.proxy`)."
+    } else if lower.contains("ssl") || lower.contains("certificate") {
+        "TLS verification failed. If you are behind a corporate proxy, point `http.sslCAInfo` at its CA bundle."
+    } else {
+        return check("connectivity", GitAuthCheckStatus::Error, detail, None);
+    };
+    check(
+        "connectivity",
+        GitAuthCheckStatus::Error,
+        detail,
+        Some(hint.to_string()),
+    )
+}
+
+struct ProbeOutput {
+    success: bool,
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+async fn probe(
+    program: &Path,
+    args: &[&str],
+    cwd: &Path,
+    envs: &[(&str, &str)],
+) -> Result<ProbeOutput, String> {
+    let mut command = tokio_command(program);
+    command
+        .args(args)
+        .current_dir(cwd)
+        .env("PATH", git_env_path())
+        .stdin(std::process::Stdio::null());
+    for (key, value) in envs {
+        command.env(key, value);
+    }
+    let output = match timeout(PROBE_TIMEOUT, command.output()).await {
+        Ok(result) => result.map_err(|err| err.to_string())?,
+        Err(_) => {
+            return Err(format!(
+                "timed out after {} seconds",
+                PROBE_TIMEOUT.as_secs()
+            ))
+        }
+    };
+    Ok(ProbeOutput {
+        success: output.status.success(),
+        code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+fn find_program(name: &str) -> Option<std::path::PathBuf> {
+    let path = git_env_path();
+    std::env::split_paths(&path)
+        .flat_map(|dir| [dir.join(name), dir.join(format!("{name}.exe"))])
+        .find(|candidate| candidate.is_file())
+}
+
+async fn ssh_agent_check(repo_root: &Path) -> GitAuthCheck {
+    let Some(socket) = std::env::var_os("SSH_AUTH_SOCK").filter(|value| !value.is_empty()) else {
+        return check(
+            "sshAgent",
+            GitAuthCheckStatus::Warning,
+            "SSH_AUTH_SOCK is not set, so no ssh-agent is available to this process.",
+            Some(
+                "Start ssh-agent and add your key with `ssh-add`, or set an IdentityFile for the host in ~/.ssh/config.".to_string(),
+            ),
+        );
+    };
+    let Some(ssh_add) = find_program("ssh-add") else {
+        return check(
+            "sshAgent",
+            GitAuthCheckStatus::Skipped,
+            format!(
+                "ssh-add not found; agent socket is {}",
+                socket.to_string_lossy()
+            ),
+            None,
+        );
+    };
+    match probe(&ssh_add, &["-l"], repo_root, &[]).await {
+        Ok(output) if output.success => {
+            let keys = output.stdout.lines().filter(|line| !line.trim().is_empty()).count();
+            check(
+                "sshAgent",
+                GitAuthCheckStatus::Ok,
+                format!("ssh-agent holds {keys} key(s)."),
+                None,
+            )
+        }
+        Ok(output) if output.stdout.contains("no identities") || output.stderr.contains("no identities") => {
+            check(
+                "sshAgent",
+                GitAuthCheckStatus::Warning,
+                "ssh-agent is running but holds no keys.",
+                Some("Load your key with `ssh-add ~/.ssh/id_ed25519` (or the key registered with the host).".to_string()),
+            )
+        }
+        Ok(output) => check(
+            "sshAgent",
+            GitAuthCheckStatus::Warning,
+            format!("Could not talk to ssh-agent: {}", first_line(&output.stderr)),
+            Some("Restart ssh-agent and make sure SSH_AUTH_SOCK points at it.".to_string()),
+        ),
+        Err(error) => check(
+            "sshAgent",
+            GitAuthCheckStatus::Warning,
+            format!("ssh-add -l failed: {error}"),
+            None,
+        ),
+    }
+}
+
+async fn ssh_connectivity_check(repo_root: &Path, target: &RemoteTarget) -> GitAuthCheck {
+    let Some(destination) = target.ssh_destination() else {
+        return check(
+            "sshConnectivity",
+            GitAuthCheckStatus::Error,
+            "The SSH remote has no host.",
+            Some("Fix the remote URL with `git remote set-url`.".to_string()),
+        );
+    };
+    let Some(ssh) = find_program("ssh") else {
+        return check(
+            "sshConnectivity",
+            GitAuthCheckStatus::Error,
+            "ssh was not found on PATH.",
+            Some("Install OpenSSH or switch the remote to HTTPS.".to_string()),
+        );
+    };
+    let port = target.port.map(|port| port.to_string());
+    let mut args = vec!["-T", "-o", "BatchMode=yes", "-o", "ConnectTimeout=10"];
+    if let Some(port) = port.as_deref() {
+        args.extend(["-p", port]);
+    }
+    args.push(&destination);
+    match probe(&ssh, &args, repo_root, &[]).await {
+        Ok(output) => classify_ssh_probe(
+            output.success,
+            &format!("{}\n{}", output.stderr, output.stdout),
+            &destination,
+        ),
+        Err(error) => check(
+            "sshConnectivity",
+            GitAuthCheckStatus::Error,
+            format!("ssh -T {destination} failed: {error}"),
+            None,
+        ),
+    }
+}
+
+async fn credential_helper_check(git_bin: &Path, repo_root: &Path) -> GitAuthCheck {
+    let helpers = probe(
+        git_bin,
+        &["config", "--get-all", "credential.helper"],
+        repo_root,
+        &[],
+    )
+    .await
+    .map(|output| {
+        output
+            .stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    if helpers.is_empty() {
+        return check(
+            "credentialHelper",
+            GitAuthCheckStatus::Warning,
+            "No credential helper is configured.",
+            Some(
+                "Run `gh auth setup-git`, or set `git config --global credential.helper` to `osxkeychain`, `manager` or `libsecret`.".to_string(),
+            ),
+        );
+    }
+    check(
+        "credentialHelper",
+        GitAuthCheckStatus::Ok,
+        format!("Credential helper: {}", helpers.join(", ")),
+        None,
+    )
+}
+
+async fn ls_remote_check(git_bin: &Path, repo_root: &Path, remote_name: &str) -> GitAuthCheck {
+    let result = probe(
+        git_bin,
+        &["ls-remote", "--exit-code", remote_name, "HEAD"],
+        repo_root,
+        &[("GIT_TERMINAL_PROMPT", "0")],
+    )
+    .await;
+    match result {
+        // `--exit-code` exits with 2 when the remote is reachable but empty.
+        Ok(output) if output.success || output.code == Some(2) => check(
+            "connectivity",
+            GitAuthCheckStatus::Ok,
+            format!("`git ls-remote {remote_name}` succeeded."),
+            None,
+        ),
+        Ok(output) => classify_ls_remote_failure(&format!("{}\n{}", output.stderr, output.stdout)),
+        Err(error) => check(
+            "connectivity",
+            GitAuthCheckStatus::Error,
+            format!("git ls-remote failed: {error}"),
+            None,
+        ),
+    }
+}
+
+fn preferred_remote(repo_root: &Path) -> Result<Option<(String, String)>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin".to_string()
+    } else {
+        remotes.iter().flatten().next().unwrap_or("").to_string()
+    };
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    Ok(remote.url().map(|url| (name, url.to_string())))
+}
+
+pub(super) async fn git_auth_doctor_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitAuthDoctorReport, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let Some((remote_name, remote_url)) = preferred_remote(&repo_root)? else {
+        return Ok(GitAuthDoctorReport {
+            remote_name: None,
+            remote_url: None,
+            scheme: "none".to_string(),
+            ok: false,
+            checks: vec![check(
+                "remote",
+                GitAuthCheckStatus::Error,
+                "The repository has no remotes.",
+                Some("Add one with `git remote add origin <url>`.".to_string()),
+            )],
+        });
+    };
+    let target = parse_remote_url(&remote_url);
+    let remote_url = redact_remote_url(&remote_url);
+    let mut checks = vec![check(
+        "remote",
+        GitAuthCheckStatus::Ok,
+        format!("{remote_name} → {remote_url}"),
+        None,
+    )];
+    match target.scheme.as_str() {
+        "ssh" => {
+            checks.push(ssh_agent_check(&repo_root).await);
+            checks.push(ssh_connectivity_check(&repo_root, &target).await);
+        }
+        "https" | "http" => {
+            if target.scheme == "http" {
+                checks.push(check(
+                    "transport",
+                    GitAuthCheckStatus::Warning,
+                    "The remote uses plain HTTP, so credentials are sent unencrypted.",
+                    Some("Switch the remote to HTTPS with `git remote set-url`.".to_string()),
+                ));
+            }
+            let git_bin = resolve_git_binary()?;
+            checks.push(credential_helper_check(&git_bin, &repo_root).await);
+            checks.push(ls_remote_check(&git_bin, &repo_root, &remote_name).await);
+        }
+        "file" => {
+            let path = remote_url.trim_start_matches("file://");
+            let exists = Path::new(path).exists();
+            checks.push(check(
+                "connectivity",
+                if exists {
+                    GitAuthCheckStatus::Ok
+                } else {
+                    GitAuthCheckStatus::Error
+                },
+                if exists {
+                    format!("Local remote {path} exists.")
+                } else {
+                    format!("Local remote {path} does not exist.")
+                },
+                None,
+            ));
+        }
+        other => checks.push(check(
+            "connectivity",
+            GitAuthCheckStatus::Skipped,
+            format!("No diagnostics for `{other}` remotes."),
+            None,
+        )),
+    }
+    let ok = checks
+        .iter()
+        .all(|check| check.status != GitAuthCheckStatus::Error);
+    Ok(GitAuthDoctorReport {
+        remote_name: Some(remote_name),
+        remote_url: Some(remote_url),
+        scheme: target.scheme,
+        ok,
+        checks,
+    })
+}
@@ -4,42 +4,22 @@ use std::path::{Path, PathBuf};
 
 use git2::{BranchType, Repository, Status, StatusOptions};
 use serde_json::{json, Value};
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::git_utils::{
     checkout_branch, list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
 };
+use crate::shared::git_core;
 use crate::shared::process_core::tokio_command;
-use crate::types::{BranchInfo, WorkspaceEntry};
-use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
+use crate::types::{BranchInfo, GitStashEntry, WorkspaceEntry};
+use crate::utils::normalize_git_path;
 
 use super::context::workspace_entry_for_id;
 
 async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = tokio_command(git_bin)
-        .args(args)
-        .current_dir(repo_root)
-        .env("PATH", git_env_path())
-        .output()
+    git_core::run_git_command(&repo_root.to_path_buf(), args)
         .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-
-    if output.status.success() {
-        return Ok(());
-    }
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let detail = if stderr.trim().is_empty() {
-        stdout.trim()
-    } else {
-        stderr.trim()
-    };
-    if detail.is_empty() {
-        return Err("Git command failed.".to_string());
-    }
-    Err(detail.to_string())
+        .map(|_| ())
 }
 
 async fn run_gh_command(repo_root: &Path, args: &[&str]) -> Result<(String, String), String> {
@@ -398,7 +378,7 @@ async fn pull_with_default_strategy(repo_root: &Path) -> Result<(), String> {
 }
 
 pub(super) async fn stage_git_file_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
@@ -411,7 +391,7 @@ pub(super) async fn stage_git_file_inner(
 }
 
 pub(super) async fn stage_git_all_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -420,7 +400,7 @@ pub(super) async fn stage_git_all_inner(
 }
 
 pub(super) async fn unstage_git_file_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
@@ -433,7 +413,7 @@ pub(super) async fn unstage_git_file_inner(
 }
 
 pub(super) async fn revert_git_file_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     path: String,
 ) -> Result<(), String> {
@@ -455,7 +435,7 @@ pub(super) async fn revert_git_file_inner(
 }
 
 pub(super) async fn revert_git_all_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -469,7 +449,7 @@ pub(super) async fn revert_git_all_inner(
 }
 
 pub(super) async fn commit_git_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     message: String,
 ) -> Result<(), String> {
@@ -479,7 +459,7 @@ pub(super) async fn commit_git_inner(
 }
 
 pub(super) async fn push_git_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -488,7 +468,7 @@ pub(super) async fn push_git_inner(
 }
 
 pub(super) async fn pull_git_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -497,7 +477,7 @@ pub(super) async fn pull_git_inner(
 }
 
 pub(super) async fn fetch_git_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -506,7 +486,7 @@ pub(super) async fn fetch_git_inner(
 }
 
 pub(super) async fn sync_git_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -516,7 +496,7 @@ pub(super) async fn sync_git_inner(
 }
 
 pub(super) async fn list_git_roots_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     depth: Option<usize>,
 ) -> Result<Vec<String>, String> {
@@ -527,7 +507,7 @@ pub(super) async fn list_git_roots_inner(
 }
 
 pub(super) async fn init_git_repo_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     branch: String,
     force: bool,
@@ -589,7 +569,7 @@ pub(super) async fn init_git_repo_inner(
 }
 
 pub(super) async fn create_github_repo_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     repo: String,
     visibility: String,
@@ -723,7 +703,7 @@ pub(super) async fn create_github_repo_inner(
 }
 
 pub(super) async fn list_git_branches_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -752,7 +732,7 @@ pub(super) async fn list_git_branches_inner(
 }
 
 pub(super) async fn checkout_git_branch_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     name: String,
 ) -> Result<(), String> {
@@ -763,7 +743,7 @@ pub(super) async fn checkout_git_branch_inner(
 }
 
 pub(super) async fn create_git_branch_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     name: String,
 ) -> Result<(), String> {
@@ -777,6 +757,115 @@ pub(super) async fn create_git_branch_inner(
     checkout_branch(&repo, &name).map_err(|e| e.to_string())
 }
 
+pub(super) async fn delete_git_branch_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+    force: bool,
+) -> Result<(), String> {
+    let name = validate_branch_name(&name)?;
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut branch = repo
+        .find_branch(&name, BranchType::Local)
+        .map_err(|_| format!("Branch '{name}' does not exist."))?;
+    if branch.is_head() {
+        return Err("Cannot delete the currently checked out branch.".to_string());
+    }
+    if !force {
+        let branch_oid = branch
+            .get()
+            .target()
+            .ok_or_else(|| format!("Branch '{name}' has no target commit."))?;
+        let head_oid = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .ok_or_else(|| "Unable to resolve HEAD.".to_string())?;
+        let merged = branch_oid == head_oid
+            || repo
+                .merge_base(head_oid, branch_oid)
+                .map(|base| base == branch_oid)
+                .unwrap_or(false);
+        if !merged {
+            return Err(format!(
+                "Branch '{name}' is not fully merged. Delete it with force to discard its commits."
+            ));
+        }
+    }
+    branch.delete().map_err(|e| e.to_string())
+}
+
+pub(super) async fn save_git_stash_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    message: Option<String>,
+) -> Result<(), String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let has_changes = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let mut status_options = StatusOptions::new();
+        status_options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false);
+        repo.statuses(Some(&mut status_options))
+            .map(|statuses| !statuses.is_empty())
+            .map_err(|e| e.to_string())?
+    };
+    if !has_changes {
+        return Err("No local changes to stash.".to_string());
+    }
+    let message = message
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let mut args = vec!["stash", "push", "--include-untracked"];
+    if let Some(message) = message.as_deref() {
+        args.extend(["-m", message]);
+    }
+    run_git_command(&repo_root, &args).await
+}
+
+pub(super) async fn list_git_stashes_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut raw_entries = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        raw_entries.push((index, message.to_string(), *oid));
+        true
+    })
+    .map_err(|e| e.to_string())?;
+    let stashes = raw_entries
+        .into_iter()
+        .map(|(index, message, oid)| GitStashEntry {
+            index,
+            message,
+            sha: oid.to_string(),
+            timestamp: repo
+                .find_commit(oid)
+                .map(|commit| commit.time().seconds())
+                .unwrap_or(0),
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({ "stashes": stashes }))
+}
+
+pub(super) async fn pop_git_stash_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    index: Option<usize>,
+) -> Result<(), String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let stash_ref = format!("stash@{{{}}}", index.unwrap_or(0));
+    run_git_command(&repo_root, &["stash", "pop", &stash_ref]).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::{gh_repo_create_args, validate_branch_name};
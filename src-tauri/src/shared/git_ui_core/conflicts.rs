@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path};
+
+use git2::{IndexEntry, Repository, RepositoryState};
+use tokio::sync::RwLock;
+
+use crate::git_utils::resolve_git_root;
+use crate::types::{GitConflictFile, GitConflictsResponse, WorkspaceEntry};
+use crate::utils::normalize_git_path;
+
+use super::context::workspace_entry_for_id;
+
+fn operation_label(state: RepositoryState) -> Option<String> {
+    let label = match state {
+        RepositoryState::Clean => return None,
+        RepositoryState::Merge => "merge",
+        RepositoryState::Revert | RepositoryState::RevertSequence => "revert",
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => "cherry-pick",
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => "rebase",
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => "am",
+        RepositoryState::Bisect => "bisect",
+    };
+    Some(label.to_string())
+}
+
+fn entry_path(entry: &IndexEntry) -> String {
+    normalize_git_path(&String::from_utf8_lossy(&entry.path))
+}
+
+/// Reads one side of a conflict. Returns `Ok(None)` for a missing side
+/// (added/deleted on one branch) and `Err(())` when the blob is binary.
+fn read_conflict_side(repo: &Repository, entry: Option<&IndexEntry>) -> Result<Option<String>, ()> {
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+    let Ok(blob) = repo.find_blob(entry.id) else {
+        return Ok(None);
+    };
+    if blob.is_binary() {
+        return Err(());
+    }
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+fn validate_conflict_path(path: &str) -> Result<String, String> {
+    let normalized = normalize_git_path(path.trim());
+    if normalized.is_empty() {
+        return Err("Conflict path is required.".to_string());
+    }
+    let candidate = Path::new(&normalized);
+    let escapes = candidate
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err("Conflict path must be relative to the repository root.".to_string());
+    }
+    Ok(normalized)
+}
+
+fn collect_conflicts(repo: &Repository) -> Result<Vec<GitConflictFile>, String> {
+    let index = repo.index().map_err(|e| e.to_string())?;
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for conflict in index.conflicts().map_err(|e| e.to_string())? {
+        let conflict = conflict.map_err(|e| e.to_string())?;
+        let Some(path) = conflict
+            .our
+            .as_ref()
+            .or(conflict.their.as_ref())
+            .or(conflict.ancestor.as_ref())
+            .map(entry_path)
+        else {
+            continue;
+        };
+        let sides = (
+            read_conflict_side(repo, conflict.ancestor.as_ref()),
+            read_conflict_side(repo, conflict.our.as_ref()),
+            read_conflict_side(repo, conflict.their.as_ref()),
+        );
+        let file = match sides {
+            (Ok(base), Ok(ours), Ok(theirs)) => GitConflictFile {
+                path,
+                base,
+                ours,
+                theirs,
+                is_binary: false,
+            },
+            _ => GitConflictFile {
+                path,
+                base: None,
+                ours: None,
+                theirs: None,
+                is_binary: true,
+            },
+        };
+        files.push(file);
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+pub(super) async fn get_git_conflicts_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitConflictsResponse, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let files = collect_conflicts(&repo)?;
+    Ok(GitConflictsResponse {
+        operation: operation_label(repo.state()),
+        files,
+    })
+}
+
+pub(super) async fn resolve_git_conflict_inner(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    let path = validate_conflict_path(&path)?;
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    let conflicted = index
+        .conflicts()
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .any(|conflict| {
+            [&conflict.ancestor, &conflict.our, &conflict.their]
+                .into_iter()
+                .flatten()
+                .any(|entry| entry_path(entry) == path)
+        });
+    if !conflicted {
+        return Err(format!("'{path}' has no unresolved conflict."));
+    }
+
+    let target = repo_root.join(&path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to write {path}: {e}"))?;
+    }
+    fs::write(&target, content).map_err(|e| format!("Failed to write {path}: {e}"))?;
+    // Staging the resolved file clears the conflict stages for this path.
+    index
+        .add_path(Path::new(&path))
+        .map_err(|e| e.to_string())?;
+    index.write().map_err(|e| e.to_string())
+}
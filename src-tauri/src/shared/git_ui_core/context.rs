@@ -1,16 +1,16 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::git_utils::resolve_git_root;
 use crate::types::WorkspaceEntry;
 
 pub(super) async fn workspace_entry_for_id(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<WorkspaceEntry, String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     workspaces
         .get(workspace_id)
         .cloned()
@@ -18,7 +18,7 @@ pub(super) async fn workspace_entry_for_id(
 }
 
 pub(super) async fn resolve_repo_root_for_workspace(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<PathBuf, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
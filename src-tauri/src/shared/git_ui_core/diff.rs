@@ -7,7 +7,7 @@ use std::process::Stdio;
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use git2::{DiffOptions, Repository, Status, StatusOptions};
 use serde_json::{json, Value};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::git_utils::{
     diff_patch_to_string, diff_stats_for_path, image_mime_type, resolve_git_root,
@@ -332,11 +332,15 @@ pub(super) fn collect_workspace_diff(repo_root: &Path) -> Result<String, String>
 }
 
 pub(super) async fn get_git_status_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
-    let repo_root = resolve_git_root(&entry)?;
+    git_status_for_entry(&entry)
+}
+
+pub(super) fn git_status_for_entry(entry: &WorkspaceEntry) -> Result<Value, String> {
+    let repo_root = resolve_git_root(entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
 
     let branch_name = repo
@@ -463,7 +467,7 @@ pub(super) async fn get_git_status_inner(
 }
 
 pub(super) async fn get_git_diffs_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<Vec<GitFileDiff>, String> {
@@ -616,7 +620,7 @@ pub(super) async fn get_git_diffs_inner(
 }
 
 pub(super) async fn get_git_commit_diff_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     sha: String,
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use git2::Repository;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::git_utils::{parse_github_repo, resolve_git_root};
 use crate::shared::process_core::tokio_command;
@@ -141,7 +141,7 @@ fn command_failure_detail(stdout: &[u8], stderr: &[u8], fallback: &str) -> Strin
 }
 
 pub(super) async fn checkout_github_pull_request_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<(), String> {
@@ -168,7 +168,7 @@ pub(super) async fn checkout_github_pull_request_inner(
 }
 
 pub(super) async fn get_github_issues_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<GitHubIssuesResponse, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -225,7 +225,7 @@ pub(super) async fn get_github_issues_inner(
 }
 
 pub(super) async fn get_github_pull_requests_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<GitHubPullRequestsResponse, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -287,7 +287,7 @@ pub(super) async fn get_github_pull_requests_inner(
 }
 
 pub(super) async fn get_github_pull_request_diff_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
@@ -323,7 +323,7 @@ pub(super) async fn get_github_pull_request_diff_inner(
 }
 
 pub(super) async fn get_github_pull_request_comments_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestComment>, String> {
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use git2::{BranchType, Repository, Sort};
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::git_utils::{commit_to_entry, resolve_git_root};
 use crate::types::{GitLogResponse, WorkspaceEntry};
@@ -9,7 +9,7 @@ use crate::types::{GitLogResponse, WorkspaceEntry};
 use super::context::workspace_entry_for_id;
 
 pub(super) async fn get_git_log_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     limit: Option<usize>,
 ) -> Result<GitLogResponse, String> {
@@ -101,7 +101,7 @@ pub(super) async fn get_git_log_inner(
 }
 
 pub(super) async fn get_git_remote_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Option<String>, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
@@ -0,0 +1,313 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared::process_core::tokio_command;
+
+/// Below either of these, the data-dir volume is reported as low on space.
+const LOW_DISK_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const LOW_DISK_PERCENT: f64 = 5.0;
+/// Swap use past this share of swap is reported as swapping.
+const SWAPPING_PERCENT: f64 = 25.0;
+/// A one-minute load average past this many times the CPU count is reported
+/// as overloaded.
+const HIGH_LOAD_PER_CPU: f64 = 1.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MemoryStats {
+    pub(crate) total_bytes: u64,
+    pub(crate) available_bytes: u64,
+    pub(crate) swap_total_bytes: u64,
+    pub(crate) swap_used_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiskStats {
+    /// The data directory the volume was measured for.
+    pub(crate) path: String,
+    pub(crate) total_bytes: u64,
+    pub(crate) available_bytes: u64,
+}
+
+/// Health of the machine running the Codex sessions. Each part is `None`
+/// where the platform does not report it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HostStats {
+    pub(crate) cpu_count: usize,
+    /// 1, 5 and 15 minute load averages.
+    pub(crate) load_average: Option<[f64; 3]>,
+    pub(crate) memory: Option<MemoryStats>,
+    pub(crate) disk: Option<DiskStats>,
+    /// Running `codex` processes, including ones this app did not start.
+    pub(crate) codex_processes: Option<usize>,
+    /// Human-readable problems: low disk, swapping, overload.
+    pub(crate) warnings: Vec<String>,
+}
+
+#[cfg(unix)]
+fn load_average() -> Option<[f64; 3]> {
+    let mut loads = [0f64; 3];
+    let read = unsafe { libc::getloadavg(loads.as_mut_ptr(), 3) };
+    (read == 3).then_some(loads)
+}
+
+#[cfg(not(unix))]
+fn load_average() -> Option<[f64; 3]> {
+    None
+}
+
+#[cfg(unix)]
+fn disk_stats(path: &Path) -> Option<DiskStats> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    // The data dir may not exist yet on a fresh install; measure its parent.
+    let existing = path.ancestors().find(|candidate| candidate.exists())?;
+    let c_path = CString::new(existing.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some(DiskStats {
+        path: path.to_string_lossy().to_string(),
+        total_bytes: stat.f_blocks as u64 * block,
+        available_bytes: stat.f_bavail as u64 * block,
+    })
+}
+
+#[cfg(not(unix))]
+fn disk_stats(_path: &Path) -> Option<DiskStats> {
+    None
+}
+
+/// `/proc/meminfo` values are in KiB.
+fn parse_meminfo(data: &str) -> Option<MemoryStats> {
+    let field = |name: &str| {
+        data.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|rest| rest.split_whitespace().next()?.parse::<u64>().ok())
+            .map(|kib| kib * 1024)
+    };
+    let swap_total = field("SwapTotal").unwrap_or(0);
+    Some(MemoryStats {
+        total_bytes: field("MemTotal")?,
+        available_bytes: field("MemAvailable").or_else(|| field("MemFree"))?,
+        swap_total_bytes: swap_total,
+        swap_used_bytes: swap_total.saturating_sub(field("SwapFree").unwrap_or(swap_total)),
+    })
+}
+
+/// Free, inactive and speculative pages from `vm_stat`, in bytes.
+fn parse_vm_stat_available(data: &str) -> Option<u64> {
+    let page_size = data
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse::<u64>()
+        .ok()?;
+    let pages = |name: &str| {
+        data.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|rest| rest.trim().trim_end_matches('.').parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    Some((pages("Pages free") + pages("Pages inactive") + pages("Pages speculative")) * page_size)
+}
+
+/// `sysctl -n vm.swapusage`, e.g. `total = 2048.00M  used = 1024.50M ...`.
+fn parse_swapusage(data: &str) -> Option<(u64, u64)> {
+    let value = |name: &str| {
+        let rest = data.split(&format!("{name} = ")).nth(1)?;
+        let token = rest.split_whitespace().next()?;
+        let (number, unit) = token.split_at(token.len().checked_sub(1)?);
+        let scale: f64 = match unit {
+            "K" => 1024.0,
+            "M" => 1024.0 * 1024.0,
+            "G" => 1024.0 * 1024.0 * 1024.0,
+            _ => return None,
+        };
+        Some((number.parse::<f64>().ok()? * scale) as u64)
+    };
+    Some((value("total")?, value("used")?))
+}
+
+async fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio_command(program).args(args).output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn memory_stats() -> Option<MemoryStats> {
+    if cfg!(target_os = "linux") {
+        let data = tokio::fs::read_to_string("/proc/meminfo").await.ok()?;
+        return parse_meminfo(&data);
+    }
+    if cfg!(target_os = "macos") {
+        let total = command_stdout("sysctl", &["-n", "hw.memsize"])
+            .await?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        let available = parse_vm_stat_available(&command_stdout("vm_stat", &[]).await?)?;
+        let (swap_total, swap_used) = command_stdout("sysctl", &["-n", "vm.swapusage"])
+            .await
+            .and_then(|data| parse_swapusage(&data))
+            .unwrap_or((0, 0));
+        return Some(MemoryStats {
+            total_bytes: total,
+            available_bytes: available.min(total),
+            swap_total_bytes: swap_total,
+            swap_used_bytes: swap_used,
+        });
+    }
+    None
+}
+
+/// Counts `ps -o comm=` lines naming the Codex CLI, leaving out this app.
+fn count_codex_processes(ps_output: &str) -> usize {
+    ps_output
+        .lines()
+        .filter_map(|line| {
+            let command = line.trim();
+            Path::new(command).file_name()?.to_str()
+        })
+        .filter(|name| {
+            let name = name.trim_end_matches(".exe");
+            (name == "codex" || name.starts_with("codex-"))
+                && !name.starts_with("codex-monitor")
+                && !name.starts_with("codex_monitor")
+        })
+        .count()
+}
+
+async fn codex_processes() -> Option<usize> {
+    if cfg!(windows) {
+        return None;
+    }
+    let output = command_stdout("ps", &["-A", "-o", "comm="]).await?;
+    Some(count_codex_processes(&output))
+}
+
+fn warnings_for(stats: &HostStats) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(disk) = &stats.disk {
+        let percent = if disk.total_bytes == 0 {
+            100.0
+        } else {
+            disk.available_bytes as f64 * 100.0 / disk.total_bytes as f64
+        };
+        if disk.available_bytes < LOW_DISK_BYTES || percent < LOW_DISK_PERCENT {
+            warnings.push(format!(
+                "Low disk space: {:.1} GB free ({percent:.0}%).",
+                disk.available_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            ));
+        }
+    }
+    if let Some(memory) = &stats.memory {
+        if memory.swap_total_bytes > 0 {
+            let percent = memory.swap_used_bytes as f64 * 100.0 / memory.swap_total_bytes as f64;
+            if percent > SWAPPING_PERCENT {
+                warnings.push(format!("Swapping: {percent:.0}% of swap in use."));
+            }
+        }
+    }
+    if let Some([one_minute, _, _]) = stats.load_average {
+        if one_minute > stats.cpu_count as f64 * HIGH_LOAD_PER_CPU {
+            warnings.push(format!(
+                "High load: {one_minute:.1} on {} CPUs.",
+                stats.cpu_count
+            ));
+        }
+    }
+    warnings
+}
+
+/// Load, memory, free space on the volume holding `data_dir`, and running
+/// `codex` processes.
+pub(crate) async fn host_stats_core(data_dir: &Path) -> HostStats {
+    let data_dir = data_dir.to_path_buf();
+    let disk = tokio::task::spawn_blocking(move || disk_stats(&data_dir))
+        .await
+        .ok()
+        .flatten();
+    let (memory, codex_processes) = tokio::join!(memory_stats(), codex_processes());
+    let mut stats = HostStats {
+        cpu_count: std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1),
+        load_average: load_average(),
+        memory,
+        disk,
+        codex_processes,
+        warnings: Vec::new(),
+    };
+    stats.warnings = warnings_for(&stats);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_linux_meminfo() {
+        let data = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    8000000 kB\nSwapTotal:       2000000 kB\nSwapFree:        1500000 kB\n";
+        let memory = parse_meminfo(data).expect("memory");
+        assert_eq!(memory.total_bytes, 16_000_000 * 1024);
+        assert_eq!(memory.available_bytes, 8_000_000 * 1024);
+        assert_eq!(memory.swap_total_bytes, 2_000_000 * 1024);
+        assert_eq!(memory.swap_used_bytes, 500_000 * 1024);
+        assert!(parse_meminfo("SwapTotal: 0 kB\n").is_none());
+    }
+
+    #[test]
+    fn parses_macos_vm_stat_and_swapusage() {
+        let vm_stat = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\nPages free:                               10.\nPages active:                             99.\nPages inactive:                            5.\nPages speculative:                         1.\n";
+        assert_eq!(parse_vm_stat_available(vm_stat), Some(16 * 16384));
+        assert_eq!(
+            parse_swapusage("total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)"),
+            Some((2048 * 1024 * 1024, (1024.5 * 1024.0 * 1024.0) as u64))
+        );
+    }
+
+    #[test]
+    fn counts_codex_cli_processes_only() {
+        let ps = "/usr/local/bin/codex\ncodex-exec\n/Applications/Codex Monitor.app/Contents/MacOS/codex-monitor\ncodex_monitor_daemon\nnode\ncodex.exe\n";
+        assert_eq!(count_codex_processes(ps), 3);
+    }
+
+    #[test]
+    fn warns_about_low_disk_swapping_and_load() {
+        let stats = HostStats {
+            cpu_count: 2,
+            load_average: Some([4.0, 2.0, 1.0]),
+            memory: Some(MemoryStats {
+                total_bytes: 8,
+                available_bytes: 4,
+                swap_total_bytes: 100,
+                swap_used_bytes: 50,
+            }),
+            disk: Some(DiskStats {
+                path: "/data".to_string(),
+                total_bytes: 500 * 1024 * 1024 * 1024,
+                available_bytes: 1024 * 1024 * 1024,
+            }),
+            codex_processes: Some(1),
+            warnings: Vec::new(),
+        };
+        let warnings = warnings_for(&stats);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("Low disk space"));
+        assert!(warnings[1].starts_with("Swapping"));
+        assert!(warnings[2].starts_with("High load"));
+    }
+}
@@ -0,0 +1,325 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use serde::Serialize;
+
+/// Files and pasted data larger than this are refused before decoding.
+pub(crate) const MAX_SOURCE_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
+/// Longest edge sent to Codex; larger images are downscaled.
+const MAX_SEND_DIMENSION: u32 = 2048;
+const THUMBNAIL_DIMENSION: u32 = 256;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PreparedImage {
+    /// The payload to send, as a `data:` URL.
+    pub(crate) data_url: String,
+    pub(crate) mime_type: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) original_width: u32,
+    pub(crate) original_height: u32,
+    /// Size of the payload before base64 encoding.
+    pub(crate) bytes: usize,
+    pub(crate) downscaled: bool,
+    /// Re-encoded as PNG rather than sent as read.
+    pub(crate) transcoded: bool,
+    /// PNG no larger than 256 pixels on its longest edge.
+    pub(crate) thumbnail_data_url: Option<String>,
+}
+
+fn data_url(mime_type: &str, bytes: &[u8]) -> String {
+    format!("data:{mime_type};base64,{}", STANDARD.encode(bytes))
+}
+
+fn decode_data_url(source: &str) -> Result<Vec<u8>, String> {
+    let encoded = source
+        .trim()
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(_, data)| data)
+        .ok_or_else(|| "Only base64 data URLs are supported".to_string())?;
+    if encoded.len() as u64 / 4 * 3 > MAX_SOURCE_IMAGE_BYTES {
+        return Err(format!(
+            "Pasted image exceeds maximum size of {MAX_SOURCE_IMAGE_BYTES} bytes"
+        ));
+    }
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|err| format!("Invalid image data URL: {err}"))?;
+    if bytes.is_empty() {
+        return Err("Pasted image is empty".to_string());
+    }
+    Ok(bytes)
+}
+
+fn read_source_file(path: &str) -> Result<Vec<u8>, String> {
+    let metadata = std::fs::symlink_metadata(path)
+        .map_err(|err| format!("Failed to stat image file at {path}: {err}"))?;
+    if metadata.file_type().is_symlink() {
+        return Err(format!("Image path must not be a symlink: {path}"));
+    }
+    if !metadata.is_file() {
+        return Err(format!("Image path is not a file: {path}"));
+    }
+    if metadata.len() > MAX_SOURCE_IMAGE_BYTES {
+        return Err(format!(
+            "Image file exceeds maximum size of {MAX_SOURCE_IMAGE_BYTES} bytes: {path}"
+        ));
+    }
+    let bytes =
+        std::fs::read(path).map_err(|err| format!("Failed to read image file at {path}: {err}"))?;
+    if bytes.is_empty() {
+        return Err(format!("Image file is empty: {path}"));
+    }
+    Ok(bytes)
+}
+
+/// HEIC, HEIF and AVIF share the ISO base media container; the brand in the
+/// `ftyp` box tells them apart from video.
+fn is_heif_container(bytes: &[u8]) -> bool {
+    if bytes.len() < 12 || &bytes[4..8] != b"ftyp" {
+        return false;
+    }
+    matches!(
+        &bytes[8..12],
+        b"heic"
+            | b"heix"
+            | b"heim"
+            | b"heis"
+            | b"hevc"
+            | b"hevx"
+            | b"mif1"
+            | b"msf1"
+            | b"avif"
+            | b"avis"
+    )
+}
+
+fn temp_image_path(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "codex-monitor-image-{}.{extension}",
+        uuid::Uuid::new_v4()
+    ))
+}
+
+/// Converts a HEIC/HEIF/AVIF file to PNG with whichever system tool is
+/// present: `sips` on macOS, otherwise libheif's `heif-convert` or
+/// ImageMagick.
+fn convert_heif_to_png(input: &Path, label: &str) -> Result<Vec<u8>, String> {
+    let output = temp_image_path("png");
+    let converters: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("/usr/bin/sips", &["-s", "format", "png"])]
+    } else {
+        &[("heif-convert", &[]), ("magick", &[]), ("convert", &[])]
+    };
+    for (program, args) in converters {
+        let mut command = Command::new(program);
+        command.args(*args).arg(input);
+        if program.ends_with("sips") {
+            command.arg("--out");
+        }
+        let status = command
+            .arg(&output)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if !matches!(status, Ok(status) if status.success()) {
+            continue;
+        }
+        let bytes = std::fs::read(&output).unwrap_or_default();
+        let _ = std::fs::remove_file(&output);
+        if !bytes.is_empty() {
+            return Ok(bytes);
+        }
+    }
+    let _ = std::fs::remove_file(&output);
+    Err(format!(
+        "Failed to convert HEIC/HEIF/AVIF image {label}; install libheif or ImageMagick, or convert it to PNG or JPEG first"
+    ))
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, ImageFormat::Png)
+        .map_err(|err| format!("Failed to encode PNG: {err}"))?;
+    Ok(buffer.into_inner())
+}
+
+/// Formats Codex accepts as they are.
+fn can_send_as_is(format: ImageFormat) -> bool {
+    matches!(
+        format,
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP
+    )
+}
+
+/// Reads an image from a path or `data:` URL and makes it sendable:
+/// HEIC/HEIF/AVIF and formats Codex does not take are transcoded to PNG,
+/// and images longer than 2048 pixels on either edge are downscaled.
+/// Anything else is passed through without decoding more than its header.
+fn prepare_image(source: &str, with_thumbnail: bool) -> Result<PreparedImage, String> {
+    let trimmed = source.trim();
+    let (mut bytes, label) = if trimmed.starts_with("data:") {
+        (decode_data_url(trimmed)?, "pasted image".to_string())
+    } else {
+        (read_source_file(trimmed)?, trimmed.to_string())
+    };
+    let mut converted = false;
+    if is_heif_container(&bytes) {
+        bytes = if trimmed.starts_with("data:") {
+            let input = temp_image_path("heic");
+            std::fs::write(&input, &bytes)
+                .map_err(|err| format!("Failed to stage pasted image: {err}"))?;
+            let result = convert_heif_to_png(&input, &label);
+            let _ = std::fs::remove_file(&input);
+            result?
+        } else {
+            convert_heif_to_png(Path::new(trimmed), &label)?
+        };
+        converted = true;
+    }
+
+    let reader = ImageReader::new(Cursor::new(&bytes))
+        .with_guessed_format()
+        .map_err(|err| format!("Failed to read image {label}: {err}"))?;
+    let format = reader
+        .format()
+        .ok_or_else(|| format!("Unsupported image format: {label}"))?;
+    let (original_width, original_height) = reader
+        .into_dimensions()
+        .map_err(|err| format!("Failed to read image {label}: {err}"))?;
+    let downscaled = original_width.max(original_height) > MAX_SEND_DIMENSION;
+    let transcoded = converted || downscaled || !can_send_as_is(format);
+
+    let decoded = if transcoded || with_thumbnail {
+        Some(
+            image::load_from_memory_with_format(&bytes, format)
+                .map_err(|err| format!("Failed to decode image {label}: {err}"))?,
+        )
+    } else {
+        None
+    };
+    let (payload, mime_type, width, height) = match decoded.as_ref().filter(|_| transcoded) {
+        Some(image) => {
+            let image = if downscaled {
+                image.resize(MAX_SEND_DIMENSION, MAX_SEND_DIMENSION, FilterType::Triangle)
+            } else {
+                image.clone()
+            };
+            (
+                encode_png(&image)?,
+                "image/png",
+                image.width(),
+                image.height(),
+            )
+        }
+        None => (
+            bytes,
+            format.to_mime_type(),
+            original_width,
+            original_height,
+        ),
+    };
+    let thumbnail_data_url = match decoded.as_ref().filter(|_| with_thumbnail) {
+        Some(image) => Some(data_url(
+            "image/png",
+            &encode_png(&image.thumbnail(THUMBNAIL_DIMENSION, THUMBNAIL_DIMENSION))?,
+        )),
+        None => None,
+    };
+    Ok(PreparedImage {
+        data_url: data_url(mime_type, &payload),
+        mime_type: mime_type.to_string(),
+        width,
+        height,
+        original_width,
+        original_height,
+        bytes: payload.len(),
+        downscaled,
+        transcoded,
+        thumbnail_data_url,
+    })
+}
+
+/// The sendable payload for an image path or `data:` URL.
+pub(crate) fn image_data_url_core(source: &str) -> Result<String, String> {
+    prepare_image(source, false).map(|prepared| prepared.data_url)
+}
+
+/// Like [`image_data_url_core`], plus dimensions and a thumbnail for the
+/// composer.
+// Only the app prepares images before sending.
+#[allow(dead_code)]
+pub(crate) fn prepare_image_for_send_core(source: &str) -> Result<PreparedImage, String> {
+    prepare_image(source, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([40, 120, 200]));
+        encode_png(&DynamicImage::ImageRgb8(image)).expect("encode")
+    }
+
+    #[test]
+    fn small_images_pass_through_and_large_ones_are_downscaled() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let small = dir.join("small.png");
+        std::fs::write(&small, png_bytes(40, 20)).unwrap();
+        let prepared = prepare_image_for_send_core(small.to_str().unwrap()).unwrap();
+        assert!(!prepared.transcoded);
+        assert_eq!((prepared.width, prepared.height), (40, 20));
+        assert_eq!(
+            prepared.bytes,
+            std::fs::metadata(&small).unwrap().len() as usize
+        );
+        assert!(prepared.thumbnail_data_url.is_some());
+
+        let wide = dir.join("wide.png");
+        std::fs::write(&wide, png_bytes(3072, 768)).unwrap();
+        let prepared = prepare_image_for_send_core(wide.to_str().unwrap()).unwrap();
+        assert!(prepared.downscaled && prepared.transcoded);
+        assert_eq!((prepared.width, prepared.height), (2048, 512));
+        assert_eq!(
+            (prepared.original_width, prepared.original_height),
+            (3072, 768)
+        );
+        assert!(prepared.data_url.starts_with("data:image/png;base64,"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pasted_data_urls_are_accepted_and_bmp_is_transcoded() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 8, Rgb([0, 0, 0])));
+        let mut bmp = Cursor::new(Vec::new());
+        image.write_to(&mut bmp, ImageFormat::Bmp).unwrap();
+        let pasted = data_url("image/bmp", &bmp.into_inner());
+        let prepared = prepare_image_for_send_core(&pasted).unwrap();
+        assert!(prepared.transcoded && !prepared.downscaled);
+        assert_eq!(prepared.mime_type, "image/png");
+
+        assert!(prepare_image_for_send_core("data:image/png,abc").is_err());
+        assert!(image_data_url_core("/nonexistent/photo.png")
+            .unwrap_err()
+            .contains("/nonexistent/photo.png"));
+    }
+
+    #[test]
+    fn heif_and_avif_containers_are_recognized_by_brand() {
+        assert!(is_heif_container(b"\0\0\0\x18ftypheic\0\0\0\0"));
+        assert!(is_heif_container(b"\0\0\0\x1cftypavif\0\0\0\0"));
+        assert!(!is_heif_container(b"\0\0\0\x18ftypisom\0\0\0\0"));
+        assert!(!is_heif_container(&png_bytes(1, 1)));
+    }
+}
@@ -5,7 +5,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::types::{
@@ -31,7 +31,7 @@ struct UsageTotals {
 const MAX_ACTIVITY_GAP_MS: i64 = 2 * 60 * 1000;
 
 pub(crate) async fn local_usage_snapshot_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     days: Option<u32>,
     workspace_path: Option<String>,
 ) -> Result<LocalUsageSnapshot, String> {
@@ -45,7 +45,7 @@ pub(crate) async fn local_usage_snapshot_core(
         }
     });
     let sessions_roots = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         resolve_sessions_roots(&workspaces, workspace_path.as_deref())
     };
     let snapshot = tokio::task::spawn_blocking(move || {
@@ -56,7 +56,7 @@ pub(crate) async fn local_usage_snapshot_core(
     Ok(snapshot)
 }
 
-fn scan_local_usage(
+pub(crate) fn scan_local_usage(
     days: u32,
     workspace_path: Option<&Path>,
     sessions_roots: &[PathBuf],
@@ -508,7 +508,7 @@ fn path_matches_workspace(cwd: &str, workspace_path: &Path) -> bool {
     cwd_path == workspace_path || cwd_path.starts_with(workspace_path)
 }
 
-fn make_day_keys(days: u32) -> Vec<String> {
+pub(crate) fn make_day_keys(days: u32) -> Vec<String> {
     let today = Local::now().date_naive();
     (0..days)
         .rev()
@@ -585,7 +585,7 @@ fn resolve_workspace_codex_home_for_path(
     resolve_workspace_codex_home(entry, parent_entry)
 }
 
-fn day_dir_for_key(root: &Path, day_key: &str) -> PathBuf {
+pub(crate) fn day_dir_for_key(root: &Path, day_key: &str) -> PathBuf {
     let mut parts = day_key.split('-');
     let year = parts.next().unwrap_or("1970");
     let month = parts.next().unwrap_or("01");
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::shared::git_core::run_git_command;
+use crate::shared::process_core::{shell_command, tokio_command};
+use crate::types::WorkspaceEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MergeQueueStage {
+    Rebasing,
+    Testing,
+    Merging,
+    Removing,
+    Merged,
+    Conflict,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CiStatus {
+    Passing,
+    Pending,
+    Failing,
+    NoPullRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MergeOutcome {
+    Merged { base_branch: String },
+    Conflict { detail: String },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MergeCandidate {
+    pub(crate) worktree: WorkspaceEntry,
+    pub(crate) parent: WorkspaceEntry,
+    pub(crate) branch: String,
+}
+
+/// Worktrees labeled ready for the merge queue, ordered by branch name.
+pub(crate) fn ready_merge_candidates(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+) -> Vec<MergeCandidate> {
+    let mut candidates = workspaces
+        .values()
+        .filter(|entry| entry.settings.merge_ready)
+        .filter_map(|entry| {
+            let branch = entry.worktree.as_ref()?.branch.clone();
+            let parent = workspaces.get(entry.parent_id.as_ref()?)?.clone();
+            Some(MergeCandidate {
+                worktree: entry.clone(),
+                parent,
+                branch,
+            })
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.branch.cmp(&b.branch));
+    candidates
+}
+
+/// Collapses a `gh pr view --json statusCheckRollup` payload. A pull request
+/// without any checks counts as passing.
+pub(crate) fn ci_status_from_rollup(value: &Value) -> CiStatus {
+    let Some(checks) = value.get("statusCheckRollup").and_then(Value::as_array) else {
+        return CiStatus::Passing;
+    };
+    let mut pending = false;
+    for check in checks {
+        // Check runs report `status` + `conclusion`; commit statuses report `state`.
+        let state = check
+            .get("conclusion")
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .or_else(|| check.get("state").and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match state.as_str() {
+            "SUCCESS" | "NEUTRAL" | "SKIPPED" => {}
+            "" | "PENDING" | "EXPECTED" | "QUEUED" | "IN_PROGRESS" => pending = true,
+            _ => return CiStatus::Failing,
+        }
+    }
+    if pending {
+        CiStatus::Pending
+    } else {
+        CiStatus::Passing
+    }
+}
+
+pub(crate) async fn fetch_ci_status(repo_path: &PathBuf, branch: &str) -> Result<CiStatus, String> {
+    let output = tokio_command("gh")
+        .args(["pr", "view", branch, "--json", "statusCheckRollup"])
+        .current_dir(repo_path)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        if stderr.contains("no pull requests found") {
+            return Ok(CiStatus::NoPullRequest);
+        }
+        let detail = stderr.trim();
+        return Err(if detail.is_empty() {
+            "GitHub CLI command failed.".to_string()
+        } else {
+            detail.to_string()
+        });
+    }
+    let value: Value = serde_json::from_str(&stdout).map_err(|e| e.to_string())?;
+    Ok(ci_status_from_rollup(&value))
+}
+
+async fn ensure_clean(path: &PathBuf, label: &str) -> Result<(), String> {
+    let status = run_git_command(path, &["status", "--porcelain"]).await?;
+    if status.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{label} has uncommitted changes."))
+    }
+}
+
+async fn run_test_command(worktree_path: &PathBuf, command: &str) -> Result<(), String> {
+    let output = shell_command(command)
+        .current_dir(worktree_path)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run test command: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    let tail = detail
+        .lines()
+        .rev()
+        .take(20)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(format!("Test command failed.\n{tail}").trim().to_string())
+}
+
+/// Rebases the candidate branch onto the parent's checked-out branch, runs the
+/// parent's merge-queue test command, and fast-forwards the parent. A rebase
+/// conflict is aborted and reported as [`MergeOutcome::Conflict`].
+pub(crate) async fn rebase_test_and_merge<F>(
+    candidate: &MergeCandidate,
+    mut on_stage: F,
+) -> Result<MergeOutcome, String>
+where
+    F: FnMut(MergeQueueStage),
+{
+    let worktree_path = PathBuf::from(&candidate.worktree.path);
+    let parent_path = PathBuf::from(&candidate.parent.path);
+    let base_branch = run_git_command(&parent_path, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    if base_branch == "HEAD" {
+        return Err("Parent repository is not on a branch.".to_string());
+    }
+    ensure_clean(&worktree_path, "Worktree").await?;
+    ensure_clean(&parent_path, "Parent repository").await?;
+
+    on_stage(MergeQueueStage::Rebasing);
+    if let Err(detail) = run_git_command(&worktree_path, &["rebase", &base_branch]).await {
+        let _ = run_git_command(&worktree_path, &["rebase", "--abort"]).await;
+        return Ok(MergeOutcome::Conflict { detail });
+    }
+
+    if let Some(command) = candidate
+        .parent
+        .settings
+        .merge_queue_test_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        on_stage(MergeQueueStage::Testing);
+        run_test_command(&worktree_path, command).await?;
+    }
+
+    on_stage(MergeQueueStage::Merging);
+    run_git_command(&parent_path, &["merge", "--ff-only", &candidate.branch]).await?;
+    Ok(MergeOutcome::Merged { base_branch })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+    use serde_json::json;
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn git(dir: &PathBuf, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn entry(
+        id: &str,
+        path: &Path,
+        parent_id: Option<&str>,
+        branch: Option<&str>,
+    ) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind: if branch.is_some() {
+                WorkspaceKind::Worktree
+            } else {
+                WorkspaceKind::Main
+            },
+            parent_id: parent_id.map(str::to_string),
+            worktree: branch.map(|branch| WorktreeInfo {
+                branch: branch.to_string(),
+            }),
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn ci_status_from_rollup_collapses_checks() {
+        assert_eq!(
+            ci_status_from_rollup(&json!({ "statusCheckRollup": [] })),
+            CiStatus::Passing
+        );
+        assert_eq!(
+            ci_status_from_rollup(&json!({ "statusCheckRollup": [
+                { "status": "COMPLETED", "conclusion": "SUCCESS" },
+                { "state": "PENDING" }
+            ] })),
+            CiStatus::Pending
+        );
+        assert_eq!(
+            ci_status_from_rollup(&json!({ "statusCheckRollup": [
+                { "status": "IN_PROGRESS", "conclusion": "" },
+                { "status": "COMPLETED", "conclusion": "FAILURE" }
+            ] })),
+            CiStatus::Failing
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn rebase_test_and_merge_fast_forwards_parent() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("codex-monitor-merge-queue-{nonce}"));
+        let repo = root.join("repo");
+        let worktree = root.join("feature");
+        std::fs::create_dir_all(&repo).expect("create repo dir");
+        git(&repo, &["init", "-q", "-b", "main"]);
+        git(&repo, &["config", "user.name", "Test"]);
+        git(&repo, &["config", "user.email", "test@example.com"]);
+        std::fs::write(repo.join("a.txt"), "a\n").expect("write file");
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(worktree.join("b.txt"), "b\n").expect("write file");
+        git(&worktree, &["add", "."]);
+        git(&worktree, &["commit", "-q", "-m", "feature"]);
+        std::fs::write(repo.join("c.txt"), "c\n").expect("write file");
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "main moved"]);
+
+        let mut parent = entry("parent", &repo, None, None);
+        parent.settings.merge_queue_test_command = Some("test -f b.txt".to_string());
+        let mut child = entry("child", &worktree, Some("parent"), Some("feature"));
+        child.settings.merge_ready = true;
+        let workspaces =
+            HashMap::from([("parent".to_string(), parent), ("child".to_string(), child)]);
+        let candidates = ready_merge_candidates(&workspaces);
+        assert_eq!(candidates.len(), 1);
+
+        let mut stages = Vec::new();
+        let outcome = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(rebase_test_and_merge(&candidates[0], |stage| {
+                stages.push(stage)
+            }))
+            .expect("merge");
+        assert_eq!(
+            outcome,
+            MergeOutcome::Merged {
+                base_branch: "main".to_string()
+            }
+        );
+        assert_eq!(
+            stages,
+            vec![
+                MergeQueueStage::Rebasing,
+                MergeQueueStage::Testing,
+                MergeQueueStage::Merging
+            ]
+        );
+        assert!(repo.join("b.txt").exists());
+        assert!(repo.join("c.txt").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,17 +1,65 @@
 pub(crate) mod account;
 pub(crate) mod agents_config_core;
+pub(crate) mod archive_undo_core;
+// Only the daemon runs automation scripts.
+#[allow(dead_code)]
+pub(crate) mod automation_core;
+pub(crate) mod broadcast_core;
+pub(crate) mod capabilities_core;
 pub(crate) mod codex_aux_core;
 pub(crate) mod codex_core;
 pub(crate) mod codex_update_core;
 pub(crate) mod config_toml_core;
+pub(crate) mod context_attach_core;
+pub(crate) mod daemon_version_core;
+pub(crate) mod dashboard_core;
+pub(crate) mod dir_tree_core;
+// The daemon advertises itself; the app browses.
+#[allow(dead_code)]
+pub(crate) mod discovery_core;
+// Only the daemon forwards events to clients.
+#[allow(dead_code)]
+pub(crate) mod event_forward_core;
+pub(crate) mod file_edit_core;
+pub(crate) mod file_index_core;
+pub(crate) mod file_search_core;
+pub(crate) mod file_transfer_core;
+pub(crate) mod file_watch_core;
 pub(crate) mod files_core;
 pub(crate) mod git_core;
 pub(crate) mod git_rpc;
+pub(crate) mod git_status_core;
 pub(crate) mod git_ui_core;
+pub(crate) mod host_stats_core;
+pub(crate) mod image_prepare_core;
 pub(crate) mod local_usage_core;
+// Only the daemon runs the merge queue.
+#[allow(dead_code)]
+pub(crate) mod merge_queue_core;
+pub(crate) mod network_core;
+pub(crate) mod notifications_core;
+pub(crate) mod playbooks_core;
 pub(crate) mod process_core;
+pub(crate) mod prompt_library_core;
 pub(crate) mod prompts_core;
+pub(crate) mod release_core;
+pub(crate) mod scheduled_messages_core;
+pub(crate) mod search_core;
 pub(crate) mod settings_core;
+pub(crate) mod slash_commands_core;
+// Only the app hibernates UI state.
+#[allow(dead_code)]
+pub(crate) mod state_snapshot_core;
+pub(crate) mod storage_core;
+pub(crate) mod task_output_core;
+pub(crate) mod thread_export_core;
+pub(crate) mod thread_meta_core;
+pub(crate) mod thread_order_core;
+pub(crate) mod thread_tags_core;
+pub(crate) mod workspace_recency_core;
 pub(crate) mod workspace_rpc;
+pub(crate) mod workspace_summary_core;
 pub(crate) mod workspaces_core;
+pub(crate) mod worktree_compare_core;
 pub(crate) mod worktree_core;
+pub(crate) mod worktree_lock_core;
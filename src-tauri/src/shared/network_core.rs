@@ -0,0 +1,211 @@
+use std::ffi::OsString;
+use std::time::{Duration, Instant};
+
+use crate::types::{NetworkConnectivityReport, NetworkProbeResult, NetworkSettings};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_PROBE_URLS: &[&str] = &["https://api.github.com", "https://api.openai.com/v1/models"];
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// `settings` with blank values dropped.
+pub(crate) fn normalize(settings: Option<&NetworkSettings>) -> NetworkSettings {
+    let Some(settings) = settings else {
+        return NetworkSettings::default();
+    };
+    NetworkSettings {
+        http_proxy: non_empty(settings.http_proxy.as_ref()),
+        https_proxy: non_empty(settings.https_proxy.as_ref()),
+        no_proxy: non_empty(settings.no_proxy.as_ref()),
+        ca_certificates_path: non_empty(settings.ca_certificates_path.as_ref()),
+    }
+}
+
+/// Environment handed to spawned processes so `codex`, `git`, `gh`, `npm`
+/// and friends use the same proxy and trust store as the app.
+pub(crate) fn child_process_env(settings: &NetworkSettings) -> Vec<(&'static str, String)> {
+    let mut env = Vec::new();
+    if let Some(proxy) = settings.http_proxy.as_ref() {
+        env.push(("HTTP_PROXY", proxy.clone()));
+        env.push(("http_proxy", proxy.clone()));
+    }
+    if let Some(proxy) = settings.https_proxy.as_ref() {
+        env.push(("HTTPS_PROXY", proxy.clone()));
+        env.push(("https_proxy", proxy.clone()));
+    }
+    if let Some(no_proxy) = settings.no_proxy.as_ref() {
+        env.push(("NO_PROXY", no_proxy.clone()));
+        env.push(("no_proxy", no_proxy.clone()));
+    }
+    if let Some(path) = settings.ca_certificates_path.as_ref() {
+        env.push(("SSL_CERT_FILE", path.clone()));
+        env.push(("NODE_EXTRA_CA_CERTS", path.clone()));
+    }
+    env
+}
+
+/// Variables `child_process_env` can set.
+const CHILD_ENV_KEYS: [&str; 8] = [
+    "HTTP_PROXY",
+    "http_proxy",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "NO_PROXY",
+    "no_proxy",
+    "SSL_CERT_FILE",
+    "NODE_EXTRA_CA_CERTS",
+];
+
+/// The proxy and CA variables as this process inherited them. Spawned
+/// processes (`codex`, `git`, `gh`, `npm`, ...) inherit the process env, so
+/// the configured values are applied there, and a cleared setting gives the
+/// inherited value back.
+pub(crate) struct InheritedNetworkEnv(Vec<(&'static str, Option<OsString>)>);
+
+impl InheritedNetworkEnv {
+    pub(crate) fn capture() -> Self {
+        Self(
+            CHILD_ENV_KEYS
+                .iter()
+                .map(|key| (*key, std::env::var_os(key)))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn apply(&self, settings: &NetworkSettings) {
+        let configured = child_process_env(settings);
+        for (key, inherited) in &self.0 {
+            let value = configured
+                .iter()
+                .find(|(configured_key, _)| configured_key == key)
+                .map(|(_, value)| OsString::from(value))
+                .or_else(|| inherited.clone());
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// A `reqwest` builder with `settings`' proxy and CA certificates. Every
+/// outbound HTTP client should start from this.
+pub(crate) fn http_client_builder(
+    settings: &NetworkSettings,
+) -> Result<reqwest::ClientBuilder, String> {
+    let mut builder = reqwest::Client::builder();
+    let no_proxy = settings
+        .no_proxy
+        .as_deref()
+        .and_then(reqwest::NoProxy::from_string);
+    if let Some(proxy) = settings.https_proxy.as_deref() {
+        let proxy = reqwest::Proxy::https(proxy)
+            .map_err(|err| format!("Invalid HTTPS proxy `{proxy}`: {err}"))?
+            .no_proxy(no_proxy.clone());
+        builder = builder.proxy(proxy);
+    }
+    if let Some(proxy) = settings.http_proxy.as_deref() {
+        let proxy = reqwest::Proxy::http(proxy)
+            .map_err(|err| format!("Invalid HTTP proxy `{proxy}`: {err}"))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = settings.ca_certificates_path.as_deref() {
+        let pem = std::fs::read(path)
+            .map_err(|err| format!("Failed to read CA certificates {path}: {err}"))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|err| format!("Invalid CA certificates in {path}: {err}"))?;
+        if certificates.is_empty() {
+            return Err(format!("No certificates found in {path}"));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder)
+}
+
+async fn probe(client: &reqwest::Client, url: String) -> NetworkProbeResult {
+    let started = Instant::now();
+    let outcome = client.get(&url).send().await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        // Any HTTP response, even 401, means the proxy and TLS chain work.
+        Ok(response) => NetworkProbeResult {
+            url,
+            ok: true,
+            status: Some(response.status().as_u16()),
+            elapsed_ms,
+            error: None,
+        },
+        Err(err) => NetworkProbeResult {
+            url,
+            ok: false,
+            status: None,
+            elapsed_ms,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+pub(crate) async fn test_network_connectivity_core(
+    settings: NetworkSettings,
+    urls: Option<Vec<String>>,
+) -> Result<NetworkConnectivityReport, String> {
+    let client = http_client_builder(&settings)?
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let urls = urls.filter(|urls| !urls.is_empty()).unwrap_or_else(|| {
+        DEFAULT_PROBE_URLS
+            .iter()
+            .map(|url| url.to_string())
+            .collect()
+    });
+    let mut probes = Vec::with_capacity(urls.len());
+    for url in urls {
+        probes.push(probe(&client, url).await);
+    }
+    Ok(NetworkConnectivityReport {
+        ok: probes.iter().all(|probe| probe.ok),
+        http_proxy: settings.http_proxy,
+        https_proxy: settings.https_proxy,
+        ca_certificates_path: settings.ca_certificates_path,
+        probes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn child_env_skips_blank_values() {
+        let settings = normalize(Some(&NetworkSettings {
+            http_proxy: Some("  ".to_string()),
+            https_proxy: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: Some("localhost,.corp".to_string()),
+            ca_certificates_path: None,
+        }));
+        let env = child_process_env(&settings);
+        assert!(env.contains(&("HTTPS_PROXY", "http://proxy.corp:3128".to_string())));
+        assert!(env.contains(&("no_proxy", "localhost,.corp".to_string())));
+        assert!(!env.iter().any(|(key, _)| *key == "HTTP_PROXY"));
+        assert!(!env.iter().any(|(key, _)| *key == "SSL_CERT_FILE"));
+    }
+
+    #[test]
+    fn client_builder_rejects_missing_ca_file() {
+        let settings = NetworkSettings {
+            ca_certificates_path: Some("/nonexistent/ca.pem".to_string()),
+            ..NetworkSettings::default()
+        };
+        let err = http_client_builder(&settings).expect_err("missing CA file");
+        assert!(err.contains("/nonexistent/ca.pem"));
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::backend::events::AppServerEvent;
+use crate::shared::network_core;
+use crate::types::{NetworkSettings, WorkspaceEntry};
+
+// Only the daemon posts webhooks.
+#[allow(dead_code)]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BODY_CHARS: usize = 200;
+/// Oldest entries are dropped past this so `notifications.json` stays small.
+const MAX_NOTIFICATIONS: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum NotificationKind {
+    TurnCompleted,
+    TurnFailed,
+    ApprovalRequested,
+    CiResult,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceNotification {
+    pub(crate) kind: NotificationKind,
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) title: String,
+    pub(crate) body: String,
+}
+
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .filter(|value| !value.is_empty())
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_BODY_CHARS {
+        return text.to_string();
+    }
+    let mut out = text.chars().take(MAX_BODY_CHARS - 1).collect::<String>();
+    out.push('…');
+    out
+}
+
+/// Picks out the events worth a notification: finished or failed turns and
+/// approval requests. Retried errors and interrupted turns are skipped.
+fn classify(message: &Value) -> Option<(NotificationKind, Option<String>, String)> {
+    let method = message.get("method").and_then(Value::as_str)?;
+    let params = message.get("params").unwrap_or(&Value::Null);
+    let turn = params.get("turn").unwrap_or(&Value::Null);
+    let thread_id = str_field(params, &["threadId", "thread_id"])
+        .or_else(|| str_field(turn, &["threadId", "thread_id"]));
+    match method {
+        "turn/completed" => match str_field(turn, &["status"]).as_deref() {
+            Some("failed") => {
+                let detail = turn
+                    .get("error")
+                    .and_then(|error| str_field(error, &["message"]))
+                    .unwrap_or_else(|| "The turn failed.".to_string());
+                Some((NotificationKind::TurnFailed, thread_id, detail))
+            }
+            Some("interrupted") => None,
+            _ => Some((
+                NotificationKind::TurnCompleted,
+                thread_id,
+                "Your agent has finished its task.".to_string(),
+            )),
+        },
+        "error" => {
+            if params.get("willRetry").and_then(Value::as_bool) == Some(true) {
+                return None;
+            }
+            let detail = params
+                .get("error")
+                .and_then(|error| str_field(error, &["message"]))
+                .unwrap_or_else(|| "The agent reported an error.".to_string());
+            Some((NotificationKind::TurnFailed, thread_id, detail))
+        }
+        "mergeQueue/progress" => {
+            let branch = str_field(params, &["branch"]).unwrap_or_else(|| "worktree".to_string());
+            let detail = str_field(params, &["detail"]);
+            let summary = match str_field(params, &["stage"]).as_deref()? {
+                "merged" => format!("{branch} passed CI and was merged"),
+                "conflict" => format!("{branch} hit a merge conflict"),
+                "failed" => format!("{branch} failed the merge queue"),
+                _ => return None,
+            };
+            let summary = match detail {
+                Some(detail) => format!("{summary}: {detail}"),
+                None => summary,
+            };
+            Some((NotificationKind::CiResult, None, summary))
+        }
+        _ if method.ends_with("requestApproval") && message.get("id").is_some() => {
+            let detail = params
+                .get("command")
+                .and_then(|command| match command {
+                    Value::String(command) => Some(command.clone()),
+                    Value::Array(parts) => Some(
+                        parts
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    _ => None,
+                })
+                .or_else(|| str_field(params, &["reason"]))
+                .unwrap_or_else(|| method.to_string());
+            Some((NotificationKind::ApprovalRequested, thread_id, detail))
+        }
+        _ => None,
+    }
+}
+
+/// Describes `event` for the notification center, regardless of whether its
+/// workspace opted in to system notifications.
+pub(crate) async fn describe_event(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event: &AppServerEvent,
+) -> Option<WorkspaceNotification> {
+    let (kind, thread_id, detail) = classify(&event.message)?;
+    let workspace_name = workspaces
+        .read()
+        .await
+        .get(&event.workspace_id)?
+        .name
+        .clone();
+    let title = match kind {
+        NotificationKind::TurnCompleted => workspace_name.clone(),
+        NotificationKind::TurnFailed => format!("Error — {workspace_name}"),
+        NotificationKind::ApprovalRequested => format!("Approval needed — {workspace_name}"),
+        NotificationKind::CiResult => format!("Merge queue — {workspace_name}"),
+    };
+    Some(WorkspaceNotification {
+        kind,
+        workspace_id: event.workspace_id.clone(),
+        workspace_name,
+        thread_id,
+        title,
+        body: truncate(&detail),
+    })
+}
+
+pub(crate) async fn notifications_enabled(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> bool {
+    workspaces
+        .read()
+        .await
+        .get(workspace_id)
+        .is_some_and(|entry| entry.settings.notifications_enabled)
+}
+
+/// Builds the notification for `event` if its workspace opted in via
+/// `notificationsEnabled`.
+// Only the daemon's notification webhook needs the combined check.
+#[allow(dead_code)]
+pub(crate) async fn notification_for_event(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event: &AppServerEvent,
+) -> Option<WorkspaceNotification> {
+    if !may_notify(event) || !notifications_enabled(workspaces, &event.workspace_id).await {
+        return None;
+    }
+    describe_event(workspaces, event).await
+}
+
+/// Raw event forwarded by the daemon's `--webhook-url` sink. Unlike
+/// [`WorkspaceNotification`] it ignores the per-workspace opt-in and keeps the
+/// original params for integrations that want more than a title and body.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WebhookEvent {
+    pub(crate) kind: NotificationKind,
+    pub(crate) method: String,
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: Option<String>,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) summary: String,
+    pub(crate) params: Value,
+}
+
+// Only the daemon runs the webhook sink.
+#[allow(dead_code)]
+pub(crate) async fn webhook_event_for(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event: &AppServerEvent,
+) -> Option<WebhookEvent> {
+    let (kind, thread_id, detail) = classify(&event.message)?;
+    let workspace_name = workspaces
+        .read()
+        .await
+        .get(&event.workspace_id)
+        .map(|entry| entry.name.clone());
+    Some(WebhookEvent {
+        kind,
+        method: event
+            .message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        workspace_id: event.workspace_id.clone(),
+        workspace_name,
+        thread_id,
+        summary: truncate(&detail),
+        params: event.message.get("params").cloned().unwrap_or(Value::Null),
+    })
+}
+
+/// Cheap pre-filter so callers can skip the workspace lookup for the bulk of
+/// streaming events.
+pub(crate) fn may_notify(event: &AppServerEvent) -> bool {
+    classify(&event.message).is_some()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NotificationEntry {
+    pub(crate) id: String,
+    pub(crate) kind: NotificationKind,
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) title: String,
+    pub(crate) body: String,
+    pub(crate) created_at: u64,
+    #[serde(default)]
+    pub(crate) read: bool,
+}
+
+/// Persisted notification list with read state, shared by every client of
+/// the backend so desktop and mobile show the same unread items.
+pub(crate) struct NotificationCenter {
+    path: PathBuf,
+    entries: Mutex<Vec<NotificationEntry>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl NotificationCenter {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    fn persist(&self, entries: &[NotificationEntry]) {
+        let result = (|| -> Result<(), String> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let data = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+            let tmp = self.path.with_extension("json.tmp");
+            std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+            std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+        })();
+        if let Err(error) = result {
+            eprintln!("failed to persist notifications: {error}");
+        }
+    }
+
+    pub(crate) fn record(&self, notification: &WorkspaceNotification) -> NotificationEntry {
+        let entry = NotificationEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: notification.kind,
+            workspace_id: notification.workspace_id.clone(),
+            workspace_name: notification.workspace_name.clone(),
+            thread_id: notification.thread_id.clone(),
+            title: notification.title.clone(),
+            body: notification.body.clone(),
+            created_at: now_ms(),
+            read: false,
+        };
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry.clone());
+            if entries.len() > MAX_NOTIFICATIONS {
+                let excess = entries.len() - MAX_NOTIFICATIONS;
+                entries.drain(..excess);
+            }
+            self.persist(&entries);
+        }
+        entry
+    }
+
+    /// Newest first.
+    pub(crate) fn list(&self, unread_only: bool, limit: Option<usize>) -> Vec<NotificationEntry> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| !unread_only || !entry.read)
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Marks `ids` as read, or everything when `ids` is `None`. Returns how
+    /// many entries changed.
+    pub(crate) fn mark_read(&self, ids: Option<&[String]>) -> usize {
+        let Ok(mut entries) = self.entries.lock() else {
+            return 0;
+        };
+        let mut changed = 0;
+        for entry in entries.iter_mut() {
+            if entry.read || ids.is_some_and(|ids| !ids.contains(&entry.id)) {
+                continue;
+            }
+            entry.read = true;
+            changed += 1;
+        }
+        if changed > 0 {
+            self.persist(&entries);
+        }
+        changed
+    }
+}
+
+/// POSTs `payload` as JSON to `url` through the configured proxy.
+#[allow(dead_code)]
+pub(crate) async fn post_webhook<T: Serialize>(
+    network: &NetworkSettings,
+    url: &str,
+    payload: &T,
+) -> Result<(), String> {
+    let payload = serde_json::to_vec(payload).map_err(|err| err.to_string())?;
+    let client = network_core::http_client_builder(network)?
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload)
+        .send()
+        .await
+        .map_err(|err| format!("Webhook POST to {url} failed: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook {url} returned {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+    use serde_json::json;
+
+    fn workspaces(notifications_enabled: bool) -> RwLock<HashMap<String, WorkspaceEntry>> {
+        let entry = WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "Repo".to_string(),
+            path: "/tmp/repo".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                notifications_enabled,
+                ..WorkspaceSettings::default()
+            },
+        };
+        RwLock::new(HashMap::from([(entry.id.clone(), entry)]))
+    }
+
+    fn event(message: Value) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message,
+        }
+    }
+
+    #[test]
+    fn classifies_turns_errors_and_approvals() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let workspaces = workspaces(true);
+            let completed = event(json!({
+                "method": "turn/completed",
+                "params": { "threadId": "t-1", "turn": { "status": "completed" } }
+            }));
+            let notification = notification_for_event(&workspaces, &completed)
+                .await
+                .expect("completed notifies");
+            assert_eq!(notification.kind, NotificationKind::TurnCompleted);
+            assert_eq!(notification.title, "Repo");
+            assert_eq!(notification.thread_id.as_deref(), Some("t-1"));
+
+            let failed = event(json!({
+                "method": "turn/completed",
+                "params": { "turn": { "status": "failed", "error": { "message": "boom" } } }
+            }));
+            let notification = notification_for_event(&workspaces, &failed)
+                .await
+                .expect("failed notifies");
+            assert_eq!(notification.kind, NotificationKind::TurnFailed);
+            assert_eq!(notification.body, "boom");
+
+            let approval = event(json!({
+                "id": 7,
+                "method": "item/commandExecution/requestApproval",
+                "params": { "threadId": "t-1", "command": ["rm", "-rf", "build"] }
+            }));
+            let notification = notification_for_event(&workspaces, &approval)
+                .await
+                .expect("approval notifies");
+            assert_eq!(notification.kind, NotificationKind::ApprovalRequested);
+            assert_eq!(notification.body, "rm -rf build");
+
+            let retried = event(json!({
+                "method": "error",
+                "params": { "willRetry": true, "error": { "message": "flaky" } }
+            }));
+            assert!(!may_notify(&retried));
+            assert!(!may_notify(&event(
+                json!({ "method": "item/agentMessage/delta" })
+            )));
+        });
+    }
+
+    #[test]
+    fn webhook_events_ignore_opt_in_and_keep_params() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let workspaces = workspaces(false);
+            let failed = event(json!({
+                "method": "error",
+                "params": { "threadId": "t-9", "error": { "message": "quota" } }
+            }));
+            let payload = webhook_event_for(&workspaces, &failed)
+                .await
+                .expect("error forwarded");
+            assert_eq!(payload.kind, NotificationKind::TurnFailed);
+            assert_eq!(payload.method, "error");
+            assert_eq!(payload.workspace_name.as_deref(), Some("Repo"));
+            assert_eq!(payload.params["error"]["message"], "quota");
+        });
+    }
+
+    #[test]
+    fn notification_center_tracks_read_state() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-notifications-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let path = dir.join("notifications.json");
+        let center = NotificationCenter::load(path.clone());
+        let merged = tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let workspaces = workspaces(false);
+            describe_event(
+                &workspaces,
+                &event(json!({
+                    "method": "mergeQueue/progress",
+                    "params": { "branch": "feat/x", "stage": "merged" }
+                })),
+            )
+            .await
+            .expect("merge result described without opt-in")
+        });
+        assert_eq!(merged.kind, NotificationKind::CiResult);
+        let first = center.record(&merged);
+        let second = center.record(&merged);
+        assert_eq!(center.mark_read(Some(std::slice::from_ref(&first.id))), 1);
+
+        let reloaded = NotificationCenter::load(path);
+        let unread = reloaded.list(true, None);
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].id, second.id);
+        assert_eq!(reloaded.list(false, Some(1))[0].id, second.id);
+        assert_eq!(reloaded.mark_read(None), 1);
+        assert!(reloaded.list(true, None).is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn skips_workspaces_that_did_not_opt_in() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let workspaces = workspaces(false);
+            let completed = event(json!({
+                "method": "turn/completed",
+                "params": { "turn": { "status": "completed" } }
+            }));
+            assert!(may_notify(&completed));
+            assert!(notification_for_event(&workspaces, &completed)
+                .await
+                .is_none());
+        });
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::shared::codex_core;
+use crate::shared::git_core::run_git_command;
+use crate::shared::process_core::shell_command;
+use crate::shared::prompt_library_core::{render_template, template_variables};
+use crate::types::{WorkspaceEntry, WorktreeResult};
+
+const MAX_PLAYBOOKS: usize = 200;
+const MAX_TEMPLATE_BYTES: usize = 64 * 1024;
+const MAX_CONTEXT_FILES: usize = 20;
+const MAX_CONTEXT_FILE_BYTES: u64 = 64 * 1024;
+
+/// A stored recipe for starting a thread: optionally a new worktree on a
+/// rendered branch name, a setup script, context files attached to the
+/// first message, and the model/effort to run it with.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Playbook {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    /// Branch template for a new worktree, e.g. `fix/{{ticket}}`. Without it
+    /// the thread starts in the workspace itself.
+    #[serde(default)]
+    pub(crate) worktree_branch: Option<String>,
+    /// Shell script run in the worktree (or workspace) before the thread starts.
+    #[serde(default)]
+    pub(crate) setup_script: Option<String>,
+    /// Paths relative to the workspace whose contents follow the prompt.
+    #[serde(default)]
+    pub(crate) context_files: Vec<String>,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) effort: Option<String>,
+    pub(crate) prompt_template: String,
+    /// Placeholders used by the branch and prompt templates, in first-use order.
+    #[serde(default)]
+    pub(crate) variables: Vec<String>,
+    pub(crate) created_at: u64,
+    pub(crate) updated_at: u64,
+}
+
+/// The editable fields of a playbook; `id` selects the one to replace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlaybookDraft {
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) worktree_branch: Option<String>,
+    #[serde(default)]
+    pub(crate) setup_script: Option<String>,
+    #[serde(default)]
+    pub(crate) context_files: Vec<String>,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) effort: Option<String>,
+    pub(crate) prompt_template: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlaybookRun {
+    pub(crate) playbook_id: String,
+    /// Where the thread runs: the new worktree, or the requested workspace.
+    pub(crate) workspace_id: String,
+    pub(crate) worktree_workspace_id: Option<String>,
+    pub(crate) branch: Option<String>,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+}
+
+pub(crate) struct PlaybookStore {
+    path: PathBuf,
+    playbooks: Mutex<Vec<Playbook>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl PlaybookStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let playbooks = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            playbooks: Mutex::new(playbooks),
+        }
+    }
+
+    fn persist(&self, playbooks: &[Playbook]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(playbooks).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+
+    fn get(&self, id: &str) -> Result<Playbook, String> {
+        self.playbooks
+            .lock()
+            .map_err(|_| "playbooks are poisoned".to_string())?
+            .iter()
+            .find(|playbook| playbook.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Playbook {id} not found"))
+    }
+}
+
+pub(crate) fn playbook_list_core(store: &PlaybookStore) -> Vec<Playbook> {
+    let mut playbooks = store
+        .playbooks
+        .lock()
+        .map(|playbooks| playbooks.clone())
+        .unwrap_or_default();
+    playbooks.sort_by_key(|playbook| playbook.name.to_lowercase());
+    playbooks
+}
+
+/// Creates a playbook, or replaces the one with `draft.id`.
+pub(crate) fn playbook_save_core(
+    store: &PlaybookStore,
+    draft: PlaybookDraft,
+) -> Result<Playbook, String> {
+    let name = draft.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Playbook name must not be empty".to_string());
+    }
+    if draft.prompt_template.trim().is_empty() {
+        return Err("Playbook prompt must not be empty".to_string());
+    }
+    if draft.prompt_template.len() > MAX_TEMPLATE_BYTES {
+        return Err(format!(
+            "Playbook prompts are limited to {MAX_TEMPLATE_BYTES} bytes"
+        ));
+    }
+    let context_files: Vec<String> = draft
+        .context_files
+        .iter()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect();
+    if context_files.len() > MAX_CONTEXT_FILES {
+        return Err(format!(
+            "A playbook attaches at most {MAX_CONTEXT_FILES} context files"
+        ));
+    }
+    if let Some(path) = context_files
+        .iter()
+        .find(|path| Path::new(path).is_absolute())
+    {
+        return Err(format!(
+            "Context file `{path}` must be relative to the workspace"
+        ));
+    }
+    let worktree_branch = non_empty(draft.worktree_branch);
+    let mut variables = template_variables(worktree_branch.as_deref().unwrap_or_default());
+    for name in template_variables(&draft.prompt_template) {
+        if !variables.contains(&name) {
+            variables.push(name);
+        }
+    }
+
+    let mut playbooks = store
+        .playbooks
+        .lock()
+        .map_err(|_| "playbooks are poisoned".to_string())?;
+    let now = now_ms();
+    let mut playbook = Playbook {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        description: non_empty(draft.description),
+        worktree_branch,
+        setup_script: non_empty(draft.setup_script),
+        context_files,
+        model: non_empty(draft.model),
+        effort: non_empty(draft.effort),
+        prompt_template: draft.prompt_template,
+        variables,
+        created_at: now,
+        updated_at: now,
+    };
+    match draft.id {
+        Some(id) => {
+            let existing = playbooks
+                .iter_mut()
+                .find(|existing| existing.id == id)
+                .ok_or_else(|| format!("Playbook {id} not found"))?;
+            playbook.id = id;
+            playbook.created_at = existing.created_at;
+            *existing = playbook.clone();
+        }
+        None => {
+            if playbooks.len() >= MAX_PLAYBOOKS {
+                return Err(format!("At most {MAX_PLAYBOOKS} playbooks can be saved"));
+            }
+            playbooks.push(playbook.clone());
+        }
+    }
+    store.persist(&playbooks)?;
+    Ok(playbook)
+}
+
+pub(crate) fn playbook_delete_core(store: &PlaybookStore, id: &str) -> Result<(), String> {
+    let mut playbooks = store
+        .playbooks
+        .lock()
+        .map_err(|_| "playbooks are poisoned".to_string())?;
+    let before = playbooks.len();
+    playbooks.retain(|playbook| playbook.id != id);
+    if playbooks.len() == before {
+        return Err(format!("Playbook {id} not found"));
+    }
+    store.persist(&playbooks)
+}
+
+/// Reads a context file under `root`, refusing paths that escape it.
+fn read_context_file(root: &Path, relative_path: &str) -> Result<String, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let canonical_path = canonical_root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to open context file `{relative_path}`: {err}"))?;
+    if !canonical_path.starts_with(&canonical_root) || !canonical_path.is_file() {
+        return Err(format!("Invalid context file `{relative_path}`"));
+    }
+    let mut buffer = Vec::new();
+    std::fs::File::open(&canonical_path)
+        .and_then(|file| {
+            file.take(MAX_CONTEXT_FILE_BYTES + 1)
+                .read_to_end(&mut buffer)
+        })
+        .map_err(|err| format!("Failed to read context file `{relative_path}`: {err}"))?;
+    let truncated = buffer.len() as u64 > MAX_CONTEXT_FILE_BYTES;
+    buffer.truncate(MAX_CONTEXT_FILE_BYTES as usize);
+    let mut content = String::from_utf8_lossy(&buffer).into_owned();
+    if truncated {
+        content.push_str("\n[truncated]");
+    }
+    Ok(content)
+}
+
+/// The rendered prompt followed by each context file in a fenced block.
+fn compose_message(prompt: &str, files: &[(String, String)]) -> String {
+    let mut message = prompt.trim_end().to_string();
+    for (path, content) in files {
+        message.push_str(&format!("\n\n`{path}`:\n```\n{}\n```", content.trim_end()));
+    }
+    message
+}
+
+async fn run_setup_script(script: &str, cwd: &str) -> Result<(), String> {
+    let output = shell_command(script)
+        .current_dir(cwd)
+        .output()
+        .await
+        .map_err(|err| format!("Failed to run setup script: {err}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr.trim().lines().last().unwrap_or_default();
+    Err(format!("Setup script failed: {detail}").trim().to_string())
+}
+
+/// Runs a playbook against `workspace_id` as one operation: renders the
+/// branch and prompt (built-ins `workspace`, `path` and `branch`; `variables`
+/// override them), creates the worktree, runs the setup script, attaches the
+/// context files and starts the thread. Every placeholder is checked before
+/// anything is created.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_playbook_core<C, FutC, W, FutW>(
+    store: &PlaybookStore,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    playbook_id: String,
+    variables: HashMap<String, String>,
+    connect: C,
+    add_worktree: W,
+) -> Result<PlaybookRun, String>
+where
+    C: Fn(String) -> FutC,
+    FutC: Future<Output = Result<(), String>>,
+    W: Fn(String, String) -> FutW,
+    FutW: Future<Output = Result<WorktreeResult, String>>,
+{
+    let playbook = store.get(&playbook_id)?;
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+
+    let mut values = HashMap::from([
+        ("workspace".to_string(), entry.name.clone()),
+        ("path".to_string(), entry.path.clone()),
+    ]);
+    values.extend(variables);
+    let branch = match playbook.worktree_branch.as_deref() {
+        Some(template) => {
+            let branch = render_template(template, &values)?.trim().to_string();
+            if branch.is_empty() {
+                return Err("Playbook branch rendered empty".to_string());
+            }
+            Some(branch)
+        }
+        None => None,
+    };
+    if !values.contains_key("branch") {
+        let current = match &branch {
+            Some(branch) => Some(branch.clone()),
+            None if template_variables(&playbook.prompt_template)
+                .iter()
+                .any(|name| name == "branch") =>
+            {
+                Some(
+                    run_git_command(
+                        &PathBuf::from(&entry.path),
+                        &["rev-parse", "--abbrev-ref", "HEAD"],
+                    )
+                    .await?,
+                )
+            }
+            None => None,
+        };
+        if let Some(current) = current {
+            values.insert("branch".to_string(), current);
+        }
+    }
+    let prompt = render_template(&playbook.prompt_template, &values)?;
+
+    let (target_id, target_path, worktree_workspace_id, branch) = match branch {
+        Some(branch) => {
+            let worktree = add_worktree(workspace_id.clone(), branch).await?.workspace;
+            // The branch may have been suffixed to avoid a collision.
+            let branch = worktree.worktree.map(|info| info.branch);
+            (
+                worktree.id.clone(),
+                worktree.path,
+                Some(worktree.id),
+                branch,
+            )
+        }
+        None => (workspace_id.clone(), entry.path.clone(), None, None),
+    };
+    if let Some(script) = playbook.setup_script.as_deref() {
+        run_setup_script(script, &target_path).await?;
+    }
+    let files = playbook
+        .context_files
+        .iter()
+        .map(|path| read_context_file(Path::new(&target_path), path).map(|c| (path.clone(), c)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let message = compose_message(&prompt, &files);
+
+    connect(target_id.clone()).await?;
+    let thread = codex_core::start_thread_core(sessions, workspaces, target_id.clone()).await?;
+    let thread_id = codex_core::response_object_id(&thread, "thread")
+        .ok_or_else(|| "thread/start did not return a thread id".to_string())?;
+    let turn = codex_core::send_user_message_core(
+        sessions,
+        workspaces,
+        target_id.clone(),
+        thread_id.clone(),
+        message,
+        playbook.model.clone(),
+        playbook.effort.clone(),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(PlaybookRun {
+        playbook_id,
+        workspace_id: target_id,
+        worktree_workspace_id,
+        branch,
+        thread_id,
+        turn_id: codex_core::response_object_id(&turn, "turn"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("codex-monitor-playbooks-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn draft(prompt: &str) -> PlaybookDraft {
+        PlaybookDraft {
+            name: "Fix ticket".to_string(),
+            worktree_branch: Some("fix/{{ticket}}".to_string()),
+            context_files: vec!["README.md".to_string()],
+            prompt_template: prompt.to_string(),
+            ..PlaybookDraft::default()
+        }
+    }
+
+    #[test]
+    fn save_collects_variables_and_persists() {
+        let dir = temp_dir();
+        let store = PlaybookStore::load(dir.join("playbooks.json"));
+        let created =
+            playbook_save_core(&store, draft("Fix {{ticket}} on {{branch}}: {{summary}}"))
+                .expect("create");
+        assert_eq!(created.variables, vec!["ticket", "branch", "summary"]);
+
+        let updated = playbook_save_core(
+            &store,
+            PlaybookDraft {
+                id: Some(created.id.clone()),
+                model: Some(" gpt-x ".to_string()),
+                ..draft("Fix {{ticket}}")
+            },
+        )
+        .expect("update");
+        assert_eq!(updated.created_at, created.created_at);
+        assert_eq!(updated.model.as_deref(), Some("gpt-x"));
+
+        let reloaded = PlaybookStore::load(dir.join("playbooks.json"));
+        assert_eq!(playbook_list_core(&reloaded), vec![updated]);
+        assert!(playbook_save_core(
+            &store,
+            PlaybookDraft {
+                context_files: vec!["/etc/passwd".to_string()],
+                ..draft("x")
+            }
+        )
+        .is_err());
+        playbook_delete_core(&reloaded, &created.id).expect("delete");
+        assert!(playbook_delete_core(&reloaded, &created.id).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn context_files_stay_inside_the_workspace() {
+        let dir = temp_dir();
+        let root = dir.join("repo");
+        std::fs::create_dir_all(&root).expect("create repo");
+        std::fs::write(root.join("notes.md"), "remember this\n").expect("write notes");
+        std::fs::write(dir.join("secret.txt"), "nope").expect("write secret");
+
+        let content = read_context_file(&root, "notes.md").expect("read");
+        assert_eq!(
+            compose_message("Do it.\n", &[("notes.md".to_string(), content)]),
+            "Do it.\n\n`notes.md`:\n```\nremember this\n```"
+        );
+        assert!(read_context_file(&root, "../secret.txt").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -31,6 +31,22 @@ pub(crate) fn std_command(program: impl AsRef<OsStr>) -> std::process::Command {
     command
 }
 
+/// Runs `script` through the platform shell (`sh -c` / `cmd /C`).
+pub(crate) fn shell_command(script: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = tokio_command("cmd");
+        command.args(["/D", "/C", script]);
+        command
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut command = tokio_command("sh");
+        command.args(["-c", script]);
+        command
+    }
+}
+
 pub(crate) async fn kill_child_process_tree(child: &mut Child) {
     #[cfg(windows)]
     {
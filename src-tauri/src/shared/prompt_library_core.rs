@@ -0,0 +1,317 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::shared::git_core::{run_git_command, run_git_command_bytes};
+use crate::types::WorkspaceEntry;
+
+const MAX_TEMPLATE_BYTES: usize = 64 * 1024;
+const MAX_PROMPTS: usize = 500;
+
+/// A reusable prompt shared by every workspace. `{{name}}` placeholders are
+/// filled in by `prompt_render`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SavedPrompt {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    pub(crate) template: String,
+    /// Placeholders used by `template`, in first-use order.
+    #[serde(default)]
+    pub(crate) variables: Vec<String>,
+    pub(crate) created_at: u64,
+    pub(crate) updated_at: u64,
+}
+
+pub(crate) struct PromptLibrary {
+    path: PathBuf,
+    prompts: Mutex<Vec<SavedPrompt>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("valid placeholder regex")
+    })
+}
+
+impl PromptLibrary {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let prompts = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            prompts: Mutex::new(prompts),
+        }
+    }
+
+    fn persist(&self, prompts: &[SavedPrompt]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(prompts).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+/// Placeholder names in `template`, deduplicated in first-use order.
+pub(crate) fn template_variables(template: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    placeholder_regex()
+        .captures_iter(template)
+        .filter_map(|captures| captures.get(1).map(|name| name.as_str().to_string()))
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Replaces every `{{name}}` in `template`. Fails listing all placeholders
+/// without a value rather than sending a half-filled prompt.
+pub(crate) fn render_template(
+    template: &str,
+    values: &HashMap<String, String>,
+) -> Result<String, String> {
+    let missing: Vec<String> = template_variables(template)
+        .into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing values for {}",
+            missing
+                .iter()
+                .map(|name| format!("{{{{{name}}}}}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(placeholder_regex()
+        .replace_all(template, |captures: &regex::Captures| {
+            values[&captures[1]].clone()
+        })
+        .into_owned())
+}
+
+pub(crate) fn prompt_list_core(library: &PromptLibrary) -> Vec<SavedPrompt> {
+    let mut prompts = library
+        .prompts
+        .lock()
+        .map(|prompts| prompts.clone())
+        .unwrap_or_default();
+    prompts.sort_by_key(|prompt| prompt.name.to_lowercase());
+    prompts
+}
+
+/// Creates a prompt, or replaces the one with `id`.
+pub(crate) fn prompt_save_core(
+    library: &PromptLibrary,
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    template: String,
+) -> Result<SavedPrompt, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Prompt name must not be empty".to_string());
+    }
+    if template.trim().is_empty() {
+        return Err("Prompt template must not be empty".to_string());
+    }
+    if template.len() > MAX_TEMPLATE_BYTES {
+        return Err(format!(
+            "Prompt templates are limited to {MAX_TEMPLATE_BYTES} bytes"
+        ));
+    }
+    let description = description
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let variables = template_variables(&template);
+    let mut prompts = library
+        .prompts
+        .lock()
+        .map_err(|_| "prompt library is poisoned".to_string())?;
+    let now = now_ms();
+    let saved = match id {
+        Some(id) => {
+            let existing = prompts
+                .iter_mut()
+                .find(|prompt| prompt.id == id)
+                .ok_or_else(|| format!("Prompt {id} not found"))?;
+            existing.name = name;
+            existing.description = description;
+            existing.template = template;
+            existing.variables = variables;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            if prompts.len() >= MAX_PROMPTS {
+                return Err(format!(
+                    "The prompt library holds at most {MAX_PROMPTS} prompts"
+                ));
+            }
+            let prompt = SavedPrompt {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                description,
+                template,
+                variables,
+                created_at: now,
+                updated_at: now,
+            };
+            prompts.push(prompt.clone());
+            prompt
+        }
+    };
+    library.persist(&prompts)?;
+    Ok(saved)
+}
+
+pub(crate) fn prompt_delete_core(library: &PromptLibrary, id: &str) -> Result<(), String> {
+    let mut prompts = library
+        .prompts
+        .lock()
+        .map_err(|_| "prompt library is poisoned".to_string())?;
+    let before = prompts.len();
+    prompts.retain(|prompt| prompt.id != id);
+    if prompts.len() == before {
+        return Err(format!("Prompt {id} not found"));
+    }
+    library.persist(&prompts)
+}
+
+/// Paths from `git status --porcelain`, one per line, new path for renames.
+fn changed_files(status: &str) -> String {
+    status
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| path.rsplit(" -> ").next().unwrap_or(path))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fills the prompt for `workspace_id`. Built-in values are `workspace`,
+/// `path`, `branch` and `files` (changed files); `values` override them.
+pub(crate) async fn prompt_render_core(
+    library: &PromptLibrary,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    id: String,
+    workspace_id: String,
+    values: HashMap<String, String>,
+) -> Result<String, String> {
+    let template = library
+        .prompts
+        .lock()
+        .map_err(|_| "prompt library is poisoned".to_string())?
+        .iter()
+        .find(|prompt| prompt.id == id)
+        .map(|prompt| prompt.template.clone())
+        .ok_or_else(|| format!("Prompt {id} not found"))?;
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let needed = template_variables(&template);
+    let wants = |name: &str| needed.iter().any(|var| var == name) && !values.contains_key(name);
+    let repo_path = PathBuf::from(&entry.path);
+
+    let mut resolved = HashMap::new();
+    resolved.insert("workspace".to_string(), entry.name.clone());
+    resolved.insert("path".to_string(), entry.path.clone());
+    if wants("branch") {
+        let branch = run_git_command(&repo_path, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+        resolved.insert("branch".to_string(), branch);
+    }
+    if wants("files") {
+        let status = run_git_command_bytes(&repo_path, &["status", "--porcelain"]).await?;
+        resolved.insert(
+            "files".to_string(),
+            changed_files(&String::from_utf8_lossy(&status)),
+        );
+    }
+    resolved.extend(values);
+    render_template(&template, &resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fills_placeholders_and_reports_missing() {
+        let template = "Write tests for {{ files }} on {{branch}}; keep {{branch}} green.";
+        assert_eq!(template_variables(template), vec!["files", "branch"]);
+
+        let values = HashMap::from([
+            ("files".to_string(), "src/a.rs".to_string()),
+            ("branch".to_string(), "main".to_string()),
+        ]);
+        assert_eq!(
+            render_template(template, &values).expect("render"),
+            "Write tests for src/a.rs on main; keep main green."
+        );
+
+        let err = render_template(template, &HashMap::new()).expect_err("missing");
+        assert_eq!(err, "Missing values for {{files}}, {{branch}}");
+    }
+
+    #[test]
+    fn save_updates_in_place_and_persists() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-prompt-library-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let path = dir.join("prompt-library.json");
+        let library = PromptLibrary::load(path.clone());
+
+        let created = prompt_save_core(
+            &library,
+            None,
+            "Tests".to_string(),
+            None,
+            "Write tests for {{files}}".to_string(),
+        )
+        .expect("create");
+        let updated = prompt_save_core(
+            &library,
+            Some(created.id.clone()),
+            "Tests".to_string(),
+            Some("changed files".to_string()),
+            "Write tests for {{files}} on {{branch}}".to_string(),
+        )
+        .expect("update");
+        assert_eq!(updated.variables, vec!["files", "branch"]);
+
+        let reloaded = PromptLibrary::load(path);
+        assert_eq!(prompt_list_core(&reloaded), vec![updated]);
+        prompt_delete_core(&reloaded, &created.id).expect("delete");
+        assert!(prompt_list_core(&reloaded).is_empty());
+        assert!(prompt_delete_core(&reloaded, &created.id).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn changed_files_uses_new_path_for_renames() {
+        let status = " M src/lib.rs\nR  old.rs -> new.rs\n?? notes.md";
+        assert_eq!(changed_files(status), "src/lib.rs\nnew.rs\nnotes.md");
+    }
+}
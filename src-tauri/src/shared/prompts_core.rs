@@ -2,7 +2,7 @@ use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 use tokio::task;
 
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
@@ -270,12 +270,12 @@ fn discover_prompts_in(dir: &Path, scope: Option<&str>) -> Vec<CustomPromptEntry
 }
 
 pub(crate) async fn prompts_list_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
 ) -> Result<Vec<CustomPromptEntry>, String> {
     let (workspace_dir, global_dir) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces.get(&workspace_id).cloned();
         let workspace_dir = entry
             .as_ref()
@@ -303,12 +303,12 @@ pub(crate) async fn prompts_list_core(
 }
 
 pub(crate) async fn prompts_workspace_dir_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
 ) -> Result<String, String> {
     let dir = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         workspace_prompts_dir(settings_path, &entry)?
     };
@@ -317,10 +317,10 @@ pub(crate) async fn prompts_workspace_dir_core(
 }
 
 pub(crate) async fn prompts_global_dir_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<String, String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = require_workspace_entry(&workspaces, &workspace_id)?;
     let dir = default_prompts_dir_for_workspace(&workspaces, &entry)
         .ok_or("Unable to resolve CODEX_HOME".to_string())?;
@@ -329,7 +329,7 @@ pub(crate) async fn prompts_global_dir_core(
 }
 
 pub(crate) async fn prompts_create_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
     scope: String,
@@ -340,7 +340,7 @@ pub(crate) async fn prompts_create_core(
 ) -> Result<CustomPromptEntry, String> {
     let name = sanitize_prompt_name(&name)?;
     let (target_dir, resolved_scope) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         match scope.as_str() {
             "workspace" => {
@@ -375,7 +375,7 @@ pub(crate) async fn prompts_create_core(
 }
 
 pub(crate) async fn prompts_update_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
     path: String,
@@ -390,7 +390,7 @@ pub(crate) async fn prompts_update_core(
         return Err("Prompt not found.".to_string());
     }
     {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         let roots = prompt_roots_for_workspace(settings_path, &workspaces, &entry)?;
         ensure_path_within_roots(&target_path, &roots)?;
@@ -408,7 +408,7 @@ pub(crate) async fn prompts_update_core(
         fs::remove_file(&target_path).map_err(|err| err.to_string())?;
     }
     let scope = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         let workspace_dir = workspace_prompts_dir(settings_path, &entry)?;
         if next_path.starts_with(&workspace_dir) {
@@ -428,7 +428,7 @@ pub(crate) async fn prompts_update_core(
 }
 
 pub(crate) async fn prompts_delete_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
     path: String,
@@ -438,7 +438,7 @@ pub(crate) async fn prompts_delete_core(
         return Ok(());
     }
     {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         let roots = prompt_roots_for_workspace(settings_path, &workspaces, &entry)?;
         ensure_path_within_roots(&target, &roots)?;
@@ -447,7 +447,7 @@ pub(crate) async fn prompts_delete_core(
 }
 
 pub(crate) async fn prompts_move_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     settings_path: &Path,
     workspace_id: String,
     path: String,
@@ -458,7 +458,7 @@ pub(crate) async fn prompts_move_core(
         return Err("Prompt not found.".to_string());
     }
     let roots = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         prompt_roots_for_workspace(settings_path, &workspaces, &entry)?
     };
@@ -468,7 +468,7 @@ pub(crate) async fn prompts_move_core(
         .and_then(|value| value.to_str())
         .ok_or("Invalid prompt path.".to_string())?;
     let target_dir = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         match scope.as_str() {
             "workspace" => workspace_prompts_dir(settings_path, &entry)?,
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+use crate::shared::process_core::shell_command;
+use crate::types::{ReleaseArtifact, ReleaseManifest, WorkspaceEntry};
+use crate::utils::normalize_git_path;
+
+const RELEASE_ARTIFACTS_DIR: &str = "release-artifacts";
+const DEFAULT_ARTIFACT_PATTERNS: &[&str] = &[
+    "src-tauri/target/release/bundle/**",
+    "target/release/bundle/**",
+];
+
+/// Resolves `task` to a configured launch script by id or label.
+pub(crate) fn resolve_launch_script(entry: &WorkspaceEntry, task: &str) -> Result<String, String> {
+    let task = task.trim();
+    if task.is_empty() {
+        return Err("Task is required.".to_string());
+    }
+    entry
+        .settings
+        .launch_scripts
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find(|script| {
+            script.id == task
+                || script
+                    .label
+                    .as_deref()
+                    .is_some_and(|label| label.trim().eq_ignore_ascii_case(task))
+        })
+        .map(|script| script.script.trim().to_string())
+        .filter(|script| !script.is_empty())
+        .ok_or_else(|| format!("No launch script named `{task}` is configured for this workspace."))
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(name, ".git" | "node_modules" | RELEASE_ARTIFACTS_DIR)
+}
+
+fn collect_artifact_paths(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut builder = OverrideBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add(pattern)
+            .map_err(|err| format!("Invalid artifact pattern `{pattern}`: {err}"))?;
+    }
+    let overrides = builder.build().map_err(|err| err.to_string())?;
+    let walker = WalkBuilder::new(root)
+        // Build outputs are normally gitignored, so skip the standard filters.
+        .standard_filters(false)
+        .follow_links(false)
+        .overrides(overrides)
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+    let mut paths = walker
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|err| err.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|err| err.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Copies matching artifacts into `output_dir`, preserving their
+/// workspace-relative layout, and returns their checksums.
+fn stage_artifacts(
+    root: &Path,
+    output_dir: &Path,
+    patterns: &[String],
+) -> Result<Vec<ReleaseArtifact>, String> {
+    let mut artifacts = Vec::new();
+    for source in collect_artifact_paths(root, patterns)? {
+        let relative = source.strip_prefix(root).map_err(|err| err.to_string())?;
+        let destination = output_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let size = fs::copy(&source, &destination)
+            .map_err(|err| format!("Failed to copy {}: {err}", source.display()))?;
+        artifacts.push(ReleaseArtifact {
+            path: normalize_git_path(&relative.to_string_lossy()),
+            size,
+            sha256: sha256_file(&destination)?,
+        });
+    }
+    Ok(artifacts)
+}
+
+pub(crate) async fn package_release_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    task: String,
+) -> Result<ReleaseManifest, String> {
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let script = resolve_launch_script(&entry, &task)?;
+    let root = PathBuf::from(&entry.path);
+    let now = chrono::Utc::now();
+    let output_dir = root
+        .join(RELEASE_ARTIFACTS_DIR)
+        .join(now.format("%Y%m%d-%H%M%S").to_string());
+    fs::create_dir_all(&output_dir)
+        .map_err(|err| format!("Failed to create {}: {err}", output_dir.display()))?;
+
+    let output = shell_command(&script)
+        .current_dir(&root)
+        .output()
+        .await
+        .map_err(|err| format!("Failed to run build task `{task}`: {err}"))?;
+    let log_path = output_dir.join("build.log");
+    let mut log = output.stdout;
+    log.extend_from_slice(&output.stderr);
+    fs::write(&log_path, log).map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Build task `{task}` failed. See {}.",
+            log_path.display()
+        ));
+    }
+
+    let patterns = entry
+        .settings
+        .release_artifacts
+        .clone()
+        .filter(|patterns| !patterns.is_empty())
+        .unwrap_or_else(|| {
+            DEFAULT_ARTIFACT_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect()
+        });
+    let staging_root = root.clone();
+    let staging_dir = output_dir.clone();
+    let artifacts = tokio::task::spawn_blocking(move || {
+        stage_artifacts(&staging_root, &staging_dir, &patterns)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    if artifacts.is_empty() {
+        return Err(format!(
+            "Build task `{task}` finished but no artifacts matched. See {}.",
+            log_path.display()
+        ));
+    }
+
+    let manifest = ReleaseManifest {
+        task,
+        created_at: now.timestamp_millis(),
+        output_dir: output_dir.to_string_lossy().to_string(),
+        artifacts,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|err| err.to_string())?;
+    fs::write(output_dir.join("manifest.json"), manifest_json).map_err(|err| err.to_string())?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LaunchScriptEntry, WorkspaceKind, WorkspaceSettings};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn package_release_runs_task_and_writes_manifest() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("codex-monitor-release-{nonce}"));
+        fs::create_dir_all(&root).expect("create workspace");
+        let entry = WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "ws".to_string(),
+            path: root.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                launch_scripts: Some(vec![LaunchScriptEntry {
+                    id: "build".to_string(),
+                    script: "mkdir -p dist/bin && printf hello > dist/bin/app".to_string(),
+                    icon: "play".to_string(),
+                    label: Some("Build".to_string()),
+                }]),
+                release_artifacts: Some(vec!["dist/bin/*".to_string()]),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let workspaces = RwLock::new(HashMap::from([("ws-1".to_string(), entry)]));
+
+        let manifest = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(package_release_core(
+                &workspaces,
+                "ws-1".to_string(),
+                "build".to_string(),
+            ))
+            .expect("package release");
+
+        assert_eq!(manifest.artifacts.len(), 1);
+        assert_eq!(manifest.artifacts[0].path, "dist/bin/app");
+        assert_eq!(manifest.artifacts[0].size, 5);
+        assert_eq!(
+            manifest.artifacts[0].sha256,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        let output_dir = PathBuf::from(&manifest.output_dir);
+        assert!(output_dir.join("manifest.json").is_file());
+        assert!(output_dir.join("dist/bin/app").is_file());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::shared::codex_core;
+use crate::types::WorkspaceEntry;
+
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(15);
+const MAX_SCHEDULED_MESSAGES: usize = 500;
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+const MAX_ATTEMPTS: u32 = 10;
+const RETRY_BASE_MS: u64 = 60 * 1000;
+const RETRY_MAX_MS: u64 = 30 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScheduledMessageStatus {
+    Pending,
+    /// Gave up after `MAX_ATTEMPTS`; kept until canceled so the error is visible.
+    Failed,
+}
+
+/// A message to send to a thread at `send_at` (ms since the epoch).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScheduledMessage {
+    pub(crate) id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) text: String,
+    pub(crate) send_at: u64,
+    pub(crate) created_at: u64,
+    pub(crate) status: ScheduledMessageStatus,
+    #[serde(default)]
+    pub(crate) attempts: u32,
+    /// When the next delivery attempt is due; `send_at` until the first failure.
+    pub(crate) next_attempt_at: u64,
+    #[serde(default)]
+    pub(crate) last_error: Option<String>,
+}
+
+pub(crate) struct ScheduledMessageStore {
+    path: PathBuf,
+    messages: Mutex<Vec<ScheduledMessage>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn retry_delay_ms(attempts: u32) -> u64 {
+    RETRY_BASE_MS
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(RETRY_MAX_MS)
+}
+
+impl ScheduledMessageStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let messages = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            messages: Mutex::new(messages),
+        }
+    }
+
+    fn persist(&self, messages: &[ScheduledMessage]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(messages).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut Vec<ScheduledMessage>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut messages = self
+            .messages
+            .lock()
+            .map_err(|_| "scheduled messages are poisoned".to_string())?;
+        let result = apply(&mut messages)?;
+        self.persist(&messages)?;
+        Ok(result)
+    }
+
+    /// Pending messages whose next attempt is due at `now`, oldest first.
+    fn due(&self, now: u64) -> Vec<ScheduledMessage> {
+        let Ok(messages) = self.messages.lock() else {
+            return Vec::new();
+        };
+        let mut due: Vec<ScheduledMessage> = messages
+            .iter()
+            .filter(|message| {
+                message.status == ScheduledMessageStatus::Pending && message.next_attempt_at <= now
+            })
+            .cloned()
+            .collect();
+        due.sort_by_key(|message| message.next_attempt_at);
+        due
+    }
+
+    /// Drops a delivered message. A no-op if it was canceled meanwhile.
+    fn complete(&self, id: &str) -> Result<(), String> {
+        self.update(|messages| {
+            messages.retain(|message| message.id != id);
+            Ok(())
+        })
+    }
+
+    /// Records a failed attempt and schedules a retry, or marks the message
+    /// failed once `retry` is false or the attempts run out.
+    fn record_failure(
+        &self,
+        id: &str,
+        error: String,
+        retry: bool,
+        now: u64,
+    ) -> Result<Option<ScheduledMessage>, String> {
+        self.update(|messages| {
+            let Some(message) = messages.iter_mut().find(|message| message.id == id) else {
+                return Ok(None);
+            };
+            message.attempts += 1;
+            message.last_error = Some(error);
+            if retry && message.attempts < MAX_ATTEMPTS {
+                message.next_attempt_at = now + retry_delay_ms(message.attempts);
+            } else {
+                message.status = ScheduledMessageStatus::Failed;
+            }
+            Ok(Some(message.clone()))
+        })
+    }
+}
+
+pub(crate) fn schedule_message_core(
+    store: &ScheduledMessageStore,
+    workspace_id: String,
+    thread_id: String,
+    text: String,
+    send_at: u64,
+) -> Result<ScheduledMessage, String> {
+    if workspace_id.trim().is_empty() || thread_id.trim().is_empty() {
+        return Err("workspaceId and threadId are required".to_string());
+    }
+    if text.trim().is_empty() {
+        return Err("Scheduled message must not be empty".to_string());
+    }
+    if text.len() > MAX_MESSAGE_BYTES {
+        return Err(format!(
+            "Scheduled messages are limited to {MAX_MESSAGE_BYTES} bytes"
+        ));
+    }
+    store.update(|messages| {
+        if messages.len() >= MAX_SCHEDULED_MESSAGES {
+            return Err(format!(
+                "At most {MAX_SCHEDULED_MESSAGES} messages can be scheduled"
+            ));
+        }
+        let message = ScheduledMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            workspace_id,
+            thread_id,
+            text,
+            send_at,
+            created_at: now_ms(),
+            status: ScheduledMessageStatus::Pending,
+            attempts: 0,
+            next_attempt_at: send_at,
+            last_error: None,
+        };
+        messages.push(message.clone());
+        Ok(message)
+    })
+}
+
+/// Scheduled messages ordered by send time, optionally for one workspace.
+pub(crate) fn list_scheduled_messages_core(
+    store: &ScheduledMessageStore,
+    workspace_id: Option<&str>,
+) -> Vec<ScheduledMessage> {
+    let mut messages: Vec<ScheduledMessage> = store
+        .messages
+        .lock()
+        .map(|messages| {
+            messages
+                .iter()
+                .filter(|message| workspace_id.is_none_or(|id| message.workspace_id == id))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    messages.sort_by_key(|message| message.send_at);
+    messages
+}
+
+pub(crate) fn cancel_scheduled_message_core(
+    store: &ScheduledMessageStore,
+    id: &str,
+) -> Result<(), String> {
+    store.update(|messages| {
+        let before = messages.len();
+        messages.retain(|message| message.id != id);
+        if messages.len() == before {
+            return Err(format!("Scheduled message {id} not found"));
+        }
+        Ok(())
+    })
+}
+
+fn emit_schedule_event<E: EventSink>(
+    event_sink: &E,
+    message: &ScheduledMessage,
+    method: &str,
+    extra: Value,
+) {
+    let mut params = json!({
+        "scheduleId": message.id,
+        "threadId": message.thread_id,
+    });
+    if let (Some(params), Value::Object(extra)) = (params.as_object_mut(), extra) {
+        params.extend(extra);
+    }
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: message.workspace_id.clone(),
+        message: json!({ "method": method, "params": params }),
+    });
+}
+
+/// Connects the workspace if needed, loads the thread and starts the turn.
+async fn deliver<C, Fut>(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    connect: &C,
+    message: &ScheduledMessage,
+) -> Result<Value, String>
+where
+    C: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    if !sessions.read().await.contains_key(&message.workspace_id) {
+        connect(message.workspace_id.clone()).await?;
+    }
+    codex_core::resume_thread_core(
+        sessions,
+        message.workspace_id.clone(),
+        message.thread_id.clone(),
+    )
+    .await?;
+    codex_core::send_user_message_core(
+        sessions,
+        workspaces,
+        message.workspace_id.clone(),
+        message.thread_id.clone(),
+        message.text.clone(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Sends due messages one at a time. A message whose workspace cannot be
+/// connected or whose turn fails is retried with backoff; progress arrives as
+/// `schedule/sent`, `schedule/retry` and `schedule/failed` events.
+pub(crate) async fn run_message_scheduler<E, C, Fut>(
+    store: &ScheduledMessageStore,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    event_sink: E,
+    connect: C,
+) where
+    E: EventSink,
+    C: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let mut ticker = tokio::time::interval(SCHEDULER_INTERVAL);
+    loop {
+        ticker.tick().await;
+        for message in store.due(now_ms()) {
+            let known = workspaces.read().await.contains_key(&message.workspace_id);
+            let result = if known {
+                deliver(sessions, workspaces, &connect, &message).await
+            } else {
+                Err("Workspace no longer exists".to_string())
+            };
+            match result {
+                Ok(response) => {
+                    if let Err(error) = store.complete(&message.id) {
+                        eprintln!("scheduler: failed to persist scheduled messages: {error}");
+                    }
+                    let turn_id = codex_core::response_object_id(&response, "turn");
+                    emit_schedule_event(
+                        &event_sink,
+                        &message,
+                        "schedule/sent",
+                        json!({ "turnId": turn_id }),
+                    );
+                }
+                Err(error) => {
+                    match store.record_failure(&message.id, error.clone(), known, now_ms()) {
+                        Ok(Some(updated)) if updated.status == ScheduledMessageStatus::Pending => {
+                            emit_schedule_event(
+                                &event_sink,
+                                &updated,
+                                "schedule/retry",
+                                json!({
+                                    "attempts": updated.attempts,
+                                    "nextAttemptAt": updated.next_attempt_at,
+                                    "error": error,
+                                }),
+                            );
+                        }
+                        Ok(Some(updated)) => {
+                            emit_schedule_event(
+                                &event_sink,
+                                &updated,
+                                "schedule/failed",
+                                json!({ "attempts": updated.attempts, "error": error }),
+                            );
+                        }
+                        Ok(None) => {}
+                        Err(error) => {
+                            eprintln!("scheduler: failed to persist scheduled messages: {error}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (PathBuf, ScheduledMessageStore) {
+        let path = std::env::temp_dir()
+            .join(format!("codex-monitor-scheduled-{}", uuid::Uuid::new_v4()))
+            .join("scheduled-messages.json");
+        (path.clone(), ScheduledMessageStore::load(path))
+    }
+
+    #[test]
+    fn messages_persist_and_come_due_in_order() {
+        let (path, store) = temp_store();
+        let later = schedule_message_core(&store, "ws".into(), "t1".into(), "later".into(), 2_000)
+            .expect("schedule");
+        schedule_message_core(&store, "ws".into(), "t2".into(), "sooner".into(), 1_000)
+            .expect("schedule");
+        assert!(schedule_message_core(&store, "ws".into(), "t3".into(), "  ".into(), 0).is_err());
+
+        let reloaded = ScheduledMessageStore::load(path.clone());
+        let texts: Vec<String> = list_scheduled_messages_core(&reloaded, Some("ws"))
+            .into_iter()
+            .map(|message| message.text)
+            .collect();
+        assert_eq!(texts, vec!["sooner", "later"]);
+        assert!(list_scheduled_messages_core(&reloaded, Some("other")).is_empty());
+
+        assert_eq!(reloaded.due(1_500).len(), 1);
+        assert_eq!(reloaded.due(2_000).len(), 2);
+        cancel_scheduled_message_core(&reloaded, &later.id).expect("cancel");
+        assert!(cancel_scheduled_message_core(&reloaded, &later.id).is_err());
+        assert_eq!(reloaded.due(2_000).len(), 1);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn failures_back_off_then_give_up() {
+        let (path, store) = temp_store();
+        let message = schedule_message_core(&store, "ws".into(), "t".into(), "hi".into(), 0)
+            .expect("schedule");
+
+        let retried = store
+            .record_failure(&message.id, "not connected".into(), true, 10_000)
+            .expect("record")
+            .expect("message");
+        assert_eq!(retried.status, ScheduledMessageStatus::Pending);
+        assert_eq!(retried.next_attempt_at, 10_000 + RETRY_BASE_MS);
+        assert!(store.due(10_000).is_empty());
+
+        let retried = store
+            .record_failure(&message.id, "not connected".into(), true, 10_000)
+            .expect("record")
+            .expect("message");
+        assert_eq!(retried.next_attempt_at, 10_000 + 2 * RETRY_BASE_MS);
+        assert_eq!(retry_delay_ms(MAX_ATTEMPTS), RETRY_MAX_MS);
+
+        let failed = store
+            .record_failure(&message.id, "gone".into(), false, 10_000)
+            .expect("record")
+            .expect("message");
+        assert_eq!(failed.status, ScheduledMessageStatus::Failed);
+        assert_eq!(failed.last_error.as_deref(), Some("gone"));
+        assert!(store.due(u64::MAX).is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
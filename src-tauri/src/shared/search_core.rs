@@ -0,0 +1,395 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+use crate::shared::thread_export_core::{self, TranscriptEntry};
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 200;
+/// Command output and diffs are indexed up to this many bytes per item.
+const MAX_INDEXED_BYTES: usize = 32 * 1024;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS items (
+    id INTEGER PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    turn_id TEXT,
+    item_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    body TEXT NOT NULL,
+    indexed_at INTEGER NOT NULL,
+    UNIQUE (workspace_id, thread_id, item_id)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+    body,
+    content = 'items',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+CREATE TRIGGER IF NOT EXISTS items_ai AFTER INSERT ON items BEGIN
+    INSERT INTO items_fts (rowid, body) VALUES (new.id, new.body);
+END;
+CREATE TRIGGER IF NOT EXISTS items_ad AFTER DELETE ON items BEGIN
+    INSERT INTO items_fts (items_fts, rowid, body) VALUES ('delete', old.id, old.body);
+END;
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchHit {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+    pub(crate) item_id: String,
+    /// `user`, `agent`, `command`, `fileChange`, `toolCall` or `webSearch`.
+    pub(crate) kind: String,
+    /// Matching text with the matched terms wrapped in `**`.
+    pub(crate) snippet: String,
+    pub(crate) indexed_at: u64,
+}
+
+/// Full-text index of completed thread items, kept in `search-index.sqlite`.
+/// Items are ingested from `item/completed` events, so threads only become
+/// searchable from the point this index first saw them.
+pub(crate) struct SearchIndex {
+    path: PathBuf,
+    connection: Mutex<Option<Connection>>,
+}
+
+/// One indexable item pulled out of an `item/completed` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexedItem {
+    thread_id: String,
+    turn_id: Option<String>,
+    item_id: String,
+    kind: &'static str,
+    body: String,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .filter(|value| !value.is_empty())
+}
+
+fn truncate_bytes(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+fn entry_text(entry: &TranscriptEntry) -> (&'static str, String) {
+    match entry {
+        TranscriptEntry::User { text, .. } => ("user", text.clone()),
+        TranscriptEntry::Agent { text, .. } => ("agent", text.clone()),
+        TranscriptEntry::Command {
+            command, output, ..
+        } => (
+            "command",
+            format!("{command}\n{}", truncate_bytes(output, MAX_INDEXED_BYTES)),
+        ),
+        TranscriptEntry::FileChange { changes, .. } => {
+            let mut body = changes
+                .iter()
+                .map(|change| change.path.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            for diff in changes.iter().filter_map(|change| change.diff.as_deref()) {
+                body.push('\n');
+                body.push_str(diff);
+            }
+            (
+                "fileChange",
+                truncate_bytes(&body, MAX_INDEXED_BYTES).to_string(),
+            )
+        }
+        TranscriptEntry::ToolCall {
+            server,
+            tool,
+            arguments,
+            ..
+        } => {
+            let arguments = arguments.as_ref().map(Value::to_string).unwrap_or_default();
+            ("toolCall", format!("{server} {tool}\n{arguments}"))
+        }
+        TranscriptEntry::WebSearch { query, .. } => ("webSearch", query.clone()),
+    }
+}
+
+fn indexed_item(event: &AppServerEvent) -> Option<IndexedItem> {
+    if event.message.get("method").and_then(Value::as_str) != Some("item/completed") {
+        return None;
+    }
+    let params = event.message.get("params")?;
+    let item = params.get("item")?;
+    let thread_id = str_field(params, &["threadId", "thread_id"])?;
+    let item_id = str_field(item, &["id"])?;
+    let turn_id = str_field(params, &["turnId", "turn_id"]);
+    let entry = thread_export_core::transcript_entry(turn_id.as_deref().unwrap_or_default(), item)?;
+    let (kind, body) = entry_text(&entry);
+    if body.trim().is_empty() {
+        return None;
+    }
+    Some(IndexedItem {
+        thread_id,
+        turn_id,
+        item_id,
+        kind,
+        body,
+    })
+}
+
+/// Quotes every word so user input is matched literally (all words must
+/// appear) instead of being parsed as FTS5 query syntax.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+impl SearchIndex {
+    /// Opens lazily on first use so a damaged index never blocks startup.
+    pub(crate) fn load(path: PathBuf) -> Self {
+        Self {
+            path,
+            connection: Mutex::new(None),
+        }
+    }
+
+    fn with_connection<T>(
+        &self,
+        run: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut guard = self
+            .connection
+            .lock()
+            .map_err(|_| "search index lock poisoned".to_string())?;
+        if guard.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let connection = Connection::open(&self.path).map_err(|e| e.to_string())?;
+            connection
+                .execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+                .and_then(|_| connection.execute_batch(SCHEMA))
+                .map_err(|e| e.to_string())?;
+            *guard = Some(connection);
+        }
+        let connection = guard.as_mut().expect("search index connection");
+        run(connection).map_err(|e| e.to_string())
+    }
+
+    /// Indexes the item of an `item/completed` event; other events are
+    /// ignored. A re-sent item replaces its earlier text.
+    pub(crate) fn record(&self, event: &AppServerEvent) {
+        let Some(item) = indexed_item(event) else {
+            return;
+        };
+        let result = self.with_connection(|connection| {
+            let tx = connection.transaction()?;
+            tx.execute(
+                "DELETE FROM items WHERE workspace_id = ?1 AND thread_id = ?2 AND item_id = ?3",
+                params![event.workspace_id, item.thread_id, item.item_id],
+            )?;
+            tx.execute(
+                "INSERT INTO items (workspace_id, thread_id, turn_id, item_id, kind, body, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    event.workspace_id,
+                    item.thread_id,
+                    item.turn_id,
+                    item.item_id,
+                    item.kind,
+                    item.body,
+                    now_ms() as i64,
+                ],
+            )?;
+            tx.commit()
+        });
+        if let Err(error) = result {
+            eprintln!("search index: failed to record item: {error}");
+        }
+    }
+
+    /// Best-ranked hits, at most one per turn, optionally within one workspace.
+    pub(crate) fn search(
+        &self,
+        workspace_id: Option<&str>,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<SearchHit>, String> {
+        let Some(expression) = match_expression(query) else {
+            return Err("Search query must not be empty".to_string());
+        };
+        let limit = limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+        let rows = self.with_connection(|connection| {
+            let mut statement = connection.prepare(
+                "SELECT i.workspace_id, i.thread_id, i.turn_id, i.item_id, i.kind, i.indexed_at,
+                        snippet(items_fts, 0, '**', '**', '…', 16)
+                 FROM items_fts JOIN items i ON i.id = items_fts.rowid
+                 WHERE items_fts MATCH ?1 AND (?2 IS NULL OR i.workspace_id = ?2)
+                 ORDER BY items_fts.rank
+                 LIMIT ?3",
+            )?;
+            let rows = statement.query_map(
+                params![expression, workspace_id, (limit * 5) as i64],
+                |row| {
+                    Ok(SearchHit {
+                        workspace_id: row.get(0)?,
+                        thread_id: row.get(1)?,
+                        turn_id: row.get(2)?,
+                        item_id: row.get(3)?,
+                        kind: row.get(4)?,
+                        indexed_at: row.get::<_, i64>(5)?.max(0) as u64,
+                        snippet: row.get(6)?,
+                    })
+                },
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+        let mut seen = HashSet::new();
+        Ok(rows
+            .into_iter()
+            .filter(|hit| {
+                seen.insert((
+                    hit.workspace_id.clone(),
+                    hit.thread_id.clone(),
+                    hit.turn_id.clone().unwrap_or_else(|| hit.item_id.clone()),
+                ))
+            })
+            .take(limit)
+            .collect())
+    }
+
+    #[cfg(test)]
+    fn item_count(&self) -> Result<u64, String> {
+        self.with_connection(|connection| {
+            connection
+                .query_row("SELECT COUNT(*) FROM items", [], |row| row.get::<_, i64>(0))
+                .map(|count| count.max(0) as u64)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn completed(
+        workspace_id: &str,
+        thread_id: &str,
+        turn_id: &str,
+        item: Value,
+    ) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message: json!({
+                "method": "item/completed",
+                "params": { "threadId": thread_id, "turnId": turn_id, "item": item }
+            }),
+        }
+    }
+
+    fn temp_index() -> (SearchIndex, PathBuf) {
+        let nonce = uuid::Uuid::new_v4();
+        let dir = std::env::temp_dir().join(format!("codex-monitor-search-{nonce}"));
+        (SearchIndex::load(dir.join("search-index.sqlite")), dir)
+    }
+
+    #[test]
+    fn finds_items_by_words_and_workspace() {
+        let (index, dir) = temp_index();
+        index.record(&completed(
+            "ws-1",
+            "thread-1",
+            "turn-1",
+            json!({
+                "type": "fileChange",
+                "id": "f1",
+                "changes": [{ "path": "src/auth/session.rs", "diff": "+fn refresh_token()" }]
+            }),
+        ));
+        index.record(&completed(
+            "ws-1",
+            "thread-1",
+            "turn-1",
+            json!({ "type": "agentMessage", "id": "a1", "text": "Updated the auth modules." }),
+        ));
+        index.record(&completed(
+            "ws-2",
+            "thread-2",
+            "turn-2",
+            json!({ "type": "userMessage", "id": "u1", "content": [{ "type": "text", "text": "Document the auth module" }] }),
+        ));
+        index.record(&completed(
+            "ws-2",
+            "thread-2",
+            "turn-2",
+            json!({ "type": "reasoning", "id": "r1", "summary": ["auth module"] }),
+        ));
+
+        let hits = index.search(None, "auth module", None).unwrap();
+        assert_eq!(hits.len(), 2, "one hit per turn: {hits:?}");
+        assert!(hits.iter().all(|hit| hit.snippet.contains("**")));
+
+        let hits = index.search(Some("ws-2"), "auth", None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].thread_id, "thread-2");
+        assert_eq!(hits[0].kind, "user");
+
+        let hits = index.search(Some("ws-1"), "session.rs", None).unwrap();
+        assert_eq!(hits[0].kind, "fileChange");
+        assert!(index.search(None, "   ", None).is_err());
+        // Query syntax is treated as literal text.
+        assert!(index.search(None, "auth OR \"NEAR(", None).is_ok());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn re_recorded_items_replace_their_text() {
+        let (index, dir) = temp_index();
+        let message = |text: &str| {
+            completed(
+                "ws-1",
+                "thread-1",
+                "turn-1",
+                json!({ "type": "agentMessage", "id": "a1", "text": text }),
+            )
+        };
+        index.record(&message("first draft"));
+        index.record(&message("final answer"));
+        assert_eq!(index.item_count(), Ok(1));
+        assert!(index.search(None, "draft", None).unwrap().is_empty());
+        assert_eq!(index.search(None, "answer", None).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
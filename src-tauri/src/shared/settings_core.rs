@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use tokio::sync::Mutex;
 
+use crate::backend::services::BackendServices;
 use crate::codex::config as codex_config;
 use crate::storage::write_settings;
 use crate::types::AppSettings;
@@ -44,6 +45,7 @@ pub(crate) async fn update_app_settings_core(
     mut settings: AppSettings,
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
+    services: &BackendServices,
 ) -> Result<AppSettings, String> {
     settings.global_worktrees_folder = settings
         .global_worktrees_folder
@@ -53,7 +55,8 @@ pub(crate) async fn update_app_settings_core(
     let _ = codex_config::write_unified_exec_enabled(settings.unified_exec_enabled);
     let _ = codex_config::write_apps_enabled(settings.experimental_apps_enabled);
     let _ = codex_config::write_personality(settings.personality.as_str());
-    write_settings(settings_path, &settings)?;
+    write_settings(settings_path, &settings).await?;
+    services.apply_settings(&settings);
     let mut current = app_settings.lock().await;
     *current = settings.clone();
     Ok(settings)
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const TEST_PROMPT: &str = "Run the project's test suite, then fix any failing tests. \
+Summarize what failed and what you changed.";
+
+/// Turn parameters a slash command may rewrite before `turn/start`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TurnRequest {
+    pub(crate) text: String,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    pub(crate) access_mode: Option<String>,
+}
+
+/// What to do with a message after preprocessing.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SlashOutcome {
+    /// Start a turn with the (possibly rewritten) request.
+    Turn(TurnRequest),
+    /// Start a review instead of a turn.
+    Review { target: Value },
+    /// Compact the thread instead of starting a turn.
+    Compact,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SlashCommandInfo {
+    pub(crate) name: String,
+    pub(crate) usage: String,
+    pub(crate) description: String,
+}
+
+/// A parsed `/name rest`, where `rest` is split into its first word (`arg`)
+/// and the remaining text (`body`).
+struct SlashInvocation<'a> {
+    rest: &'a str,
+    arg: &'a str,
+    body: &'a str,
+}
+
+type SlashHandler = fn(&SlashInvocation<'_>, TurnRequest) -> Result<SlashOutcome, String>;
+
+struct SlashCommand {
+    name: &'static str,
+    usage: &'static str,
+    description: &'static str,
+    handler: SlashHandler,
+}
+
+/// Every command the backend understands. Add an entry here to register a
+/// new one; unknown `/words` are sent to the agent unchanged.
+const SLASH_COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        name: "review",
+        usage: "/review [base <branch> | commit <sha> [title] | custom <instructions>]",
+        description:
+            "Start a review of uncommitted changes, a base branch, a commit or custom instructions.",
+        handler: review_command,
+    },
+    SlashCommand {
+        name: "test",
+        usage: "/test [extra instructions]",
+        description: "Ask the agent to run the test suite and fix failures.",
+        handler: test_command,
+    },
+    SlashCommand {
+        name: "model",
+        usage: "/model <model> <message>",
+        description: "Send the message with a different model.",
+        handler: model_command,
+    },
+    SlashCommand {
+        name: "effort",
+        usage: "/effort <low|medium|high> <message>",
+        description: "Send the message with a different reasoning effort.",
+        handler: effort_command,
+    },
+    SlashCommand {
+        name: "mode",
+        usage: "/mode <read-only|current|full-access> <message>",
+        description: "Send the message with a different access mode.",
+        handler: mode_command,
+    },
+    SlashCommand {
+        name: "compact",
+        usage: "/compact",
+        description: "Compact the thread's context instead of sending a message.",
+        handler: compact_command,
+    },
+];
+
+pub(crate) fn list_slash_commands_core() -> Vec<SlashCommandInfo> {
+    SLASH_COMMANDS
+        .iter()
+        .map(|command| SlashCommandInfo {
+            name: command.name.to_string(),
+            usage: command.usage.to_string(),
+            description: command.description.to_string(),
+        })
+        .collect()
+}
+
+/// Applies a leading slash command in `request.text`. Text that does not
+/// start with a registered command passes through; `//` escapes a literal `/`.
+pub(crate) fn preprocess_turn(mut request: TurnRequest) -> Result<SlashOutcome, String> {
+    let text = request.text.clone();
+    let trimmed = text.trim_start();
+    if let Some(escaped) = trimmed.strip_prefix("//") {
+        request.text = format!("/{escaped}");
+        return Ok(SlashOutcome::Turn(request));
+    }
+    let Some(command_text) = trimmed.strip_prefix('/') else {
+        return Ok(SlashOutcome::Turn(request));
+    };
+    let name_end = command_text
+        .find(char::is_whitespace)
+        .unwrap_or(command_text.len());
+    let name = command_text[..name_end].to_ascii_lowercase();
+    let Some(command) = SLASH_COMMANDS.iter().find(|command| command.name == name) else {
+        return Ok(SlashOutcome::Turn(request));
+    };
+    let rest = command_text[name_end..].trim();
+    let (arg, body) = match rest.split_once(char::is_whitespace) {
+        Some((arg, body)) => (arg, body.trim()),
+        None => (rest, ""),
+    };
+    let invocation = SlashInvocation { rest, arg, body };
+    (command.handler)(&invocation, request)
+}
+
+/// Mirrors the composer's `/review` parsing.
+fn review_command(
+    invocation: &SlashInvocation<'_>,
+    _request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    let rest = invocation.rest;
+    let option = invocation.arg.to_ascii_lowercase();
+    let target = if rest.is_empty() {
+        json!({ "type": "uncommittedChanges" })
+    } else if option == "base" && !invocation.body.is_empty() {
+        json!({ "type": "baseBranch", "branch": invocation.body })
+    } else if option == "commit" && !invocation.body.is_empty() {
+        let (sha, title) = match invocation.body.split_once(char::is_whitespace) {
+            Some((sha, title)) => (sha, title.trim()),
+            None => (invocation.body, ""),
+        };
+        if title.is_empty() {
+            json!({ "type": "commit", "sha": sha })
+        } else {
+            json!({ "type": "commit", "sha": sha, "title": title })
+        }
+    } else if option == "custom" && !invocation.body.is_empty() {
+        json!({ "type": "custom", "instructions": invocation.body })
+    } else {
+        json!({ "type": "custom", "instructions": rest })
+    };
+    Ok(SlashOutcome::Review { target })
+}
+
+fn test_command(
+    invocation: &SlashInvocation<'_>,
+    request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    let text = if invocation.rest.is_empty() {
+        TEST_PROMPT.to_string()
+    } else {
+        format!("{TEST_PROMPT}\n\n{}", invocation.rest)
+    };
+    Ok(SlashOutcome::Turn(TurnRequest { text, ..request }))
+}
+
+/// The message after a command's argument; commands that only change turn
+/// settings still need something to send.
+fn require_body(command: &str, invocation: &SlashInvocation<'_>) -> Result<String, String> {
+    if invocation.arg.is_empty() {
+        return Err(format!("/{command} needs an argument"));
+    }
+    if invocation.body.is_empty() {
+        return Err(format!("/{command} needs a message after its argument"));
+    }
+    Ok(invocation.body.to_string())
+}
+
+fn model_command(
+    invocation: &SlashInvocation<'_>,
+    request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    Ok(SlashOutcome::Turn(TurnRequest {
+        text: require_body("model", invocation)?,
+        model: Some(invocation.arg.to_string()),
+        ..request
+    }))
+}
+
+fn effort_command(
+    invocation: &SlashInvocation<'_>,
+    request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    let text = require_body("effort", invocation)?;
+    let effort = invocation.arg.to_ascii_lowercase();
+    if !matches!(
+        effort.as_str(),
+        "minimal" | "low" | "medium" | "high" | "xhigh"
+    ) {
+        return Err(format!("Unknown reasoning effort `{}`", invocation.arg));
+    }
+    Ok(SlashOutcome::Turn(TurnRequest {
+        text,
+        effort: Some(effort),
+        ..request
+    }))
+}
+
+fn mode_command(
+    invocation: &SlashInvocation<'_>,
+    request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    let text = require_body("mode", invocation)?;
+    let mode = invocation.arg.to_ascii_lowercase();
+    if !matches!(mode.as_str(), "read-only" | "current" | "full-access") {
+        return Err(format!(
+            "Unknown access mode `{}`; use read-only, current or full-access",
+            invocation.arg
+        ));
+    }
+    Ok(SlashOutcome::Turn(TurnRequest {
+        text,
+        access_mode: Some(mode),
+        ..request
+    }))
+}
+
+fn compact_command(
+    _invocation: &SlashInvocation<'_>,
+    _request: TurnRequest,
+) -> Result<SlashOutcome, String> {
+    Ok(SlashOutcome::Compact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> TurnRequest {
+        TurnRequest {
+            text: text.to_string(),
+            model: Some("default-model".to_string()),
+            ..TurnRequest::default()
+        }
+    }
+
+    #[test]
+    fn settings_commands_rewrite_the_turn() {
+        assert_eq!(
+            preprocess_turn(request("/model gpt-x fix the build")),
+            Ok(SlashOutcome::Turn(TurnRequest {
+                text: "fix the build".to_string(),
+                model: Some("gpt-x".to_string()),
+                ..TurnRequest::default()
+            }))
+        );
+        let SlashOutcome::Turn(turn) =
+            preprocess_turn(request("/MODE Read-Only explain\nthe code")).unwrap()
+        else {
+            panic!("expected a turn");
+        };
+        assert_eq!(turn.access_mode.as_deref(), Some("read-only"));
+        assert_eq!(turn.model.as_deref(), Some("default-model"));
+        assert_eq!(turn.text, "explain\nthe code");
+        assert!(preprocess_turn(request("/mode yolo go")).is_err());
+        assert!(preprocess_turn(request("/model gpt-x")).is_err());
+    }
+
+    #[test]
+    fn review_targets_match_the_composer() {
+        let review = |text: &str| match preprocess_turn(request(text)).unwrap() {
+            SlashOutcome::Review { target } => target,
+            other => panic!("expected a review, got {other:?}"),
+        };
+        assert_eq!(review("/review"), json!({ "type": "uncommittedChanges" }));
+        assert_eq!(
+            review("/review base main"),
+            json!({ "type": "baseBranch", "branch": "main" })
+        );
+        assert_eq!(
+            review("/review commit abc123 Fix login"),
+            json!({ "type": "commit", "sha": "abc123", "title": "Fix login" })
+        );
+        assert_eq!(
+            review("/review look at error handling"),
+            json!({ "type": "custom", "instructions": "look at error handling" })
+        );
+    }
+
+    #[test]
+    fn unknown_commands_and_escapes_pass_through() {
+        assert_eq!(
+            preprocess_turn(request("/usr/bin/env is missing")),
+            Ok(SlashOutcome::Turn(request("/usr/bin/env is missing")))
+        );
+        assert_eq!(
+            preprocess_turn(request("//model is a word")),
+            Ok(SlashOutcome::Turn(request("/model is a word")))
+        );
+        assert_eq!(
+            preprocess_turn(request(" /compact")),
+            Ok(SlashOutcome::Compact)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{WorkspaceEntry, WorkspaceInfo};
+
+const SNAPSHOT_VERSION: u32 = 1;
+const MAX_SNAPSHOT_AGE_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+const MAX_SNAPSHOT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Resolved UI state written on quit and handed back at launch, so the window
+/// can render workspaces and threads before any session is spawned. The
+/// per-workspace maps are opaque to the backend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StateSnapshot {
+    #[serde(default)]
+    pub(crate) version: u32,
+    #[serde(default)]
+    pub(crate) saved_at: u64,
+    /// `local`, or `remote:<host>`; a snapshot is only restored for the same backend.
+    #[serde(default)]
+    pub(crate) backend: String,
+    #[serde(default)]
+    pub(crate) workspaces: Vec<WorkspaceInfo>,
+    #[serde(default)]
+    pub(crate) workspace_meta: HashMap<String, Value>,
+    #[serde(default)]
+    pub(crate) threads: HashMap<String, Value>,
+    #[serde(default)]
+    pub(crate) active_turns: HashMap<String, Value>,
+}
+
+/// Holds the latest state reported by the frontend until it is hibernated.
+pub(crate) struct StateSnapshotStore {
+    path: PathBuf,
+    pending: Mutex<Option<StateSnapshot>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl StateSnapshotStore {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            pending: Mutex::new(None),
+        }
+    }
+}
+
+/// Replaces the state to hibernate. Cheap, so the frontend can call it
+/// whenever its caches settle.
+pub(crate) fn update_state_snapshot_core(store: &StateSnapshotStore, snapshot: StateSnapshot) {
+    if let Ok(mut pending) = store.pending.lock() {
+        *pending = Some(snapshot);
+    }
+}
+
+/// Writes the pending state as one compact file. Without pending state any
+/// previous snapshot is removed rather than restored stale.
+pub(crate) fn hibernate_core(store: &StateSnapshotStore, backend: String) -> Result<(), String> {
+    let pending = store
+        .pending
+        .lock()
+        .map_err(|_| "state snapshot is poisoned".to_string())?
+        .clone();
+    let Some(mut snapshot) = pending else {
+        let _ = std::fs::remove_file(&store.path);
+        return Ok(());
+    };
+    snapshot.version = SNAPSHOT_VERSION;
+    snapshot.saved_at = now_ms();
+    snapshot.backend = backend;
+    for workspace in &mut snapshot.workspaces {
+        workspace.connected = false;
+    }
+    let data = serde_json::to_vec(&snapshot).map_err(|e| e.to_string())?;
+    if data.len() > MAX_SNAPSHOT_BYTES {
+        let _ = std::fs::remove_file(&store.path);
+        return Err(format!(
+            "State snapshot is {} bytes; the limit is {MAX_SNAPSHOT_BYTES}",
+            data.len()
+        ));
+    }
+    if let Some(parent) = store.path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = store.path.with_extension("json.tmp");
+    std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &store.path).map_err(|e| e.to_string())
+}
+
+/// Reads the hibernated state for `backend`. With `known` (local mode) the
+/// workspace list is reconciled against `workspaces.json`: removed workspaces
+/// are dropped, new ones added and settings taken from disk.
+pub(crate) fn restore_state_snapshot_core(
+    store: &StateSnapshotStore,
+    backend: &str,
+    known: Option<&HashMap<String, WorkspaceEntry>>,
+) -> Option<StateSnapshot> {
+    let data = std::fs::read(&store.path).ok()?;
+    let mut snapshot: StateSnapshot = serde_json::from_slice(&data).ok()?;
+    if snapshot.version != SNAPSHOT_VERSION
+        || snapshot.backend != backend
+        || now_ms().saturating_sub(snapshot.saved_at) > MAX_SNAPSHOT_AGE_MS
+    {
+        return None;
+    }
+    if let Some(known) = known {
+        snapshot
+            .workspaces
+            .retain(|workspace| known.contains_key(&workspace.id));
+        let mut listed: HashSet<String> = HashSet::new();
+        for workspace in &mut snapshot.workspaces {
+            if let Some(entry) = known.get(&workspace.id) {
+                workspace.name = entry.name.clone();
+                workspace.path = entry.path.clone();
+                workspace.settings = entry.settings.clone();
+            }
+            listed.insert(workspace.id.clone());
+        }
+        for entry in known.values().filter(|entry| !listed.contains(&entry.id)) {
+            snapshot.workspaces.push(WorkspaceInfo {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+                path: entry.path.clone(),
+                connected: false,
+                kind: entry.kind.clone(),
+                parent_id: entry.parent_id.clone(),
+                worktree: entry.worktree.clone(),
+                settings: entry.settings.clone(),
+            });
+        }
+        snapshot
+            .workspace_meta
+            .retain(|id, _| known.contains_key(id));
+        snapshot.threads.retain(|id, _| known.contains_key(id));
+        snapshot.active_turns.retain(|id, _| known.contains_key(id));
+    }
+    Some(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+    use serde_json::json;
+
+    fn entry(id: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: format!("{id}-renamed"),
+            path: format!("/repos/{id}"),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    fn info(id: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/repos/{id}"),
+            connected: true,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn restore_reconciles_workspaces_and_backend() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-state-snapshot-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let store = StateSnapshotStore::new(dir.join("state-snapshot.json"));
+        update_state_snapshot_core(
+            &store,
+            StateSnapshot {
+                workspaces: vec![info("kept"), info("removed")],
+                threads: HashMap::from([
+                    ("kept".to_string(), json!([{ "id": "t1" }])),
+                    ("removed".to_string(), json!([{ "id": "t2" }])),
+                ]),
+                ..StateSnapshot::default()
+            },
+        );
+        hibernate_core(&store, "local".to_string()).expect("hibernate");
+
+        assert!(restore_state_snapshot_core(&store, "remote:host:4732", None).is_none());
+
+        let known = HashMap::from([
+            ("kept".to_string(), entry("kept")),
+            ("added".to_string(), entry("added")),
+        ]);
+        let restored = restore_state_snapshot_core(&store, "local", Some(&known)).expect("restore");
+        let ids: Vec<&str> = restored
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["kept", "added"]);
+        assert_eq!(restored.workspaces[0].name, "kept-renamed");
+        assert!(restored
+            .workspaces
+            .iter()
+            .all(|workspace| !workspace.connected));
+        assert_eq!(restored.threads.keys().collect::<Vec<_>>(), vec!["kept"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,325 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::Mutex;
+
+use crate::types::{
+    AppSettings, StorageCategory, StorageCategoryUsage, StorageCleanupResult, StorageUsageReport,
+};
+
+const ENFORCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+pub(crate) const ALL_CATEGORIES: [StorageCategory; 5] = [
+    StorageCategory::Journal,
+    StorageCategory::Transcripts,
+    StorageCategory::Attachments,
+    StorageCategory::Logs,
+    StorageCategory::Recordings,
+];
+
+struct StoredFile {
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// Where files of `category` live. Anything that writes journals,
+/// transcripts, attachments, logs or recordings should put them here so the
+/// budget covers them.
+pub(crate) fn category_dir(data_dir: &Path, category: StorageCategory) -> PathBuf {
+    let name = match category {
+        StorageCategory::Journal => "journal",
+        StorageCategory::Transcripts => "transcripts",
+        StorageCategory::Attachments => "attachments",
+        StorageCategory::Logs => "logs",
+        StorageCategory::Recordings => "recordings",
+    };
+    data_dir.join(name)
+}
+
+/// Where writers kept their files before they used [`category_dir`], and
+/// where those files belong now.
+fn legacy_paths(data_dir: &Path) -> [(PathBuf, PathBuf); 4] {
+    let logs = category_dir(data_dir, StorageCategory::Logs);
+    [
+        (
+            data_dir.join("thread-journals"),
+            category_dir(data_dir, StorageCategory::Journal),
+        ),
+        (
+            data_dir.join("uploads"),
+            category_dir(data_dir, StorageCategory::Attachments),
+        ),
+        (
+            data_dir.join("protocol-traces"),
+            logs.join("protocol-traces"),
+        ),
+        (data_dir.join("daemon.log"), logs.join("daemon.log")),
+    ]
+}
+
+/// Moves files left at their old locations into their category directories
+/// so the budget covers them. Run at startup, before the writers open them.
+pub(crate) fn adopt_legacy_paths(data_dir: &Path) {
+    for (legacy, path) in legacy_paths(data_dir) {
+        if !legacy.exists() || path.exists() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(error) = fs::rename(&legacy, &path) {
+            eprintln!(
+                "failed to move {} to {}: {error}",
+                legacy.display(),
+                path.display()
+            );
+        }
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<StoredFile>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&entry.path(), files);
+        } else if metadata.is_file() {
+            files.push(StoredFile {
+                path: entry.path(),
+                bytes: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+    }
+}
+
+fn quota_bytes(settings: &AppSettings, category: StorageCategory) -> Option<u64> {
+    settings
+        .storage_quotas_mb
+        .get(&category)
+        .map(|mb| mb.saturating_mul(BYTES_PER_MB))
+}
+
+fn remove_files(
+    category: StorageCategory,
+    files: impl IntoIterator<Item = StoredFile>,
+) -> StorageCleanupResult {
+    let mut result = StorageCleanupResult {
+        category,
+        removed_files: 0,
+        freed_bytes: 0,
+    };
+    for file in files {
+        if fs::remove_file(&file.path).is_ok() {
+            result.removed_files += 1;
+            result.freed_bytes += file.bytes;
+        }
+    }
+    result
+}
+
+pub(crate) fn storage_usage_report_core(
+    data_dir: &Path,
+    settings: &AppSettings,
+) -> StorageUsageReport {
+    let categories: Vec<StorageCategoryUsage> = ALL_CATEGORIES
+        .iter()
+        .map(|&category| {
+            let dir = category_dir(data_dir, category);
+            let mut files = Vec::new();
+            collect_files(&dir, &mut files);
+            let bytes = files.iter().map(|file| file.bytes).sum();
+            let quota_bytes = quota_bytes(settings, category);
+            StorageCategoryUsage {
+                category,
+                path: dir.to_string_lossy().to_string(),
+                bytes,
+                file_count: files.len(),
+                quota_bytes,
+                over_quota: quota_bytes.is_some_and(|quota| bytes > quota),
+            }
+        })
+        .collect();
+    StorageUsageReport {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        total_bytes: categories.iter().map(|usage| usage.bytes).sum(),
+        retention_days: settings.storage_retention_days,
+        categories,
+    }
+}
+
+/// Deletes files in `category`, or only those older than `older_than_days`.
+pub(crate) fn cleanup_storage_core(
+    data_dir: &Path,
+    category: StorageCategory,
+    older_than_days: Option<u32>,
+) -> StorageCleanupResult {
+    let mut files = Vec::new();
+    collect_files(&category_dir(data_dir, category), &mut files);
+    let cutoff = older_than_days.and_then(|days| {
+        SystemTime::now().checked_sub(Duration::from_secs(u64::from(days) * SECONDS_PER_DAY))
+    });
+    let files = files
+        .into_iter()
+        .filter(|file| cutoff.is_none_or(|cutoff| file.modified < cutoff));
+    remove_files(category, files)
+}
+
+/// Applies the retention window, then deletes the oldest files of every
+/// category still over its quota. Categories with nothing removed are omitted.
+pub(crate) fn enforce_storage_budget_core(
+    data_dir: &Path,
+    settings: &AppSettings,
+) -> Vec<StorageCleanupResult> {
+    let mut results = Vec::new();
+    for category in ALL_CATEGORIES {
+        let mut result = match settings.storage_retention_days {
+            Some(days) => cleanup_storage_core(data_dir, category, Some(days)),
+            None => StorageCleanupResult {
+                category,
+                removed_files: 0,
+                freed_bytes: 0,
+            },
+        };
+        if let Some(quota) = quota_bytes(settings, category) {
+            let mut files = Vec::new();
+            collect_files(&category_dir(data_dir, category), &mut files);
+            let mut total: u64 = files.iter().map(|file| file.bytes).sum();
+            files.sort_by_key(|file| file.modified);
+            let mut evicted = Vec::new();
+            for file in files {
+                if total <= quota {
+                    break;
+                }
+                total = total.saturating_sub(file.bytes);
+                evicted.push(file);
+            }
+            let quota_result = remove_files(category, evicted);
+            result.removed_files += quota_result.removed_files;
+            result.freed_bytes += quota_result.freed_bytes;
+        }
+        if result.removed_files > 0 {
+            results.push(result);
+        }
+    }
+    results
+}
+
+/// Enforces the budget now and then hourly, re-reading settings each time.
+pub(crate) async fn run_storage_budget(data_dir: PathBuf, app_settings: &Mutex<AppSettings>) {
+    let mut ticker = tokio::time::interval(ENFORCE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let settings = app_settings.lock().await.clone();
+        let data_dir = data_dir.clone();
+        let _ =
+            tokio::task::spawn_blocking(move || enforce_storage_budget_core(&data_dir, &settings))
+                .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_data_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("codex-monitor-storage-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn write_file(path: &Path, bytes: usize, age: Duration) {
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, vec![b'x'; bytes]).expect("write file");
+        let file = fs::File::options().write(true).open(path).expect("open");
+        file.set_modified(SystemTime::now() - age)
+            .expect("set mtime");
+    }
+
+    #[test]
+    fn quota_evicts_oldest_files_first() {
+        let data_dir = temp_data_dir();
+        let logs = category_dir(&data_dir, StorageCategory::Logs);
+        let half_mb = (BYTES_PER_MB / 2) as usize;
+        write_file(&logs.join("old.log"), half_mb, Duration::from_secs(300));
+        write_file(&logs.join("mid.log"), half_mb, Duration::from_secs(200));
+        write_file(
+            &logs.join("nested/new.log"),
+            half_mb,
+            Duration::from_secs(100),
+        );
+
+        let mut settings = AppSettings::default();
+        settings.storage_quotas_mb.insert(StorageCategory::Logs, 1);
+        let results = enforce_storage_budget_core(&data_dir, &settings);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].removed_files, 1);
+        assert!(!logs.join("old.log").exists());
+        assert!(logs.join("mid.log").exists());
+        assert!(logs.join("nested/new.log").exists());
+
+        let report = storage_usage_report_core(&data_dir, &settings);
+        let usage = report
+            .categories
+            .iter()
+            .find(|usage| usage.category == StorageCategory::Logs)
+            .expect("logs usage");
+        assert_eq!(usage.file_count, 2);
+        assert!(!usage.over_quota);
+    }
+
+    #[test]
+    fn cleanup_respects_age_cutoff() {
+        let data_dir = temp_data_dir();
+        let journal = category_dir(&data_dir, StorageCategory::Journal);
+        write_file(
+            &journal.join("stale.jsonl"),
+            10,
+            Duration::from_secs(10 * SECONDS_PER_DAY),
+        );
+        write_file(&journal.join("fresh.jsonl"), 10, Duration::from_secs(60));
+
+        let result = cleanup_storage_core(&data_dir, StorageCategory::Journal, Some(7));
+
+        assert_eq!(result.removed_files, 1);
+        assert_eq!(result.freed_bytes, 10);
+        assert!(journal.join("fresh.jsonl").exists());
+    }
+
+    #[test]
+    fn legacy_writer_paths_move_into_their_categories() {
+        let data_dir = temp_data_dir();
+        write_file(
+            &data_dir.join("thread-journals/t-1.jsonl"),
+            10,
+            Duration::from_secs(60),
+        );
+        write_file(&data_dir.join("daemon.log"), 10, Duration::from_secs(60));
+
+        adopt_legacy_paths(&data_dir);
+
+        let journal = category_dir(&data_dir, StorageCategory::Journal);
+        let logs = category_dir(&data_dir, StorageCategory::Logs);
+        assert!(journal.join("t-1.jsonl").exists());
+        assert!(logs.join("daemon.log").exists());
+        assert!(!data_dir.join("thread-journals").exists());
+        let report = storage_usage_report_core(&data_dir, &AppSettings::default());
+        let files = |category| {
+            report
+                .categories
+                .iter()
+                .find(|usage| usage.category == category)
+                .map(|usage| usage.file_count)
+        };
+        assert_eq!(files(StorageCategory::Journal), Some(1));
+        assert_eq!(files(StorageCategory::Logs), Some(1));
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
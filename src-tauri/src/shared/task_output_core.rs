@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::shared::process_core::shell_command;
+use crate::shared::release_core::resolve_launch_script;
+use crate::shared::thread_export_core::fenced;
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
+use crate::types::WorkspaceEntry;
+
+const RUN_AND_ATTACH_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Bytes kept from the start and end of each stream. Failures usually show
+/// up at the end, so the tail gets most of the budget.
+const HEAD_BYTES: usize = 4 * 1024;
+const TAIL_BYTES: usize = 12 * 1024;
+
+/// Output of a launch script, ready to paste into a user message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AttachedOutput {
+    pub(crate) task: String,
+    pub(crate) script: String,
+    /// `None` when the process was ended by a signal.
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) duration_ms: u64,
+    /// Bytes dropped from the middle of stdout and stderr together.
+    pub(crate) omitted_bytes: usize,
+    /// Markdown: a header line plus fenced stdout/stderr blocks.
+    pub(crate) text: String,
+}
+
+fn ansi_regex() -> &'static Regex {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| {
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(\x07|\x1b\\)")
+            .expect("valid ansi regex")
+    })
+}
+
+fn floor_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Keeps the first `head` and last `tail` bytes, noting how much was cut.
+fn truncate_middle(text: &str, head: usize, tail: usize) -> (String, usize) {
+    if text.len() <= head + tail {
+        return (text.to_string(), 0);
+    }
+    let head_end = floor_boundary(text, head);
+    let tail_start = ceil_boundary(text, text.len() - tail);
+    let omitted = tail_start - head_end;
+    (
+        format!(
+            "{}\n… [{omitted} bytes omitted] …\n{}",
+            &text[..head_end],
+            &text[tail_start..]
+        ),
+        omitted,
+    )
+}
+
+/// Strips terminal colour codes and carriage-return progress redraws, then
+/// truncates the middle.
+fn clean_stream(bytes: &[u8]) -> (String, usize) {
+    let text = String::from_utf8_lossy(bytes);
+    let text = ansi_regex().replace_all(&text, "");
+    let text = text
+        .lines()
+        .map(|line| line.rsplit('\r').next().unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    truncate_middle(text.trim_end(), HEAD_BYTES, TAIL_BYTES)
+}
+
+fn format_duration(duration_ms: u64) -> String {
+    if duration_ms < 1000 {
+        format!("{duration_ms}ms")
+    } else {
+        format!("{:.1}s", duration_ms as f64 / 1000.0)
+    }
+}
+
+fn format_attachment(
+    task: &str,
+    exit_code: Option<i32>,
+    duration_ms: u64,
+    stdout: &str,
+    stderr: &str,
+    omitted_bytes: usize,
+) -> String {
+    let status = match exit_code {
+        Some(0) => "succeeded".to_string(),
+        Some(code) => format!("failed with exit code {code}"),
+        None => "was terminated by a signal".to_string(),
+    };
+    let mut text = format!(
+        "Output of `{task}` ({status} after {}):\n",
+        format_duration(duration_ms)
+    );
+    if stdout.is_empty() && stderr.is_empty() {
+        text.push_str("\n(no output)\n");
+    }
+    for (label, stream) in [("stdout", stdout), ("stderr", stderr)] {
+        if stream.is_empty() {
+            continue;
+        }
+        if !stdout.is_empty() && !stderr.is_empty() {
+            text.push_str(&format!("\n{label}:\n"));
+        } else {
+            text.push('\n');
+        }
+        text.push_str(&fenced(stream, "text"));
+    }
+    if omitted_bytes > 0 {
+        text.push_str(&format!(
+            "\n_{omitted_bytes} bytes of output were omitted from the middle._\n"
+        ));
+    }
+    text
+}
+
+/// Runs one of the workspace's configured launch scripts (by id or label;
+/// nothing else may run) and formats its output for the next user message.
+/// A failing script is still a successful attach: the exit code is part of
+/// the result.
+pub(crate) async fn run_and_attach_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    command: String,
+) -> Result<AttachedOutput, String> {
+    ensure_workspace_writable_core(workspaces, &workspace_id).await?;
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let task = command.trim().to_string();
+    let script = resolve_launch_script(&entry, &task)?;
+    let started = Instant::now();
+    let child = shell_command(&script)
+        .current_dir(&entry.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("Failed to run task `{task}`: {err}"))?;
+    let output = tokio::time::timeout(RUN_AND_ATTACH_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            format!(
+                "Task `{task}` did not finish within {} minutes",
+                RUN_AND_ATTACH_TIMEOUT.as_secs() / 60
+            )
+        })?
+        .map_err(|err| format!("Failed to run task `{task}`: {err}"))?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let (stdout, stdout_omitted) = clean_stream(&output.stdout);
+    let (stderr, stderr_omitted) = clean_stream(&output.stderr);
+    let omitted_bytes = stdout_omitted + stderr_omitted;
+    let exit_code = output.status.code();
+    Ok(AttachedOutput {
+        text: format_attachment(
+            &task,
+            exit_code,
+            duration_ms,
+            &stdout,
+            &stderr,
+            omitted_bytes,
+        ),
+        task,
+        script,
+        exit_code,
+        duration_ms,
+        omitted_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_cleaned_and_truncated_in_the_middle() {
+        let (text, omitted) = clean_stream(b"\x1b[31mFAIL\x1b[0m auth::login\r\n10%\r50%\r100%\n");
+        assert_eq!(text, "FAIL auth::login\n100%");
+        assert_eq!(omitted, 0);
+
+        let long = format!("{}{}", "a".repeat(HEAD_BYTES), "é".repeat(TAIL_BYTES));
+        let (text, omitted) = truncate_middle(&long, HEAD_BYTES, TAIL_BYTES / 2);
+        assert!(omitted > 0);
+        assert!(text.starts_with(&"a".repeat(HEAD_BYTES)));
+        assert!(text.contains(&format!("[{omitted} bytes omitted]")));
+        assert!(text.ends_with('é'));
+    }
+
+    #[test]
+    fn attachment_names_the_task_and_exit_code() {
+        let text = format_attachment("test", Some(1), 3200, "", "1 failed", 0);
+        assert_eq!(
+            text,
+            "Output of `test` (failed with exit code 1 after 3.2s):\n\n```text\n1 failed\n```\n"
+        );
+        let text = format_attachment("lint", Some(0), 40, "ok", "warn", 12);
+        assert!(text.contains("succeeded after 40ms"));
+        assert!(text.contains("\nstdout:\n```text\nok\n```\n"));
+        assert!(text.contains("\nstderr:\n```text\nwarn\n```\n"));
+        assert!(text.ends_with("_12 bytes of output were omitted from the middle._\n"));
+    }
+}
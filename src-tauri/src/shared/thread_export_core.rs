@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::shared::codex_core;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown export format `{other}`; use markdown or json"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileChangeEntry {
+    pub(crate) path: String,
+    pub(crate) kind: Option<String>,
+    pub(crate) diff: Option<String>,
+}
+
+/// One transcript entry, normalized from an app-server thread item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum TranscriptEntry {
+    #[serde(rename_all = "camelCase")]
+    User { turn_id: String, text: String },
+    #[serde(rename_all = "camelCase")]
+    Agent { turn_id: String, text: String },
+    #[serde(rename_all = "camelCase")]
+    Command {
+        turn_id: String,
+        command: String,
+        cwd: Option<String>,
+        exit_code: Option<i64>,
+        output: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    FileChange {
+        turn_id: String,
+        changes: Vec<FileChangeEntry>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        turn_id: String,
+        server: String,
+        tool: String,
+        arguments: Option<Value>,
+        result: Option<Value>,
+        error: Option<Value>,
+    },
+    #[serde(rename_all = "camelCase")]
+    WebSearch { turn_id: String, query: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadTranscript {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) title: Option<String>,
+    pub(crate) exported_at: String,
+    pub(crate) entries: Vec<TranscriptEntry>,
+}
+
+/// The rendered export: written to `path` when one was given, otherwise
+/// returned inline as `content`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadExport {
+    pub(crate) format: ExportFormat,
+    pub(crate) path: Option<String>,
+    pub(crate) content: Option<String>,
+}
+
+fn str_field(item: &Value, key: &str) -> String {
+    item.get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn optional_str(item: &Value, key: &str) -> Option<String> {
+    item.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn non_null(item: &Value, key: &str) -> Option<Value> {
+    item.get(key).filter(|value| !value.is_null()).cloned()
+}
+
+/// Text of a `userMessage` content list; images are kept as placeholders so
+/// the transcript shows that one was attached.
+fn user_text(content: &Value) -> String {
+    content
+        .as_array()
+        .map(|inputs| {
+            inputs
+                .iter()
+                .filter_map(|input| match input.get("type").and_then(Value::as_str) {
+                    Some("text") => Some(str_field(input, "text")),
+                    Some("image") => Some(format!("[image: {}]", str_field(input, "url"))),
+                    Some("localImage") => Some(format!("[image: {}]", str_field(input, "path"))),
+                    Some("skill") => Some(format!("[skill: {}]", str_field(input, "name"))),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+fn command_text(item: &Value) -> String {
+    match item.get("command") {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Some(Value::String(command)) => command.clone(),
+        _ => String::new(),
+    }
+}
+
+fn file_changes(item: &Value) -> Vec<FileChangeEntry> {
+    item.get("changes")
+        .and_then(Value::as_array)
+        .map(|changes| {
+            changes
+                .iter()
+                .filter_map(|change| {
+                    let path = optional_str(change, "path")?;
+                    let kind = match change.get("kind") {
+                        Some(Value::String(kind)) => Some(kind.to_ascii_lowercase()),
+                        Some(kind) => optional_str(kind, "type").map(|kind| kind.to_lowercase()),
+                        None => None,
+                    };
+                    Some(FileChangeEntry {
+                        path,
+                        kind,
+                        diff: optional_str(change, "diff"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn transcript_entry(turn_id: &str, item: &Value) -> Option<TranscriptEntry> {
+    let turn_id = turn_id.to_string();
+    let entry = match item.get("type").and_then(Value::as_str)? {
+        "userMessage" => TranscriptEntry::User {
+            turn_id,
+            text: user_text(item.get("content").unwrap_or(&Value::Null)),
+        },
+        "agentMessage" => TranscriptEntry::Agent {
+            turn_id,
+            text: str_field(item, "text"),
+        },
+        "commandExecution" => TranscriptEntry::Command {
+            turn_id,
+            command: command_text(item),
+            cwd: optional_str(item, "cwd"),
+            exit_code: item.get("exitCode").and_then(Value::as_i64),
+            output: str_field(item, "aggregatedOutput"),
+        },
+        "fileChange" => TranscriptEntry::FileChange {
+            turn_id,
+            changes: file_changes(item),
+        },
+        "mcpToolCall" => TranscriptEntry::ToolCall {
+            turn_id,
+            server: str_field(item, "server"),
+            tool: str_field(item, "tool"),
+            arguments: non_null(item, "arguments"),
+            result: non_null(item, "result"),
+            error: non_null(item, "error"),
+        },
+        "webSearch" => TranscriptEntry::WebSearch {
+            turn_id,
+            query: str_field(item, "query"),
+        },
+        _ => return None,
+    };
+    Some(entry)
+}
+
+/// Builds the transcript from a `thread/read` response that includes turns.
+pub(crate) fn build_transcript(
+    workspace_id: &str,
+    thread_id: &str,
+    response: &Value,
+) -> Result<ThreadTranscript, String> {
+    let thread = response
+        .get("result")
+        .unwrap_or(response)
+        .get("thread")
+        .ok_or_else(|| "thread/read did not return a thread".to_string())?;
+    let entries = thread
+        .get("turns")
+        .and_then(Value::as_array)
+        .map(|turns| {
+            turns
+                .iter()
+                .flat_map(|turn| {
+                    let turn_id = str_field(turn, "id");
+                    turn.get("items")
+                        .and_then(Value::as_array)
+                        .map(|items| {
+                            items
+                                .iter()
+                                .filter_map(|item| transcript_entry(&turn_id, item))
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(ThreadTranscript {
+        workspace_id: workspace_id.to_string(),
+        thread_id: thread_id.to_string(),
+        title: optional_str(thread, "name").or_else(|| optional_str(thread, "preview")),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        entries,
+    })
+}
+
+/// A fenced block whose fence is longer than any backtick run in `content`,
+/// so command output or diffs containing ``` cannot close it early.
+pub(crate) fn fenced(content: &str, language: &str) -> String {
+    let longest_run = content
+        .split(|ch| ch != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!(
+        "{fence}{language}\n{}\n{fence}\n",
+        content.trim_end_matches('\n')
+    )
+}
+
+fn pretty_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+pub(crate) fn render_markdown(transcript: &ThreadTranscript) -> String {
+    let title = transcript
+        .title
+        .as_deref()
+        .map(|title| title.lines().next().unwrap_or(title))
+        .unwrap_or(&transcript.thread_id);
+    let mut out = format!(
+        "# {title}\n\n_Thread `{}` · exported {}_\n",
+        transcript.thread_id, transcript.exported_at
+    );
+    for entry in &transcript.entries {
+        out.push('\n');
+        match entry {
+            TranscriptEntry::User { text, .. } => {
+                out.push_str(&format!("## User\n\n{}\n", text.trim()));
+            }
+            TranscriptEntry::Agent { text, .. } => {
+                out.push_str(&format!("## Agent\n\n{}\n", text.trim()));
+            }
+            TranscriptEntry::Command {
+                command,
+                cwd,
+                exit_code,
+                output,
+                ..
+            } => {
+                out.push_str("### Command\n\n");
+                out.push_str(&fenced(command, "sh"));
+                let mut details = Vec::new();
+                if let Some(cwd) = cwd {
+                    details.push(format!("cwd `{cwd}`"));
+                }
+                if let Some(code) = exit_code {
+                    details.push(format!("exit code {code}"));
+                }
+                if !details.is_empty() {
+                    out.push_str(&format!("\n{}\n", details.join(" · ")));
+                }
+                if !output.trim().is_empty() {
+                    out.push('\n');
+                    out.push_str(&fenced(output, ""));
+                }
+            }
+            TranscriptEntry::FileChange { changes, .. } => {
+                out.push_str("### File changes\n\n");
+                for change in changes {
+                    let kind = change.kind.as_deref().unwrap_or("update");
+                    out.push_str(&format!("- `{}` ({kind})\n", change.path));
+                }
+                for change in changes {
+                    if let Some(diff) = &change.diff {
+                        out.push('\n');
+                        out.push_str(&fenced(diff, "diff"));
+                    }
+                }
+            }
+            TranscriptEntry::ToolCall {
+                server,
+                tool,
+                arguments,
+                result,
+                error,
+                ..
+            } => {
+                out.push_str(&format!("### Tool `{server}/{tool}`\n"));
+                if let Some(arguments) = arguments {
+                    out.push('\n');
+                    out.push_str(&fenced(&pretty_json(arguments), "json"));
+                }
+                if let Some(outcome) = error.as_ref().or(result.as_ref()) {
+                    out.push('\n');
+                    out.push_str(&fenced(&pretty_json(outcome), "json"));
+                }
+            }
+            TranscriptEntry::WebSearch { query, .. } => {
+                out.push_str(&format!("### Web search\n\n{query}\n"));
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn render_transcript(
+    transcript: &ThreadTranscript,
+    format: ExportFormat,
+) -> Result<String, String> {
+    match format {
+        ExportFormat::Markdown => Ok(render_markdown(transcript)),
+        ExportFormat::Json => serde_json::to_string_pretty(transcript).map_err(|e| e.to_string()),
+    }
+}
+
+fn write_export(path: &str, content: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    if path.is_dir() {
+        return Err(format!("{} is a directory", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Exports the full conversation of a thread as Markdown or JSON.
+pub(crate) async fn export_thread_core(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+    format: String,
+    path: Option<String>,
+) -> Result<ThreadExport, String> {
+    let format = ExportFormat::parse(&format)?;
+    let response =
+        codex_core::read_thread_turns_core(sessions, workspace_id.clone(), thread_id.clone())
+            .await?;
+    if let Some(error) = response.get("error") {
+        let error_msg = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error reading thread");
+        return Err(error_msg.to_string());
+    }
+    let transcript = build_transcript(&workspace_id, &thread_id, &response)?;
+    let content = render_transcript(&transcript, format)?;
+    match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => {
+            let written = write_export(&path, &content)?;
+            Ok(ThreadExport {
+                format,
+                path: Some(written.to_string_lossy().to_string()),
+                content: None,
+            })
+        }
+        None => Ok(ThreadExport {
+            format,
+            path: None,
+            content: Some(content),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_response() -> Value {
+        json!({
+            "result": {
+                "thread": {
+                    "id": "thread-1",
+                    "preview": "Fix the build",
+                    "turns": [{
+                        "id": "turn-1",
+                        "items": [
+                            {
+                                "type": "userMessage",
+                                "id": "u1",
+                                "content": [{ "type": "text", "text": "Fix the build" }]
+                            },
+                            { "type": "reasoning", "id": "r1", "summary": ["thinking"] },
+                            {
+                                "type": "commandExecution",
+                                "id": "c1",
+                                "command": "cargo build",
+                                "cwd": "/repo",
+                                "exitCode": 101,
+                                "aggregatedOutput": "error: ```oops```\n"
+                            },
+                            {
+                                "type": "fileChange",
+                                "id": "f1",
+                                "changes": [{
+                                    "path": "src/lib.rs",
+                                    "kind": { "type": "update" },
+                                    "diff": "-a\n+b"
+                                }]
+                            },
+                            { "type": "agentMessage", "id": "a1", "text": "Fixed it." }
+                        ]
+                    }]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn transcript_keeps_conversation_items_in_order() {
+        let transcript = build_transcript("ws-1", "thread-1", &sample_response()).unwrap();
+        assert_eq!(transcript.title.as_deref(), Some("Fix the build"));
+        let kinds: Vec<&str> = transcript
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                TranscriptEntry::User { .. } => "user",
+                TranscriptEntry::Agent { .. } => "agent",
+                TranscriptEntry::Command { .. } => "command",
+                TranscriptEntry::FileChange { .. } => "fileChange",
+                TranscriptEntry::ToolCall { .. } => "toolCall",
+                TranscriptEntry::WebSearch { .. } => "webSearch",
+            })
+            .collect();
+        assert_eq!(kinds, ["user", "command", "fileChange", "agent"]);
+        assert_eq!(
+            transcript.entries[2],
+            TranscriptEntry::FileChange {
+                turn_id: "turn-1".to_string(),
+                changes: vec![FileChangeEntry {
+                    path: "src/lib.rs".to_string(),
+                    kind: Some("update".to_string()),
+                    diff: Some("-a\n+b".to_string()),
+                }],
+            }
+        );
+        let json: Value =
+            serde_json::from_str(&render_transcript(&transcript, ExportFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(json["entries"][1]["kind"], "command");
+        assert_eq!(json["entries"][1]["exitCode"], 101);
+    }
+
+    #[test]
+    fn markdown_fences_outlast_backticks_in_output() {
+        let transcript = build_transcript("ws-1", "thread-1", &sample_response()).unwrap();
+        let markdown = render_markdown(&transcript);
+        assert!(markdown.starts_with("# Fix the build\n"));
+        assert!(markdown.contains("## User\n\nFix the build\n"));
+        assert!(markdown.contains("````\nerror: ```oops```\n````\n"));
+        assert!(markdown.contains("- `src/lib.rs` (update)\n"));
+        assert!(markdown.contains("```diff\n-a\n+b\n```\n"));
+        assert!(markdown.contains("cwd `/repo` · exit code 101"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+const MAX_KEY_CHARS: usize = 64;
+const MAX_VALUE_BYTES: usize = 4 * 1024;
+const MAX_KEYS_PER_THREAD: usize = 32;
+const MAX_THREADS: usize = 5000;
+
+type ThreadMeta = BTreeMap<String, Value>;
+
+/// Free-form per-thread key/value pairs owned by the frontend (colors,
+/// emojis, layout prefs). The backend only enforces size limits.
+pub(crate) struct ThreadMetaStore {
+    path: PathBuf,
+    threads: Mutex<HashMap<String, ThreadMeta>>,
+}
+
+impl ThreadMetaStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let threads = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            threads: Mutex::new(threads),
+        }
+    }
+
+    fn persist(&self, threads: &HashMap<String, ThreadMeta>) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(threads).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+fn validate_entry(key: &str, value: &Value) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Metadata key must not be empty".to_string());
+    }
+    if key.chars().count() > MAX_KEY_CHARS {
+        return Err(format!(
+            "Metadata keys are limited to {MAX_KEY_CHARS} characters"
+        ));
+    }
+    let size = serde_json::to_vec(value).map_err(|e| e.to_string())?.len();
+    if size > MAX_VALUE_BYTES {
+        return Err(format!(
+            "Metadata value for `{key}` is {size} bytes; the limit is {MAX_VALUE_BYTES}"
+        ));
+    }
+    Ok(())
+}
+
+/// Sets `key` on `thread_id`. A `null` value removes the key.
+pub(crate) fn set_thread_meta_core(
+    store: &ThreadMetaStore,
+    thread_id: String,
+    key: String,
+    value: Value,
+) -> Result<ThreadMeta, String> {
+    validate_entry(&key, &value)?;
+    let mut threads = store
+        .threads
+        .lock()
+        .map_err(|_| "thread metadata store is poisoned".to_string())?;
+    if value.is_null() {
+        let Some(meta) = threads.get_mut(&thread_id) else {
+            return Ok(ThreadMeta::new());
+        };
+        if meta.remove(&key).is_none() {
+            return Ok(meta.clone());
+        }
+        let meta = meta.clone();
+        if meta.is_empty() {
+            threads.remove(&thread_id);
+        }
+        store.persist(&threads)?;
+        return Ok(meta);
+    }
+    if !threads.contains_key(&thread_id) && threads.len() >= MAX_THREADS {
+        return Err(format!(
+            "Metadata is stored for at most {MAX_THREADS} threads"
+        ));
+    }
+    let meta = threads.entry(thread_id).or_default();
+    if !meta.contains_key(&key) && meta.len() >= MAX_KEYS_PER_THREAD {
+        return Err(format!(
+            "Threads hold at most {MAX_KEYS_PER_THREAD} metadata keys"
+        ));
+    }
+    meta.insert(key, value);
+    let meta = meta.clone();
+    store.persist(&threads)?;
+    Ok(meta)
+}
+
+pub(crate) fn get_thread_meta_core(store: &ThreadMetaStore, thread_id: &str) -> ThreadMeta {
+    store
+        .threads
+        .lock()
+        .ok()
+        .and_then(|threads| threads.get(thread_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Drops every key for `thread_id`, e.g. after the thread is archived.
+pub(crate) fn clear_thread_meta_core(
+    store: &ThreadMetaStore,
+    thread_id: &str,
+) -> Result<(), String> {
+    let mut threads = store
+        .threads
+        .lock()
+        .map_err(|_| "thread metadata store is poisoned".to_string())?;
+    if threads.remove(thread_id).is_some() {
+        store.persist(&threads)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store() -> (ThreadMetaStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-thread-meta-{}",
+            uuid::Uuid::new_v4()
+        ));
+        (ThreadMetaStore::load(dir.join("thread-meta.json")), dir)
+    }
+
+    #[test]
+    fn values_round_trip_and_null_removes() {
+        let (store, dir) = temp_store();
+        set_thread_meta_core(&store, "t-1".into(), "color".into(), json!("#ff0000"))
+            .expect("set color");
+        set_thread_meta_core(
+            &store,
+            "t-1".into(),
+            "layout".into(),
+            json!({ "split": 0.4 }),
+        )
+        .expect("set layout");
+
+        let reloaded = ThreadMetaStore::load(dir.join("thread-meta.json"));
+        let meta = get_thread_meta_core(&reloaded, "t-1");
+        assert_eq!(meta["color"], json!("#ff0000"));
+        assert_eq!(meta["layout"]["split"], json!(0.4));
+
+        let meta = set_thread_meta_core(&reloaded, "t-1".into(), "color".into(), Value::Null)
+            .expect("remove color");
+        assert!(!meta.contains_key("color"));
+        clear_thread_meta_core(&reloaded, "t-1").expect("clear");
+        assert!(get_thread_meta_core(&reloaded, "t-1").is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn enforces_size_limits() {
+        let (store, dir) = temp_store();
+        let err = set_thread_meta_core(
+            &store,
+            "t-1".into(),
+            "blob".into(),
+            json!("x".repeat(MAX_VALUE_BYTES)),
+        )
+        .expect_err("value too large");
+        assert!(err.contains("limit"));
+        assert!(set_thread_meta_core(&store, "t-1".into(), " ".into(), json!(1)).is_err());
+        for index in 0..MAX_KEYS_PER_THREAD {
+            set_thread_meta_core(&store, "t-1".into(), format!("k{index}"), json!(index))
+                .expect("within key limit");
+        }
+        assert!(set_thread_meta_core(&store, "t-1".into(), "extra".into(), json!(1)).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const MAX_PINNED_THREADS: usize = 50;
+const MAX_ORDERED_THREADS: usize = 1000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadOrder {
+    /// Pinned thread ids, in the order they were pinned.
+    #[serde(default)]
+    pub(crate) pinned: Vec<String>,
+    /// Manual order set by `set_thread_order`; it also orders pinned threads
+    /// among themselves.
+    #[serde(default)]
+    pub(crate) order: Vec<String>,
+}
+
+impl ThreadOrder {
+    fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.order.is_empty()
+    }
+}
+
+/// Pins and manual sidebar order per workspace. The app-server only sorts by
+/// time, so these live next to `workspaces.json`.
+pub(crate) struct ThreadOrderStore {
+    path: PathBuf,
+    workspaces: Mutex<HashMap<String, ThreadOrder>>,
+}
+
+impl ThreadOrderStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let workspaces = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            workspaces: Mutex::new(workspaces),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut HashMap<String, ThreadOrder>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut workspaces = self
+            .workspaces
+            .lock()
+            .map_err(|_| "thread order store is poisoned".to_string())?;
+        let result = apply(&mut workspaces)?;
+        workspaces.retain(|_, order| !order.is_empty());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(&*workspaces).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    fn get(&self, workspace_id: &str) -> ThreadOrder {
+        self.workspaces
+            .lock()
+            .ok()
+            .and_then(|workspaces| workspaces.get(workspace_id).cloned())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn pin_thread_core(
+    store: &ThreadOrderStore,
+    workspace_id: String,
+    thread_id: String,
+    pinned: bool,
+) -> Result<ThreadOrder, String> {
+    store.update(|workspaces| {
+        let order = workspaces.entry(workspace_id).or_default();
+        let already = order.pinned.contains(&thread_id);
+        if pinned && !already {
+            if order.pinned.len() >= MAX_PINNED_THREADS {
+                return Err(format!(
+                    "At most {MAX_PINNED_THREADS} threads can be pinned per workspace"
+                ));
+            }
+            order.pinned.push(thread_id);
+        } else if !pinned && already {
+            order.pinned.retain(|id| id != &thread_id);
+        }
+        Ok(order.clone())
+    })
+}
+
+/// Replaces the workspace's manual order. Threads not listed keep the
+/// app-server's order after the listed ones; an empty list clears it.
+pub(crate) fn set_thread_order_core(
+    store: &ThreadOrderStore,
+    workspace_id: String,
+    thread_ids: Vec<String>,
+) -> Result<ThreadOrder, String> {
+    if thread_ids.len() > MAX_ORDERED_THREADS {
+        return Err(format!(
+            "Thread order is limited to {MAX_ORDERED_THREADS} threads"
+        ));
+    }
+    let mut deduped: Vec<String> = Vec::with_capacity(thread_ids.len());
+    for thread_id in thread_ids {
+        let thread_id = thread_id.trim().to_string();
+        if !thread_id.is_empty() && !deduped.contains(&thread_id) {
+            deduped.push(thread_id);
+        }
+    }
+    store.update(|workspaces| {
+        let order = workspaces.entry(workspace_id).or_default();
+        order.order = deduped;
+        Ok(order.clone())
+    })
+}
+
+/// Forgets an archived thread's pin and position.
+pub(crate) fn clear_thread_order_core(
+    store: &ThreadOrderStore,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<(), String> {
+    store.update(|workspaces| {
+        if let Some(order) = workspaces.get_mut(workspace_id) {
+            order.pinned.retain(|id| id != thread_id);
+            order.order.retain(|id| id != thread_id);
+        }
+        Ok(())
+    })
+}
+
+/// Marks pinned `thread/list` entries with `pinned: true` and moves them to
+/// the top, then manually ordered threads, then the rest in server order.
+/// Sorting happens per page, so the response also carries `pinnedThreadIds`
+/// for pinned threads that are on a later page.
+pub(crate) fn apply_thread_order(
+    store: &ThreadOrderStore,
+    workspace_id: &str,
+    mut response: Value,
+) -> Value {
+    let order = store.get(workspace_id);
+    let nested = response
+        .get("result")
+        .is_some_and(|result| result.get("data").is_some());
+    let container = if nested {
+        response.get_mut("result")
+    } else {
+        Some(&mut response)
+    };
+    let Some(container) = container else {
+        return response;
+    };
+    let Some(Value::Array(items)) = container.get_mut("data") else {
+        return response;
+    };
+    let rank = |thread_id: Option<&str>| {
+        let Some(thread_id) = thread_id else {
+            return (2, usize::MAX);
+        };
+        let position = order.order.iter().position(|id| id == thread_id);
+        match order.pinned.iter().position(|id| id == thread_id) {
+            Some(pin_index) => (0, position.unwrap_or(order.order.len() + pin_index)),
+            None => position.map_or((2, usize::MAX), |position| (1, position)),
+        }
+    };
+    let mut ranked: Vec<_> = items
+        .drain(..)
+        .enumerate()
+        .map(|(index, mut item)| {
+            let (group, position) = rank(item.get("id").and_then(Value::as_str));
+            if let Some(object) = item.as_object_mut() {
+                object.insert("pinned".to_string(), Value::Bool(group == 0));
+            }
+            ((group, position, index), item)
+        })
+        .collect();
+    ranked.sort_by_key(|(key, _)| *key);
+    items.extend(ranked.into_iter().map(|(_, item)| item));
+    if let Some(object) = container.as_object_mut() {
+        object.insert("pinnedThreadIds".to_string(), json!(order.pinned));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_and_manual_order_sort_thread_lists() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-thread-order-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let path = dir.join("thread-order.json");
+        let store = ThreadOrderStore::load(path.clone());
+        pin_thread_core(&store, "ws-1".into(), "t-4".into(), true).expect("pin");
+        pin_thread_core(&store, "ws-1".into(), "t-3".into(), true).expect("pin");
+        set_thread_order_core(&store, "ws-1".into(), vec!["t-3".into(), "t-2".into()])
+            .expect("order");
+
+        let reloaded = ThreadOrderStore::load(path);
+        let response = json!({ "result": {
+            "data": [{ "id": "t-1" }, { "id": "t-2" }, { "id": "t-3" }, { "id": "t-4" }],
+        } });
+        let sorted = apply_thread_order(&reloaded, "ws-1", response);
+        let ids: Vec<_> = sorted["result"]["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["t-3", "t-4", "t-2", "t-1"]);
+        assert_eq!(sorted["result"]["data"][1]["pinned"], json!(true));
+        assert_eq!(sorted["result"]["data"][2]["pinned"], json!(false));
+        assert_eq!(sorted["result"]["pinnedThreadIds"], json!(["t-4", "t-3"]));
+
+        clear_thread_order_core(&reloaded, "ws-1", "t-3").expect("clear");
+        pin_thread_core(&reloaded, "ws-1".into(), "t-4".into(), false).expect("unpin");
+        assert_eq!(
+            reloaded.get("ws-1"),
+            ThreadOrder {
+                pinned: Vec::new(),
+                order: vec!["t-2".to_string()],
+            }
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const MAX_TAG_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ThreadLinkKind {
+    FollowUp,
+    ReviewOf,
+    RetryOf,
+}
+
+impl ThreadLinkKind {
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        serde_json::from_value(Value::String(value.to_string()))
+            .map_err(|_| format!("Unknown thread link kind `{value}`"))
+    }
+}
+
+/// `kind` reads from the linking thread, e.g. a `reviewOf` link on thread B
+/// pointing at thread A means B reviews A.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadLink {
+    pub(crate) thread_id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) kind: ThreadLinkKind,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadAnnotations {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) links: Vec<ThreadLink>,
+}
+
+/// Local-only tags and links keyed by thread id. The app-server has no notion
+/// of either, so they live next to `workspaces.json`.
+pub(crate) struct ThreadTagStore {
+    path: PathBuf,
+    threads: Mutex<HashMap<String, ThreadAnnotations>>,
+}
+
+impl ThreadTagStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let threads = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            threads: Mutex::new(threads),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut HashMap<String, ThreadAnnotations>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut threads = self
+            .threads
+            .lock()
+            .map_err(|_| "thread tag store is poisoned".to_string())?;
+        let result = apply(&mut threads)?;
+        threads.retain(|_, entry| !entry.tags.is_empty() || !entry.links.is_empty());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(&*threads).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    fn snapshot(&self) -> HashMap<String, ThreadAnnotations> {
+        self.threads
+            .lock()
+            .map(|threads| threads.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(format!("Tags are limited to {MAX_TAG_CHARS} characters"));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized.sort();
+    Ok(normalized)
+}
+
+/// Replaces the tags on `thread_id`. An empty list clears them.
+pub(crate) fn tag_thread_core(
+    store: &ThreadTagStore,
+    workspace_id: String,
+    thread_id: String,
+    tags: Vec<String>,
+) -> Result<ThreadAnnotations, String> {
+    let tags = normalize_tags(tags)?;
+    store.update(|threads| {
+        let entry = threads.entry(thread_id).or_default();
+        entry.workspace_id = workspace_id;
+        entry.tags = tags;
+        Ok(entry.clone())
+    })
+}
+
+pub(crate) fn link_threads_core(
+    store: &ThreadTagStore,
+    workspace_id: String,
+    thread_id: String,
+    target: ThreadLink,
+) -> Result<ThreadAnnotations, String> {
+    if target.thread_id == thread_id {
+        return Err("A thread cannot link to itself".to_string());
+    }
+    store.update(|threads| {
+        let entry = threads.entry(thread_id).or_default();
+        entry.workspace_id = workspace_id;
+        if !entry.links.contains(&target) {
+            entry.links.push(target);
+        }
+        Ok(entry.clone())
+    })
+}
+
+pub(crate) fn unlink_threads_core(
+    store: &ThreadTagStore,
+    thread_id: String,
+    target_thread_id: String,
+    kind: Option<ThreadLinkKind>,
+) -> Result<(), String> {
+    store.update(|threads| {
+        if let Some(entry) = threads.get_mut(&thread_id) {
+            entry.links.retain(|link| {
+                link.thread_id != target_thread_id || kind.is_some_and(|kind| link.kind != kind)
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Annotations for threads in `workspace_id`, plus any thread elsewhere that
+/// links into it so the UI can show backlinks.
+pub(crate) fn list_thread_annotations_core(
+    store: &ThreadTagStore,
+    workspace_id: &str,
+) -> HashMap<String, ThreadAnnotations> {
+    store
+        .snapshot()
+        .into_iter()
+        .filter(|(_, entry)| {
+            entry.workspace_id == workspace_id
+                || entry
+                    .links
+                    .iter()
+                    .any(|link| link.workspace_id == workspace_id)
+        })
+        .collect()
+}
+
+/// Drops `thread/list` entries that do not carry `tag`. Filtering happens per
+/// page, so a page may come back shorter than the requested limit.
+pub(crate) fn filter_thread_list_by_tag(
+    store: &ThreadTagStore,
+    mut response: Value,
+    tag: &str,
+) -> Value {
+    let tag = tag.trim().to_lowercase();
+    let threads = store.snapshot();
+    let has_tag = |thread: &Value| {
+        thread
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| threads.get(id))
+            .is_some_and(|entry| entry.tags.contains(&tag))
+    };
+    let data = match response.get_mut("result") {
+        Some(result) if result.get("data").is_some() => result.get_mut("data"),
+        _ => response.get_mut("data"),
+    };
+    if let Some(Value::Array(items)) = data {
+        items.retain(has_tag);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store() -> (ThreadTagStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-thread-tags-{}",
+            uuid::Uuid::new_v4()
+        ));
+        (ThreadTagStore::load(dir.join("thread-tags.json")), dir)
+    }
+
+    #[test]
+    fn tags_and_links_persist_and_filter_lists() {
+        let (store, dir) = temp_store();
+        let tagged = tag_thread_core(
+            &store,
+            "ws-1".into(),
+            "t-1".into(),
+            vec![" Bug ".into(), "bug".into(), "".into(), "infra".into()],
+        )
+        .expect("tag");
+        assert_eq!(tagged.tags, vec!["bug", "infra"]);
+        link_threads_core(
+            &store,
+            "ws-2".into(),
+            "t-2".into(),
+            ThreadLink {
+                thread_id: "t-1".into(),
+                workspace_id: "ws-1".into(),
+                kind: ThreadLinkKind::ReviewOf,
+            },
+        )
+        .expect("link");
+
+        let reloaded = ThreadTagStore::load(dir.join("thread-tags.json"));
+        let annotations = list_thread_annotations_core(&reloaded, "ws-1");
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations["t-2"].links[0].kind, ThreadLinkKind::ReviewOf);
+
+        let response = json!({ "result": { "data": [{ "id": "t-1" }, { "id": "t-3" }] } });
+        let filtered = filter_thread_list_by_tag(&reloaded, response, "BUG");
+        assert_eq!(filtered["result"]["data"], json!([{ "id": "t-1" }]));
+
+        unlink_threads_core(&reloaded, "t-2".into(), "t-1".into(), None).expect("unlink");
+        tag_thread_core(&reloaded, "ws-1".into(), "t-1".into(), Vec::new()).expect("clear");
+        assert!(list_thread_annotations_core(&reloaded, "ws-1").is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_self_links_and_unknown_kinds() {
+        let (store, dir) = temp_store();
+        let err = link_threads_core(
+            &store,
+            "ws-1".into(),
+            "t-1".into(),
+            ThreadLink {
+                thread_id: "t-1".into(),
+                workspace_id: "ws-1".into(),
+                kind: ThreadLinkKind::FollowUp,
+            },
+        )
+        .expect_err("self link");
+        assert!(err.contains("itself"));
+        assert_eq!(
+            ThreadLinkKind::parse("retryOf"),
+            Ok(ThreadLinkKind::RetryOf)
+        );
+        assert!(ThreadLinkKind::parse("duplicateOf").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::types::WorkspaceEntry;
+
+/// Older focus records are dropped past this many workspaces.
+const MAX_TRACKED_WORKSPACES: usize = 200;
+const MAX_DEVICE_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFocus {
+    pub(crate) workspace_id: String,
+    /// Milliseconds since the epoch, by the clock of whoever stores it.
+    pub(crate) focused_at: u64,
+    /// The device that last focused the workspace, e.g. `desktop`, `phone`.
+    #[serde(default)]
+    pub(crate) device: Option<String>,
+    #[serde(default)]
+    pub(crate) focus_count: u64,
+}
+
+/// Most recently focused workspaces, newest first. In remote mode the
+/// daemon's store is used so the order is the same on every device.
+pub(crate) struct WorkspaceRecencyStore {
+    path: PathBuf,
+    entries: Mutex<Vec<WorkspaceFocus>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl WorkspaceRecencyStore {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    fn persist(&self, entries: &[WorkspaceFocus]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+
+    fn record(
+        &self,
+        workspace_id: &str,
+        device: Option<String>,
+        now: u64,
+    ) -> Result<WorkspaceFocus, String> {
+        let device = device
+            .map(|device| {
+                device
+                    .trim()
+                    .chars()
+                    .take(MAX_DEVICE_CHARS)
+                    .collect::<String>()
+            })
+            .filter(|device| !device.is_empty());
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| "workspace recency store is poisoned".to_string())?;
+        let focus_count = match entries
+            .iter()
+            .position(|entry| entry.workspace_id == workspace_id)
+        {
+            Some(index) => entries.remove(index).focus_count,
+            None => 0,
+        };
+        let focus = WorkspaceFocus {
+            workspace_id: workspace_id.to_string(),
+            // Never move backwards, even if this clock is behind the
+            // previous writer's.
+            focused_at: entries
+                .first()
+                .map_or(now, |newest| now.max(newest.focused_at)),
+            device,
+            focus_count: focus_count + 1,
+        };
+        entries.insert(0, focus.clone());
+        entries.truncate(MAX_TRACKED_WORKSPACES);
+        self.persist(&entries)?;
+        Ok(focus)
+    }
+
+    fn snapshot(&self) -> Vec<WorkspaceFocus> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) async fn record_workspace_focus_core(
+    store: &WorkspaceRecencyStore,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    device: Option<String>,
+) -> Result<WorkspaceFocus, String> {
+    if !workspaces.read().await.contains_key(workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    store.record(workspace_id, device, now_ms())
+}
+
+/// Focused workspaces that still exist, newest first.
+pub(crate) async fn recent_workspaces_core(
+    store: &WorkspaceRecencyStore,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    limit: Option<usize>,
+) -> Vec<WorkspaceFocus> {
+    let workspaces = workspaces.read().await;
+    store
+        .snapshot()
+        .into_iter()
+        .filter(|entry| workspaces.contains_key(&entry.workspace_id))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_moves_a_workspace_to_the_front_and_persists() {
+        let dir =
+            std::env::temp_dir().join(format!("codex-monitor-recency-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("workspace-recency.json");
+        let store = WorkspaceRecencyStore::load(path.clone());
+        store
+            .record("ws-1", Some("desktop".to_string()), 1_000)
+            .unwrap();
+        store.record("ws-2", None, 2_000).unwrap();
+        let focus = store
+            .record("ws-1", Some(" phone ".to_string()), 1_500)
+            .unwrap();
+        assert_eq!(focus.focused_at, 2_000);
+        assert_eq!(focus.focus_count, 2);
+        assert_eq!(focus.device.as_deref(), Some("phone"));
+
+        let reloaded = WorkspaceRecencyStore::load(path);
+        let order: Vec<_> = reloaded
+            .snapshot()
+            .into_iter()
+            .map(|entry| entry.workspace_id)
+            .collect();
+        assert_eq!(order, ["ws-1", "ws-2"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
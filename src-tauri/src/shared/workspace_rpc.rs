@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -19,6 +21,105 @@ pub(crate) fn from_params<T: DeserializeOwned>(params: &Value) -> Result<T, Stri
 pub(crate) struct ReadWorkspaceFileRequest {
     pub(crate) workspace_id: String,
     pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) offset: Option<u64>,
+    #[serde(default)]
+    pub(crate) length: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WriteWorkspaceFileRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) path: String,
+    pub(crate) content: String,
+    pub(crate) expected_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApplyWorkspacePatchRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) path: String,
+    pub(crate) patch: String,
+    pub(crate) expected_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UploadFileRequest {
+    pub(crate) workspace_id: String,
+    /// Omitted for a temporary upload outside the workspace.
+    #[serde(default)]
+    pub(crate) relative_path: Option<String>,
+    #[serde(default)]
+    pub(crate) file_name: Option<String>,
+    pub(crate) bytes_base64: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DownloadFileRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) relative_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ListWorkspaceFilesRequest {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) refresh: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ListWorkspaceFilesPageRequest {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) refresh: bool,
+    pub(crate) offset: Option<usize>,
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AttachContextRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ListWorkspaceDirRequest {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordWorkspaceFocusRequest {
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) device: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecentWorkspacesRequest {
+    #[serde(default)]
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchWorkspaceFilesRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) query: String,
+    #[serde(default)]
+    pub(crate) regex: bool,
+    pub(crate) max_results: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,17 +155,71 @@ pub(crate) struct AddWorktreeRequest {
     pub(crate) copy_agents_md: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BroadcastRunRequest {
+    pub(crate) workspace_ids: Vec<String>,
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) worktree_branch: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SelectWinnerRequest {
+    pub(crate) run_id: String,
+    pub(crate) worktree_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunPlaybookRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) playbook_id: String,
+    #[serde(default)]
+    pub(crate) variables: Option<HashMap<String, String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct WorkspaceIdRequest {
     pub(crate) workspace_id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PackageReleaseRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) task: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SuggestBranchNameRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) base: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunAndAttachRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) command: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct IdRequest {
     pub(crate) id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationIdRequest {
+    pub(crate) operation_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct RenameWorktreeRequest {
     pub(crate) id: String,
@@ -79,12 +234,25 @@ pub(crate) struct RenameWorktreeUpstreamRequest {
     pub(crate) new_branch: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CompareWorktreesRequest {
+    pub(crate) a: String,
+    pub(crate) b: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct UpdateWorkspaceSettingsRequest {
     pub(crate) id: String,
     pub(crate) settings: WorkspaceSettings,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdateWorkspacesBulkRequest {
+    pub(crate) ids: Vec<String>,
+    pub(crate) partial_settings: Value,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct AddCloneRequest {
@@ -0,0 +1,467 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::backend::events::AppServerEvent;
+use crate::backend::services::BackendServices;
+use crate::shared::git_core::run_git_command;
+use crate::types::WorkspaceEntry;
+
+const README_NAMES: &[&str] = &[
+    "README.md",
+    "README.markdown",
+    "README.rst",
+    "README.txt",
+    "README",
+    "readme.md",
+];
+const README_HEAD_LINES: usize = 40;
+const README_HEAD_BYTES: usize = 4 * 1024;
+const MAX_LANGUAGES: usize = 8;
+const MAX_SCANNED_FILES: usize = 20_000;
+const RECENT_COMMITS: usize = 10;
+/// Language counts drift without touching HEAD or the root directory, so even
+/// an unchanged fingerprint is recomputed after this long.
+const MAX_CACHE_AGE: Duration = Duration::from_secs(10 * 60);
+
+const BUILD_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Cargo"),
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "Yarn"),
+    ("bun.lockb", "Bun"),
+    ("package-lock.json", "npm"),
+    ("pyproject.toml", "Python (pyproject)"),
+    ("requirements.txt", "pip"),
+    ("go.mod", "Go modules"),
+    ("build.gradle", "Gradle"),
+    ("build.gradle.kts", "Gradle"),
+    ("pom.xml", "Maven"),
+    ("CMakeLists.txt", "CMake"),
+    ("Makefile", "Make"),
+    ("Gemfile", "Bundler"),
+    ("mix.exs", "Mix"),
+    ("Package.swift", "SwiftPM"),
+    ("composer.json", "Composer"),
+    ("deno.json", "Deno"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadmeHead {
+    /// Relative to the workspace root.
+    pub(crate) path: String,
+    pub(crate) head: String,
+    pub(crate) truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LanguageShare {
+    pub(crate) language: String,
+    pub(crate) files: usize,
+    /// Share of the recognised source files, rounded to a whole percent.
+    pub(crate) percent: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommitSubject {
+    pub(crate) sha: String,
+    pub(crate) subject: String,
+    pub(crate) author: String,
+    /// Seconds since the epoch.
+    pub(crate) timestamp: i64,
+}
+
+/// The parts of the summary read from disk and git, which are cached.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct RepoSummary {
+    readme: Option<ReadmeHead>,
+    languages: Vec<LanguageShare>,
+    build_systems: Vec<String>,
+    recent_commits: Vec<CommitSubject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSummary {
+    pub(crate) workspace_id: String,
+    pub(crate) readme: Option<ReadmeHead>,
+    pub(crate) languages: Vec<LanguageShare>,
+    pub(crate) build_systems: Vec<String>,
+    pub(crate) recent_commits: Vec<CommitSubject>,
+    /// Threads this app has seen in the workspace.
+    pub(crate) thread_count: usize,
+    pub(crate) active_turns: usize,
+    pub(crate) queued_turns: usize,
+    /// Turns recorded by the run metrics, when they are available.
+    pub(crate) completed_turns: Option<u64>,
+    /// When the cached repository details were read (ms since the epoch).
+    pub(crate) generated_at: u64,
+}
+
+struct CachedSummary {
+    fingerprint: String,
+    generated_at: u64,
+    repo: RepoSummary,
+}
+
+/// Repository details per workspace, reused while HEAD, the README and the
+/// root directory are unchanged. `invalidate` drops an entry early so edits
+/// show up without waiting for the fingerprint to move.
+#[derive(Default)]
+pub(crate) struct WorkspaceSummaryCache {
+    entries: Mutex<HashMap<String, CachedSummary>>,
+}
+
+impl WorkspaceSummaryCache {
+    pub(crate) fn invalidate(&self, workspace_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(workspace_id);
+        }
+    }
+
+    /// Drops the workspace's entry when a turn completes, since turns are
+    /// what usually change the tree, or when its file watcher saw changes.
+    pub(crate) fn record(&self, event: &AppServerEvent) {
+        if matches!(
+            event
+                .message
+                .get("method")
+                .and_then(|method| method.as_str()),
+            Some("turn/completed" | "workspace/filesChanged")
+        ) {
+            self.invalidate(&event.workspace_id);
+        }
+    }
+
+    fn get(&self, workspace_id: &str, fingerprint: &str) -> Option<(u64, RepoSummary)> {
+        let entries = self.entries.lock().ok()?;
+        let cached = entries.get(workspace_id)?;
+        let age = now_ms().saturating_sub(cached.generated_at);
+        (cached.fingerprint == fingerprint && age < MAX_CACHE_AGE.as_millis() as u64)
+            .then(|| (cached.generated_at, cached.repo.clone()))
+    }
+
+    fn put(&self, workspace_id: &str, fingerprint: String, generated_at: u64, repo: RepoSummary) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                workspace_id.to_string(),
+                CachedSummary {
+                    fingerprint,
+                    generated_at,
+                    repo,
+                },
+            );
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn mtime_ms(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn find_readme(root: &Path) -> Option<PathBuf> {
+    README_NAMES
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+}
+
+fn readme_head(root: &Path, path: &Path) -> Option<ReadmeHead> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut head = String::new();
+    let mut truncated = false;
+    for (index, line) in content.lines().enumerate() {
+        if index >= README_HEAD_LINES || head.len() + line.len() + 1 > README_HEAD_BYTES {
+            truncated = true;
+            break;
+        }
+        head.push_str(line);
+        head.push('\n');
+    }
+    Some(ReadmeHead {
+        path: path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned(),
+        head: head.trim_end().to_string(),
+        truncated,
+    })
+}
+
+fn language_for(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "ex" | "exs" => "Elixir",
+        "scala" => "Scala",
+        "dart" => "Dart",
+        "lua" => "Lua",
+        "sh" | "bash" | "zsh" => "Shell",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "html" | "htm" => "HTML",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "sql" => "SQL",
+        _ => return None,
+    })
+}
+
+/// Share of recognised source files per language, largest first. Honours
+/// `.gitignore` and stops after `MAX_SCANNED_FILES` files.
+fn detect_languages(root: &Path) -> Vec<LanguageShare> {
+    let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let walker = WalkBuilder::new(root)
+        .follow_links(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            !matches!(
+                entry.file_name().to_str(),
+                Some(".git" | "node_modules" | "target" | "dist" | "vendor")
+            )
+        })
+        .build();
+    for entry in walker
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .take(MAX_SCANNED_FILES)
+    {
+        if let Some(language) = language_for(entry.path()) {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+    let total: usize = counts.values().sum();
+    let mut languages: Vec<LanguageShare> = counts
+        .into_iter()
+        .map(|(language, files)| LanguageShare {
+            language: language.to_string(),
+            files,
+            percent: ((files * 100) as f64 / total as f64).round() as u32,
+        })
+        .collect();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+    languages.truncate(MAX_LANGUAGES);
+    languages
+}
+
+fn detect_build_systems(root: &Path) -> Vec<String> {
+    let mut systems: Vec<String> = Vec::new();
+    for (marker, system) in BUILD_MARKERS {
+        if root.join(marker).is_file() && !systems.iter().any(|known| known == system) {
+            systems.push(system.to_string());
+        }
+    }
+    let has_js_tool = systems
+        .iter()
+        .any(|system| matches!(system.as_str(), "pnpm" | "Yarn" | "Bun" | "npm"));
+    if !has_js_tool && root.join("package.json").is_file() {
+        systems.push("npm".to_string());
+    }
+    systems
+}
+
+fn parse_commits(output: &str) -> Vec<CommitSubject> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            let sha = fields.next()?.to_string();
+            let subject = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let timestamp = fields.next()?.trim().parse().ok()?;
+            Some(CommitSubject {
+                sha,
+                subject,
+                author,
+                timestamp,
+            })
+        })
+        .collect()
+}
+
+async fn fingerprint(root: &PathBuf) -> String {
+    let head = run_git_command(root, &["rev-parse", "HEAD"])
+        .await
+        .unwrap_or_default();
+    let readme_mtime = find_readme(root).map(|path| mtime_ms(&path)).unwrap_or(0);
+    format!("{}:{}:{}", head.trim(), readme_mtime, mtime_ms(root))
+}
+
+async fn read_repo_summary(root: PathBuf) -> RepoSummary {
+    let count = RECENT_COMMITS.to_string();
+    let recent_commits = run_git_command(
+        &root,
+        &["log", "-n", &count, "--format=%h%x1f%s%x1f%an%x1f%ct"],
+    )
+    .await
+    .map(|output| parse_commits(&output))
+    .unwrap_or_default();
+    let scan_root = root.clone();
+    let (readme, languages, build_systems) = tokio::task::spawn_blocking(move || {
+        let readme = find_readme(&scan_root).and_then(|path| readme_head(&scan_root, &path));
+        (
+            readme,
+            detect_languages(&scan_root),
+            detect_build_systems(&scan_root),
+        )
+    })
+    .await
+    .unwrap_or_default();
+    RepoSummary {
+        readme,
+        languages,
+        build_systems,
+        recent_commits,
+    }
+}
+
+/// README head, languages, build systems and recent commits (cached), plus
+/// live thread and turn counts for the workspace home screen.
+pub(crate) async fn workspace_summary_core(
+    services: &BackendServices,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    cache: &WorkspaceSummaryCache,
+    workspace_id: String,
+) -> Result<WorkspaceSummary, String> {
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let root = PathBuf::from(&entry.path);
+    if !root.is_dir() {
+        return Err(format!("Workspace path does not exist: {}", entry.path));
+    }
+    let fingerprint = fingerprint(&root).await;
+    let (generated_at, repo) = match cache.get(&workspace_id, &fingerprint) {
+        Some(cached) => cached,
+        None => {
+            let generated_at = now_ms();
+            let repo = read_repo_summary(root).await;
+            cache.put(&workspace_id, fingerprint, generated_at, repo.clone());
+            (generated_at, repo)
+        }
+    };
+
+    let thread_count = services
+        .thread_routes
+        .workspace_map()
+        .values()
+        .filter(|id| **id == workspace_id)
+        .count();
+    let queued_turns = services
+        .run_manager
+        .snapshot()
+        .queued
+        .iter()
+        .filter(|run| run.workspace_id == workspace_id)
+        .count();
+    let completed_turns = services.usage_metrics.completed_turns(&workspace_id).ok();
+    Ok(WorkspaceSummary {
+        active_turns: services.turn_tracker.list(Some(&workspace_id)).len(),
+        workspace_id,
+        readme: repo.readme,
+        languages: repo.languages,
+        build_systems: repo.build_systems,
+        recent_commits: repo.recent_commits,
+        thread_count,
+        queued_turns,
+        completed_turns,
+        generated_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_repo() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("summary-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("src")).expect("create temp repo");
+        root
+    }
+
+    #[test]
+    fn detects_languages_build_systems_and_readme() {
+        let root = temp_repo();
+        fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("src/app.ts"), "").unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+        let readme = (1..=50).map(|n| format!("line {n}\n")).collect::<String>();
+        fs::write(root.join("README.md"), readme).unwrap();
+
+        assert_eq!(detect_build_systems(&root), vec!["Cargo", "npm"]);
+        let languages = detect_languages(&root);
+        assert_eq!(
+            languages
+                .iter()
+                .map(|share| (share.language.as_str(), share.files, share.percent))
+                .collect::<Vec<_>>(),
+            vec![("Rust", 2, 67), ("TypeScript", 1, 33)]
+        );
+        let head = readme_head(&root, &find_readme(&root).unwrap()).unwrap();
+        assert_eq!(head.path, "README.md");
+        assert!(head.truncated);
+        assert_eq!(head.head.lines().count(), README_HEAD_LINES);
+
+        fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn parses_commit_log_and_caches_by_fingerprint() {
+        let commits = parse_commits("abc1234\x1fFix: a|b\x1fAda\x1f1700000000\nbroken\n");
+        assert_eq!(
+            commits,
+            vec![CommitSubject {
+                sha: "abc1234".to_string(),
+                subject: "Fix: a|b".to_string(),
+                author: "Ada".to_string(),
+                timestamp: 1_700_000_000,
+            }]
+        );
+
+        let cache = WorkspaceSummaryCache::default();
+        cache.put("ws", "head:1".to_string(), now_ms(), RepoSummary::default());
+        assert!(cache.get("ws", "head:1").is_some());
+        assert!(cache.get("ws", "head:2").is_none());
+        cache.invalidate("ws");
+        assert!(cache.get("ws", "head:1").is_none());
+    }
+}
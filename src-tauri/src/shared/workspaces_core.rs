@@ -6,21 +6,21 @@ mod io;
 mod runtime_codex_args;
 mod worktree;
 
-pub(crate) use connect::connect_workspace_core;
+pub(crate) use connect::{connect_workspace_core, disconnect_all_sessions_core};
 pub(crate) use crud_persistence::{
     add_clone_core, add_workspace_core, add_workspace_from_git_url_core, remove_workspace_core,
-    update_workspace_settings_core,
+    update_workspace_settings_core, update_workspaces_bulk_core,
 };
 pub(crate) use git_orchestration::{apply_worktree_changes_core, run_git_command_unit};
-pub(crate) use helpers::{is_workspace_path_dir_core, list_workspaces_core};
-pub(crate) use io::{
-    get_open_app_icon_core, list_workspace_files_core, open_workspace_in_core,
-    read_workspace_file_core,
+pub(crate) use helpers::{
+    ensure_workspace_writable_core, is_workspace_path_dir_core, list_workspaces_core,
+    sync_request_timeouts_core,
 };
+pub(crate) use io::{get_open_app_icon_core, open_workspace_in_core, read_workspace_file_core};
 pub(crate) use runtime_codex_args::{
     set_workspace_runtime_codex_args_core, WorkspaceRuntimeCodexArgsResult,
 };
 pub(crate) use worktree::{
     add_worktree_core, remove_worktree_core, rename_worktree_core, rename_worktree_upstream_core,
-    worktree_setup_mark_ran_core, worktree_setup_status_core,
+    suggest_branch_name_core, worktree_setup_mark_ran_core, worktree_setup_status_core,
 };
@@ -4,15 +4,16 @@ use std::path::PathBuf;
 use std::sync::OnceLock;
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::services::BackendServices;
 use crate::codex::args::resolve_workspace_codex_args;
 use crate::codex::home::resolve_workspace_codex_home;
 use crate::shared::process_core::kill_child_process_tree;
 use crate::types::{AppSettings, WorkspaceEntry};
 
-use super::helpers::resolve_entry_and_parent;
+use super::helpers::{resolve_entry_and_parent, sync_request_timeouts_core};
 
 static CONNECT_WORKSPACE_SPAWN_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
@@ -26,20 +27,23 @@ async fn session_process_is_alive(session: &Arc<WorkspaceSession>) -> bool {
 }
 
 async fn remove_session_references(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     session: &Arc<WorkspaceSession>,
 ) {
-    let mut sessions = sessions.lock().await;
+    let mut sessions = sessions.write().await;
     sessions.retain(|_, candidate| !Arc::ptr_eq(candidate, session));
 }
 
 pub(super) async fn take_live_shared_session(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
 ) -> Option<Arc<WorkspaceSession>> {
     loop {
         let existing_session = {
-            let sessions = sessions.lock().await;
-            sessions.values().next().cloned()
+            let sessions = sessions.read().await;
+            sessions
+                .values()
+                .find(|session| session.backend.kind().shares_session() && !session.isolated_env)
+                .cloned()
         };
         let Some(existing_session) = existing_session else {
             return None;
@@ -53,8 +57,9 @@ pub(super) async fn take_live_shared_session(
 
 pub(crate) async fn connect_workspace_core<F, Fut>(
     workspace_id: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
     app_settings: &Mutex<AppSettings>,
     spawn_session: F,
 ) -> Result<(), String>
@@ -63,9 +68,12 @@ where
     Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
 {
     let (entry, parent_entry) = resolve_entry_and_parent(workspaces, &workspace_id).await?;
+    services
+        .session_quotas
+        .set_quota(&entry.id, entry.settings.quota.clone());
     let _spawn_guard = workspace_session_spawn_lock().lock().await;
     if let Some(existing_for_entry) = {
-        let sessions = sessions.lock().await;
+        let sessions = sessions.read().await;
         sessions.get(&entry.id).cloned()
     } {
         if session_process_is_alive(&existing_for_entry).await {
@@ -73,14 +81,21 @@ where
         }
         remove_session_references(sessions, &existing_for_entry).await;
     }
-    if let Some(existing_session) = take_live_shared_session(sessions).await {
+    let agent_backend = entry.settings.agent_backend.unwrap_or_default();
+    let shared_session = if agent_backend.shares_session() && entry.settings.env.is_empty() {
+        take_live_shared_session(sessions).await
+    } else {
+        None
+    };
+    if let Some(existing_session) = shared_session {
         existing_session
             .register_workspace_with_path(&entry.id, Some(&entry.path))
             .await;
         sessions
-            .lock()
+            .write()
             .await
             .insert(entry.id.clone(), existing_session);
+        sync_request_timeouts_core(workspaces, sessions, app_settings).await;
         return Ok(());
     }
     let (default_bin, codex_args) = {
@@ -95,16 +110,17 @@ where
     session
         .register_workspace_with_path(&entry.id, Some(&entry.path))
         .await;
-    sessions.lock().await.insert(entry.id, session);
+    sessions.write().await.insert(entry.id, session);
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
     Ok(())
 }
 
 pub(super) async fn kill_session_by_id(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     id: &str,
 ) {
     let (removed, still_referenced) = {
-        let mut sessions = sessions.lock().await;
+        let mut sessions = sessions.write().await;
         let removed = sessions.remove(id);
         let still_referenced = removed.as_ref().is_some_and(|session| {
             sessions
@@ -123,6 +139,18 @@ pub(super) async fn kill_session_by_id(
     }
 }
 
+/// Stops every local session, e.g. before handing the workspaces to a
+/// daemon. Returns how many workspaces were disconnected.
+pub(crate) async fn disconnect_all_sessions_core(
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+) -> usize {
+    let ids: Vec<String> = sessions.read().await.keys().cloned().collect();
+    for id in &ids {
+        kill_session_by_id(sessions, id).await;
+    }
+    ids.len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +163,7 @@ mod tests {
     use tokio::process::Command;
     use tokio::sync::Mutex;
 
+    use crate::backend::agent::default_agent_backend;
     use crate::types::{WorkspaceKind, WorkspaceSettings};
 
     fn make_workspace_entry(id: &str) -> WorkspaceEntry {
@@ -171,8 +200,8 @@ mod tests {
             codex_args: None,
             child: Mutex::new(child),
             stdin: Mutex::new(stdin),
-            pending: Mutex::new(HashMap::new()),
-            request_context: Mutex::new(HashMap::new()),
+            pending: Default::default(),
+            request_context: Default::default(),
             thread_workspace: Mutex::new(HashMap::new()),
             hidden_thread_ids: Mutex::new(HashSet::new()),
             next_id: AtomicU64::new(0),
@@ -180,6 +209,10 @@ mod tests {
             owner_workspace_id: "test-owner".to_string(),
             workspace_ids: Mutex::new(HashSet::from(["test-owner".to_string()])),
             workspace_roots: Mutex::new(HashMap::new()),
+            backend: default_agent_backend(),
+            request_timeouts: Default::default(),
+            isolated_env: false,
+            services: BackendServices::for_tests(),
         })
     }
 
@@ -187,8 +220,8 @@ mod tests {
     fn connect_workspace_is_noop_when_already_connected() {
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             let entry = make_workspace_entry("ws-1");
-            let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
-            let sessions = Mutex::new(HashMap::from([(
+            let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+            let sessions = RwLock::new(HashMap::from([(
                 entry.id.clone(),
                 make_session(entry.clone()),
             )]));
@@ -200,6 +233,7 @@ mod tests {
                 entry.id.clone(),
                 &workspaces,
                 &sessions,
+                &BackendServices::for_tests(),
                 &app_settings,
                 move |_entry, _default_bin, _codex_args, _codex_home| {
                     let spawn_calls_ref = spawn_calls_ref.clone();
@@ -221,8 +255,8 @@ mod tests {
     fn connect_workspace_spawns_when_not_connected() {
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             let entry = make_workspace_entry("ws-2");
-            let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
-            let sessions = Mutex::new(HashMap::<String, Arc<WorkspaceSession>>::new());
+            let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+            let sessions = RwLock::new(HashMap::<String, Arc<WorkspaceSession>>::new());
             let app_settings = Mutex::new(AppSettings::default());
             let spawn_calls = Arc::new(AtomicUsize::new(0));
             let spawn_calls_ref = spawn_calls.clone();
@@ -232,6 +266,7 @@ mod tests {
                 entry.id.clone(),
                 &workspaces,
                 &sessions,
+                &BackendServices::for_tests(),
                 &app_settings,
                 move |_entry, _default_bin, _codex_args, _codex_home| {
                     let spawn_calls_ref = spawn_calls_ref.clone();
@@ -246,7 +281,7 @@ mod tests {
             .expect("connect should spawn");
 
             assert_eq!(spawn_calls.load(Ordering::SeqCst), 1);
-            assert!(sessions.lock().await.contains_key(&entry.id));
+            assert!(sessions.read().await.contains_key(&entry.id));
             kill_session_by_id(&sessions, &entry.id).await;
         });
     }
@@ -3,27 +3,34 @@ use std::future::Future;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
+use serde_json::Value;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::services::BackendServices;
 use crate::codex::args::resolve_workspace_codex_args;
 use crate::codex::home::resolve_workspace_codex_home;
+use crate::shared::file_watch_core::unwatch_workspace_files_core;
 use crate::shared::process_core::kill_child_process_tree;
+use crate::shared::worktree_lock_core::prepare_locked_worktree;
 use crate::shared::{git_core, worktree_core};
-use crate::storage::write_workspaces;
+use crate::storage::{write_workspaces, StoreWriteOptions};
 use crate::types::{AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings};
-use crate::utils::normalize_windows_namespace_path;
 
-use super::connect::{kill_session_by_id, take_live_shared_session, workspace_session_spawn_lock};
+use super::connect::{
+    connect_workspace_core, kill_session_by_id, take_live_shared_session,
+    workspace_session_spawn_lock,
+};
 use super::helpers::{
-    normalize_setup_script, normalize_workspace_path_input, workspace_path_to_string,
+    apply_workspace_settings_patch, normalize_workspace_path_input, normalize_workspace_settings,
+    sync_request_timeouts_core, workspace_path_to_string,
 };
 
 pub(crate) async fn add_workspace_core<F, Fut>(
     path: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     spawn_session: F,
@@ -73,13 +80,15 @@ where
     };
 
     if let Err(error) = {
-        let mut workspaces = workspaces.lock().await;
+        let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+        let mut workspaces = workspaces.write().await;
         workspaces.insert(entry.id.clone(), entry.clone());
+        let workspaces = workspaces.downgrade();
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)
+        write_workspaces(storage_path, &list, store_options).await
     } {
         {
-            let mut workspaces = workspaces.lock().await;
+            let mut workspaces = workspaces.write().await;
             workspaces.remove(&entry.id);
         }
         if spawned_new_session {
@@ -92,7 +101,8 @@ where
     session
         .register_workspace_with_path(&entry.id, Some(&entry.path))
         .await;
-    sessions.lock().await.insert(entry.id.clone(), session);
+    sessions.write().await.insert(entry.id.clone(), session);
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
 
     Ok(WorkspaceInfo {
         id: entry.id,
@@ -110,8 +120,8 @@ pub(crate) async fn add_clone_core<F, Fut>(
     source_workspace_id: String,
     copy_name: String,
     copies_folder: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     spawn_session: F,
@@ -137,7 +147,7 @@ where
     }
 
     let (source_entry, inherited_group_id) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let source_entry = workspaces
             .get(&source_workspace_id)
             .cloned()
@@ -227,13 +237,15 @@ where
     };
 
     if let Err(error) = {
-        let mut workspaces = workspaces.lock().await;
+        let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+        let mut workspaces = workspaces.write().await;
         workspaces.insert(entry.id.clone(), entry.clone());
+        let workspaces = workspaces.downgrade();
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)
+        write_workspaces(storage_path, &list, store_options).await
     } {
         {
-            let mut workspaces = workspaces.lock().await;
+            let mut workspaces = workspaces.write().await;
             workspaces.remove(&entry.id);
         }
         if spawned_new_session {
@@ -247,7 +259,8 @@ where
     session
         .register_workspace_with_path(&entry.id, Some(&entry.path))
         .await;
-    sessions.lock().await.insert(entry.id.clone(), session);
+    sessions.write().await.insert(entry.id.clone(), session);
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
 
     Ok(WorkspaceInfo {
         id: entry.id,
@@ -302,8 +315,8 @@ pub(crate) async fn add_workspace_from_git_url_core<F, Fut>(
     url: String,
     destination_path: String,
     target_folder_name: Option<String>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     spawn_session: F,
@@ -392,13 +405,15 @@ where
     };
 
     if let Err(error) = {
-        let mut workspaces = workspaces.lock().await;
+        let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+        let mut workspaces = workspaces.write().await;
         workspaces.insert(entry.id.clone(), entry.clone());
+        let workspaces = workspaces.downgrade();
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)
+        write_workspaces(storage_path, &list, store_options).await
     } {
         {
-            let mut workspaces = workspaces.lock().await;
+            let mut workspaces = workspaces.write().await;
             workspaces.remove(&entry.id);
         }
         if spawned_new_session {
@@ -412,7 +427,8 @@ where
     session
         .register_workspace_with_path(&entry.id, Some(&entry.path))
         .await;
-    sessions.lock().await.insert(entry.id.clone(), session);
+    sessions.write().await.insert(entry.id.clone(), session);
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
 
     Ok(WorkspaceInfo {
         id: entry.id,
@@ -428,9 +444,11 @@ where
 
 pub(crate) async fn remove_workspace_core<FRunGit, FutRunGit, FIsMissing, FRemoveDirAll>(
     id: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
     storage_path: &PathBuf,
+    store_options: StoreWriteOptions,
     run_git_command: FRunGit,
     is_missing_worktree_error: FIsMissing,
     remove_dir_all: FRemoveDirAll,
@@ -444,7 +462,7 @@ where
     FRemoveDirAll: Fn(&PathBuf) -> Result<(), String>,
 {
     let (entry, child_worktrees) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces
             .get(&id)
             .cloned()
@@ -469,6 +487,15 @@ where
         kill_session_by_id(sessions, &child.id).await;
 
         let child_path = PathBuf::from(&child.path);
+        if child_path.exists() && repo_path_exists {
+            if let Err(error) = prepare_locked_worktree(&child_path) {
+                if continue_on_child_error {
+                    failures.push((child.id.clone(), error));
+                    continue;
+                }
+                return Err(error);
+            }
+        }
         if child_path.exists() {
             if !repo_path_exists {
                 if let Err(fs_error) = remove_dir_all(&child_path) {
@@ -514,12 +541,14 @@ where
     }
 
     {
-        let mut workspaces = workspaces.lock().await;
+        let mut workspaces = workspaces.write().await;
         for workspace_id in ids_to_remove {
+            unwatch_workspace_files_core(services, &workspace_id);
             workspaces.remove(&workspace_id);
         }
+        let workspaces = workspaces.downgrade();
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
+        write_workspaces(storage_path, &list, store_options).await?;
     }
 
     if failures.is_empty() {
@@ -540,10 +569,11 @@ where
 
 pub(crate) async fn update_workspace_settings_core<FApplySettings, FSpawn, FutSpawn>(
     id: String,
-    mut settings: WorkspaceSettings,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    _app_settings: &Mutex<AppSettings>,
+    settings: WorkspaceSettings,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
+    app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     apply_settings_update: FApplySettings,
     _spawn_session: FSpawn,
@@ -557,34 +587,37 @@ where
     FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
     FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
 {
-    settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
-    settings.worktrees_folder = settings
-        .worktrees_folder
-        .map(|path| normalize_windows_namespace_path(&path));
+    let settings = normalize_workspace_settings(settings)?;
 
-    let (entry_snapshot, previous_worktree_setup_script, child_entries) = {
-        let mut workspaces = workspaces.lock().await;
+    let (entry_snapshot, previous_settings, child_entries) = {
+        let mut workspaces = workspaces.write().await;
         let previous_entry = workspaces
             .get(&id)
             .cloned()
             .ok_or_else(|| "workspace not found".to_string())?;
-        let previous_worktree_setup_script = previous_entry.settings.worktree_setup_script.clone();
         let entry_snapshot = apply_settings_update(&mut workspaces, &id, settings)?;
         let child_entries = workspaces
             .values()
             .filter(|entry| entry.parent_id.as_deref() == Some(&id))
             .cloned()
             .collect::<Vec<_>>();
-        (
-            entry_snapshot,
-            previous_worktree_setup_script,
-            child_entries,
-        )
+        (entry_snapshot, previous_entry.settings, child_entries)
     };
 
     let worktree_setup_script_changed =
-        previous_worktree_setup_script != entry_snapshot.settings.worktree_setup_script;
-    let connected = sessions.lock().await.contains_key(&id);
+        previous_settings.worktree_setup_script != entry_snapshot.settings.worktree_setup_script;
+    let agent_backend_changed = previous_settings.agent_backend.unwrap_or_default()
+        != entry_snapshot.settings.agent_backend.unwrap_or_default()
+        || previous_settings.agent_command != entry_snapshot.settings.agent_command;
+    if agent_backend_changed {
+        // The session was spawned for the previous agent; drop it so the next
+        // connect launches the newly selected backend.
+        kill_session_by_id(sessions, &id).await;
+    }
+    services
+        .session_quotas
+        .set_quota(&id, entry_snapshot.settings.quota.clone());
+    let connected = sessions.read().await.contains_key(&id);
 
     if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
         let child_ids = child_entries
@@ -592,7 +625,7 @@ where
             .map(|child| child.id.clone())
             .collect::<Vec<_>>();
         if !child_ids.is_empty() {
-            let mut workspaces = workspaces.lock().await;
+            let mut workspaces = workspaces.write().await;
             for child_id in child_ids {
                 if let Some(child) = workspaces.get_mut(&child_id) {
                     child.settings.worktree_setup_script =
@@ -602,10 +635,12 @@ where
         }
     }
     let list: Vec<_> = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         workspaces.values().cloned().collect()
     };
-    write_workspaces(storage_path, &list)?;
+    let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+    write_workspaces(storage_path, &list, store_options).await?;
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
@@ -618,9 +653,242 @@ where
     })
 }
 
+/// Applies one settings patch to several workspaces with a single
+/// `workspaces.json` write. Every workspace is validated before any is
+/// changed. Connected sessions whose agent changed are restarted together
+/// once the settings are stored.
+pub(crate) async fn update_workspaces_bulk_core<F, Fut>(
+    ids: Vec<String>,
+    partial_settings: Value,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    spawn_session: F,
+) -> Result<Vec<WorkspaceInfo>, String>
+where
+    F: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let patch = partial_settings
+        .as_object()
+        .ok_or_else(|| "partialSettings must be an object".to_string())?;
+    let mut unique_ids = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique_ids.contains(&id) {
+            unique_ids.push(id);
+        }
+    }
+
+    let (agent_changed_ids, list) = {
+        let mut workspaces = workspaces.write().await;
+        let mut updates = Vec::with_capacity(unique_ids.len());
+        for id in &unique_ids {
+            let entry = workspaces
+                .get(id)
+                .ok_or_else(|| format!("workspace not found: {id}"))?;
+            let next = apply_workspace_settings_patch(&entry.settings, patch)
+                .and_then(normalize_workspace_settings)
+                .map_err(|err| format!("{}: {err}", entry.name))?;
+            updates.push((id.clone(), next));
+        }
+
+        let mut agent_changed_ids = Vec::new();
+        for (id, next) in updates {
+            let Some(entry) = workspaces.get_mut(&id) else {
+                continue;
+            };
+            let previous = std::mem::replace(&mut entry.settings, next);
+            let current = &entry.settings;
+            if previous.agent_backend.unwrap_or_default()
+                != current.agent_backend.unwrap_or_default()
+                || previous.agent_command != current.agent_command
+            {
+                agent_changed_ids.push(id.clone());
+            }
+            services
+                .session_quotas
+                .set_quota(&id, current.quota.clone());
+            if previous.worktree_setup_script != current.worktree_setup_script
+                && !entry.kind.is_worktree()
+            {
+                let script = current.worktree_setup_script.clone();
+                for child in workspaces
+                    .values_mut()
+                    .filter(|child| child.parent_id.as_deref() == Some(id.as_str()))
+                {
+                    child.settings.worktree_setup_script = script.clone();
+                }
+            }
+        }
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        (agent_changed_ids, list)
+    };
+    let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+    write_workspaces(storage_path, &list, store_options).await?;
+
+    let mut restart_ids = Vec::new();
+    for id in agent_changed_ids {
+        if sessions.read().await.contains_key(&id) {
+            restart_ids.push(id.clone());
+        }
+        kill_session_by_id(sessions, &id).await;
+    }
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
+    for id in restart_ids {
+        if let Err(error) = connect_workspace_core(
+            id.clone(),
+            workspaces,
+            sessions,
+            services,
+            app_settings,
+            &spawn_session,
+        )
+        .await
+        {
+            eprintln!("update_workspaces_bulk: failed to restart session for {id}: {error}");
+        }
+    }
+
+    let workspaces = workspaces.read().await;
+    let sessions = sessions.read().await;
+    Ok(unique_ids
+        .iter()
+        .filter_map(|id| workspaces.get(id))
+        .map(|entry| WorkspaceInfo {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            path: entry.path.clone(),
+            connected: sessions.contains_key(&entry.id),
+            kind: entry.kind.clone(),
+            parent_id: entry.parent_id.clone(),
+            worktree: entry.worktree.clone(),
+            settings: entry.settings.clone(),
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{default_repo_name_from_url, validate_target_folder_name};
+    use super::{
+        default_repo_name_from_url, update_workspaces_bulk_core, validate_target_folder_name,
+    };
+
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use tokio::sync::{Mutex, RwLock};
+    use uuid::Uuid;
+
+    use crate::backend::app_server::WorkspaceSession;
+    use crate::backend::services::BackendServices;
+    use crate::storage::read_workspaces;
+    use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+    fn make_entry(id: &str, kind: WorkspaceKind, parent_id: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: "/tmp".to_string(),
+            kind,
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    async fn no_spawn(
+        _entry: WorkspaceEntry,
+        _default_bin: Option<String>,
+        _codex_args: Option<String>,
+        _codex_home: Option<PathBuf>,
+    ) -> Result<Arc<WorkspaceSession>, String> {
+        Err("no session expected".to_string())
+    }
+
+    #[test]
+    fn bulk_update_patches_every_workspace_in_one_write() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let dir = std::env::temp_dir().join(format!("codex-monitor-{}", Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).expect("create temp dir");
+            let storage_path = dir.join("workspaces.json");
+            let mut untouched = make_entry("c", WorkspaceKind::Main, None);
+            untouched.settings.default_model = Some("gpt-5".to_string());
+            let entries = [
+                make_entry("a", WorkspaceKind::Main, None),
+                make_entry("b", WorkspaceKind::Main, None),
+                make_entry("a-wt", WorkspaceKind::Worktree, Some("a")),
+                untouched,
+            ];
+            let workspaces = RwLock::new(
+                entries
+                    .into_iter()
+                    .map(|entry| (entry.id.clone(), entry))
+                    .collect::<HashMap<_, _>>(),
+            );
+            let sessions = RwLock::new(HashMap::new());
+            let services = BackendServices::load(&dir);
+            let app_settings = Mutex::new(AppSettings::default());
+
+            let error = update_workspaces_bulk_core(
+                vec!["a".to_string(), "missing".to_string()],
+                serde_json::json!({ "defaultModel": "gpt-5-codex" }),
+                &workspaces,
+                &sessions,
+                &services,
+                &app_settings,
+                &storage_path,
+                no_spawn,
+            )
+            .await
+            .expect_err("unknown workspace should fail");
+            assert!(error.contains("missing"));
+            assert!(workspaces.read().await["a"]
+                .settings
+                .default_model
+                .is_none());
+            assert!(!storage_path.exists());
+
+            let updated = update_workspaces_bulk_core(
+                vec!["a".to_string(), "b".to_string(), "a".to_string()],
+                serde_json::json!({
+                    "defaultModel": "gpt-5-codex",
+                    "worktreeSetupScript": "pnpm install",
+                }),
+                &workspaces,
+                &sessions,
+                &services,
+                &app_settings,
+                &storage_path,
+                no_spawn,
+            )
+            .await
+            .expect("bulk update should succeed");
+            assert_eq!(
+                updated
+                    .iter()
+                    .map(|info| info.id.as_str())
+                    .collect::<Vec<_>>(),
+                ["a", "b"]
+            );
+
+            let stored = read_workspaces(&storage_path).expect("read stored workspaces");
+            for id in ["a", "b"] {
+                assert_eq!(
+                    stored[id].settings.default_model.as_deref(),
+                    Some("gpt-5-codex")
+                );
+            }
+            assert_eq!(
+                stored["a-wt"].settings.worktree_setup_script.as_deref(),
+                Some("pnpm install")
+            );
+            assert_eq!(stored["c"].settings.default_model.as_deref(), Some("gpt-5"));
+            let _ = std::fs::remove_dir_all(dir);
+        });
+    }
 
     #[test]
     fn derives_repo_name_from_https_url() {
@@ -4,13 +4,15 @@ use std::path::PathBuf;
 use std::process::Stdio;
 
 use tokio::io::AsyncWriteExt;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::git_utils::resolve_git_root;
 use crate::shared::process_core::tokio_command;
 use crate::shared::{git_core, worktree_core};
 use crate::types::WorkspaceEntry;
 
+use super::helpers::ensure_workspace_writable_core;
+
 pub(crate) fn run_git_command_unit<F, Fut>(
     repo_path: &PathBuf,
     args: &[&str],
@@ -33,11 +35,11 @@ where
 }
 
 pub(crate) async fn apply_worktree_changes_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<(), String> {
     let (entry, parent) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces
             .get(&workspace_id)
             .cloned()
@@ -55,6 +57,7 @@ pub(crate) async fn apply_worktree_changes_core(
             .ok_or_else(|| "worktree parent not found".to_string())?;
         (entry, parent)
     };
+    ensure_workspace_writable_core(workspaces, &parent.id).await?;
 
     apply_worktree_changes_inner_core(&entry, &parent).await
 }
@@ -2,10 +2,10 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::backend::app_server::WorkspaceSession;
-use crate::types::{WorkspaceEntry, WorkspaceInfo};
+use crate::types::{AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceSettings};
 use crate::utils::normalize_windows_namespace_path;
 
 pub(crate) const WORKTREE_SETUP_MARKERS_DIR: &str = "worktree-setup";
@@ -57,6 +57,83 @@ pub(crate) fn normalize_setup_script(script: Option<String>) -> Option<String> {
     }
 }
 
+const MAX_WORKSPACE_ICON_CHARS: usize = 32;
+
+/// Accepts `#rgb` or `#rrggbb` and stores `#rrggbb` in lowercase; blank
+/// clears the color.
+pub(crate) fn normalize_workspace_color(color: Option<String>) -> Result<Option<String>, String> {
+    let Some(color) = color.map(|color| color.trim().to_ascii_lowercase()) else {
+        return Ok(None);
+    };
+    if color.is_empty() {
+        return Ok(None);
+    }
+    let hex = color
+        .strip_prefix('#')
+        .filter(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|ch| ch.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("Invalid workspace color `{color}`; use #rrggbb."))?;
+    if hex.len() == 3 {
+        let expanded: String = hex.chars().flat_map(|ch| [ch, ch]).collect();
+        return Ok(Some(format!("#{expanded}")));
+    }
+    Ok(Some(color))
+}
+
+/// Accepts an icon name or a short emoji sequence; blank clears the icon.
+pub(crate) fn normalize_workspace_icon(icon: Option<String>) -> Result<Option<String>, String> {
+    let Some(icon) = icon.map(|icon| icon.trim().to_string()) else {
+        return Ok(None);
+    };
+    if icon.is_empty() {
+        return Ok(None);
+    }
+    if icon.chars().count() > MAX_WORKSPACE_ICON_CHARS {
+        return Err(format!(
+            "Workspace icon must be at most {MAX_WORKSPACE_ICON_CHARS} characters."
+        ));
+    }
+    if icon.chars().any(|ch| ch.is_whitespace() || ch.is_control()) {
+        return Err("Workspace icon cannot contain spaces or control characters.".to_string());
+    }
+    Ok(Some(icon))
+}
+
+/// Normalizes the fields every settings write cleans up before storing.
+pub(crate) fn normalize_workspace_settings(
+    mut settings: WorkspaceSettings,
+) -> Result<WorkspaceSettings, String> {
+    settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
+    settings.color = normalize_workspace_color(settings.color)?;
+    settings.icon = normalize_workspace_icon(settings.icon)?;
+    settings.worktrees_folder = settings
+        .worktrees_folder
+        .map(|path| normalize_windows_namespace_path(&path));
+    Ok(settings)
+}
+
+/// Overlays the top-level keys of `patch` (camelCase, as in
+/// `WorkspaceSettings`) on `settings`. `null` resets a key to its default.
+pub(crate) fn apply_workspace_settings_patch(
+    settings: &WorkspaceSettings,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) -> Result<WorkspaceSettings, String> {
+    let mut value = serde_json::to_value(settings).map_err(|err| err.to_string())?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| "workspace settings are not an object".to_string())?;
+    for (key, patch_value) in patch {
+        if !fields.contains_key(key) {
+            return Err(format!("Unknown workspace setting `{key}`."));
+        }
+        if patch_value.is_null() {
+            fields.remove(key);
+        } else {
+            fields.insert(key.clone(), patch_value.clone());
+        }
+    }
+    serde_json::from_value(value).map_err(|err| format!("Invalid workspace settings: {err}"))
+}
+
 pub(crate) fn worktree_setup_marker_path(data_dir: &PathBuf, workspace_id: &str) -> PathBuf {
     data_dir
         .join(WORKTREE_SETUP_MARKERS_DIR)
@@ -86,12 +163,28 @@ pub(crate) fn workspace_path_to_string(path: &PathBuf) -> String {
     normalize_windows_namespace_path(&path.to_string_lossy())
 }
 
+/// Fails when the workspace has `readOnly` set. Every backend path that writes
+/// to a workspace, moves its git state, or opens a shell in it calls this, so
+/// the flag holds whatever the client sends.
+pub(crate) async fn ensure_workspace_writable_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<(), String> {
+    let workspaces = workspaces.read().await;
+    match workspaces.get(workspace_id) {
+        Some(entry) if entry.settings.read_only => {
+            Err(format!("Workspace \"{}\" is read-only", entry.name))
+        }
+        _ => Ok(()),
+    }
+}
+
 pub(crate) async fn list_workspaces_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
 ) -> Vec<WorkspaceInfo> {
-    let workspaces = workspaces.lock().await;
-    let sessions = sessions.lock().await;
+    let workspaces = workspaces.read().await;
+    let sessions = sessions.read().await;
     let mut result = Vec::new();
     for entry in workspaces.values() {
         result.push(WorkspaceInfo {
@@ -109,11 +202,35 @@ pub(crate) async fn list_workspaces_core(
     result
 }
 
+/// Pushes the current request timeout settings into every live session. Call
+/// after a session is added or app/workspace settings change.
+pub(crate) async fn sync_request_timeouts_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+) {
+    let app_timeouts = app_settings.lock().await.request_timeouts.clone();
+    let workspace_timeouts = workspaces
+        .read()
+        .await
+        .values()
+        .filter_map(|entry| {
+            let timeouts = entry.settings.request_timeouts.clone()?;
+            Some((entry.id.clone(), timeouts))
+        })
+        .collect::<HashMap<_, _>>();
+    for session in sessions.read().await.values() {
+        session
+            .request_timeouts
+            .configure(app_timeouts.clone(), workspace_timeouts.clone());
+    }
+}
+
 pub(super) async fn resolve_entry_and_parent(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<(WorkspaceEntry, Option<WorkspaceEntry>), String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .cloned()
@@ -127,10 +244,10 @@ pub(super) async fn resolve_entry_and_parent(
 }
 
 pub(super) async fn resolve_workspace_root(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
 ) -> Result<PathBuf, String> {
-    let workspaces = workspaces.lock().await;
+    let workspaces = workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .cloned()
@@ -152,9 +269,12 @@ pub(super) fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
 #[cfg(test)]
 mod tests {
     use super::{
-        copy_agents_md_from_parent_to_worktree, normalize_workspace_path_input,
-        workspace_path_to_string, AGENTS_MD_FILE_NAME,
+        apply_workspace_settings_patch, copy_agents_md_from_parent_to_worktree,
+        ensure_workspace_writable_core, normalize_workspace_color, normalize_workspace_icon,
+        normalize_workspace_path_input, workspace_path_to_string, AGENTS_MD_FILE_NAME,
     };
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use std::sync::Mutex;
     use uuid::Uuid;
@@ -167,6 +287,94 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn workspace_color_and_icon_are_validated() {
+        assert_eq!(
+            normalize_workspace_color(Some(" #3B82F6 ".to_string())),
+            Ok(Some("#3b82f6".to_string()))
+        );
+        assert_eq!(
+            normalize_workspace_color(Some("#f0a".to_string())),
+            Ok(Some("#ff00aa".to_string()))
+        );
+        assert_eq!(normalize_workspace_color(Some("  ".to_string())), Ok(None));
+        assert!(normalize_workspace_color(Some("blue".to_string())).is_err());
+        assert!(normalize_workspace_color(Some("#12345g".to_string())).is_err());
+
+        assert_eq!(
+            normalize_workspace_icon(Some(" rocket ".to_string())),
+            Ok(Some("rocket".to_string()))
+        );
+        assert_eq!(
+            normalize_workspace_icon(Some("🚀".to_string())),
+            Ok(Some("🚀".to_string()))
+        );
+        assert_eq!(normalize_workspace_icon(Some(String::new())), Ok(None));
+        assert!(normalize_workspace_icon(Some("two words".to_string())).is_err());
+        assert!(normalize_workspace_icon(Some("x".repeat(33))).is_err());
+    }
+
+    #[test]
+    fn settings_patch_overlays_only_given_keys() {
+        let settings = WorkspaceSettings {
+            default_model: Some("gpt-5".to_string()),
+            launch_script: Some("npm run dev".to_string()),
+            ..WorkspaceSettings::default()
+        };
+        let patch = serde_json::json!({
+            "worktreeSetupScript": "pnpm install",
+            "defaultModel": null,
+        });
+        let patched = apply_workspace_settings_patch(&settings, patch.as_object().unwrap())
+            .expect("patch should apply");
+        assert_eq!(
+            patched.worktree_setup_script.as_deref(),
+            Some("pnpm install")
+        );
+        assert_eq!(patched.default_model, None);
+        assert_eq!(patched.launch_script.as_deref(), Some("npm run dev"));
+
+        let unknown = serde_json::json!({ "defaultModle": "gpt-5" });
+        assert!(apply_workspace_settings_patch(&settings, unknown.as_object().unwrap()).is_err());
+        let wrong_type = serde_json::json!({ "sortOrder": "first" });
+        assert!(
+            apply_workspace_settings_patch(&settings, wrong_type.as_object().unwrap()).is_err()
+        );
+    }
+
+    #[test]
+    fn read_only_workspaces_refuse_writes() {
+        let entry = |id: &str, read_only: bool| WorkspaceEntry {
+            id: id.to_string(),
+            name: format!("{id} name"),
+            path: "/tmp/repo".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                read_only,
+                ..WorkspaceSettings::default()
+            },
+        };
+        let workspaces = tokio::sync::RwLock::new(HashMap::from([
+            ("ws-1".to_string(), entry("ws-1", true)),
+            ("ws-2".to_string(), entry("ws-2", false)),
+        ]));
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            assert_eq!(
+                ensure_workspace_writable_core(&workspaces, "ws-1").await,
+                Err("Workspace \"ws-1 name\" is read-only".to_string())
+            );
+            assert_eq!(
+                ensure_workspace_writable_core(&workspaces, "ws-2").await,
+                Ok(())
+            );
+        });
+        let parsed: WorkspaceSettings =
+            serde_json::from_value(serde_json::json!({ "readOnly": true })).unwrap();
+        assert!(parsed.read_only);
+    }
+
     #[test]
     fn copies_agents_md_when_missing_in_worktree() {
         let parent = make_temp_dir();
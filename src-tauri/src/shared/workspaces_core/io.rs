@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::env;
 use std::path::{Path, PathBuf};
 
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 use crate::shared::process_core::tokio_command;
 #[cfg(target_os = "windows")]
@@ -541,20 +541,8 @@ where
     Ok(None)
 }
 
-pub(crate) async fn list_workspace_files_core<F>(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    workspace_id: &str,
-    list_files: F,
-) -> Result<Vec<String>, String>
-where
-    F: Fn(&PathBuf) -> Vec<String>,
-{
-    let root = resolve_workspace_root(workspaces, workspace_id).await?;
-    Ok(list_files(&root))
-}
-
 pub(crate) async fn read_workspace_file_core<F, T>(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
     path: &str,
     read_file: F,
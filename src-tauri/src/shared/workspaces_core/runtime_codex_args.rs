@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::backend::app_server::WorkspaceSession;
 use crate::codex::args::resolve_workspace_codex_args;
@@ -13,7 +13,7 @@ use crate::shared::process_core::kill_child_process_tree;
 use crate::types::{AppSettings, WorkspaceEntry};
 
 use super::connect::workspace_session_spawn_lock;
-use super::helpers::resolve_entry_and_parent;
+use super::helpers::{resolve_entry_and_parent, sync_request_timeouts_core};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -25,8 +25,8 @@ pub(crate) struct WorkspaceRuntimeCodexArgsResult {
 pub(crate) async fn set_workspace_runtime_codex_args_core<F, Fut>(
     workspace_id: String,
     codex_args_override: Option<String>,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     spawn_session: F,
 ) -> Result<WorkspaceRuntimeCodexArgsResult, String>
@@ -55,7 +55,7 @@ where
     // If we are not connected, we can't respawn. Treat this as a no-op success; callers
     // should call again after connecting.
     let (workspace_connected, current_session) = {
-        let sessions = sessions.lock().await;
+        let sessions = sessions.read().await;
         (
             sessions.contains_key(&entry.id),
             sessions.values().next().cloned(),
@@ -86,7 +86,7 @@ where
     let new_session =
         spawn_session(entry.clone(), default_bin, target_args.clone(), codex_home).await?;
     let workspace_ids = {
-        let mut sessions = sessions.lock().await;
+        let mut sessions = sessions.write().await;
         let keys: Vec<String> = sessions.keys().cloned().collect();
         for key in &keys {
             sessions.insert(key.clone(), Arc::clone(&new_session));
@@ -94,7 +94,7 @@ where
         keys
     };
     let workspace_paths = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         workspace_ids
             .iter()
             .map(|workspace_id| {
@@ -116,6 +116,7 @@ where
             .register_workspace_with_path(workspace_id, path)
             .await;
     }
+    sync_request_timeouts_core(workspaces, sessions, app_settings).await;
     let mut child = current_session.child.lock().await;
     kill_child_process_tree(&mut child).await;
 
@@ -135,6 +136,8 @@ mod tests {
 
     use tokio::process::Command;
 
+    use crate::backend::agent::default_agent_backend;
+    use crate::backend::services::BackendServices;
     use crate::types::{WorkspaceKind, WorkspaceSettings};
 
     fn make_workspace_entry(id: &str) -> WorkspaceEntry {
@@ -171,8 +174,8 @@ mod tests {
             codex_args,
             child: Mutex::new(child),
             stdin: Mutex::new(stdin),
-            pending: Mutex::new(HashMap::new()),
-            request_context: Mutex::new(HashMap::new()),
+            pending: Default::default(),
+            request_context: Default::default(),
             thread_workspace: Mutex::new(HashMap::new()),
             hidden_thread_ids: Mutex::new(HashSet::new()),
             next_id: AtomicU64::new(0),
@@ -180,6 +183,10 @@ mod tests {
             owner_workspace_id: "test-owner".to_string(),
             workspace_ids: Mutex::new(HashSet::from(["test-owner".to_string()])),
             workspace_roots: Mutex::new(HashMap::new()),
+            backend: default_agent_backend(),
+            request_timeouts: Default::default(),
+            isolated_env: false,
+            services: BackendServices::for_tests(),
         }
     }
 
@@ -187,8 +194,8 @@ mod tests {
     fn set_workspace_runtime_codex_args_is_noop_when_workspace_not_connected() {
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             let entry = make_workspace_entry("ws-1");
-            let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
-            let sessions = Mutex::new(HashMap::<String, Arc<WorkspaceSession>>::new());
+            let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+            let sessions = RwLock::new(HashMap::<String, Arc<WorkspaceSession>>::new());
             let app_settings = Mutex::new(AppSettings::default());
 
             let spawn_calls = Arc::new(AtomicUsize::new(0));
@@ -226,9 +233,9 @@ mod tests {
     fn set_workspace_runtime_codex_args_is_noop_when_args_match() {
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             let entry = make_workspace_entry("ws-1");
-            let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+            let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry.clone())]));
             let current_session = Arc::new(make_session(entry.clone(), Some("--same".to_string())));
-            let sessions = Mutex::new(HashMap::from([(entry.id.clone(), current_session)]));
+            let sessions = RwLock::new(HashMap::from([(entry.id.clone(), current_session)]));
             let app_settings = Mutex::new(AppSettings::default());
 
             let spawn_calls = Arc::new(AtomicUsize::new(0));
@@ -266,9 +273,9 @@ mod tests {
     fn set_workspace_runtime_codex_args_respawns_when_args_change() {
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             let entry = make_workspace_entry("ws-1");
-            let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+            let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry.clone())]));
             let current_session = Arc::new(make_session(entry.clone(), Some("--old".to_string())));
-            let sessions = Mutex::new(HashMap::from([(entry.id.clone(), current_session)]));
+            let sessions = RwLock::new(HashMap::from([(entry.id.clone(), current_session)]));
             let app_settings = Mutex::new(AppSettings::default());
 
             let spawn_calls = Arc::new(AtomicUsize::new(0));
@@ -301,7 +308,7 @@ mod tests {
             assert_eq!(spawn_calls.load(Ordering::SeqCst), 1);
 
             let next = sessions
-                .lock()
+                .read()
                 .await
                 .get(&entry.id)
                 .expect("session updated")
@@ -3,31 +3,39 @@ use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
+use serde_json::json;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::services::BackendServices;
 use crate::codex::args::resolve_workspace_codex_args;
 use crate::codex::home::resolve_workspace_codex_home;
-use crate::storage::write_workspaces;
+use crate::shared::file_watch_core::unwatch_workspace_files_core;
+use crate::shared::git_core;
+use crate::shared::process_core::kill_child_process_tree;
+use crate::shared::worktree_core::limit_worktree_name;
+use crate::shared::worktree_lock_core::prepare_locked_worktree;
+use crate::storage::{write_workspaces, StoreWriteOptions};
 use crate::types::{
-    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
-    WorktreeSetupStatus,
+    AppSettings, BranchNameSuggestion, WorkspaceEntry, WorkspaceInfo, WorkspaceKind,
+    WorkspaceSettings, WorktreeInfo, WorktreeResult, WorktreeSetupStatus,
 };
 
 use super::connect::{kill_session_by_id, take_live_shared_session, workspace_session_spawn_lock};
 use super::helpers::{
-    copy_agents_md_from_parent_to_worktree, normalize_setup_script, workspace_path_to_string,
-    worktree_setup_marker_path, AGENTS_MD_FILE_NAME,
+    copy_agents_md_from_parent_to_worktree, ensure_workspace_writable_core, normalize_setup_script,
+    sync_request_timeouts_core, workspace_path_to_string, worktree_setup_marker_path,
+    AGENTS_MD_FILE_NAME,
 };
 
 pub(crate) async fn worktree_setup_status_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
     data_dir: &PathBuf,
 ) -> Result<WorktreeSetupStatus, String> {
     let entry = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         workspaces
             .get(workspace_id)
             .cloned()
@@ -46,12 +54,12 @@ pub(crate) async fn worktree_setup_status_core(
 }
 
 pub(crate) async fn worktree_setup_mark_ran_core(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
     data_dir: &PathBuf,
 ) -> Result<(), String> {
     let entry = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         workspaces
             .get(workspace_id)
             .cloned()
@@ -74,6 +82,70 @@ pub(crate) async fn worktree_setup_mark_ran_core(
     Ok(())
 }
 
+/// The branch `add_worktree` uses for `requested`: the name itself, unless
+/// that branch is already checked out in another worktree, in which case the
+/// first free `-2`, `-3`, … is taken (as `rename_worktree` does).
+async fn worktree_branch_for(
+    repo_path: &PathBuf,
+    requested: &str,
+    branch_exists: bool,
+) -> Result<String, String> {
+    if !branch_exists
+        || !git_core::git_checked_out_branches(repo_path)
+            .await?
+            .contains(requested)
+    {
+        return Ok(requested.to_string());
+    }
+    git_core::unique_branch_name_live(repo_path, requested, None)
+        .await
+        .map(|(branch, _was_suffixed)| branch)
+}
+
+/// Preflight for `add_worktree`: the branch it would use for `base` in the
+/// workspace's repository.
+pub(crate) async fn suggest_branch_name_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    base: String,
+) -> Result<BranchNameSuggestion, String> {
+    let requested = base.trim().to_string();
+    if requested.is_empty() {
+        return Err("Branch name is required.".to_string());
+    }
+    let entry = workspaces
+        .read()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let repo_path = match entry.parent_id.as_deref() {
+        Some(parent_id) if entry.kind.is_worktree() => workspaces
+            .read()
+            .await
+            .get(parent_id)
+            .map(|parent| PathBuf::from(&parent.path))
+            .ok_or_else(|| "worktree parent not found".to_string())?,
+        _ => PathBuf::from(&entry.path),
+    };
+    let branch_exists = git_core::git_branch_exists(&repo_path, &requested).await?;
+    let branch = worktree_branch_for(&repo_path, &requested, branch_exists).await?;
+    let was_renamed = branch != requested;
+    Ok(BranchNameSuggestion {
+        existing: branch_exists && !was_renamed,
+        requested_branch: requested,
+        branch,
+        was_renamed,
+    })
+}
+
+/// What a failed `add_worktree` undoes.
+enum AddWorktreeUndo {
+    RemoveWorktree,
+    DeleteBranch,
+    StopSession(Arc<WorkspaceSession>),
+}
+
 pub(crate) async fn add_worktree_core<
     FSpawn,
     FutSpawn,
@@ -91,8 +163,9 @@ pub(crate) async fn add_worktree_core<
     name: Option<String>,
     copy_agents_md: bool,
     data_dir: &PathBuf,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     sanitize_worktree_name: FSanitize,
@@ -101,7 +174,7 @@ pub(crate) async fn add_worktree_core<
     git_find_remote_tracking_branch: Option<FFindRemoteTracking>,
     run_git_command: FRunGit,
     spawn_session: FSpawn,
-) -> Result<WorkspaceInfo, String>
+) -> Result<WorktreeResult, String>
 where
     FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
     FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
@@ -114,8 +187,8 @@ where
     FRunGit: Fn(&PathBuf, &[&str]) -> FutRunGit,
     FutRunGit: Future<Output = Result<(), String>>,
 {
-    let branch = branch.trim().to_string();
-    if branch.is_empty() {
+    let requested_branch = branch.trim().to_string();
+    if requested_branch.is_empty() {
         return Err("Branch name is required.".to_string());
     }
     let name = name
@@ -123,7 +196,7 @@ where
         .filter(|value| !value.is_empty());
 
     let parent_entry = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         workspaces
             .get(&parent_id)
             .cloned()
@@ -133,6 +206,8 @@ where
     if parent_entry.kind.is_worktree() {
         return Err("Cannot create a worktree from another worktree.".to_string());
     }
+    // The new branch and worktree metadata land in the parent's repository.
+    ensure_workspace_writable_core(workspaces, &parent_id).await?;
 
     // Determine worktree root: per-workspace setting > global setting > default
     let worktree_root = if let Some(custom_folder) = &parent_entry.settings.worktrees_folder {
@@ -152,21 +227,32 @@ where
     std::fs::create_dir_all(&worktree_root)
         .map_err(|err| format!("Failed to create worktree directory: {err}"))?;
 
-    let safe_name = sanitize_worktree_name(&branch);
+    let repo_path = PathBuf::from(&parent_entry.path);
+    let branch_exists = git_branch_exists(&repo_path, &requested_branch).await?;
+    let branch = worktree_branch_for(&repo_path, &requested_branch, branch_exists).await?;
+    let was_renamed = branch != requested_branch;
+
+    let max_name_length = app_settings.lock().await.worktree_name_max_length as usize;
+    let safe_name = limit_worktree_name(&sanitize_worktree_name(&branch), max_name_length);
     let worktree_path = unique_worktree_path(&worktree_root, &safe_name)?;
     let worktree_path_string = worktree_path.to_string_lossy().to_string();
     let stored_worktree_path = workspace_path_to_string(&worktree_path);
 
-    let repo_path = PathBuf::from(&parent_entry.path);
-    let branch_exists = git_branch_exists(&repo_path, &branch).await?;
-    if branch_exists {
-        run_git_command(
-            &repo_path,
-            &["worktree", "add", &worktree_path_string, &branch],
-        )
-        .await?;
-    } else if let Some(find_remote_tracking) = git_find_remote_tracking_branch {
-        if let Some(remote_ref) = find_remote_tracking(&repo_path, &branch).await? {
+    let mut operation = services.operations.begin(
+        "add_worktree",
+        &parent_entry.id,
+        json!({
+            "parentId": parent_entry.id,
+            "branch": requested_branch,
+            "name": name,
+            "copyAgentsMd": copy_agents_md,
+        }),
+    );
+    let steps = async {
+        operation.step("git worktree add");
+        if was_renamed {
+            // The requested branch is checked out elsewhere; start the new one
+            // from its tip.
             run_git_command(
                 &repo_path,
                 &[
@@ -175,10 +261,37 @@ where
                     "-b",
                     &branch,
                     &worktree_path_string,
-                    &remote_ref,
+                    &requested_branch,
                 ],
             )
             .await?;
+        } else if branch_exists {
+            run_git_command(
+                &repo_path,
+                &["worktree", "add", &worktree_path_string, &branch],
+            )
+            .await?;
+        } else if let Some(find_remote_tracking) = git_find_remote_tracking_branch {
+            if let Some(remote_ref) = find_remote_tracking(&repo_path, &branch).await? {
+                run_git_command(
+                    &repo_path,
+                    &[
+                        "worktree",
+                        "add",
+                        "-b",
+                        &branch,
+                        &worktree_path_string,
+                        &remote_ref,
+                    ],
+                )
+                .await?;
+            } else {
+                run_git_command(
+                    &repo_path,
+                    &["worktree", "add", "-b", &branch, &worktree_path_string],
+                )
+                .await?;
+            }
         } else {
             run_git_command(
                 &repo_path,
@@ -186,85 +299,142 @@ where
             )
             .await?;
         }
-    } else {
-        run_git_command(
-            &repo_path,
-            &["worktree", "add", "-b", &branch, &worktree_path_string],
-        )
-        .await?;
-    }
-
-    if copy_agents_md {
-        if let Err(error) = copy_agents_md_from_parent_to_worktree(&repo_path, &worktree_path) {
-            eprintln!(
-                "add_worktree: optional {} copy failed for {}: {}",
-                AGENTS_MD_FILE_NAME,
-                worktree_path.display(),
-                error
-            );
+        if !branch_exists {
+            operation.on_rollback(AddWorktreeUndo::DeleteBranch);
+        }
+        operation.on_rollback(AddWorktreeUndo::RemoveWorktree);
+
+        if copy_agents_md {
+            operation.step("copy AGENTS.md");
+            if let Err(error) = copy_agents_md_from_parent_to_worktree(&repo_path, &worktree_path) {
+                eprintln!(
+                    "add_worktree: optional {} copy failed for {}: {}",
+                    AGENTS_MD_FILE_NAME,
+                    worktree_path.display(),
+                    error
+                );
+            }
         }
-    }
 
-    let entry = WorkspaceEntry {
-        id: Uuid::new_v4().to_string(),
-        name: name.clone().unwrap_or_else(|| branch.clone()),
-        path: stored_worktree_path,
-        kind: WorkspaceKind::Worktree,
-        parent_id: Some(parent_entry.id.clone()),
-        worktree: Some(WorktreeInfo { branch }),
-        settings: WorkspaceSettings {
-            worktree_setup_script: normalize_setup_script(
-                parent_entry.settings.worktree_setup_script.clone(),
-            ),
-            ..WorkspaceSettings::default()
-        },
-    };
+        let entry = WorkspaceEntry {
+            id: Uuid::new_v4().to_string(),
+            name: name.clone().unwrap_or_else(|| branch.clone()),
+            path: stored_worktree_path,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some(parent_entry.id.clone()),
+            worktree: Some(WorktreeInfo {
+                branch: branch.clone(),
+            }),
+            settings: WorkspaceSettings {
+                worktree_setup_script: normalize_setup_script(
+                    parent_entry.settings.worktree_setup_script.clone(),
+                ),
+                env: parent_entry.settings.env.clone(),
+                color: parent_entry.settings.color.clone(),
+                icon: parent_entry.settings.icon.clone(),
+                ..WorkspaceSettings::default()
+            },
+        };
 
-    let _spawn_guard = workspace_session_spawn_lock().lock().await;
-    let existing_session = take_live_shared_session(sessions).await;
-    let session = if let Some(existing_session) = existing_session {
-        existing_session
-    } else {
-        let (default_bin, codex_args) = {
-            let settings = app_settings.lock().await;
-            (
-                settings.codex_bin.clone(),
-                resolve_workspace_codex_args(&entry, Some(&parent_entry), Some(&settings)),
-            )
+        operation.step("start session");
+        let _spawn_guard = workspace_session_spawn_lock().lock().await;
+        let existing_session = if entry.settings.env.is_empty() {
+            take_live_shared_session(sessions).await
+        } else {
+            None
+        };
+        let session = if let Some(existing_session) = existing_session {
+            existing_session
+        } else {
+            let (default_bin, codex_args) = {
+                let settings = app_settings.lock().await;
+                (
+                    settings.codex_bin.clone(),
+                    resolve_workspace_codex_args(&entry, Some(&parent_entry), Some(&settings)),
+                )
+            };
+            let codex_home = resolve_workspace_codex_home(&entry, Some(&parent_entry));
+            let session = spawn_session(entry.clone(), default_bin, codex_args, codex_home).await?;
+            operation.on_rollback(AddWorktreeUndo::StopSession(Arc::clone(&session)));
+            session
         };
-        let codex_home = resolve_workspace_codex_home(&entry, Some(&parent_entry));
-        spawn_session(entry.clone(), default_bin, codex_args, codex_home).await?
-    };
 
-    {
-        let mut workspaces = workspaces.lock().await;
-        workspaces.insert(entry.id.clone(), entry.clone());
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
-    }
+        operation.step("save workspace");
+        {
+            let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+            let mut workspaces = workspaces.write().await;
+            workspaces.insert(entry.id.clone(), entry.clone());
+            let list: Vec<_> = workspaces.values().cloned().collect();
+            if let Err(error) = write_workspaces(storage_path, &list, store_options).await {
+                workspaces.remove(&entry.id);
+                return Err(error);
+            }
+        }
 
-    session
-        .register_workspace_with_path(&entry.id, Some(&entry.path))
-        .await;
-    sessions.lock().await.insert(entry.id.clone(), session);
-
-    Ok(WorkspaceInfo {
-        id: entry.id,
-        name: entry.name,
-        path: entry.path,
-        connected: true,
-        kind: entry.kind,
-        parent_id: entry.parent_id,
-        worktree: entry.worktree,
-        settings: entry.settings,
+        session
+            .register_workspace_with_path(&entry.id, Some(&entry.path))
+            .await;
+        sessions.write().await.insert(entry.id.clone(), session);
+        sync_request_timeouts_core(workspaces, sessions, app_settings).await;
+        Ok(entry)
+    };
+    let entry = match steps.await {
+        Ok(entry) => {
+            operation.complete();
+            entry
+        }
+        Err(error) => {
+            let repo_path = &repo_path;
+            let worktree_path_string = &worktree_path_string;
+            let branch = &branch;
+            let run_git_command = &run_git_command;
+            return Err(operation
+                .fail(error, move |undo| async move {
+                    match undo {
+                        AddWorktreeUndo::RemoveWorktree => {
+                            run_git_command(
+                                repo_path,
+                                &["worktree", "remove", "--force", worktree_path_string],
+                            )
+                            .await
+                        }
+                        AddWorktreeUndo::DeleteBranch => {
+                            run_git_command(repo_path, &["branch", "-D", branch]).await
+                        }
+                        AddWorktreeUndo::StopSession(session) => {
+                            let mut child = session.child.lock().await;
+                            kill_child_process_tree(&mut child).await;
+                            Ok(())
+                        }
+                    }
+                })
+                .await);
+        }
+    };
+
+    Ok(WorktreeResult {
+        workspace: WorkspaceInfo {
+            id: entry.id,
+            name: entry.name,
+            path: entry.path,
+            connected: true,
+            kind: entry.kind,
+            parent_id: entry.parent_id,
+            worktree: entry.worktree,
+            settings: entry.settings,
+        },
+        requested_branch,
+        was_renamed,
     })
 }
 
 pub(crate) async fn remove_worktree_core<FRunGit, FutRunGit, FIsMissing, FRemoveDirAll>(
     id: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
+    services: &BackendServices,
     storage_path: &PathBuf,
+    store_options: StoreWriteOptions,
     run_git_command: FRunGit,
     is_missing_worktree_error: FIsMissing,
     remove_dir_all: FRemoveDirAll,
@@ -276,7 +446,7 @@ where
     FRemoveDirAll: Fn(&PathBuf) -> Result<(), String>,
 {
     let (entry, parent) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces
             .get(&id)
             .cloned()
@@ -298,7 +468,11 @@ where
     let parent_path = PathBuf::from(&parent.path);
     let parent_path_exists = parent_path.is_dir();
     let entry_path = PathBuf::from(&entry.path);
+    if parent_path_exists {
+        prepare_locked_worktree(&entry_path)?;
+    }
     kill_session_by_id(sessions, &entry.id).await;
+    unwatch_workspace_files_core(services, &entry.id);
 
     if entry_path.exists() {
         if !parent_path_exists {
@@ -323,10 +497,11 @@ where
     }
 
     {
-        let mut workspaces = workspaces.lock().await;
+        let mut workspaces = workspaces.write().await;
         workspaces.remove(&entry.id);
+        let workspaces = workspaces.downgrade();
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
+        write_workspaces(storage_path, &list, store_options).await?;
     }
 
     Ok(())
@@ -346,8 +521,8 @@ pub(crate) async fn rename_worktree_core<
     id: String,
     branch: String,
     data_dir: &PathBuf,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    sessions: &RwLock<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     resolve_git_root: FResolveGitRoot,
@@ -356,7 +531,7 @@ pub(crate) async fn rename_worktree_core<
     unique_worktree_path_for_rename: FUniqueRenamePath,
     run_git_command: FRunGit,
     _spawn_session: FSpawn,
-) -> Result<WorkspaceInfo, String>
+) -> Result<WorktreeResult, String>
 where
     FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
     FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
@@ -374,7 +549,7 @@ where
     }
 
     let (entry, parent) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces
             .get(&id)
             .cloned()
@@ -426,12 +601,16 @@ where
     std::fs::create_dir_all(&worktree_root)
         .map_err(|err| format!("Failed to create worktree directory: {err}"))?;
 
-    let safe_name = sanitize_worktree_name(&final_branch);
+    let max_name_length = app_settings.lock().await.worktree_name_max_length as usize;
+    let safe_name = limit_worktree_name(&sanitize_worktree_name(&final_branch), max_name_length);
     let current_path = PathBuf::from(&entry.path);
     let next_path = unique_worktree_path_for_rename(&worktree_root, &safe_name, &current_path)?;
     let next_path_string = next_path.to_string_lossy().to_string();
     let stored_next_path = workspace_path_to_string(&next_path);
     let old_path_string = entry.path.clone();
+    if stored_next_path != old_path_string {
+        prepare_locked_worktree(&current_path)?;
+    }
 
     run_git_command(&parent_root, &["branch", "-m", &old_branch, &final_branch]).await?;
 
@@ -451,7 +630,7 @@ where
     }
 
     let update_result: Result<(WorkspaceEntry, WorkspaceEntry, Vec<WorkspaceEntry>), String> = {
-        let mut workspaces = workspaces.lock().await;
+        let mut workspaces = workspaces.write().await;
         if let Some(entry) = workspaces.get_mut(&id) {
             let old_snapshot = entry.clone();
             if entry.name.trim() == old_branch {
@@ -490,7 +669,8 @@ where
             return Err(error);
         }
     };
-    if let Err(error) = write_workspaces(storage_path, &list) {
+    let store_options = StoreWriteOptions::from_settings(&*app_settings.lock().await);
+    if let Err(error) = write_workspaces(storage_path, &list, store_options).await {
         if moved_worktree {
             let _ = run_git_command(
                 &parent_root,
@@ -499,29 +679,34 @@ where
             .await;
         }
         let _ = run_git_command(&parent_root, &["branch", "-m", &final_branch, &old_branch]).await;
-        let mut workspaces = workspaces.lock().await;
+        let mut workspaces = workspaces.write().await;
         if let Some(entry) = workspaces.get_mut(&id) {
             *entry = old_snapshot;
         }
         return Err(error);
     }
 
-    if let Some(session) = sessions.lock().await.get(&entry_snapshot.id).cloned() {
+    let session = sessions.read().await.get(&entry_snapshot.id).cloned();
+    if let Some(session) = session {
         session
             .register_workspace_with_path(&entry_snapshot.id, Some(&entry_snapshot.path))
             .await;
     }
 
-    let connected = sessions.lock().await.contains_key(&entry_snapshot.id);
-    Ok(WorkspaceInfo {
-        id: entry_snapshot.id,
-        name: entry_snapshot.name,
-        path: entry_snapshot.path,
-        connected,
-        kind: entry_snapshot.kind,
-        parent_id: entry_snapshot.parent_id,
-        worktree: entry_snapshot.worktree,
-        settings: entry_snapshot.settings,
+    let connected = sessions.read().await.contains_key(&entry_snapshot.id);
+    Ok(WorktreeResult {
+        workspace: WorkspaceInfo {
+            id: entry_snapshot.id,
+            name: entry_snapshot.name,
+            path: entry_snapshot.path,
+            connected,
+            kind: entry_snapshot.kind,
+            parent_id: entry_snapshot.parent_id,
+            worktree: entry_snapshot.worktree,
+            settings: entry_snapshot.settings,
+        },
+        was_renamed: final_branch != trimmed,
+        requested_branch: trimmed.to_string(),
     })
 }
 
@@ -541,7 +726,7 @@ pub(crate) async fn rename_worktree_upstream_core<
     id: String,
     old_branch: String,
     new_branch: String,
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
     resolve_git_root: FResolveGitRoot,
     git_branch_exists: FBranchExists,
     git_find_remote_for_branch: FFindRemote,
@@ -572,7 +757,7 @@ where
     }
 
     let (_entry, parent) = {
-        let workspaces = workspaces.lock().await;
+        let workspaces = workspaces.read().await;
         let entry = workspaces
             .get(&id)
             .cloned()
@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::backend::events::AppServerEvent;
+use crate::shared::git_core::run_git_command;
+use crate::types::{AgentBackendKind, WorkspaceEntry};
+
+/// Thread/turn whose `fileChange` item last touched a path in a workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileChangeAttribution {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+    pub(crate) item_id: Option<String>,
+}
+
+/// Last writer per (workspace, path), fed from completed `fileChange` items.
+/// Kept in memory only: attributions for edits made before startup are unknown.
+#[derive(Default)]
+pub(crate) struct ChangeAttributions {
+    by_workspace: Mutex<HashMap<String, HashMap<String, FileChangeAttribution>>>,
+}
+
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .filter(|value| !value.is_empty())
+}
+
+impl ChangeAttributions {
+    pub(crate) fn record(&self, event: &AppServerEvent) {
+        if event.message.get("method").and_then(Value::as_str) != Some("item/completed") {
+            return;
+        }
+        let params = event.message.get("params").unwrap_or(&Value::Null);
+        let item = params.get("item").unwrap_or(&Value::Null);
+        if item.get("type").and_then(Value::as_str) != Some("fileChange") {
+            return;
+        }
+        let Some(thread_id) = str_field(params, &["threadId", "thread_id"]) else {
+            return;
+        };
+        let attribution = FileChangeAttribution {
+            thread_id,
+            turn_id: str_field(params, &["turnId", "turn_id"]),
+            item_id: str_field(item, &["id"]),
+        };
+        let Some(changes) = item.get("changes").and_then(Value::as_array) else {
+            return;
+        };
+        let Ok(mut by_workspace) = self.by_workspace.lock() else {
+            return;
+        };
+        let paths = by_workspace.entry(event.workspace_id.clone()).or_default();
+        for change in changes {
+            if let Some(path) = str_field(change, &["path"]) {
+                paths.insert(path, attribution.clone());
+            }
+        }
+    }
+
+    /// Attributions for `workspace_id`, keyed by path relative to `root`.
+    pub(crate) fn for_workspace(
+        &self,
+        workspace_id: &str,
+        root: &Path,
+    ) -> HashMap<String, FileChangeAttribution> {
+        let Ok(by_workspace) = self.by_workspace.lock() else {
+            return HashMap::new();
+        };
+        let Some(paths) = by_workspace.get(workspace_id) else {
+            return HashMap::new();
+        };
+        paths
+            .iter()
+            .map(|(path, attribution)| {
+                let relative = Path::new(path)
+                    .strip_prefix(root)
+                    .map(|relative| relative.to_string_lossy().to_string())
+                    .unwrap_or_else(|_| path.clone());
+                (relative.replace('\\', "/"), attribution.clone())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeFileChange {
+    pub(crate) status: String,
+    pub(crate) additions: i64,
+    pub(crate) deletions: i64,
+    pub(crate) attribution: Option<FileChangeAttribution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeCompareFile {
+    pub(crate) path: String,
+    pub(crate) a: Option<WorktreeFileChange>,
+    pub(crate) b: Option<WorktreeFileChange>,
+    /// Both sides changed the file and ended up with the same content.
+    pub(crate) identical: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeCompareSide {
+    pub(crate) workspace_id: String,
+    pub(crate) branch: Option<String>,
+    pub(crate) head: String,
+    pub(crate) agent_backend: AgentBackendKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeComparison {
+    pub(crate) parent_id: String,
+    pub(crate) base: String,
+    pub(crate) a: WorktreeCompareSide,
+    pub(crate) b: WorktreeCompareSide,
+    pub(crate) files: Vec<WorktreeCompareFile>,
+}
+
+fn resolve_pair(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    a_id: &str,
+    b_id: &str,
+) -> Result<(WorkspaceEntry, WorkspaceEntry, String), String> {
+    if a_id == b_id {
+        return Err("Cannot compare a worktree with itself.".to_string());
+    }
+    let lookup = |id: &str| {
+        workspaces
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("workspace not found: {id}"))
+    };
+    let a = lookup(a_id)?;
+    let b = lookup(b_id)?;
+    for entry in [&a, &b] {
+        if !entry.kind.is_worktree() {
+            return Err(format!("{} is not a worktree.", entry.name));
+        }
+    }
+    match (&a.parent_id, &b.parent_id) {
+        (Some(a_parent), Some(b_parent)) if a_parent == b_parent => {
+            let parent_id = a_parent.clone();
+            Ok((a, b, parent_id))
+        }
+        _ => Err("Worktrees must share the same parent workspace.".to_string()),
+    }
+}
+
+/// Files changed relative to `base` (committed and uncommitted), keyed by
+/// repo-relative path, plus untracked files.
+async fn changed_files(
+    root: &PathBuf,
+    base: &str,
+) -> Result<BTreeMap<String, (String, i64, i64)>, String> {
+    let mut files = BTreeMap::new();
+    let name_status =
+        run_git_command(root, &["diff", "--no-renames", "--name-status", base]).await?;
+    for line in name_status.lines() {
+        let mut parts = line.splitn(2, '\t');
+        let (Some(status), Some(path)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        files.insert(path.to_string(), (status.to_string(), 0, 0));
+    }
+    let numstat = run_git_command(root, &["diff", "--no-renames", "--numstat", base]).await?;
+    for line in numstat.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (Some(additions), Some(deletions), Some(path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if let Some(entry) = files.get_mut(path) {
+            // Binary files report "-" for both counts.
+            entry.1 = additions.parse().unwrap_or(0);
+            entry.2 = deletions.parse().unwrap_or(0);
+        }
+    }
+    let untracked = run_git_command(root, &["ls-files", "--others", "--exclude-standard"]).await?;
+    for path in untracked.lines().filter(|line| !line.is_empty()) {
+        let additions = std::fs::read_to_string(root.join(path))
+            .map(|content| content.lines().count() as i64)
+            .unwrap_or(0);
+        files.insert(path.to_string(), ("A".to_string(), additions, 0));
+    }
+    Ok(files)
+}
+
+fn same_content(a_root: &Path, b_root: &Path, path: &str) -> bool {
+    match (
+        std::fs::read(a_root.join(path)),
+        std::fs::read(b_root.join(path)),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(a), Err(b)) => {
+            a.kind() == std::io::ErrorKind::NotFound && b.kind() == std::io::ErrorKind::NotFound
+        }
+        _ => false,
+    }
+}
+
+/// File-level differences between two worktrees of the same parent, each
+/// measured against their merge base so only the competing edits show up.
+pub(crate) async fn compare_worktrees_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    attributions: &ChangeAttributions,
+    a_id: String,
+    b_id: String,
+) -> Result<WorktreeComparison, String> {
+    let (a, b, parent_id) = {
+        let workspaces = workspaces.read().await;
+        resolve_pair(&workspaces, &a_id, &b_id)?
+    };
+    let a_root = PathBuf::from(&a.path);
+    let b_root = PathBuf::from(&b.path);
+    let a_head = run_git_command(&a_root, &["rev-parse", "HEAD"]).await?;
+    let b_head = run_git_command(&b_root, &["rev-parse", "HEAD"]).await?;
+    let base = run_git_command(&a_root, &["merge-base", &a_head, &b_head]).await?;
+
+    let a_files = changed_files(&a_root, &base).await?;
+    let b_files = changed_files(&b_root, &base).await?;
+    let a_attributions = attributions.for_workspace(&a.id, &a_root);
+    let b_attributions = attributions.for_workspace(&b.id, &b_root);
+
+    let mut paths = a_files.keys().chain(b_files.keys()).collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+    let side_change = |files: &BTreeMap<String, (String, i64, i64)>,
+                       attributions: &HashMap<String, FileChangeAttribution>,
+                       path: &str| {
+        files
+            .get(path)
+            .map(|(status, additions, deletions)| WorktreeFileChange {
+                status: status.clone(),
+                additions: *additions,
+                deletions: *deletions,
+                attribution: attributions.get(path).cloned(),
+            })
+    };
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let a_change = side_change(&a_files, &a_attributions, path);
+            let b_change = side_change(&b_files, &b_attributions, path);
+            let identical =
+                a_change.is_some() && b_change.is_some() && same_content(&a_root, &b_root, path);
+            WorktreeCompareFile {
+                path: path.clone(),
+                a: a_change,
+                b: b_change,
+                identical,
+            }
+        })
+        .collect();
+
+    let side = |entry: &WorkspaceEntry, head: String| WorktreeCompareSide {
+        workspace_id: entry.id.clone(),
+        branch: entry
+            .worktree
+            .as_ref()
+            .map(|worktree| worktree.branch.clone()),
+        head,
+        agent_backend: entry.settings.agent_backend.unwrap_or_default(),
+    };
+    Ok(WorktreeComparison {
+        parent_id,
+        base,
+        a: side(&a, a_head),
+        b: side(&b, b_head),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+    use serde_json::json;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn worktree_entry(id: &str, path: &Path, branch: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("parent".to_string()),
+            worktree: Some(WorktreeInfo {
+                branch: branch.to_string(),
+            }),
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn records_file_change_items_relative_to_workspace_root() {
+        let attributions = ChangeAttributions::default();
+        attributions.record(&AppServerEvent {
+            workspace_id: "ws-a".to_string(),
+            message: json!({
+                "method": "item/completed",
+                "params": {
+                    "threadId": "thread-1",
+                    "turnId": "turn-2",
+                    "item": {
+                        "id": "item-3",
+                        "type": "fileChange",
+                        "changes": [{ "path": "/repo/a/src/lib.rs", "kind": "update" }]
+                    }
+                }
+            }),
+        });
+        attributions.record(&AppServerEvent {
+            workspace_id: "ws-a".to_string(),
+            message: json!({
+                "method": "item/completed",
+                "params": { "threadId": "thread-1", "item": { "type": "commandExecution" } }
+            }),
+        });
+
+        let map = attributions.for_workspace("ws-a", Path::new("/repo/a"));
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            map.get("src/lib.rs"),
+            Some(&FileChangeAttribution {
+                thread_id: "thread-1".to_string(),
+                turn_id: Some("turn-2".to_string()),
+                item_id: Some("item-3".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn compare_worktrees_reports_per_side_changes() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let root = std::env::temp_dir()
+                .join(format!("codex-monitor-compare-{}", uuid::Uuid::new_v4()));
+            let main = root.join("main");
+            std::fs::create_dir_all(&main).expect("create repo");
+            git(&main, &["init", "-q", "-b", "main"]);
+            git(&main, &["config", "user.email", "test@example.com"]);
+            git(&main, &["config", "user.name", "Test"]);
+            std::fs::write(main.join("shared.txt"), "base\n").expect("write");
+            std::fs::write(main.join("same.txt"), "base\n").expect("write");
+            git(&main, &["add", "."]);
+            git(&main, &["commit", "-q", "-m", "base"]);
+
+            let a_path = root.join("a");
+            let b_path = root.join("b");
+            git(
+                &main,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    "attempt-a",
+                    a_path.to_str().unwrap(),
+                ],
+            );
+            git(
+                &main,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    "attempt-b",
+                    b_path.to_str().unwrap(),
+                ],
+            );
+            std::fs::write(a_path.join("shared.txt"), "from a\n").expect("write");
+            std::fs::write(b_path.join("shared.txt"), "from b\nand more\n").expect("write");
+            std::fs::write(a_path.join("same.txt"), "agreed\n").expect("write");
+            std::fs::write(b_path.join("same.txt"), "agreed\n").expect("write");
+            std::fs::write(b_path.join("only_b.txt"), "new\n").expect("write");
+
+            let a = worktree_entry("a", &a_path, "attempt-a");
+            let b = worktree_entry("b", &b_path, "attempt-b");
+            let workspaces = RwLock::new(HashMap::from([
+                (a.id.clone(), a.clone()),
+                (b.id.clone(), b.clone()),
+            ]));
+            let attributions = ChangeAttributions::default();
+            attributions.record(&AppServerEvent {
+                workspace_id: "b".to_string(),
+                message: json!({
+                    "method": "item/completed",
+                    "params": {
+                        "threadId": "thread-b",
+                        "turnId": "turn-b",
+                        "item": { "type": "fileChange", "changes": [{ "path": "only_b.txt" }] }
+                    }
+                }),
+            });
+
+            let comparison =
+                compare_worktrees_core(&workspaces, &attributions, "a".into(), "b".into())
+                    .await
+                    .expect("compare");
+            let _ = std::fs::remove_dir_all(&root);
+
+            assert_eq!(comparison.parent_id, "parent");
+            assert_eq!(comparison.a.branch.as_deref(), Some("attempt-a"));
+            let paths = comparison
+                .files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(paths, vec!["only_b.txt", "same.txt", "shared.txt"]);
+
+            let only_b = &comparison.files[0];
+            assert!(only_b.a.is_none());
+            let only_b_change = only_b.b.as_ref().expect("b change");
+            assert_eq!(only_b_change.status, "A");
+            assert_eq!(
+                only_b_change
+                    .attribution
+                    .as_ref()
+                    .and_then(|attribution| attribution.turn_id.as_deref()),
+                Some("turn-b")
+            );
+            assert!(comparison.files[1].identical);
+            let shared = &comparison.files[2];
+            assert!(!shared.identical);
+            assert_eq!(shared.a.as_ref().map(|change| change.additions), Some(1));
+            assert_eq!(shared.b.as_ref().map(|change| change.additions), Some(2));
+        });
+    }
+
+    #[test]
+    fn compare_worktrees_rejects_different_parents() {
+        let mut a = worktree_entry("a", Path::new("/tmp/a"), "a");
+        let b = worktree_entry("b", Path::new("/tmp/b"), "b");
+        a.parent_id = Some("other".to_string());
+        let workspaces = HashMap::from([(a.id.clone(), a), (b.id.clone(), b)]);
+        assert!(resolve_pair(&workspaces, "a", "b").is_err());
+        assert!(resolve_pair(&workspaces, "a", "a").is_err());
+    }
+}
@@ -1,8 +1,16 @@
 use std::path::PathBuf;
 
+use sha2::{Digest, Sha256};
+
+/// Names are never cut shorter than this, whatever the setting says.
+const MIN_WORKTREE_NAME_LENGTH: usize = 16;
+const NAME_HASH_LENGTH: usize = 8;
+
+/// Transliterates to ASCII (`Straße` -> `Strasse`, `日本` -> `Ri-Ben`) and
+/// replaces anything else that is unsafe in a folder name with `-`.
 fn sanitize_name(value: &str, fallback: &str) -> String {
     let mut result = String::new();
-    for ch in value.chars() {
+    for ch in deunicode::deunicode(value).chars() {
         if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
             result.push(ch);
         } else {
@@ -21,6 +29,28 @@ pub(crate) fn sanitize_worktree_name(branch: &str) -> String {
     sanitize_name(branch, "worktree")
 }
 
+/// Cuts a sanitized name to `max_length` characters. The tail is replaced
+/// with a hash of the full name so long names sharing a prefix stay apart.
+pub(crate) fn limit_worktree_name(name: &str, max_length: usize) -> String {
+    let max_length = max_length.max(MIN_WORKTREE_NAME_LENGTH);
+    if name.chars().count() <= max_length {
+        return name.to_string();
+    }
+    let digest = Sha256::digest(name.as_bytes());
+    let hash: String = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>()
+        .chars()
+        .take(NAME_HASH_LENGTH)
+        .collect();
+    let head: String = name
+        .chars()
+        .take(max_length - NAME_HASH_LENGTH - 1)
+        .collect();
+    format!("{}-{hash}", head.trim_end_matches(['-', '.']))
+}
+
 pub(crate) fn sanitize_clone_dir_name(name: &str) -> String {
     sanitize_name(name, "copy")
 }
@@ -40,8 +70,6 @@ pub(crate) fn unique_worktree_path_best_effort(base_dir: &PathBuf, name: &str) -
     candidate
 }
 
-// Used by daemon-only worktree creation paths.
-#[allow(dead_code)]
 pub(crate) fn unique_worktree_path_strict(
     base_dir: &PathBuf,
     name: &str,
@@ -98,3 +126,45 @@ pub(crate) fn null_device_path() -> &'static str {
         "/dev/null"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn unique_worktree_path_strict_skips_existing_entries() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let base = std::env::temp_dir().join(format!("codex-monitor-worktree-core-{nonce}"));
+        std::fs::create_dir_all(base.join("feature")).expect("create existing worktree");
+        std::fs::create_dir_all(base.join("feature-2")).expect("create existing worktree");
+
+        let path = unique_worktree_path_strict(&base, "feature").expect("unique path");
+        assert_eq!(path, base.join("feature-3"));
+        assert_eq!(
+            unique_worktree_path_strict(&base, "other").expect("unique path"),
+            base.join("other")
+        );
+        let _ = std::fs::remove_dir_all(&base);
+    }
+    #[test]
+    fn worktree_names_are_transliterated() {
+        assert_eq!(sanitize_worktree_name("feature/Straße"), "feature-Strasse");
+        assert_eq!(sanitize_worktree_name("fix/Жук"), "fix-Zhuk");
+        assert_eq!(sanitize_worktree_name("日本"), "Ri-Ben");
+    }
+
+    #[test]
+    fn long_worktree_names_keep_a_distinguishing_hash() {
+        assert_eq!(limit_worktree_name("short", 64), "short");
+        let first = limit_worktree_name(&format!("{}-one", "a".repeat(40)), 32);
+        let second = limit_worktree_name(&format!("{}-two", "a".repeat(40)), 32);
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+        assert!(first.starts_with(&"a".repeat(23)));
+        assert_eq!(limit_worktree_name(&"b".repeat(40), 1).len(), 16);
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::types::WorkspaceEntry;
+
+/// The reason `git worktree add` writes while it checks the worktree out.
+const INITIALIZING_REASON: &str = "initializing";
+/// An `initializing` lock older than this was left by an add that never
+/// finished.
+const INITIALIZING_GRACE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeLock {
+    /// The text git stored with the lock, if any.
+    pub(crate) reason: Option<String>,
+    /// When the lock file was written (ms since the epoch).
+    pub(crate) locked_at: Option<u64>,
+    /// Left behind by a `git worktree add` that did not finish; removed
+    /// automatically before the worktree is moved or removed.
+    pub(crate) stale: bool,
+}
+
+/// The worktree's admin folder (`<repo>/.git/worktrees/<name>`), read from
+/// the `gitdir:` line of its `.git` file.
+fn worktree_admin_dir(worktree_path: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(worktree_path.join(".git")).ok()?;
+    let gitdir = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))?
+        .trim();
+    if gitdir.is_empty() {
+        return None;
+    }
+    let gitdir = PathBuf::from(gitdir);
+    Some(if gitdir.is_absolute() {
+        gitdir
+    } else {
+        worktree_path.join(gitdir)
+    })
+}
+
+fn lock_file_path(worktree_path: &Path) -> Option<PathBuf> {
+    worktree_admin_dir(worktree_path).map(|dir| dir.join("locked"))
+}
+
+fn lock_from_file(path: &Path, now: SystemTime) -> Option<WorktreeLock> {
+    let metadata = fs::metadata(path).ok()?;
+    let reason = fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let modified = metadata.modified().ok();
+    let stale = reason.as_deref() == Some(INITIALIZING_REASON)
+        && modified
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= INITIALIZING_GRACE);
+    Some(WorktreeLock {
+        reason,
+        locked_at: modified
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as u64),
+        stale,
+    })
+}
+
+/// The lock on a linked worktree, or `None` when it is unlocked or is not a
+/// linked worktree.
+pub(crate) fn read_worktree_lock(worktree_path: &Path) -> Option<WorktreeLock> {
+    lock_from_file(&lock_file_path(worktree_path)?, SystemTime::now())
+}
+
+fn remove_lock_file(worktree_path: &Path) -> Result<(), String> {
+    let Some(path) = lock_file_path(worktree_path) else {
+        return Ok(());
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("Failed to remove worktree lock: {err}")),
+    }
+}
+
+/// Clears a stale lock so `git worktree move`/`remove` can proceed, and
+/// turns any other lock into an error that says how to release it.
+pub(crate) fn prepare_locked_worktree(worktree_path: &Path) -> Result<(), String> {
+    match read_worktree_lock(worktree_path) {
+        None => Ok(()),
+        Some(lock) if lock.stale => remove_lock_file(worktree_path),
+        Some(lock) => Err(match lock.reason {
+            Some(reason) => {
+                format!("Worktree is locked ({reason}). Run unlock_worktree to release it first.")
+            }
+            None => "Worktree is locked. Run unlock_worktree to release it first.".to_string(),
+        }),
+    }
+}
+
+/// Removes the lock on a worktree workspace, like `git worktree unlock`.
+/// A lock from an add that may still be running is left in place.
+pub(crate) async fn unlock_worktree_core(
+    workspaces: &RwLock<HashMap<String, WorkspaceEntry>>,
+    id: &str,
+) -> Result<Option<WorktreeLock>, String> {
+    let entry = {
+        let workspaces = workspaces.read().await;
+        workspaces
+            .get(id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?
+    };
+    if !entry.kind.is_worktree() {
+        return Err("Not a worktree workspace.".to_string());
+    }
+    let worktree_path = PathBuf::from(&entry.path);
+    let Some(lock) = read_worktree_lock(&worktree_path) else {
+        return Ok(None);
+    };
+    if lock.reason.as_deref() == Some(INITIALIZING_REASON) && !lock.stale {
+        return Err("Worktree is still being created; try again shortly.".to_string());
+    }
+    remove_lock_file(&worktree_path)?;
+    Ok(Some(lock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_worktree(label: &str) -> (PathBuf, PathBuf) {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let base =
+            std::env::temp_dir().join(format!("codex-monitor-worktree-lock-{label}-{nonce}"));
+        let worktree = base.join("worktree");
+        let admin = base
+            .join("repo")
+            .join(".git")
+            .join("worktrees")
+            .join("worktree");
+        fs::create_dir_all(&worktree).expect("create worktree");
+        fs::create_dir_all(&admin).expect("create admin dir");
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", admin.to_string_lossy()),
+        )
+        .expect("write .git file");
+        (base, worktree)
+    }
+
+    #[test]
+    fn reads_lock_reason_through_the_gitdir_file() {
+        let (base, worktree) = temp_worktree("read");
+        assert_eq!(read_worktree_lock(&worktree), None);
+
+        let lock_path = lock_file_path(&worktree).expect("lock path");
+        fs::write(&lock_path, "on a USB drive\n").expect("write lock");
+        let lock = read_worktree_lock(&worktree).expect("locked");
+        assert_eq!(lock.reason.as_deref(), Some("on a USB drive"));
+        assert!(!lock.stale);
+        assert!(prepare_locked_worktree(&worktree)
+            .expect_err("held lock")
+            .contains("on a USB drive"));
+        assert!(lock_path.exists());
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn only_old_initializing_locks_are_stale() {
+        let (base, worktree) = temp_worktree("stale");
+        let lock_path = lock_file_path(&worktree).expect("lock path");
+        fs::write(&lock_path, INITIALIZING_REASON).expect("write lock");
+
+        let now = SystemTime::now();
+        assert!(!lock_from_file(&lock_path, now).expect("locked").stale);
+        let later = now + INITIALIZING_GRACE + Duration::from_secs(1);
+        assert!(lock_from_file(&lock_path, later).expect("locked").stale);
+
+        fs::write(&lock_path, "manual").expect("write lock");
+        assert!(!lock_from_file(&lock_path, later).expect("locked").stale);
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn main_worktrees_have_no_lock() {
+        let (base, _) = temp_worktree("main");
+        assert_eq!(read_worktree_lock(&base.join("repo")), None);
+        assert!(prepare_locked_worktree(&base.join("repo")).is_ok());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -3,16 +3,36 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 use tokio::process::Child;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
+use crate::backend::services::BackendServices;
 use crate::dictation::DictationState;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend::supervisor::RemoteSupervisor;
+use crate::secrets::{claim_backend, SecretBackend};
+use crate::shared::broadcast_core::BroadcastRunStore;
 use crate::shared::codex_core::CodexLoginCancelState;
+use crate::shared::notifications_core::NotificationCenter;
+use crate::shared::playbooks_core::PlaybookStore;
+use crate::shared::prompt_library_core::PromptLibrary;
+use crate::shared::scheduled_messages_core::ScheduledMessageStore;
+use crate::shared::search_core::SearchIndex;
+use crate::shared::state_snapshot_core::StateSnapshotStore;
+use crate::shared::thread_meta_core::ThreadMetaStore;
+use crate::shared::thread_order_core::ThreadOrderStore;
+use crate::shared::thread_tags_core::ThreadTagStore;
+use crate::shared::workspace_recency_core::WorkspaceRecencyStore;
+use crate::shared::workspace_summary_core::WorkspaceSummaryCache;
+use crate::shared::worktree_compare_core::ChangeAttributions;
 use crate::storage::{read_settings, read_workspaces};
+use crate::tailscale::watch::TailscaleWatch;
 use crate::types::{AppSettings, TcpDaemonState, TcpDaemonStatus, WorkspaceEntry};
 
 pub(crate) struct TcpDaemonRuntime {
     pub(crate) child: Option<Child>,
     pub(crate) status: TcpDaemonStatus,
+    /// Where the running child writes its output.
+    pub(crate) log_path: Option<PathBuf>,
 }
 
 impl Default for TcpDaemonRuntime {
@@ -26,21 +46,41 @@ impl Default for TcpDaemonRuntime {
                 last_error: None,
                 listen_addr: None,
             },
+            log_path: None,
         }
     }
 }
 
 pub(crate) struct AppState {
-    pub(crate) workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
-    pub(crate) sessions: Mutex<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
+    pub(crate) workspaces: RwLock<HashMap<String, WorkspaceEntry>>,
+    pub(crate) sessions: RwLock<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
     pub(crate) terminal_sessions: Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
+    /// `push_register_device` params, replayed on every new remote
+    /// connection so the daemon knows this device is reachable.
+    pub(crate) push_registration: Mutex<Option<serde_json::Value>>,
     pub(crate) storage_path: PathBuf,
     pub(crate) settings_path: PathBuf,
     pub(crate) app_settings: Mutex<AppSettings>,
     pub(crate) dictation: Mutex<DictationState>,
     pub(crate) codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
     pub(crate) tcp_daemon: Mutex<TcpDaemonRuntime>,
+    pub(crate) change_attributions: ChangeAttributions,
+    pub(crate) thread_tags: ThreadTagStore,
+    pub(crate) broadcast_runs: BroadcastRunStore,
+    pub(crate) thread_meta: ThreadMetaStore,
+    pub(crate) thread_order: ThreadOrderStore,
+    pub(crate) notification_center: NotificationCenter,
+    pub(crate) prompt_library: PromptLibrary,
+    pub(crate) playbooks: PlaybookStore,
+    pub(crate) state_snapshot: StateSnapshotStore,
+    pub(crate) scheduled_messages: ScheduledMessageStore,
+    pub(crate) search_index: SearchIndex,
+    pub(crate) workspace_summaries: WorkspaceSummaryCache,
+    pub(crate) workspace_recency: WorkspaceRecencyStore,
+    pub(crate) remote_supervisor: RemoteSupervisor,
+    pub(crate) tailscale_watch: TailscaleWatch,
+    pub(crate) services: Arc<BackendServices>,
 }
 
 impl AppState {
@@ -51,19 +91,73 @@ impl AppState {
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| ".".into()));
         let storage_path = data_dir.join("workspaces.json");
         let settings_path = data_dir.join("settings.json");
+        let services = BackendServices::load(&data_dir);
+        let thread_tags = ThreadTagStore::load(data_dir.join("thread-tags.json"));
+        let broadcast_runs = BroadcastRunStore::load(data_dir.join("broadcast-runs.json"));
+        let thread_meta = ThreadMetaStore::load(data_dir.join("thread-meta.json"));
+        let thread_order = ThreadOrderStore::load(data_dir.join("thread-order.json"));
+        let notification_center = NotificationCenter::load(data_dir.join("notifications.json"));
+        let prompt_library = PromptLibrary::load(data_dir.join("prompt-library.json"));
+        let playbooks = PlaybookStore::load(data_dir.join("playbooks.json"));
+        let state_snapshot = StateSnapshotStore::new(data_dir.join("state-snapshot.json"));
+        let scheduled_messages =
+            ScheduledMessageStore::load(data_dir.join("scheduled-messages.json"));
+        let search_index = SearchIndex::load(data_dir.join("search-index.sqlite"));
+        let workspace_recency =
+            WorkspaceRecencyStore::load(data_dir.join("workspace-recency.json"));
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
+        let backend = if cfg!(any(target_os = "android", target_os = "ios")) {
+            SecretBackend::File
+        } else {
+            SecretBackend::Keychain
+        };
+        if let Err(error) = claim_backend(&data_dir, backend) {
+            eprintln!("Failed to record the secrets store: {error}");
+        }
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        services.apply_settings(&app_settings);
+        services
+            .run_manager
+            .set_event_sink(TauriEventSink::new(app.clone()));
+        services
+            .operations
+            .set_event_sink(TauriEventSink::new(app.clone()));
         Self {
-            workspaces: Mutex::new(workspaces),
-            sessions: Mutex::new(HashMap::new()),
+            workspaces: RwLock::new(workspaces),
+            sessions: RwLock::new(HashMap::new()),
             terminal_sessions: Mutex::new(HashMap::new()),
             remote_backend: Mutex::new(None),
+            push_registration: Mutex::new(None),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
             dictation: Mutex::new(DictationState::default()),
             codex_login_cancels: Mutex::new(HashMap::new()),
             tcp_daemon: Mutex::new(TcpDaemonRuntime::default()),
+            change_attributions: ChangeAttributions::default(),
+            thread_tags,
+            broadcast_runs,
+            thread_meta,
+            thread_order,
+            notification_center,
+            prompt_library,
+            playbooks,
+            state_snapshot,
+            scheduled_messages,
+            search_index,
+            workspace_summaries: WorkspaceSummaryCache::default(),
+            workspace_recency,
+            remote_supervisor: RemoteSupervisor::default(),
+            tailscale_watch: TailscaleWatch::default(),
+            services,
         }
     }
+
+    /// The app data dir, which holds `workspaces.json` and `settings.json`.
+    pub(crate) fn data_dir(&self) -> PathBuf {
+        self.storage_path
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
 }
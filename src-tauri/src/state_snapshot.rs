@@ -0,0 +1,48 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::shared::state_snapshot_core::{self, StateSnapshot};
+use crate::state::AppState;
+use crate::types::{AppSettings, BackendMode};
+
+fn backend_key(settings: &AppSettings) -> String {
+    match settings.backend_mode {
+        BackendMode::Local => "local".to_string(),
+        BackendMode::Remote => format!("remote:{}", settings.remote_backend_host),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_state_snapshot(
+    state: State<'_, AppState>,
+    snapshot: StateSnapshot,
+) -> Result<(), String> {
+    state_snapshot_core::update_state_snapshot_core(&state.state_snapshot, snapshot);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn restore_state_snapshot(
+    state: State<'_, AppState>,
+) -> Result<Option<StateSnapshot>, String> {
+    let settings = state.app_settings.lock().await.clone();
+    let backend = backend_key(&settings);
+    let workspaces = state.workspaces.read().await;
+    let known = matches!(settings.backend_mode, BackendMode::Local).then_some(&*workspaces);
+    Ok(state_snapshot_core::restore_state_snapshot_core(
+        &state.state_snapshot,
+        &backend,
+        known,
+    ))
+}
+
+/// Writes the last reported UI state before the app exits.
+pub(crate) fn hibernate_for_exit(app_handle: &AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let backend = tauri::async_runtime::block_on(async {
+        let settings = state.app_settings.lock().await;
+        backend_key(&settings)
+    });
+    if let Err(error) = state_snapshot_core::hibernate_core(&state.state_snapshot, backend) {
+        eprintln!("Failed to hibernate app state: {error}");
+    }
+}
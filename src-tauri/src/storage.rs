@@ -1,8 +1,11 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
+use crate::secrets::{self, SecretBackend};
 use crate::types::{AppSettings, WorkspaceEntry, WorkspaceSettings};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 fn normalize_windows_namespace_path(path: &str) -> String {
     if path.is_empty() {
@@ -120,7 +123,7 @@ fn normalize_app_settings(settings: AppSettings) -> (AppSettings, bool) {
 }
 
 fn try_rewrite_settings_with_normalized_paths(path: &PathBuf, settings: &AppSettings) {
-    if let Err(error) = write_settings(path, settings) {
+    if let Err(error) = write_settings_blocking(path, settings) {
         eprintln!(
             "read_settings: failed to persist normalized settings paths to {}: {}",
             path.display(),
@@ -142,13 +145,28 @@ pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, Workspac
         .collect())
 }
 
-pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
+/// Writes `workspaces.json` on the blocking pool.
+pub(crate) async fn write_workspaces(
+    path: &PathBuf,
+    entries: &[WorkspaceEntry],
+    options: StoreWriteOptions,
+) -> Result<(), String> {
+    let path = path.clone();
+    let entries = entries.to_vec();
+    run_blocking(move || write_workspaces_blocking(&path, &entries, options)).await
+}
+
+fn write_workspaces_blocking(
+    path: &PathBuf,
+    entries: &[WorkspaceEntry],
+    options: StoreWriteOptions,
+) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     let (entries, _) = normalize_workspace_entries(entries.iter().cloned());
     let data = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    write_store_file(path, data.as_bytes(), options)
 }
 
 pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
@@ -157,26 +175,257 @@ pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
     }
     let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
     let mut value: Value = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let has_inline_secrets = open_settings_secrets(settings_dir(path), &mut value);
     migrate_follow_up_message_behavior(&mut value);
-    match serde_json::from_value(value.clone()) {
-        Ok(settings) => Ok(finalize_loaded_settings(path, settings)),
+    let settings = match serde_json::from_value(value.clone()) {
+        Ok(settings) => finalize_loaded_settings(path, settings),
         Err(_) => {
             sanitize_remote_settings_for_tcp_only(&mut value);
             migrate_follow_up_message_behavior(&mut value);
             serde_json::from_value(value)
                 .map(|settings| finalize_loaded_settings(path, settings))
-                .map_err(|e| e.to_string())
+                .map_err(|e| e.to_string())?
+        }
+    };
+    // Settings written before secrets moved out of the file.
+    if has_inline_secrets && secrets::backend_for(settings_dir(path)).is_some() {
+        if let Err(error) = write_settings_blocking(path, &settings) {
+            eprintln!(
+                "read_settings: failed to move secrets out of {}: {}",
+                path.display(),
+                error
+            );
         }
     }
+    Ok(settings)
 }
 
-pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
+/// Writes `settings.json`, and the secrets it references, on the blocking
+/// pool, with the store options the settings themselves carry.
+pub(crate) async fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
+    let path = path.clone();
+    let settings = settings.clone();
+    run_blocking(move || write_settings_blocking(&path, &settings)).await
+}
+
+fn write_settings_blocking(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     let (settings, _) = normalize_app_settings(settings.clone());
-    let data = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    let options = StoreWriteOptions::from_settings(&settings);
+    let mut value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    let dir = settings_dir(path);
+    if let Some(backend) = secrets::backend_for(dir) {
+        seal_settings_secrets(dir, backend, &mut value)?;
+    }
+    let data = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    write_store_file(path, data.as_bytes(), options)
+}
+
+const DEFAULT_STORE_BACKUPS: usize = 3;
+
+/// How a store write treats durability: `storageFsync` and
+/// `storageBackups` from the app settings. Callers take them from the
+/// settings held in app or daemon state at the time of the write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StoreWriteOptions {
+    fsync: bool,
+    backups: usize,
+}
+
+impl Default for StoreWriteOptions {
+    fn default() -> Self {
+        Self {
+            fsync: true,
+            backups: DEFAULT_STORE_BACKUPS,
+        }
+    }
+}
+
+impl StoreWriteOptions {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            fsync: settings.storage_fsync,
+            backups: settings.storage_backups as usize,
+        }
+    }
+}
+
+async fn run_blocking<F>(write: F) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(write)
+        .await
+        .map_err(|err| format!("Store write failed: {err}"))?
+}
+
+/// Replaces `path` with `data` through a temp file and a rename, after
+/// rotating the previous contents into numbered backups. A lock file next to
+/// the store orders every writer, whether another thread of this process or
+/// another process sharing the data dir, so writes never interleave.
+fn write_store_file(path: &Path, data: &[u8], options: StoreWriteOptions) -> Result<(), String> {
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(store_sibling(path, ".lock"))
+        .map_err(|e| e.to_string())?;
+    lock.lock()
+        .map_err(|e| format!("Failed to lock {}: {e}", path.display()))?;
+
+    if path.exists() {
+        rotate_store_backups(path, options.backups)?;
+    }
+    let tmp = store_sibling(path, ".tmp");
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(data)?;
+        if options.fsync {
+            file.sync_all()?;
+        }
+        Ok(())
+    });
+    if let Err(error) = written.and_then(|()| std::fs::rename(&tmp, path)) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {error}", path.display()));
+    }
+    #[cfg(unix)]
+    if options.fsync {
+        if let Some(parent) = path.parent() {
+            let _ = File::open(parent).and_then(|dir| dir.sync_all());
+        }
+    }
+    Ok(())
+}
+
+fn store_sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn store_backup_path(path: &Path, index: usize) -> PathBuf {
+    store_sibling(path, &format!(".bak.{index}"))
+}
+
+/// Shifts `.bak.1`..`.bak.N-1` up by one and copies the current file to
+/// `.bak.1`. Copying keeps `path` in place until the new contents land.
+fn rotate_store_backups(path: &Path, keep: usize) -> Result<(), String> {
+    let mut index = keep + 1;
+    while store_backup_path(path, index).exists() {
+        let _ = std::fs::remove_file(store_backup_path(path, index));
+        index += 1;
+    }
+    if keep == 0 {
+        return Ok(());
+    }
+    for index in (1..keep).rev() {
+        let from = store_backup_path(path, index);
+        if from.exists() {
+            std::fs::rename(&from, store_backup_path(path, index + 1))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    std::fs::copy(path, store_backup_path(path, 1)).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn settings_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new("."))
+}
+
+/// Visits each settings field that holds a secret as `(name, parent, key)`.
+fn for_each_secret_field(
+    value: &mut Value,
+    mut visit: impl FnMut(String, &mut Map<String, Value>, &str),
+) {
+    let Value::Object(root) = value else {
+        return;
+    };
+    visit("remoteBackendToken".to_string(), root, "remoteBackendToken");
+    if let Some(Value::Array(remote_backends)) = root.get_mut("remoteBackends") {
+        for entry in remote_backends {
+            let Value::Object(entry_obj) = entry else {
+                continue;
+            };
+            let Some(id) = entry_obj
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            visit(format!("remoteBackends/{id}/token"), entry_obj, "token");
+        }
+    }
+    if let Some(Value::Object(approval_chat)) = root.get_mut("approvalChat") {
+        visit(
+            "approvalChat/botToken".to_string(),
+            approval_chat,
+            "botToken",
+        );
+        visit(
+            "approvalChat/appToken".to_string(),
+            approval_chat,
+            "appToken",
+        );
+    }
+    if let Some(Value::Object(push_relay)) = root.get_mut("pushRelay") {
+        visit("pushRelay/apiKey".to_string(), push_relay, "apiKey");
+    }
+}
+
+/// Replaces secret references with their values. A secret that cannot be
+/// read keeps its reference, so saving settings later leaves it untouched.
+/// Returns whether any secret was stored inline.
+fn open_settings_secrets(dir: &Path, value: &mut Value) -> bool {
+    let mut has_inline_secrets = false;
+    for_each_secret_field(value, |name, parent, key| {
+        let Some(Value::String(stored)) = parent.get(key) else {
+            return;
+        };
+        if !secrets::is_reference(stored) {
+            has_inline_secrets |= !stored.is_empty();
+            return;
+        }
+        match secrets::resolve(dir, stored) {
+            Ok(secret) => {
+                parent.insert(key.to_string(), Value::String(secret));
+            }
+            Err(error) => {
+                eprintln!("read_settings: failed to read secret {name}: {error}");
+            }
+        }
+    });
+    has_inline_secrets
+}
+
+/// Moves non-empty secrets into `backend`, leaving references behind.
+fn seal_settings_secrets(
+    dir: &Path,
+    backend: SecretBackend,
+    value: &mut Value,
+) -> Result<(), String> {
+    let mut result = Ok(());
+    for_each_secret_field(value, |name, parent, key| {
+        if result.is_err() {
+            return;
+        }
+        let Some(Value::String(secret)) = parent.get(key) else {
+            return;
+        };
+        if secret.is_empty() || secrets::is_reference(secret) {
+            return;
+        }
+        match secrets::store(dir, backend, &name, secret) {
+            Ok(reference) => {
+                parent.insert(key.to_string(), Value::String(reference));
+            }
+            Err(error) => result = Err(error),
+        }
+    });
+    result
 }
 
 fn finalize_loaded_settings(path: &PathBuf, settings: AppSettings) -> AppSettings {
@@ -232,8 +481,14 @@ fn migrate_follow_up_message_behavior(value: &mut Value) {
 
 #[cfg(test)]
 mod tests {
-    use super::{read_settings, read_workspaces, write_settings, write_workspaces};
+    use super::{
+        open_settings_secrets, read_settings, read_workspaces, seal_settings_secrets,
+        store_backup_path, store_sibling, write_settings_blocking, write_workspaces_blocking,
+        StoreWriteOptions, DEFAULT_STORE_BACKUPS,
+    };
+    use crate::secrets::{self, SecretBackend};
     use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use serde_json::json;
     use uuid::Uuid;
 
     #[test]
@@ -258,7 +513,8 @@ mod tests {
             settings: settings.clone(),
         };
 
-        write_workspaces(&path, &[entry]).expect("write workspaces");
+        write_workspaces_blocking(&path, &[entry], StoreWriteOptions::default())
+            .expect("write workspaces");
         let read = read_workspaces(&path).expect("read workspaces");
         let stored = read.get("w1").expect("stored workspace");
         assert_eq!(stored.settings.sort_order, Some(5));
@@ -283,7 +539,8 @@ mod tests {
             settings: WorkspaceSettings::default(),
         };
 
-        write_workspaces(&path, &[entry]).expect("write workspaces");
+        write_workspaces_blocking(&path, &[entry], StoreWriteOptions::default())
+            .expect("write workspaces");
 
         let read = read_workspaces(&path).expect("read workspaces");
         let stored = read.get("w1").expect("stored workspace");
@@ -320,7 +577,10 @@ mod tests {
         let persisted_entries: Vec<WorkspaceEntry> =
             serde_json::from_str(&persisted).expect("deserialize persisted workspaces");
         assert_eq!(persisted_entries.len(), 1);
-        assert_eq!(persisted_entries[0].path, r"\\?\I:\gpt-projects\json-composer");
+        assert_eq!(
+            persisted_entries[0].path,
+            r"\\?\I:\gpt-projects\json-composer"
+        );
     }
 
     #[test]
@@ -412,7 +672,7 @@ mod tests {
         let mut settings = AppSettings::default();
         settings.global_worktrees_folder = Some(r"\\?\I:\gpt-projects\worktrees".to_string());
 
-        write_settings(&path, &settings).expect("write settings");
+        write_settings_blocking(&path, &settings).expect("write settings");
         let read = read_settings(&path).expect("read settings");
         assert_eq!(
             read.global_worktrees_folder.as_deref(),
@@ -469,4 +729,187 @@ mod tests {
         let settings = read_settings(&path).expect("read settings");
         assert_eq!(settings.follow_up_message_behavior, "queue");
     }
+
+    #[test]
+    fn workspace_writes_replace_the_file_and_rotate_backups() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let path = temp_dir.join("workspaces.json");
+        let entry = |name: &str| WorkspaceEntry {
+            id: "w1".to_string(),
+            name: name.to_string(),
+            path: "/tmp".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+
+        for round in 0..=DEFAULT_STORE_BACKUPS + 1 {
+            write_workspaces_blocking(
+                &path,
+                &[entry(&format!("round-{round}"))],
+                StoreWriteOptions::default(),
+            )
+            .expect("write");
+        }
+
+        let read = read_workspaces(&path).expect("read");
+        let last = DEFAULT_STORE_BACKUPS + 1;
+        assert_eq!(read["w1"].name, format!("round-{last}"));
+        let newest_backup = read_workspaces(&store_backup_path(&path, 1)).expect("read backup");
+        assert_eq!(newest_backup["w1"].name, format!("round-{}", last - 1));
+        assert!(store_backup_path(&path, DEFAULT_STORE_BACKUPS).exists());
+        assert!(!store_backup_path(&path, DEFAULT_STORE_BACKUPS + 1).exists());
+        assert!(!store_sibling(&path, ".tmp").exists());
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[test]
+    fn store_writes_follow_the_options_they_are_given() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let workspaces_path = temp_dir.join("workspaces.json");
+        let settings_path = temp_dir.join("settings.json");
+        let entry = WorkspaceEntry {
+            id: "w1".to_string(),
+            name: "Workspace".to_string(),
+            path: "/tmp".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        let mut settings = AppSettings::default();
+        settings.storage_fsync = false;
+        settings.storage_backups = 0;
+
+        for _ in 0..2 {
+            write_workspaces_blocking(
+                &workspaces_path,
+                &[entry.clone()],
+                StoreWriteOptions::from_settings(&settings),
+            )
+            .expect("write workspaces");
+            write_settings_blocking(&settings_path, &settings).expect("write settings");
+        }
+        assert!(!store_backup_path(&workspaces_path, 1).exists());
+        assert!(!store_backup_path(&settings_path, 1).exists());
+
+        settings.storage_backups = 1;
+        for _ in 0..2 {
+            write_settings_blocking(&settings_path, &settings).expect("write settings");
+        }
+        assert!(store_backup_path(&settings_path, 1).exists());
+        assert!(!store_backup_path(&settings_path, 2).exists());
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[test]
+    fn concurrent_workspace_writes_each_land_whole() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let path = temp_dir.join("workspaces.json");
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for round in 0..10 {
+                        let entry = WorkspaceEntry {
+                            id: format!("w{writer}"),
+                            name: format!("round-{round}"),
+                            path: "/tmp".to_string(),
+                            kind: WorkspaceKind::Main,
+                            parent_id: None,
+                            worktree: None,
+                            settings: WorkspaceSettings::default(),
+                        };
+                        write_workspaces_blocking(&path, &[entry], StoreWriteOptions::default())
+                            .expect("write");
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("writer thread");
+        }
+
+        let read = read_workspaces(&path).expect("read");
+        assert_eq!(read.len(), 1);
+        assert!(read.values().all(|entry| entry.name == "round-9"));
+        assert!(!store_sibling(&path, ".tmp").exists());
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[test]
+    fn settings_secrets_are_sealed_into_references_and_reopened() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let mut value = json!({
+            "remoteBackendToken": "token-1",
+            "remoteBackends": [
+                { "id": "remote-a", "token": "token-a" },
+                { "id": "remote-b", "token": null }
+            ],
+            "approvalChat": { "provider": "slack", "botToken": "xoxb-1", "channelId": "C1" },
+            "pushRelay": { "url": "https://relay.example", "apiKey": "relay-key" }
+        });
+
+        seal_settings_secrets(&temp_dir, SecretBackend::File, &mut value).expect("seal");
+        assert_eq!(
+            value["remoteBackendToken"],
+            "secret://file/remoteBackendToken"
+        );
+        assert_eq!(
+            value["remoteBackends"][0]["token"],
+            "secret://file/remoteBackends/remote-a/token"
+        );
+        assert!(value["remoteBackends"][1]["token"].is_null());
+        assert_eq!(value["approvalChat"]["channelId"], "C1");
+        assert_eq!(
+            value["pushRelay"]["apiKey"],
+            "secret://file/pushRelay/apiKey"
+        );
+        assert_eq!(value["pushRelay"]["url"], "https://relay.example");
+        assert!(!value.to_string().contains("token-1"));
+        assert!(!value.to_string().contains("relay-key"));
+
+        assert!(!open_settings_secrets(&temp_dir, &mut value));
+        assert_eq!(value["remoteBackendToken"], "token-1");
+        assert_eq!(value["remoteBackends"][0]["token"], "token-a");
+        assert_eq!(value["approvalChat"]["botToken"], "xoxb-1");
+        assert_eq!(value["pushRelay"]["apiKey"], "relay-key");
+        assert!(open_settings_secrets(&temp_dir, &mut value));
+
+        let mut missing = json!({ "remoteBackendToken": "secret://file/gone" });
+        open_settings_secrets(&temp_dir, &mut missing);
+        assert_eq!(missing["remoteBackendToken"], "secret://file/gone");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn settings_writes_keep_unreadable_secret_references() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        secrets::claim_backend(&temp_dir, SecretBackend::File).expect("claim backend");
+        let path = temp_dir.join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{ "remoteBackendToken": "secret://keychain/remoteBackendToken" }"#,
+        )
+        .expect("write settings");
+
+        let mut settings = read_settings(&path).expect("read settings");
+        assert_eq!(
+            settings.remote_backend_token.as_deref(),
+            Some("secret://keychain/remoteBackendToken")
+        );
+        settings.theme = "dark".to_string();
+        write_settings_blocking(&path, &settings).expect("write settings");
+
+        let persisted: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect("read persisted"))
+                .expect("parse persisted");
+        assert_eq!(
+            persisted["remoteBackendToken"],
+            "secret://keychain/remoteBackendToken"
+        );
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -0,0 +1,644 @@
+use std::path::{Path, PathBuf};
+
+use super::*;
+
+const LAUNCHD_LABEL: &str = "com.dimillian.codexmonitor.daemon";
+const SYSTEMD_UNIT: &str = "codex-monitor-daemon.service";
+const WINDOWS_TASK_NAME: &str = "CodexMonitorDaemon";
+/// Holds the token for the installed daemon, next to `settings.json`.
+const TOKEN_FILE: &str = "daemon-autostart.token";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Manager {
+    Launchd,
+    Systemd,
+    ScheduledTask,
+}
+
+impl Manager {
+    fn current() -> Result<Self, String> {
+        if cfg!(target_os = "macos") {
+            Ok(Self::Launchd)
+        } else if cfg!(target_os = "linux") {
+            Ok(Self::Systemd)
+        } else if cfg!(windows) {
+            Ok(Self::ScheduledTask)
+        } else {
+            Err("Daemon autostart is not supported on this platform.".to_string())
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Launchd => "launchd",
+            Self::Systemd => "systemd",
+            Self::ScheduledTask => "scheduledTask",
+        }
+    }
+}
+
+/// What the installed definition runs. The token is in neither the
+/// definition nor the command line: the daemon reads it from `token_file`,
+/// which only the owner can read. `PATH` is the app's, so the daemon finds
+/// `codex` and `git` the same way.
+struct AutostartSpec {
+    daemon_path: String,
+    listen_addr: String,
+    data_dir: String,
+    token: String,
+    token_file: String,
+    path_env: Option<String>,
+    /// `daemon.log`, shared with a daemon the app starts.
+    log_path: String,
+}
+
+impl AutostartSpec {
+    fn args(&self) -> [&str; 7] {
+        [
+            self.daemon_path.as_str(),
+            "--listen",
+            self.listen_addr.as_str(),
+            "--data-dir",
+            self.data_dir.as_str(),
+            "--token-file",
+            self.token_file.as_str(),
+        ]
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A launch agent that starts the daemon at login and again if it exits
+/// with an error. A clean exit, as after `daemon_shutdown`, leaves it
+/// stopped.
+fn launchd_plist(spec: &AutostartSpec) -> String {
+    let arguments: String = spec
+        .args()
+        .iter()
+        .map(|arg| format!("    <string>{}</string>\n", xml_escape(arg)))
+        .collect();
+    let environment = match &spec.path_env {
+        Some(path) => format!(
+            "    <key>PATH</key>\n    <string>{}</string>\n",
+            xml_escape(path)
+        ),
+        None => String::new(),
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{LAUNCHD_LABEL}</string>
+  <key>ProgramArguments</key>
+  <array>
+{arguments}  </array>
+  <key>EnvironmentVariables</key>
+  <dict>
+{environment}  </dict>
+  <key>StandardOutPath</key>
+  <string>{log_path}</string>
+  <key>StandardErrorPath</key>
+  <string>{log_path}</string>
+  <key>RunAtLoad</key>
+  <true/>
+  <key>KeepAlive</key>
+  <dict>
+    <key>SuccessfulExit</key>
+    <false/>
+  </dict>
+</dict>
+</plist>
+"#,
+        log_path = xml_escape(&spec.log_path),
+    )
+}
+
+/// Quotes a value for a systemd unit, where `%` starts a specifier and `$`
+/// a variable.
+fn systemd_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{escaped}\"")
+}
+
+fn systemd_unit(spec: &AutostartSpec) -> String {
+    let exec: Vec<String> = spec.args().iter().map(|arg| systemd_quote(arg)).collect();
+    let environment = match &spec.path_env {
+        Some(path) => format!("Environment={}\n", systemd_quote(&format!("PATH={path}"))),
+        None => String::new(),
+    };
+    format!(
+        "[Unit]\n\
+Description=Codex Monitor daemon\n\
+After=network-online.target\n\
+\n\
+[Service]\n\
+ExecStart={}\n\
+{environment}\
+StandardOutput=append:{log_path}\n\
+StandardError=append:{log_path}\n\
+Restart=on-failure\n\
+RestartSec=5\n\
+\n\
+[Install]\n\
+WantedBy=default.target\n",
+        exec.join(" "),
+        log_path = spec.log_path.replace('%', "%%"),
+    )
+}
+
+/// Quotes an argument the way the Windows C runtime splits command lines.
+fn windows_quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains([' ', '\t', '"']) {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for ch in value.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// A task that starts the daemon when `user_id` logs on and restarts it
+/// after a failure. The daemon is not a Windows service, so the task is
+/// the closest equivalent that does not need an administrator.
+fn windows_task_xml(spec: &AutostartSpec, user_id: &str) -> String {
+    let [daemon_path, rest @ ..] = spec.args();
+    let arguments: Vec<String> = rest.iter().copied().map(windows_quote).collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Description>Codex Monitor daemon</Description>
+  </RegistrationInfo>
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>
+      <UserId>{user}</UserId>
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <UserId>{user}</UserId>
+      <LogonType>InteractiveToken</LogonType>
+      <RunLevel>LeastPrivilege</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <RestartOnFailure>
+      <Interval>PT1M</Interval>
+      <Count>999</Count>
+    </RestartOnFailure>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>{command}</Command>
+      <Arguments>{arguments}</Arguments>
+    </Exec>
+  </Actions>
+</Task>
+"#,
+        user = xml_escape(user_id),
+        command = xml_escape(daemon_path),
+        arguments = xml_escape(&arguments.join(" ")),
+    )
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    crate::codex::home::resolve_home_dir()
+        .ok_or_else(|| "Unable to resolve home directory".to_string())
+}
+
+fn definition_path(manager: Manager) -> Result<Option<PathBuf>, String> {
+    Ok(match manager {
+        Manager::Launchd => Some(
+            home_dir()?
+                .join("Library/LaunchAgents")
+                .join(format!("{LAUNCHD_LABEL}.plist")),
+        ),
+        Manager::Systemd => {
+            let config_dir = match std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+            {
+                Some(dir) => dir,
+                None => home_dir()?.join(".config"),
+            };
+            Some(config_dir.join("systemd/user").join(SYSTEMD_UNIT))
+        }
+        Manager::ScheduledTask => None,
+    })
+}
+
+#[cfg(unix)]
+fn launchd_domain() -> String {
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+#[cfg(not(unix))]
+fn launchd_domain() -> String {
+    String::new()
+}
+
+fn windows_user_id() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    match std::env::var("USERDOMAIN") {
+        Ok(domain) if !domain.is_empty() => format!("{domain}\\{user}"),
+        _ => user,
+    }
+}
+
+/// Writes a file that holds the token so only the owner can read it. The
+/// file is created with that mode and renamed into place, so the token is
+/// never readable by others, even briefly.
+fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options
+        .open(&tmp)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, contents))
+        .and_then(|()| std::fs::rename(&tmp, path));
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {err}", path.display()));
+    }
+    Ok(())
+}
+
+async fn run_command(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = tokio_command(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|err| format!("Failed to run {program}: {err}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let detail = trim_to_non_empty(std::str::from_utf8(&output.stderr).ok())
+        .or_else(|| trim_to_non_empty(std::str::from_utf8(&output.stdout).ok()))
+        .unwrap_or_else(|| format!("exit status {}", output.status));
+    Err(format!(
+        "{program} {} failed: {}",
+        args.join(" "),
+        truncate_preview(&detail, 400)
+    ))
+}
+
+fn data_dir(state: &AppState) -> Result<PathBuf, String> {
+    state
+        .settings_path
+        .parent()
+        .map(|path| path.to_path_buf())
+        .ok_or_else(|| "Unable to resolve app data directory".to_string())
+}
+
+/// The installed definition starts its own daemon; a copy the app spawned
+/// would hold the port.
+async fn stop_app_managed_daemon(state: &AppState) {
+    let mut runtime = state.tcp_daemon.lock().await;
+    if let Some(mut child) = runtime.child.take() {
+        kill_child_process_tree(&mut child).await;
+        let _ = child.wait().await;
+        runtime.status.state = TcpDaemonState::Stopped;
+        runtime.status.pid = None;
+        runtime.status.started_at_ms = None;
+    }
+}
+
+async fn autostart_status(manager: Manager) -> Result<DaemonAutostartStatus, String> {
+    let (installed, definition) = match definition_path(manager)? {
+        Some(path) => (path.is_file(), path.to_string_lossy().to_string()),
+        None => (
+            run_command("schtasks", &["/Query", "/TN", WINDOWS_TASK_NAME])
+                .await
+                .is_ok(),
+            WINDOWS_TASK_NAME.to_string(),
+        ),
+    };
+    let message = match (installed, manager) {
+        (false, _) => "The daemon does not start at login.".to_string(),
+        (true, Manager::Launchd) => "The daemon starts at login through launchd.".to_string(),
+        (true, Manager::Systemd) => "The daemon starts with a systemd user unit. Run `loginctl enable-linger` to keep it running while you are logged out.".to_string(),
+        (true, Manager::ScheduledTask) => {
+            "The daemon starts at logon through a scheduled task.".to_string()
+        }
+    };
+    Ok(DaemonAutostartStatus {
+        installed,
+        manager: manager.name().to_string(),
+        definition,
+        message,
+    })
+}
+
+pub(super) async fn daemon_autostart_status() -> Result<DaemonAutostartStatus, String> {
+    autostart_status(Manager::current()?).await
+}
+
+pub(super) async fn daemon_install_autostart(
+    state: State<'_, AppState>,
+) -> Result<DaemonAutostartStatus, String> {
+    let settings = state.app_settings.lock().await.clone();
+    install(&state, &settings).await
+}
+
+/// Rewrites an installed definition when the token or listen address in
+/// settings changed, and restarts the daemon so it takes the new values.
+pub(super) async fn sync_autostart_settings(
+    state: &AppState,
+    previous: &crate::types::AppSettings,
+    updated: &crate::types::AppSettings,
+) -> Result<(), String> {
+    if previous.remote_backend_token == updated.remote_backend_token
+        && configured_daemon_listen_addr(previous) == configured_daemon_listen_addr(updated)
+    {
+        return Ok(());
+    }
+    if !autostart_status(Manager::current()?).await?.installed {
+        return Ok(());
+    }
+    install(state, updated).await.map(|_| ())
+}
+
+async fn install(
+    state: &AppState,
+    settings: &crate::types::AppSettings,
+) -> Result<DaemonAutostartStatus, String> {
+    let manager = Manager::current()?;
+    let token = settings
+        .remote_backend_token
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| {
+            "Set a Remote backend token before installing daemon autostart.".to_string()
+        })?;
+    let data_dir = data_dir(state)?;
+    // The service manager opens the log itself and does not create its
+    // directory.
+    let log_path = daemon_log::daemon_log_path(&data_dir);
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let spec = AutostartSpec {
+        daemon_path: resolve_daemon_binary_path()?.to_string_lossy().to_string(),
+        listen_addr: configured_daemon_listen_addr(settings),
+        data_dir: data_dir.to_string_lossy().to_string(),
+        token: token.to_string(),
+        token_file: data_dir.join(TOKEN_FILE).to_string_lossy().to_string(),
+        path_env: std::env::var("PATH").ok().filter(|path| !path.is_empty()),
+        log_path: log_path.to_string_lossy().to_string(),
+    };
+
+    stop_app_managed_daemon(state).await;
+    write_private_file(Path::new(&spec.token_file), spec.token.as_bytes())?;
+    match manager {
+        Manager::Launchd => {
+            let path = definition_path(manager)?.unwrap_or_default();
+            write_private_file(&path, launchd_plist(&spec).as_bytes())?;
+            let domain = launchd_domain();
+            let service = format!("{domain}/{LAUNCHD_LABEL}");
+            // Reinstalling replaces a loaded agent.
+            let _ = run_command("launchctl", &["bootout", &service]).await;
+            run_command(
+                "launchctl",
+                &["bootstrap", &domain, &path.to_string_lossy()],
+            )
+            .await?;
+        }
+        Manager::Systemd => {
+            let path = definition_path(manager)?.unwrap_or_default();
+            write_private_file(&path, systemd_unit(&spec).as_bytes())?;
+            run_command("systemctl", &["--user", "daemon-reload"]).await?;
+            run_command("systemctl", &["--user", "enable", SYSTEMD_UNIT]).await?;
+            run_command("systemctl", &["--user", "restart", SYSTEMD_UNIT]).await?;
+        }
+        Manager::ScheduledTask => {
+            // schtasks reads task XML as UTF-16.
+            let xml = windows_task_xml(&spec, &windows_user_id());
+            let bytes: Vec<u8> = std::iter::once(0xfeff)
+                .chain(xml.encode_utf16())
+                .flat_map(u16::to_le_bytes)
+                .collect();
+            let xml_path = data_dir.join("daemon-autostart-task.xml");
+            write_private_file(&xml_path, &bytes)?;
+            let created = run_command(
+                "schtasks",
+                &[
+                    "/Create",
+                    "/TN",
+                    WINDOWS_TASK_NAME,
+                    "/XML",
+                    &xml_path.to_string_lossy(),
+                    "/F",
+                ],
+            )
+            .await;
+            let _ = std::fs::remove_file(&xml_path);
+            created?;
+            let _ = run_command("schtasks", &["/End", "/TN", WINDOWS_TASK_NAME]).await;
+            run_command("schtasks", &["/Run", "/TN", WINDOWS_TASK_NAME]).await?;
+        }
+    }
+    autostart_status(manager).await
+}
+
+/// Restarts the daemon through the installed definition, so it keeps the
+/// definition's data directory and token. Returns `false` when autostart is
+/// not installed.
+pub(super) async fn restart_autostart_daemon() -> Result<bool, String> {
+    let manager = Manager::current()?;
+    if !autostart_status(manager).await?.installed {
+        return Ok(false);
+    }
+    match manager {
+        Manager::Launchd => {
+            let service = format!("{}/{LAUNCHD_LABEL}", launchd_domain());
+            run_command("launchctl", &["kickstart", "-k", &service]).await?;
+        }
+        Manager::Systemd => {
+            run_command("systemctl", &["--user", "restart", SYSTEMD_UNIT]).await?;
+        }
+        Manager::ScheduledTask => {
+            let _ = run_command("schtasks", &["/End", "/TN", WINDOWS_TASK_NAME]).await;
+            run_command("schtasks", &["/Run", "/TN", WINDOWS_TASK_NAME]).await?;
+        }
+    }
+    Ok(true)
+}
+
+/// Removes the definition and stops the daemon it started.
+pub(super) async fn daemon_uninstall_autostart(
+    state: State<'_, AppState>,
+) -> Result<DaemonAutostartStatus, String> {
+    let manager = Manager::current()?;
+    match manager {
+        Manager::Launchd => {
+            let service = format!("{}/{LAUNCHD_LABEL}", launchd_domain());
+            let _ = run_command("launchctl", &["bootout", &service]).await;
+        }
+        Manager::Systemd => {
+            let _ = run_command("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT]).await;
+        }
+        Manager::ScheduledTask => {
+            let _ = run_command("schtasks", &["/End", "/TN", WINDOWS_TASK_NAME]).await;
+            let _ = run_command("schtasks", &["/Delete", "/TN", WINDOWS_TASK_NAME, "/F"]).await;
+        }
+    }
+    let _ = std::fs::remove_file(data_dir(&state)?.join(TOKEN_FILE));
+    if let Some(path) = definition_path(manager)? {
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed to remove {}: {err}", path.display())),
+        }
+        if manager == Manager::Systemd {
+            let _ = run_command("systemctl", &["--user", "daemon-reload"]).await;
+        }
+    }
+    autostart_status(manager).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> AutostartSpec {
+        AutostartSpec {
+            daemon_path: "/Applications/Codex Monitor.app/Contents/MacOS/codex-monitor-daemon"
+                .to_string(),
+            listen_addr: "0.0.0.0:4732".to_string(),
+            data_dir: "/Users/me/Library/Application Support/com.dimillian.codexmonitor"
+                .to_string(),
+            token: "s3cr%t<&>".to_string(),
+            token_file:
+                "/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon-autostart.token"
+                    .to_string(),
+            path_env: Some("/opt/homebrew/bin:/usr/bin".to_string()),
+            log_path: "/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon.log"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn launchd_plist_points_the_daemon_at_the_token_file() {
+        let plist = launchd_plist(&spec());
+        assert!(plist.contains("<string>com.dimillian.codexmonitor.daemon</string>"));
+        assert!(plist.contains(
+            "<string>/Applications/Codex Monitor.app/Contents/MacOS/codex-monitor-daemon</string>"
+        ));
+        assert!(plist.contains("<string>0.0.0.0:4732</string>"));
+        assert!(plist.contains("<string>--token-file</string>\n    <string>/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon-autostart.token</string>"));
+        assert!(!plist.contains("s3cr"));
+        assert!(!plist.contains("CODEX_MONITOR_DAEMON_TOKEN"));
+        assert!(plist.contains("<string>/opt/homebrew/bin:/usr/bin</string>"));
+        assert!(plist.contains(
+            "<key>StandardErrorPath</key>\n  <string>/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon.log</string>"
+        ));
+    }
+
+    #[test]
+    fn systemd_unit_escapes_specifiers_and_quotes() {
+        let unit = systemd_unit(&spec());
+        assert!(unit.contains(
+            "ExecStart=\"/Applications/Codex Monitor.app/Contents/MacOS/codex-monitor-daemon\" \"--listen\" \"0.0.0.0:4732\""
+        ));
+        assert!(unit.contains(
+            "\"--token-file\" \"/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon-autostart.token\"\n"
+        ));
+        assert!(!unit.contains("s3cr"));
+        assert!(unit.contains("Environment=\"PATH=/opt/homebrew/bin:/usr/bin\"\n"));
+        assert!(unit.contains(
+            "StandardError=append:/Users/me/Library/Application Support/com.dimillian.codexmonitor/daemon.log\n"
+        ));
+        assert!(unit.contains("WantedBy=default.target"));
+        assert_eq!(systemd_quote("a$b\"c"), "\"a$$b\\\"c\"");
+    }
+
+    #[test]
+    fn windows_task_reads_the_token_from_a_file() {
+        let mut spec = spec();
+        spec.daemon_path = r"C:\Program Files\Codex Monitor\codex-monitor-daemon.exe".to_string();
+        spec.data_dir = r"C:\Users\me\AppData\Roaming\com.dimillian.codexmonitor".to_string();
+        spec.token_file =
+            r"C:\Users\me\AppData\Roaming\com.dimillian.codexmonitor\daemon-autostart.token"
+                .to_string();
+        spec.token = "tok en".to_string();
+        let xml = windows_task_xml(&spec, r"DESKTOP\me");
+        assert!(xml.contains(
+            r"<Command>C:\Program Files\Codex Monitor\codex-monitor-daemon.exe</Command>"
+        ));
+        assert!(xml.contains(
+            r"<Arguments>--listen 0.0.0.0:4732 --data-dir C:\Users\me\AppData\Roaming\com.dimillian.codexmonitor --token-file C:\Users\me\AppData\Roaming\com.dimillian.codexmonitor\daemon-autostart.token</Arguments>"
+        ));
+        assert!(!xml.contains("tok en"));
+        assert!(xml.contains(r"<UserId>DESKTOP\me</UserId>"));
+        assert_eq!(windows_quote(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(
+            windows_quote(r"C:\dir with space\"),
+            r#""C:\dir with space\\""#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_files_are_created_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir =
+            std::env::temp_dir().join(format!("codex-monitor-autostart-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("daemon.plist");
+        write_private_file(&path, b"token-1").expect("write");
+        write_private_file(&path, b"token-2").expect("rewrite");
+        let mode = std::fs::metadata(&path)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read(&path).expect("read"), b"token-2");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,11 +1,19 @@
+use std::net::IpAddr;
 use std::path::Path;
 
 use serde::Deserialize;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::types::{TailscaleDaemonCommandPreview, TailscaleStatus};
+use crate::types::{
+    TailscaleDaemonCommandPreview, TailscalePeer, TailscaleServeStatus, TailscaleStatus,
+};
 
 const DEFAULT_DAEMON_LISTEN_ADDR: &str = "0.0.0.0:4732";
+const DEFAULT_DAEMON_PORT: u16 = 4732;
+/// Tailscale serves TLS on this port of the machine's tailnet name.
+pub(crate) const DEFAULT_SERVE_PORT: u16 = 443;
+/// The only ports Tailscale Funnel accepts.
+const FUNNEL_PORTS: [u16; 3] = [443, 8443, 10000];
 const REMOTE_TOKEN_PLACEHOLDER: &str = "<remote-backend-token>";
 
 pub(crate) fn unavailable_status(version: Option<String>, message: String) -> TailscaleStatus {
@@ -178,28 +186,7 @@ pub(crate) fn status_from_json(
         .filter(|value| !value.is_empty())
         .map(str::to_string);
 
-    let ip_values = self_node
-        .and_then(|node| node.get("TailscaleIPs"))
-        .and_then(Value::as_array)
-        .map(|items| {
-            items
-                .iter()
-                .filter_map(Value::as_str)
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .map(str::to_string)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-    let mut ipv4 = Vec::new();
-    let mut ipv6 = Vec::new();
-    for ip in ip_values {
-        if ip.contains(':') {
-            ipv6.push(ip);
-        } else {
-            ipv4.push(ip);
-        }
-    }
+    let (ipv4, ipv6) = self_node.map(node_ips).unwrap_or_default();
 
     let suggested_remote_host = suggested_remote_host(dns_name.as_deref(), &ipv4, &ipv6);
     let message = if running {
@@ -228,6 +215,77 @@ pub(crate) fn status_from_json(
     })
 }
 
+/// Splits a node's `TailscaleIPs` into IPv4 and IPv6 addresses.
+fn node_ips(node: &Map<String, Value>) -> (Vec<String>, Vec<String>) {
+    let mut ipv4 = Vec::new();
+    let mut ipv6 = Vec::new();
+    let ips = node
+        .get("TailscaleIPs")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    for ip in ips {
+        if ip.contains(':') {
+            ipv6.push(ip.to_string());
+        } else {
+            ipv4.push(ip.to_string());
+        }
+    }
+    (ipv4, ipv6)
+}
+
+fn node_string(node: &Map<String, Value>, key: &str) -> Option<String> {
+    node.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// The other machines on the tailnet from `tailscale status --json`, online
+/// ones first, then by name.
+pub(crate) fn peers_from_json(payload: &str) -> Result<Vec<TailscalePeer>, String> {
+    let json = parse_status_json(payload)?;
+    let mut peers: Vec<TailscalePeer> = json
+        .get("Peer")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|peers| peers.iter())
+        .filter_map(|(key, node)| {
+            let node = node.as_object()?;
+            let dns_name = node_string(node, "DNSName")
+                .map(|name| trim_dns_name(&name).to_string())
+                .filter(|name| !name.is_empty());
+            let host_name = node_string(node, "HostName").or_else(|| {
+                dns_name
+                    .as_deref()
+                    .map(|name| name.split('.').next().unwrap_or(name).to_string())
+            })?;
+            let (ipv4, ipv6) = node_ips(node);
+            let suggested_remote_host = suggested_remote_host(dns_name.as_deref(), &ipv4, &ipv6);
+            Some(TailscalePeer {
+                id: node_string(node, "ID").unwrap_or_else(|| key.clone()),
+                host_name,
+                dns_name,
+                os: node_string(node, "OS"),
+                online: node.get("Online").and_then(Value::as_bool).unwrap_or(false),
+                ipv4,
+                ipv6,
+                suggested_remote_host,
+            })
+        })
+        .collect();
+    peers.sort_by(|a, b| {
+        b.online
+            .cmp(&a.online)
+            .then_with(|| a.host_name.to_lowercase().cmp(&b.host_name.to_lowercase()))
+    });
+    Ok(peers)
+}
+
 pub(crate) fn suggested_remote_host(
     dns_name: Option<&str>,
     ipv4: &[String],
@@ -272,6 +330,141 @@ pub(crate) fn daemon_command_preview(
         daemon_path: daemon_path_str,
         args,
         token_configured,
+        serve_command: Some(format!(
+            "tailscale {}",
+            serve_args(DEFAULT_SERVE_PORT, DEFAULT_DAEMON_PORT, false)
+                .expect("default serve port")
+                .join(" ")
+        )),
+    }
+}
+
+/// Arguments for `tailscale` that terminate TLS on `serve_port` and forward
+/// the raw connection to the daemon, which speaks line-delimited JSON rather
+/// than HTTP. With `funnel` the port is also opened to the internet.
+pub(crate) fn serve_args(
+    serve_port: u16,
+    daemon_port: u16,
+    funnel: bool,
+) -> Result<Vec<String>, String> {
+    if funnel && !FUNNEL_PORTS.contains(&serve_port) {
+        return Err(format!(
+            "Tailscale Funnel only serves ports 443, 8443 and 10000, not {serve_port}."
+        ));
+    }
+    Ok(vec![
+        if funnel { "funnel" } else { "serve" }.to_string(),
+        "--bg".to_string(),
+        format!("--tls-terminated-tcp={serve_port}"),
+        format!("tcp://127.0.0.1:{daemon_port}"),
+    ])
+}
+
+pub(crate) fn serve_off_args(serve_port: u16, funnel: bool) -> Vec<String> {
+    vec![
+        if funnel { "funnel" } else { "serve" }.to_string(),
+        format!("--tls-terminated-tcp={serve_port}"),
+        "off".to_string(),
+    ]
+}
+
+/// Reads `tailscale serve status --json` and reports the TCP handler that
+/// forwards to `daemon_port`, if any.
+pub(crate) fn serve_status_from_json(
+    payload: &str,
+    daemon_port: u16,
+    dns_name: Option<&str>,
+) -> Result<TailscaleServeStatus, String> {
+    let trimmed = payload.trim();
+    let json: Value = if trimmed.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(trimmed)
+            .map_err(|err| format!("Invalid tailscale serve status JSON: {err}"))?
+    };
+    let target_suffix = format!(":{daemon_port}");
+    let handler = json
+        .get("TCP")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(port, handler)| {
+            let target = handler.get("TCPForward").and_then(Value::as_str)?;
+            if !target.ends_with(&target_suffix) {
+                return None;
+            }
+            let port = port.parse::<u16>().ok()?;
+            let host = handler
+                .get("TerminateTLS")
+                .and_then(Value::as_str)
+                .map(trim_dns_name)
+                .filter(|value| !value.is_empty())
+                .or(dns_name.map(trim_dns_name));
+            Some((port, target.to_string(), host.map(str::to_string)))
+        })
+        .min_by_key(|(port, _, _)| *port);
+    let Some((serve_port, target, host)) = handler else {
+        return Ok(TailscaleServeStatus {
+            enabled: false,
+            funnel: false,
+            serve_port: None,
+            target: None,
+            remote_host: None,
+            url: None,
+            message: format!("Tailscale is not serving the daemon on port {daemon_port}."),
+        });
+    };
+    let funnel = host.as_deref().is_some_and(|host| {
+        json.get("AllowFunnel")
+            .and_then(|allow| allow.get(format!("{host}:{serve_port}")))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    });
+    let remote_host = host.as_deref().map(|host| format!("{host}:{serve_port}"));
+    let url = remote_host.as_deref().map(|host| format!("tls://{host}"));
+    let reach = if funnel {
+        "the internet"
+    } else {
+        "the tailnet"
+    };
+    let message = match remote_host.as_deref() {
+        Some(remote_host) => {
+            format!("The daemon is reachable from {reach} at {remote_host} over TLS.")
+        }
+        None => format!("Tailscale serves the daemon to {reach} on port {serve_port}."),
+    };
+    Ok(TailscaleServeStatus {
+        enabled: true,
+        funnel,
+        serve_port: Some(serve_port),
+        target: Some(target),
+        remote_host,
+        url,
+        message,
+    })
+}
+
+/// Whether a remote backend host is reached through Tailscale: a MagicDNS
+/// name or an address in the tailnet ranges (100.64.0.0/10,
+/// fd7a:115c:a1e0::/48). The port, if any, is ignored.
+pub(crate) fn is_tailnet_host(remote_host: &str) -> bool {
+    let host = remote_host.trim();
+    let host = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => match host.rsplit_once(':') {
+            Some((name, _)) if !name.contains(':') => name,
+            _ => host,
+        },
+    };
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            let [first, second, ..] = ip.octets();
+            first == 100 && (second & 0xc0) == 64
+        }
+        Ok(IpAddr::V6(ip)) => ip.segments()[..3] == [0xfd7a, 0x115c, 0xa1e0],
+        Err(_) => trim_dns_name(host)
+            .to_ascii_lowercase()
+            .ends_with(".ts.net"),
     }
 }
 
@@ -294,7 +487,10 @@ fn shell_quote(value: &str) -> String {
 mod tests {
     use std::path::Path;
 
-    use super::{daemon_command_preview, status_from_json, suggested_remote_host};
+    use super::{
+        daemon_command_preview, is_tailnet_host, peers_from_json, serve_args, serve_off_args,
+        serve_status_from_json, status_from_json, suggested_remote_host,
+    };
 
     #[test]
     fn status_from_json_extracts_running_fields() {
@@ -435,5 +631,113 @@ extra diagnostics line"#;
         assert!(preview.command.contains("0.0.0.0:4732"));
         assert!(preview.command.contains("<remote-backend-token>"));
         assert!(preview.token_configured);
+        assert_eq!(
+            preview.serve_command.as_deref(),
+            Some("tailscale serve --bg --tls-terminated-tcp=443 tcp://127.0.0.1:4732")
+        );
+    }
+
+    #[test]
+    fn serve_args_forward_tls_to_the_daemon() {
+        assert_eq!(
+            serve_args(8443, 4732, true).expect("funnel args"),
+            vec![
+                "funnel",
+                "--bg",
+                "--tls-terminated-tcp=8443",
+                "tcp://127.0.0.1:4732"
+            ]
+        );
+        assert!(serve_args(9000, 4732, true).is_err());
+        assert!(serve_args(9000, 4732, false).is_ok());
+        assert_eq!(
+            serve_off_args(443, false),
+            vec!["serve", "--tls-terminated-tcp=443", "off"]
+        );
+    }
+
+    #[test]
+    fn serve_status_reports_the_handler_for_the_daemon_port() {
+        let payload = r#"{
+          "TCP": {
+            "443": { "TCPForward": "127.0.0.1:4732", "TerminateTLS": "mac.example.ts.net" },
+            "8080": { "TCPForward": "127.0.0.1:3000" }
+          },
+          "AllowFunnel": { "mac.example.ts.net:443": true }
+        }"#;
+        let status = serve_status_from_json(payload, 4732, None).expect("serve status");
+        assert!(status.enabled);
+        assert!(status.funnel);
+        assert_eq!(status.serve_port, Some(443));
+        assert_eq!(
+            status.remote_host.as_deref(),
+            Some("mac.example.ts.net:443")
+        );
+        assert_eq!(status.url.as_deref(), Some("tls://mac.example.ts.net:443"));
+
+        let other = serve_status_from_json(payload, 5000, None).expect("serve status");
+        assert!(!other.enabled);
+        let empty = serve_status_from_json("", 4732, Some("mac.example.ts.net.")).expect("empty");
+        assert!(!empty.enabled);
+    }
+
+    #[test]
+    fn peers_from_json_lists_other_machines() {
+        let payload = r#"{
+          "BackendState": "Running",
+          "Self": { "DNSName": "macbook.example.ts.net.", "HostName": "macbook" },
+          "Peer": {
+            "nodekey:a": {
+              "ID": "n1",
+              "HostName": "workstation",
+              "DNSName": "workstation.example.ts.net.",
+              "OS": "linux",
+              "Online": false,
+              "TailscaleIPs": ["100.64.0.2", "fd7a:115c:a1e0::2"]
+            },
+            "nodekey:b": {
+              "ID": "n2",
+              "HostName": "Build-Box",
+              "DNSName": "build-box.example.ts.net.",
+              "OS": "windows",
+              "Online": true,
+              "TailscaleIPs": ["100.64.0.3"]
+            },
+            "nodekey:c": { "DNSName": "phone.example.ts.net.", "Online": true }
+          }
+        }"#;
+
+        let peers = peers_from_json(payload).expect("peers");
+        let names: Vec<_> = peers.iter().map(|peer| peer.host_name.as_str()).collect();
+        assert_eq!(names, vec!["Build-Box", "phone", "workstation"]);
+        assert_eq!(peers[0].id, "n2");
+        assert_eq!(peers[0].os.as_deref(), Some("windows"));
+        assert_eq!(peers[1].id, "nodekey:c");
+        let workstation = &peers[2];
+        assert!(!workstation.online);
+        assert_eq!(
+            workstation.dns_name.as_deref(),
+            Some("workstation.example.ts.net")
+        );
+        assert_eq!(workstation.ipv6, vec!["fd7a:115c:a1e0::2".to_string()]);
+        assert_eq!(
+            workstation.suggested_remote_host.as_deref(),
+            Some("workstation.example.ts.net:4732")
+        );
+
+        let alone = peers_from_json(r#"{"BackendState":"Running"}"#).expect("no peers");
+        assert!(alone.is_empty());
+    }
+
+    #[test]
+    fn tailnet_hosts_are_recognized_by_name_or_range() {
+        assert!(is_tailnet_host("mac.example.ts.net:4732"));
+        assert!(is_tailnet_host("Mac.Example.TS.NET."));
+        assert!(is_tailnet_host("100.101.2.3:4732"));
+        assert!(is_tailnet_host("[fd7a:115c:a1e0::1]:4732"));
+        assert!(!is_tailnet_host("100.128.0.1:4732"));
+        assert!(!is_tailnet_host("192.168.1.20:4732"));
+        assert!(!is_tailnet_host("studio.local:4732"));
+        assert!(!is_tailnet_host("[::1]:4732"));
     }
 }
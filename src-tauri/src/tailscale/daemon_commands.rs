@@ -2,10 +2,13 @@ use super::rpc_client::{
     probe_daemon, request_daemon_shutdown, wait_for_daemon_shutdown, DaemonInfo, DaemonProbe,
 };
 use super::*;
+use std::path::{Path, PathBuf};
 
 const EXPECTED_DAEMON_NAME: &str = "codex-monitor-daemon";
 const EXPECTED_DAEMON_MODE: &str = "tcp";
 const CURRENT_APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// How long a restarted daemon gets to answer after an upgrade.
+const UPGRADE_RESTART_TIMEOUT: Duration = Duration::from_secs(5);
 
 fn is_managed_daemon(info: &DaemonInfo) -> bool {
     info.name == EXPECTED_DAEMON_NAME
@@ -46,6 +49,140 @@ fn daemon_restart_reason(info: Option<&DaemonInfo>) -> String {
     "Daemon restart required".to_string()
 }
 
+/// Compares a running daemon with the bundled one. `fallback_message`
+/// explains the result when no managed daemon reported its identity.
+fn daemon_version_report(
+    running: bool,
+    info: Option<&DaemonInfo>,
+    fallback_message: String,
+) -> TcpDaemonVersion {
+    let ours = daemon_version_core::daemon_version_core();
+    let mut report = TcpDaemonVersion {
+        running,
+        version: info.map(|info| info.version.clone()),
+        protocol_version: info.and_then(|info| info.protocol_version),
+        binary_path: info.and_then(|info| info.binary_path.clone()),
+        bundled_version: ours.version.clone(),
+        bundled_protocol_version: ours.protocol_version,
+        compatible: false,
+        upgrade_available: false,
+        message: fallback_message,
+    };
+    let Some(info) = info.filter(|info| is_managed_daemon(info)) else {
+        return report;
+    };
+    let protocol = daemon_version_core::check_peer_protocol(
+        "app",
+        "daemon",
+        info.protocol_version.map(u64::from),
+        info.min_protocol_version.map(u64::from),
+    );
+    report.compatible = protocol.is_ok();
+    report.upgrade_available =
+        info.version != ours.version || info.protocol_version != Some(ours.protocol_version);
+    report.message = match protocol {
+        Err(err) => err,
+        Ok(_) if report.upgrade_available => format!(
+            "Daemon {} is running, but the app bundles {}.",
+            info.version, ours.version
+        ),
+        Ok(_) => format!("Daemon {} matches the app.", info.version),
+    };
+    report
+}
+
+/// Only files named like the daemon are overwritten on upgrade, whatever
+/// path the running daemon reports.
+fn is_daemon_binary_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| daemon_binary_candidates().contains(&name))
+}
+
+/// Where a daemon binary may live: the directories searched for the bundled
+/// daemon, plus the one `CODEX_MONITOR_DAEMON_PATH` points at.
+fn daemon_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(daemon_search_dirs))
+        .unwrap_or_default();
+    if let Ok(explicit) = std::env::var("CODEX_MONITOR_DAEMON_PATH") {
+        let explicit = PathBuf::from(explicit.trim());
+        if explicit.is_dir() {
+            dirs.push(explicit);
+        } else if let Some(parent) = explicit.parent() {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// The binary to overwrite with the bundled daemon, when the running daemon
+/// was started from another copy. The reported path is only trusted once it
+/// resolves to a daemon binary inside one of `install_dirs`.
+fn binary_to_replace(
+    reported: Option<&str>,
+    bundled: &Path,
+    install_dirs: &[PathBuf],
+) -> Result<Option<PathBuf>, String> {
+    let Some(reported) = reported else {
+        return Ok(None);
+    };
+    let untrusted = || {
+        format!("Daemon runs from {reported}, which is not an installed daemon binary; refusing to overwrite it.")
+    };
+    let target = PathBuf::from(reported)
+        .canonicalize()
+        .map_err(|_| untrusted())?;
+    if bundled
+        .canonicalize()
+        .is_ok_and(|bundled| bundled == target)
+    {
+        return Ok(None);
+    }
+    let installed = target.parent().is_some_and(|parent| {
+        install_dirs
+            .iter()
+            .filter_map(|dir| dir.canonicalize().ok())
+            .any(|dir| dir == parent)
+    });
+    if installed && target.is_file() && is_daemon_binary_name(&target) {
+        Ok(Some(target))
+    } else {
+        Err(untrusted())
+    }
+}
+
+/// Copies the bundled daemon next to `target` and renames it over, so a
+/// failed copy leaves the old binary in place.
+fn replace_daemon_binary(bundled: &Path, target: &Path) -> Result<(), String> {
+    let mut staged_name = target.file_name().unwrap_or_default().to_os_string();
+    staged_name.push(".upgrade");
+    let staged = target.with_file_name(staged_name);
+    std::fs::copy(bundled, &staged)
+        .map_err(|err| format!("Failed to copy {}: {err}", bundled.display()))?;
+    std::fs::rename(&staged, target).map_err(|err| {
+        let _ = std::fs::remove_file(&staged);
+        format!("Failed to replace {}: {err}", target.display())
+    })
+}
+
+async fn wait_for_daemon_version(listen_addr: &str, token: Option<&str>) -> Option<String> {
+    let deadline = Instant::now() + UPGRADE_RESTART_TIMEOUT;
+    loop {
+        if let DaemonProbe::Running {
+            info: Some(info), ..
+        } = probe_daemon(listen_addr, token).await
+        {
+            return Some(info.version);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+}
+
 async fn resolve_daemon_pid(listen_port: u16, info: Option<&DaemonInfo>) -> Option<u32> {
     match info.and_then(|entry| entry.pid) {
         Some(pid) => Some(pid),
@@ -212,6 +349,8 @@ pub(super) async fn tailscale_daemon_start(
 
     ensure_listen_addr_available(&listen_addr).await?;
 
+    let log_path = daemon_log::daemon_log_path(&data_dir);
+    let (stdout, stderr) = daemon_log::open_daemon_log(&log_path)?;
     let child = tokio_command(&daemon_binary)
         .arg("--listen")
         .arg(&listen_addr)
@@ -220,8 +359,8 @@ pub(super) async fn tailscale_daemon_start(
         .arg("--token")
         .arg(token)
         .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
         .spawn()
         .map_err(|err| format!("Failed to start mobile access daemon: {err}"))?;
 
@@ -233,6 +372,7 @@ pub(super) async fn tailscale_daemon_start(
         listen_addr: Some(listen_addr),
     };
     runtime.child = Some(child);
+    runtime.log_path = Some(log_path);
 
     Ok(runtime.status.clone())
 }
@@ -418,12 +558,132 @@ pub(super) async fn tailscale_daemon_status(
     Ok(runtime.status.clone())
 }
 
+pub(super) async fn tailscale_daemon_version(
+    state: State<'_, AppState>,
+) -> Result<TcpDaemonVersion, String> {
+    let settings = state.app_settings.lock().await.clone();
+    let listen_addr = configured_daemon_listen_addr(&settings);
+    let report = match probe_daemon(&listen_addr, settings.remote_backend_token.as_deref()).await {
+        DaemonProbe::Running {
+            auth_error, info, ..
+        } => daemon_version_report(
+            true,
+            info.as_ref(),
+            auth_error.unwrap_or_else(|| {
+                "Daemon is running but did not report identity/version metadata.".to_string()
+            }),
+        ),
+        DaemonProbe::NotDaemon => daemon_version_report(
+            false,
+            None,
+            format!("{listen_addr} is in use by a non-daemon process."),
+        ),
+        DaemonProbe::NotReachable => {
+            daemon_version_report(false, None, "Daemon is not running.".to_string())
+        }
+    };
+    Ok(report)
+}
+
+/// An installed autostart definition restarts the daemon with its own data
+/// directory and token; otherwise the app starts it as usual.
+async fn restart_daemon(
+    state: State<'_, AppState>,
+    listen_addr: &str,
+    token: Option<&str>,
+) -> Result<TcpDaemonStatus, String> {
+    if autostart::restart_autostart_daemon().await? {
+        wait_for_daemon_version(listen_addr, token).await;
+        tailscale_daemon_status(state).await
+    } else {
+        tailscale_daemon_start(state).await
+    }
+}
+
+pub(super) async fn tailscale_daemon_upgrade(
+    state: State<'_, AppState>,
+) -> Result<TcpDaemonUpgradeResult, String> {
+    if cfg!(any(target_os = "android", target_os = "ios")) {
+        return Err("Daemon upgrade is only supported on desktop.".to_string());
+    }
+
+    let settings = state.app_settings.lock().await.clone();
+    let listen_addr = configured_daemon_listen_addr(&settings);
+    let token = settings.remote_backend_token.as_deref();
+    let info = match probe_daemon(&listen_addr, token).await {
+        DaemonProbe::Running {
+            auth_ok: true,
+            info: Some(info),
+            ..
+        } if is_managed_daemon(&info) => info,
+        DaemonProbe::Running { auth_error, .. } => {
+            return Err(auth_error.unwrap_or_else(|| {
+                "Daemon is running but its identity could not be verified; refusing to upgrade it."
+                    .to_string()
+            }));
+        }
+        DaemonProbe::NotDaemon => {
+            return Err(format!(
+                "{listen_addr} is in use by a non-daemon process; refusing to upgrade it."
+            ));
+        }
+        DaemonProbe::NotReachable => {
+            return Err("Daemon is not running; start it instead.".to_string());
+        }
+    };
+    let bundled = resolve_daemon_binary_path()?;
+    let replace_target = binary_to_replace(
+        info.binary_path.as_deref(),
+        &bundled,
+        &daemon_install_dirs(),
+    )?;
+
+    let stopped = tailscale_daemon_stop(state.clone()).await?;
+    if !matches!(stopped.state, TcpDaemonState::Stopped) {
+        return Err(stopped
+            .last_error
+            .unwrap_or_else(|| "Daemon did not stop; upgrade aborted.".to_string()));
+    }
+
+    let replaced_binary = match replace_target {
+        Some(target) => {
+            if let Err(error) = replace_daemon_binary(&bundled, &target) {
+                // Never leave the user without a daemon: start it again
+                // before reporting the failure.
+                return Err(
+                    match restart_daemon(state.clone(), &listen_addr, token).await {
+                        Ok(_) => format!("{error}; the daemon was restarted."),
+                        Err(restart_error) => {
+                            format!("{error}; restarting the daemon also failed: {restart_error}")
+                        }
+                    },
+                );
+            }
+            Some(target.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+
+    let status = restart_daemon(state.clone(), &listen_addr, token).await?;
+    let version = wait_for_daemon_version(&listen_addr, token).await;
+
+    Ok(TcpDaemonUpgradeResult {
+        previous_version: info.version,
+        version,
+        replaced_binary,
+        status,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        can_force_stop_daemon, should_restart_daemon, DaemonInfo, CURRENT_APP_VERSION,
-        EXPECTED_DAEMON_MODE, EXPECTED_DAEMON_NAME,
+        binary_to_replace, can_force_stop_daemon, daemon_version_report, is_daemon_binary_name,
+        should_restart_daemon, DaemonInfo, CURRENT_APP_VERSION, EXPECTED_DAEMON_MODE,
+        EXPECTED_DAEMON_NAME,
     };
+    use crate::shared::daemon_version_core::DAEMON_PROTOCOL_VERSION;
+    use std::path::Path;
 
     fn daemon_info(version: &str) -> DaemonInfo {
         DaemonInfo {
@@ -432,6 +692,8 @@ mod tests {
             pid: Some(42),
             mode: EXPECTED_DAEMON_MODE.to_string(),
             binary_path: Some("/tmp/codex-monitor-daemon".to_string()),
+            protocol_version: Some(DAEMON_PROTOCOL_VERSION),
+            min_protocol_version: Some(0),
         }
     }
 
@@ -455,4 +717,68 @@ mod tests {
         assert!(!can_force_stop_daemon(false, Some(&info)));
         assert!(!can_force_stop_daemon(true, None));
     }
+
+    #[test]
+    fn version_report_flags_older_daemons_for_upgrade() {
+        let current =
+            daemon_version_report(true, Some(&daemon_info(CURRENT_APP_VERSION)), String::new());
+        assert!(current.compatible);
+        assert!(!current.upgrade_available);
+
+        let mut legacy = daemon_info("0.1.0");
+        legacy.protocol_version = None;
+        legacy.min_protocol_version = None;
+        let report = daemon_version_report(true, Some(&legacy), String::new());
+        assert!(report.compatible);
+        assert!(report.upgrade_available);
+        assert_eq!(report.protocol_version, None);
+
+        let stopped = daemon_version_report(false, None, "Daemon is not running.".to_string());
+        assert!(!stopped.upgrade_available);
+        assert_eq!(stopped.message, "Daemon is not running.");
+    }
+
+    #[test]
+    fn only_daemon_binaries_are_replaced() {
+        let name = if cfg!(windows) {
+            "codex-monitor-daemon.exe"
+        } else {
+            "codex-monitor-daemon"
+        };
+        assert!(is_daemon_binary_name(&Path::new("/opt/codex").join(name)));
+        assert!(!is_daemon_binary_name(Path::new("/usr/bin/bash")));
+    }
+
+    #[test]
+    fn only_daemons_in_install_dirs_are_replaced() {
+        let root =
+            std::env::temp_dir().join(format!("codex-monitor-upgrade-{}", uuid::Uuid::new_v4()));
+        let installed = root.join("installed");
+        let elsewhere = root.join("elsewhere");
+        std::fs::create_dir_all(&installed).unwrap();
+        std::fs::create_dir_all(&elsewhere).unwrap();
+        let name = crate::daemon_binary::daemon_binary_candidates()[0];
+        let bundled = root.join(name);
+        let target = installed.join(name);
+        let stray = elsewhere.join(name);
+        for path in [&bundled, &target, &stray] {
+            std::fs::write(path, b"daemon").unwrap();
+        }
+        let install_dirs = vec![installed.clone()];
+        let check = |reported: &Path| binary_to_replace(reported.to_str(), &bundled, &install_dirs);
+
+        assert_eq!(binary_to_replace(None, &bundled, &install_dirs), Ok(None));
+        assert_eq!(check(&bundled), Ok(None));
+        assert_eq!(check(&target), Ok(Some(target.canonicalize().unwrap())));
+        assert!(check(&stray).is_err());
+        assert!(check(&installed.join("missing")).is_err());
+        #[cfg(unix)]
+        {
+            let link = installed.join("codex-monitor-daemon-link");
+            std::os::unix::fs::symlink(&stray, &link).unwrap();
+            assert!(check(&link).is_err());
+        }
+
+        let _ = std::fs::remove_dir_all(root);
+    }
 }
@@ -0,0 +1,116 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::backend::protocol_trace::{rotated_path, tail_lines};
+use crate::shared::storage_core::category_dir;
+use crate::types::{StorageCategory, TcpDaemonLogs};
+
+const DAEMON_LOG_FILE: &str = "daemon.log";
+/// A log past this size is rotated to `daemon.log.1` when the daemon starts.
+const MAX_DAEMON_LOG_BYTES: u64 = 4 * 1024 * 1024;
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5000;
+/// Output lines quoted in `last_error` when the daemon dies.
+const EXIT_OUTPUT_LINES: usize = 10;
+const START_MARKER: &str = "--- daemon started ";
+
+pub(super) fn daemon_log_path(data_dir: &Path) -> PathBuf {
+    category_dir(data_dir, StorageCategory::Logs).join(DAEMON_LOG_FILE)
+}
+
+/// Opens the log for a daemon about to start and marks where its output
+/// begins. The daemon writes to the file directly, so its output survives
+/// the app quitting. Returns one handle for stdout and one for stderr.
+pub(super) fn open_daemon_log(path: &Path) -> Result<(File, File), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let too_large = std::fs::metadata(path)
+        .map(|meta| meta.len() > MAX_DAEMON_LOG_BYTES)
+        .unwrap_or(false);
+    if too_large {
+        let _ = std::fs::rename(path, rotated_path(path));
+    }
+    let mut stdout = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Failed to open daemon log {}: {err}", path.display()))?;
+    let _ = writeln!(
+        stdout,
+        "{START_MARKER}{} ---",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+    );
+    let stderr = stdout
+        .try_clone()
+        .map_err(|err| format!("Failed to open daemon log {}: {err}", path.display()))?;
+    Ok((stdout, stderr))
+}
+
+/// The last lines the daemon wrote since it started, for `last_error`.
+pub(super) fn exit_output(path: &Path) -> Option<String> {
+    let lines = tail_lines(path, EXIT_OUTPUT_LINES + 1);
+    let since_start = match lines
+        .iter()
+        .rposition(|line| line.starts_with(START_MARKER))
+    {
+        Some(marker) => &lines[marker + 1..],
+        None => &lines[lines.len().saturating_sub(EXIT_OUTPUT_LINES)..],
+    };
+    let output = since_start
+        .iter()
+        .map(|line| line.trim_end())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!output.is_empty()).then_some(output)
+}
+
+/// The newest `lines` log lines, reaching into the rotated file when the
+/// current one is short.
+pub(super) fn daemon_log_tail(path: &Path, lines: Option<usize>) -> TcpDaemonLogs {
+    let count = lines.unwrap_or(DEFAULT_TAIL_LINES).clamp(1, MAX_TAIL_LINES);
+    let mut tail = tail_lines(path, count);
+    if tail.len() < count {
+        let mut older = tail_lines(&rotated_path(path), count - tail.len());
+        older.append(&mut tail);
+        tail = older;
+    }
+    TcpDaemonLogs {
+        path: path.to_string_lossy().to_string(),
+        lines: tail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_output_covers_the_latest_run_only() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        let path = daemon_log_path(&dir);
+
+        let (mut stdout, _) = open_daemon_log(&path).expect("open");
+        writeln!(stdout, "daemon: first run").unwrap();
+        let (mut stdout, mut stderr) = open_daemon_log(&path).expect("reopen");
+        writeln!(stdout, "listening on 0.0.0.0:4732").unwrap();
+        writeln!(stderr, "failed to bind 0.0.0.0:4732: address in use").unwrap();
+
+        assert_eq!(
+            exit_output(&path).as_deref(),
+            Some("listening on 0.0.0.0:4732\nfailed to bind 0.0.0.0:4732: address in use")
+        );
+        let tail = daemon_log_tail(&path, Some(2));
+        assert_eq!(tail.lines.len(), 2);
+        assert!(tail.lines[1].starts_with("failed to bind"));
+        assert_eq!(daemon_log_tail(&path, None).lines.len(), 5);
+
+        let _ = open_daemon_log(&path).expect("restart");
+        assert_eq!(exit_output(&path), None);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,6 +1,10 @@
+mod autostart;
 mod core;
 mod daemon_commands;
+mod daemon_log;
 mod rpc_client;
+mod serve_commands;
+pub(crate) mod watch;
 
 use std::ffi::{OsStr, OsString};
 use std::io::ErrorKind;
@@ -14,11 +18,16 @@ use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tokio::time::{sleep, timeout, Instant};
 
-use crate::daemon_binary::resolve_daemon_binary_path;
+use crate::daemon_binary::{
+    daemon_binary_candidates, daemon_search_dirs, resolve_daemon_binary_path,
+};
+use crate::shared::daemon_version_core;
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
 use crate::state::{AppState, TcpDaemonRuntime};
 use crate::types::{
-    TailscaleDaemonCommandPreview, TailscaleStatus, TcpDaemonState, TcpDaemonStatus,
+    DaemonAutostartStatus, TailscaleDaemonCommandPreview, TailscalePeer, TailscaleServeStatus,
+    TailscaleStatus, TcpDaemonLogs, TcpDaemonState, TcpDaemonStatus, TcpDaemonUpgradeResult,
+    TcpDaemonVersion,
 };
 
 use self::core as tailscale_core;
@@ -299,12 +308,18 @@ async fn refresh_tcp_daemon_runtime(runtime: &mut TcpDaemonRuntime) {
                 } else {
                     ""
                 };
+                let output = runtime
+                    .log_path
+                    .as_deref()
+                    .and_then(daemon_log::exit_output)
+                    .map(|output| format!("\nLast output:\n{output}"))
+                    .unwrap_or_default();
                 runtime.status = TcpDaemonStatus {
                     state: TcpDaemonState::Error,
                     pid,
                     started_at_ms: runtime.status.started_at_ms,
                     last_error: Some(format!(
-                        "Daemon exited with status: {status}.{failure_hint}"
+                        "Daemon exited with status: {status}.{failure_hint}{output}"
                     )),
                     listen_addr: runtime.status.listen_addr.clone(),
                 };
@@ -683,3 +698,105 @@ pub(crate) async fn tailscale_daemon_status(
 ) -> Result<TcpDaemonStatus, String> {
     daemon_commands::tailscale_daemon_status(state).await
 }
+
+/// The running daemon's version and protocol next to the bundled daemon's.
+#[tauri::command]
+pub(crate) async fn tailscale_daemon_version(
+    state: State<'_, AppState>,
+) -> Result<TcpDaemonVersion, String> {
+    daemon_commands::tailscale_daemon_version(state).await
+}
+
+/// Stops the running daemon, replaces its binary with the bundled one when
+/// it ran from another copy, and restarts it on the same data directory.
+#[tauri::command]
+pub(crate) async fn tailscale_daemon_upgrade(
+    state: State<'_, AppState>,
+) -> Result<TcpDaemonUpgradeResult, String> {
+    daemon_commands::tailscale_daemon_upgrade(state).await
+}
+
+/// The newest `lines` (default 200) lines the daemon wrote to `daemon.log`
+/// in the app data directory.
+#[tauri::command]
+pub(crate) async fn tailscale_daemon_logs(
+    lines: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<TcpDaemonLogs, String> {
+    let data_dir = state
+        .settings_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data directory".to_string())?;
+    Ok(daemon_log::daemon_log_tail(
+        &daemon_log::daemon_log_path(data_dir),
+        lines,
+    ))
+}
+
+/// Serves the daemon over TLS on the machine's tailnet name, or to the
+/// internet with `funnel`, through `tailscale serve`.
+#[tauri::command]
+pub(crate) async fn tailscale_serve_enable(
+    port: Option<u16>,
+    funnel: Option<bool>,
+    serve_port: Option<u16>,
+    state: State<'_, AppState>,
+) -> Result<TailscaleServeStatus, String> {
+    serve_commands::tailscale_serve_enable(state, port, funnel.unwrap_or(false), serve_port).await
+}
+
+#[tauri::command]
+pub(crate) async fn tailscale_serve_disable(
+    port: Option<u16>,
+    state: State<'_, AppState>,
+) -> Result<TailscaleServeStatus, String> {
+    serve_commands::tailscale_serve_disable(state, port).await
+}
+
+/// Other machines on the tailnet, so a remote backend host can be picked
+/// rather than typed.
+#[tauri::command]
+pub(crate) async fn tailscale_list_peers() -> Result<Vec<TailscalePeer>, String> {
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    {
+        return Err(UNSUPPORTED_MESSAGE.to_string());
+    }
+
+    let binary = serve_commands::require_tailscale_binary().await?;
+    let output = serve_commands::run_tailscale(&binary, &["status", "--json"]).await?;
+    tailscale_core::peers_from_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Installs a launchd agent, systemd user unit or logon task that starts
+/// the daemon with the configured listen address, data directory and token,
+/// so mobile access survives a reboot.
+#[tauri::command]
+pub(crate) async fn daemon_install_autostart(
+    state: State<'_, AppState>,
+) -> Result<DaemonAutostartStatus, String> {
+    autostart::daemon_install_autostart(state).await
+}
+
+#[tauri::command]
+pub(crate) async fn daemon_uninstall_autostart(
+    state: State<'_, AppState>,
+) -> Result<DaemonAutostartStatus, String> {
+    autostart::daemon_uninstall_autostart(state).await
+}
+
+/// Carries a rotated token or a new listen address into an installed
+/// autostart definition. Saving settings does not fail over it.
+pub(crate) async fn sync_daemon_autostart(
+    state: &AppState,
+    previous: &crate::types::AppSettings,
+    updated: &crate::types::AppSettings,
+) {
+    if let Err(error) = autostart::sync_autostart_settings(state, previous, updated).await {
+        eprintln!("Failed to update daemon autostart: {error}");
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn daemon_autostart_status() -> Result<DaemonAutostartStatus, String> {
+    autostart::daemon_autostart_status().await
+}
@@ -9,6 +9,9 @@ pub(super) struct DaemonInfo {
     pub(super) pid: Option<u32>,
     pub(super) mode: String,
     pub(super) binary_path: Option<String>,
+    /// Missing from daemons that predate protocol versioning.
+    pub(super) protocol_version: Option<u32>,
+    pub(super) min_protocol_version: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +40,21 @@ fn is_auth_error_message(message: &str) -> bool {
     lower.contains("unauthorized") || lower.contains("invalid token")
 }
 
+/// The daemon refuses the handshake when either side is too old for the
+/// other; it is still a daemon worth reporting as running.
+fn is_protocol_error_message(message: &str) -> bool {
+    message.to_ascii_lowercase().contains("protocol")
+}
+
+fn auth_params(token: &str) -> Value {
+    let ours = daemon_version_core::daemon_version_core();
+    json!({
+        "token": token,
+        "protocolVersion": ours.protocol_version,
+        "minProtocolVersion": ours.min_protocol_version,
+    })
+}
+
 fn parse_daemon_info(value: &Value) -> Result<DaemonInfo, String> {
     let name = value
         .get("name")
@@ -69,6 +87,12 @@ fn parse_daemon_info(value: &Value) -> Result<DaemonInfo, String> {
         .map(str::trim)
         .filter(|value| !value.is_empty())
         .map(str::to_string);
+    let protocol_field = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_u64)
+            .and_then(|value| u32::try_from(value).ok())
+    };
 
     Ok(DaemonInfo {
         name,
@@ -76,6 +100,8 @@ fn parse_daemon_info(value: &Value) -> Result<DaemonInfo, String> {
         pid,
         mode,
         binary_path,
+        protocol_version: protocol_field("protocolVersion"),
+        min_protocol_version: protocol_field("minProtocolVersion"),
     })
 }
 
@@ -191,7 +217,7 @@ pub(super) async fn probe_daemon(listen_addr: &str, token: Option<&str>) -> Daem
                 &mut lines,
                 10,
                 "auth",
-                json!({ "token": auth_token }),
+                auth_params(auth_token),
             )
             .await
             {
@@ -214,7 +240,13 @@ pub(super) async fn probe_daemon(listen_addr: &str, token: Option<&str>) -> Daem
                     }
                 }
                 Err(auth_error) => {
-                    if is_auth_error_message(&auth_error) {
+                    if is_protocol_error_message(&auth_error) {
+                        DaemonProbe::Running {
+                            auth_ok: false,
+                            auth_error: Some(auth_error),
+                            info: None,
+                        }
+                    } else if is_auth_error_message(&auth_error) {
                         DaemonProbe::Running {
                             auth_ok: false,
                             auth_error: Some(format!(
@@ -256,15 +288,9 @@ pub(super) async fn request_daemon_shutdown(
                 .ok_or_else(|| {
                     "Daemon is running but requires a remote backend token.".to_string()
                 })?;
-            send_and_expect_result(
-                &mut writer,
-                &mut lines,
-                2,
-                "auth",
-                json!({ "token": auth_token }),
-            )
-            .await
-            .map_err(|err| format!("Daemon authentication failed: {err}"))?;
+            send_and_expect_result(&mut writer, &mut lines, 2, "auth", auth_params(auth_token))
+                .await
+                .map_err(|err| format!("Daemon authentication failed: {err}"))?;
         }
         Err(message) => {
             return Err(format!("Daemon ping failed: {message}"));
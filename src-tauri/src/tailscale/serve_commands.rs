@@ -0,0 +1,95 @@
+use super::*;
+
+/// The port the local daemon listens on, unless the caller names one.
+async fn resolve_daemon_port(state: &AppState, port: Option<u16>) -> Result<u16, String> {
+    if let Some(port) = port {
+        return Ok(port);
+    }
+    let settings = state.app_settings.lock().await.clone();
+    let listen_addr = configured_daemon_listen_addr(&settings);
+    parse_port_from_remote_host(&listen_addr)
+        .ok_or_else(|| format!("Invalid daemon listen address: {listen_addr}"))
+}
+
+pub(super) async fn require_tailscale_binary() -> Result<OsString, String> {
+    match resolve_tailscale_binary().await? {
+        Some((binary, _)) => Ok(binary),
+        None => Err(missing_tailscale_message()),
+    }
+}
+
+pub(super) async fn run_tailscale(
+    binary: &OsStr,
+    args: &[impl AsRef<str>],
+) -> Result<Output, String> {
+    let args: Vec<&str> = args.iter().map(AsRef::<str>::as_ref).collect();
+    let output = tailscale_output(binary, &args)
+        .await
+        .map_err(|err| format!("Failed to run tailscale {}: {err}", args.join(" ")))?;
+    if output.status.success() {
+        return Ok(output);
+    }
+    let detail = trim_to_non_empty(std::str::from_utf8(&output.stderr).ok())
+        .or_else(|| trim_to_non_empty(std::str::from_utf8(&output.stdout).ok()))
+        .unwrap_or_else(|| format!("exit status {}", output.status));
+    Err(format!(
+        "tailscale {} failed: {}",
+        args.join(" "),
+        truncate_preview(&detail, 400)
+    ))
+}
+
+async fn read_serve_status(
+    binary: &OsStr,
+    daemon_port: u16,
+) -> Result<TailscaleServeStatus, String> {
+    let output = run_tailscale(binary, &["serve", "status", "--json"]).await?;
+    let payload = String::from_utf8_lossy(&output.stdout);
+    let dns_name = tailscale_status()
+        .await
+        .ok()
+        .and_then(|status| status.dns_name);
+    tailscale_core::serve_status_from_json(&payload, daemon_port, dns_name.as_deref())
+}
+
+pub(super) async fn tailscale_serve_enable(
+    state: State<'_, AppState>,
+    port: Option<u16>,
+    funnel: bool,
+    serve_port: Option<u16>,
+) -> Result<TailscaleServeStatus, String> {
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    {
+        return Err(UNSUPPORTED_MESSAGE.to_string());
+    }
+
+    let daemon_port = resolve_daemon_port(&state, port).await?;
+    let serve_port = serve_port.unwrap_or(tailscale_core::DEFAULT_SERVE_PORT);
+    let args = tailscale_core::serve_args(serve_port, daemon_port, funnel)?;
+    let binary = require_tailscale_binary().await?;
+    run_tailscale(&binary, &args).await?;
+    read_serve_status(&binary, daemon_port).await
+}
+
+pub(super) async fn tailscale_serve_disable(
+    state: State<'_, AppState>,
+    port: Option<u16>,
+) -> Result<TailscaleServeStatus, String> {
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    {
+        return Err(UNSUPPORTED_MESSAGE.to_string());
+    }
+
+    let daemon_port = resolve_daemon_port(&state, port).await?;
+    let binary = require_tailscale_binary().await?;
+    let current = read_serve_status(&binary, daemon_port).await?;
+    let Some(serve_port) = current.serve_port.filter(|_| current.enabled) else {
+        return Ok(current);
+    };
+    run_tailscale(
+        &binary,
+        &tailscale_core::serve_off_args(serve_port, current.funnel),
+    )
+    .await?;
+    read_serve_status(&binary, daemon_port).await
+}
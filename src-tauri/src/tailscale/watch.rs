@@ -0,0 +1,137 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::time::timeout;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::event_sink::TauriEventSink;
+use crate::state::AppState;
+use crate::types::TailscaleStatus;
+
+use super::tailscale_core;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Used while Tailscale is not installed, so the binary search stays rare.
+const MISSING_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct WatchState {
+    last: Option<TailscaleStatus>,
+}
+
+impl WatchState {
+    /// Stores `status` and, when it differs from the previous one, returns
+    /// whether Tailscale was running before. The first status is only
+    /// stored.
+    fn record(&mut self, status: &TailscaleStatus) -> Option<bool> {
+        let previous = self.last.replace(status.clone())?;
+        (previous != *status).then_some(previous.running)
+    }
+}
+
+/// The last Tailscale status `run_status_watch` saw, kept on `AppState`.
+#[derive(Default)]
+pub(crate) struct TailscaleWatch(Mutex<WatchState>);
+
+impl TailscaleWatch {
+    fn lock(&self) -> MutexGuard<'_, WatchState> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether `remote_host` is a tailnet address while Tailscale is
+    /// installed here but not connected, so there is no point trying to
+    /// reach it.
+    pub(crate) fn tailnet_offline_for(&self, remote_host: &str) -> bool {
+        let offline = self
+            .lock()
+            .last
+            .as_ref()
+            .is_some_and(|status| status.installed && !status.running);
+        offline && tailscale_core::is_tailnet_host(remote_host)
+    }
+}
+
+/// Reads `tailscale status` every few seconds and emits
+/// `tailscale/statusChanged` whenever the result changes. Connecting or
+/// disconnecting also wakes the remote backend supervisor, which holds off
+/// pinging tailnet hosts while Tailscale is down.
+pub(crate) async fn run_status_watch(app: AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        let delay = match timeout(STATUS_TIMEOUT, super::tailscale_status()).await {
+            Ok(Ok(status)) => {
+                let was_running = state.tailscale_watch.lock().record(&status);
+                if let Some(was_running) = was_running {
+                    TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+                        workspace_id: String::new(),
+                        message: json!({
+                            "method": "tailscale/statusChanged",
+                            "params": {
+                                "status": status,
+                                "wasRunning": was_running,
+                            }
+                        }),
+                    });
+                    if was_running != status.running {
+                        state.remote_supervisor.report_disconnect();
+                    }
+                }
+                if status.installed {
+                    POLL_INTERVAL
+                } else {
+                    MISSING_POLL_INTERVAL
+                }
+            }
+            _ => POLL_INTERVAL,
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(running: bool, dns_name: Option<&str>) -> TailscaleStatus {
+        TailscaleStatus {
+            installed: true,
+            running,
+            version: None,
+            dns_name: dns_name.map(str::to_string),
+            host_name: None,
+            tailnet_name: None,
+            ipv4: Vec::new(),
+            ipv6: Vec::new(),
+            suggested_remote_host: None,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_changes_after_the_first_status_are_reported() {
+        let mut state = WatchState::default();
+        assert_eq!(
+            state.record(&status(true, Some("mac.example.ts.net"))),
+            None
+        );
+        assert_eq!(
+            state.record(&status(true, Some("mac.example.ts.net"))),
+            None
+        );
+        assert_eq!(state.record(&status(false, None)), Some(true));
+        assert_eq!(state.record(&status(false, None)), None);
+        assert_eq!(
+            state.record(&status(true, Some("mac.example.ts.net"))),
+            Some(false)
+        );
+        assert_eq!(
+            state.record(&status(true, Some("mac.other.ts.net"))),
+            Some(true)
+        );
+    }
+}
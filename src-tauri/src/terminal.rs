@@ -4,11 +4,15 @@ use std::sync::Arc;
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use serde::Serialize;
+use serde_json::json;
 use tauri::{AppHandle, Manager, State};
 use tokio::sync::Mutex;
 
 use crate::backend::events::{EventSink, TerminalExit, TerminalOutput};
+use crate::backend::terminal_history::TerminalOutputPage;
 use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::shared::workspaces_core::ensure_workspace_writable_core;
 use crate::state::AppState;
 
 pub(crate) struct TerminalSession {
@@ -188,7 +192,7 @@ async fn get_workspace_path(
     workspace_id: &str,
     state: &State<'_, AppState>,
 ) -> Result<PathBuf, String> {
-    let workspaces = state.workspaces.lock().await;
+    let workspaces = state.workspaces.read().await;
     let entry = workspaces
         .get(workspace_id)
         .ok_or_else(|| "Unknown workspace".to_string())?;
@@ -207,6 +211,7 @@ pub(crate) async fn terminal_open(
     if terminal_id.is_empty() {
         return Err("Terminal id is required".to_string());
     }
+    ensure_workspace_writable_core(&state.workspaces, &workspace_id).await?;
     let key = terminal_key(&workspace_id, &terminal_id);
     {
         let sessions = state.terminal_sessions.lock().await;
@@ -372,6 +377,39 @@ pub(crate) async fn terminal_close(
     Ok(())
 }
 
+/// Pages back through recorded output; in remote mode the daemon's history is
+/// used.
+#[tauri::command]
+pub(crate) async fn terminal_output_page(
+    workspace_id: String,
+    terminal_id: String,
+    cursor: Option<u64>,
+    max_bytes: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TerminalOutputPage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_output_page",
+            json!({
+                "workspaceId": workspace_id,
+                "terminalId": terminal_id,
+                "cursor": cursor,
+                "maxBytes": max_bytes,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    state
+        .services
+        .terminal_history
+        .page(&workspace_id, &terminal_id, cursor, max_bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{unix_shell_args, windows_shell_args};
@@ -1,6 +1,9 @@
 use serde::Serialize;
+use serde_json::json;
 use tauri::{AppHandle, State};
 
+use crate::backend::terminal_history::TerminalOutputPage;
+use crate::remote_backend;
 use crate::state::AppState;
 
 const UNSUPPORTED_MESSAGE: &str = "Terminal is not available on mobile builds.";
@@ -58,3 +61,36 @@ pub(crate) async fn terminal_close(
 ) -> Result<(), String> {
     Err(UNSUPPORTED_MESSAGE.to_string())
 }
+
+/// Pages back through recorded output; in remote mode the daemon's history is
+/// used.
+#[tauri::command]
+pub(crate) async fn terminal_output_page(
+    workspace_id: String,
+    terminal_id: String,
+    cursor: Option<u64>,
+    max_bytes: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TerminalOutputPage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_output_page",
+            json!({
+                "workspaceId": workspace_id,
+                "terminalId": terminal_id,
+                "cursor": cursor,
+                "maxBytes": max_bytes,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    state
+        .services
+        .terminal_history
+        .page(&workspace_id, &terminal_id, cursor, max_bytes)
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -77,6 +79,54 @@ pub(crate) struct GitLogResponse {
     pub(crate) upstream: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitConflictFile {
+    pub(crate) path: String,
+    pub(crate) base: Option<String>,
+    pub(crate) ours: Option<String>,
+    pub(crate) theirs: Option<String>,
+    #[serde(default, rename = "isBinary")]
+    pub(crate) is_binary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitConflictsResponse {
+    #[serde(default)]
+    pub(crate) operation: Option<String>,
+    pub(crate) files: Vec<GitConflictFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GitAuthCheckStatus {
+    Ok,
+    Warning,
+    Error,
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitAuthCheck {
+    pub(crate) id: String,
+    pub(crate) status: GitAuthCheckStatus,
+    pub(crate) detail: String,
+    #[serde(default)]
+    pub(crate) hint: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitAuthDoctorReport {
+    #[serde(rename = "remoteName")]
+    pub(crate) remote_name: Option<String>,
+    #[serde(rename = "remoteUrl")]
+    pub(crate) remote_url: Option<String>,
+    /// `ssh`, `https`, `http`, `file`, another URL scheme, or `none` without
+    /// a remote.
+    pub(crate) scheme: String,
+    pub(crate) ok: bool,
+    pub(crate) checks: Vec<GitAuthCheck>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubIssue {
     pub(crate) number: u64,
@@ -209,8 +259,61 @@ pub(crate) struct TcpDaemonStatus {
     pub(crate) listen_addr: Option<String>,
 }
 
+/// The running daemon's version next to the one bundled with the app.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
+pub(crate) struct TcpDaemonVersion {
+    pub(crate) running: bool,
+    #[serde(default)]
+    pub(crate) version: Option<String>,
+    /// `None` for daemons that predate protocol versioning.
+    #[serde(default)]
+    pub(crate) protocol_version: Option<u32>,
+    #[serde(default)]
+    pub(crate) binary_path: Option<String>,
+    pub(crate) bundled_version: String,
+    pub(crate) bundled_protocol_version: u32,
+    pub(crate) compatible: bool,
+    pub(crate) upgrade_available: bool,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TcpDaemonUpgradeResult {
+    pub(crate) previous_version: String,
+    #[serde(default)]
+    pub(crate) version: Option<String>,
+    /// The daemon binary overwritten with the bundled one, when the old
+    /// daemon ran from somewhere else.
+    #[serde(default)]
+    pub(crate) replaced_binary: Option<String>,
+    pub(crate) status: TcpDaemonStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TcpDaemonLogs {
+    pub(crate) path: String,
+    /// Oldest first. Each start of the daemon is marked with a
+    /// `--- daemon started <time> ---` line.
+    pub(crate) lines: Vec<String>,
+}
+
+/// How the daemon is started at login, if at all.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DaemonAutostartStatus {
+    pub(crate) installed: bool,
+    /// `launchd`, `systemd` or `scheduledTask`.
+    pub(crate) manager: String,
+    /// The plist, unit or task name that starts the daemon.
+    pub(crate) definition: String,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct TailscaleStatus {
     pub(crate) installed: bool,
     pub(crate) running: bool,
@@ -231,6 +334,27 @@ pub(crate) struct TailscaleStatus {
     pub(crate) message: String,
 }
 
+/// Another machine on the tailnet, for picking a remote backend host.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TailscalePeer {
+    pub(crate) id: String,
+    pub(crate) host_name: String,
+    /// MagicDNS name without the trailing dot.
+    #[serde(default)]
+    pub(crate) dns_name: Option<String>,
+    #[serde(default)]
+    pub(crate) os: Option<String>,
+    pub(crate) online: bool,
+    #[serde(default)]
+    pub(crate) ipv4: Vec<String>,
+    #[serde(default)]
+    pub(crate) ipv6: Vec<String>,
+    /// `host:port` for the daemon's default port.
+    #[serde(default)]
+    pub(crate) suggested_remote_host: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct TailscaleDaemonCommandPreview {
@@ -238,6 +362,30 @@ pub(crate) struct TailscaleDaemonCommandPreview {
     pub(crate) daemon_path: String,
     pub(crate) args: Vec<String>,
     pub(crate) token_configured: bool,
+    /// The `tailscale serve` command `tailscale_serve_enable` runs.
+    #[serde(default)]
+    pub(crate) serve_command: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TailscaleServeStatus {
+    pub(crate) enabled: bool,
+    /// Also reachable from the public internet through Tailscale Funnel.
+    pub(crate) funnel: bool,
+    /// The HTTPS port on the tailnet name.
+    #[serde(default)]
+    pub(crate) serve_port: Option<u16>,
+    /// The local daemon address Tailscale forwards to.
+    #[serde(default)]
+    pub(crate) target: Option<String>,
+    /// `host:port` for a TLS remote profile.
+    #[serde(default)]
+    pub(crate) remote_host: Option<String>,
+    /// e.g. `tls://mac.example.ts.net:443`.
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    pub(crate) message: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -246,6 +394,14 @@ pub(crate) struct BranchInfo {
     pub(crate) last_commit: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitStashEntry {
+    pub(crate) index: usize,
+    pub(crate) message: String,
+    pub(crate) sha: String,
+    pub(crate) timestamp: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceEntry {
     pub(crate) id: String,
@@ -301,6 +457,28 @@ pub(crate) struct WorktreeInfo {
     pub(crate) branch: String,
 }
 
+/// A worktree after `add_worktree` or `rename_worktree`. When the requested
+/// branch collided, `worktree.branch` holds the suffixed name actually used.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeResult {
+    #[serde(flatten)]
+    pub(crate) workspace: WorkspaceInfo,
+    pub(crate) requested_branch: String,
+    pub(crate) was_renamed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BranchNameSuggestion {
+    pub(crate) requested_branch: String,
+    pub(crate) branch: String,
+    pub(crate) was_renamed: bool,
+    /// The branch already exists and `add_worktree` will check it out rather
+    /// than create it.
+    pub(crate) existing: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceGroup {
     pub(crate) id: String,
@@ -331,6 +509,132 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) worktree_setup_script: Option<String>,
     #[serde(default, rename = "worktreesFolder")]
     pub(crate) worktrees_folder: Option<String>,
+    #[serde(default, rename = "agentBackend")]
+    pub(crate) agent_backend: Option<AgentBackendKind>,
+    #[serde(default, rename = "agentCommand")]
+    pub(crate) agent_command: Option<String>,
+    #[serde(default, rename = "turnConcurrency")]
+    pub(crate) turn_concurrency: Option<TurnConcurrencyPolicy>,
+    #[serde(default, rename = "mergeReady")]
+    pub(crate) merge_ready: bool,
+    /// Turns run with a read-only sandbox, and file writes, git mutations,
+    /// and terminals are refused for this workspace.
+    #[serde(default, rename = "readOnly")]
+    pub(crate) read_only: bool,
+    #[serde(default, rename = "mergeQueueTestCommand")]
+    pub(crate) merge_queue_test_command: Option<String>,
+    #[serde(default, rename = "releaseArtifacts")]
+    pub(crate) release_artifacts: Option<Vec<String>>,
+    #[serde(default, rename = "requestTimeouts")]
+    pub(crate) request_timeouts: Option<RequestTimeoutSettings>,
+    /// Notify from the backend when a turn finishes, fails or needs approval.
+    #[serde(default, rename = "notificationsEnabled")]
+    pub(crate) notifications_enabled: bool,
+    /// Used by `send_user_message` when the caller does not pick one.
+    #[serde(default, rename = "defaultAccessMode")]
+    pub(crate) default_access_mode: Option<String>,
+    #[serde(default, rename = "defaultModel")]
+    pub(crate) default_model: Option<String>,
+    #[serde(default, rename = "defaultEffort")]
+    pub(crate) default_effort: Option<String>,
+    /// Extra environment for this workspace's agent process, applied on top
+    /// of `AppSettings.codex_env`. A Codex workspace with its own env gets a
+    /// dedicated app-server instead of sharing one.
+    #[serde(default)]
+    pub(crate) env: HashMap<String, String>,
+    #[serde(default)]
+    pub(crate) quota: Option<WorkspaceQuota>,
+    /// `#rrggbb`, shared by every client showing the workspace.
+    #[serde(default)]
+    pub(crate) color: Option<String>,
+    /// An icon name from the app's icon set or a single emoji.
+    #[serde(default)]
+    pub(crate) icon: Option<String>,
+}
+
+/// Limits enforced on a workspace's agent activity. When one is exceeded the
+/// running turns are interrupted and new messages are refused until the quota
+/// is resumed or the (UTC) day rolls over.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceQuota {
+    #[serde(default)]
+    pub(crate) max_cpu_minutes_per_day: Option<u32>,
+    #[serde(default)]
+    pub(crate) max_concurrent_commands: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceQuotaStatus {
+    pub(crate) workspace_id: String,
+    pub(crate) quota: Option<WorkspaceQuota>,
+    pub(crate) cpu_ms_today: u64,
+    pub(crate) running_commands: usize,
+    pub(crate) paused_reason: Option<String>,
+}
+
+/// How a workspace handles a new message while another turn is still running
+/// in the same worktree.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TurnConcurrencyPolicy {
+    #[default]
+    Parallel,
+    Queue,
+    Reject,
+}
+
+/// What happens to `auth.json` when a session's `CODEX_HOME` (set through
+/// `codexEnv` or a workspace's `env`) is not the default home.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CodexAuthPropagation {
+    /// Leave the isolated home alone; it needs its own `codex_login`.
+    #[default]
+    Off,
+    /// Point the isolated `auth.json` at the default one.
+    Symlink,
+    /// Copy the default `auth.json` again whenever it changes.
+    Copy,
+}
+
+/// What `select_winner` does with the worktrees of a broadcast's losing runs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LosingWorktreeRetention {
+    /// Remove them once their threads are archived.
+    #[default]
+    Remove,
+    /// Leave them on disk; their threads are still archived and labeled.
+    Keep,
+}
+
+/// Request timeouts in seconds, keyed by app-server method. `0` waits
+/// indefinitely.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub(crate) struct RequestTimeoutSettings {
+    #[serde(default, rename = "defaultSecs")]
+    pub(crate) default_secs: Option<u64>,
+    #[serde(default)]
+    pub(crate) methods: HashMap<String, u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AgentBackendKind {
+    #[default]
+    Codex,
+    Acp,
+    Cli,
+}
+
+impl AgentBackendKind {
+    /// Codex app-server multiplexes every workspace over one process; other
+    /// agents get a dedicated process per workspace.
+    pub(crate) fn shares_session(self) -> bool {
+        matches!(self, AgentBackendKind::Codex)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -342,6 +646,23 @@ pub(crate) struct LaunchScriptEntry {
     pub(crate) label: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ReleaseArtifact {
+    pub(crate) path: String,
+    pub(crate) size: u64,
+    pub(crate) sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ReleaseManifest {
+    pub(crate) task: String,
+    #[serde(rename = "createdAt")]
+    pub(crate) created_at: i64,
+    #[serde(rename = "outputDir")]
+    pub(crate) output_dir: String,
+    pub(crate) artifacts: Vec<ReleaseArtifact>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorktreeSetupStatus {
     #[serde(rename = "shouldRun")]
@@ -371,6 +692,12 @@ pub(crate) struct RemoteBackendTarget {
     pub(crate) host: String,
     #[serde(default)]
     pub(crate) token: Option<String>,
+    /// Wrap the connection in TLS, e.g. behind a TLS-terminating proxy.
+    #[serde(default)]
+    pub(crate) tls: bool,
+    /// Name to verify the certificate against; the host name by default.
+    #[serde(default, rename = "tlsServerName")]
+    pub(crate) tls_server_name: Option<String>,
     #[serde(default, rename = "lastConnectedAtMs")]
     pub(crate) last_connected_at_ms: Option<i64>,
 }
@@ -381,6 +708,13 @@ pub(crate) struct AppSettings {
     pub(crate) codex_bin: Option<String>,
     #[serde(default, rename = "codexArgs")]
     pub(crate) codex_args: Option<String>,
+    /// Extra environment for every `codex app-server` this app spawns.
+    #[serde(default, rename = "codexEnv")]
+    pub(crate) codex_env: HashMap<String, String>,
+    /// How sessions whose `CODEX_HOME` is overridden get the login from the
+    /// default home.
+    #[serde(default, rename = "codexAuthPropagation")]
+    pub(crate) codex_auth_propagation: CodexAuthPropagation,
     #[serde(default, rename = "backendMode")]
     pub(crate) backend_mode: BackendMode,
     #[serde(default, rename = "remoteBackendProvider")]
@@ -548,6 +882,58 @@ pub(crate) struct AppSettings {
         rename = "subagentSystemNotificationsEnabled"
     )]
     pub(crate) subagent_system_notifications_enabled: bool,
+    /// Daemon-only: opted-in workspace notifications are POSTed here as JSON.
+    #[serde(default, rename = "notificationWebhookUrl")]
+    pub(crate) notification_webhook_url: Option<String>,
+    /// Daemon-only: every turn completion, failure and approval request is
+    /// POSTed here as JSON. `--webhook-url` takes precedence.
+    #[serde(default, rename = "daemonWebhookUrl")]
+    pub(crate) daemon_webhook_url: Option<String>,
+    /// Daemon-only: approval requests are posted to this Slack or Discord
+    /// channel and answered from reactions on the message.
+    #[serde(default, rename = "approvalChat")]
+    pub(crate) approval_chat: Option<ApprovalChatSettings>,
+    /// Daemon-only: turn completions and approval requests are pushed
+    /// through this relay to registered phones with no live connection.
+    #[serde(default, rename = "pushRelay")]
+    pub(crate) push_relay: Option<PushRelaySettings>,
+    /// Proxy and extra CA certificates for every outbound HTTP request and
+    /// every spawned `codex`, `git` and `gh` process.
+    #[serde(default)]
+    pub(crate) network: Option<NetworkSettings>,
+    /// Per-category size limits for the data dir, in megabytes. The oldest
+    /// files in a category are deleted once it grows past its limit.
+    #[serde(default, rename = "storageQuotasMb")]
+    pub(crate) storage_quotas_mb: HashMap<StorageCategory, u64>,
+    /// Files in any storage category older than this many days are deleted.
+    #[serde(default, rename = "storageRetentionDays")]
+    pub(crate) storage_retention_days: Option<u32>,
+    /// Flush `workspaces.json` and `settings.json` to disk on every write.
+    #[serde(default = "default_storage_fsync", rename = "storageFsync")]
+    pub(crate) storage_fsync: bool,
+    /// Previous versions of `workspaces.json` and `settings.json` kept as
+    /// `.bak.1` (newest) to `.bak.N`.
+    #[serde(default = "default_storage_backups", rename = "storageBackups")]
+    pub(crate) storage_backups: u32,
+    /// Turns allowed to run at once across all workspaces; further messages
+    /// wait in the run queue. Unset means no limit.
+    #[serde(default, rename = "maxConcurrentTurns")]
+    pub(crate) max_concurrent_turns: Option<u32>,
+    /// Percent of an account rate-limit window after which new turns carry a
+    /// `rateLimitWarning`. `null` turns the warning off.
+    #[serde(
+        default = "default_rate_limit_warn_percent",
+        rename = "rateLimitWarnPercent"
+    )]
+    pub(crate) rate_limit_warn_percent: Option<u8>,
+    /// Percent after which new turns wait until usage drops or the window
+    /// resets. Unset never holds turns back.
+    #[serde(default, rename = "rateLimitQueuePercent")]
+    pub(crate) rate_limit_queue_percent: Option<u8>,
+    /// Bytes of output kept per terminal for `terminal_output_page`. Unset
+    /// keeps 1 MiB.
+    #[serde(default, rename = "terminalHistoryBytes")]
+    pub(crate) terminal_history_bytes: Option<u64>,
     #[serde(
         default = "default_collaboration_modes_enabled",
         rename = "collaborationModesEnabled"
@@ -644,10 +1030,164 @@ pub(crate) struct AppSettings {
     pub(crate) workspace_groups: Vec<WorkspaceGroup>,
     #[serde(default, rename = "globalWorktreesFolder")]
     pub(crate) global_worktrees_folder: Option<String>,
+    /// Longer worktree folder names are cut and given a hash suffix.
+    #[serde(
+        default = "default_worktree_name_max_length",
+        rename = "worktreeNameMaxLength"
+    )]
+    pub(crate) worktree_name_max_length: u32,
     #[serde(default = "default_open_app_targets", rename = "openAppTargets")]
     pub(crate) open_app_targets: Vec<OpenAppTarget>,
     #[serde(default = "default_selected_open_app_id", rename = "selectedOpenAppId")]
     pub(crate) selected_open_app_id: String,
+    #[serde(default, rename = "mergeQueueEnabled")]
+    pub(crate) merge_queue_enabled: bool,
+    #[serde(default, rename = "losingWorktreeRetention")]
+    pub(crate) losing_worktree_retention: LosingWorktreeRetention,
+    #[serde(default, rename = "requestTimeouts")]
+    pub(crate) request_timeouts: RequestTimeoutSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ApprovalChatProvider {
+    Slack,
+    Discord,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApprovalChatSettings {
+    pub(crate) provider: ApprovalChatProvider,
+    #[serde(default)]
+    pub(crate) bot_token: String,
+    #[serde(default)]
+    pub(crate) channel_id: String,
+    /// Chat user IDs allowed to answer requests. Empty means nobody can, so
+    /// requests are only posted for visibility.
+    #[serde(default)]
+    pub(crate) approver_ids: Vec<String>,
+    /// Slack app-level token (`xapp-…`) for Socket Mode, which delivers
+    /// button presses. Without it Slack requests are answered by reactions.
+    #[serde(default)]
+    pub(crate) app_token: Option<String>,
+}
+
+/// A service holding APNs and FCM credentials that forwards the daemon's
+/// pushes to devices.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PushRelaySettings {
+    #[serde(default)]
+    pub(crate) url: String,
+    /// Sent as a bearer token when set.
+    #[serde(default)]
+    pub(crate) api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PushPlatform {
+    Apns,
+    Fcm,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PushDevice {
+    pub(crate) device_token: String,
+    pub(crate) platform: PushPlatform,
+    /// Bundle or package id the relay addresses the push to.
+    #[serde(default)]
+    pub(crate) app_id: Option<String>,
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    pub(crate) registered_at_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NetworkSettings {
+    #[serde(default)]
+    pub(crate) http_proxy: Option<String>,
+    #[serde(default)]
+    pub(crate) https_proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy.
+    #[serde(default)]
+    pub(crate) no_proxy: Option<String>,
+    /// PEM file with additional root certificates to trust.
+    #[serde(default)]
+    pub(crate) ca_certificates_path: Option<String>,
+}
+
+/// The backend's clock as seen from this client.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerTime {
+    /// Ms since the epoch on the backend (the daemon in remote mode).
+    pub(crate) server_time: i64,
+    /// Add to the local clock to get the backend's; `0` in local mode.
+    pub(crate) offset_ms: i64,
+    pub(crate) round_trip_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NetworkProbeResult {
+    pub(crate) url: String,
+    pub(crate) ok: bool,
+    pub(crate) status: Option<u16>,
+    pub(crate) elapsed_ms: u64,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NetworkConnectivityReport {
+    pub(crate) http_proxy: Option<String>,
+    pub(crate) https_proxy: Option<String>,
+    pub(crate) ca_certificates_path: Option<String>,
+    pub(crate) ok: bool,
+    pub(crate) probes: Vec<NetworkProbeResult>,
+}
+
+/// A data-dir subdirectory whose size is tracked and bounded.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StorageCategory {
+    Journal,
+    Transcripts,
+    Attachments,
+    Logs,
+    Recordings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageCategoryUsage {
+    pub(crate) category: StorageCategory,
+    pub(crate) path: String,
+    pub(crate) bytes: u64,
+    pub(crate) file_count: usize,
+    pub(crate) quota_bytes: Option<u64>,
+    pub(crate) over_quota: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageUsageReport {
+    pub(crate) data_dir: String,
+    pub(crate) total_bytes: u64,
+    pub(crate) retention_days: Option<u32>,
+    pub(crate) categories: Vec<StorageCategoryUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageCleanupResult {
+    pub(crate) category: StorageCategory,
+    pub(crate) removed_files: usize,
+    pub(crate) freed_bytes: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -895,6 +1435,22 @@ fn default_subagent_system_notifications_enabled() -> bool {
     true
 }
 
+fn default_storage_fsync() -> bool {
+    true
+}
+
+fn default_storage_backups() -> u32 {
+    3
+}
+
+fn default_worktree_name_max_length() -> u32 {
+    64
+}
+
+fn default_rate_limit_warn_percent() -> Option<u8> {
+    Some(90)
+}
+
 fn default_split_chat_diff_view() -> bool {
     false
 }
@@ -1125,6 +1681,8 @@ impl Default for AppSettings {
         Self {
             codex_bin: None,
             codex_args: None,
+            codex_env: HashMap::new(),
+            codex_auth_propagation: CodexAuthPropagation::default(),
             backend_mode: default_backend_mode(),
             remote_backend_provider: RemoteBackendProvider::Tcp,
             remote_backend_host: default_remote_backend_host(),
@@ -1166,6 +1724,19 @@ impl Default for AppSettings {
             notification_sounds_enabled: true,
             system_notifications_enabled: true,
             subagent_system_notifications_enabled: true,
+            notification_webhook_url: None,
+            daemon_webhook_url: None,
+            approval_chat: None,
+            push_relay: None,
+            network: None,
+            storage_quotas_mb: HashMap::new(),
+            storage_retention_days: None,
+            storage_fsync: default_storage_fsync(),
+            storage_backups: default_storage_backups(),
+            max_concurrent_turns: None,
+            rate_limit_warn_percent: default_rate_limit_warn_percent(),
+            rate_limit_queue_percent: None,
+            terminal_history_bytes: None,
             split_chat_diff_view: default_split_chat_diff_view(),
             preload_git_diffs: default_preload_git_diffs(),
             git_diff_ignore_whitespace_changes: default_git_diff_ignore_whitespace_changes(),
@@ -1197,8 +1768,12 @@ impl Default for AppSettings {
             composer_code_block_copy_use_modifier: default_composer_code_block_copy_use_modifier(),
             workspace_groups: default_workspace_groups(),
             global_worktrees_folder: None,
+            worktree_name_max_length: default_worktree_name_max_length(),
             open_app_targets: default_open_app_targets(),
             selected_open_app_id: default_selected_open_app_id(),
+            merge_queue_enabled: false,
+            losing_worktree_retention: LosingWorktreeRetention::default(),
+            request_timeouts: RequestTimeoutSettings::default(),
         }
     }
 }
@@ -1206,8 +1781,8 @@ impl Default for AppSettings {
 #[cfg(test)]
 mod tests {
     use super::{
-        AppSettings, BackendMode, RemoteBackendProvider, WorkspaceEntry, WorkspaceGroup,
-        WorkspaceKind, WorkspaceSettings,
+        AppSettings, BackendMode, LosingWorktreeRetention, RemoteBackendProvider,
+        RequestTimeoutSettings, WorkspaceEntry, WorkspaceGroup, WorkspaceKind, WorkspaceSettings,
     };
 
     #[test]
@@ -1232,6 +1807,8 @@ mod tests {
         assert!(settings.remote_backends.is_empty());
         assert!(settings.active_remote_backend_id.is_none());
         assert!(!settings.keep_daemon_running_after_app_close);
+        assert_eq!(settings.rate_limit_warn_percent, Some(90));
+        assert!(settings.rate_limit_queue_percent.is_none());
         assert_eq!(settings.default_access_mode, "current");
         assert_eq!(settings.review_delivery_mode, "inline");
         let expected_primary = if cfg!(target_os = "macos") {
@@ -1343,6 +1920,12 @@ mod tests {
         assert!(settings.pause_queued_messages_when_response_required);
         assert!(settings.unified_exec_enabled);
         assert!(!settings.experimental_apps_enabled);
+        assert!(!settings.merge_queue_enabled);
+        assert_eq!(
+            settings.losing_worktree_retention,
+            LosingWorktreeRetention::Remove
+        );
+        assert_eq!(settings.request_timeouts, RequestTimeoutSettings::default());
         assert_eq!(settings.personality, "friendly");
         assert!(!settings.dictation_enabled);
         assert_eq!(settings.dictation_model_id, "base");
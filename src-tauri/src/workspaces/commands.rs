@@ -1,13 +1,17 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use std::sync::Arc;
 
+use serde_json::{json, Value};
+
 use tauri::{AppHandle, Manager, State};
 
-use super::files::{list_workspace_files_inner, read_workspace_file_inner, WorkspaceFileResponse};
+use super::files::{list_workspace_files_inner, read_workspace_file_inner};
 use super::git::{
-    git_branch_exists, git_find_remote_for_branch, git_remote_branch_exists, git_remote_exists,
-    is_missing_worktree_error, run_git_command_owned, unique_branch_name,
+    git_branch_exists, git_find_remote_for_branch, git_find_remote_tracking_branch,
+    git_remote_branch_exists, git_remote_exists, is_missing_worktree_error, run_git_command_owned,
+    unique_branch_name,
 };
 #[cfg(target_os = "macos")]
 use super::macos::get_open_app_icon_inner;
@@ -17,12 +21,34 @@ use super::worktree::{
 };
 
 use crate::backend::app_server::WorkspaceSession;
-use crate::codex::spawn_workspace_session;
+use crate::backend::operations::PendingOperation;
+use crate::backend::quotas;
+use crate::codex::{self, spawn_workspace_session};
+use crate::event_sink::TauriEventSink;
+use crate::files::io::WorkspaceFileResponse;
 use crate::git_utils::resolve_git_root;
 use crate::remote_backend;
-use crate::shared::{workspace_rpc, workspaces_core};
+use crate::shared::broadcast_core::{self, BroadcastRun, WinnerSelection};
+use crate::shared::context_attach_core::{self, ContextAttachment};
+use crate::shared::dashboard_core::{self, DashboardSnapshot};
+use crate::shared::dir_tree_core::{self, WorkspaceDirListing};
+use crate::shared::file_edit_core::{self, WorkspaceFileWriteResult};
+use crate::shared::file_index_core::{self, WorkspaceFilePage};
+use crate::shared::file_search_core::{self, FileSearchResult};
+use crate::shared::file_transfer_core::{self, DownloadedFile, UploadedFile};
+use crate::shared::file_watch_core;
+use crate::shared::task_output_core::{self, AttachedOutput};
+use crate::shared::workspace_recency_core::{self, WorkspaceFocus};
+use crate::shared::workspace_summary_core::{self, WorkspaceSummary};
+use crate::shared::worktree_compare_core::{self, WorktreeComparison};
+use crate::shared::worktree_lock_core::{self, WorktreeLock};
+use crate::shared::{release_core, workspace_rpc, workspaces_core};
 use crate::state::AppState;
-use crate::types::{WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus};
+use crate::storage::StoreWriteOptions;
+use crate::types::{
+    BranchNameSuggestion, ReleaseManifest, WorkspaceEntry, WorkspaceInfo, WorkspaceQuotaStatus,
+    WorkspaceSettings, WorktreeResult, WorktreeSetupStatus,
+};
 
 fn spawn_with_app(
     app: &AppHandle,
@@ -46,11 +72,18 @@ fn workspace_remote_empty_params() -> serde_json::Value {
 pub(crate) async fn read_workspace_file(
     workspace_id: String,
     path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<WorkspaceFileResponse, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let request = workspace_rpc::ReadWorkspaceFileRequest { workspace_id, path };
+        let request = workspace_rpc::ReadWorkspaceFileRequest {
+            workspace_id,
+            path,
+            offset,
+            length,
+        };
         let response = remote_backend::call_remote(
             &*state,
             app,
@@ -65,11 +98,147 @@ pub(crate) async fn read_workspace_file(
         &state.workspaces,
         &workspace_id,
         &path,
-        |root, rel_path| read_workspace_file_inner(root, rel_path),
+        |root, rel_path| read_workspace_file_inner(root, rel_path, offset.unwrap_or(0), length),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn write_workspace_file(
+    workspace_id: String,
+    path: String,
+    content: String,
+    expected_hash: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFileWriteResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WriteWorkspaceFileRequest {
+            workspace_id,
+            path,
+            content,
+            expected_hash,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "write_workspace_file",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_edit_core::write_workspace_file_core(
+        &state.workspaces,
+        &state.services.file_writes,
+        &workspace_id,
+        &path,
+        &content,
+        expected_hash.as_deref(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn apply_workspace_patch(
+    workspace_id: String,
+    path: String,
+    patch: String,
+    expected_hash: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFileWriteResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::ApplyWorkspacePatchRequest {
+            workspace_id,
+            path,
+            patch,
+            expected_hash,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "apply_workspace_patch",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_edit_core::apply_workspace_patch_core(
+        &state.workspaces,
+        &state.services.file_writes,
+        &workspace_id,
+        &path,
+        &patch,
+        expected_hash.as_deref(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn upload_file(
+    workspace_id: String,
+    relative_path: Option<String>,
+    file_name: Option<String>,
+    bytes_base64: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<UploadedFile, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::UploadFileRequest {
+            workspace_id,
+            relative_path,
+            file_name,
+            bytes_base64,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "upload_file",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_transfer_core::upload_file_core(
+        &state.workspaces,
+        &state.data_dir(),
+        &workspace_id,
+        relative_path.as_deref(),
+        file_name.as_deref(),
+        &bytes_base64,
     )
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn download_file(
+    workspace_id: String,
+    relative_path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DownloadedFile, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::DownloadFileRequest {
+            workspace_id,
+            relative_path,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "download_file",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_transfer_core::download_file_core(&state.workspaces, &workspace_id, &relative_path).await
+}
+
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
@@ -245,7 +414,7 @@ pub(crate) async fn add_worktree(
     copy_agents_md: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> Result<WorktreeResult, String> {
     let copy_agents_md = copy_agents_md.unwrap_or(true);
     if remote_backend::is_remote_mode(&*state).await {
         let request = workspace_rpc::AddWorktreeRequest {
@@ -277,16 +446,21 @@ pub(crate) async fn add_worktree(
         &data_dir,
         &state.workspaces,
         &state.sessions,
+        &state.services,
         &state.app_settings,
         &state.storage_path,
         |value| sanitize_worktree_name(value),
-        |root, name| Ok(unique_worktree_path(root, name)),
+        |root, name| unique_worktree_path(root, name),
         |root, branch| {
             let root = root.clone();
             let branch = branch.to_string();
             async move { git_branch_exists(&root, &branch).await }
         },
-        None::<fn(&PathBuf, &str) -> std::future::Ready<Result<Option<String>, String>>>,
+        Some(|root: &PathBuf, branch: &str| {
+            let root = root.clone();
+            let branch = branch.to_string();
+            async move { git_find_remote_tracking_branch(&root, &branch).await }
+        }),
         |root, args| {
             workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
                 run_git_command_owned(repo, args_owned)
@@ -299,6 +473,137 @@ pub(crate) async fn add_worktree(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn list_pending_operations(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PendingOperation>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_operations",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(state.services.operations.list())
+}
+
+/// Runs a failed operation again from the start with its original request.
+#[tauri::command]
+pub(crate) async fn retry_operation(
+    operation_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::OperationIdRequest { operation_id };
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "retry_operation",
+            workspace_remote_params(&request)?,
+        )
+        .await;
+    }
+
+    let operation = state.services.operations.take_failed(&operation_id)?;
+    match operation.kind.as_str() {
+        "add_worktree" => {
+            let request: workspace_rpc::AddWorktreeRequest =
+                serde_json::from_value(operation.params).map_err(|err| err.to_string())?;
+            let result = add_worktree(
+                request.parent_id,
+                request.branch,
+                request.name,
+                Some(request.copy_agents_md),
+                state,
+                app,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        kind => Err(format!("`{kind}` operations cannot be retried.")),
+    }
+}
+
+/// Starts live `workspace/filesChanged` events for the workspace.
+#[tauri::command]
+pub(crate) async fn watch_workspace_files(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::IdRequest { id };
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "watch_workspace_files",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_watch_core::watch_workspace_files_core(
+        &state.services,
+        &id,
+        &state.workspaces,
+        TauriEventSink::new(app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn unwatch_workspace_files(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::IdRequest { id };
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "unwatch_workspace_files",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_watch_core::unwatch_workspace_files_core(&state.services, &id);
+    Ok(())
+}
+
+/// The branch `add_worktree` would use for `base`, so the client can warn
+/// about a collision before creating anything.
+#[tauri::command]
+pub(crate) async fn suggest_branch_name(
+    workspace_id: String,
+    base: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BranchNameSuggestion, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::SuggestBranchNameRequest { workspace_id, base };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "suggest_branch_name",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::suggest_branch_name_core(&state.workspaces, workspace_id, base).await
+}
+
 #[tauri::command]
 pub(crate) async fn worktree_setup_status(
     workspace_id: String,
@@ -371,7 +676,9 @@ pub(crate) async fn remove_workspace(
         id,
         &state.workspaces,
         &state.sessions,
+        &state.services,
         &state.storage_path,
+        StoreWriteOptions::from_settings(&*state.app_settings.lock().await),
         |root, args| {
             workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
                 run_git_command_owned(repo, args_owned)
@@ -410,7 +717,9 @@ pub(crate) async fn remove_worktree(
         id,
         &state.workspaces,
         &state.sessions,
+        &state.services,
         &state.storage_path,
+        StoreWriteOptions::from_settings(&*state.app_settings.lock().await),
         |root, args| {
             workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
                 run_git_command_owned(repo, args_owned)
@@ -425,13 +734,34 @@ pub(crate) async fn remove_worktree(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn unlock_worktree(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<WorktreeLock>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::IdRequest { id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "unlock_worktree",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    worktree_lock_core::unlock_worktree_core(&state.workspaces, &id).await
+}
+
 #[tauri::command]
 pub(crate) async fn rename_worktree(
     id: String,
     branch: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> Result<WorktreeResult, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let request = workspace_rpc::RenameWorktreeRequest { id, branch };
         let response = remote_backend::call_remote(
@@ -549,6 +879,34 @@ pub(crate) async fn apply_worktree_changes(
     workspaces_core::apply_worktree_changes_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn compare_worktrees(
+    a: String,
+    b: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorktreeComparison, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::CompareWorktreesRequest { a, b };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "compare_worktrees",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    worktree_compare_core::compare_worktrees_core(
+        &state.workspaces,
+        &state.change_attributions,
+        a,
+        b,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn update_workspace_settings(
     id: String,
@@ -573,6 +931,7 @@ pub(crate) async fn update_workspace_settings(
         settings,
         &state.workspaces,
         &state.sessions,
+        &state.services,
         &state.app_settings,
         &state.storage_path,
         |workspaces, workspace_id, next_settings| {
@@ -585,6 +944,43 @@ pub(crate) async fn update_workspace_settings(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn update_workspaces_bulk(
+    ids: Vec<String>,
+    partial_settings: Value,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::UpdateWorkspacesBulkRequest {
+            ids,
+            partial_settings,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_workspaces_bulk",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::update_workspaces_bulk_core(
+        ids,
+        partial_settings,
+        &state.workspaces,
+        &state.sessions,
+        &state.services,
+        &state.app_settings,
+        &state.storage_path,
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn connect_workspace(
     id: String,
@@ -607,6 +1003,7 @@ pub(crate) async fn connect_workspace(
         id,
         &state.workspaces,
         &state.sessions,
+        &state.services,
         &state.app_settings,
         |entry, default_bin, codex_args, codex_home| {
             spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
@@ -616,33 +1013,435 @@ pub(crate) async fn connect_workspace(
 }
 
 #[tauri::command]
-pub(crate) async fn list_workspace_files(
-    workspace_id: String,
+pub(crate) async fn broadcast_run(
+    workspace_ids: Vec<String>,
+    text: String,
+    model: Option<String>,
+    worktree_branch: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Vec<String>, String> {
+) -> Result<BroadcastRun, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let request = workspace_rpc::BroadcastRunRequest {
+            workspace_ids,
+            text,
+            model,
+            worktree_branch,
+        };
         let response = remote_backend::call_remote(
             &*state,
             app,
-            "list_workspace_files",
+            "broadcast_run",
             workspace_remote_params(&request)?,
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    workspaces_core::list_workspace_files_core(&state.workspaces, &workspace_id, |root| {
-        list_workspace_files_inner(root, usize::MAX)
-    })
-    .await
+    let event_sink = TauriEventSink::new(app.clone());
+    let run = broadcast_core::broadcast_run_core(
+        &state.sessions,
+        &state.workspaces,
+        &event_sink,
+        workspace_ids,
+        text,
+        model,
+        worktree_branch,
+        |workspace_id| connect_workspace(workspace_id, app.state::<AppState>(), app.clone()),
+        |parent_id, branch| {
+            add_worktree(
+                parent_id,
+                branch,
+                None,
+                None,
+                app.state::<AppState>(),
+                app.clone(),
+            )
+        },
+    )
+    .await?;
+    if let Err(error) = state.broadcast_runs.record(&run) {
+        eprintln!("Failed to record broadcast run: {error}");
+    }
+    Ok(run)
 }
 
+/// Merges the chosen worktree of a broadcast run and cleans up the others.
 #[tauri::command]
-pub(crate) async fn open_workspace_in(
-    path: String,
-    app: Option<String>,
+pub(crate) async fn select_winner(
+    run_id: String,
+    worktree_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WinnerSelection, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::SelectWinnerRequest {
+            run_id,
+            worktree_id,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "select_winner",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let retention = state.app_settings.lock().await.losing_worktree_retention;
+    broadcast_core::select_winner_core(
+        &state.broadcast_runs,
+        &state.workspaces,
+        &state.thread_tags,
+        retention,
+        run_id,
+        worktree_id,
+        |workspace_id, thread_id| {
+            let app = app.clone();
+            async move {
+                connect_workspace(workspace_id.clone(), app.state::<AppState>(), app.clone())
+                    .await?;
+                codex::archive_thread(
+                    workspace_id,
+                    thread_id,
+                    app.state::<AppState>(),
+                    app.clone(),
+                )
+                .await
+                .map(|_| ())
+            }
+        },
+        |worktree_id| remove_worktree(worktree_id, app.state::<AppState>(), app.clone()),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn list_workspace_files(
+    workspace_id: String,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    let refresh = refresh.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::ListWorkspaceFilesRequest {
+            workspace_id,
+            refresh,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspace_files",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let (files, _) = file_index_core::cached_workspace_files_core(
+        &state.services,
+        &state.workspaces,
+        &workspace_id,
+        refresh,
+        |root| list_workspace_files_inner(root, usize::MAX),
+    )
+    .await?;
+    Ok(files.to_vec())
+}
+
+/// The immediate children of a workspace folder, for trees that expand
+/// lazily.
+#[tauri::command]
+pub(crate) async fn list_workspace_dir(
+    workspace_id: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceDirListing, String> {
+    let path = path.unwrap_or_default();
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::ListWorkspaceDirRequest { workspace_id, path };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspace_dir",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    dir_tree_core::list_workspace_dir_core(&state.workspaces, &workspace_id, &path).await
+}
+
+/// Expands dropped files and folders into file mentions for the next turn.
+#[tauri::command]
+pub(crate) async fn attach_context(
+    workspace_id: String,
+    paths: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ContextAttachment, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::AttachContextRequest {
+            workspace_id,
+            paths,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "attach_context",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    context_attach_core::attach_context_core(&state.workspaces, &workspace_id, paths).await
+}
+
+/// Records that the user switched to a workspace. In remote mode the
+/// daemon keeps the order, so it is shared by every connected device.
+#[tauri::command]
+pub(crate) async fn record_workspace_focus(
+    workspace_id: String,
+    device: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFocus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::RecordWorkspaceFocusRequest {
+            workspace_id,
+            device,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "record_workspace_focus",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_recency_core::record_workspace_focus_core(
+        &state.workspace_recency,
+        &state.workspaces,
+        &workspace_id,
+        device,
+    )
+    .await
+}
+
+/// Workspaces in quick-switcher order, most recently focused first.
+#[tauri::command]
+pub(crate) async fn recent_workspaces(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceFocus>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::RecentWorkspacesRequest { limit };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "recent_workspaces",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspace_recency_core::recent_workspaces_core(
+        &state.workspace_recency,
+        &state.workspaces,
+        limit,
+    )
+    .await)
+}
+
+/// A page of the cached file listing, for pickers that load lazily.
+#[tauri::command]
+pub(crate) async fn list_workspace_files_page(
+    workspace_id: String,
+    refresh: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFilePage, String> {
+    let refresh = refresh.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::ListWorkspaceFilesPageRequest {
+            workspace_id,
+            refresh,
+            offset,
+            limit,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspace_files_page",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_index_core::list_workspace_files_page_core(
+        &state.services,
+        &state.workspaces,
+        &workspace_id,
+        refresh,
+        offset,
+        limit,
+        |root| list_workspace_files_inner(root, usize::MAX),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn search_workspace_files(
+    workspace_id: String,
+    query: String,
+    regex: Option<bool>,
+    max_results: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FileSearchResult, String> {
+    let request = workspace_rpc::SearchWorkspaceFilesRequest {
+        workspace_id,
+        query,
+        regex: regex.unwrap_or(false),
+        max_results,
+    };
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "search_workspace_files",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_search_core::search_workspace_files_core(
+        &state.workspaces,
+        &request.workspace_id,
+        &request.query,
+        request.regex,
+        request.max_results,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn package_release(
+    workspace_id: String,
+    task: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ReleaseManifest, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::PackageReleaseRequest { workspace_id, task };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "package_release",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    release_core::package_release_core(&state.workspaces, workspace_id, task).await
+}
+
+/// Runs a configured launch script and returns its output formatted for the
+/// next user message.
+#[tauri::command]
+pub(crate) async fn run_and_attach(
+    workspace_id: String,
+    command: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<AttachedOutput, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::RunAndAttachRequest {
+            workspace_id,
+            command,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "run_and_attach",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    task_output_core::run_and_attach_core(&state.workspaces, workspace_id, command).await
+}
+
+/// README head, languages, build systems, recent commits and thread/turn
+/// counts for the workspace home screen.
+#[tauri::command]
+pub(crate) async fn workspace_summary(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceSummary, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_summary",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_summary_core::workspace_summary_core(
+        &state.services,
+        &state.workspaces,
+        &state.workspace_summaries,
+        workspace_id,
+    )
+    .await
+}
+
+/// Connection, turn, approval and git state of every workspace in one call.
+#[tauri::command]
+pub(crate) async fn dashboard_snapshot(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DashboardSnapshot, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "dashboard_snapshot", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    // Approvals reach the attached window directly, so none are queued here.
+    Ok(dashboard_core::dashboard_snapshot_core(
+        &state.services,
+        &state.workspaces,
+        &state.sessions,
+        &HashMap::new(),
+    )
+    .await)
+}
+
+#[tauri::command]
+pub(crate) async fn open_workspace_in(
+    path: String,
+    app: Option<String>,
     args: Vec<String>,
     command: Option<String>,
     line: Option<u32>,
@@ -666,3 +1465,55 @@ pub(crate) async fn get_open_app_icon(app_name: String) -> Result<Option<String>
         workspaces_core::get_open_app_icon_core(app_name, |_name| None).await
     }
 }
+
+#[tauri::command]
+pub(crate) async fn workspace_quota_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceQuotaStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_quota_status",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    quotas::workspace_quota_status_core(
+        &state.workspaces,
+        &state.services.session_quotas,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn resume_workspace_quota(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceQuotaStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "resume_workspace_quota",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    quotas::resume_workspace_quota_core(
+        &state.workspaces,
+        &state.services.session_quotas,
+        workspace_id,
+    )
+    .await
+}
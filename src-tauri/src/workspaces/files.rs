@@ -1,10 +1,8 @@
-use std::fs::File;
-use std::io::Read;
 use std::path::PathBuf;
 
 use ignore::WalkBuilder;
-use serde::{Deserialize, Serialize};
 
+use crate::files::io::{read_file_range_within, WorkspaceFileResponse};
 use crate::utils::normalize_git_path;
 
 fn should_skip_dir(name: &str) -> bool {
@@ -58,45 +56,11 @@ pub(crate) fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Ve
     results
 }
 
-const MAX_WORKSPACE_FILE_BYTES: u64 = 400_000;
-
-#[derive(Serialize, Deserialize, Clone)]
-pub(crate) struct WorkspaceFileResponse {
-    content: String,
-    truncated: bool,
-}
-
 pub(crate) fn read_workspace_file_inner(
     root: &PathBuf,
     relative_path: &str,
+    offset: u64,
+    length: Option<u64>,
 ) -> Result<WorkspaceFileResponse, String> {
-    let canonical_root = root
-        .canonicalize()
-        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-    let candidate = canonical_root.join(relative_path);
-    let canonical_path = candidate
-        .canonicalize()
-        .map_err(|err| format!("Failed to open file: {err}"))?;
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Invalid file path".to_string());
-    }
-    let metadata = std::fs::metadata(&canonical_path)
-        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
-    if !metadata.is_file() {
-        return Err("Path is not a file".to_string());
-    }
-
-    let file = File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
-    let mut buffer = Vec::new();
-    file.take(MAX_WORKSPACE_FILE_BYTES + 1)
-        .read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read file: {err}"))?;
-
-    let truncated = buffer.len() > MAX_WORKSPACE_FILE_BYTES as usize;
-    if truncated {
-        buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
-    }
-
-    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
-    Ok(WorkspaceFileResponse { content, truncated })
+    read_file_range_within(root, relative_path, offset, length)
 }
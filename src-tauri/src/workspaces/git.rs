@@ -36,6 +36,13 @@ pub(crate) async fn git_find_remote_for_branch(
     git_core::git_find_remote_for_branch_live(repo_path, branch).await
 }
 
+pub(crate) async fn git_find_remote_tracking_branch(
+    repo_path: &PathBuf,
+    branch: &str,
+) -> Result<Option<String>, String> {
+    git_core::git_find_remote_tracking_branch_local(repo_path, branch).await
+}
+
 pub(crate) async fn unique_branch_name(
     repo_path: &PathBuf,
     desired: &str,
@@ -9,16 +9,17 @@ use super::worktree::{
     unique_worktree_path_for_rename,
 };
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::services::BackendServices;
 use crate::shared::workspaces_core::{
-    remove_workspace_core, remove_worktree_core, rename_worktree_core,
+    remove_workspace_core, remove_worktree_core, rename_worktree_core, suggest_branch_name_core,
     update_workspace_settings_core,
 };
-use crate::storage::{read_workspaces, write_workspaces};
+use crate::storage::{read_workspaces, write_workspaces, StoreWriteOptions};
 use crate::types::{
     AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
 };
 use tokio::runtime::Runtime;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 fn workspace(name: &str, sort_order: Option<u32>) -> WorkspaceInfo {
@@ -59,6 +60,22 @@ fn workspace_with_id_and_kind(
             launch_scripts: None,
             worktree_setup_script: None,
             worktrees_folder: None,
+            agent_backend: None,
+            agent_command: None,
+            turn_concurrency: None,
+            merge_ready: false,
+            read_only: false,
+            merge_queue_test_command: None,
+            release_artifacts: None,
+            request_timeouts: None,
+            notifications_enabled: false,
+            default_access_mode: None,
+            default_model: None,
+            default_effort: None,
+            env: HashMap::new(),
+            quota: None,
+            color: None,
+            icon: None,
         },
     }
 }
@@ -245,7 +262,11 @@ fn update_workspace_settings_persists_sort_and_group() {
     std::fs::create_dir_all(&temp_dir).expect("create temp dir");
     let path = PathBuf::from(temp_dir.join("workspaces.json"));
     let list: Vec<_> = workspaces.values().cloned().collect();
-    write_workspaces(&path, &list).expect("write workspaces");
+    run_async(async {
+        write_workspaces(&path, &list, StoreWriteOptions::default())
+            .await
+            .expect("write workspaces");
+    });
 
     let read = read_workspaces(&path).expect("read workspaces");
     let stored = read.get(&id).expect("stored workspace");
@@ -296,11 +317,11 @@ fn rename_worktree_preserves_custom_name() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (worktree.id.clone(), worktree.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let app_settings = Mutex::new(AppSettings::default());
         let storage_path = temp_dir.join("workspaces.json");
 
@@ -327,14 +348,17 @@ fn rename_worktree_preserves_custom_name() {
         .await
         .expect("rename worktree");
 
-        assert_eq!(updated.name, "Custom label");
+        assert_eq!(updated.workspace.name, "Custom label");
         assert_eq!(
             updated
+                .workspace
                 .worktree
                 .as_ref()
                 .map(|worktree| worktree.branch.as_str()),
             Some("feature/new")
         );
+        assert_eq!(updated.requested_branch, "feature/new");
+        assert!(!updated.was_renamed);
     });
 }
 
@@ -367,11 +391,11 @@ fn rename_worktree_updates_name_when_unmodified() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (worktree.id.clone(), worktree.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let app_settings = Mutex::new(AppSettings::default());
         let storage_path = temp_dir.join("workspaces.json");
 
@@ -398,7 +422,7 @@ fn rename_worktree_updates_name_when_unmodified() {
         .await
         .expect("rename worktree");
 
-        assert_eq!(updated.name, "feature/new");
+        assert_eq!(updated.workspace.name, "feature/new");
     });
 }
 
@@ -436,11 +460,11 @@ fn rename_worktree_validates_worktree_root_before_branch_rename() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (worktree.id.clone(), worktree.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let app_settings = Mutex::new(AppSettings::default());
         let storage_path = temp_dir.join("workspaces.json");
 
@@ -478,7 +502,7 @@ fn rename_worktree_validates_worktree_root_before_branch_rename() {
         assert!(error.contains("Failed to create worktree directory"));
         assert!(calls.lock().expect("lock").is_empty());
 
-        let stored = workspaces.lock().await;
+        let stored = workspaces.read().await;
         let entry = stored.get(&worktree.id).expect("stored entry");
         assert_eq!(
             entry
@@ -509,8 +533,8 @@ fn update_workspace_settings_core_sanitizes_namespace_worktrees_folder() {
             worktree: None,
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([(id.clone(), entry)]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let workspaces = RwLock::new(HashMap::from([(id.clone(), entry)]));
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let app_settings = Mutex::new(AppSettings::default());
 
         let mut settings = WorkspaceSettings::default();
@@ -521,6 +545,7 @@ fn update_workspace_settings_core_sanitizes_namespace_worktrees_folder() {
             settings,
             &workspaces,
             &sessions,
+            &BackendServices::for_tests(),
             &app_settings,
             &storage_path,
             apply_workspace_settings_update,
@@ -573,11 +598,11 @@ fn rename_worktree_ignores_namespace_only_difference_in_worktree_root() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (worktree.id.clone(), worktree.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let app_settings = Mutex::new(AppSettings::default());
         let storage_path = temp_dir.join("workspaces.json");
         let calls: Arc<StdMutex<Vec<Vec<String>>>> = Arc::new(StdMutex::new(Vec::new()));
@@ -623,6 +648,58 @@ fn rename_worktree_ignores_namespace_only_difference_in_worktree_root() {
     });
 }
 
+#[test]
+fn suggest_branch_name_suffixes_only_checked_out_branches() {
+    run_async(async {
+        let repo_path = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = git2::Repository::init(&repo_path).expect("init repo");
+        let signature = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let tree_id = repo.index().expect("index").write_tree().expect("tree");
+        let tree = repo.find_tree(tree_id).expect("tree");
+        let oid = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .expect("commit");
+        repo.reference("refs/heads/feature", oid, true, "test")
+            .expect("branch");
+        let head = repo
+            .head()
+            .expect("head")
+            .shorthand()
+            .expect("head branch")
+            .to_string();
+
+        let entry = WorkspaceEntry {
+            id: "repo".to_string(),
+            name: "Repo".to_string(),
+            path: repo_path.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        let workspaces = RwLock::new(HashMap::from([(entry.id.clone(), entry)]));
+        let suggest = |base: &str| {
+            suggest_branch_name_core(&workspaces, "repo".to_string(), base.to_string())
+        };
+
+        let checked_out = suggest(&head).await.expect("suggest");
+        assert_eq!(checked_out.branch, format!("{head}-2"));
+        assert!(checked_out.was_renamed);
+        assert!(!checked_out.existing);
+
+        let existing = suggest(" feature ").await.expect("suggest");
+        assert_eq!(existing.branch, "feature");
+        assert!(!existing.was_renamed);
+        assert!(existing.existing);
+
+        let fresh = suggest("fresh").await.expect("suggest");
+        assert_eq!(fresh.branch, "fresh");
+        assert!(!fresh.was_renamed && !fresh.existing);
+
+        let _ = std::fs::remove_dir_all(&repo_path);
+    });
+}
+
 #[test]
 fn remove_workspace_succeeds_when_parent_repo_folder_is_missing() {
     run_async(async {
@@ -651,18 +728,20 @@ fn remove_workspace_succeeds_when_parent_repo_folder_is_missing() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (child.id.clone(), child.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let storage_path = temp_dir.join("workspaces.json");
 
         remove_workspace_core(
             parent.id.clone(),
             &workspaces,
             &sessions,
+            &BackendServices::for_tests(),
             &storage_path,
+            StoreWriteOptions::default(),
             |_root, _args| async move {
                 panic!("git should not run when parent repo folder is missing");
             },
@@ -675,7 +754,7 @@ fn remove_workspace_succeeds_when_parent_repo_folder_is_missing() {
         .expect("remove workspace");
 
         assert!(!child_path.exists());
-        let workspaces_guard = workspaces.lock().await;
+        let workspaces_guard = workspaces.read().await;
         assert!(workspaces_guard.is_empty());
     });
 }
@@ -708,18 +787,20 @@ fn remove_worktree_succeeds_when_parent_repo_folder_is_missing() {
             }),
             settings: WorkspaceSettings::default(),
         };
-        let workspaces = Mutex::new(HashMap::from([
+        let workspaces = RwLock::new(HashMap::from([
             (parent.id.clone(), parent.clone()),
             (child.id.clone(), child.clone()),
         ]));
-        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let sessions: RwLock<HashMap<String, Arc<WorkspaceSession>>> = RwLock::new(HashMap::new());
         let storage_path = temp_dir.join("workspaces.json");
 
         remove_worktree_core(
             child.id.clone(),
             &workspaces,
             &sessions,
+            &BackendServices::for_tests(),
             &storage_path,
+            StoreWriteOptions::default(),
             |_root, _args| async move {
                 panic!("git should not run when parent repo folder is missing");
             },
@@ -730,7 +811,7 @@ fn remove_worktree_succeeds_when_parent_repo_folder_is_missing() {
         .expect("remove worktree");
 
         assert!(!child_path.exists());
-        let workspaces_guard = workspaces.lock().await;
+        let workspaces_guard = workspaces.read().await;
         assert!(workspaces_guard.contains_key(&parent.id));
         assert!(!workspaces_guard.contains_key(&child.id));
     });
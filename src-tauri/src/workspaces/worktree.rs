@@ -11,8 +11,8 @@ pub(crate) fn sanitize_clone_dir_name(name: &str) -> String {
     worktree_core::sanitize_clone_dir_name(name)
 }
 
-pub(crate) fn unique_worktree_path(base_dir: &PathBuf, name: &str) -> PathBuf {
-    worktree_core::unique_worktree_path_best_effort(base_dir, name)
+pub(crate) fn unique_worktree_path(base_dir: &PathBuf, name: &str) -> Result<PathBuf, String> {
+    worktree_core::unique_worktree_path_strict(base_dir, name)
 }
 
 pub(crate) fn unique_worktree_path_for_rename(
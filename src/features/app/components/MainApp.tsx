@@ -63,7 +63,9 @@ import { useSystemNotificationThreadLinks } from "@app/hooks/useSystemNotificati
 import { useThreadListSortKey } from "@app/hooks/useThreadListSortKey";
 import { useThreadListActions } from "@app/hooks/useThreadListActions";
 import { useRemoteThreadLiveConnection } from "@app/hooks/useRemoteThreadLiveConnection";
+import { useServerClockSync } from "@app/hooks/useServerClockSync";
 import { useTrayRecentThreads } from "@app/hooks/useTrayRecentThreads";
+import { useStateSnapshotSync } from "@app/hooks/useStateSnapshot";
 import { useTraySessionUsage } from "@app/hooks/useTraySessionUsage";
 import { useTauriEvent } from "@app/hooks/useTauriEvent";
 import { useAppBootstrapOrchestration } from "@app/bootstrap/useAppBootstrapOrchestration";
@@ -265,6 +267,15 @@ export default function MainApp() {
     [workspacesById],
   );
 
+  // Opted-in workspaces are notified by the local backend; remote daemons
+  // deliver theirs through the notification webhook instead.
+  const isBackendNotifiedWorkspace = useCallback(
+    (workspaceId: string) =>
+      appSettings.backendMode === "local" &&
+      (workspacesById.get(workspaceId)?.settings.notificationsEnabled ?? false),
+    [appSettings.backendMode, workspacesById],
+  );
+
   const recordPendingThreadLinkRef = useRef<
     (workspaceId: string, threadId: string) => void
   >(() => {});
@@ -516,6 +527,7 @@ export default function MainApp() {
     threadSortKey: threadListSortKey,
     onThreadCodexMetadataDetected: handleThreadCodexMetadataDetected,
   });
+  useServerClockSync(appSettings.backendMode);
   const { connectionState: remoteThreadConnectionState, reconnectLive } =
     useRemoteThreadLiveConnection({
       backendMode: appSettings.backendMode,
@@ -555,6 +567,7 @@ export default function MainApp() {
       appSettings.subagentSystemNotificationsEnabled,
     isSubagentThread,
     getWorkspaceName,
+    isBackendNotifiedWorkspace,
     onThreadNotificationSent: (workspaceId, threadId) =>
       recordPendingThreadLinkRef.current(workspaceId, threadId),
     onDebug: addDebugEntry,
@@ -709,6 +722,7 @@ export default function MainApp() {
     approvals,
     userInputRequests,
     getWorkspaceName,
+    isBackendNotifiedWorkspace,
     onDebug: addDebugEntry,
   });
 
@@ -744,6 +758,11 @@ export default function MainApp() {
     threadsByWorkspace,
     isSubagentThread,
   });
+  useStateSnapshotSync({
+    workspaces,
+    threadsByWorkspace,
+    activeTurnIdByThread,
+  });
 
   useAutoExitEmptyDiff({
     centerMode,
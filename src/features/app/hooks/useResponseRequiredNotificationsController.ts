@@ -9,6 +9,7 @@ type Params = {
   approvals: ApprovalRequest[];
   userInputRequests: RequestUserInputRequest[];
   getWorkspaceName?: (workspaceId: string) => string | undefined;
+  isBackendNotifiedWorkspace?: (workspaceId: string) => boolean;
   onDebug?: (entry: DebugEntry) => void;
 };
 
@@ -19,6 +20,7 @@ export function useResponseRequiredNotificationsController({
   approvals,
   userInputRequests,
   getWorkspaceName,
+  isBackendNotifiedWorkspace,
   onDebug,
 }: Params) {
   const isWindowFocused = useWindowFocusState();
@@ -31,6 +33,7 @@ export function useResponseRequiredNotificationsController({
     approvals,
     userInputRequests,
     getWorkspaceName,
+    isBackendNotifiedWorkspace,
     onDebug,
   });
 }
@@ -0,0 +1,35 @@
+import { useEffect } from "react";
+import { getServerTime } from "@services/tauri";
+import { setServerClockOffset } from "@utils/time";
+
+/**
+ * Keeps relative times in line with the daemon's clock in remote mode by
+ * re-measuring the clock offset on mount and whenever the window regains
+ * focus (a phone's clock may have drifted or changed while it was asleep).
+ */
+export function useServerClockSync(backendMode: string) {
+  useEffect(() => {
+    if (backendMode !== "remote") {
+      setServerClockOffset(0);
+      return;
+    }
+    let canceled = false;
+    const sync = () => {
+      getServerTime()
+        .then((time) => {
+          if (!canceled) {
+            setServerClockOffset(time.offsetMs);
+          }
+        })
+        .catch(() => {
+          // Keep the last known offset while the daemon is unreachable.
+        });
+    };
+    sync();
+    window.addEventListener("focus", sync);
+    return () => {
+      canceled = true;
+      window.removeEventListener("focus", sync);
+    };
+  }, [backendMode]);
+}
@@ -0,0 +1,69 @@
+import { isTauri } from "@tauri-apps/api/core";
+import { useEffect, useMemo } from "react";
+import { restoreStateSnapshot, updateStateSnapshot } from "@services/tauri";
+import type { StateSnapshot, ThreadSummary, WorkspaceInfo } from "../../../types";
+
+const SYNC_DEBOUNCE_MS = 1000;
+
+let restoredSnapshot: Promise<StateSnapshot | null> | null = null;
+
+/**
+ * The state hibernated on the last quit, read once per launch. Callers use it
+ * to render before the first `list_workspaces` / `thread/list` round-trips.
+ */
+export function loadStateSnapshot(): Promise<StateSnapshot | null> {
+  if (!isTauri()) {
+    return Promise.resolve(null);
+  }
+  if (!restoredSnapshot) {
+    restoredSnapshot = restoreStateSnapshot().catch(() => null);
+  }
+  return restoredSnapshot;
+}
+
+type UseStateSnapshotSyncParams = {
+  workspaces: WorkspaceInfo[];
+  threadsByWorkspace: Record<string, ThreadSummary[]>;
+  activeTurnIdByThread: Record<string, string | null>;
+};
+
+export function buildStateSnapshot(
+  workspaces: WorkspaceInfo[],
+  threadsByWorkspace: Record<string, ThreadSummary[]>,
+  activeTurnIdByThread: Record<string, string | null>,
+): StateSnapshot {
+  const activeTurns: Record<string, Record<string, string>> = {};
+  Object.entries(threadsByWorkspace).forEach(([workspaceId, threads]) => {
+    threads.forEach((thread) => {
+      const turnId = activeTurnIdByThread[thread.id];
+      if (turnId) {
+        activeTurns[workspaceId] = { ...activeTurns[workspaceId], [thread.id]: turnId };
+      }
+    });
+  });
+  return { workspaces, threads: threadsByWorkspace, activeTurns };
+}
+
+/** Keeps the backend's copy of the state to hibernate on quit up to date. */
+export function useStateSnapshotSync({
+  workspaces,
+  threadsByWorkspace,
+  activeTurnIdByThread,
+}: UseStateSnapshotSyncParams) {
+  const snapshot = useMemo(
+    () => buildStateSnapshot(workspaces, threadsByWorkspace, activeTurnIdByThread),
+    [activeTurnIdByThread, threadsByWorkspace, workspaces],
+  );
+
+  useEffect(() => {
+    if (!isTauri() || snapshot.workspaces.length === 0) {
+      return;
+    }
+    const timeoutId = window.setTimeout(() => {
+      void updateStateSnapshot(snapshot).catch(() => {});
+    }, SYNC_DEBOUNCE_MS);
+    return () => {
+      window.clearTimeout(timeoutId);
+    };
+  }, [snapshot]);
+}
@@ -17,6 +17,7 @@ type Params = {
   subagentSystemNotificationsEnabled: boolean;
   isSubagentThread?: (workspaceId: string, threadId: string) => boolean;
   getWorkspaceName?: (workspaceId: string) => string | undefined;
+  isBackendNotifiedWorkspace?: (workspaceId: string) => boolean;
   onThreadNotificationSent?: (workspaceId: string, threadId: string) => void;
   onDebug: (entry: DebugEntry) => void;
   successSoundUrl: string;
@@ -87,6 +88,7 @@ export function useUpdaterController({
     isSubagentThread,
     isWindowFocused,
     getWorkspaceName,
+    isBackendNotifiedWorkspace,
     onThreadNotificationSent,
     onDebug,
   });
@@ -395,6 +395,12 @@ export function FileTreePanel({
         if (cancelled) {
           return;
         }
+        if (response.encoding === "base64") {
+          setPreviewContent("");
+          setPreviewTruncated(false);
+          setPreviewError("Binary file; no preview available.");
+          return;
+        }
         setPreviewContent(response.content ?? "");
         setPreviewTruncated(Boolean(response.truncated));
       })
@@ -51,6 +51,7 @@ type ResponseRequiredNotificationOptions = {
   subagentNotificationsEnabled?: boolean;
   isSubagentThread?: (workspaceId: string, threadId: string) => boolean;
   getWorkspaceName?: (workspaceId: string) => string | undefined;
+  isBackendNotifiedWorkspace?: (workspaceId: string) => boolean;
   onDebug?: (entry: DebugEntry) => void;
 };
 
@@ -68,6 +69,7 @@ export function useAgentResponseRequiredNotifications({
   subagentNotificationsEnabled = true,
   isSubagentThread,
   getWorkspaceName,
+  isBackendNotifiedWorkspace,
   onDebug,
 }: ResponseRequiredNotificationOptions) {
   const lastNotifiedAtRef = useRef(0);
@@ -196,6 +198,10 @@ export function useAgentResponseRequiredNotifications({
       if (notifiedApprovalsRef.current.has(key)) {
         continue;
       }
+      // The backend already notifies for workspaces that opted in.
+      if (isBackendNotifiedWorkspace?.(approval.workspace_id)) {
+        continue;
+      }
       const threadId = String(
         approval.params?.threadId ?? approval.params?.thread_id ?? "",
       ).trim();
@@ -13,6 +13,7 @@ type SystemNotificationOptions = {
   subagentNotificationsEnabled?: boolean;
   isSubagentThread?: (workspaceId: string, threadId: string) => boolean;
   getWorkspaceName?: (workspaceId: string) => string | undefined;
+  isBackendNotifiedWorkspace?: (workspaceId: string) => boolean;
   onThreadNotificationSent?: (workspaceId: string, threadId: string) => void;
   onDebug?: (entry: DebugEntry) => void;
 };
@@ -39,6 +40,7 @@ export function useAgentSystemNotifications({
   subagentNotificationsEnabled = true,
   isSubagentThread,
   getWorkspaceName,
+  isBackendNotifiedWorkspace,
   onThreadNotificationSent,
   onDebug,
 }: SystemNotificationOptions) {
@@ -127,6 +129,10 @@ export function useAgentSystemNotifications({
       if (!enabled) {
         return false;
       }
+      // The backend already notifies for workspaces that opted in.
+      if (isBackendNotifiedWorkspace?.(workspaceId)) {
+        return false;
+      }
       if (
         !subagentNotificationsEnabled &&
         isSubagentThread?.(workspaceId, threadId)
@@ -148,6 +154,7 @@ export function useAgentSystemNotifications({
     },
     [
       enabled,
+      isBackendNotifiedWorkspace,
       isSubagentThread,
       isWindowFocused,
       minDurationMs,
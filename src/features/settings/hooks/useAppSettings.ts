@@ -74,6 +74,8 @@ function normalizeRemoteBackends(settings: AppSettings): {
       provider: normalizeRemoteProvider(entry.provider),
       host: normalizeRemoteHost(entry.host),
       token: normalizeRemoteToken(entry.token),
+      tls: entry.tls === true,
+      tlsServerName: entry.tlsServerName?.trim() || null,
       lastConnectedAtMs:
         typeof entry.lastConnectedAtMs === "number" && Number.isFinite(entry.lastConnectedAtMs)
           ? entry.lastConnectedAtMs
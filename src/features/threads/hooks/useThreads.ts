@@ -10,6 +10,7 @@ import type {
 } from "@/types";
 import { CHAT_SCROLLBACK_DEFAULT } from "@utils/chatScrollback";
 import { useAppServerEvents } from "@app/hooks/useAppServerEvents";
+import { loadStateSnapshot } from "@app/hooks/useStateSnapshot";
 import { initialState, threadReducer } from "./useThreadsReducer";
 import { useThreadStorage } from "./useThreadStorage";
 import { useThreadLinking } from "./useThreadLinking";
@@ -26,6 +27,7 @@ import { useThreadTitleAutogeneration } from "./useThreadTitleAutogeneration";
 import { useDetachedReviewTracking } from "./useDetachedReviewTracking";
 import {
   archiveThread as archiveThreadService,
+  listPendingApprovals,
   readThread as readThreadService,
   setThreadName as setThreadNameService,
 } from "@services/tauri";
@@ -126,6 +128,30 @@ export function useThreads({
   threadsByWorkspaceRef.current = state.threadsByWorkspace;
   activeTurnIdByThreadRef.current = state.activeTurnIdByThread;
   threadParentByIdRef.current = state.threadParentById;
+  useEffect(() => {
+    let cancelled = false;
+    // Seed thread lists from the hibernated state; live `thread/list` results replace them.
+    void loadStateSnapshot().then((snapshot) => {
+      if (cancelled || !snapshot) {
+        return;
+      }
+      Object.entries(snapshot.threads).forEach(([workspaceId, threads]) => {
+        if (threadsByWorkspaceRef.current[workspaceId]) {
+          return;
+        }
+        dispatch({
+          type: "setThreads",
+          workspaceId,
+          threads,
+          sortKey: threadSortKey,
+          preserveAnchors: true,
+        });
+      });
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, [dispatch, threadSortKey]);
   const rateLimitsByWorkspaceRef = useRef(state.rateLimitsByWorkspace);
   rateLimitsByWorkspaceRef.current = state.rateLimitsByWorkspace;
   const { approvalAllowlistRef, handleApprovalDecision, handleApprovalRemember } =
@@ -250,13 +276,48 @@ export function useThreads({
       onSubagentThreadDetected,
     });
 
+  // Approvals that arrived while no client was connected are queued by the
+  // daemon; pull them back in whenever a workspace (re)connects.
+  const restorePendingApprovals = useCallback(
+    async (workspaceId?: string) => {
+      try {
+        const pending = await listPendingApprovals(workspaceId ?? null);
+        pending.forEach((approval) => {
+          dispatch({ type: "addApproval", approval });
+        });
+      } catch (error) {
+        onDebug?.({
+          id: `${Date.now()}-client-pending-approvals-error`,
+          timestamp: Date.now(),
+          source: "error",
+          label: "approvals/pending error",
+          payload: error instanceof Error ? error.message : String(error),
+        });
+      }
+    },
+    [onDebug],
+  );
+
+  const activeWorkspaceConnected = Boolean(activeWorkspace?.connected);
+  useEffect(() => {
+    if (activeWorkspaceConnected) {
+      void restorePendingApprovals();
+    }
+  }, [activeWorkspaceConnected, restorePendingApprovals]);
+
   const handleWorkspaceConnected = useCallback(
     (workspaceId: string) => {
       onWorkspaceConnected(workspaceId);
       void refreshAccountRateLimits(workspaceId);
       void refreshAccountInfo(workspaceId);
+      void restorePendingApprovals(workspaceId);
     },
-    [onWorkspaceConnected, refreshAccountRateLimits, refreshAccountInfo],
+    [
+      onWorkspaceConnected,
+      refreshAccountRateLimits,
+      refreshAccountInfo,
+      restorePendingApprovals,
+    ],
   );
 
   const handleAccountUpdated = useCallback(
@@ -1,7 +1,7 @@
 // @vitest-environment jsdom
 import { act, renderHook } from "@testing-library/react";
 import { beforeEach, describe, expect, it, vi } from "vitest";
-import type { WorkspaceInfo } from "../../../types";
+import type { WorkspaceInfo, WorktreeResult } from "../../../types";
 import {
   addWorkspace,
   addWorkspaceFromGitUrl,
@@ -73,8 +73,8 @@ describe("useWorkspaces.renameWorktree", () => {
     const renameWorktreeMock = vi.mocked(renameWorktree);
     listWorkspacesMock.mockResolvedValue([worktree]);
 
-    let resolveRename: (value: WorkspaceInfo) => void = () => {};
-    const renamePromise = new Promise<WorkspaceInfo>((resolve) => {
+    let resolveRename: (value: WorktreeResult) => void = () => {};
+    const renamePromise = new Promise<WorktreeResult>((resolve) => {
       resolveRename = resolve;
     });
     renameWorktreeMock.mockReturnValue(renamePromise);
@@ -102,6 +102,8 @@ describe("useWorkspaces.renameWorktree", () => {
       name: "feature/new",
       path: "/tmp/wt-1-renamed",
       worktree: { branch: "feature/new" },
+      requestedBranch: "feature/new",
+      wasRenamed: false,
     });
 
     await act(async () => {
@@ -116,7 +118,7 @@ describe("useWorkspaces.renameWorktree", () => {
     const renameWorktreeMock = vi.mocked(renameWorktree);
     listWorkspacesMock.mockResolvedValue([worktree]);
     let rejectRename: (error: Error) => void = () => {};
-    const renamePromise = new Promise<WorkspaceInfo>((_, reject) => {
+    const renamePromise = new Promise<WorktreeResult>((_, reject) => {
       rejectRename = reject;
     });
     renameWorktreeMock.mockReturnValue(renamePromise);
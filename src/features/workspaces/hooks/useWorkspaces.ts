@@ -18,6 +18,7 @@ import {
   useWorkspaceCrud,
   type AddWorkspacesFromPathsResult,
 } from "./useWorkspaceCrud";
+import { loadStateSnapshot } from "@app/hooks/useStateSnapshot";
 import { useWorkspaceGroupOps } from "./useWorkspaceGroupOps";
 import { useWorktreeOps } from "./useWorktreeOps";
 
@@ -99,7 +100,21 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}): UseWorkspaces
   });
 
   useEffect(() => {
-    void refreshWorkspaces();
+    let cancelled = false;
+    let refreshed = false;
+    // Show the hibernated list until the live one arrives.
+    void loadStateSnapshot().then((snapshot) => {
+      if (cancelled || refreshed || !snapshot || snapshot.workspaces.length === 0) {
+        return;
+      }
+      setWorkspaces(snapshot.workspaces);
+    });
+    void refreshWorkspaces().finally(() => {
+      refreshed = true;
+    });
+    return () => {
+      cancelled = true;
+    };
   }, [refreshWorkspaces]);
 
   useEffect(() => {
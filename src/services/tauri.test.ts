@@ -16,6 +16,7 @@ import {
   getGitLog,
   getGitStatus,
   getOpenAppIcon,
+  linkThreads,
   listThreads,
   listMcpServerStatus,
   readThread,
@@ -318,6 +319,21 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("maps thread link fields for link_threads", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ workspaceId: "ws-10", tags: [], links: [] });
+
+    await linkThreads("ws-10", "thread-2", "thread-1", "reviewOf");
+
+    expect(invokeMock).toHaveBeenCalledWith("link_threads", {
+      workspaceId: "ws-10",
+      threadId: "thread-2",
+      targetWorkspaceId: undefined,
+      targetThreadId: "thread-1",
+      kind: "reviewOf",
+    });
+  });
+
   it("maps workspaceId/threadId for read_thread", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
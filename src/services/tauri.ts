@@ -2,25 +2,106 @@ import { invoke } from "@tauri-apps/api/core";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import type { Options as NotificationOptions } from "@tauri-apps/plugin-notification";
 import type {
+  ActiveTurn,
   AppSettings,
+  ApprovalRequest,
+  ApprovalRule,
+  ApprovalRuleDecision,
+  ApprovalRuleKind,
+  AttachedOutput,
+  BackendMode,
+  BackendModeSwitch,
+  BranchNameSuggestion,
+  BroadcastRun,
+  Capabilities,
   CodexUpdateResult,
   CodexDoctorResult,
+  CodexConfigSettings,
+  CodexConfigStringKey,
+  CodexConfigValidation,
+  CodexLogoutResult,
+  ContextAttachment,
+  DaemonAutostartStatus,
+  DashboardSnapshot,
   DictationModelStatus,
   DictationSessionState,
+  DiscoveredDaemon,
+  DownloadedFile,
+  FileSearchResult,
+  HostStats,
   LocalUsageSnapshot,
+  McpServerConfig,
+  NetworkConnectivityReport,
+  NotificationEntry,
+  PendingArchive,
+  PendingOperation,
+  PreparedImage,
+  Playbook,
+  PlaybookDraft,
+  PlaybookRun,
+  ProtocolTraceStatus,
+  ProtocolTraceTail,
+  PushDevice,
+  PushPlatform,
+  ReleaseManifest,
+  RemoteBackendStatus,
+  RemoteProfile,
+  RemoteProfileDraft,
+  RateLimitForecast,
+  RunQueueSnapshot,
+  ScheduledMessage,
+  SavedPrompt,
+  ServerTime,
+  SessionProvenance,
+  SlashCommandInfo,
+  StateSnapshot,
+  StorageCategory,
+  StorageCleanupResult,
+  StorageUsageReport,
+  TcpDaemonLogs,
   TcpDaemonStatus,
+  TcpDaemonUpgradeResult,
+  TcpDaemonVersion,
+  TerminalOutputPage,
+  ThreadAnnotations,
+  ThreadExport,
+  ThreadExportFormat,
+  ThreadLinkKind,
+  ThreadOrder,
+  ThreadPreferences,
+  ThreadSearchHit,
+  ThreadTail,
   TailscaleDaemonCommandPreview,
+  TailscalePeer,
+  TailscaleServeStatus,
   TailscaleStatus,
   TrayRecentThreadEntry,
   TraySessionUsage,
+  UploadedFile,
+  UsageGroupBy,
+  UsageSummary,
+  WinnerSelection,
+  WorkspaceDirListing,
+  WorkspaceFileContent,
+  WorkspaceFilePage,
+  WorkspaceFileWriteResult,
+  WorkspaceFocus,
+  WorkspaceGitStatus,
   WorkspaceInfo,
+  WorkspaceQuotaStatus,
   AppMention,
   WorkspaceSettings,
+  WorkspaceSummary,
+  WorktreeLock,
+  WorktreeResult,
+  WorktreeComparison,
 } from "../types";
 import type {
+  GitAuthDoctorReport,
   GitFileDiff,
   GitFileStatus,
   GitCommitDiff,
+  GitConflictsResponse,
   GitHubIssuesResponse,
   GitHubPullRequestComment,
   GitHubPullRequestDiff,
@@ -119,6 +200,69 @@ export async function getCodexConfigPath(): Promise<string> {
   return invoke<string>("get_codex_config_path");
 }
 
+export async function getServerTime(): Promise<ServerTime> {
+  return invoke<ServerTime>("server_time");
+}
+
+export async function getCapabilities(): Promise<Capabilities> {
+  return invoke<Capabilities>("get_capabilities");
+}
+
+export async function hostStats(): Promise<HostStats> {
+  return invoke<HostStats>("host_stats");
+}
+
+export async function discoverDaemons(timeoutMs?: number): Promise<DiscoveredDaemon[]> {
+  return invoke<DiscoveredDaemon[]>("discover_daemons", { timeoutMs: timeoutMs ?? null });
+}
+
+export async function listRemoteProfiles(): Promise<RemoteProfile[]> {
+  return invoke<RemoteProfile[]>("remote_profile_list");
+}
+
+export async function addRemoteProfile(profile: RemoteProfileDraft): Promise<RemoteProfile[]> {
+  return invoke<RemoteProfile[]>("remote_profile_add", { profile });
+}
+
+export async function removeRemoteProfile(id: string): Promise<RemoteProfile[]> {
+  return invoke<RemoteProfile[]>("remote_profile_remove", { id });
+}
+
+export async function activateRemoteProfile(id: string): Promise<RemoteProfile[]> {
+  return invoke<RemoteProfile[]>("remote_profile_activate", { id });
+}
+
+export async function getRemoteBackendStatus(): Promise<RemoteBackendStatus> {
+  return invoke<RemoteBackendStatus>("remote_backend_status");
+}
+
+export async function switchBackendMode(
+  mode: BackendMode,
+  force?: boolean,
+): Promise<BackendModeSwitch> {
+  return invoke<BackendModeSwitch>("switch_backend_mode", { mode, force: force ?? null });
+}
+
+export async function testNetworkConnectivity(
+  urls?: string[],
+): Promise<NetworkConnectivityReport> {
+  return invoke<NetworkConnectivityReport>("test_network_connectivity", { urls });
+}
+
+export async function storageUsageReport(): Promise<StorageUsageReport> {
+  return invoke<StorageUsageReport>("storage_usage_report");
+}
+
+export async function storageCleanup(
+  category: StorageCategory,
+  olderThanDays?: number | null,
+): Promise<StorageCleanupResult> {
+  return invoke<StorageCleanupResult>("storage_cleanup", {
+    category,
+    olderThanDays: olderThanDays ?? null,
+  });
+}
+
 export type TextFileResponse = {
   exists: boolean;
   content: string;
@@ -198,6 +342,12 @@ export async function readImageAsDataUrl(path: string): Promise<string> {
   return invoke<string>("read_image_as_data_url", { path });
 }
 
+export async function prepareImageForSend(
+  source: string,
+): Promise<PreparedImage> {
+  return invoke<PreparedImage>("prepare_image_for_send", { source });
+}
+
 export async function readGlobalAgentsMd(): Promise<GlobalAgentsResponse> {
   return fileRead("global", "agents");
 }
@@ -296,8 +446,31 @@ export async function addWorktree(
   branch: string,
   name: string | null,
   copyAgentsMd = true,
-): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_worktree", { parentId, branch, name, copyAgentsMd });
+): Promise<WorktreeResult> {
+  return invoke<WorktreeResult>("add_worktree", { parentId, branch, name, copyAgentsMd });
+}
+
+export async function watchWorkspaceFiles(id: string): Promise<void> {
+  return invoke("watch_workspace_files", { id });
+}
+
+export async function unwatchWorkspaceFiles(id: string): Promise<void> {
+  return invoke("unwatch_workspace_files", { id });
+}
+
+export async function listPendingOperations(): Promise<PendingOperation[]> {
+  return invoke<PendingOperation[]>("list_pending_operations");
+}
+
+export async function retryOperation(operationId: string): Promise<unknown> {
+  return invoke("retry_operation", { operationId });
+}
+
+export async function suggestBranchName(
+  workspaceId: string,
+  base: string,
+): Promise<BranchNameSuggestion> {
+  return invoke<BranchNameSuggestion>("suggest_branch_name", { workspaceId, base });
 }
 
 export type WorktreeSetupStatus = {
@@ -322,6 +495,13 @@ export async function updateWorkspaceSettings(
   return invoke<WorkspaceInfo>("update_workspace_settings", { id, settings });
 }
 
+export async function updateWorkspacesBulk(
+  ids: string[],
+  partialSettings: Partial<WorkspaceSettings>,
+): Promise<WorkspaceInfo[]> {
+  return invoke<WorkspaceInfo[]>("update_workspaces_bulk", { ids, partialSettings });
+}
+
 export async function removeWorkspace(id: string): Promise<void> {
   return invoke("remove_workspace", { id });
 }
@@ -330,11 +510,15 @@ export async function removeWorktree(id: string): Promise<void> {
   return invoke("remove_worktree", { id });
 }
 
+export async function unlockWorktree(id: string) {
+  return invoke<WorktreeLock | null>("unlock_worktree", { id });
+}
+
 export async function renameWorktree(
   id: string,
   branch: string,
-): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("rename_worktree", { id, branch });
+): Promise<WorktreeResult> {
+  return invoke<WorktreeResult>("rename_worktree", { id, branch });
 }
 
 export async function renameWorktreeUpstream(
@@ -349,6 +533,13 @@ export async function applyWorktreeChanges(workspaceId: string): Promise<void> {
   return invoke("apply_worktree_changes", { workspaceId });
 }
 
+export async function compareWorktrees(
+  a: string,
+  b: string,
+): Promise<WorktreeComparison> {
+  return invoke<WorktreeComparison>("compare_worktrees", { a, b });
+}
+
 export async function openWorkspaceIn(
   path: string,
   options: {
@@ -377,6 +568,27 @@ export async function connectWorkspace(id: string): Promise<void> {
   return invoke("connect_workspace", { id });
 }
 
+export async function broadcastRun(
+  workspaceIds: string[],
+  text: string,
+  model: string | null,
+  worktreeBranch: string | null = null,
+): Promise<BroadcastRun> {
+  return invoke<BroadcastRun>("broadcast_run", {
+    workspaceIds,
+    text,
+    model,
+    worktreeBranch,
+  });
+}
+
+export async function selectWinner(
+  runId: string,
+  worktreeId: string,
+): Promise<WinnerSelection> {
+  return invoke<WinnerSelection>("select_winner", { runId, worktreeId });
+}
+
 export async function setWorkspaceRuntimeCodexArgs(
   workspaceId: string,
   codexArgs: string | null,
@@ -489,6 +701,44 @@ export async function interruptTurn(
   return invoke("turn_interrupt", { workspaceId, threadId, turnId });
 }
 
+export async function listSlashCommands(): Promise<SlashCommandInfo[]> {
+  return invoke<SlashCommandInfo[]>("list_slash_commands");
+}
+
+export async function listRunQueue(): Promise<RunQueueSnapshot> {
+  return invoke<RunQueueSnapshot>("queue_list");
+}
+
+export async function cancelQueuedRun(queueId: string): Promise<void> {
+  return invoke<void>("queue_cancel", { queueId });
+}
+
+export async function scheduleMessage(
+  workspaceId: string,
+  threadId: string,
+  text: string,
+  sendAt: number,
+): Promise<ScheduledMessage> {
+  return invoke<ScheduledMessage>("schedule_message", {
+    workspaceId,
+    threadId,
+    text,
+    sendAt,
+  });
+}
+
+export async function listScheduledMessages(
+  workspaceId?: string | null,
+): Promise<ScheduledMessage[]> {
+  return invoke<ScheduledMessage[]>("list_scheduled_messages", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function cancelScheduledMessage(scheduleId: string): Promise<void> {
+  return invoke<void>("cancel_scheduled_message", { scheduleId });
+}
+
 export async function steerTurn(
   workspaceId: string,
   threadId: string,
@@ -555,6 +805,38 @@ export async function rememberApprovalRule(
   return invoke("remember_approval_rule", { workspaceId, command });
 }
 
+export async function addApprovalRule(
+  workspaceId: string,
+  rule: {
+    kind: ApprovalRuleKind;
+    pattern: string;
+    decision: ApprovalRuleDecision;
+    scoped?: boolean;
+    expiresAt?: number | null;
+  },
+): Promise<ApprovalRule> {
+  return invoke<ApprovalRule>("add_approval_rule", {
+    workspaceId,
+    kind: rule.kind,
+    pattern: rule.pattern,
+    decision: rule.decision,
+    scoped: rule.scoped ?? false,
+    expiresAt: rule.expiresAt ?? null,
+  });
+}
+
+export async function listApprovalRules(
+  workspaceId?: string | null,
+): Promise<ApprovalRule[]> {
+  return invoke<ApprovalRule[]>("list_approval_rules", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function deleteApprovalRule(ruleId: string): Promise<void> {
+  return invoke("delete_approval_rule", { ruleId });
+}
+
 export async function getGitStatus(workspace_id: string): Promise<{
   branchName: string;
   files: GitFileStatus[];
@@ -566,6 +848,12 @@ export async function getGitStatus(workspace_id: string): Promise<{
   return invoke("get_git_status", { workspaceId: workspace_id });
 }
 
+export async function getGitStatusAll(refresh?: boolean) {
+  return invoke<WorkspaceGitStatus[]>("git_status_all", {
+    refresh: refresh ?? null,
+  });
+}
+
 export type InitGitRepoResponse =
   | { status: "initialized"; commitError?: string }
   | { status: "already_initialized" }
@@ -634,6 +922,12 @@ export async function getGitRemote(workspace_id: string): Promise<string | null>
   return invoke("get_git_remote", { workspaceId: workspace_id });
 }
 
+export async function gitAuthDoctor(
+  workspaceId: string,
+): Promise<GitAuthDoctorReport> {
+  return invoke<GitAuthDoctorReport>("git_auth_doctor", { workspaceId });
+}
+
 export async function stageGitFile(workspaceId: string, path: string) {
   return invoke("stage_git_file", { workspaceId, path });
 }
@@ -730,6 +1024,16 @@ export async function localUsageSnapshot(
   return invoke("local_usage_snapshot", payload);
 }
 
+export async function usageSummary(
+  since?: number | null,
+  groupBy?: UsageGroupBy,
+): Promise<UsageSummary> {
+  return invoke<UsageSummary>("usage_summary", {
+    since: since ?? null,
+    groupBy: groupBy ?? null,
+  });
+}
+
 export async function getModelList(workspaceId: string) {
   return invoke<any>("model_list", { workspaceId });
 }
@@ -749,6 +1053,28 @@ export async function setCodexFeatureFlag(
   return invoke("set_codex_feature_flag", { featureKey, enabled });
 }
 
+export async function getCodexConfig() {
+  return invoke<CodexConfigSettings>("get_codex_config");
+}
+
+export async function setCodexConfigValue(
+  key: CodexConfigStringKey,
+  value: string | null,
+): Promise<void> {
+  return invoke("set_codex_config_value", { key, value });
+}
+
+export async function setCodexMcpServer(
+  name: string,
+  server: McpServerConfig | null,
+): Promise<void> {
+  return invoke("set_codex_mcp_server", { name, server });
+}
+
+export async function validateCodexConfig() {
+  return invoke<CodexConfigValidation>("validate_codex_config");
+}
+
 export async function generateRunMetadata(workspaceId: string, prompt: string) {
   return invoke<{ title: string; worktreeName: string }>("generate_run_metadata", {
     workspaceId,
@@ -764,6 +1090,10 @@ export async function getAccountRateLimits(workspaceId: string) {
   return invoke<any>("account_rate_limits", { workspaceId });
 }
 
+export async function getRateLimitForecast(): Promise<RateLimitForecast> {
+  return invoke<RateLimitForecast>("rate_limit_forecast");
+}
+
 export async function getAccountInfo(workspaceId: string) {
   return invoke<any>("account_read", { workspaceId });
 }
@@ -774,6 +1104,14 @@ export async function runCodexLogin(workspaceId: string) {
   });
 }
 
+export async function loginCodexWithApiKey(workspaceId: string, apiKey: string) {
+  return invoke<{
+    type: "apiKey";
+    storedBy: "appServer" | "authFile";
+    authPath?: string;
+  }>("codex_login_api_key", { workspaceId, apiKey });
+}
+
 export async function cancelCodexLogin(workspaceId: string) {
   return invoke<{ canceled: boolean; status?: string; raw?: unknown }>(
     "codex_login_cancel",
@@ -781,6 +1119,19 @@ export async function cancelCodexLogin(workspaceId: string) {
   );
 }
 
+export async function logoutCodex(workspaceId: string) {
+  return invoke<CodexLogoutResult>("codex_logout", { workspaceId });
+}
+
+export async function switchCodexAccount(workspaceId: string, apiKey?: string) {
+  return invoke<{
+    logout: CodexLogoutResult;
+    login:
+      | { loginId: string; authUrl: string; raw?: unknown }
+      | { type: "apiKey"; storedBy: "appServer" | "authFile"; authPath?: string };
+  }>("account_switch", { workspaceId, apiKey: apiKey ?? null });
+}
+
 export async function getSkillsList(workspaceId: string) {
   return invoke<any>("skills_list", { workspaceId });
 }
@@ -861,6 +1212,64 @@ export async function movePrompt(
   });
 }
 
+export async function listLibraryPrompts(): Promise<SavedPrompt[]> {
+  return invoke<SavedPrompt[]>("prompt_list");
+}
+
+export async function saveLibraryPrompt(data: {
+  id?: string | null;
+  name: string;
+  description?: string | null;
+  template: string;
+}): Promise<SavedPrompt> {
+  return invoke<SavedPrompt>("prompt_save", {
+    id: data.id ?? null,
+    name: data.name,
+    description: data.description ?? null,
+    template: data.template,
+  });
+}
+
+export async function deleteLibraryPrompt(id: string): Promise<void> {
+  return invoke("prompt_delete", { id });
+}
+
+export async function renderLibraryPrompt(
+  id: string,
+  workspaceId: string,
+  values?: Record<string, string>,
+): Promise<string> {
+  return invoke<string>("prompt_render", {
+    id,
+    workspaceId,
+    values: values ?? null,
+  });
+}
+
+export async function listPlaybooks(): Promise<Playbook[]> {
+  return invoke<Playbook[]>("playbook_list");
+}
+
+export async function savePlaybook(playbook: PlaybookDraft): Promise<Playbook> {
+  return invoke<Playbook>("playbook_save", { playbook });
+}
+
+export async function deletePlaybook(id: string): Promise<void> {
+  return invoke<void>("playbook_delete", { id });
+}
+
+export async function runPlaybook(
+  workspaceId: string,
+  playbookId: string,
+  variables?: Record<string, string>,
+): Promise<PlaybookRun> {
+  return invoke<PlaybookRun>("run_playbook", {
+    workspaceId,
+    playbookId,
+    variables: variables ?? null,
+  });
+}
+
 export async function getAppSettings(): Promise<AppSettings> {
   return invoke<AppSettings>("get_app_settings");
 }
@@ -877,6 +1286,10 @@ export async function tailscaleStatus(): Promise<TailscaleStatus> {
   return invoke<TailscaleStatus>("tailscale_status");
 }
 
+export async function tailscaleListPeers(): Promise<TailscalePeer[]> {
+  return invoke<TailscalePeer[]>("tailscale_list_peers");
+}
+
 export async function tailscaleDaemonCommandPreview(): Promise<TailscaleDaemonCommandPreview> {
   return invoke<TailscaleDaemonCommandPreview>("tailscale_daemon_command_preview");
 }
@@ -893,6 +1306,50 @@ export async function tailscaleDaemonStatus(): Promise<TcpDaemonStatus> {
   return invoke<TcpDaemonStatus>("tailscale_daemon_status");
 }
 
+export async function tailscaleDaemonVersion(): Promise<TcpDaemonVersion> {
+  return invoke<TcpDaemonVersion>("tailscale_daemon_version");
+}
+
+export async function tailscaleDaemonUpgrade(): Promise<TcpDaemonUpgradeResult> {
+  return invoke<TcpDaemonUpgradeResult>("tailscale_daemon_upgrade");
+}
+
+export async function tailscaleDaemonLogs(lines?: number | null): Promise<TcpDaemonLogs> {
+  return invoke<TcpDaemonLogs>("tailscale_daemon_logs", { lines: lines ?? null });
+}
+
+export async function tailscaleServeEnable(options?: {
+  port?: number | null;
+  funnel?: boolean;
+  servePort?: number | null;
+}): Promise<TailscaleServeStatus> {
+  return invoke<TailscaleServeStatus>("tailscale_serve_enable", {
+    port: options?.port ?? null,
+    funnel: options?.funnel ?? false,
+    servePort: options?.servePort ?? null,
+  });
+}
+
+export async function tailscaleServeDisable(
+  port?: number | null,
+): Promise<TailscaleServeStatus> {
+  return invoke<TailscaleServeStatus>("tailscale_serve_disable", {
+    port: port ?? null,
+  });
+}
+
+export async function daemonInstallAutostart(): Promise<DaemonAutostartStatus> {
+  return invoke<DaemonAutostartStatus>("daemon_install_autostart");
+}
+
+export async function daemonUninstallAutostart(): Promise<DaemonAutostartStatus> {
+  return invoke<DaemonAutostartStatus>("daemon_uninstall_autostart");
+}
+
+export async function daemonAutostartStatus(): Promise<DaemonAutostartStatus> {
+  return invoke<DaemonAutostartStatus>("daemon_autostart_status");
+}
+
 type MenuAcceleratorUpdate = {
   id: string;
   accelerator: string | null;
@@ -918,20 +1375,160 @@ export async function runCodexUpdate(
   return invoke<CodexUpdateResult>("codex_update", { codexBin, codexArgs });
 }
 
-export async function getWorkspaceFiles(workspaceId: string) {
-  return invoke<string[]>("list_workspace_files", { workspaceId });
+export async function getWorkspaceFiles(
+  workspaceId: string,
+  options?: { refresh?: boolean },
+) {
+  return invoke<string[]>("list_workspace_files", {
+    workspaceId,
+    refresh: options?.refresh ?? null,
+  });
+}
+
+export async function listWorkspaceDir(workspaceId: string, path?: string) {
+  return invoke<WorkspaceDirListing>("list_workspace_dir", {
+    workspaceId,
+    path: path ?? null,
+  });
+}
+
+export async function attachContext(workspaceId: string, paths: string[]) {
+  return invoke<ContextAttachment>("attach_context", { workspaceId, paths });
+}
+
+export async function recordWorkspaceFocus(workspaceId: string, device?: string) {
+  return invoke<WorkspaceFocus>("record_workspace_focus", {
+    workspaceId,
+    device: device ?? null,
+  });
+}
+
+export async function getRecentWorkspaces(limit?: number) {
+  return invoke<WorkspaceFocus[]>("recent_workspaces", { limit: limit ?? null });
+}
+
+export async function getWorkspaceFilesPage(
+  workspaceId: string,
+  options?: { refresh?: boolean; offset?: number; limit?: number },
+) {
+  return invoke<WorkspaceFilePage>("list_workspace_files_page", {
+    workspaceId,
+    refresh: options?.refresh ?? null,
+    offset: options?.offset ?? null,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function searchWorkspaceFiles(
+  workspaceId: string,
+  query: string,
+  options: { regex?: boolean; maxResults?: number } = {},
+): Promise<FileSearchResult> {
+  return invoke<FileSearchResult>("search_workspace_files", {
+    workspaceId,
+    query,
+    regex: options.regex ?? false,
+    maxResults: options.maxResults ?? null,
+  });
 }
 
 export async function readWorkspaceFile(
   workspaceId: string,
   path: string,
-): Promise<{ content: string; truncated: boolean }> {
-  return invoke<{ content: string; truncated: boolean }>("read_workspace_file", {
+  options?: { offset?: number; length?: number },
+): Promise<WorkspaceFileContent> {
+  return invoke<WorkspaceFileContent>("read_workspace_file", {
+    workspaceId,
+    path,
+    offset: options?.offset ?? null,
+    length: options?.length ?? null,
+  });
+}
+
+export async function writeWorkspaceFile(
+  workspaceId: string,
+  path: string,
+  content: string,
+  expectedHash?: string | null,
+): Promise<WorkspaceFileWriteResult> {
+  return invoke<WorkspaceFileWriteResult>("write_workspace_file", {
+    workspaceId,
+    path,
+    content,
+    expectedHash: expectedHash ?? null,
+  });
+}
+
+export async function applyWorkspacePatch(
+  workspaceId: string,
+  path: string,
+  patch: string,
+  expectedHash?: string | null,
+): Promise<WorkspaceFileWriteResult> {
+  return invoke<WorkspaceFileWriteResult>("apply_workspace_patch", {
     workspaceId,
     path,
+    patch,
+    expectedHash: expectedHash ?? null,
   });
 }
 
+export async function uploadFile(
+  workspaceId: string,
+  bytesBase64: string,
+  options?: { relativePath?: string | null; fileName?: string | null },
+): Promise<UploadedFile> {
+  return invoke<UploadedFile>("upload_file", {
+    workspaceId,
+    relativePath: options?.relativePath ?? null,
+    fileName: options?.fileName ?? null,
+    bytesBase64,
+  });
+}
+
+export async function downloadFile(
+  workspaceId: string,
+  relativePath: string,
+): Promise<DownloadedFile> {
+  return invoke<DownloadedFile>("download_file", { workspaceId, relativePath });
+}
+
+export async function packageRelease(
+  workspaceId: string,
+  task: string,
+): Promise<ReleaseManifest> {
+  return invoke<ReleaseManifest>("package_release", { workspaceId, task });
+}
+
+export async function runAndAttach(
+  workspaceId: string,
+  command: string,
+): Promise<AttachedOutput> {
+  return invoke<AttachedOutput>("run_and_attach", { workspaceId, command });
+}
+
+export async function getWorkspaceSummary(
+  workspaceId: string,
+): Promise<WorkspaceSummary> {
+  return invoke<WorkspaceSummary>("workspace_summary", { workspaceId });
+}
+
+export async function getDashboardSnapshot(): Promise<DashboardSnapshot> {
+  return invoke<DashboardSnapshot>("dashboard_snapshot");
+}
+
+export async function getWorkspaceQuotaStatus(
+  workspaceId: string,
+): Promise<WorkspaceQuotaStatus> {
+  return invoke<WorkspaceQuotaStatus>("workspace_quota_status", { workspaceId });
+}
+
+export async function resumeWorkspaceQuota(
+  workspaceId: string,
+): Promise<WorkspaceQuotaStatus> {
+  return invoke<WorkspaceQuotaStatus>("resume_workspace_quota", { workspaceId });
+}
+
 export async function readAgentMd(workspaceId: string): Promise<AgentMdResponse> {
   return fileRead("workspace", "agents", workspaceId);
 }
@@ -952,6 +1549,40 @@ export async function createGitBranch(workspaceId: string, name: string) {
   return invoke("create_git_branch", { workspaceId, name });
 }
 
+export async function deleteGitBranch(
+  workspaceId: string,
+  name: string,
+  force?: boolean,
+) {
+  return invoke("delete_git_branch", { workspaceId, name, force: force ?? null });
+}
+
+export async function saveGitStash(workspaceId: string, message?: string | null) {
+  return invoke("save_git_stash", { workspaceId, message: message ?? null });
+}
+
+export async function listGitStashes(workspaceId: string) {
+  return invoke<any>("list_git_stashes", { workspaceId });
+}
+
+export async function popGitStash(workspaceId: string, index?: number | null) {
+  return invoke("pop_git_stash", { workspaceId, index: index ?? null });
+}
+
+export async function getGitConflicts(
+  workspaceId: string,
+): Promise<GitConflictsResponse> {
+  return invoke("get_git_conflicts", { workspaceId });
+}
+
+export async function resolveGitConflict(
+  workspaceId: string,
+  path: string,
+  content: string,
+) {
+  return invoke("resolve_git_conflict", { workspaceId, path, content });
+}
+
 function withModelId(modelId?: string | null) {
   return modelId ? { modelId } : {};
 }
@@ -1043,13 +1674,189 @@ export async function closeTerminalSession(
   return invoke("terminal_close", { workspaceId, terminalId });
 }
 
+export async function terminalOutputPage(
+  workspaceId: string,
+  terminalId: string,
+  cursor?: number | null,
+  maxBytes?: number | null,
+): Promise<TerminalOutputPage> {
+  return invoke<TerminalOutputPage>("terminal_output_page", {
+    workspaceId,
+    terminalId,
+    cursor: cursor ?? null,
+    maxBytes: maxBytes ?? null,
+  });
+}
+
 export async function listThreads(
   workspaceId: string,
   cursor?: string | null,
   limit?: number | null,
   sortKey?: "created_at" | "updated_at" | null,
+  tag?: string | null,
 ) {
-  return invoke<any>("list_threads", { workspaceId, cursor, limit, sortKey });
+  return invoke<any>("list_threads", {
+    workspaceId,
+    cursor,
+    limit,
+    sortKey,
+    tag,
+  });
+}
+
+export async function tagThread(
+  workspaceId: string,
+  threadId: string,
+  tags: string[],
+): Promise<ThreadAnnotations> {
+  return invoke<ThreadAnnotations>("tag_thread", { workspaceId, threadId, tags });
+}
+
+export async function linkThreads(
+  workspaceId: string,
+  threadId: string,
+  targetThreadId: string,
+  kind: ThreadLinkKind,
+  targetWorkspaceId?: string | null,
+): Promise<ThreadAnnotations> {
+  return invoke<ThreadAnnotations>("link_threads", {
+    workspaceId,
+    threadId,
+    targetWorkspaceId,
+    targetThreadId,
+    kind,
+  });
+}
+
+export async function unlinkThreads(
+  threadId: string,
+  targetThreadId: string,
+  kind?: ThreadLinkKind | null,
+): Promise<void> {
+  return invoke("unlink_threads", { threadId, targetThreadId, kind });
+}
+
+export async function pinThread(
+  workspaceId: string,
+  threadId: string,
+  pinned: boolean,
+): Promise<ThreadOrder> {
+  return invoke<ThreadOrder>("pin_thread", { workspaceId, threadId, pinned });
+}
+
+export async function setThreadOrder(
+  workspaceId: string,
+  threadIds: string[],
+): Promise<ThreadOrder> {
+  return invoke<ThreadOrder>("set_thread_order", { workspaceId, threadIds });
+}
+
+export async function setThreadMeta(
+  threadId: string,
+  key: string,
+  value: unknown,
+): Promise<Record<string, unknown>> {
+  return invoke<Record<string, unknown>>("set_thread_meta", {
+    threadId,
+    key,
+    value: value ?? null,
+  });
+}
+
+export async function getThreadMeta(
+  threadId: string,
+): Promise<Record<string, unknown>> {
+  return invoke<Record<string, unknown>>("get_thread_meta", { threadId });
+}
+
+export async function getThreadPreferences(
+  threadId: string,
+  workspaceId?: string | null,
+): Promise<ThreadPreferences> {
+  return invoke<ThreadPreferences>("get_thread_preferences", {
+    threadId,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function setThreadPreferences(
+  threadId: string,
+  preferences: {
+    workspaceId?: string | null;
+    model?: string | null;
+    effort?: string | null;
+  },
+): Promise<ThreadPreferences> {
+  return invoke<ThreadPreferences>("set_thread_preferences", {
+    threadId,
+    workspaceId: preferences.workspaceId ?? null,
+    model: preferences.model ?? null,
+    effort: preferences.effort ?? null,
+  });
+}
+
+export async function getSessionProvenance(
+  workspaceId: string,
+): Promise<SessionProvenance[]> {
+  return invoke<SessionProvenance[]>("session_provenance", { workspaceId });
+}
+
+export async function listActiveTurns(
+  workspaceId?: string | null,
+): Promise<ActiveTurn[]> {
+  return invoke<ActiveTurn[]>("list_active_turns", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function listNotifications(options?: {
+  unreadOnly?: boolean;
+  limit?: number | null;
+}): Promise<NotificationEntry[]> {
+  return invoke<NotificationEntry[]>("list_notifications", {
+    unreadOnly: options?.unreadOnly ?? false,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function markNotificationsRead(ids?: string[] | null): Promise<number> {
+  return invoke<number>("mark_notifications_read", { ids: ids ?? null });
+}
+
+export async function pushRegisterDevice(
+  deviceToken: string,
+  platform: PushPlatform,
+  appId?: string | null,
+  name?: string | null,
+): Promise<PushDevice> {
+  return invoke<PushDevice>("push_register_device", {
+    deviceToken,
+    platform,
+    appId: appId ?? null,
+    name: name ?? null,
+  });
+}
+
+export async function pushUnregisterDevice(deviceToken: string): Promise<boolean> {
+  return invoke<boolean>("push_unregister_device", { deviceToken });
+}
+
+export async function pushListDevices(): Promise<PushDevice[]> {
+  return invoke<PushDevice[]>("push_list_devices");
+}
+
+export async function listPendingApprovals(
+  workspaceId?: string | null,
+): Promise<ApprovalRequest[]> {
+  return invoke<ApprovalRequest[]>("list_pending_approvals", { workspaceId });
+}
+
+export async function listThreadAnnotations(
+  workspaceId: string,
+): Promise<Record<string, ThreadAnnotations>> {
+  return invoke<Record<string, ThreadAnnotations>>("list_thread_annotations", {
+    workspaceId,
+  });
 }
 
 export async function listMcpServerStatus(
@@ -1068,6 +1875,32 @@ export async function readThread(workspaceId: string, threadId: string) {
   return invoke<any>("read_thread", { workspaceId, threadId });
 }
 
+export async function exportThread(
+  workspaceId: string,
+  threadId: string,
+  format: ThreadExportFormat,
+  path?: string | null,
+) {
+  return invoke<ThreadExport>("export_thread", {
+    workspaceId,
+    threadId,
+    format,
+    path: path ?? null,
+  });
+}
+
+export async function searchThreads(
+  query: string,
+  workspaceId?: string | null,
+  limit?: number,
+): Promise<ThreadSearchHit[]> {
+  return invoke<ThreadSearchHit[]>("search_threads", {
+    workspaceId: workspaceId ?? null,
+    query,
+    limit: limit ?? null,
+  });
+}
+
 export async function threadLiveSubscribe(workspaceId: string, threadId: string) {
   return invoke<any>("thread_live_subscribe", { workspaceId, threadId });
 }
@@ -1080,6 +1913,43 @@ export async function archiveThread(workspaceId: string, threadId: string) {
   return invoke<any>("archive_thread", { workspaceId, threadId });
 }
 
+export async function archiveThreadWithUndo(
+  workspaceId: string,
+  threadId: string,
+  delaySeconds?: number | null,
+) {
+  return invoke<PendingArchive>("archive_thread_with_undo", {
+    workspaceId,
+    threadId,
+    delaySeconds: delaySeconds ?? null,
+  });
+}
+
+export async function undoArchive(token: string) {
+  return invoke<PendingArchive>("undo_archive", { token });
+}
+
+export async function setProtocolTrace(workspaceId: string, enabled: boolean) {
+  return invoke<ProtocolTraceStatus>("set_protocol_trace", {
+    workspaceId,
+    enabled,
+  });
+}
+
+export async function protocolTraceTail(workspaceId: string, lines?: number) {
+  return invoke<ProtocolTraceTail>("protocol_trace_tail", {
+    workspaceId,
+    lines: lines ?? null,
+  });
+}
+
+export async function getThreadTail(threadId: string, lines?: number) {
+  return invoke<ThreadTail>("get_thread_tail", {
+    threadId,
+    lines: lines ?? null,
+  });
+}
+
 export async function setThreadName(
   workspaceId: string,
   threadId: string,
@@ -1092,6 +1962,14 @@ export async function setTrayRecentThreads(entries: TrayRecentThreadEntry[]) {
   return invoke<void>("set_tray_recent_threads", { entries });
 }
 
+export async function updateStateSnapshot(snapshot: StateSnapshot) {
+  return invoke<void>("update_state_snapshot", { snapshot });
+}
+
+export async function restoreStateSnapshot(): Promise<StateSnapshot | null> {
+  return invoke<StateSnapshot | null>("restore_state_snapshot");
+}
+
 export async function setTraySessionUsage(usage: TraySessionUsage | null) {
   return invoke<void>("set_tray_session_usage", { usage });
 }
@@ -8,6 +8,312 @@ export type WorkspaceSettings = {
   launchScripts?: LaunchScriptEntry[] | null;
   worktreeSetupScript?: string | null;
   worktreesFolder?: string | null;
+  agentBackend?: AgentBackendKind | null;
+  agentCommand?: string | null;
+  turnConcurrency?: TurnConcurrencyPolicy | null;
+  mergeReady?: boolean;
+  readOnly?: boolean;
+  mergeQueueTestCommand?: string | null;
+  releaseArtifacts?: string[] | null;
+  requestTimeouts?: RequestTimeoutSettings | null;
+  notificationsEnabled?: boolean;
+  defaultAccessMode?: AccessMode | null;
+  defaultModel?: string | null;
+  defaultEffort?: string | null;
+  env?: Record<string, string>;
+  quota?: WorkspaceQuota | null;
+  color?: string | null;
+  icon?: string | null;
+};
+
+export type WorkspaceQuota = {
+  maxCpuMinutesPerDay?: number | null;
+  maxConcurrentCommands?: number | null;
+};
+
+export type WorkspaceQuotaStatus = {
+  workspaceId: string;
+  quota: WorkspaceQuota | null;
+  cpuMsToday: number;
+  runningCommands: number;
+  pausedReason: string | null;
+};
+
+export type SlashCommandInfo = {
+  name: string;
+  usage: string;
+  description: string;
+};
+
+export type ThreadExportFormat = "markdown" | "json";
+
+export type ThreadExport = {
+  format: ThreadExportFormat;
+  path: string | null;
+  content: string | null;
+};
+
+export type ThreadSearchHit = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string | null;
+  itemId: string;
+  kind: "user" | "agent" | "command" | "fileChange" | "toolCall" | "webSearch";
+  snippet: string;
+  indexedAt: number;
+};
+
+export type AttachedOutput = {
+  task: string;
+  script: string;
+  exitCode: number | null;
+  durationMs: number;
+  omittedBytes: number;
+  text: string;
+};
+
+export type WorkspaceSummary = {
+  workspaceId: string;
+  readme: { path: string; head: string; truncated: boolean } | null;
+  languages: { language: string; files: number; percent: number }[];
+  buildSystems: string[];
+  recentCommits: {
+    sha: string;
+    subject: string;
+    author: string;
+    timestamp: number;
+  }[];
+  threadCount: number;
+  activeTurns: number;
+  queuedTurns: number;
+  completedTurns: number | null;
+  generatedAt: number;
+};
+
+export type ActiveTurn = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string | null;
+  startedAt: number;
+};
+
+export type WorktreeLock = {
+  reason: string | null;
+  lockedAt: number | null;
+  stale: boolean;
+};
+
+export type DashboardWorkspace = {
+  workspaceId: string;
+  name: string;
+  connected: boolean;
+  activeTurns: ActiveTurn[];
+  queuedTurns: number;
+  lastActivityAt: number | null;
+  pendingApprovals: number;
+  git: {
+    branch: string | null;
+    upstream: string | null;
+    ahead: number;
+    behind: number;
+  } | null;
+  worktreeLock: WorktreeLock | null;
+};
+
+export type DashboardSnapshot = {
+  generatedAt: number;
+  workspaces: DashboardWorkspace[];
+};
+
+export type QueuedRun = {
+  id: string;
+  workspaceId: string;
+  threadId: string;
+  preview: string;
+  position: number;
+  enqueuedAt: number;
+  blockedBy: "runLimit" | "workspaceTurn" | "rateLimit";
+};
+
+export type ScheduledMessage = {
+  id: string;
+  workspaceId: string;
+  threadId: string;
+  text: string;
+  sendAt: number;
+  createdAt: number;
+  status: "pending" | "failed";
+  attempts: number;
+  nextAttemptAt: number;
+  lastError?: string | null;
+};
+
+export type PendingArchive = {
+  token: string;
+  workspaceId: string;
+  threadId: string;
+  archiveAt: number;
+};
+
+export type ProtocolTraceStatus = {
+  workspaceId: string;
+  enabled: boolean;
+  path: string;
+};
+
+export type ProtocolTraceTail = ProtocolTraceStatus & {
+  lines: string[];
+};
+
+export type ThreadJournalEntry = {
+  at: number;
+  workspaceId: string;
+  message: Record<string, unknown>;
+};
+
+export type ThreadTail = {
+  threadId: string;
+  events: ThreadJournalEntry[];
+  hasMore: boolean;
+};
+
+export type RunQueueSnapshot = {
+  maxConcurrentTurns: number | null;
+  running: number;
+  queued: QueuedRun[];
+};
+
+export type FileChange = {
+  path: string;
+  kind: "created" | "modified" | "deleted";
+};
+
+export type TerminalOutputPage = {
+  data: string;
+  start: number;
+  end: number;
+  oldest: number;
+  latest: number;
+  hasMore: boolean;
+};
+
+export type WorkspaceDirEntry = {
+  name: string;
+  path: string;
+  type: "file" | "dir" | "symlink";
+  size?: number | null;
+  mtime?: number | null;
+};
+
+export type WorkspaceDirListing = {
+  path: string;
+  entries: WorkspaceDirEntry[];
+  truncated: boolean;
+};
+
+export type ContextMention = {
+  name: string;
+  path: string;
+};
+
+export type ContextAttachment = {
+  mentions: ContextMention[];
+  bytes: number;
+  skipped: { path: string; reason: string }[];
+  truncated: boolean;
+};
+
+export type WorkspaceFocus = {
+  workspaceId: string;
+  focusedAt: number;
+  device?: string | null;
+  focusCount: number;
+};
+
+export type WorkspaceFileContent = {
+  content: string;
+  truncated: boolean;
+  hash?: string | null;
+  encoding: "utf8" | "base64";
+  offset: number;
+  totalBytes: number;
+  nextOffset?: number | null;
+};
+
+export type WorkspaceFilePage = {
+  files: string[];
+  offset: number;
+  total: number;
+  nextOffset?: number | null;
+  ageMs: number;
+};
+
+export type WorkspaceFileWriteResult = {
+  path: string;
+  hash: string;
+  bytes: number;
+  created: boolean;
+};
+
+export type UploadedFile = {
+  path: string;
+  bytes: number;
+  temporary: boolean;
+};
+
+export type DownloadedFile = {
+  relativePath: string;
+  bytes: number;
+  bytesBase64: string;
+};
+
+export type FileSearchMatch = {
+  path: string;
+  line: number;
+  column: number;
+  snippet: string;
+};
+
+export type FileSearchResult = {
+  matches: FileSearchMatch[];
+  truncated: boolean;
+};
+
+export type OperationStep = {
+  name: string;
+  status: "running" | "done" | "failed" | "rolledBack" | "rollbackFailed";
+  error: string | null;
+};
+
+export type PendingOperation = {
+  id: string;
+  kind: string;
+  workspaceId: string;
+  status: "running" | "failed";
+  steps: OperationStep[];
+  error: string | null;
+  params: Record<string, unknown>;
+  startedAt: number;
+  updatedAt: number;
+};
+
+export type StateSnapshot = {
+  version?: number;
+  savedAt?: number;
+  backend?: string;
+  workspaces: WorkspaceInfo[];
+  workspaceMeta?: Record<string, unknown>;
+  threads: Record<string, ThreadSummary[]>;
+  activeTurns?: Record<string, Record<string, string>>;
+};
+
+export type AgentBackendKind = "codex" | "acp" | "cli";
+
+export type TurnConcurrencyPolicy = "parallel" | "queue" | "reject";
+
+export type RequestTimeoutSettings = {
+  defaultSecs?: number | null;
+  methods?: Record<string, number>;
 };
 
 export type LaunchScriptIconId =
@@ -43,6 +349,28 @@ export type WorkspaceGroup = {
 
 export type WorkspaceKind = "main" | "worktree";
 
+export type BroadcastRunResult = {
+  workspaceId: string;
+  worktreeWorkspaceId: string | null;
+  threadId: string | null;
+  turnId: string | null;
+  error: string | null;
+};
+
+export type BroadcastRun = {
+  broadcastId: string;
+  results: BroadcastRunResult[];
+};
+
+export type WinnerSelection = {
+  broadcastId: string;
+  winnerWorkspaceId: string;
+  baseBranch: string;
+  archivedThreadIds: string[];
+  removedWorktreeIds: string[];
+  errors: string[];
+};
+
 export type WorktreeInfo = {
   branch: string;
 };
@@ -58,9 +386,320 @@ export type WorkspaceInfo = {
   settings: WorkspaceSettings;
 };
 
+/** `worktree.branch` is the suffixed name when `wasRenamed` is set. */
+export type WorktreeResult = WorkspaceInfo & {
+  requestedBranch: string;
+  wasRenamed: boolean;
+};
+
+export type BranchNameSuggestion = {
+  requestedBranch: string;
+  branch: string;
+  wasRenamed: boolean;
+  existing: boolean;
+};
+
+export type FileChangeAttribution = {
+  threadId: string;
+  turnId: string | null;
+  itemId: string | null;
+};
+
+export type WorktreeFileChange = {
+  status: string;
+  additions: number;
+  deletions: number;
+  attribution: FileChangeAttribution | null;
+};
+
+export type WorktreeCompareFile = {
+  path: string;
+  a: WorktreeFileChange | null;
+  b: WorktreeFileChange | null;
+  identical: boolean;
+};
+
+export type WorktreeCompareSide = {
+  workspaceId: string;
+  branch: string | null;
+  head: string;
+  agentBackend: AgentBackendKind;
+};
+
+export type WorktreeComparison = {
+  parentId: string;
+  base: string;
+  a: WorktreeCompareSide;
+  b: WorktreeCompareSide;
+  files: WorktreeCompareFile[];
+};
+
+export type ThreadLinkKind = "followUp" | "reviewOf" | "retryOf";
+
+export type ThreadLink = {
+  threadId: string;
+  workspaceId: string;
+  kind: ThreadLinkKind;
+};
+
+export type ThreadAnnotations = {
+  workspaceId: string;
+  tags: string[];
+  links: ThreadLink[];
+};
+
+export type ModelPreference = {
+  model: string | null;
+  effort: string | null;
+  pinned: boolean;
+  updatedAt: number;
+};
+
+export type ThreadPreferences = {
+  thread: ModelPreference | null;
+  workspace: ModelPreference | null;
+};
+
+export type SessionProvenance = {
+  workspaceId: string;
+  spawnedAt: number;
+  pid: number | null;
+  backend: AgentBackendKind;
+  program: string;
+  args: string[];
+  cwd: string;
+  codexHome: string | null;
+  codexVersion: string | null;
+  env: Record<string, string>;
+  gitHead: { commit: string | null; branch: string | null } | null;
+};
+
+export type NotificationKind =
+  | "turnCompleted"
+  | "turnFailed"
+  | "approvalRequested"
+  | "ciResult";
+
+export type NotificationEntry = {
+  id: string;
+  kind: NotificationKind;
+  workspaceId: string;
+  workspaceName: string;
+  threadId: string | null;
+  title: string;
+  body: string;
+  createdAt: number;
+  read: boolean;
+};
+
+export type ApprovalChatSettings = {
+  provider: "slack" | "discord";
+  botToken: string;
+  channelId: string;
+  approverIds?: string[];
+  appToken?: string | null;
+};
+
+export type PushRelaySettings = {
+  url: string;
+  apiKey?: string | null;
+};
+
+export type PushPlatform = "apns" | "fcm";
+
+export type PushDevice = {
+  deviceToken: string;
+  platform: PushPlatform;
+  appId: string | null;
+  name: string | null;
+  registeredAtMs: number;
+};
+
+export type NetworkSettings = {
+  httpProxy?: string | null;
+  httpsProxy?: string | null;
+  noProxy?: string | null;
+  caCertificatesPath?: string | null;
+};
+
+export type NetworkProbeResult = {
+  url: string;
+  ok: boolean;
+  status: number | null;
+  elapsedMs: number;
+  error: string | null;
+};
+
+export type NetworkConnectivityReport = {
+  httpProxy: string | null;
+  httpsProxy: string | null;
+  caCertificatesPath: string | null;
+  ok: boolean;
+  probes: NetworkProbeResult[];
+};
+
+export type StorageCategory =
+  | "journal"
+  | "transcripts"
+  | "attachments"
+  | "logs"
+  | "recordings";
+
+export type StorageCategoryUsage = {
+  category: StorageCategory;
+  path: string;
+  bytes: number;
+  fileCount: number;
+  quotaBytes: number | null;
+  overQuota: boolean;
+};
+
+export type StorageUsageReport = {
+  dataDir: string;
+  totalBytes: number;
+  retentionDays: number | null;
+  categories: StorageCategoryUsage[];
+};
+
+export type StorageCleanupResult = {
+  category: StorageCategory;
+  removedFiles: number;
+  freedBytes: number;
+};
+
+export type ApprovalRuleKind = "prefix" | "regex";
+
+export type ApprovalRuleDecision = "allow" | "deny";
+
+export type ApprovalRule = {
+  id: string;
+  kind: ApprovalRuleKind;
+  pattern: string;
+  decision: ApprovalRuleDecision;
+  scopePath: string | null;
+  expiresAt: number | null;
+  createdAt: number;
+};
+
+export type PlatformInfo = {
+  os: string;
+  arch: string;
+  mobile: boolean;
+};
+
+export type HostCapabilities = {
+  version: string;
+  platform: PlatformInfo;
+  codex: {
+    installed: boolean;
+    version?: string | null;
+    error?: string | null;
+    steer: boolean;
+    collaborationModes: boolean;
+    unifiedExec: boolean;
+    apps: boolean;
+  };
+};
+
+export type Capabilities = {
+  backendMode: BackendMode;
+  daemonReachable?: boolean | null;
+  daemonError?: string | null;
+  platform: PlatformInfo;
+  host?: HostCapabilities | null;
+  tailscale: { installed: boolean; running: boolean };
+};
+
+export type HostStats = {
+  cpuCount: number;
+  loadAverage?: [number, number, number] | null;
+  memory?: {
+    totalBytes: number;
+    availableBytes: number;
+    swapTotalBytes: number;
+    swapUsedBytes: number;
+  } | null;
+  disk?: { path: string; totalBytes: number; availableBytes: number } | null;
+  codexProcesses?: number | null;
+  warnings: string[];
+};
+
+export type DiscoveredDaemon = {
+  name: string;
+  host: string;
+  port: number;
+  addresses: string[];
+  version?: string | null;
+  authRequired?: boolean | null;
+  remoteHost?: string | null;
+};
+
+export type RemoteProfile = {
+  id: string;
+  name: string;
+  host: string;
+  hasToken: boolean;
+  tls: boolean;
+  tlsServerName?: string | null;
+  lastConnectedAtMs?: number | null;
+  active: boolean;
+};
+
+export type RemoteProfileDraft = {
+  id?: string | null;
+  name: string;
+  host: string;
+  port?: number | null;
+  token?: string | null;
+  tls?: boolean;
+  tlsServerName?: string | null;
+};
+
+export type RemoteBackendStatus = {
+  remoteMode: boolean;
+  host?: string | null;
+  connected: boolean;
+  reconnecting: boolean;
+  latencyMs?: number | null;
+  averageLatencyMs?: number | null;
+  lastPingAtMs?: number | null;
+  connectedSinceMs?: number | null;
+  reconnectAttempts: number;
+  lastError?: string | null;
+};
+
+export type BackendModeSwitch = {
+  mode: BackendMode;
+  stoppedSessions: number;
+  workspaces: WorkspaceInfo[];
+  workspacesError?: string | null;
+};
+
+export type PreparedImage = {
+  dataUrl: string;
+  mimeType: string;
+  width: number;
+  height: number;
+  originalWidth: number;
+  originalHeight: number;
+  bytes: number;
+  downscaled: boolean;
+  transcoded: boolean;
+  thumbnailDataUrl: string | null;
+};
+
+export type ServerTime = {
+  serverTime: number;
+  offsetMs: number;
+  roundTripMs: number;
+};
+
 export type AppServerEvent = {
   workspace_id: string;
   message: Record<string, unknown>;
+  seq?: number;
+  emittedAt?: number;
+  revision?: string;
 };
 
 export type TrayRecentThreadEntry = {
@@ -151,6 +790,12 @@ export type ThreadSummary = {
   isSubagent?: boolean;
   subagentNickname?: string | null;
   subagentRole?: string | null;
+  pinned?: boolean;
+};
+
+export type ThreadOrder = {
+  pinned: string[];
+  order: string[];
 };
 
 export type ThreadListSortKey = "created_at" | "updated_at";
@@ -203,6 +848,8 @@ export type RemoteBackendTarget = {
   provider: RemoteBackendProvider;
   host: string;
   token: string | null;
+  tls?: boolean;
+  tlsServerName?: string | null;
   lastConnectedAtMs?: number | null;
 };
 export type ThemePreference = "system" | "light" | "dark" | "dim";
@@ -235,9 +882,57 @@ export type OpenAppTarget = {
   args: string[];
 };
 
+export type CodexAuthPropagation = "off" | "symlink" | "copy";
+
+export type CodexLogoutResult = {
+  loggedOut: boolean;
+  codexHome: string;
+  removed: boolean;
+  backupPath: string | null;
+  sessionsNotified: number;
+};
+
+export type CodexConfigStringKey =
+  | "model"
+  | "review_model"
+  | "model_provider"
+  | "model_reasoning_effort"
+  | "model_reasoning_summary"
+  | "model_verbosity"
+  | "approval_policy"
+  | "sandbox_mode";
+
+export type McpServerConfig = {
+  command?: string | null;
+  args?: string[];
+  env?: Record<string, string>;
+  url?: string | null;
+  enabled?: boolean | null;
+};
+
+export type CodexConfigSettings = {
+  model: string | null;
+  modelReasoningEffort: string | null;
+  approvalPolicy: string | null;
+  sandboxMode: string | null;
+  features: Record<string, boolean>;
+  mcpServers: Record<string, McpServerConfig>;
+};
+
+export type CodexConfigValidation = {
+  path: string | null;
+  exists: boolean;
+  valid: boolean;
+  parseError: { message: string; line: number | null; column: number | null } | null;
+  unknownKeys: string[];
+  issues: { key: string; message: string }[];
+};
+
 export type AppSettings = {
   codexBin: string | null;
   codexArgs: string | null;
+  codexEnv?: Record<string, string>;
+  codexAuthPropagation?: CodexAuthPropagation;
   backendMode: BackendMode;
   remoteBackendProvider: RemoteBackendProvider;
   remoteBackendHost: string;
@@ -280,6 +975,19 @@ export type AppSettings = {
   notificationSoundsEnabled: boolean;
   systemNotificationsEnabled: boolean;
   subagentSystemNotificationsEnabled: boolean;
+  notificationWebhookUrl?: string | null;
+  daemonWebhookUrl?: string | null;
+  approvalChat?: ApprovalChatSettings | null;
+  pushRelay?: PushRelaySettings | null;
+  network?: NetworkSettings | null;
+  storageQuotasMb?: Partial<Record<StorageCategory, number>>;
+  storageRetentionDays?: number | null;
+  storageFsync?: boolean;
+  storageBackups?: number;
+  maxConcurrentTurns?: number | null;
+  terminalHistoryBytes?: number | null;
+  rateLimitWarnPercent?: number | null;
+  rateLimitQueuePercent?: number | null;
   splitChatDiffView: boolean;
   preloadGitDiffs: boolean;
   gitDiffIgnoreWhitespaceChanges: boolean;
@@ -308,8 +1016,12 @@ export type AppSettings = {
   composerCodeBlockCopyUseModifier: boolean;
   workspaceGroups: WorkspaceGroup[];
   globalWorktreesFolder: string | null;
+  worktreeNameMaxLength?: number;
   openAppTargets: OpenAppTarget[];
   selectedOpenAppId: string;
+  mergeQueueEnabled?: boolean;
+  losingWorktreeRetention?: "remove" | "keep";
+  requestTimeouts?: RequestTimeoutSettings;
 };
 
 export type CodexFeatureStage =
@@ -331,6 +1043,18 @@ export type CodexFeature = {
 
 export type TcpDaemonState = "stopped" | "running" | "error";
 
+export type TcpDaemonLogs = {
+  path: string;
+  lines: string[];
+};
+
+export type DaemonAutostartStatus = {
+  installed: boolean;
+  manager: "launchd" | "systemd" | "scheduledTask";
+  definition: string;
+  message: string;
+};
+
 export type TcpDaemonStatus = {
   state: TcpDaemonState;
   pid: number | null;
@@ -339,6 +1063,25 @@ export type TcpDaemonStatus = {
   listenAddr: string | null;
 };
 
+export type TcpDaemonVersion = {
+  running: boolean;
+  version: string | null;
+  protocolVersion: number | null;
+  binaryPath: string | null;
+  bundledVersion: string;
+  bundledProtocolVersion: number;
+  compatible: boolean;
+  upgradeAvailable: boolean;
+  message: string;
+};
+
+export type TcpDaemonUpgradeResult = {
+  previousVersion: string;
+  version: string | null;
+  replacedBinary: string | null;
+  status: TcpDaemonStatus;
+};
+
 export type TailscaleStatus = {
   installed: boolean;
   running: boolean;
@@ -357,6 +1100,28 @@ export type TailscaleDaemonCommandPreview = {
   daemonPath: string;
   args: string[];
   tokenConfigured: boolean;
+  serveCommand?: string | null;
+};
+
+export type TailscalePeer = {
+  id: string;
+  hostName: string;
+  dnsName: string | null;
+  os: string | null;
+  online: boolean;
+  ipv4: string[];
+  ipv6: string[];
+  suggestedRemoteHost: string | null;
+};
+
+export type TailscaleServeStatus = {
+  enabled: boolean;
+  funnel: boolean;
+  servePort: number | null;
+  target: string | null;
+  remoteHost: string | null;
+  url: string | null;
+  message: string;
 };
 
 export type CodexDoctorResult = {
@@ -432,6 +1197,20 @@ export type GitFileStatus = {
   deletions: number;
 };
 
+export type WorkspaceGitStatus = {
+  workspaceId: string;
+  status: {
+    branchName: string;
+    files: GitFileStatus[];
+    stagedFiles: GitFileStatus[];
+    unstagedFiles: GitFileStatus[];
+    totalAdditions: number;
+    totalDeletions: number;
+  } | null;
+  error: string | null;
+  ageMs: number;
+};
+
 export type GitFileDiff = {
   path: string;
   diff: string;
@@ -476,6 +1255,50 @@ export type GitLogResponse = {
   upstream: string | null;
 };
 
+export type GitAuthCheckStatus = "ok" | "warning" | "error" | "skipped";
+
+export type GitAuthCheck = {
+  id: string;
+  status: GitAuthCheckStatus;
+  detail: string;
+  hint: string | null;
+};
+
+export type GitAuthDoctorReport = {
+  remoteName: string | null;
+  remoteUrl: string | null;
+  /** `ssh`, `https`, `http`, `file`, another URL scheme, or `none`. */
+  scheme: string;
+  ok: boolean;
+  checks: GitAuthCheck[];
+};
+
+export type ReleaseArtifact = {
+  path: string;
+  size: number;
+  sha256: string;
+};
+
+export type ReleaseManifest = {
+  task: string;
+  createdAt: number;
+  outputDir: string;
+  artifacts: ReleaseArtifact[];
+};
+
+export type GitConflictFile = {
+  path: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+  isBinary: boolean;
+};
+
+export type GitConflictsResponse = {
+  operation: string | null;
+  files: GitConflictFile[];
+};
+
 export type GitHubIssue = {
   number: number;
   title: string;
@@ -570,6 +1393,27 @@ export type LocalUsageSnapshot = {
   topModels: LocalUsageModel[];
 };
 
+export type UsageGroupBy = "workspace" | "model" | "day";
+
+export type UsageSummaryRow = {
+  key: string;
+  turns: number;
+  failedTurns: number;
+  interruptedTurns: number;
+  durationMs: number;
+  inputTokens: number;
+  cachedInputTokens: number;
+  outputTokens: number;
+  reasoningOutputTokens: number;
+  totalTokens: number;
+};
+
+export type UsageSummary = {
+  since: number | null;
+  groupBy: UsageGroupBy;
+  rows: UsageSummaryRow[];
+};
+
 export type TurnPlanStepStatus = "pending" | "inProgress" | "completed";
 
 export type TurnPlanStep = {
@@ -602,6 +1446,28 @@ export type RateLimitSnapshot = {
   planType: string | null;
 };
 
+export type RateLimitLevel = "ok" | "warn" | "queue";
+
+export type RateLimitWindowForecast = {
+  window: "primary" | "secondary";
+  usedPercent: number;
+  remainingPercent: number;
+  windowDurationMins: number | null;
+  resetsAt: number | null;
+  burnRatePerHour: number | null;
+  exhaustsAt: number | null;
+  level: RateLimitLevel;
+};
+
+export type RateLimitForecast = {
+  updatedAt: number | null;
+  warnPercent: number | null;
+  queuePercent: number | null;
+  level: RateLimitLevel;
+  windows: RateLimitWindowForecast[];
+  waitingTurns: number;
+};
+
 export type AccountSnapshot = {
   type: "chatgpt" | "apikey" | "unknown";
   email: string | null;
@@ -666,6 +1532,52 @@ export type CustomPromptOption = {
   scope?: "workspace" | "global";
 };
 
+export type SavedPrompt = {
+  id: string;
+  name: string;
+  description: string | null;
+  template: string;
+  variables: string[];
+  createdAt: number;
+  updatedAt: number;
+};
+
+export type Playbook = {
+  id: string;
+  name: string;
+  description: string | null;
+  worktreeBranch: string | null;
+  setupScript: string | null;
+  contextFiles: string[];
+  model: string | null;
+  effort: string | null;
+  promptTemplate: string;
+  variables: string[];
+  createdAt: number;
+  updatedAt: number;
+};
+
+export type PlaybookDraft = {
+  id?: string | null;
+  name: string;
+  description?: string | null;
+  worktreeBranch?: string | null;
+  setupScript?: string | null;
+  contextFiles?: string[];
+  model?: string | null;
+  effort?: string | null;
+  promptTemplate: string;
+};
+
+export type PlaybookRun = {
+  playbookId: string;
+  workspaceId: string;
+  worktreeWorkspaceId: string | null;
+  branch: string | null;
+  threadId: string;
+  turnId: string | null;
+};
+
 export type BranchInfo = {
   name: string;
   lastCommit: number;
@@ -1,5 +1,20 @@
+let serverClockOffsetMs = 0;
+
+/**
+ * Timestamps come from the backend's clock; in remote mode that is the
+ * daemon's, which may disagree with this device. Relative times are measured
+ * against the local clock shifted by this offset.
+ */
+export function setServerClockOffset(offsetMs: number) {
+  serverClockOffsetMs = Number.isFinite(offsetMs) ? offsetMs : 0;
+}
+
+export function serverNow() {
+  return Date.now() + serverClockOffsetMs;
+}
+
 export function formatRelativeTime(timestamp: number) {
-  const now = Date.now();
+  const now = serverNow();
   const diffSeconds = Math.round((timestamp - now) / 1000);
   const absSeconds = Math.abs(diffSeconds);
   if (absSeconds < 5) {
@@ -34,7 +49,7 @@ export function formatRelativeTime(timestamp: number) {
 }
 
 export function formatRelativeTimeShort(timestamp: number) {
-  const now = Date.now();
+  const now = serverNow();
   const absSeconds = Math.abs(Math.round((timestamp - now) / 1000));
   if (absSeconds < 60) {
     return "now";